@@ -0,0 +1,257 @@
+//! Generates the methods of the `OpenRPC` document of the RPC from the jsonrpsee trait definitions
+//! of `src/eth_rpc/api`, served at `/openrpc.json` and by `rpc.discover`.
+//!
+//! The api modules are parsed with `syn`. For each module, a function is generated which returns
+//! the methods and subscriptions of its traits with their doc comments, the schemas of their
+//! parameters and results being derived at runtime with `schemars` from the Rust types of the
+//! signatures (see `src/eth_rpc/openrpc`). The build fails if no method is found.
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use std::{fs, path::Path};
+use syn::{
+    parse::ParseStream, Attribute, Expr, ExprLit, FnArg, GenericArgument, Ident, Item, ItemTrait, Lit, LitStr, Meta,
+    Pat, PathArguments, ReturnType, Token, TraitItem, TraitItemFn, Type,
+};
+
+/// The directory of the jsonrpsee trait definitions.
+const API_DIR: &str = "src/eth_rpc/api";
+
+/// The name of the generated file, included by `src/eth_rpc/openrpc/mod.rs`.
+const GENERATED_FILE: &str = "openrpc_methods.rs";
+
+fn main() {
+    println!("cargo:rerun-if-changed={API_DIR}");
+
+    let mut modules = Vec::new();
+    let mut extends = Vec::new();
+    let mut count = 0;
+    for (module, cfgs) in api_modules() {
+        let path = Path::new(API_DIR).join(format!("{module}.rs"));
+        println!("cargo:rerun-if-changed={}", path.display());
+        let file = parse_file(&path);
+
+        let uses = file.items.iter().filter(|item| matches!(item, Item::Use(_)));
+        let methods = file
+            .items
+            .iter()
+            .filter_map(|item| match item {
+                Item::Trait(rpc_trait) => rpc_namespace(rpc_trait).map(|namespace| (namespace, rpc_trait)),
+                _ => None,
+            })
+            .flat_map(|(namespace, rpc_trait)| {
+                rpc_trait.items.iter().filter_map(|item| match item {
+                    TraitItem::Fn(method) => Some(rpc_methods(namespace.as_deref(), method)),
+                    _ => None,
+                })
+            })
+            .flatten()
+            .collect::<Vec<_>>();
+        count += methods.len();
+
+        modules.push(quote! {
+            #(#cfgs)*
+            mod #module {
+                #![allow(unused_imports)]
+                use super::*;
+                #(#uses)*
+
+                pub(super) fn methods(generator: &mut SchemaGenerator) -> Vec<Method> {
+                    vec![#(#methods),*]
+                }
+            }
+        });
+        extends.push(quote! {
+            #(#cfgs)*
+            methods.extend(#module::methods(generator));
+        });
+    }
+    assert!(count > 0, "no RPC method found in {API_DIR}");
+
+    let generated = quote! {
+        #(#modules)*
+
+        /// Returns the methods of the API, generated from the trait definitions of the api modules.
+        fn api_methods(generator: &mut SchemaGenerator) -> Vec<Method> {
+            let mut methods = Vec::new();
+            #(#extends)*
+            methods
+        }
+    };
+
+    let out_dir = std::env::var("OUT_DIR").expect("missing OUT_DIR");
+    fs::write(Path::new(&out_dir).join(GENERATED_FILE), generated.to_string())
+        .expect("failed to write the generated methods");
+}
+
+/// Parses a source file of the api directory.
+fn parse_file(path: &Path) -> syn::File {
+    let source = fs::read_to_string(path).unwrap_or_else(|err| panic!("failed to read {}: {err}", path.display()));
+    syn::parse_file(&source).unwrap_or_else(|err| panic!("failed to parse {}: {err}", path.display()))
+}
+
+/// Returns the api modules declared in the `mod.rs` of the api directory, along with their `cfg`
+/// attributes.
+fn api_modules() -> Vec<(Ident, Vec<Attribute>)> {
+    parse_file(&Path::new(API_DIR).join("mod.rs"))
+        .items
+        .into_iter()
+        .filter_map(|item| match item {
+            Item::Mod(module) => {
+                let cfgs = module.attrs.into_iter().filter(|attr| attr.path().is_ident("cfg")).collect();
+                Some((module.ident, cfgs))
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+/// Returns the namespace of a trait annotated with `#[rpc(...)]`, `None` if the trait isn't an RPC
+/// trait.
+fn rpc_namespace(rpc_trait: &ItemTrait) -> Option<Option<String>> {
+    let attr = rpc_trait.attrs.iter().find(|attr| attr.path().is_ident("rpc"))?;
+    Some(RpcArgs::parse(attr).namespace)
+}
+
+/// Returns the expressions building the methods of a trait method annotated with
+/// `#[method(...)]`, or the subscribe and unsubscribe methods of a trait method annotated with
+/// `#[subscription(...)]`.
+fn rpc_methods(namespace: Option<&str>, method: &TraitItemFn) -> Vec<TokenStream> {
+    let Some(attr) =
+        method.attrs.iter().find(|attr| attr.path().is_ident("method") || attr.path().is_ident("subscription"))
+    else {
+        return Vec::new();
+    };
+    let args = RpcArgs::parse(attr);
+    let name = args.name.expect("missing method name");
+    let description = description(&method.attrs);
+    let namespace = namespace.map_or_else(|| quote!(None), |namespace| quote!(Some(#namespace)));
+
+    let params = method.sig.inputs.iter().filter_map(|input| match input {
+        FnArg::Typed(param) => {
+            let Pat::Ident(pat) = &*param.pat else { panic!("unsupported parameter pattern in {name}") };
+            let param_name = camel_case(&pat.ident.to_string());
+            Some(generic_argument(&param.ty, "Option").map_or_else(
+                || {
+                    let ty = &param.ty;
+                    quote!(.param::<#ty>(generator, #param_name))
+                },
+                |inner| quote!(.optional_param::<#inner>(generator, #param_name)),
+            ))
+        }
+        FnArg::Receiver(_) => None,
+    });
+    let method_expr = quote!(Method::new(#namespace, #name, #description) #(#params)*);
+
+    if attr.path().is_ident("subscription") {
+        let notification = args.notification.expect("missing subscription notification");
+        let unsubscribe = args.unsubscribe.expect("missing unsubscribe method");
+        let item = args.item.expect("missing subscription item");
+        return vec![
+            quote!(#method_expr.subscription::<#item>(generator, #notification, #unsubscribe)),
+            quote!(Method::unsubscribe(#namespace, #unsubscribe, #name)),
+        ];
+    }
+
+    let result = match &method.sig.output {
+        ReturnType::Type(_, ty) => generic_argument(ty, "RpcResult").expect("expected a RpcResult").clone(),
+        ReturnType::Default => syn::parse_quote!(()),
+    };
+    vec![quote!(#method_expr.result::<#result>(generator))]
+}
+
+/// The arguments of the jsonrpsee attributes used to generate the document.
+#[derive(Default)]
+struct RpcArgs {
+    /// The namespace of an `#[rpc(...)]` trait.
+    namespace: Option<String>,
+    /// The name of a method or a subscription.
+    name: Option<String>,
+    /// The name of the notifications of a subscription.
+    notification: Option<String>,
+    /// The name of the unsubscribe method of a subscription.
+    unsubscribe: Option<String>,
+    /// The type of the items of a subscription.
+    item: Option<Type>,
+}
+
+impl RpcArgs {
+    /// Parses the arguments of an attribute, e.g. `#[rpc(server, namespace = "eth")]` or
+    /// `#[subscription(name = "subscribe" => "subscription", unsubscribe = "unsubscribe", item = Log)]`.
+    /// The other arguments are skipped.
+    fn parse(attr: &Attribute) -> Self {
+        let mut args = Self::default();
+        attr.parse_args_with(|input: ParseStream<'_>| {
+            while !input.is_empty() {
+                let key: Ident = input.parse()?;
+                if input.parse::<Option<Token![=]>>()?.is_some() {
+                    match key.to_string().as_str() {
+                        "namespace" => args.namespace = Some(input.parse::<LitStr>()?.value()),
+                        "name" => {
+                            args.name = Some(input.parse::<LitStr>()?.value());
+                            if input.parse::<Option<Token![=>]>>()?.is_some() {
+                                args.notification = Some(input.parse::<LitStr>()?.value());
+                            }
+                        }
+                        "unsubscribe" => args.unsubscribe = Some(input.parse::<LitStr>()?.value()),
+                        "item" => args.item = Some(input.parse()?),
+                        _ => {
+                            input.parse::<Expr>()?;
+                        }
+                    }
+                }
+                input.parse::<Option<Token![,]>>()?;
+            }
+            Ok(())
+        })
+        .unwrap_or_else(|err| panic!("invalid jsonrpsee attribute: {err}"));
+        args
+    }
+}
+
+/// Returns the description of a method from its doc comment, the paragraphs being separated by
+/// blank lines.
+fn description(attrs: &[Attribute]) -> String {
+    let lines = attrs
+        .iter()
+        .filter_map(|attr| match &attr.meta {
+            Meta::NameValue(meta) if meta.path.is_ident("doc") => match &meta.value {
+                Expr::Lit(ExprLit { lit: Lit::Str(doc), .. }) => Some(doc.value().trim().to_string()),
+                _ => None,
+            },
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+
+    lines
+        .split(String::is_empty)
+        .map(|paragraph| paragraph.join(" "))
+        .filter(|paragraph| !paragraph.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+/// Returns the type argument of the given generic type, if the type is an instance of it.
+fn generic_argument<'a>(ty: &'a Type, name: &str) -> Option<&'a Type> {
+    let Type::Path(path) = ty else { return None };
+    let segment = path.path.segments.last().filter(|segment| segment.ident == name)?;
+    let PathArguments::AngleBracketed(args) = &segment.arguments else { return None };
+    match args.args.first()? {
+        GenericArgument::Type(ty) => Some(ty),
+        _ => None,
+    }
+}
+
+/// Converts a snake case name to camel case.
+fn camel_case(name: &str) -> String {
+    let mut parts = name.split('_').filter(|part| !part.is_empty());
+    let mut camel = parts.next().unwrap_or_default().to_string();
+    for part in parts {
+        let mut chars = part.chars();
+        if let Some(first) = chars.next() {
+            camel.extend(first.to_uppercase());
+            camel.push_str(chars.as_str());
+        }
+    }
+    camel
+}
@@ -43,6 +43,7 @@ export type {
 export {
   bigIntToBytes,
   bigIntToHex,
+  bigIntToUnpaddedBytes,
   bytesToBigInt,
   bytesToHex,
   bytesToInt,
@@ -64,3 +65,5 @@ export { Trie } from "https://esm.sh/@ethereumjs/trie@6.0.1";
 export type { Log } from "https://esm.sh/@ethereumjs/evm@2.1.0";
 
 export { RLP } from "https://esm.sh/@ethereumjs/rlp@5.0.1";
+
+export { keccak256 } from "https://esm.sh/ethereum-cryptography@2.1.3/keccak.js";
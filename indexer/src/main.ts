@@ -1,20 +1,27 @@
 // Utils
 import { padString, toHexString } from "./utils/hex.ts";
+import { guardDocumentSize } from "./utils/size.ts";
 import {
   ethValidationFailed,
   isKakarotTransaction,
+  isNativeTokenTransfer,
+  isOutOfResources,
   isReverted,
 } from "./utils/filter.ts";
 
 // Constants
 import {
   AUTH_TOKEN,
+  EVM_BLOCK_HASH,
+  NATIVE_TOKEN_ADDRESS,
+  NON_INVOKE_TRANSACTIONS,
   NULL_HASH,
   SINK_OPTIONS,
   SINK_TYPE,
   STARTING_BLOCK,
   STREAM_URL,
   TRANSACTION_EXECUTED,
+  TRANSFER,
 } from "./constants.ts";
 
 // Types
@@ -23,14 +30,33 @@ import {
   toTypedEthTx,
   typedTransactionToEthTx,
 } from "./types/transaction.ts";
-import { toEthHeader } from "./types/header.ts";
+import {
+  EvmBlockHashes,
+  getCoinbase,
+  toEthHeader,
+  toFeeRecipient,
+} from "./types/header.ts";
 import {
   toEthReceipt,
   toRevertedOutOfResourcesReceipt,
+  verifyReceiptStatus,
 } from "./types/receipt.ts";
 import { toEthLog } from "./types/log.ts";
+import {
+  toDepositReceipt,
+  toDepositTx,
+  toSkippedTransactions,
+} from "./types/deposit.ts";
+import { toBalanceSnapshots } from "./types/balance.ts";
 import { createTrieData } from "./types/tries.ts";
-import { Collection, JsonRpcLog, StoreItem, TrieData } from "./types/types.ts";
+import {
+  Collection,
+  JsonRpcDepositTx,
+  JsonRpcLog,
+  JsonRpcReceipt,
+  StoreItem,
+  TrieData,
+} from "./types/types.ts";
 // Starknet
 import {
   BlockHeader,
@@ -39,6 +65,7 @@ import {
   hexToBytes,
   JsonRpcTx,
   NetworkOptions,
+  PrefixedHexString,
   SinkOptions,
   TransactionWithReceipt,
 } from "./deps.ts";
@@ -63,6 +90,10 @@ export const config: Config<NetworkOptions, SinkOptions> = {
       {
         keys: [TRANSACTION_EXECUTED],
       },
+      {
+        fromAddress: NATIVE_TOKEN_ADDRESS,
+        keys: [TRANSFER],
+      },
     ],
     transactions: [{ includeReverted: true }],
   },
@@ -70,6 +101,9 @@ export const config: Config<NetworkOptions, SinkOptions> = {
   sinkOptions: SINK_OPTIONS,
 };
 
+// The EVM-style hashes of the last indexed blocks, chaining the block hashes.
+const evmBlockHashes = new EvmBlockHashes();
+
 export default async function transform({
   header,
   events,
@@ -83,7 +117,14 @@ export default async function transform({
   const store: Array<StoreItem> = [];
   const blockLogsBloom = new Bloom();
 
+  // Native token transfers are only used to snapshot the balances of the
+  // accounts involved.
+  const transferEvents = (events ?? []).filter((event) =>
+    isNativeTokenTransfer(event.event)
+  );
+
   const processedEvents = (events ?? [])
+    .filter((event) => !isNativeTokenTransfer(event.event))
     // Can be false if the transaction is not related to a specific instance of the Kakarot contract.
     // This is typically the case if there are multiple Kakarot contracts on the same chain.
     // Skip if the transaction_executed event contains "eth validation failed".
@@ -119,20 +160,91 @@ export default async function transform({
   );
   updateStoreWithTransactions(store, processedTransactions);
 
+  // The non-Invoke Starknet transactions are counted in the header document,
+  // unless converted to deposit transactions.
+  const deposits = NON_INVOKE_TRANSACTIONS === "deposit"
+    ? processDeposits(transactions, blockInfo, cumulativeGasUsages)
+    : [];
+  store.push(...deposits.flatMap(({ tx, receipt }) => [
+    { collection: Collection.Transactions, data: { tx } },
+    { collection: Collection.Receipts, data: { receipt } },
+  ]));
+  const skippedTransactions = toSkippedTransactions(
+    (transactions ?? []).map(({ transaction }) => transaction),
+    new Set(deposits.map(({ tx }) => BigInt(tx.hash))),
+  );
+
+  const { coinbase, verified } = await getCoinbase(blockInfo);
   const ethHeader = await toEthHeader({
     header: header,
     gasUsed: cumulativeGasUsed,
     logsBloom: blockLogsBloom,
     receiptRoot: receiptTrie.root(),
     transactionRoot: transactionTrie.root(),
+    coinbase,
     ...blockInfo,
   });
+
+  // With EVM-style block hashes, the block is identified by the keccak hash of
+  // its header everywhere, its parent by its own EVM-style hash, and its
+  // Starknet hash is kept in the header document.
+  const starknetBlockHash = EVM_BLOCK_HASH && !blockInfo.isPendingBlock
+    ? blockInfo.blockHash
+    : null;
+  if (starknetBlockHash) {
+    ethHeader.hash = evmBlockHashes.hash(ethHeader, starknetBlockHash);
+    replaceBlockHash(store, starknetBlockHash, ethHeader.hash);
+  }
   store.push({
     collection: Collection.Headers,
-    data: { header: ethHeader },
+    data: {
+      header: ethHeader,
+      ...(starknetBlockHash ? { starknetBlockHash } : {}),
+      feeRecipient: toFeeRecipient({ header, coinbase, verified }),
+      ...(Object.keys(skippedTransactions).length
+        ? { skippedTransactions }
+        : {}),
+    },
   });
 
-  return store;
+  const balances = await toBalanceSnapshots({
+    events: transferEvents.map(({ event }) => event),
+    ...blockInfo,
+  });
+  store.push(...balances.map((balance) => ({
+    collection: Collection.Balances,
+    data: { balance },
+  })));
+
+  // A single oversized document would fail the write of the whole block.
+  return store
+    .map((item) => guardDocumentSize(item))
+    .filter((item): item is StoreItem => item !== null);
+}
+
+/**
+ * Replaces the block hash of the transactions, receipts and logs of the store.
+ * @param store - The items of the block.
+ * @param from - The block hash to replace.
+ * @param to - The new block hash.
+ */
+function replaceBlockHash(
+  store: Array<StoreItem>,
+  from: PrefixedHexString,
+  to: PrefixedHexString,
+) {
+  for (const { data } of store) {
+    const document = "tx" in data
+      ? data.tx
+      : "receipt" in data
+      ? data.receipt
+      : "log" in data
+      ? data.log
+      : null;
+    if (document?.blockHash === from) {
+      document.blockHash = to;
+    }
+  }
 }
 
 function createBlockInfo(header: BlockHeader): BlockInfo {
@@ -175,13 +287,17 @@ function processEvent(blockInfo: BlockInfo) {
       logIndex: index.toString(),
     }));
 
-    const ethReceipt = toEthReceipt({
-      transaction: ethTx as JsonRpcTx,
-      logs: ethLogsIndexed,
-      event: event.event,
-      cumulativeGasUsed: 0n, // This will be updated later
-      ...blockInfo,
-    });
+    // The status and the gas used are verified before the cumulative gas used is computed.
+    const ethReceipt = verifyReceiptStatus(
+      toEthReceipt({
+        transaction: ethTx as JsonRpcTx,
+        logs: ethLogsIndexed,
+        event: event.event,
+        cumulativeGasUsed: 0n, // This will be updated later
+        ...blockInfo,
+      }),
+      event.receipt,
+    );
 
     return { event, typedEthTx, ethTx, ethLogs: ethLogsIndexed, ethReceipt };
   };
@@ -276,26 +392,72 @@ function createProcessedTransaction(
     ...blockInfo,
   });
   if (!ethTx) return null;
-  // Get the cumulative gas used for the reverted transaction.
-  // Example:
-  // const cumulativeGasUsages = [300n, undefined, undefined, 200n, undefined, 100n, undefined, undefined, 10n, undefined];
-  // const ethTx = { transactionIndex: 5 };
-  // const revertedTransactionCumulativeGasUsed = 100n;
-  const revertedTransactionCumulativeGasUsed = cumulativeGasUsages.find(
-    (gas, i) =>
-      Number(ethTx.transactionIndex) >= cumulativeGasUsages.length - 1 - i &&
-      gas,
-  ) ?? 0n;
+  const revertedTransactionCumulativeGasUsed = cumulativeGasUsedAt(
+    cumulativeGasUsages,
+    Number(ethTx.transactionIndex),
+  );
 
-  const ethReceipt = toRevertedOutOfResourcesReceipt({
-    transaction: ethTx as JsonRpcTx,
-    cumulativeGasUsed: revertedTransactionCumulativeGasUsed,
-    ...blockInfo,
-  });
+  const ethReceipt = verifyReceiptStatus(
+    toRevertedOutOfResourcesReceipt({
+      transaction: ethTx as JsonRpcTx,
+      cumulativeGasUsed: revertedTransactionCumulativeGasUsed,
+      ...blockInfo,
+      outOfResources: isOutOfResources(tx.receipt),
+    }),
+    tx.receipt,
+  );
 
   return { ethTx, ethReceipt };
 }
 
+/**
+ * Returns the cumulative gas used of the block up to the transaction at the
+ * given index.
+ * @param cumulativeGasUsages - The cumulative gas used by the Kakarot
+ * transactions, sorted by descending transaction index.
+ * @param transactionIndex - The index of the transaction.
+ *
+ * Example:
+ * const cumulativeGasUsages = [300n, undefined, undefined, 200n, undefined, 100n, undefined, undefined, 10n, undefined];
+ * const transactionIndex = 5;
+ * const cumulativeGasUsed = 100n;
+ */
+function cumulativeGasUsedAt(
+  cumulativeGasUsages: bigint[],
+  transactionIndex: number,
+): bigint {
+  return cumulativeGasUsages.find(
+    (gas, i) => transactionIndex >= cumulativeGasUsages.length - 1 - i && gas,
+  ) ?? 0n;
+}
+
+function processDeposits(
+  transactions: TransactionWithReceipt[],
+  blockInfo: BlockInfo,
+  cumulativeGasUsages: bigint[],
+): Array<{ tx: JsonRpcDepositTx; receipt: JsonRpcReceipt }> {
+  return (transactions ?? [])
+    .filter((tx) => tx.transaction && tx.receipt)
+    .map((tx) =>
+      toDepositTx({
+        transaction: tx.transaction,
+        receipt: tx.receipt,
+        ...blockInfo,
+      })
+    )
+    .filter((tx): tx is JsonRpcDepositTx => tx !== null)
+    .map((tx) => ({
+      tx,
+      receipt: toDepositReceipt(
+        tx,
+        cumulativeGasUsedAt(
+          cumulativeGasUsages,
+          Number(tx.transactionIndex ?? 0),
+        ),
+      ),
+    }));
+}
+
 function updateStoreWithTransactions(
   store: Array<StoreItem>,
   processedTransactions: ProcessedTransaction[],
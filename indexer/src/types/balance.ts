@@ -0,0 +1,94 @@
+// Utils
+import { padBigint, padString } from "../utils/hex.ts";
+
+// Starknet
+import { Event, uint256 } from "../deps.ts";
+import { PROVIDER } from "../provider.ts";
+
+// Eth
+import { bigIntToHex, PrefixedHexString } from "../deps.ts";
+import { BalanceSnapshot } from "./types.ts";
+
+// Constants
+import { NATIVE_TOKEN_ADDRESS } from "../constants.ts";
+
+/**
+ * Returns the Starknet addresses involved in native token `Transfer` events.
+ * @param events - The native token `Transfer` events.
+ * @returns The deduplicated sender and recipient addresses, padded to 32 bytes.
+ *
+ * Note: Cairo 0 tokens emit the sender and recipient in the data of the event,
+ * while Cairo 1 tokens can emit them as keys.
+ */
+export function transferParticipants(events: Event[]): PrefixedHexString[] {
+  const addresses = events.flatMap(({ keys, data }) =>
+    keys.length >= 3 ? [keys[1], keys[2]] : [data[0], data[1]]
+  )
+    .filter((address): address is `0x${string}` => address !== undefined)
+    .map((address) => padBigint(BigInt(address), 32));
+  return [...new Set(addresses)];
+}
+
+/**
+ * Converts the native token `Transfer` events of a block to snapshots of the
+ * balances of the accounts involved, at the end of the block.
+ * @param events - The native token `Transfer` events of the block.
+ * @param blockNumber - The block number in hex.
+ * @param blockHash - The block hash in hex.
+ * @param isPendingBlock - Whether the block is pending.
+ * @returns The balance snapshots, or an empty array for pending blocks.
+ *
+ * Note: Pending blocks are skipped, their snapshots are taken once they are
+ * accepted. Accounts whose balance can't be fetched get a snapshot with a null
+ * balance, marking the previous snapshots of the account as stale: the RPC
+ * then falls back to the Starknet node instead of serving them.
+ */
+export async function toBalanceSnapshots({
+  events,
+  blockNumber,
+  blockHash,
+  isPendingBlock,
+}: {
+  events: Event[];
+  blockNumber: PrefixedHexString;
+  blockHash: PrefixedHexString;
+  isPendingBlock: boolean;
+}): Promise<BalanceSnapshot[]> {
+  if (isPendingBlock || !events.length) return [];
+
+  const snapshots = await Promise.all(
+    transferParticipants(events).map(async (address) => {
+      try {
+        // ⚠️ StarknetJS: blockIdentifier is a block hash if value is BigInt or String, otherwise it's a block number.
+        const { result } = await PROVIDER.callContract(
+          {
+            contractAddress: NATIVE_TOKEN_ADDRESS,
+            entrypoint: "balanceOf",
+            calldata: [address],
+          },
+          blockHash,
+        );
+        const balance = uint256.uint256ToBN({
+          low: result[0],
+          high: result[1],
+        });
+        return {
+          address,
+          blockNumber: padString(blockNumber, 8),
+          balance: bigIntToHex(BigInt(balance)),
+        };
+      } catch (error) {
+        console.error(
+          `Failed to fetch the balance of ${address} at block ${blockNumber}: ${error}`,
+        );
+        return {
+          address,
+          blockNumber: padString(blockNumber, 8),
+          balance: null,
+        };
+      }
+    }),
+  );
+
+  return snapshots;
+}
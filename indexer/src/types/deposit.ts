@@ -0,0 +1,182 @@
+// Utils
+import { padBigint, padString } from "../utils/hex.ts";
+import { isReverted } from "../utils/filter.ts";
+
+// Starknet
+import { Transaction } from "../deps.ts";
+
+// Eth
+import { bigIntToHex, Bloom, bytesToHex } from "../deps.ts";
+
+// Constants
+import { HANDLE_L1_MESSAGE, KAKAROT_ADDRESS, NULL_HASH } from "../constants.ts";
+
+// Types
+import {
+  JsonRpcDepositTx,
+  JsonRpcReceipt,
+  SkippedTransactions,
+} from "./types.ts";
+import { TransactionContext } from "./interfaces.ts";
+
+// The type of the deposit transactions of the OP stack.
+export const DEPOSIT_TX_TYPE = "0x7e";
+
+// The version of the deposit receipts of the OP stack, since the Canyon upgrade.
+export const DEPOSIT_RECEIPT_VERSION = "0x1";
+
+/**
+ * Returns the type of a Starknet transaction, without its version.
+ *
+ * @param transaction - The Starknet transaction.
+ * @returns - The type of the transaction (e.g. "invoke", "l1Handler", "deployAccount"), or null
+ * if the transaction has no body.
+ */
+export function starknetTransactionType(
+  transaction: Transaction,
+): string | null {
+  const body = Object.entries(transaction).find(([key, value]) =>
+    key !== "meta" && value
+  );
+  return body ? body[0].replace(/V\d+$/, "") : null;
+}
+
+/**
+ * Counts the non-Invoke Starknet transactions of a block which weren't converted to Ethereum
+ * transactions, by Starknet transaction type, so that the transaction count of a block can be
+ * reconciled with the Starknet explorers.
+ *
+ * @param transactions - The Starknet transactions of the block.
+ * @param converted - The hashes of the Starknet transactions converted to deposit transactions.
+ * @returns - The number of skipped transactions by type, empty if none was skipped.
+ */
+export function toSkippedTransactions(
+  transactions: Transaction[],
+  converted: Set<bigint>,
+): SkippedTransactions {
+  const skipped: SkippedTransactions = {};
+  for (const transaction of transactions) {
+    const type = starknetTransactionType(transaction);
+    if (!type || type === "invoke") continue;
+    if (converted.has(BigInt(transaction.meta?.hash ?? 0))) continue;
+    skipped[type] = (skipped[type] ?? 0) + 1;
+  }
+  return skipped;
+}
+
+/**
+ * Converts a Starknet L1 handler transaction executing an L1 message on Kakarot into a
+ * deposit transaction, as the OP stack does for the L1 deposits.
+ *
+ * The calldata of the L1 handler is expected to follow the `handle_l1_message` entrypoint:
+ * - fromAddress <- calldata[0], the L1 contract which sent the message
+ * - l1Sender <- calldata[1]
+ * - toAddress <- calldata[2]
+ * - value <- calldata[3]
+ * - dataLen <- calldata[4]
+ * - data <- calldata[5..], one byte per felt
+ *
+ * Note: the deposit transactions aren't part of the transaction trie of the block, as they
+ * aren't signed Ethereum transactions. Their receipt is built by `toDepositReceipt`.
+ *
+ * @param transaction - The Starknet transaction.
+ * @param receipt - The receipt of the Starknet transaction.
+ * @param blockNumber - The block number of the transaction in hex.
+ * @param blockHash - The block hash of the transaction in hex.
+ * @param isPendingBlock - Whether the block is pending.
+ * @returns - The deposit transaction, or null if the transaction isn't an L1 handler
+ * transaction executing an L1 message on Kakarot.
+ */
+export function toDepositTx({
+  transaction,
+  receipt,
+  blockNumber,
+  blockHash,
+  isPendingBlock,
+}: TransactionContext): JsonRpcDepositTx | null {
+  const l1Handler = transaction.l1Handler;
+  if (
+    !l1Handler ||
+    BigInt(l1Handler.contractAddress ?? 0) !== BigInt(KAKAROT_ADDRESS) ||
+    BigInt(l1Handler.entryPointSelector ?? 0) !== BigInt(HANDLE_L1_MESSAGE)
+  ) {
+    return null;
+  }
+
+  const calldata = (l1Handler.calldata ?? []).map((felt) => BigInt(felt));
+  if (calldata.length < 5) {
+    console.error(
+      `Invalid L1 handler calldata length. Got ${calldata.length}, expected >= 5`,
+    );
+    return null;
+  }
+  const [fromAddress, l1Sender, toAddress, value, dataLen] = calldata;
+  const data = calldata.slice(5);
+  if (
+    BigInt(data.length) !== dataLen || data.some((byte) => byte > 0xffn) ||
+    [fromAddress, l1Sender, toAddress].some((address) => address >= 1n << 160n)
+  ) {
+    console.error(
+      `Invalid L1 message in the L1 handler transaction ${transaction.meta?.hash}`,
+    );
+    return null;
+  }
+
+  const deposit: JsonRpcDepositTx = {
+    blockHash: isPendingBlock ? null : blockHash,
+    blockNumber,
+    from: padBigint(l1Sender, 20),
+    gas: "0x0",
+    gasPrice: "0x0",
+    hash: padString(transaction.meta?.hash ?? NULL_HASH, 32),
+    input: bytesToHex(new Uint8Array(data.map(Number))),
+    nonce: bigIntToHex(BigInt(l1Handler.nonce ?? 0)),
+    to: padBigint(toAddress, 20),
+    transactionIndex: isPendingBlock
+      ? null
+      : padBigint(BigInt(receipt.transactionIndex ?? 0), 8),
+    value: bigIntToHex(value),
+    type: DEPOSIT_TX_TYPE,
+    l1FromAddress: padBigint(fromAddress, 20),
+    isSystemTx: false,
+  };
+  if (isReverted(receipt)) {
+    deposit.reverted = receipt.revertReason;
+  }
+  return deposit;
+}
+
+/**
+ * Builds the receipt of a deposit transaction, as the OP stack does for the L1 deposits. The
+ * deposit transactions use no EVM gas nor emit EVM logs, their receipt is successful unless the
+ * Starknet transaction reverted.
+ *
+ * Note: the deposit receipts aren't part of the receipt trie of the block, as their transactions.
+ *
+ * @param deposit - The deposit transaction.
+ * @param cumulativeGasUsed - The gas used by the transactions of the block up to the deposit.
+ * @returns - The deposit receipt.
+ */
+export function toDepositReceipt(
+  deposit: JsonRpcDepositTx,
+  cumulativeGasUsed: bigint,
+): JsonRpcReceipt {
+  return {
+    transactionHash: deposit.hash,
+    transactionIndex: bigIntToHex(BigInt(deposit.transactionIndex ?? 0)),
+    blockHash: deposit.blockHash ?? NULL_HASH,
+    blockNumber: deposit.blockNumber,
+    from: deposit.from,
+    to: deposit.to,
+    cumulativeGasUsed: bigIntToHex(cumulativeGasUsed),
+    gasUsed: bigIntToHex(0n),
+    effectiveGasPrice: bigIntToHex(0n),
+    contractAddress: null,
+    logs: [],
+    logsBloom: bytesToHex(new Bloom().bitvector),
+    status: deposit.reverted === undefined ? bigIntToHex(1n) : bigIntToHex(0n),
+    type: DEPOSIT_TX_TYPE,
+    depositNonce: deposit.nonce,
+    depositReceiptVersion: DEPOSIT_RECEIPT_VERSION,
+  };
+}
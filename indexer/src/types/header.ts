@@ -5,13 +5,82 @@ import { padString } from "../utils/hex.ts";
 import { BlockHeader } from "../deps.ts";
 
 // Eth
-import { bigIntToHex, Bloom, bytesToHex, PrefixedHexString } from "../deps.ts";
-import { JsonRpcBlock } from "./types.ts";
+import {
+  bigIntToHex,
+  bigIntToUnpaddedBytes,
+  Bloom,
+  bytesToHex,
+  hexToBytes,
+  keccak256,
+  PrefixedHexString,
+  RLP,
+} from "../deps.ts";
+import { FeeRecipient, JsonRpcBlock } from "./types.ts";
 import { KAKAROT } from "../provider.ts";
 
 // Constant
 import { DEFAULT_BLOCK_GAS_LIMIT, NULL_HASH } from "../constants.ts";
 
+/**
+ * Returns the coinbase of Kakarot core at the block, i.e. the recipient of the EVM fees.
+ * @param blockNumber - The block number in hex.
+ * @param blockHash - The block hash in hex.
+ * @param isPendingBlock - Whether the block is pending.
+ * @returns The coinbase, and whether it was read from Kakarot core and is a valid EVM
+ * address. Otherwise, the coinbase defaults to the zero address.
+ */
+export async function getCoinbase({
+  blockNumber,
+  blockHash,
+  isPendingBlock,
+}: {
+  blockNumber: PrefixedHexString;
+  blockHash: PrefixedHexString;
+  isPendingBlock: boolean;
+}): Promise<{ coinbase: bigint; verified: boolean }> {
+  const blockIdentifier = isPendingBlock ? "pending" : blockHash;
+  try {
+    const response = (await KAKAROT.call("get_coinbase", [], {
+      blockIdentifier,
+    })) as { coinbase?: bigint };
+    const coinbase = BigInt(response.coinbase ?? 0);
+    if (coinbase >= 1n << 160n) {
+      console.warn(
+        `⚠️ Invalid coinbase ${bigIntToHex(coinbase)} for block ${blockNumber}: not an EVM address`,
+      );
+      return { coinbase: 0n, verified: false };
+    }
+    return { coinbase, verified: response.coinbase !== undefined };
+  } catch (error) {
+    console.warn(
+      `⚠️ Failed to get get_coinbase for block ${blockNumber} - Error: ${error.message}`,
+    );
+    return { coinbase: 0n, verified: false };
+  }
+}
+
+/**
+ * @param header - A Starknet block header.
+ * @param coinbase - The coinbase of Kakarot core at the block.
+ * @param verified - Whether the coinbase was read from Kakarot core.
+ * @returns The recipients of the EVM and Starknet fees of the block.
+ */
+export function toFeeRecipient({
+  header,
+  coinbase,
+  verified,
+}: {
+  header: BlockHeader;
+  coinbase: bigint;
+  verified: boolean;
+}): FeeRecipient {
+  return {
+    coinbase: padString(bigIntToHex(coinbase), 20),
+    sequencerAddress: padString(header.sequencerAddress ?? undefined, 32),
+    verified,
+  };
+}
+
 /**
  * Converts a Starknet block header to an Ethereum block header in JSON RPC format.
  * @param header - A Starknet block header.
@@ -22,6 +91,7 @@ import { DEFAULT_BLOCK_GAS_LIMIT, NULL_HASH } from "../constants.ts";
  * @param receiptRoot - The transaction receipt trie root of the block.
  * @param transactionRoot - The transaction trie root of the block.
  * @param isPendingBlock - Whether the block is pending.
+ * @param coinbase - The coinbase of Kakarot core at the block, fetched if undefined.
  * @returns The Ethereum block header in the json RPC format.
  *
  * Note: We return a JsonRpcBlock instead of a JsonHeader, since the
@@ -38,6 +108,7 @@ export async function toEthHeader({
   receiptRoot,
   transactionRoot,
   isPendingBlock,
+  coinbase,
 }: {
   header: BlockHeader;
   blockNumber: PrefixedHexString;
@@ -47,6 +118,7 @@ export async function toEthHeader({
   receiptRoot: Uint8Array;
   transactionRoot: Uint8Array;
   isPendingBlock: boolean;
+  coinbase?: bigint;
 }): Promise<JsonRpcBlock> {
   // Convert timestamp to Unix timestamp (seconds since Jan 1, 1970, UTC)
   const timestampUnix = Date.parse(header.timestamp);
@@ -67,15 +139,12 @@ export async function toEthHeader({
       // Make the KAKAROT RPC call to retrieve blockchain data
       const response =
         (await KAKAROT.call(method, [], { blockIdentifier })) as {
-          coinbase?: bigint;
           base_fee?: bigint;
           block_gas_limit?: bigint;
         };
 
       // Extract and return the specific field from the response, or fallback to default value
       switch (method) {
-        case "get_coinbase":
-          return BigInt(response.coinbase ?? defaultValue);
         case "get_base_fee":
           return BigInt(response.base_fee ?? defaultValue);
         case "get_block_gas_limit":
@@ -93,8 +162,11 @@ export async function toEthHeader({
   };
 
   // Retrieve responses for coinbase, baseFee, and blockGasLimit asynchronously
-  const [coinbase, baseFee, blockGasLimit] = await Promise.all([
-    getResponse("get_coinbase", BigInt(0)),
+  const [miner, baseFee, blockGasLimit] = await Promise.all([
+    coinbase ??
+      getCoinbase({ blockNumber, blockHash, isPendingBlock }).then((result) =>
+        result.coinbase
+      ),
     getResponse("get_base_fee", BigInt(0)),
     getResponse("get_block_gas_limit", BigInt(DEFAULT_BLOCK_GAS_LIMIT)),
   ]);
@@ -123,7 +195,7 @@ export async function toEthHeader({
     // Convert receipt trie root to hexadecimal string
     receiptsRoot: bytesToHex(receiptRoot),
     // Convert coinbase address to padded hexadecimal string
-    miner: padString(bigIntToHex(coinbase), 20),
+    miner: padString(bigIntToHex(miner), 20),
     // Difficulty field (unused in this context)
     difficulty: "0x00",
     // Total difficulty field (unused in this context)
@@ -152,3 +224,85 @@ export async function toEthHeader({
     baseFeePerGas: padString(bigIntToHex(baseFee), 32),
   };
 }
+
+/**
+ * Computes the EVM-style hash of an Ethereum block header, i.e. the keccak hash
+ * of its RLP encoding, as computed by Reth.
+ * @param header - The Ethereum block header in JSON RPC format.
+ * @returns The keccak hash of the RLP encoded header in hex.
+ *
+ * Note: the parent hash of the header must be the EVM-style hash of the parent
+ * block, see `EvmBlockHashes`.
+ */
+export function toEvmBlockHash(header: JsonRpcBlock): PrefixedHexString {
+  const quantity = (value: PrefixedHexString) =>
+    bigIntToUnpaddedBytes(BigInt(value));
+  const encoded = RLP.encode([
+    hexToBytes(header.parentHash),
+    hexToBytes(header.sha3Uncles),
+    hexToBytes(header.miner),
+    hexToBytes(header.stateRoot),
+    hexToBytes(header.transactionsRoot),
+    hexToBytes(header.receiptsRoot),
+    hexToBytes(header.logsBloom),
+    quantity(header.difficulty),
+    quantity(header.number),
+    quantity(header.gasLimit),
+    quantity(header.gasUsed),
+    quantity(header.timestamp),
+    hexToBytes(header.extraData),
+    hexToBytes(header.mixHash),
+    hexToBytes(header.nonce),
+    quantity(header.baseFeePerGas!),
+    hexToBytes(header.withdrawalsRoot!),
+  ]);
+  return bytesToHex(keccak256(encoded));
+}
+
+/**
+ * The EVM-style hashes of the last indexed blocks, by Starknet block hash.
+ * The blocks being indexed in order, the parent hash of a block is replaced by
+ * the EVM-style hash of its parent before hashing it, so that the hashes are
+ * chained as on Ethereum.
+ */
+export class EvmBlockHashes {
+  private readonly hashes = new Map<PrefixedHexString, PrefixedHexString>();
+
+  /**
+   * @param capacity - The number of recent blocks of which the hash is kept.
+   */
+  constructor(private readonly capacity: number = 1024) {}
+
+  /**
+   * Links the header to its parent and computes its EVM-style hash, kept for
+   * its child.
+   * @param header - The Ethereum block header, its parent hash being the
+   * Starknet hash of the parent block.
+   * @param starknetBlockHash - The Starknet hash of the block.
+   * @returns The EVM-style hash of the header.
+   *
+   * Note: the parent of the first block indexed after a restart is unknown, its
+   * Starknet hash is then kept as the parent hash.
+   */
+  hash(
+    header: JsonRpcBlock,
+    starknetBlockHash: PrefixedHexString,
+  ): PrefixedHexString {
+    const parentHash = this.hashes.get(header.parentHash);
+    if (parentHash) {
+      header.parentHash = parentHash;
+    } else if (header.parentHash !== NULL_HASH) {
+      console.warn(
+        `⚠️ Unknown EVM hash of the parent ${header.parentHash} of block ${header.number}, keeping its Starknet hash`,
+      );
+    }
+
+    const hash = toEvmBlockHash(header);
+    this.hashes.set(starknetBlockHash, hash);
+    // The map iterates in insertion order, the oldest block comes first.
+    if (this.hashes.size > this.capacity) {
+      this.hashes.delete(this.hashes.keys().next().value!);
+    }
+    return hash;
+  }
+}
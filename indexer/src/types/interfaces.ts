@@ -43,6 +43,10 @@ export interface ExtendedJsonRpcTx extends JsonRpcTx {
   yParity?: string;
   /** Indicates the reverted message if the transaction was reverted. */
   reverted?: string;
+  /** Indicates that the transaction ran out of Cairo resources. */
+  outOfResources?: boolean;
+  /** Indicates the input was truncated to fit in a document. */
+  inputTruncated?: boolean;
 }
 
 /**
@@ -1,5 +1,6 @@
 // Utils
 import { padBytes } from "../utils/hex.ts";
+import { isReverted } from "../utils/filter.ts";
 
 // Constants
 import { NULL_HASH } from "../constants.ts";
@@ -9,7 +10,7 @@ import { fromJsonRpcLog } from "./log.ts";
 import { JsonRpcLog, JsonRpcReceipt } from "./types.ts";
 
 // Starknet
-import { Event } from "../deps.ts";
+import { Event, TransactionReceipt } from "../deps.ts";
 
 // Eth
 import {
@@ -96,6 +97,7 @@ export function toEthReceipt({
  * @param blockHash - The block hash of the transaction in hex.
  * @param cumulativeGasUsed - The cumulative gas used up to this transaction.
  * @param isPendingBlock - Whether the block is pending.
+ * @param outOfResources - Whether the Starknet transaction ran out of Cairo resources.
  * @returns - The Ethereum receipt corresponding to a reverted out of resources transaction.
  */
 export function toRevertedOutOfResourcesReceipt({
@@ -104,12 +106,14 @@ export function toRevertedOutOfResourcesReceipt({
   blockHash,
   cumulativeGasUsed,
   isPendingBlock,
+  outOfResources,
 }: {
   transaction: JsonRpcTx;
   blockNumber: PrefixedHexString;
   blockHash: PrefixedHexString;
   cumulativeGasUsed: bigint;
   isPendingBlock?: boolean;
+  outOfResources?: boolean;
 }): JsonRpcReceipt {
   return {
     transactionHash: transaction.hash,
@@ -130,9 +134,42 @@ export function toRevertedOutOfResourcesReceipt({
     logsBloom: logsBloom([]),
     status: bigIntToHex(0n),
     type: transaction.type,
+    ...(outOfResources ? { outOfResources } : {}),
   };
 }
 
+/**
+ * Verifies the status and the gas used of the Ethereum receipt against the outcome of its
+ * Starknet transaction. The status must be either 0x0 or 0x1, and the receipt of a transaction
+ * reverted on Starknet must be failed and use no gas. The inconsistencies are logged and fixed.
+ *
+ * @param receipt - The Ethereum receipt.
+ * @param starknetReceipt - The receipt of the Starknet transaction.
+ * @returns - The verified Ethereum receipt.
+ */
+export function verifyReceiptStatus(
+  receipt: JsonRpcReceipt,
+  starknetReceipt: TransactionReceipt,
+): JsonRpcReceipt {
+  const reverted = isReverted(starknetReceipt);
+  const status = reverted || BigInt(receipt.status ?? "0x0") === 0n
+    ? bigIntToHex(0n)
+    : bigIntToHex(1n);
+  const gasUsed = reverted ? bigIntToHex(0n) : receipt.gasUsed;
+  if (status === receipt.status && gasUsed === receipt.gasUsed) {
+    return receipt;
+  }
+
+  console.warn(
+    `⚠️ Receipt ${receipt.transactionHash} with status ${receipt.status} and gas used ${receipt.gasUsed} doesn't match the Starknet execution status ${starknetReceipt.executionStatus}${
+      starknetReceipt.revertReason
+        ? ` - Revert reason: ${starknetReceipt.revertReason}`
+        : ""
+    }`,
+  );
+  return { ...receipt, status, gasUsed };
+}
+
 /**
  * @param logs - A array of Ethereum logs.
  * @returns - The corresponding logs bloom.
@@ -1,6 +1,6 @@
 // Utils
 import { padBigint, padBytes } from "../utils/hex.ts";
-import { isReverted } from "../utils/filter.ts";
+import { isOutOfResources, isReverted } from "../utils/filter.ts";
 
 // Starknet
 import { Transaction, TransactionReceipt, uint256 } from "../deps.ts";
@@ -135,6 +135,10 @@ export function setRevertedFlag(
     // Set the reverted field to the result
     result.reverted = receipt.revertReason;
   }
+  // Tag the transactions which ran out of Cairo resources
+  if (isOutOfResources(receipt)) {
+    result.outOfResources = true;
+  }
 }
 
 /**
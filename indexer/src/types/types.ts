@@ -16,6 +16,7 @@ export enum Collection {
   Logs = "logs",
   Receipts = "receipts",
   Headers = "headers",
+  Balances = "balances",
 }
 
 /**
@@ -23,10 +24,62 @@ export enum Collection {
  */
 export type StoreItem<C = Collection> = {
   collection: C;
-  data: C extends Collection.Transactions ? { tx: JsonRpcTx }
+  data: C extends Collection.Transactions ? { tx: JsonRpcTx | JsonRpcDepositTx }
     : C extends Collection.Logs ? { log: JsonRpcLog }
     : C extends Collection.Receipts ? { receipt: JsonRpcReceipt }
-    : { header: JsonRpcBlock };
+    : C extends Collection.Balances ? { balance: BalanceSnapshot }
+    : {
+      header: JsonRpcBlock;
+      starknetBlockHash?: string;
+      feeRecipient?: FeeRecipient;
+      skippedTransactions?: SkippedTransactions;
+    };
+};
+
+/**
+ * Represents the number of Starknet transactions of a block which weren't converted to
+ * Ethereum transactions, by Starknet transaction type (e.g. "l1Handler", "deployAccount").
+ */
+export type SkippedTransactions = Record<string, number>;
+
+/**
+ * Represents a deposit transaction, converted from a Starknet L1 handler transaction executing
+ * an L1 message on Kakarot, in the format of the deposit transactions of the OP stack.
+ */
+export type JsonRpcDepositTx = {
+  blockHash: string | null; // DATA, 32 Bytes - hash of the block where this transaction was in. null when it's pending.
+  blockNumber: string | null; // QUANTITY - block number where this transaction was in.
+  from: string; // DATA, 20 Bytes - the L1 sender of the message.
+  gas: string; // QUANTITY - always 0, the L1 handler transaction isn't charged in EVM gas.
+  gasPrice: string; // QUANTITY - always 0.
+  hash: string; // DATA, 32 Bytes - hash of the Starknet L1 handler transaction.
+  input: string; // DATA - the data of the message.
+  nonce: string; // QUANTITY - the nonce of the L1 message.
+  to: string | null; // DATA, 20 Bytes - the EVM recipient of the message.
+  transactionIndex: string | null; // QUANTITY - index of the Starknet transaction in the block. null when it's pending.
+  value: string; // QUANTITY - value transferred with the message, in Wei.
+  type: string; // QUANTITY - always 0x7e, the type of the deposit transactions.
+  l1FromAddress: string; // DATA, 20 Bytes - the L1 contract which sent the message to Starknet.
+  isSystemTx: boolean; // always false.
+  reverted?: string; // the revert reason of the Starknet transaction, if reverted.
+};
+
+/**
+ * Represents the recipients of the fees of a block.
+ */
+export type FeeRecipient = {
+  coinbase: string; // DATA, 20 Bytes - the coinbase of Kakarot core at the block, receiving the EVM fees.
+  sequencerAddress: string; // DATA, 32 Bytes - the Starknet sequencer of the block, receiving the Starknet fees.
+  verified: boolean; // true if the coinbase was read from Kakarot core at the block and is a valid EVM address.
+};
+
+/**
+ * Represents the native token balance of a Starknet account at the end of a block.
+ */
+export type BalanceSnapshot = {
+  address: string; // DATA, 32 Bytes - Starknet address of the account.
+  blockNumber: string; // QUANTITY - the block number at the end of which the balance was taken.
+  balance: string | null; // QUANTITY - the native token balance of the account, null if it couldn't be fetched.
 };
 
 /**
@@ -44,6 +97,8 @@ export type JsonRpcLog = {
   topics: string[]; // Array of DATA - Array of 0 to 4 32 Bytes DATA of indexed log arguments.
   // (In solidity: The first topic is the hash of the signature of the event
   // (e.g. Deposit(address,bytes32,uint256)), except you declared the event with the anonymous specifier.)
+  dataTruncated?: boolean; // true if the data was truncated to fit in a document, see `dataSize`.
+  dataSize?: string; // QUANTITY - the size in bytes of the data before its truncation.
 };
 
 /**
@@ -69,6 +124,10 @@ export type JsonRpcReceipt = {
   status?: string; // QUANTITY, either 1 (success) or 0 (failure)
   blobGasUsed?: string; // QUANTITY, blob gas consumed by transaction (if blob transaction)
   blobGasPrice?: string; // QUAntity, blob gas price for block including this transaction (if blob transaction)
+  outOfResources?: boolean; // true if the Starknet transaction ran out of Cairo resources.
+  logsTruncated?: boolean; // true if the data of some logs was truncated to fit in a document.
+  depositNonce?: string; // QUANTITY - the nonce of the L1 message, for the deposit receipts.
+  depositReceiptVersion?: string; // QUANTITY - the version of the deposit receipts, for the deposit receipts.
 };
 
 /**
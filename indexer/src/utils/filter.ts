@@ -2,7 +2,12 @@
 import { Event, Transaction, TransactionReceipt } from "../deps.ts";
 
 // Constants
-import { KAKAROT_ADDRESS } from "../constants.ts";
+import {
+  KAKAROT_ADDRESS,
+  NATIVE_TOKEN_ADDRESS,
+  OUT_OF_RESOURCES_REVERT_REASON,
+  TRANSFER,
+} from "../constants.ts";
 
 /**
  * Determines if a given transaction is related to Kakarot.
@@ -81,3 +86,31 @@ export function isReverted(
     receipt.executionStatus.includes("REVERTED")
   );
 }
+
+/**
+ * Checks if a transaction receipt indicates that it was reverted because it ran out of Cairo resources.
+ *
+ * @param {TransactionReceipt} receipt - The transaction receipt to check.
+ * @returns {boolean} - Returns true if the transaction ran out of resources, otherwise false.
+ */
+export function isOutOfResources(
+  receipt: TransactionReceipt,
+): boolean {
+  return (
+    isReverted(receipt) &&
+    (receipt.revertReason?.includes(OUT_OF_RESOURCES_REVERT_REASON) ?? false)
+  );
+}
+
+/**
+ * Checks if an event is a transfer of the native token.
+ *
+ * @param {Event} event - The event to check.
+ * @returns {boolean} - Returns true if the event is a native token `Transfer` event, otherwise false.
+ */
+export function isNativeTokenTransfer(event: Event): boolean {
+  return (
+    BigInt(event.fromAddress ?? 0) === BigInt(NATIVE_TOKEN_ADDRESS) &&
+    BigInt(event.keys?.[0] ?? 0) === BigInt(TRANSFER)
+  );
+}
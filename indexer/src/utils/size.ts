@@ -0,0 +1,143 @@
+// Types
+import {
+  Collection,
+  JsonRpcLog,
+  JsonRpcReceipt,
+  StoreItem,
+} from "../types/types.ts";
+import { ExtendedJsonRpcTx } from "../types/interfaces.ts";
+
+// Constants
+import { MAX_DOCUMENT_SIZE } from "../constants.ts";
+
+const encoder = new TextEncoder();
+
+/**
+ * The number of truncated and skipped documents since the start of the indexer.
+ */
+export const oversizedDocuments = { truncated: 0, skipped: 0 };
+
+/**
+ * @param data - The data of a document.
+ * @returns - An upper bound of the size of the BSON encoding of the document, in bytes.
+ * The hex strings dominate the size of the documents, and are encoded as is in both formats.
+ */
+export function documentSize(data: unknown): number {
+  return encoder.encode(JSON.stringify(data)).length;
+}
+
+/**
+ * @param data - A hex string.
+ * @returns - The number of bytes of the hex string.
+ */
+function hexSize(data: string): number {
+  return Math.ceil((data.length - 2) / 2);
+}
+
+/**
+ * @param log - A log.
+ * @returns - The log without its data, flagged with `dataTruncated` and the
+ * size of the original data in `dataSize`.
+ */
+export function truncateLogData(log: JsonRpcLog): JsonRpcLog {
+  return {
+    ...log,
+    data: "0x",
+    dataTruncated: true,
+    dataSize: `0x${hexSize(log.data).toString(16)}`,
+  };
+}
+
+/**
+ * Truncates the largest logs of a list until the document fits under the
+ * maximum size.
+ * @param logs - The logs of the document.
+ * @param fits - Returns true if the document fits with the given logs.
+ * @returns - The logs, the largest ones being truncated.
+ */
+function truncateLargestLogs(
+  logs: JsonRpcLog[],
+  fits: (logs: JsonRpcLog[]) => boolean,
+): JsonRpcLog[] {
+  const truncated = [...logs];
+  const bySize = logs
+    .map((log, index) => ({ index, size: log.data.length }))
+    .sort((a, b) => b.size - a.size);
+  for (const { index } of bySize) {
+    if (fits(truncated)) break;
+    truncated[index] = truncateLogData(truncated[index]);
+  }
+  return truncated;
+}
+
+/**
+ * Guards the size of a document against the maximum size of a MongoDB
+ * document, so that a single oversized document doesn't fail the write of its
+ * whole block. The oversized payloads are truncated and flagged, depending on
+ * the collection:
+ * - the data of a log is truncated, with the `dataTruncated` flag,
+ * - the data of the largest logs of a receipt are truncated, with the
+ *   `logsTruncated` flag on the receipt,
+ * - the input of a transaction is truncated, with the `inputTruncated` flag.
+ *
+ * @param item - The document to store.
+ * @param maxSize - The maximum size of a document, in bytes.
+ * @returns - The document, truncated if needed, or null if it can't fit.
+ */
+export function guardDocumentSize(
+  item: StoreItem,
+  maxSize: number = MAX_DOCUMENT_SIZE,
+): StoreItem | null {
+  const size = documentSize(item.data);
+  if (size <= maxSize) return item;
+
+  let guarded: StoreItem | null = null;
+  switch (item.collection) {
+    case Collection.Logs: {
+      const { log } = item.data as { log: JsonRpcLog };
+      guarded = {
+        collection: Collection.Logs,
+        data: { log: truncateLogData(log) },
+      };
+      break;
+    }
+    case Collection.Receipts: {
+      const { receipt } = item.data as { receipt: JsonRpcReceipt };
+      const logs = truncateLargestLogs(
+        receipt.logs,
+        (logs) => documentSize({ receipt: { ...receipt, logs } }) <= maxSize,
+      );
+      guarded = {
+        collection: Collection.Receipts,
+        data: { receipt: { ...receipt, logs, logsTruncated: true } },
+      };
+      break;
+    }
+    case Collection.Transactions: {
+      const { tx } = item.data as { tx: ExtendedJsonRpcTx };
+      guarded = {
+        collection: Collection.Transactions,
+        data: {
+          tx: { ...tx, input: "0x", inputTruncated: true } as ExtendedJsonRpcTx,
+        },
+      };
+      break;
+    }
+  }
+
+  if (guarded === null || documentSize(guarded.data) > maxSize) {
+    oversizedDocuments.skipped += 1;
+    console.error(
+      `⚠️ Skipped oversized document in ${item.collection}: ${size} bytes, maximum ${maxSize} bytes.`,
+      `Skipped documents: ${oversizedDocuments.skipped}`,
+    );
+    return null;
+  }
+
+  oversizedDocuments.truncated += 1;
+  console.warn(
+    `⚠️ Truncated oversized document in ${item.collection}: ${size} bytes, maximum ${maxSize} bytes.`,
+    `Truncated documents: ${oversizedDocuments.truncated}`,
+  );
+  return guarded;
+}
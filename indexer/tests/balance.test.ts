@@ -0,0 +1,134 @@
+import { assertEquals } from "https://deno.land/std@0.213.0/assert/assert_equals.ts";
+import sinon from "npm:sinon";
+import { Event } from "../src/deps.ts";
+import { PROVIDER } from "../src/provider.ts";
+import {
+  toBalanceSnapshots,
+  transferParticipants,
+} from "../src/types/balance.ts";
+import { NATIVE_TOKEN_ADDRESS, TRANSFER } from "../src/constants.ts";
+import { padString } from "../src/utils/hex.ts";
+
+const transfer = (from: `0x${string}`, to: `0x${string}`) => {
+  return {
+    index: 0,
+    fromAddress: NATIVE_TOKEN_ADDRESS,
+    keys: [TRANSFER],
+    data: [from, to, "0x64", "0x0"],
+  } as Event;
+};
+
+Deno.test("transferParticipants: deduplicated senders and recipients", () => {
+  // Given
+  const events = [transfer("0x1", "0x2"), transfer("0x2", "0x3")];
+
+  // When
+  const participants = transferParticipants(events);
+
+  // Then
+  assertEquals(participants, [
+    padString("0x1", 32),
+    padString("0x2", 32),
+    padString("0x3", 32),
+  ]);
+});
+
+Deno.test("transferParticipants: addresses as keys", () => {
+  // Given
+  const event = {
+    index: 0,
+    fromAddress: NATIVE_TOKEN_ADDRESS,
+    keys: [TRANSFER, "0x1", "0x2"],
+    data: ["0x64", "0x0"],
+  } as Event;
+
+  // When
+  const participants = transferParticipants([event]);
+
+  // Then
+  assertEquals(participants, [padString("0x1", 32), padString("0x2", 32)]);
+});
+
+Deno.test("toBalanceSnapshots: balances at the end of the block", async () => {
+  // Given
+  const callStub = sinon.stub(PROVIDER, "callContract").resolves({
+    result: ["0x64", "0x1"],
+  });
+
+  // When
+  const snapshots = await toBalanceSnapshots({
+    events: [transfer("0x1", "0x2")],
+    blockNumber: "0x0000000000000005",
+    blockHash: padString("0x5", 32),
+    isPendingBlock: false,
+  });
+
+  // Then
+  const balance = "0x100000000000000000000000000000064";
+  assertEquals(snapshots, [
+    {
+      address: padString("0x1", 32),
+      blockNumber: "0x0000000000000005",
+      balance,
+    },
+    {
+      address: padString("0x2", 32),
+      blockNumber: "0x0000000000000005",
+      balance,
+    },
+  ]);
+  assertEquals(callStub.callCount, 2);
+  assertEquals(callStub.firstCall.args[1], padString("0x5", 32));
+
+  callStub.restore();
+});
+
+Deno.test("toBalanceSnapshots: pending block", async () => {
+  // Given
+  const callStub = sinon.stub(PROVIDER, "callContract");
+
+  // When
+  const snapshots = await toBalanceSnapshots({
+    events: [transfer("0x1", "0x2")],
+    blockNumber: "0x0000000000000005",
+    blockHash: padString("0x", 32),
+    isPendingBlock: true,
+  });
+
+  // Then
+  assertEquals(snapshots, []);
+  assertEquals(callStub.callCount, 0);
+
+  callStub.restore();
+});
+
+Deno.test("toBalanceSnapshots: balance fetch failure", async () => {
+  // Given
+  const callStub = sinon.stub(PROVIDER, "callContract");
+  callStub.onFirstCall().resolves({ result: ["0x64", "0x0"] });
+  callStub.onSecondCall().rejects(new Error("block not found"));
+
+  // When
+  const snapshots = await toBalanceSnapshots({
+    events: [transfer("0x1", "0x2")],
+    blockNumber: "0x0000000000000005",
+    blockHash: padString("0x5", 32),
+    isPendingBlock: false,
+  });
+
+  // Then
+  assertEquals(snapshots, [
+    {
+      address: padString("0x1", 32),
+      blockNumber: "0x0000000000000005",
+      balance: "0x64",
+    },
+    {
+      address: padString("0x2", 32),
+      blockNumber: "0x0000000000000005",
+      balance: null,
+    },
+  ]);
+
+  callStub.restore();
+});
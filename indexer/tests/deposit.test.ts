@@ -0,0 +1,170 @@
+import { assertEquals } from "https://deno.land/std@0.213.0/assert/mod.ts";
+import {
+  DEPOSIT_RECEIPT_VERSION,
+  DEPOSIT_TX_TYPE,
+  starknetTransactionType,
+  toDepositReceipt,
+  toDepositTx,
+  toSkippedTransactions,
+} from "../src/types/deposit.ts";
+import { HANDLE_L1_MESSAGE, KAKAROT_ADDRESS } from "../src/constants.ts";
+import { Transaction, TransactionReceipt } from "../src/deps.ts";
+
+const l1Handler = (
+  hash: `0x${string}`,
+  calldata: `0x${string}`[],
+  contractAddress: string = KAKAROT_ADDRESS,
+) =>
+  ({
+    meta: { hash, maxFee: "0x0", signature: [], nonce: "0x0", version: "0x0" },
+    l1Handler: {
+      contractAddress,
+      entryPointSelector: HANDLE_L1_MESSAGE,
+      calldata,
+      nonce: "0x5",
+    },
+  }) as unknown as Transaction;
+
+const receipt = {
+  executionStatus: "EXECUTION_STATUS_SUCCEEDED",
+  transactionHash: "0x1",
+  transactionIndex: "3",
+  events: [],
+} as unknown as TransactionReceipt;
+
+const blockInfo = {
+  blockNumber: "0x0000000000000001",
+  blockHash:
+    "0x0000000000000000000000000000000000000000000000000000000000000abc",
+  isPendingBlock: false,
+};
+
+Deno.test("starknetTransactionType: strips the version", () => {
+  assertEquals(
+    starknetTransactionType({ meta: {}, invokeV1: {} } as Transaction),
+    "invoke",
+  );
+  assertEquals(
+    starknetTransactionType(
+      { meta: {}, deployAccountV3: {} } as unknown as Transaction,
+    ),
+    "deployAccount",
+  );
+  assertEquals(starknetTransactionType(l1Handler("0x1", [])), "l1Handler");
+  assertEquals(starknetTransactionType({ meta: {} } as Transaction), null);
+});
+
+Deno.test("toDepositTx: L1 message to Kakarot", () => {
+  // Given
+  // fromAddress, l1Sender, toAddress, value, dataLen, data
+  const calldata: `0x${string}`[] = [
+    "0xa",
+    "0xb",
+    "0xc",
+    "0x64",
+    "0x2",
+    "0x12",
+    "0x34",
+  ];
+
+  // When
+  const deposit = toDepositTx({
+    transaction: l1Handler("0x1", calldata),
+    receipt,
+    ...blockInfo,
+  });
+
+  // Then
+  assertEquals(deposit, {
+    blockHash: blockInfo.blockHash,
+    blockNumber: blockInfo.blockNumber,
+    from: "0x000000000000000000000000000000000000000b",
+    gas: "0x0",
+    gasPrice: "0x0",
+    hash: "0x0000000000000000000000000000000000000000000000000000000000000001",
+    input: "0x1234",
+    nonce: "0x5",
+    to: "0x000000000000000000000000000000000000000c",
+    transactionIndex: "0x0000000000000003",
+    value: "0x64",
+    type: DEPOSIT_TX_TYPE,
+    l1FromAddress: "0x000000000000000000000000000000000000000a",
+    isSystemTx: false,
+  });
+});
+
+Deno.test("toDepositReceipt: receipt of a deposit", () => {
+  // Given
+  const deposit = toDepositTx({
+    transaction: l1Handler("0x1", ["0xa", "0xb", "0xc", "0x64", "0x0"]),
+    receipt,
+    ...blockInfo,
+  })!;
+
+  // When
+  const depositReceipt = toDepositReceipt(deposit, 100n);
+
+  // Then
+  assertEquals(depositReceipt.transactionHash, deposit.hash);
+  assertEquals(depositReceipt.transactionIndex, "0x3");
+  assertEquals(depositReceipt.blockHash, blockInfo.blockHash);
+  assertEquals(depositReceipt.cumulativeGasUsed, "0x64");
+  assertEquals(depositReceipt.gasUsed, "0x0");
+  assertEquals(depositReceipt.logs, []);
+  assertEquals(depositReceipt.status, "0x1");
+  assertEquals(depositReceipt.type, DEPOSIT_TX_TYPE);
+  assertEquals(depositReceipt.depositNonce, deposit.nonce);
+  assertEquals(depositReceipt.depositReceiptVersion, DEPOSIT_RECEIPT_VERSION);
+  assertEquals(
+    toDepositReceipt({ ...deposit, reverted: "reason" }, 100n).status,
+    "0x0",
+  );
+});
+
+Deno.test("toDepositTx: invalid L1 messages", () => {
+  // Other contract
+  assertEquals(
+    toDepositTx({
+      transaction: l1Handler("0x1", ["0xa", "0xb", "0xc", "0x0", "0x0"], "0x2"),
+      receipt,
+      ...blockInfo,
+    }),
+    null,
+  );
+  // Data length mismatch
+  assertEquals(
+    toDepositTx({
+      transaction: l1Handler("0x1", ["0xa", "0xb", "0xc", "0x0", "0x2", "0x1"]),
+      receipt,
+      ...blockInfo,
+    }),
+    null,
+  );
+  // Missing fields
+  assertEquals(
+    toDepositTx({
+      transaction: l1Handler("0x1", ["0xa", "0xb"]),
+      receipt,
+      ...blockInfo,
+    }),
+    null,
+  );
+});
+
+Deno.test("toSkippedTransactions: counts the non-Invoke transactions", () => {
+  // Given
+  const transactions = [
+    { meta: { hash: "0x1" }, invokeV1: {} },
+    { meta: { hash: "0x2" }, deployAccount: {} },
+    { meta: { hash: "0x3" }, deployAccountV3: {} },
+    { meta: { hash: "0x4" }, l1Handler: {} },
+    { meta: { hash: "0x5" }, l1Handler: {} },
+  ] as unknown as Transaction[];
+
+  // When
+  const skipped = toSkippedTransactions(transactions, new Set([4n]));
+
+  // Then
+  assertEquals(skipped, { deployAccount: 2, l1Handler: 1 });
+  assertEquals(toSkippedTransactions(transactions.slice(0, 1), new Set()), {});
+});
@@ -5,6 +5,7 @@ import {
 import {
   ethValidationFailed,
   isKakarotTransaction,
+  isOutOfResources,
   isReverted,
 } from "../src/utils/filter.ts";
 import { Event, Transaction, TransactionReceipt } from "../src/deps.ts";
@@ -311,3 +312,50 @@ Deno.test(
     assert(isReverted(receipt));
   },
 );
+
+Deno.test(
+  "isOutOfResources: true on status reverted and out of resources revert reason",
+  () => {
+    const receipt: TransactionReceipt = {
+      executionStatus: "EXECUTION_STATUS_REVERTED",
+      transactionHash: "0x01",
+      transactionIndex: "0x01",
+      actualFee: "0x01",
+      contractAddress: "0x01",
+      l2ToL1Messages: [],
+      events: [],
+      revertReason:
+        "Could not reach the end of the program. RunResources has no remaining steps",
+    };
+    assert(isOutOfResources(receipt));
+  },
+);
+
+Deno.test("isOutOfResources: false on another revert reason", () => {
+  const receipt: TransactionReceipt = {
+    executionStatus: "EXECUTION_STATUS_REVERTED",
+    transactionHash: "0x01",
+    transactionIndex: "0x01",
+    actualFee: "0x01",
+    contractAddress: "0x01",
+    l2ToL1Messages: [],
+    events: [],
+    revertReason: "eth validation failed",
+  };
+  assertFalse(isOutOfResources(receipt));
+});
+
+Deno.test("isOutOfResources: false on status succeeded", () => {
+  const receipt: TransactionReceipt = {
+    executionStatus: "EXECUTION_STATUS_SUCCEEDED",
+    transactionHash: "0x01",
+    transactionIndex: "0x01",
+    actualFee: "0x01",
+    contractAddress: "0x01",
+    l2ToL1Messages: [],
+    events: [],
+    revertReason:
+      "Could not reach the end of the program. RunResources has no remaining steps",
+  };
+  assertFalse(isOutOfResources(receipt));
+});
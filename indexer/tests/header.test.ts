@@ -6,7 +6,13 @@ import {
   PrefixedHexString,
 } from "../src/deps.ts";
 import { assertEquals } from "https://deno.land/std@0.213.0/assert/assert_equals.ts";
-import { toEthHeader } from "../src/types/header.ts";
+import {
+  EvmBlockHashes,
+  getCoinbase,
+  toEthHeader,
+  toEvmBlockHash,
+  toFeeRecipient,
+} from "../src/types/header.ts";
 import { JsonRpcBlock } from "../src/types/types.ts";
 import { DEFAULT_BLOCK_GAS_LIMIT } from "../src/constants.ts";
 import { padString } from "../src/utils/hex.ts";
@@ -451,3 +457,167 @@ Deno.test("toEthHeader with mocked call", async () => {
   // Restore the original KAKAROT.call function
   callStub.restore();
 });
+
+Deno.test("getCoinbase with a valid coinbase", async () => {
+  const callStub = sinon.stub(KAKAROT, "call").resolves({
+    coinbase: BigInt(2),
+  });
+
+  assertEquals(
+    await getCoinbase({
+      blockNumber: "0x1",
+      blockHash: NULL_HASH,
+      isPendingBlock: true,
+    }),
+    { coinbase: BigInt(2), verified: true },
+  );
+
+  callStub.restore();
+});
+
+Deno.test("getCoinbase with a coinbase which is not an EVM address", async () => {
+  const callStub = sinon.stub(KAKAROT, "call").resolves({
+    coinbase: BigInt(1) << BigInt(160),
+  });
+
+  assertEquals(
+    await getCoinbase({
+      blockNumber: "0x1",
+      blockHash: NULL_HASH,
+      isPendingBlock: true,
+    }),
+    { coinbase: BigInt(0), verified: false },
+  );
+
+  callStub.restore();
+});
+
+Deno.test("getCoinbase with a failing call", async () => {
+  const callStub = sinon.stub(KAKAROT, "call").rejects(new Error("error"));
+
+  assertEquals(
+    await getCoinbase({
+      blockNumber: "0x1",
+      blockHash: NULL_HASH,
+      isPendingBlock: true,
+    }),
+    { coinbase: BigInt(0), verified: false },
+  );
+
+  callStub.restore();
+});
+
+Deno.test("toFeeRecipient pads the coinbase and the sequencer address", () => {
+  const header: BlockHeader = {
+    blockHash: NULL_HASH,
+    parentBlockHash: NULL_HASH,
+    blockNumber: "0x1",
+    sequencerAddress: "0x1234",
+    newRoot: NULL_HASH,
+    timestamp: "2023-07-03T12:34:56Z",
+  };
+
+  assertEquals(
+    toFeeRecipient({ header, coinbase: BigInt(2), verified: true }),
+    {
+      coinbase: "0x0000000000000000000000000000000000000002",
+      sequencerAddress:
+        "0x0000000000000000000000000000000000000000000000000000000000001234",
+      verified: true,
+    },
+  );
+});
+
+Deno.test("toEvmBlockHash hashes the RLP encoded header", () => {
+  const emptyRoot =
+    "0x56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421";
+  const header: JsonRpcBlock = {
+    number: "0x1",
+    hash: NULL_HASH,
+    parentHash:
+      "0x14e330b38856cb7e21b04db7e9fd6872840333ecf753050fe421dfb42b3a3486",
+    mixHash: NULL_HASH,
+    nonce: "0x0000000000000000",
+    sha3Uncles:
+      "0x1dcc4de8dec75d7aab85b567b6ccd41ad312451b948a7413f0a142fd40d49347",
+    logsBloom: bytesToHex(new Uint8Array(256)),
+    transactionsRoot: emptyRoot,
+    stateRoot:
+      "0x3485c8e91ae6107202df98042de861c3229618b46a4bd03407edd84bafeb452f",
+    receiptsRoot: emptyRoot,
+    miner: "0x0000000000000000000000000000000000000000",
+    difficulty: "0x00",
+    totalDifficulty: "0x00",
+    extraData: "0x",
+    size: "0x00",
+    gasLimit: padString(bigIntToHex(7_000_000n), 32),
+    gasUsed: "0xa",
+    timestamp: "0x64a2bf90",
+    transactions: [],
+    uncles: [],
+    withdrawals: [],
+    withdrawalsRoot: emptyRoot,
+    baseFeePerGas: padString("0x1", 32),
+  };
+
+  // The hash computed by Reth for the header, see the tests of the header
+  // types of the RPC.
+  assertEquals(
+    toEvmBlockHash(header),
+    "0x15bb90d9a0bd7c984b8c2f798274b82264bac69582dcdd83e0541e0677c67657",
+  );
+  // The hash doesn't depend on the stored hash
+  assertEquals(
+    toEvmBlockHash({ ...header, hash: "0x1234" }),
+    toEvmBlockHash(header),
+  );
+});
+
+Deno.test("EvmBlockHashes chains the hashes of consecutive blocks", () => {
+  const emptyRoot =
+    "0x56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421";
+  const header = (
+    number: PrefixedHexString,
+    parentHash: PrefixedHexString,
+  ): JsonRpcBlock => ({
+    number,
+    hash: NULL_HASH,
+    parentHash,
+    mixHash: NULL_HASH,
+    nonce: "0x0000000000000000",
+    sha3Uncles:
+      "0x1dcc4de8dec75d7aab85b567b6ccd41ad312451b948a7413f0a142fd40d49347",
+    logsBloom: bytesToHex(new Uint8Array(256)),
+    transactionsRoot: emptyRoot,
+    stateRoot: NULL_HASH,
+    receiptsRoot: emptyRoot,
+    miner: "0x0000000000000000000000000000000000000000",
+    difficulty: "0x00",
+    totalDifficulty: "0x00",
+    extraData: "0x",
+    size: "0x00",
+    gasLimit: padString(bigIntToHex(7_000_000n), 32),
+    gasUsed: "0x0",
+    timestamp: "0x64a2bf90",
+    transactions: [],
+    uncles: [],
+    withdrawals: [],
+    withdrawalsRoot: emptyRoot,
+    baseFeePerGas: padString("0x1", 32),
+  });
+  const firstStarknetHash = padString("0x1", 32);
+  const secondStarknetHash = padString("0x2", 32);
+  const evmBlockHashes = new EvmBlockHashes();
+
+  const first = header("0x1", padString("0xabc", 32));
+  const firstHash = evmBlockHashes.hash(first, firstStarknetHash);
+  const second = header("0x2", firstStarknetHash);
+  const secondHash = evmBlockHashes.hash(second, secondStarknetHash);
+
+  // The parent of the first block is unknown, its Starknet hash is kept
+  assertEquals(first.parentHash, padString("0xabc", 32));
+  assertEquals(firstHash, toEvmBlockHash(first));
+  // The second block is linked to the EVM-style hash of the first one
+  assertEquals(second.parentHash, firstHash);
+  assertEquals(secondHash, toEvmBlockHash(second));
+});
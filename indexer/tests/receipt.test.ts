@@ -0,0 +1,86 @@
+import { assertEquals } from "https://deno.land/std@0.213.0/assert/mod.ts";
+import { verifyReceiptStatus } from "../src/types/receipt.ts";
+import { JsonRpcReceipt } from "../src/types/types.ts";
+import { TransactionReceipt } from "../src/deps.ts";
+
+const receipt = (status: string, gasUsed: string): JsonRpcReceipt => {
+  return {
+    transactionHash: "0x01",
+    transactionIndex: "0x0",
+    blockHash: "0x02",
+    blockNumber: "0x1",
+    from: "0x03",
+    to: "0x04",
+    cumulativeGasUsed: gasUsed,
+    effectiveGasPrice: "0x1",
+    gasUsed,
+    contractAddress: null,
+    logs: [],
+    logsBloom: "0x",
+    type: "0x2",
+    status,
+  };
+};
+
+const starknetReceipt = (
+  executionStatus: string,
+  revertReason?: string,
+): TransactionReceipt => {
+  return {
+    executionStatus,
+    transactionHash: "0x01",
+    transactionIndex: "0x01",
+    actualFee: "0x01",
+    contractAddress: "0x01",
+    l2ToL1Messages: [],
+    events: [],
+    revertReason,
+  } as TransactionReceipt;
+};
+
+Deno.test("verifyReceiptStatus: keeps a successful receipt", () => {
+  const ethReceipt = receipt("0x1", "0x5208");
+  assertEquals(
+    verifyReceiptStatus(
+      ethReceipt,
+      starknetReceipt("EXECUTION_STATUS_SUCCEEDED"),
+    ),
+    ethReceipt,
+  );
+});
+
+Deno.test("verifyReceiptStatus: keeps an EVM reverted receipt", () => {
+  const ethReceipt = receipt("0x0", "0x5208");
+  assertEquals(
+    verifyReceiptStatus(
+      ethReceipt,
+      starknetReceipt("EXECUTION_STATUS_SUCCEEDED"),
+    ),
+    ethReceipt,
+  );
+});
+
+Deno.test("verifyReceiptStatus: normalizes the status", () => {
+  assertEquals(
+    verifyReceiptStatus(
+      receipt("0x2", "0x5208"),
+      starknetReceipt("EXECUTION_STATUS_SUCCEEDED"),
+    ),
+    receipt("0x1", "0x5208"),
+  );
+});
+
+Deno.test(
+  "verifyReceiptStatus: fails the receipt of a Starknet reverted transaction",
+  () => {
+    const verified = verifyReceiptStatus(
+      receipt("0x1", "0x5208"),
+      starknetReceipt(
+        "EXECUTION_STATUS_REVERTED",
+        "Error in the called contract",
+      ),
+    );
+    assertEquals(verified.status, "0x0");
+    assertEquals(verified.gasUsed, "0x0");
+  },
+);
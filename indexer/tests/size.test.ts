@@ -0,0 +1,82 @@
+import {
+  assert,
+  assertEquals,
+} from "https://deno.land/std@0.213.0/assert/mod.ts";
+import {
+  documentSize,
+  guardDocumentSize,
+  truncateLogData,
+} from "../src/utils/size.ts";
+import { Collection, JsonRpcLog, JsonRpcReceipt } from "../src/types/types.ts";
+import { JsonRpcTx } from "../src/deps.ts";
+
+const log = (data: string): JsonRpcLog => ({
+  removed: false,
+  logIndex: "0x0",
+  transactionIndex: "0x0",
+  transactionHash: "0x01",
+  blockHash: "0x02",
+  blockNumber: "0x1",
+  address: "0x03",
+  data,
+  topics: ["0x04"],
+});
+
+Deno.test("guardDocumentSize: keeps the documents under the maximum size", () => {
+  const item = { collection: Collection.Logs, data: { log: log("0x0102") } };
+  assertEquals(guardDocumentSize(item, 1024), item);
+});
+
+Deno.test("guardDocumentSize: truncates the data of an oversized log", () => {
+  const item = {
+    collection: Collection.Logs,
+    data: { log: log(`0x${"ff".repeat(1024)}`) },
+  };
+  const guarded = guardDocumentSize(item, 512);
+  assertEquals(guarded, {
+    collection: Collection.Logs,
+    data: {
+      log: { ...log("0x"), dataTruncated: true, dataSize: "0x400" },
+    },
+  });
+});
+
+Deno.test("guardDocumentSize: truncates the largest logs of an oversized receipt", () => {
+  const receipt = {
+    transactionHash: "0x01",
+    logs: [log("0x01"), log(`0x${"ff".repeat(1024)}`), log("0x02")],
+  } as JsonRpcReceipt;
+  const guarded = guardDocumentSize(
+    { collection: Collection.Receipts, data: { receipt } },
+    1024,
+  );
+  const { receipt: guardedReceipt } = guarded!.data as {
+    receipt: JsonRpcReceipt;
+  };
+  assert(guardedReceipt.logsTruncated);
+  assertEquals(guardedReceipt.logs, [
+    log("0x01"),
+    truncateLogData(log(`0x${"ff".repeat(1024)}`)),
+    log("0x02"),
+  ]);
+  assert(documentSize(guarded!.data) <= 1024);
+});
+
+Deno.test("guardDocumentSize: truncates the input of an oversized transaction", () => {
+  const tx = { hash: "0x01", input: `0x${"ff".repeat(1024)}` } as JsonRpcTx;
+  const guarded = guardDocumentSize(
+    { collection: Collection.Transactions, data: { tx } },
+    512,
+  );
+  assertEquals(guarded!.data, {
+    tx: { hash: "0x01", input: "0x", inputTruncated: true },
+  });
+});
+
+Deno.test("guardDocumentSize: skips the documents which can't be truncated", () => {
+  const item = {
+    collection: Collection.Logs,
+    data: { log: { ...log("0x"), topics: Array(32).fill("0x04") } },
+  };
+  assertEquals(guardDocumentSize(item, 128), null);
+});
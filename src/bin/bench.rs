@@ -0,0 +1,132 @@
+use alloy_consensus::TxEip1559;
+use alloy_eips::eip2718::Encodable2718;
+use alloy_primitives::{Address, TxKind, U256};
+use clap::Parser;
+use kakarot_rpc::{
+    client::KakarotTransactions,
+    pool::{
+        mempool::{AccountManager, PoolMaintainer},
+        prune::PruneSchedule,
+    },
+    providers::eth_provider::database::{
+        filter::{self, EthDatabaseFilterBuilder},
+        types::transaction::StoredEthStarknetTransactionHash,
+    },
+    test_utils::{bench::LatencyRecorder, eoa::Eoa, katana::Katana},
+};
+use reth_primitives::Transaction;
+use starknet::accounts::Account;
+use std::{sync::Arc, time::Duration};
+use tokio::{sync::Mutex, time::Instant};
+
+/// The inputs to the binary.
+#[derive(Parser, Debug)]
+pub struct Args {
+    /// The number of transactions to send per second.
+    #[clap(long, default_value_t = 10)]
+    tps: u64,
+    /// The duration of the benchmark, in seconds.
+    #[clap(long, default_value_t = 30)]
+    duration: u64,
+    /// The maximum time to wait for a transaction to be relayed, in seconds.
+    #[clap(long, default_value_t = 60)]
+    relay_timeout: u64,
+}
+
+/// Drives the mempool of a Kakarot RPC running against a Katana devnet at a configurable
+/// rate and reports the latencies of `send_raw_transaction`, the pool maintenance and the
+/// relayer submission.
+#[tokio::main]
+async fn main() -> eyre::Result<()> {
+    let args = Args::parse();
+    let relay_timeout = Duration::from_secs(args.relay_timeout);
+
+    // Start the devnet and the node services
+    let katana = Katana::new_empty().await;
+    let eth_client = Arc::new(katana.eth_client());
+    AccountManager::new(vec![katana.sequencer.account().address()], Arc::clone(&eth_client)).start();
+
+    // Run the pool maintenance, recording the duration of each iteration
+    let maintenance = Arc::new(Mutex::new(LatencyRecorder::default()));
+    {
+        let maintenance = Arc::clone(&maintenance);
+        let mut maintainer = PoolMaintainer::new(Arc::clone(&eth_client), PruneSchedule::default());
+        tokio::spawn(async move {
+            loop {
+                let start = Instant::now();
+                maintainer.maintain().await;
+                maintenance.lock().await.record(start.elapsed());
+                tokio::time::sleep(Duration::from_secs(1)).await;
+            }
+        });
+    }
+
+    let chain_id = eth_client.eth_provider().chain_id;
+    let mut send = LatencyRecorder::default();
+    let relay = Arc::new(Mutex::new(LatencyRecorder::default()));
+    let mut failures = 0usize;
+    let mut relay_tasks = Vec::new();
+
+    let mut interval = tokio::time::interval(Duration::from_secs_f64(1. / args.tps.max(1) as f64));
+    let start = Instant::now();
+    let mut nonce = 0;
+
+    while start.elapsed() < Duration::from_secs(args.duration) {
+        interval.tick().await;
+
+        let transaction = katana.eoa().sign_transaction(Transaction::Eip1559(TxEip1559 {
+            chain_id,
+            nonce,
+            gas_limit: 21000,
+            to: TxKind::Call(Address::random()),
+            value: U256::from(1),
+            max_fee_per_gas: 875_000_000,
+            ..Default::default()
+        }))?;
+
+        let sent_at = Instant::now();
+        let hash = match eth_client.send_raw_transaction(transaction.encoded_2718().into()).await {
+            Ok(hash) => hash,
+            Err(err) => {
+                failures += 1;
+                eprintln!("failed to send transaction {nonce}: {err}");
+                continue;
+            }
+        };
+        send.record(sent_at.elapsed());
+        nonce += 1;
+
+        // Wait for the relayer to submit the transaction to Starknet
+        let eth_client = Arc::clone(&eth_client);
+        let relay = Arc::clone(&relay);
+        relay_tasks.push(tokio::spawn(async move {
+            let filter =
+                EthDatabaseFilterBuilder::<filter::EthStarknetTransactionHash>::default().with_tx_hash(&hash).build();
+            while sent_at.elapsed() < relay_timeout {
+                let mapping: Option<StoredEthStarknetTransactionHash> =
+                    eth_client.eth_provider().database().get_one(filter.clone(), None).await.ok().flatten();
+                if mapping.is_some() {
+                    relay.lock().await.record(sent_at.elapsed());
+                    return;
+                }
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        }));
+    }
+    let elapsed = start.elapsed();
+
+    for task in relay_tasks {
+        task.await?;
+    }
+
+    let relay = relay.lock().await;
+    println!("benchmark of {nonce} transactions over {elapsed:?} ({failures} failed)");
+    println!("{}", send.report("send_raw_transaction", elapsed));
+    println!("{}", maintenance.lock().await.report("pool maintenance", elapsed));
+    println!("{}", relay.report("relayer submission", elapsed));
+    if relay.len() < send.len() {
+        println!("{} transactions were not relayed within {relay_timeout:?}", send.len() - relay.len());
+    }
+
+    Ok(())
+}
@@ -0,0 +1,65 @@
+use clap::Parser;
+use kakarot_rpc::{
+    providers::eth_provider::database::{
+        ethereum::EthereumBlockStore,
+        filter::{self, EthDatabaseFilterBuilder},
+        types::receipt::StoredTransactionReceipt,
+    },
+    test_utils::{
+        bench::LatencyRecorder,
+        mongo::{MongoFuzzer, RANDOM_BYTES_SIZE},
+    },
+};
+use tokio::time::Instant;
+
+/// The inputs to the binary.
+#[derive(Parser, Debug)]
+pub struct Args {
+    /// The number of transactions, receipts and headers in the database.
+    #[clap(long, default_value_t = 1000)]
+    transactions: usize,
+    /// The number of times the receipts of a block are retrieved by each strategy.
+    #[clap(long, default_value_t = 1000)]
+    iterations: usize,
+}
+
+/// Compares the latency of retrieving the receipts of a block by checking that the block exists
+/// before querying its receipts, with the aggregation pipeline joining the receipts to the header
+/// in a single round trip.
+#[tokio::main]
+async fn main() -> eyre::Result<()> {
+    let args = Args::parse();
+
+    let mut mongo_fuzzer = MongoFuzzer::new(RANDOM_BYTES_SIZE).await;
+    let database = mongo_fuzzer.mock_database(args.transactions).await;
+    database.create_indexes().await?;
+
+    let blocks: Vec<_> = mongo_fuzzer.headers.iter().map(|header| header.hash).collect();
+
+    let mut queries = LatencyRecorder::default();
+    let start = Instant::now();
+    for block_hash in blocks.iter().cycle().take(args.iterations) {
+        let started_at = Instant::now();
+        if database.block_exists((*block_hash).into()).await? {
+            let filter = EthDatabaseFilterBuilder::<filter::Receipt>::default().with_block_hash(block_hash).build();
+            let _: Vec<StoredTransactionReceipt> = database.get(filter, None).await?;
+        }
+        queries.record(started_at.elapsed());
+    }
+    let queries_elapsed = start.elapsed();
+
+    let mut aggregate = LatencyRecorder::default();
+    let start = Instant::now();
+    for block_hash in blocks.iter().cycle().take(args.iterations) {
+        let started_at = Instant::now();
+        database.block_receipts((*block_hash).into()).await?;
+        aggregate.record(started_at.elapsed());
+    }
+    let aggregate_elapsed = start.elapsed();
+
+    println!("receipts of {} blocks retrieved {} times", blocks.len(), args.iterations);
+    println!("{}", queries.report("header + receipts", queries_elapsed));
+    println!("{}", aggregate.report("aggregation pipeline", aggregate_elapsed));
+
+    Ok(())
+}
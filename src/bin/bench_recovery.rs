@@ -0,0 +1,57 @@
+use alloy_consensus::TxEip1559;
+use clap::Parser;
+use kakarot_rpc::{models::transaction::recover_signers, test_utils::bench::LatencyRecorder};
+use reth_primitives::{Transaction, TransactionSigned};
+use reth_testing_utils::generators;
+use std::time::Instant;
+
+/// The inputs to the binary.
+#[derive(Parser, Debug)]
+pub struct Args {
+    /// The number of transactions of a batch, e.g. of a block ingested during a catch-up.
+    #[clap(long, default_value_t = 500)]
+    transactions: usize,
+    /// The number of times the signers of the batch are recovered by each strategy.
+    #[clap(long, default_value_t = 100)]
+    iterations: usize,
+}
+
+/// Compares the latency of recovering the signers of a batch of transactions one after the
+/// other, with the recovery on the rayon thread pool.
+fn main() -> eyre::Result<()> {
+    let args = Args::parse();
+
+    let mut rng = generators::rng();
+    let transactions: Vec<_> = (0..args.transactions as u64)
+        .map(|nonce| {
+            let transaction = Transaction::Eip1559(TxEip1559 { nonce, ..Default::default() });
+            generators::sign_tx_with_random_key_pair(&mut rng, transaction)
+        })
+        .collect();
+
+    let mut serial = LatencyRecorder::default();
+    let start = Instant::now();
+    for _ in 0..args.iterations {
+        let started_at = Instant::now();
+        let signers: Vec<_> = transactions.iter().map(TransactionSigned::recover_signer).collect();
+        eyre::ensure!(signers.iter().all(Option::is_some), "failed to recover a signer");
+        serial.record(started_at.elapsed());
+    }
+    let serial_elapsed = start.elapsed();
+
+    let mut parallel = LatencyRecorder::default();
+    let start = Instant::now();
+    for _ in 0..args.iterations {
+        let started_at = Instant::now();
+        let signers = recover_signers(&transactions);
+        eyre::ensure!(signers.iter().all(Option::is_some), "failed to recover a signer");
+        parallel.record(started_at.elapsed());
+    }
+    let parallel_elapsed = start.elapsed();
+
+    println!("signers of {} transactions recovered {} times", args.transactions, args.iterations);
+    println!("{}", serial.report("serial recovery", serial_elapsed));
+    println!("{}", parallel.report("parallel recovery", parallel_elapsed));
+
+    Ok(())
+}
@@ -0,0 +1,8 @@
+use kakarot_rpc::eth_rpc::openrpc::OPENRPC_DOCUMENT;
+
+/// Prints the `OpenRPC` document of the API, generated from the trait definitions of the API, so
+/// that it can be committed and diffed between releases.
+fn main() -> eyre::Result<()> {
+    println!("{}", serde_json::to_string_pretty(&*OPENRPC_DOCUMENT)?);
+    Ok(())
+}
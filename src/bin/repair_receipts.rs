@@ -0,0 +1,43 @@
+use clap::Parser;
+use dotenvy::dotenv;
+use kakarot_rpc::{
+    constants::KAKAROT_RPC_CONFIG,
+    providers::{
+        eth_provider::{database::Database, provider::EthDataProvider},
+        sn_provider::StarknetProvider,
+    },
+};
+use starknet::providers::{jsonrpc::HttpTransport, JsonRpcClient};
+
+/// The inputs to the binary.
+#[derive(Parser, Debug)]
+pub struct Args {
+    /// The first block whose receipts are verified.
+    #[clap(long)]
+    from_block: u64,
+    /// The last block whose receipts are verified.
+    #[clap(long)]
+    to_block: u64,
+    /// Only report the receipts to repair, without rewriting them.
+    #[clap(long, default_value_t = false)]
+    dry_run: bool,
+}
+
+/// Verifies the status and the gas used of the receipts indexed for a block range against the
+/// Starknet chain, and rebuilds the receipts which are missing or wrong. The database and the
+/// Starknet node are read from the environment, as the RPC does.
+#[tokio::main]
+async fn main() -> eyre::Result<()> {
+    dotenv().ok();
+    let args = Args::parse();
+
+    let database =
+        Database::connect(&std::env::var("MONGO_CONNECTION_STRING")?, &std::env::var("MONGO_DATABASE_NAME")?).await?;
+    let starknet_provider = JsonRpcClient::new(HttpTransport::new(KAKAROT_RPC_CONFIG.network_url.clone()));
+    let eth_provider = EthDataProvider::new(database, StarknetProvider::new(starknet_provider));
+
+    let repair = eth_provider.repair_receipts(args.from_block, args.to_block, args.dry_run).await?;
+    println!("{}", serde_json::to_string_pretty(&repair)?);
+
+    Ok(())
+}
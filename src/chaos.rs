@@ -0,0 +1,225 @@
+//! Fault injection, used to test the resilience of the retry and maintenance subsystems.
+//!
+//! The faults are injected with the configured probabilities in the calls to the Starknet
+//! provider, in the writes to the database and in the submissions of the relayers. The
+//! configuration is read from the environment and can be updated at runtime with the
+//! `admin_setFaultInjection` method. Setting a seed makes the sequence of faults deterministic,
+//! which allows to reproduce a run in CI.
+//!
+//! Only compiled with the `chaos` feature, which must never be enabled in production.
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use starknet::providers::{ProviderError, ProviderImplError};
+use std::{
+    any::Any,
+    str::FromStr,
+    sync::{Arc, LazyLock, Mutex, MutexGuard, PoisonError},
+    time::Duration,
+};
+
+/// The fault injector of the node, configured from the environment.
+pub static FAULT_INJECTOR: LazyLock<Arc<FaultInjector>> =
+    LazyLock::new(|| Arc::new(FaultInjector::new(FaultConfig::from_env())));
+
+/// The probabilities and the delays of the injected faults.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase", default)]
+pub struct FaultConfig {
+    /// Probability that a call to the Starknet provider fails without being sent.
+    pub starknet_drop_probability: f64,
+    /// Delay added before each call to the Starknet provider, in milliseconds.
+    pub starknet_delay_ms: u64,
+    /// Probability that a write to the database fails without being sent.
+    pub mongo_write_failure_probability: f64,
+    /// Probability that a transaction relayed by a relayer is submitted a second time.
+    pub relayer_duplicate_probability: f64,
+    /// Seed of the random generator drawing the faults, drawn from the entropy if unset.
+    pub seed: Option<u64>,
+}
+
+impl FaultConfig {
+    /// Reads the configuration from the environment, no fault being injected by default.
+    pub fn from_env() -> Self {
+        fn var<T: FromStr>(key: &str) -> Option<T> {
+            std::env::var(key).ok().and_then(|val| T::from_str(&val).ok())
+        }
+
+        Self {
+            starknet_drop_probability: var("CHAOS_STARKNET_DROP_PROBABILITY").unwrap_or_default(),
+            starknet_delay_ms: var("CHAOS_STARKNET_DELAY_MS").unwrap_or_default(),
+            mongo_write_failure_probability: var("CHAOS_MONGO_WRITE_FAILURE_PROBABILITY").unwrap_or_default(),
+            relayer_duplicate_probability: var("CHAOS_RELAYER_DUPLICATE_PROBABILITY").unwrap_or_default(),
+            seed: var("CHAOS_SEED"),
+        }
+    }
+
+    /// Checks that the probabilities are between 0 and 1.
+    pub fn validate(&self) -> Result<(), FaultError> {
+        [self.starknet_drop_probability, self.mongo_write_failure_probability, self.relayer_duplicate_probability]
+            .into_iter()
+            .find(|probability| !(0.0..=1.0).contains(probability))
+            .map_or(Ok(()), |probability| Err(FaultError::InvalidProbability(probability)))
+    }
+}
+
+/// The errors raised by the fault injector.
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum FaultError {
+    /// The call to the Starknet provider was dropped.
+    #[error("fault injected: starknet call dropped")]
+    StarknetCallDropped,
+    /// The write to the database failed.
+    #[error("fault injected: database write failed")]
+    DatabaseWrite,
+    /// A probability of the configuration isn't between 0 and 1.
+    #[error("invalid fault probability {0}, expected a value between 0 and 1")]
+    InvalidProbability(f64),
+}
+
+impl ProviderImplError for FaultError {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl From<FaultError> for ProviderError {
+    fn from(err: FaultError) -> Self {
+        Self::Other(Box::new(err))
+    }
+}
+
+#[cfg(feature = "mongo")]
+impl From<FaultError> for mongodb::error::Error {
+    fn from(err: FaultError) -> Self {
+        Self::custom(err)
+    }
+}
+
+/// Draws the faults injected in the node from its configuration.
+#[derive(Debug)]
+pub struct FaultInjector {
+    config: Mutex<FaultConfig>,
+    rng: Mutex<StdRng>,
+}
+
+impl FaultInjector {
+    /// Creates a new fault injector. The invalid probabilities are ignored.
+    pub fn new(config: FaultConfig) -> Self {
+        let config = if let Err(err) = config.validate() {
+            tracing::warn!(target: "chaos", %err, "ignoring the fault injection configuration");
+            FaultConfig::default()
+        } else {
+            config
+        };
+        Self { rng: Mutex::new(rng(config.seed)), config: Mutex::new(config) }
+    }
+
+    /// Returns the current configuration.
+    pub fn config(&self) -> FaultConfig {
+        *lock(&self.config)
+    }
+
+    /// Replaces the configuration, reseeding the random generator if a seed is set.
+    pub fn set_config(&self, config: FaultConfig) -> Result<(), FaultError> {
+        config.validate()?;
+        if config.seed.is_some() {
+            *lock(&self.rng) = rng(config.seed);
+        }
+        *lock(&self.config) = config;
+        tracing::warn!(target: "chaos", ?config, "updated the fault injection configuration");
+        Ok(())
+    }
+
+    /// Delays the call to the Starknet provider, then drops it with the configured probability.
+    pub async fn starknet_call(&self) -> Result<(), FaultError> {
+        let config = self.config();
+        if config.starknet_delay_ms > 0 {
+            tokio::time::sleep(Duration::from_millis(config.starknet_delay_ms)).await;
+        }
+        if self.draw(config.starknet_drop_probability, "starknet_drop") {
+            return Err(FaultError::StarknetCallDropped);
+        }
+        Ok(())
+    }
+
+    /// Fails the write to the database with the configured probability.
+    pub fn database_write(&self) -> Result<(), FaultError> {
+        if self.draw(self.config().mongo_write_failure_probability, "mongo_write_failure") {
+            return Err(FaultError::DatabaseWrite);
+        }
+        Ok(())
+    }
+
+    /// Returns true if the relayed transaction must be submitted a second time.
+    pub fn duplicate_submission(&self) -> bool {
+        self.draw(self.config().relayer_duplicate_probability, "relayer_duplicate")
+    }
+
+    fn draw(&self, probability: f64, fault: &'static str) -> bool {
+        let injected = probability > 0. && lock(&self.rng).gen_bool(probability.min(1.));
+        if injected {
+            tracing::warn!(target: "chaos", monotonic_counter.injected_faults = 1, fault, "injected fault");
+        }
+        injected
+    }
+}
+
+fn rng(seed: Option<u64>) -> StdRng {
+    seed.map_or_else(StdRng::from_entropy, StdRng::seed_from_u64)
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fault_injector_is_deterministic() {
+        // Given
+        let config = FaultConfig { mongo_write_failure_probability: 0.5, seed: Some(42), ..Default::default() };
+        let first = FaultInjector::new(config);
+        let second = FaultInjector::new(config);
+
+        // When
+        let draws = |injector: &FaultInjector| (0..32).map(|_| injector.database_write().is_err()).collect::<Vec<_>>();
+        let first_draws = draws(&first);
+
+        // Then
+        assert_eq!(first_draws, draws(&second));
+        assert!(first_draws.contains(&true));
+        assert!(first_draws.contains(&false));
+    }
+
+    #[tokio::test]
+    async fn test_fault_injector_disabled() {
+        // Given
+        let injector = FaultInjector::new(FaultConfig::default());
+
+        // When
+        // Then
+        for _ in 0..32 {
+            assert!(injector.starknet_call().await.is_ok());
+            assert!(injector.database_write().is_ok());
+            assert!(!injector.duplicate_submission());
+        }
+    }
+
+    #[test]
+    fn test_fault_injector_rejects_invalid_probability() {
+        // Given
+        let injector = FaultInjector::new(FaultConfig::default());
+        let config = FaultConfig { starknet_drop_probability: 1.5, ..Default::default() };
+
+        // When
+        let result = injector.set_config(config);
+
+        // Then
+        assert_eq!(result, Err(FaultError::InvalidProbability(1.5)));
+        assert_eq!(injector.config(), FaultConfig::default());
+    }
+}
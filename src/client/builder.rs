@@ -0,0 +1,105 @@
+use super::EthClient;
+use crate::providers::eth_provider::database::{archive::ArchiveConfig, Database, ReadConfig};
+use eyre::eyre;
+use reth_transaction_pool::PoolConfig;
+use starknet::providers::Provider;
+
+/// Builder for an [`EthClient`].
+///
+/// Allows to embed the Kakarot Ethereum provider and mempool in another
+/// application without running the RPC server.
+#[derive(Debug)]
+pub struct EthClientBuilder<SP> {
+    starknet_provider: SP,
+    database: Option<Database>,
+    pool_config: PoolConfig,
+}
+
+impl<SP> EthClientBuilder<SP>
+where
+    SP: Provider + Clone + Sync + Send,
+{
+    /// Creates a new builder using the provided Starknet provider.
+    pub fn new(starknet_provider: SP) -> Self {
+        Self { starknet_provider, database: None, pool_config: PoolConfig::default() }
+    }
+
+    /// Sets the database of the client.
+    #[must_use]
+    pub fn with_database(mut self, database: Database) -> Self {
+        self.database = Some(database);
+        self
+    }
+
+    /// Connects to the `MongoDB` database `database_name`, creates its indexes and uses it as the database
+    /// of the client.
+    pub async fn with_mongo(self, connection_string: &str, database_name: &str) -> eyre::Result<Self> {
+        self.with_mongo_read_config(connection_string, database_name, &ReadConfig::default()).await
+    }
+
+    /// Connects to the `MongoDB` database `database_name`, sending the reads according to the read configuration,
+    /// creates its indexes and uses it as the database of the client.
+    pub async fn with_mongo_read_config(
+        self,
+        connection_string: &str,
+        database_name: &str,
+        read_config: &ReadConfig,
+    ) -> eyre::Result<Self> {
+        let database = Database::connect_with_read_config(connection_string, database_name, read_config).await?;
+        database.create_indexes().await?;
+        Ok(self.with_database(database))
+    }
+
+    /// Connects to the `MongoDB` archive database, creates its indexes and uses it to store the logs and receipts
+    /// out of the hot window of the database of the client. Must be called after the database is set.
+    pub async fn with_mongo_archive(
+        mut self,
+        connection_string: &str,
+        archive_config: &ArchiveConfig,
+    ) -> eyre::Result<Self> {
+        let database = self.database.take().ok_or_else(|| eyre!("missing database for the archive"))?;
+        let connection_string = archive_config.connection_string.as_deref().unwrap_or(connection_string);
+        let archive = Database::connect(connection_string, &archive_config.database_name).await?;
+        archive.create_indexes().await?;
+        Ok(self.with_database(database.with_archive(archive)))
+    }
+
+    /// Sets the configuration of the mempool.
+    #[must_use]
+    pub fn with_pool_config(mut self, pool_config: PoolConfig) -> Self {
+        self.pool_config = pool_config;
+        self
+    }
+
+    /// Builds the [`EthClient`].
+    pub fn build(self) -> eyre::Result<EthClient<SP>> {
+        let database = self.database.ok_or_else(|| eyre!("missing database for the eth client"))?;
+        Ok(EthClient::new(self.starknet_provider, self.pool_config, database))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{client::DynKakarotTransactions, providers::eth_provider::provider::DynEthereumProvider};
+    use starknet::providers::{jsonrpc::HttpTransport, JsonRpcClient};
+    use url::Url;
+
+    #[test]
+    fn test_build_without_database() {
+        // Given
+        let provider = JsonRpcClient::new(HttpTransport::new(Url::parse("http://0.0.0.0:5050").unwrap()));
+
+        // When
+        let client = EthClientBuilder::new(provider).build();
+
+        // Then
+        assert!(client.is_err());
+    }
+
+    #[test]
+    fn test_traits_are_object_safe() {
+        fn assert_object_safe(_: Option<DynEthereumProvider>, _: Option<DynKakarotTransactions>) {}
+        assert_object_safe(None, None);
+    }
+}
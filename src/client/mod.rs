@@ -1,51 +1,98 @@
+pub mod builder;
+
+pub use builder::EthClientBuilder;
+
 use crate::{
-    constants::{ETH_CHAIN_ID, KKRT_BLOCK_GAS_LIMIT},
+    constants::ETH_CHAIN_ID,
     pool::{
-        mempool::{KakarotPool, TransactionOrdering},
+        constants::{DEPLOYER_ALLOWLIST, STRICT_EIP155},
+        mempool::{KakarotPool, TransactionOrdering, Validator},
         validate::KakarotTransactionValidatorBuilder,
     },
     providers::{
         eth_provider::{
             database::{
+                ethereum::EthereumTransactionStore,
                 filter,
                 filter::EthDatabaseFilterBuilder,
-                types::transaction::{ExtendedTransaction, StoredEthStarknetTransactionHash},
+                relayer::RelayerNonceStore,
+                types::{
+                    header::ExtendedBlock,
+                    receipt::ExtendedTxReceipt,
+                    transaction::{
+                        DropReason, DroppedTransaction, ExtendedTransaction, PoolEvent, PoolEventKind, RawTransaction,
+                        StoredEthStarknetTransactionHash,
+                    },
+                },
                 Database,
             },
             error::SignatureError,
+            fallback::{submission_receipt, SubmissionStatus},
             provider::{EthApiResult, EthDataProvider},
-            TransactionProvider, TxPoolProvider,
+            starknet::kakarot_core::MAX_FELTS_IN_CALLDATA,
+            BlockProvider, ReceiptProvider, TransactionProvider, TxPoolProvider,
         },
         sn_provider::StarknetProvider,
     },
 };
-use alloy_eips::eip2718::Encodable2718;
-use alloy_primitives::{Address, Bytes, B256};
+use alloy_eips::{eip2718::Encodable2718, BlockId, BlockNumberOrTag};
+use alloy_primitives::{Address, Bytes, B256, U256};
 use alloy_rlp::Decodable;
+use alloy_rpc_types::{Block, BlockTransactions, Header, Index};
 use alloy_rpc_types_txpool::TxpoolContent;
 use alloy_serde::WithOtherFields;
 use async_trait::async_trait;
+use auto_impl::auto_impl;
 use reth_chainspec::ChainSpec;
-use reth_primitives::{TransactionSigned, TransactionSignedEcRecovered};
+use reth_primitives::{InvalidTransactionError, TransactionSigned, TransactionSignedEcRecovered};
 use reth_rpc::eth::EthTxBuilder;
 use reth_rpc_eth_types::TransactionSource;
 use reth_transaction_pool::{
-    blobstore::NoopBlobStore, AllPoolTransactions, EthPooledTransaction, PoolConfig, PoolTransaction,
-    TransactionOrigin, TransactionPool,
+    blobstore::NoopBlobStore,
+    error::{InvalidPoolTransactionError, PoolErrorKind},
+    AllPoolTransactions, EthPooledTransaction, PoolConfig, PoolTransaction, TransactionOrigin, TransactionPool,
+    ValidPoolTransaction,
 };
 use starknet::providers::Provider;
-use std::{collections::BTreeMap, sync::Arc};
+use std::{
+    collections::{BTreeMap, HashSet},
+    sync::Arc,
+};
+
+/// A shareable, type-erased [`KakarotTransactions`].
+pub type DynKakarotTransactions = Arc<dyn KakarotTransactions + Send + Sync>;
 
 #[async_trait]
+#[auto_impl(Arc, &)]
 pub trait KakarotTransactions {
     /// Send a raw transaction to the network and returns the transactions hash.
     async fn send_raw_transaction(&self, transaction: Bytes) -> EthApiResult<B256>;
 }
 
 #[async_trait]
+#[auto_impl(Arc, &)]
 pub trait TransactionHashProvider {
     /// Returns the transaction by hash.
     async fn transaction_by_hash(&self, hash: B256) -> EthApiResult<Option<ExtendedTransaction>>;
+
+    /// Returns the receipt by hash of the transaction.
+    async fn transaction_receipt(&self, hash: B256) -> EthApiResult<Option<ExtendedTxReceipt>>;
+}
+
+/// Provides access to the pending block: the block indexed from the pending Starknet block,
+/// followed by the best transactions of the pool which it doesn't include yet.
+#[async_trait]
+#[auto_impl(Arc, &)]
+pub trait PendingBlockProvider {
+    /// Returns the pending block. Returns None if no pending block is indexed and the pool is
+    /// empty.
+    async fn pending_block(&self, full: bool) -> EthApiResult<Option<ExtendedBlock>>;
+
+    /// Returns the transaction at the given index in the pending block.
+    async fn pending_transaction_by_index(&self, index: Index) -> EthApiResult<Option<ExtendedTransaction>>;
+
+    /// Returns the number of transactions in the pending block.
+    async fn pending_block_transaction_count(&self) -> EthApiResult<Option<U256>>;
 }
 
 /// Provides a wrapper structure around the Ethereum Provider
@@ -54,6 +101,7 @@ pub trait TransactionHashProvider {
 pub struct EthClient<SP: Provider + Send + Sync> {
     eth_provider: EthDataProvider<SP>,
     pool: Arc<KakarotPool<EthDataProvider<SP>>>,
+    validator: Validator<EthDataProvider<SP>>,
 }
 
 impl<SP> EthClient<SP>
@@ -72,19 +120,22 @@ where
 
         let validator = KakarotTransactionValidatorBuilder::new(&Arc::new(ChainSpec {
             chain: (*ETH_CHAIN_ID).into(),
-            max_gas_limit: KKRT_BLOCK_GAS_LIMIT,
+            max_gas_limit: pool_config.gas_limit,
             ..Default::default()
         }))
+        .with_strict_eip155(*STRICT_EIP155)
+        .with_deployer_allowlist(DEPLOYER_ALLOWLIST.clone())
+        .with_max_felts_in_calldata((!cfg!(feature = "hive")).then(|| *MAX_FELTS_IN_CALLDATA))
         .build::<_, EthPooledTransaction>(eth_provider.clone());
 
         let pool = Arc::new(KakarotPool::new(
-            validator,
+            validator.clone(),
             TransactionOrdering::default(),
             NoopBlobStore::default(),
             pool_config,
         ));
 
-        Self { eth_provider, pool }
+        Self { eth_provider, pool, validator }
     }
 
     /// Returns a clone of the [`EthDataProvider`]
@@ -96,22 +147,67 @@ where
     pub fn mempool(&self) -> Arc<KakarotPool<EthDataProvider<SP>>> {
         self.pool.clone()
     }
-}
 
-#[async_trait]
-impl<SP> KakarotTransactions for EthClient<SP>
-where
-    SP: Provider + Clone + Sync + Send,
-{
-    async fn send_raw_transaction(&self, transaction: Bytes) -> EthApiResult<B256> {
-        // Decode the transaction data
-        let transaction_signed = TransactionSigned::decode(&mut transaction.0.as_ref())?;
+    /// Returns the block gas limit enforced by the mempool.
+    pub fn block_gas_limit(&self) -> u64 {
+        self.validator.block_gas_limit()
+    }
 
-        // Recover the signer from the transaction
-        let signer = transaction_signed.recover_signer().ok_or(SignatureError::Recovery)?;
+    /// Updates the block gas limit enforced by the mempool.
+    pub fn set_block_gas_limit(&self, block_gas_limit: u64) {
+        self.validator.set_block_gas_limit(block_gas_limit);
+    }
+
+    /// Returns the senders allowed to deploy contracts, anyone if unset.
+    pub fn deployer_allowlist(&self) -> Option<HashSet<Address>> {
+        self.validator.deployer_allowlist()
+    }
+
+    /// Updates the senders allowed to deploy contracts, unset to allow anyone. The transactions
+    /// already in the mempool are kept.
+    pub fn set_deployer_allowlist(&self, deployer_allowlist: Option<HashSet<Address>>) {
+        self.validator.set_deployer_allowlist(deployer_allowlist);
+    }
+
+    /// Returns true if the transaction is already known to the node, either because it
+    /// is in the pool, has been relayed to Starknet or has been mined.
+    async fn is_known_transaction(&self, hash: &B256) -> EthApiResult<bool> {
+        if self.pool.contains(hash) {
+            return Ok(true);
+        }
+
+        // Transactions are removed from the pool once picked up by a relayer
+        let filter =
+            EthDatabaseFilterBuilder::<filter::EthStarknetTransactionHash>::default().with_tx_hash(hash).build();
+        if self.eth_provider.database().get_one::<StoredEthStarknetTransactionHash>(filter, None).await?.is_some() {
+            return Ok(true);
+        }
+
+        // The nonce is leased before the submission, the hash mapping is only stored after it
+        if self.eth_provider.database().nonce_lease_of(hash).await?.is_some() {
+            return Ok(true);
+        }
+
+        Ok(self.eth_provider.transaction_by_hash(*hash).await?.is_some())
+    }
+
+    /// Adds a transaction whose signer was already recovered to the pool, the raw bytes being
+    /// stored once the transaction is accepted. Returns the hash of a transaction already known.
+    pub(crate) async fn add_recovered_transaction(
+        &self,
+        transaction_signed: TransactionSigned,
+        signer: Address,
+        transaction: Bytes,
+    ) -> EthApiResult<B256> {
         let hash = transaction_signed.hash();
         let to = transaction_signed.to();
 
+        // Resubmitting a known transaction is not an error, return its hash
+        if self.is_known_transaction(&hash).await? {
+            tracing::debug!(?hash, "transaction already known");
+            return Ok(hash);
+        }
+
         let transaction_signed_ec_recovered =
             TransactionSignedEcRecovered::from_signed_transaction(transaction_signed.clone(), signer);
 
@@ -124,13 +220,76 @@ where
         self.eth_provider.deploy_evm_transaction_signer(signer).await?;
 
         // Add the transaction to the pool and wait for it to be picked up by a relayer
-        let hash = self
-            .pool
-            .add_transaction(TransactionOrigin::Local, pool_transaction)
-            .await
-            .inspect_err(|err| tracing::warn!(?err, ?hash, ?to, from = ?signer))?;
+        match self.pool.add_transaction(TransactionOrigin::Local, pool_transaction).await {
+            Ok(hash) => {
+                self.record_raw_transaction(RawTransaction { hash, bytes: transaction }).await;
+                Ok(hash)
+            }
+            // The transaction was concurrently added to the pool
+            Err(err) if matches!(err.kind, PoolErrorKind::AlreadyImported) => Ok(hash),
+            Err(err) => {
+                tracing::warn!(?err, ?hash, ?to, from = ?signer);
+                self.record_dropped_transaction(DroppedTransaction::new(hash, drop_reason(&err.kind), err.to_string()))
+                    .await;
+                Err(err.into())
+            }
+        }
+    }
+
+    /// Stores the exact bytes of a transaction submitted to the node, served by
+    /// `eth_getRawTransaction*` instead of an encoding rebuilt from the fields of the transaction.
+    async fn record_raw_transaction(&self, raw: RawTransaction) {
+        let hash = raw.hash;
+        if let Err(err) = self.eth_provider.database().upsert_raw_transaction(raw).await {
+            tracing::error!(?err, ?hash, "failed to store raw transaction");
+        }
+    }
+
+    /// Stores the reason why a transaction was dropped, so that it can be queried by its sender.
+    pub(crate) async fn record_dropped_transaction(&self, dropped: DroppedTransaction) {
+        let hash = dropped.hash;
+        let kind = PoolEventKind::Dropped { reason: dropped.reason, message: dropped.message.clone() };
+        if let Err(err) = self.eth_provider.database().upsert_dropped_transaction(dropped).await {
+            tracing::error!(?err, ?hash, "failed to store dropped transaction");
+        }
+        self.record_pool_event(PoolEvent::new(hash, kind)).await;
+    }
+
+    /// Stores an event of the lifecycle of a transaction in the pool, for its timeline.
+    pub(crate) async fn record_pool_event(&self, event: PoolEvent) {
+        let hash = event.hash;
+        if let Err(err) = self.eth_provider.database().insert_pool_event(event).await {
+            tracing::error!(?err, ?hash, "failed to store pool event");
+        }
+    }
+}
+
+/// Returns the reason for which the pool rejected a transaction.
+const fn drop_reason(kind: &PoolErrorKind) -> DropReason {
+    match kind {
+        PoolErrorKind::ReplacementUnderpriced
+        | PoolErrorKind::FeeCapBelowMinimumProtocolFeeCap(_)
+        | PoolErrorKind::InvalidTransaction(
+            InvalidPoolTransactionError::Underpriced
+            | InvalidPoolTransactionError::Consensus(InvalidTransactionError::FeeCapTooLow),
+        ) => DropReason::Underpriced,
+        _ => DropReason::Invalid,
+    }
+}
+
+#[async_trait]
+impl<SP> KakarotTransactions for EthClient<SP>
+where
+    SP: Provider + Clone + Sync + Send,
+{
+    async fn send_raw_transaction(&self, transaction: Bytes) -> EthApiResult<B256> {
+        // Decode the transaction data
+        let transaction_signed = TransactionSigned::decode(&mut transaction.0.as_ref())?;
 
-        Ok(hash)
+        // Recover the signer from the transaction
+        let signer = transaction_signed.recover_signer().ok_or(SignatureError::Recovery)?;
+
+        self.add_recovered_transaction(transaction_signed, signer, transaction).await
     }
 }
 
@@ -174,25 +333,130 @@ where
     }
 }
 
+#[async_trait]
+impl<SP> PendingBlockProvider for EthClient<SP>
+where
+    SP: starknet::providers::Provider + Send + Sync,
+{
+    async fn pending_block(&self, full: bool) -> EthApiResult<Option<ExtendedBlock>> {
+        let indexed = self.eth_provider.block_by_number(BlockNumberOrTag::Pending, full).await?;
+        let included = indexed.iter().flat_map(|block| block.transactions.hashes()).collect::<HashSet<_>>();
+        let pooled = self
+            .best_pending_transactions(&included)
+            .enumerate()
+            .map(|(index, transaction)| pending_transaction(&transaction.transaction, included.len() + index))
+            .collect::<Vec<_>>();
+
+        let mut block = match indexed {
+            Some(block) => block,
+            None if pooled.is_empty() => return Ok(None),
+            // Without indexed pending block, the pending block is built on top of the latest block
+            None => {
+                let Some(latest) = self.eth_provider.header(&BlockId::latest()).await? else {
+                    return Ok(None);
+                };
+                WithOtherFields::new(Block {
+                    header: Header {
+                        number: latest.number + 1,
+                        parent_hash: latest.hash,
+                        timestamp: latest.timestamp,
+                        miner: latest.miner,
+                        gas_limit: latest.gas_limit,
+                        base_fee_per_gas: latest.base_fee_per_gas,
+                        ..Default::default()
+                    },
+                    transactions: if full {
+                        BlockTransactions::Full(Vec::new())
+                    } else {
+                        BlockTransactions::Hashes(Vec::new())
+                    },
+                    ..Default::default()
+                })
+            }
+        };
+
+        match &mut block.transactions {
+            BlockTransactions::Full(transactions) => transactions.extend(pooled),
+            BlockTransactions::Hashes(hashes) => hashes.extend(pooled.iter().map(|transaction| transaction.hash)),
+            BlockTransactions::Uncle => {}
+        }
+        Ok(Some(block))
+    }
+
+    async fn pending_transaction_by_index(&self, index: Index) -> EthApiResult<Option<ExtendedTransaction>> {
+        let index = usize::from(index);
+        let included = self.indexed_pending_transactions().await?;
+        if index < included.len() {
+            return self
+                .eth_provider
+                .transaction_by_block_number_and_index(BlockNumberOrTag::Pending, index.into())
+                .await;
+        }
+
+        Ok(self
+            .best_pending_transactions(&included)
+            .nth(index - included.len())
+            .map(|transaction| pending_transaction(&transaction.transaction, index)))
+    }
+
+    async fn pending_block_transaction_count(&self) -> EthApiResult<Option<U256>> {
+        let included = self.indexed_pending_transactions().await?;
+        let count = included.len() + self.best_pending_transactions(&included).count();
+        Ok((count > 0).then(|| U256::from(count)))
+    }
+}
+
+impl<SP> EthClient<SP>
+where
+    SP: starknet::providers::Provider + Send + Sync,
+{
+    /// Returns the hashes of the transactions of the indexed pending block.
+    async fn indexed_pending_transactions(&self) -> EthApiResult<HashSet<B256>> {
+        let block = self.eth_provider.block_by_number(BlockNumberOrTag::Pending, false).await?;
+        Ok(block.iter().flat_map(|block| block.transactions.hashes()).collect())
+    }
+
+    /// Returns the best transactions of the pool, ordered by priority, which aren't part of the
+    /// indexed pending block.
+    fn best_pending_transactions<'a>(
+        &self,
+        included: &'a HashSet<B256>,
+    ) -> impl Iterator<Item = Arc<ValidPoolTransaction<EthPooledTransaction>>> + 'a {
+        self.pool.best_transactions().filter(move |transaction| !included.contains(transaction.hash()))
+    }
+}
+
+/// Converts a transaction of the pool to the transaction at the given index of the pending block.
+fn pending_transaction(transaction: &EthPooledTransaction, index: usize) -> ExtendedTransaction {
+    let mut transaction = WithOtherFields::new(
+        TransactionSource::Pool(transaction.transaction().clone()).into_transaction(&EthTxBuilder {}),
+    );
+    transaction.transaction_index = Some(index as u64);
+    transaction
+}
+
 #[async_trait]
 impl<SP> TransactionHashProvider for EthClient<SP>
 where
     SP: starknet::providers::Provider + Send + Sync,
 {
     async fn transaction_by_hash(&self, hash: B256) -> EthApiResult<Option<ExtendedTransaction>> {
-        // Try to get the information from:
-        // 1. The pool if the transaction is in the pool.
-        // 2. The Ethereum provider if the transaction is not in the pool.
-        let mut tx = self
-            .pool
-            .get(&hash)
-            .map(|transaction| {
-                WithOtherFields::new(
+        // Try to get the information from, the cheapest lookups first:
+        // 1. The pool if the transaction is in the pool, waiting to be relayed.
+        // 2. The Ethereum provider if the transaction is not in the pool: the database if the
+        //    transaction is indexed, otherwise Starknet if the transaction was relayed.
+        let pooled = self.pool.get(&hash);
+        let mut tx = match &pooled {
+            Some(transaction) => {
+                let mut transaction = WithOtherFields::new(
                     TransactionSource::Pool(transaction.transaction.transaction().clone())
                         .into_transaction(&EthTxBuilder {}),
-                )
-            })
-            .or(self.eth_provider.transaction_by_hash(hash).await?);
+                );
+                SubmissionStatus::Pending.set(&mut transaction);
+                Some(transaction)
+            }
+            None => self.eth_provider.transaction_by_hash(hash).await?,
+        };
 
         if let Some(ref mut transaction) = tx {
             // Fetch the Starknet transaction hash if it exists.
@@ -205,6 +469,10 @@ where
 
             // Add the Starknet transaction hash to the transaction fields.
             if let Some(hash_mapping) = hash_mapping {
+                // The relayed transactions stay in the pool until they are mined
+                if pooled.is_some() {
+                    SubmissionStatus::Relayed.set(transaction);
+                }
                 transaction.other.insert(
                     "starknet_transaction_hash".to_string(),
                     serde_json::Value::String(hash_mapping.hashes.starknet_hash.to_fixed_hex_string()),
@@ -214,4 +482,23 @@ where
 
         Ok(tx)
     }
+
+    async fn transaction_receipt(&self, hash: B256) -> EthApiResult<Option<ExtendedTxReceipt>> {
+        // The receipt is served by the Ethereum provider once the transaction is executed: from the
+        // database if indexed, otherwise from Starknet if relayed by the node
+        if let Some(receipt) = self.eth_provider.transaction_receipt(hash).await? {
+            return Ok(Some(receipt));
+        }
+
+        // Until then, a transaction submitted to the node is served with its submission status
+        let Some(pooled) = self.pool.get(&hash) else {
+            return Ok(None);
+        };
+        let status = if self.eth_provider.database().starknet_transaction_hash(&hash).await?.is_some() {
+            SubmissionStatus::Relayed
+        } else {
+            SubmissionStatus::Pending
+        };
+        Ok(Some(submission_receipt(pooled.transaction.transaction(), status)))
+    }
 }
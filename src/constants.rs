@@ -4,7 +4,7 @@ use starknet::{
     core::types::{Felt, NonZeroFelt},
     providers::{jsonrpc::HttpTransport, JsonRpcClient, Provider},
 };
-use std::sync::LazyLock;
+use std::{sync::LazyLock, time::Duration};
 
 /// The max chain id allowed by [Metamask](https://gist.github.com/rekmarks/a47bd5f2525936c4b8eee31a16345553)
 pub static MAX_CHAIN_ID: u64 = (2u64.pow(53) - 39) / 2;
@@ -30,5 +30,55 @@ pub static KAKAROT_RPC_CONFIG: LazyLock<KakarotRpcConfig> =
 pub static RPC_CONFIG: LazyLock<RPCConfig> =
     LazyLock::new(|| RPCConfig::from_env().expect("failed to load RPC config"));
 
-/// The gas limit for Kakarot blocks.
+/// If set, the admin namespace, which allows to operate the relayers of the node, is served.
+pub static ADMIN_API_ENABLED: LazyLock<bool> =
+    LazyLock::new(|| std::env::var("ADMIN_API_ENABLED").ok().and_then(|val| val.parse::<bool>().ok()).unwrap_or(false));
+
+/// If set, with the `--dry-run` argument or the `DRY_RUN` environment variable, the node validates,
+/// orders and prepares the transactions of the mempool, but doesn't submit them to Starknet. An
+/// invalid `DRY_RUN` is rejected rather than read as false, which would submit the transactions.
+pub static DRY_RUN: LazyLock<bool> = LazyLock::new(|| {
+    std::env::args().any(|arg| arg == "--dry-run")
+        || std::env::var("DRY_RUN").is_ok_and(|val| val.parse::<bool>().expect("invalid DRY_RUN, use true or false"))
+});
+
+/// If set, with the `--replay` argument or the `REPLAY_MODE` environment variable, the node
+/// serves the database it is connected to (e.g. a snapshot exported from another node) without
+/// accessing Starknet, and rejects the methods which write or read Starknet or the mempool. An
+/// invalid `REPLAY_MODE` is rejected rather than read as false, which would access Starknet.
+pub static REPLAY_MODE: LazyLock<bool> = LazyLock::new(|| {
+    std::env::args().any(|arg| arg == "--replay")
+        || std::env::var("REPLAY_MODE")
+            .is_ok_and(|val| val.parse::<bool>().expect("invalid REPLAY_MODE, use true or false"))
+});
+
+/// The default gas limit for Kakarot blocks, used if the block gas limit of Kakarot core can't
+/// be fetched.
 pub const KKRT_BLOCK_GAS_LIMIT: u64 = 7_000_000;
+
+/// The interval at which the block gas limit of Kakarot core is refreshed.
+pub static BLOCK_GAS_LIMIT_REFRESH_INTERVAL: LazyLock<Duration> = LazyLock::new(|| {
+    Duration::from_secs(
+        std::env::var("BLOCK_GAS_LIMIT_REFRESH_INTERVAL_SECS").ok().and_then(|val| val.parse().ok()).unwrap_or(60),
+    )
+});
+
+/// If set, the transactions and receipts served from Starknet while missing from the database
+/// are stored, until the indexer overwrites them.
+pub static STARKNET_FALLBACK_PERSIST: LazyLock<bool> = LazyLock::new(|| {
+    std::env::var("STARKNET_FALLBACK_PERSIST").ok().and_then(|val| val.parse::<bool>().ok()).unwrap_or(false)
+});
+
+/// The interval at which the Starknet pending block is polled for the transactions relayed by
+/// the node, whose receipts are served before the indexer stores them.
+pub static PENDING_BLOCK_POLL_INTERVAL: LazyLock<Duration> = LazyLock::new(|| {
+    Duration::from_millis(
+        std::env::var("PENDING_BLOCK_POLL_INTERVAL_MS").ok().and_then(|val| val.parse().ok()).unwrap_or(1000),
+    )
+});
+
+/// The duration for which a relayed transaction and its receipt built from the pending block are
+/// kept, which should cover the delay of the indexer.
+pub static PENDING_RECEIPTS_TTL: LazyLock<Duration> = LazyLock::new(|| {
+    Duration::from_secs(std::env::var("PENDING_RECEIPTS_TTL_SECS").ok().and_then(|val| val.parse().ok()).unwrap_or(120))
+});
@@ -0,0 +1,264 @@
+//! Coordination of the instances of the RPC sharing a database.
+//!
+//! Several instances can serve the reads behind a load balancer, but the background writers
+//! (the relayers, the reconciliation of the relayed transactions, the probe and the archival)
+//! must only run on one of them, otherwise the transactions would be submitted twice and the
+//! writes would race. The instances elect the writer through a lease in the database: the
+//! elected instance renews its lease while the other ones wait for it to expire.
+//!
+//! The writers of an instance can't be stopped once started, so that an instance which can't
+//! renew its lease exits before the lease expires, to be restarted as a follower by its
+//! supervisor, rather than writing along with the newly elected instance. The expiry of the leases
+//! is measured by the clock of the database, so that the clocks of the instances don't need to be
+//! synchronized. The followers reject the writes (e.g. `eth_sendRawTransaction`), which would
+//! never be relayed from their mempool, see [`FollowerLayer`](crate::eth_rpc::middleware::FollowerLayer).
+
+#[cfg(feature = "mongo")]
+use crate::providers::eth_provider::database::{instance::InstanceLeaseStore, Database};
+#[cfg(feature = "mongo")]
+use std::{future::Future, time::Instant};
+use std::{
+    str::FromStr,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+/// The role of the instance running the background writers.
+pub const WRITER_ROLE: &str = "writer";
+
+/// Configuration of the coordination of the instances.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CoordinationConfig {
+    /// Whether the instances elect the writer. If not, the instance runs the writers.
+    pub enabled: bool,
+    /// The id of the instance. Defaults to the hostname and the process id.
+    pub instance_id: String,
+    /// The duration of a lease, renewed every third of it.
+    pub lease_ttl: Duration,
+}
+
+impl Default for CoordinationConfig {
+    fn default() -> Self {
+        let hostname = std::env::var("HOSTNAME").unwrap_or_else(|_| "kakarot-rpc".to_string());
+        let started_at = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_millis();
+        Self {
+            enabled: false,
+            instance_id: format!("{hostname}-{}-{started_at}", std::process::id()),
+            lease_ttl: Duration::from_secs(30),
+        }
+    }
+}
+
+impl CoordinationConfig {
+    /// Reads the configuration from the environment.
+    pub fn from_env() -> Self {
+        fn var<T: FromStr>(key: &str) -> Option<T> {
+            std::env::var(key).ok().and_then(|val| T::from_str(&val).ok())
+        }
+
+        let default = Self::default();
+        Self {
+            enabled: var("INSTANCE_COORDINATION_ENABLED").unwrap_or(default.enabled),
+            instance_id: var::<String>("INSTANCE_ID").filter(|id| !id.is_empty()).unwrap_or(default.instance_id),
+            lease_ttl: var("INSTANCE_LEASE_TTL_SECS")
+                .filter(|ttl| *ttl > 0)
+                .map_or(default.lease_ttl, Duration::from_secs),
+        }
+    }
+}
+
+/// Whether the instance runs the background writers, shared with the tasks and the middlewares
+/// which must only act on the elected instance.
+#[derive(Debug, Clone)]
+pub struct Leadership(Arc<AtomicBool>);
+
+impl Default for Leadership {
+    fn default() -> Self {
+        Self::writer()
+    }
+}
+
+impl Leadership {
+    /// The leadership of an instance running the writers, e.g. because the instances aren't
+    /// coordinated.
+    pub fn writer() -> Self {
+        Self(Arc::new(AtomicBool::new(true)))
+    }
+
+    /// The leadership of an instance waiting for its election.
+    pub fn follower() -> Self {
+        Self(Arc::new(AtomicBool::new(false)))
+    }
+
+    /// Returns true if the instance runs the writers.
+    pub fn is_writer(&self) -> bool {
+        self.0.load(Ordering::Acquire)
+    }
+
+    fn set_writer(&self, writer: bool) {
+        self.0.store(writer, Ordering::Release);
+    }
+}
+
+/// Elects the instance running the writers among the instances sharing the database.
+#[cfg(feature = "mongo")]
+#[derive(Debug, Clone)]
+pub struct InstanceCoordinator {
+    database: Database,
+    config: CoordinationConfig,
+    leadership: Leadership,
+}
+
+#[cfg(feature = "mongo")]
+impl InstanceCoordinator {
+    /// Creates a new coordinator of the instance.
+    pub fn new(database: Database, config: CoordinationConfig) -> Self {
+        let leadership = if config.enabled { Leadership::follower() } else { Leadership::writer() };
+        Self { database, config, leadership }
+    }
+
+    /// Returns the leadership of the instance, updated once the instance is elected.
+    pub fn leadership(&self) -> Leadership {
+        self.leadership.clone()
+    }
+
+    /// Runs the writers once the instance is elected, immediately if the coordination is
+    /// disabled. The lease is then renewed until the instance stops, the instance exiting if it
+    /// can't renew its lease before it expires.
+    pub fn run_writers<F>(&self, writers: F)
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        if !self.config.enabled {
+            tokio::spawn(writers);
+            return;
+        }
+
+        let this = self.clone();
+        tokio::spawn(async move {
+            this.wait_for_election().await;
+            this.leadership.set_writer(true);
+            tokio::spawn(writers);
+            this.renew_lease().await;
+        });
+    }
+
+    /// Releases the lease of the instance, if held, so that another instance is elected without
+    /// waiting for the lease to expire.
+    pub async fn release(&self) {
+        if !self.config.enabled {
+            return;
+        }
+        if let Err(err) = self.database.release_instance_lease(WRITER_ROLE, &self.config.instance_id).await {
+            tracing::warn!(target: "coordination", ?err, "failed to release the instance lease");
+        }
+    }
+
+    /// Waits until the instance acquires the lease of the writer role.
+    async fn wait_for_election(&self) {
+        let instance = &self.config.instance_id;
+        tracing::info!(target: "coordination", %instance, "waiting for the election of the writer instance");
+        loop {
+            match self.database.acquire_instance_lease(WRITER_ROLE, instance, self.config.lease_ttl).await {
+                Ok(true) => {
+                    tracing::info!(
+                        target: "coordination",
+                        monotonic_counter.instance_elections = 1,
+                        %instance,
+                        "elected as the writer instance"
+                    );
+                    return;
+                }
+                Ok(false) => {}
+                Err(err) => tracing::warn!(target: "coordination", ?err, "failed to acquire the instance lease"),
+            }
+            tokio::time::sleep(self.renewal_interval()).await;
+        }
+    }
+
+    /// Renews the lease of the instance, exiting the process once the lease is lost.
+    ///
+    /// If the lease can't be renewed, the instance steps down one renewal interval before the
+    /// lease expires, so that it stopped writing by the time another instance can be elected. The
+    /// lease is considered renewed when the renewal was sent, the database setting its expiry at
+    /// the time it receives it.
+    async fn renew_lease(&self) {
+        let instance = &self.config.instance_id;
+        let mut renewed_at = Instant::now();
+        loop {
+            tokio::time::sleep(self.renewal_interval()).await;
+            let sent_at = Instant::now();
+            match self.database.acquire_instance_lease(WRITER_ROLE, instance, self.config.lease_ttl).await {
+                Ok(true) => renewed_at = sent_at,
+                Ok(false) => self.lost_lease("taken over by another instance"),
+                Err(err) if renewed_at.elapsed() + self.renewal_interval() >= self.config.lease_ttl => {
+                    tracing::warn!(target: "coordination", ?err, "failed to renew the instance lease");
+                    self.lost_lease("about to expire");
+                }
+                Err(err) => tracing::warn!(target: "coordination", ?err, "failed to renew the instance lease"),
+            }
+        }
+    }
+
+    /// Stops the instance after the loss of its lease, as its writers can't be stopped.
+    fn lost_lease(&self, reason: &str) -> ! {
+        self.leadership.set_writer(false);
+        tracing::error!(
+            target: "coordination",
+            monotonic_counter.instance_lease_losses = 1,
+            instance = %self.config.instance_id,
+            reason,
+            "lost the writer lease, stopping the instance"
+        );
+        std::process::exit(1)
+    }
+
+    /// Returns the interval between two attempts to acquire or renew the lease.
+    fn renewal_interval(&self) -> Duration {
+        self.config.lease_ttl / 3
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_coordination_config_from_env() {
+        // Given
+        std::env::set_var("INSTANCE_COORDINATION_ENABLED", "true");
+        std::env::set_var("INSTANCE_ID", "rpc-0");
+        std::env::set_var("INSTANCE_LEASE_TTL_SECS", "0");
+
+        // When
+        let config = CoordinationConfig::from_env();
+
+        // Then
+        assert!(config.enabled);
+        assert_eq!(config.instance_id, "rpc-0");
+        assert_eq!(config.lease_ttl, CoordinationConfig::default().lease_ttl);
+
+        std::env::remove_var("INSTANCE_COORDINATION_ENABLED");
+        std::env::remove_var("INSTANCE_ID");
+        std::env::remove_var("INSTANCE_LEASE_TTL_SECS");
+    }
+
+    #[test]
+    fn test_leadership() {
+        // Given
+        let leadership = Leadership::follower();
+        let shared = leadership.clone();
+
+        // When
+        let before = shared.is_writer();
+        leadership.set_writer(true);
+
+        // Then
+        assert!(!before);
+        assert!(shared.is_writer());
+        assert!(Leadership::default().is_writer());
+    }
+}
@@ -0,0 +1,71 @@
+use crate::{
+    eth_rpc::middleware::usage::Usage,
+    pool::{
+        ordering::PoolDrainOrder,
+        relayers::{RelayerQueue, TrafficClass},
+        snapshot::{PoolImport, PoolSnapshot},
+    },
+};
+use alloy_primitives::Address;
+use jsonrpsee::{
+    core::{RpcResult, SubscriptionResult},
+    proc_macros::rpc,
+};
+use starknet::core::types::Felt;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Admin API, used to operate the relayers of the node.
+#[rpc(server, namespace = "admin")]
+#[async_trait]
+pub trait AdminApi {
+    /// Returns the number of transactions waiting to be relayed and the queue depth and
+    /// submission latency of each relayer.
+    #[method(name = "relayerQueue")]
+    async fn relayer_queue(&self) -> RpcResult<RelayerQueue>;
+
+    /// Returns the pending transactions of the mempool in the order in which they would be
+    /// relayed, with their effective tip and the relay loop iteration in which they would be
+    /// picked, along with the funded relayers they can be assigned to. The funded relayers are
+    /// refreshed at most every 10 seconds.
+    #[method(name = "poolInspectOrder")]
+    async fn pool_inspect_order(&self) -> RpcResult<PoolDrainOrder>;
+
+    /// Registers a new relayer account serving the traffic class, the users by default, without
+    /// restarting the node. Returns false if the relayer was already registered. Fails if the
+    /// account isn't deployed or if its balance doesn't cover the relaying fees.
+    #[method(name = "addRelayer")]
+    async fn add_relayer(&self, address: Felt, traffic_class: Option<TrafficClass>) -> RpcResult<bool>;
+
+    /// Returns the number of calls, the compute units and the size of the responses of each API
+    /// key since the start of the node, by id of the key. The calls without API key are reported
+    /// under `anonymous`.
+    #[method(name = "apiKeyUsage")]
+    async fn api_key_usage(&self) -> RpcResult<BTreeMap<String, Usage>>;
+
+    /// Returns a snapshot of the pending and queued transactions of the mempool, along with their
+    /// validation metadata, which can be saved to a JSON file and imported in another node.
+    #[method(name = "exportPool")]
+    async fn export_pool(&self) -> RpcResult<PoolSnapshot>;
+
+    /// Validates and adds the transactions of a snapshot of the mempool, returning the imported
+    /// transactions and the reason why the others were rejected.
+    #[method(name = "importPool")]
+    async fn import_pool(&self, snapshot: PoolSnapshot) -> RpcResult<PoolImport>;
+
+    /// Returns the senders allowed to deploy contracts, or null if anyone can deploy contracts.
+    #[method(name = "deployerAllowlist")]
+    async fn deployer_allowlist(&self) -> RpcResult<Option<BTreeSet<Address>>>;
+
+    /// Restricts the contract creations accepted by the mempool to the senders of the allowlist,
+    /// an empty allowlist rejecting all of them. Setting null allows anyone to deploy contracts.
+    #[method(name = "setDeployerAllowlist")]
+    async fn set_deployer_allowlist(&self, allowlist: Option<BTreeSet<Address>>) -> RpcResult<()>;
+
+    /// Subscribes to the relayer queue, which is sent every `interval` milliseconds.
+    #[subscription(
+        name = "subscribeRelayerQueue" => "relayerQueue",
+        unsubscribe = "unsubscribeRelayerQueue",
+        item = RelayerQueue
+    )]
+    async fn subscribe_relayer_queue(&self, interval: Option<u64>) -> SubscriptionResult;
+}
@@ -0,0 +1,17 @@
+use crate::chaos::FaultConfig;
+use jsonrpsee::{core::RpcResult, proc_macros::rpc};
+
+/// Fault injection API, served by the nodes built with the `chaos` feature when the admin API is
+/// enabled.
+#[rpc(server, namespace = "admin")]
+#[async_trait]
+pub trait ChaosApi {
+    /// Returns the probabilities and the delays of the injected faults.
+    #[method(name = "faultInjection")]
+    async fn fault_injection(&self) -> RpcResult<FaultConfig>;
+
+    /// Replaces the configuration of the injected faults. Setting a seed reseeds the random
+    /// generator, making the following faults deterministic.
+    #[method(name = "setFaultInjection")]
+    async fn set_fault_injection(&self, config: FaultConfig) -> RpcResult<FaultConfig>;
+}
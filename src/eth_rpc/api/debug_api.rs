@@ -1,7 +1,11 @@
+use crate::{models::transaction_request::NormalizedTransactionRequest, tracing::diff::TransactionDiff};
 use alloy_primitives::{Bytes, B256};
-use alloy_rpc_types::{BlockId, BlockNumberOrTag, TransactionRequest};
+use alloy_rpc_types::{BlockId, BlockNumberOrTag};
 use alloy_rpc_types_trace::geth::{GethDebugTracingCallOptions, GethDebugTracingOptions, GethTrace, TraceResult};
-use jsonrpsee::{core::RpcResult, proc_macros::rpc};
+use jsonrpsee::{
+    core::{RpcResult, SubscriptionResult},
+    proc_macros::rpc,
+};
 
 /// Debug API
 /// Taken from Reth's DebugApi trait:
@@ -47,6 +51,21 @@ pub trait DebugApi {
         opts: Option<GethDebugTracingOptions>,
     ) -> RpcResult<Vec<TraceResult>>;
 
+    /// Subscribes to the Geth debug traces of the given block, sent one transaction at a time.
+    ///
+    /// Unlike `debug_traceBlockByNumber` and `debug_traceBlockByHash`, the traces of the whole
+    /// block are not buffered in memory, which makes it suited for very large blocks.
+    #[subscription(
+        name = "subscribeTraceBlock" => "traceBlock",
+        unsubscribe = "unsubscribeTraceBlock",
+        item = TraceResult
+    )]
+    async fn subscribe_trace_block(
+        &self,
+        block_id: BlockId,
+        opts: Option<GethDebugTracingOptions>,
+    ) -> SubscriptionResult;
+
     /// Returns the Geth debug trace for the given transaction hash.
     #[method(name = "traceTransaction")]
     async fn trace_transaction(
@@ -59,8 +78,13 @@ pub trait DebugApi {
     #[method(name = "traceCall")]
     async fn trace_call(
         &self,
-        request: TransactionRequest,
+        request: NormalizedTransactionRequest,
         block_number: Option<BlockId>,
         opts: Option<GethDebugTracingCallOptions>,
     ) -> RpcResult<GethTrace>;
+
+    /// Re-executes a mined transaction with the local EVM and reports the divergences (status,
+    /// gas used, return data and logs) with its execution by Kakarot.
+    #[method(name = "diffTransaction")]
+    async fn diff_transaction(&self, transaction_hash: B256) -> RpcResult<Option<TransactionDiff>>;
 }
@@ -1,13 +1,22 @@
-use crate::providers::eth_provider::database::types::receipt::ExtendedTxReceipt;
+use crate::{
+    models::{
+        filter::NormalizedFilter, log_subscription::LogFilterExtension,
+        transaction_request::NormalizedTransactionRequest,
+    },
+    providers::eth_provider::database::types::receipt::ExtendedTxReceipt,
+};
 use alloy_eips::{BlockId, BlockNumberOrTag};
 use alloy_primitives::{Address, Bytes, B256, B64, U256, U64};
 use alloy_rpc_types::{
-    serde_helpers::JsonStorageKey, state::StateOverride, AccessListResult, Block, BlockOverrides,
-    EIP1186AccountProofResponse, FeeHistory, Filter, FilterChanges, Index, SyncStatus, Transaction as EthTransaction,
-    TransactionRequest, Work,
+    pubsub::SubscriptionKind, serde_helpers::JsonStorageKey, state::StateOverride, AccessListResult, Block,
+    BlockOverrides, Bundle, EIP1186AccountProofResponse, EthCallResponse, FeeHistory, FilterChanges, Index, Log,
+    StateContext, SyncStatus, Transaction as EthTransaction, TransactionRequest, Work,
 };
 use alloy_serde::WithOtherFields;
-use jsonrpsee::{core::RpcResult, proc_macros::rpc};
+use jsonrpsee::{
+    core::{RpcResult, SubscriptionResult},
+    proc_macros::rpc,
+};
 
 /// Ethereum JSON-RPC API Trait
 /// Mostly based on <https://github.com/paradigmxyz/reth/blob/559124ac5a0b25030250203babcd8a94693df648/crates/rpc/rpc-api/src/eth.rs#L15>
@@ -30,9 +39,9 @@ pub trait EthApi {
     #[method(name = "accounts")]
     async fn accounts(&self) -> RpcResult<Vec<Address>>;
 
-    /// Returns the chain ID of the current network.
+    /// Returns the chain ID of the current network, served from memory.
     #[method(name = "chainId")]
-    async fn chain_id(&self) -> RpcResult<Option<U64>>;
+    fn chain_id(&self) -> RpcResult<Option<U64>>;
 
     /// Returns information about a block by hash.
     #[method(name = "getBlockByHash")]
@@ -51,6 +60,8 @@ pub trait EthApi {
     ) -> RpcResult<Option<WithOtherFields<Block<WithOtherFields<EthTransaction>>>>>;
 
     /// Returns the number of transactions in a block from a block matching the given block hash.
+    /// The zero hash of the pending block returned by `eth_getBlockByNumber` designates the pending
+    /// block.
     #[method(name = "getBlockTransactionCountByHash")]
     async fn block_transaction_count_by_hash(&self, hash: B256) -> RpcResult<Option<U256>>;
 
@@ -87,6 +98,8 @@ pub trait EthApi {
     async fn transaction_by_hash(&self, hash: B256) -> RpcResult<Option<WithOtherFields<EthTransaction>>>;
 
     /// Returns information about a transaction by block hash and transaction index position.
+    /// The zero hash of the pending block returned by `eth_getBlockByNumber` designates the pending
+    /// block, its transactions being looked up as with `eth_getTransactionByBlockNumberAndIndex`.
     #[method(name = "getTransactionByBlockHashAndIndex")]
     async fn transaction_by_block_hash_and_index(
         &self,
@@ -102,6 +115,25 @@ pub trait EthApi {
         index: Index,
     ) -> RpcResult<Option<WithOtherFields<EthTransaction>>>;
 
+    /// Returns the EIP-2718 binary-encoded transaction requested by transaction hash, e.g. to
+    /// re-broadcast it.
+    #[method(name = "getRawTransactionByHash")]
+    async fn raw_transaction_by_hash(&self, hash: B256) -> RpcResult<Option<Bytes>>;
+
+    /// Returns the EIP-2718 binary-encoded transaction by block hash and transaction index
+    /// position.
+    #[method(name = "getRawTransactionByBlockHashAndIndex")]
+    async fn raw_transaction_by_block_hash_and_index(&self, hash: B256, index: Index) -> RpcResult<Option<Bytes>>;
+
+    /// Returns the EIP-2718 binary-encoded transaction by block number and transaction index
+    /// position.
+    #[method(name = "getRawTransactionByBlockNumberAndIndex")]
+    async fn raw_transaction_by_block_number_and_index(
+        &self,
+        number: BlockNumberOrTag,
+        index: Index,
+    ) -> RpcResult<Option<Bytes>>;
+
     /// Returns the receipt of a transaction by transaction hash.
     #[method(name = "getTransactionReceipt")]
     async fn transaction_receipt(&self, hash: B256) -> RpcResult<Option<ExtendedTxReceipt>>;
@@ -124,18 +156,28 @@ pub trait EthApi {
 
     /// Returns the logs corresponding to the given filter object.
     #[method(name = "getLogs")]
-    async fn get_logs(&self, filter: Filter) -> RpcResult<FilterChanges>;
+    async fn get_logs(&self, filter: NormalizedFilter) -> RpcResult<FilterChanges>;
 
     /// Executes a new message call immediately without creating a transaction on the block chain.
     #[method(name = "call")]
     async fn call(
         &self,
-        request: TransactionRequest,
+        request: NormalizedTransactionRequest,
         block_id: Option<BlockId>,
         state_overrides: Option<StateOverride>,
         block_overrides: Option<Box<BlockOverrides>>,
     ) -> RpcResult<Bytes>;
 
+    /// Executes the bundles of calls sequentially, each call seeing the state changes of the
+    /// previous ones, without creating transactions on the block chain.
+    #[method(name = "callMany")]
+    async fn call_many(
+        &self,
+        bundles: Vec<Bundle>,
+        state_context: Option<StateContext>,
+        state_overrides: Option<StateOverride>,
+    ) -> RpcResult<Vec<Vec<EthCallResponse>>>;
+
     /// Generates an access list for a transaction.
     ///
     /// This method creates an [EIP2930](https://eips.ethereum.org/EIPS/eip-2930) type accessList based on a given Transaction.
@@ -160,7 +202,7 @@ pub trait EthApi {
     /// Generates and returns an estimate of how much gas is necessary to allow the transaction to
     /// complete.
     #[method(name = "estimateGas")]
-    async fn estimate_gas(&self, request: TransactionRequest, block_id: Option<BlockId>) -> RpcResult<U256>;
+    async fn estimate_gas(&self, request: NormalizedTransactionRequest, block_id: Option<BlockId>) -> RpcResult<U256>;
 
     /// Returns the current price per gas in wei.
     #[method(name = "gasPrice")]
@@ -245,7 +287,7 @@ pub trait EthApi {
 
     /// Creates a filter object, based on filter options, to notify when the state changes (logs).
     #[method(name = "newFilter")]
-    async fn new_filter(&self, filter: Filter) -> RpcResult<U64>;
+    async fn new_filter(&self, filter: NormalizedFilter) -> RpcResult<U64>;
 
     /// Creates a filter in the node, to notify when a new block arrives.
     #[method(name = "newBlockFilter")]
@@ -270,4 +312,15 @@ pub trait EthApi {
     /// Returns all transaction receipts for a given block.
     #[method(name = "getBlockReceipts")]
     async fn block_receipts(&self, block_id: Option<BlockId>) -> RpcResult<Option<Vec<ExtendedTxReceipt>>>;
+
+    /// Subscribes to the logs matching the filter in the blocks indexed after the subscription.
+    /// The optional extension filters the logs of an event on the values of its arguments.
+    /// Only the `logs` subscriptions are supported.
+    #[subscription(name = "subscribe" => "subscription", unsubscribe = "unsubscribe", item = Log)]
+    async fn subscribe(
+        &self,
+        kind: SubscriptionKind,
+        filter: Option<NormalizedFilter>,
+        extension: Option<LogFilterExtension>,
+    ) -> SubscriptionResult;
 }
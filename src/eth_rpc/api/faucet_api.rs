@@ -0,0 +1,17 @@
+use alloy_primitives::Address;
+use jsonrpsee::{core::RpcResult, proc_macros::rpc};
+use starknet::core::types::Felt;
+
+/// Kakarot faucet API, served by the development and testnet nodes built with the `faucet`
+/// feature.
+#[rpc(server, namespace = "kakarot")]
+#[async_trait]
+pub trait KakarotFaucetApi {
+    /// Transfers `FAUCET_AMOUNT_WEI` of test ETH to the address, using a funded faucet relayer
+    /// (`FAUCET_RELAYERS_ADDRESSES`), never the relayers of the users.
+    /// Each address and each client IP can request funds once every `FAUCET_INTERVAL_SECS`, the
+    /// requests of which the client IP can't be determined are rejected.
+    /// Returns the hash of the Starknet transfer transaction.
+    #[method(name = "requestFunds", with_extensions)]
+    async fn request_funds(&self, address: Address) -> RpcResult<Felt>;
+}
@@ -1,9 +1,140 @@
-use crate::providers::eth_provider::constant::Constant;
+use crate::{
+    pool::{
+        build_stats::BlockBuildStats,
+        content::{TxpoolContentCursor, TxpoolContentPage},
+        nonce_gaps::NonceGaps,
+    },
+    providers::eth_provider::{
+        constant::{Capabilities, ChainMetadata, Constant},
+        database::types::{
+            header::ExtendedBlock,
+            transaction::{DroppedTransaction, ExtendedTransaction, PoolEvent},
+        },
+        gas::{DataAvailabilityCost, FeeSuggestions},
+        logs::{LogQuery, LogQueryResult},
+        receipts::{ReceiptProof, TransactionConfirmations, TransactionExecutionResources},
+        state::AccountType,
+        transactions::{AddressHistory, AddressHistoryRequest},
+    },
+};
+use alloy_eips::BlockId;
+use alloy_primitives::{Address, B256, U64};
+use alloy_rpc_types::TransactionRequest;
 use jsonrpsee::{core::RpcResult, proc_macros::rpc};
+use starknet::core::types::Felt;
 
 #[rpc(server, namespace = "kakarot")]
 #[async_trait]
 pub trait KakarotApi {
     #[method(name = "getConfig")]
     async fn get_config(&self) -> RpcResult<Constant>;
+
+    /// Returns the number of confirmations and the Starknet finality status
+    /// (`ACCEPTED_ON_L2` or `ACCEPTED_ON_L1`) of a mined transaction.
+    #[method(name = "getTransactionConfirmations")]
+    async fn get_transaction_confirmations(&self, hash: B256) -> RpcResult<Option<TransactionConfirmations>>;
+
+    /// Returns a page of the transactions touching an address, as sender, recipient or log emitter,
+    /// over a block range. The `nextCursor` of the response is used to fetch the following page.
+    #[method(name = "getAddressHistory")]
+    async fn get_address_history(&self, request: AddressHistoryRequest) -> RpcResult<AddressHistory>;
+
+    /// Returns the reason why the transaction with the given hash was dropped by the node, if it was.
+    #[method(name = "getDroppedTransaction")]
+    async fn get_dropped_transaction(&self, hash: B256) -> RpcResult<Option<DroppedTransaction>>;
+
+    /// Returns the events of the transaction with the given hash in the mempool, in the order they
+    /// happened: added or queued, promoted, replaced, removed, dropped with its reason, relayed
+    /// with its Starknet hash and mined. Only the events of the recent transactions are kept.
+    #[method(name = "getTransactionTimeline")]
+    async fn get_transaction_timeline(&self, hash: B256) -> RpcResult<Vec<PoolEvent>>;
+
+    /// Returns `maxFeePerGas` and `maxPriorityFeePerGas` recommendations for the slow, normal and
+    /// fast tiers, derived from the gas oracle and the priority fees of the recent blocks. If a
+    /// transaction is given, its estimated L1 data availability cost is included.
+    #[method(name = "suggestFees")]
+    async fn suggest_fees(&self, request: Option<TransactionRequest>) -> RpcResult<FeeSuggestions>;
+
+    /// Returns the estimated share of the transaction in the cost of posting the state diffs of
+    /// Starknet to L1, from the size of its calldata and the current L1 prices.
+    #[method(name = "estimateDataAvailabilityCost")]
+    async fn estimate_data_availability_cost(&self, request: TransactionRequest) -> RpcResult<DataAvailabilityCost>;
+
+    /// Returns the proof of the receipt of a transaction against the receipts root of its block,
+    /// along with the Starknet transaction, block and events needed to verify its inclusion against
+    /// a Starknet block hash if the transaction was relayed by the node.
+    #[method(name = "getReceiptProof")]
+    async fn get_receipt_proof(&self, hash: B256) -> RpcResult<Option<ReceiptProof>>;
+
+    /// Returns the Starknet execution resources (Cairo steps, builtins and L1 gas) consumed by the
+    /// Starknet transaction which executed a mined transaction.
+    #[method(name = "getExecutionResources")]
+    async fn get_execution_resources(&self, hash: B256) -> RpcResult<Option<TransactionExecutionResources>>;
+
+    /// Returns the supported EVM hardfork, the enabled precompiles, the maximum code size and the
+    /// supported and unsupported transaction types.
+    #[method(name = "getCapabilities")]
+    async fn get_capabilities(&self) -> RpcResult<Capabilities>;
+
+    /// Returns the chain id, the name, the native currency, the RPC urls and the block explorer urls
+    /// of the chain, in the EIP-3085 format of the `wallet_addEthereumChain` parameters.
+    #[method(name = "getChainMetadata")]
+    async fn get_chain_metadata(&self) -> RpcResult<ChainMetadata>;
+
+    /// Returns the type of the account at the address: `eoa`, `contract`, or `uninitialized` if
+    /// its Starknet contract isn't deployed yet.
+    #[method(name = "getAccountType")]
+    async fn get_account_type(&self, address: Address, block_id: Option<BlockId>) -> RpcResult<AccountType>;
+
+    /// Returns the block built from the Starknet block with the given hash. Block can be full or
+    /// just the hashes of the transactions.
+    #[method(name = "getBlockByStarknetHash")]
+    async fn get_block_by_starknet_hash(&self, hash: B256, full: bool) -> RpcResult<Option<ExtendedBlock>>;
+
+    /// Returns a page of the indexed logs matching a structured query over sets of emitters,
+    /// topic patterns and a block range. The `nextCursor` of the response is used to fetch the
+    /// following page.
+    #[method(name = "queryLogs")]
+    async fn query_logs(&self, query: LogQuery) -> RpcResult<LogQueryResult>;
+}
+
+/// Kakarot API of the mempool, served by the nodes running the relayers.
+#[rpc(server, namespace = "kakarot")]
+#[async_trait]
+pub trait KakarotPoolApi {
+    /// Returns the on-chain nonce of the address, the highest nonce of its transactions in the
+    /// mempool and the missing nonces in between, the first of which blocks the relaying of the
+    /// following transactions.
+    #[method(name = "getNonceGaps")]
+    async fn get_nonce_gaps(&self, address: Address) -> RpcResult<NonceGaps>;
+
+    /// Returns the stats of the transactions pulled from the mempool by the relay loop while the
+    /// block was pending: the number of transactions pulled and skipped for lack of gas, the
+    /// submissions of each relayer and the total fees. Only the latest blocks are tracked.
+    #[method(name = "getBlockBuildStats")]
+    async fn get_block_build_stats(&self, block_number: U64) -> RpcResult<Option<BlockBuildStats>>;
+
+    /// Returns a page of the content of the mempool, grouped like `txpool_content`. The
+    /// transactions are iterated by sender and nonce, so that the pages are stable while
+    /// transactions are added or removed. The `nextCursor` of the response is used to fetch the
+    /// following page.
+    #[method(name = "txpoolContentPaginated")]
+    async fn txpool_content_paginated(
+        &self,
+        cursor: Option<TxpoolContentCursor>,
+        limit: Option<U64>,
+    ) -> RpcResult<TxpoolContentPage<ExtendedTransaction>>;
+}
+
+/// Kakarot API reserved to the operators of the node, served along with the admin API.
+#[rpc(server, namespace = "kakarot")]
+#[async_trait]
+pub trait KakarotAdminApi {
+    /// Deploys the Starknet account of an address which was never deployed, using a funded
+    /// relayer. Returns the hash of the Starknet deployment transaction.
+    ///
+    /// Requires the admin role. The deployments are spaced by at least
+    /// `DEPLOY_ACCOUNT_INTERVAL_SECS`.
+    #[method(name = "deployAccount")]
+    async fn deploy_account(&self, address: Address) -> RpcResult<Felt>;
 }
@@ -0,0 +1,24 @@
+use crate::meta_tx::{MetaTransactionRequest, SponsorInfo};
+use alloy_primitives::{Address, B256, U256};
+use jsonrpsee::{core::RpcResult, proc_macros::rpc};
+
+/// Kakarot meta-transaction API, served by the nodes built with the `meta-tx` feature where a
+/// trusted forwarder is configured.
+#[rpc(server, namespace = "kakarot")]
+#[async_trait]
+pub trait KakarotMetaTxApi {
+    /// Checks the EIP-712 signed forward request, then relays it through the trusted forwarder in
+    /// a transaction paid by a sponsor of the node. Returns the hash of the sponsor transaction.
+    #[method(name = "sendMetaTransaction")]
+    async fn send_meta_transaction(&self, request: MetaTransactionRequest) -> RpcResult<B256>;
+
+    /// Returns the nonce of the next forward request of the address, accounting for its requests
+    /// relayed by the node which aren't mined yet.
+    #[method(name = "getMetaTransactionNonce")]
+    async fn get_meta_transaction_nonce(&self, address: Address) -> RpcResult<U256>;
+
+    /// Returns the sponsors of the node, with the maximum gas of the requests they pay for and
+    /// their remaining budget.
+    #[method(name = "getMetaTransactionSponsors")]
+    async fn get_meta_transaction_sponsors(&self) -> RpcResult<Vec<SponsorInfo>>;
+}
@@ -1,8 +1,16 @@
+pub mod admin_api;
 pub mod alchemy_api;
+#[cfg(feature = "chaos")]
+pub mod chaos_api;
 pub mod debug_api;
 pub mod eth_api;
+#[cfg(feature = "faucet")]
+pub mod faucet_api;
 pub mod kakarot_api;
+#[cfg(feature = "meta-tx")]
+pub mod meta_tx_api;
 pub mod net_api;
+pub mod openrpc_api;
 pub mod trace_api;
 pub mod txpool_api;
 pub mod web3_api;
@@ -5,9 +5,9 @@ use jsonrpsee::{core::RpcResult, proc_macros::rpc};
 #[rpc(server, namespace = "net")]
 #[async_trait]
 pub trait NetApi {
-    /// Returns the protocol version encoded as a string.
+    /// Returns the protocol version encoded as a string, served from memory.
     #[method(name = "version")]
-    async fn version(&self) -> RpcResult<U64>;
+    fn version(&self) -> RpcResult<U64>;
 
     /// Returns number of peers connected to node.
     #[method(name = "peerCount")]
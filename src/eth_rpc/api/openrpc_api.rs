@@ -0,0 +1,11 @@
+use jsonrpsee::{core::RpcResult, proc_macros::rpc};
+use serde_json::Value;
+
+#[rpc(server)]
+#[async_trait]
+pub trait OpenRpcApi {
+    /// Returns the `OpenRPC` document of the API, listing the methods with their parameters and
+    /// result schemas. The document is also served at `/openrpc.json`.
+    #[method(name = "rpc.discover")]
+    fn discover(&self) -> RpcResult<Value>;
+}
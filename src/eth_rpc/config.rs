@@ -1,19 +1,99 @@
+use crate::{
+    coordination::Leadership,
+    eth_rpc::middleware::{
+        admin_access::AdminApiKeys,
+        client_ip::DEFAULT_TRUSTED_PROXIES,
+        concurrency::{ConcurrencyLimits, DEFAULT_CONCURRENCY_LIMITS, DEFAULT_CONCURRENCY_QUEUE_TIMEOUT},
+        origins::AllowedOrigins,
+        public_trace::PublicTraceConfig,
+        usage::TenantApiKeys,
+    },
+};
 use eyre::{eyre, Result};
+use std::{str::FromStr, time::Duration};
+
+/// The default maximum size of a request, in bytes, which is the default of jsonrpsee (10 MiB).
+const DEFAULT_MAX_BODY_SIZE: u32 = 10 * 1024 * 1024;
+
+/// The default maximum number of concurrent connections.
+const DEFAULT_MAX_CONNECTIONS: u32 = 100;
 
 #[derive(Debug, Clone)]
 pub struct RPCConfig {
     pub socket_addr: String,
+    /// Maximum size of a request, in bytes. Batch requests count as a single request.
+    pub max_request_body_size: u32,
+    /// Maximum number of concurrent connections.
+    pub max_connections: u32,
+    /// Whether TCP keep-alive probes are sent on idle connections, so that connections dropped
+    /// by a load balancer are detected and closed.
+    pub tcp_keepalive: bool,
+    /// Whether Nagle's algorithm is disabled on the connections.
+    pub tcp_nodelay: bool,
+    /// Maximum number of concurrent calls per namespace or method.
+    pub concurrency_limits: ConcurrencyLimits,
+    /// Duration a call waits for its concurrency quota before being rejected.
+    pub concurrency_queue_timeout: Duration,
+    /// Roles of the API keys allowed to call the admin namespace. Every admin call is denied if
+    /// empty.
+    pub admin_api_keys: AdminApiKeys,
+    /// API keys of the tenants, whose usage is accounted separately. The calls with another key
+    /// are accounted together as unknown.
+    pub tenant_api_keys: TenantApiKeys,
+    /// Configuration of the public tracing tier, serving the traces of the requests without an
+    /// API key with lower limits. The tracing endpoints aren't degraded if unset.
+    pub public_trace: Option<PublicTraceConfig>,
+    /// Origins allowed to call each namespace or method. All the origins are allowed if empty.
+    pub allowed_origins: AllowedOrigins,
+    /// Number of reverse proxies in front of the node, appending the address of the client to the
+    /// `X-Forwarded-For` header, used to identify the client of a request (e.g. for the faucet).
+    pub trusted_proxies: usize,
+    /// Leadership of the instance among the coordinated instances sharing the database, the
+    /// writes being rejected until the instance is elected. Unset if the instances aren't
+    /// coordinated.
+    pub leadership: Option<Leadership>,
 }
 
 impl RPCConfig {
-    pub const fn new(socket_addr: String) -> Self {
-        Self { socket_addr }
+    pub fn new(socket_addr: String) -> Self {
+        Self {
+            socket_addr,
+            max_request_body_size: DEFAULT_MAX_BODY_SIZE,
+            max_connections: DEFAULT_MAX_CONNECTIONS,
+            tcp_keepalive: false,
+            tcp_nodelay: true,
+            concurrency_limits: DEFAULT_CONCURRENCY_LIMITS.parse().expect("valid default concurrency limits"),
+            concurrency_queue_timeout: DEFAULT_CONCURRENCY_QUEUE_TIMEOUT,
+            admin_api_keys: AdminApiKeys::default(),
+            tenant_api_keys: TenantApiKeys::default(),
+            public_trace: None,
+            allowed_origins: AllowedOrigins::default(),
+            trusted_proxies: DEFAULT_TRUSTED_PROXIES,
+            leadership: None,
+        }
     }
 
     pub fn from_env() -> Result<Self> {
         let socket_addr = std::env::var("KAKAROT_RPC_URL")
             .map_err(|_| eyre!("Missing mandatory environment variable: KAKAROT_RPC_URL"))?;
-        Ok(Self::new(socket_addr))
+        let default = Self::new(socket_addr);
+        Ok(Self {
+            max_request_body_size: env_or("RPC_MAX_REQUEST_BODY_SIZE", default.max_request_body_size)?,
+            max_connections: env_or("RPC_MAX_CONNECTIONS", default.max_connections)?,
+            tcp_keepalive: env_or("RPC_TCP_KEEPALIVE", default.tcp_keepalive)?,
+            tcp_nodelay: env_or("RPC_TCP_NODELAY", default.tcp_nodelay)?,
+            concurrency_limits: env_or("RPC_CONCURRENCY_LIMITS", default.concurrency_limits.clone())?,
+            concurrency_queue_timeout: Duration::from_millis(env_or(
+                "RPC_CONCURRENCY_QUEUE_TIMEOUT_MS",
+                default.concurrency_queue_timeout.as_millis() as u64,
+            )?),
+            admin_api_keys: env_or("ADMIN_API_KEYS", AdminApiKeys::default())?,
+            tenant_api_keys: env_or("TENANT_API_KEYS", TenantApiKeys::default())?,
+            public_trace: public_trace_config()?,
+            allowed_origins: env_or("RPC_ALLOWED_ORIGINS", AllowedOrigins::default())?,
+            trusted_proxies: env_or("RPC_TRUSTED_PROXIES", default.trusted_proxies)?,
+            ..default
+        })
     }
 
     pub fn from_port(port: u16) -> Result<Self> {
@@ -27,11 +107,37 @@ impl RPCConfig {
     }
 }
 
+/// Reads the configuration of the public tracing tier, if enabled.
+fn public_trace_config() -> Result<Option<PublicTraceConfig>> {
+    if !env_or("TRACE_PUBLIC_TIER", false)? {
+        return Ok(None);
+    }
+    let default = PublicTraceConfig::default();
+    Ok(Some(PublicTraceConfig {
+        concurrency: env_or("TRACE_PUBLIC_CONCURRENCY", default.concurrency)?,
+        queue_size: env_or("TRACE_PUBLIC_QUEUE_SIZE", default.queue_size)?,
+        queue_timeout: Duration::from_secs(env_or("TRACE_PUBLIC_QUEUE_TIMEOUT_SECS", default.queue_timeout.as_secs())?),
+    }))
+}
+
+/// Parses the environment variable, falling back to the default if it isn't set.
+fn env_or<T: FromStr>(name: &str, default: T) -> Result<T> {
+    std::env::var(name).map_or(Ok(default), |value| {
+        value.parse().map_err(|_| eyre!("Invalid value for environment variable {name}: {value}"))
+    })
+}
+
 #[cfg(feature = "testing")]
 impl RPCConfig {
     pub fn new_test_config() -> Self {
-        // Hardcode the socket address for testing environment
-        Self::new("127.0.0.1:3030".to_string())
+        // Hardcode the socket address for testing environment, the admin namespace is served to
+        // the test admin API key
+        Self {
+            admin_api_keys: format!("admin:{}", crate::test_utils::rpc::TEST_ADMIN_API_KEY)
+                .parse()
+                .expect("valid test admin API key"),
+            ..Self::new("127.0.0.1:3030".to_string())
+        }
     }
 
     pub fn new_test_config_from_port(port: u16) -> Self {
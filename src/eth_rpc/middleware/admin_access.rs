@@ -0,0 +1,281 @@
+//! Role-based access control of the admin namespace.
+//!
+//! The API key of a request, sent in the `Authorization: Bearer <key>` or in the `X-Api-Key`
+//! header, is recorded by the HTTP middleware. The RPC middleware then resolves the role of the
+//! key and rejects the admin calls which require a higher role: the read-only role can inspect
+//! the relayers, only the admin role can modify them or deploy accounts. The calls outside of
+//! the admin namespace are never restricted. Without any key, every admin call is rejected.
+
+use crate::providers::eth_provider::error::EthRpcErrorCode;
+use hyper::{header::HeaderMap, Request as HttpRequest};
+use jsonrpsee::{
+    server::middleware::rpc::RpcServiceT,
+    types::{ErrorObject, Request},
+    MethodResponse,
+};
+use std::{
+    collections::HashMap,
+    future::Future,
+    pin::Pin,
+    str::FromStr,
+    sync::Arc,
+    task::{Context, Poll},
+};
+use thiserror::Error;
+
+/// The admin methods which the read-only role can call. The other admin methods require the
+/// admin role.
+const READ_ONLY_ADMIN_METHODS: [&str; 8] = [
+    "admin_relayerQueue",
+    "admin_poolInspectOrder",
+    "admin_subscribeRelayerQueue",
+    "admin_unsubscribeRelayerQueue",
+    "admin_apiKeyUsage",
+    "admin_exportPool",
+    "admin_faultInjection",
+    "admin_deployerAllowlist",
+];
+
+/// The methods outside of the admin namespace which require the admin role.
+const ADMIN_METHODS: [&str; 1] = ["kakarot_deployAccount"];
+
+/// Error returned when parsing invalid admin API keys.
+#[derive(Debug, Error, PartialEq, Eq)]
+#[error("invalid admin API key {0}, expected <read or admin>:<key>")]
+pub struct InvalidAdminApiKey(String);
+
+/// The role of an admin API key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum AdminRole {
+    /// Can inspect the state of the node.
+    ReadOnly,
+    /// Can inspect and operate the node.
+    Admin,
+}
+
+impl AdminRole {
+    /// Returns the role required to call the method, if any.
+    pub fn required_by(method: &str) -> Option<Self> {
+        if READ_ONLY_ADMIN_METHODS.contains(&method) {
+            Some(Self::ReadOnly)
+        } else if method.starts_with("admin_") || ADMIN_METHODS.contains(&method) {
+            Some(Self::Admin)
+        } else {
+            None
+        }
+    }
+}
+
+impl FromStr for AdminRole {
+    type Err = InvalidAdminApiKey;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "read" => Ok(Self::ReadOnly),
+            "admin" => Ok(Self::Admin),
+            _ => Err(InvalidAdminApiKey(s.to_string())),
+        }
+    }
+}
+
+/// The roles of the admin API keys. If empty, the admin namespace can't be called.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AdminApiKeys(HashMap<String, AdminRole>);
+
+impl AdminApiKeys {
+    /// Returns true if no API key is set.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Returns the role of the API key, if known.
+    pub fn role(&self, key: &ApiKey) -> Option<AdminRole> {
+        self.0.get(&key.0).copied()
+    }
+}
+
+impl FromStr for AdminApiKeys {
+    type Err = InvalidAdminApiKey;
+
+    /// Parses a comma separated list of `<read or admin>:<key>`, e.g. `read:abc,admin:def`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.replace(' ', "")
+            .split(',')
+            .filter(|key| !key.is_empty())
+            .map(|entry| {
+                entry
+                    .split_once(':')
+                    .filter(|(_, key)| !key.is_empty())
+                    .and_then(|(role, key)| Some((key.to_string(), role.parse().ok()?)))
+                    .ok_or_else(|| InvalidAdminApiKey(entry.to_string()))
+            })
+            .collect::<Result<_, _>>()
+            .map(Self)
+    }
+}
+
+/// The API key of the request.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ApiKey(pub String);
+
+impl ApiKey {
+    /// Returns the API key from the headers of the request: the bearer token of the
+    /// `Authorization` header, or the `X-Api-Key` header.
+    pub fn from_headers(headers: &HeaderMap) -> Option<Self> {
+        let bearer = headers
+            .get("authorization")
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "));
+        let api_key = headers.get("x-api-key").and_then(|value| value.to_str().ok());
+
+        bearer.or(api_key).map(|key| Self(key.trim().to_string()))
+    }
+}
+
+/// API key layer.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ApiKeyLayer;
+
+impl<S> tower::Layer<S> for ApiKeyLayer {
+    type Service = ApiKeyService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        ApiKeyService { service: inner }
+    }
+}
+
+/// API key middleware.
+#[derive(Clone, Debug)]
+pub struct ApiKeyService<S> {
+    service: S,
+}
+
+impl<S, B> tower::Service<HttpRequest<B>> for ApiKeyService<S>
+where
+    S: tower::Service<HttpRequest<B>>,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = S::Future;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.service.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut request: HttpRequest<B>) -> Self::Future {
+        if let Some(api_key) = ApiKey::from_headers(request.headers()) {
+            request.extensions_mut().insert(api_key);
+        }
+        self.service.call(request)
+    }
+}
+
+/// Admin access layer.
+#[derive(Clone, Debug)]
+pub struct AdminAccessLayer {
+    keys: Arc<AdminApiKeys>,
+}
+
+impl AdminAccessLayer {
+    /// Creates a new layer restricting the admin namespace to the API keys, denying every admin
+    /// call if there is none.
+    pub fn new(keys: AdminApiKeys) -> Self {
+        Self { keys: Arc::new(keys) }
+    }
+}
+
+impl<S> tower::Layer<S> for AdminAccessLayer {
+    type Service = AdminAccessService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        AdminAccessService { service: inner, keys: Arc::clone(&self.keys) }
+    }
+}
+
+/// Admin access middleware.
+#[derive(Clone, Debug)]
+pub struct AdminAccessService<S> {
+    service: S,
+    keys: Arc<AdminApiKeys>,
+}
+
+impl<'a, S> RpcServiceT<'a> for AdminAccessService<S>
+where
+    S: Send + Sync + Clone + RpcServiceT<'a> + 'a,
+    S::Future: 'a,
+{
+    type Future = Pin<Box<dyn Future<Output = MethodResponse> + Send + 'a>>;
+
+    fn call(&self, req: Request<'a>) -> Self::Future {
+        let Some(required) = AdminRole::required_by(req.method_name()) else {
+            return Box::pin(self.service.call(req));
+        };
+
+        let role = req.extensions().get::<ApiKey>().and_then(|key| self.keys.role(key));
+        if role.is_some_and(|role| role >= required) {
+            return Box::pin(self.service.call(req));
+        }
+
+        tracing::warn!(
+            target: "admin_access",
+            monotonic_counter.admin_access_denied_calls = 1,
+            method = %req.method_name(),
+            ?role,
+            "call denied, missing admin role"
+        );
+        let error = ErrorObject::owned(
+            EthRpcErrorCode::InvalidRequest as i32,
+            format!("unauthorized, {} requires the {required:?} role", req.method_name()),
+            None::<()>,
+        );
+        Box::pin(std::future::ready(MethodResponse::error(req.id().into_owned(), error)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hyper::header::HeaderValue;
+
+    #[test]
+    fn test_parse_admin_api_keys() {
+        // When
+        let keys = AdminApiKeys::from_str("read:oncall, admin:operator").unwrap();
+        let empty = AdminApiKeys::from_str("").unwrap();
+        let invalid = ["oncall", "write:oncall", "read:", ":oncall"].map(AdminApiKeys::from_str);
+
+        // Then
+        assert_eq!(keys.role(&ApiKey("oncall".to_string())), Some(AdminRole::ReadOnly));
+        assert_eq!(keys.role(&ApiKey("operator".to_string())), Some(AdminRole::Admin));
+        assert_eq!(keys.role(&ApiKey("unknown".to_string())), None);
+        assert!(empty.is_empty());
+        assert!(invalid.iter().all(Result::is_err));
+    }
+
+    #[test]
+    fn test_admin_role_required_by() {
+        assert_eq!(AdminRole::required_by("admin_relayerQueue"), Some(AdminRole::ReadOnly));
+        assert_eq!(AdminRole::required_by("admin_apiKeyUsage"), Some(AdminRole::ReadOnly));
+        assert_eq!(AdminRole::required_by("admin_addRelayer"), Some(AdminRole::Admin));
+        assert_eq!(AdminRole::required_by("kakarot_deployAccount"), Some(AdminRole::Admin));
+        assert_eq!(AdminRole::required_by("eth_blockNumber"), None);
+        assert!(AdminRole::Admin > AdminRole::ReadOnly);
+    }
+
+    #[test]
+    fn test_api_key_from_headers() {
+        // Given
+        let mut headers = HeaderMap::new();
+        headers.insert("x-api-key", HeaderValue::from_static("oncall"));
+
+        // When
+        let api_key = ApiKey::from_headers(&headers);
+        headers.insert("authorization", HeaderValue::from_static("Bearer operator"));
+        let bearer = ApiKey::from_headers(&headers);
+
+        // Then
+        assert_eq!(api_key, Some(ApiKey("oncall".to_string())));
+        assert_eq!(bearer, Some(ApiKey("operator".to_string())));
+        assert_eq!(ApiKey::from_headers(&HeaderMap::new()), None);
+    }
+}
@@ -0,0 +1,199 @@
+//! HTTP middleware recording the IP of the client of the request.
+//!
+//! The node is expected to run behind reverse proxies, so the IP is read from the
+//! `X-Forwarded-For` header, or from the `X-Real-IP` header, and inserted in the extensions of
+//! the request, which are available to the RPC methods.
+//!
+//! Each proxy appends the address it received the request from to the `X-Forwarded-For` header,
+//! after the entries sent by the client, which the client controls. The IP of the client is
+//! therefore the entry appended by the outermost trusted proxy, counted from the right.
+//!
+//! Without these headers, e.g. if the node is reached directly, the IP is the address of the peer
+//! of the connection, recorded by the server in the extensions of the request.
+
+use hyper::{header::HeaderMap, Request};
+use std::{
+    net::{IpAddr, SocketAddr},
+    task::{Context, Poll},
+};
+
+/// The default number of reverse proxies in front of the node.
+pub const DEFAULT_TRUSTED_PROXIES: usize = 1;
+
+/// The address of the peer of the connection the request was received on.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct PeerAddr(pub SocketAddr);
+
+/// The IP of the client of the request, as reported by the reverse proxy or the address of the
+/// peer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ClientIp(pub IpAddr);
+
+impl ClientIp {
+    /// Returns the IP of the client from the headers of the request, behind the given number of
+    /// trusted reverse proxies: the address appended to the `X-Forwarded-For` header by the
+    /// outermost trusted proxy, or the `X-Real-IP`. The leftmost entries of the `X-Forwarded-For`
+    /// header, sent by the client, are ignored.
+    pub fn from_headers(headers: &HeaderMap, trusted_proxies: usize) -> Option<Self> {
+        let forwarded_for = headers.get("x-forwarded-for").and_then(|value| value.to_str().ok()).and_then(|value| {
+            let entries = value.split(',').collect::<Vec<_>>();
+            // The header has less entries than trusted proxies if it was set by the trusted proxies
+            entries.get(entries.len().saturating_sub(trusted_proxies.max(1))).copied()
+        });
+        let real_ip = headers.get("x-real-ip").and_then(|value| value.to_str().ok());
+
+        forwarded_for.or(real_ip).and_then(|ip| ip.trim().parse().ok()).map(Self)
+    }
+
+    /// Returns the IP of the client of the request, from its headers or else from the address of
+    /// the peer of the connection.
+    pub fn from_request<B>(request: &Request<B>, trusted_proxies: usize) -> Option<Self> {
+        Self::from_headers(request.headers(), trusted_proxies)
+            .or_else(|| request.extensions().get::<PeerAddr>().map(|peer| Self(peer.0.ip())))
+    }
+}
+
+/// Client IP layer.
+#[derive(Clone, Copy, Debug)]
+pub struct ClientIpLayer {
+    trusted_proxies: usize,
+}
+
+impl Default for ClientIpLayer {
+    fn default() -> Self {
+        Self::new(DEFAULT_TRUSTED_PROXIES)
+    }
+}
+
+impl ClientIpLayer {
+    /// Creates a new layer for a node behind the given number of trusted reverse proxies.
+    pub const fn new(trusted_proxies: usize) -> Self {
+        Self { trusted_proxies }
+    }
+}
+
+impl<S> tower::Layer<S> for ClientIpLayer {
+    type Service = ClientIpService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        ClientIpService { service: inner, trusted_proxies: self.trusted_proxies }
+    }
+}
+
+/// Client IP middleware.
+#[derive(Clone, Debug)]
+pub struct ClientIpService<S> {
+    service: S,
+    trusted_proxies: usize,
+}
+
+impl<S, B> tower::Service<Request<B>> for ClientIpService<S>
+where
+    S: tower::Service<Request<B>>,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = S::Future;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.service.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut request: Request<B>) -> Self::Future {
+        if let Some(client_ip) = ClientIp::from_request(&request, self.trusted_proxies) {
+            request.extensions_mut().insert(client_ip);
+        }
+        self.service.call(request)
+    }
+}
+
+/// Service recording the address of the peer of a connection in its requests.
+#[derive(Clone, Debug)]
+pub struct PeerAddrService<S> {
+    service: S,
+    peer: SocketAddr,
+}
+
+impl<S> PeerAddrService<S> {
+    /// Wraps the service of the connection of the given peer.
+    pub const fn new(service: S, peer: SocketAddr) -> Self {
+        Self { service, peer }
+    }
+}
+
+impl<S, B> tower::Service<Request<B>> for PeerAddrService<S>
+where
+    S: tower::Service<Request<B>>,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = S::Future;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.service.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut request: Request<B>) -> Self::Future {
+        request.extensions_mut().insert(PeerAddr(self.peer));
+        self.service.call(request)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hyper::header::HeaderValue;
+    use std::net::Ipv4Addr;
+
+    #[test]
+    fn test_client_ip_from_headers() {
+        // Given
+        let mut headers = HeaderMap::new();
+        headers.insert("x-real-ip", HeaderValue::from_static("10.0.0.2"));
+
+        // When
+        let real_ip = ClientIp::from_headers(&headers, 1);
+        headers.insert("x-forwarded-for", HeaderValue::from_static("10.0.0.1, 10.0.0.3"));
+        let forwarded_for = ClientIp::from_headers(&headers, 1);
+
+        // Then
+        assert_eq!(real_ip, Some(ClientIp(IpAddr::V4(Ipv4Addr::new(10, 0, 0, 2)))));
+        assert_eq!(forwarded_for, Some(ClientIp(IpAddr::V4(Ipv4Addr::new(10, 0, 0, 3)))));
+        assert_eq!(ClientIp::from_headers(&HeaderMap::new(), 1), None);
+    }
+
+    #[test]
+    fn test_client_ip_ignores_spoofed_entries() {
+        // Given
+        // The client sends random entries, the two trusted proxies append the client and the
+        // outer proxy addresses
+        let mut headers = HeaderMap::new();
+        headers.insert("x-forwarded-for", HeaderValue::from_static("1.2.3.4, 5.6.7.8, 10.0.0.1, 10.0.0.9"));
+
+        // When
+        let behind_two_proxies = ClientIp::from_headers(&headers, 2);
+        let behind_more_proxies = ClientIp::from_headers(&headers, 10);
+
+        // Then
+        assert_eq!(behind_two_proxies, Some(ClientIp(IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1)))));
+        assert_eq!(behind_more_proxies, Some(ClientIp(IpAddr::V4(Ipv4Addr::new(1, 2, 3, 4)))));
+    }
+
+    #[test]
+    fn test_client_ip_falls_back_to_peer_addr() {
+        // Given
+        let peer = PeerAddr(SocketAddr::new(IpAddr::V4(Ipv4Addr::new(10, 0, 0, 4)), 40_000));
+        let mut request = Request::new(());
+        request.extensions_mut().insert(peer);
+
+        // When
+        let without_headers = ClientIp::from_request(&request, 1);
+        request.headers_mut().insert("x-real-ip", HeaderValue::from_static("10.0.0.2"));
+        let with_headers = ClientIp::from_request(&request, 1);
+
+        // Then
+        assert_eq!(without_headers, Some(ClientIp(IpAddr::V4(Ipv4Addr::new(10, 0, 0, 4)))));
+        assert_eq!(with_headers, Some(ClientIp(IpAddr::V4(Ipv4Addr::new(10, 0, 0, 2)))));
+        assert_eq!(ClientIp::from_request(&Request::new(()), 1), None);
+    }
+}
@@ -0,0 +1,172 @@
+//! RPC middleware limiting the number of concurrent calls per namespace or method.
+//!
+//! Expensive calls (e.g. `debug_traceTransaction` or `eth_call`) are queued once their quota is
+//! reached, and rejected if they can't start before the queue timeout, so that tracing workloads
+//! can't starve the cheap reads of wallets served by the same instance. Calls without a quota
+//! are never limited.
+
+use crate::providers::eth_provider::error::EthRpcErrorCode;
+use jsonrpsee::{
+    server::middleware::rpc::RpcServiceT,
+    types::{ErrorObject, Request},
+    MethodResponse,
+};
+use std::{collections::HashMap, future::Future, pin::Pin, str::FromStr, sync::Arc, time::Duration};
+use thiserror::Error;
+use tokio::sync::Semaphore;
+
+/// The default quotas: at most 2 concurrent traces and 16 concurrent EVM executions.
+pub const DEFAULT_CONCURRENCY_LIMITS: &str = "debug=2,trace=2,eth_call=16,eth_estimateGas=16,eth_createAccessList=16";
+
+/// The default duration a call waits for its quota before being rejected.
+pub const DEFAULT_CONCURRENCY_QUEUE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Error returned when parsing invalid concurrency limits.
+#[derive(Debug, Error, PartialEq, Eq)]
+#[error("invalid concurrency limit {0}, expected <namespace or method>=<limit>")]
+pub struct InvalidConcurrencyLimit(String);
+
+/// The maximum number of concurrent calls per namespace (e.g. `debug`) or per method
+/// (e.g. `eth_call`). The quota of a method takes precedence over the one of its namespace.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ConcurrencyLimits(HashMap<String, usize>);
+
+impl ConcurrencyLimits {
+    /// Returns the key and the quota applying to the method, if any.
+    fn quota(&self, method: &str) -> Option<(&str, usize)> {
+        let namespace = method.split_once('_').map_or(method, |(namespace, _)| namespace);
+        [method, namespace]
+            .into_iter()
+            .find_map(|key| self.0.get_key_value(key))
+            .map(|(key, limit)| (key.as_str(), *limit))
+    }
+}
+
+impl FromStr for ConcurrencyLimits {
+    type Err = InvalidConcurrencyLimit;
+
+    /// Parses a comma separated list of `<namespace or method>=<limit>`, e.g. `debug=2,eth_call=16`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.replace(' ', "")
+            .split(',')
+            .filter(|limit| !limit.is_empty())
+            .map(|limit| {
+                limit
+                    .split_once('=')
+                    .and_then(|(key, value)| Some((key.to_string(), value.parse().ok().filter(|value| *value > 0)?)))
+                    .filter(|(key, _)| !key.is_empty())
+                    .ok_or_else(|| InvalidConcurrencyLimit(limit.to_string()))
+            })
+            .collect::<Result<_, _>>()
+            .map(Self)
+    }
+}
+
+/// Concurrency limit layer.
+#[derive(Clone, Debug)]
+pub struct ConcurrencyLayer {
+    semaphores: Arc<HashMap<String, Arc<Semaphore>>>,
+    limits: ConcurrencyLimits,
+    queue_timeout: Duration,
+}
+
+impl ConcurrencyLayer {
+    /// Creates a new layer enforcing the limits, waiting at most `queue_timeout` for a quota.
+    pub fn new(limits: ConcurrencyLimits, queue_timeout: Duration) -> Self {
+        let semaphores = limits.0.iter().map(|(key, limit)| (key.clone(), Arc::new(Semaphore::new(*limit)))).collect();
+        Self { semaphores: Arc::new(semaphores), limits, queue_timeout }
+    }
+}
+
+impl<S> tower::Layer<S> for ConcurrencyLayer {
+    type Service = ConcurrencyService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        ConcurrencyService { service: inner, layer: self.clone() }
+    }
+}
+
+/// Concurrency limit middleware.
+#[derive(Clone, Debug)]
+pub struct ConcurrencyService<S> {
+    service: S,
+    layer: ConcurrencyLayer,
+}
+
+impl<'a, S> RpcServiceT<'a> for ConcurrencyService<S>
+where
+    S: Send + Sync + Clone + RpcServiceT<'a> + 'a,
+    S::Future: 'a,
+{
+    type Future = Pin<Box<dyn Future<Output = MethodResponse> + Send + 'a>>;
+
+    fn call(&self, req: Request<'a>) -> Self::Future {
+        let Some((key, semaphore)) = self
+            .layer
+            .limits
+            .quota(req.method_name())
+            .and_then(|(key, _)| Some((key.to_string(), self.layer.semaphores.get(key).cloned()?)))
+        else {
+            return Box::pin(self.service.call(req));
+        };
+
+        let service = self.service.clone();
+        let queue_timeout = self.layer.queue_timeout;
+        Box::pin(async move {
+            // The permit is held until the call completes
+            let Ok(Ok(_permit)) = tokio::time::timeout(queue_timeout, semaphore.acquire_owned()).await else {
+                tracing::warn!(
+                    target: "rpc_concurrency",
+                    monotonic_counter.rpc_concurrency_rejected_calls = 1,
+                    quota = %key,
+                    method = %req.method_name(),
+                    "call rejected, concurrency quota exceeded"
+                );
+                let error = ErrorObject::owned(
+                    EthRpcErrorCode::RequestLimitExceeded as i32,
+                    format!("too many concurrent {key} calls, retry later"),
+                    None::<()>,
+                );
+                return MethodResponse::error(req.id().into_owned(), error);
+            };
+            service.call(req).await
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_concurrency_limits() {
+        // When
+        let limits = ConcurrencyLimits::from_str(DEFAULT_CONCURRENCY_LIMITS).unwrap();
+        let empty = ConcurrencyLimits::from_str("").unwrap();
+        let invalid = ["debug", "debug=0", "=2", "debug=two"].map(ConcurrencyLimits::from_str);
+
+        // Then
+        assert_eq!(limits.0.len(), 5);
+        assert_eq!(limits.0.get("debug"), Some(&2));
+        assert!(empty.0.is_empty());
+        assert!(invalid.iter().all(Result::is_err));
+    }
+
+    #[test]
+    fn test_concurrency_quota() {
+        // Given
+        let limits = ConcurrencyLimits::from_str("debug=2, eth_call=16, eth=32").unwrap();
+
+        // When
+        let trace = limits.quota("debug_traceTransaction");
+        let call = limits.quota("eth_call");
+        let read = limits.quota("eth_blockNumber");
+        let unlimited = limits.quota("net_version");
+
+        // Then
+        assert_eq!(trace, Some(("debug", 2)));
+        assert_eq!(call, Some(("eth_call", 16)));
+        assert_eq!(read, Some(("eth", 32)));
+        assert_eq!(unlimited, None);
+    }
+}
@@ -0,0 +1,260 @@
+//! RPC middleware serving the deprecated method names with their current handlers.
+//!
+//! The legacy names are declared in [`DEPRECATED_METHODS`], along with the method replacing
+//! them. A call to a legacy name is routed to the current method, so that the other middlewares
+//! (usage, limits, metrics) only ever see the current names, and is logged and counted so that
+//! the remaining callers can be tracked before the alias is removed. No method was renamed yet, so
+//! that the table is empty.
+//!
+//! The envelope of the successful responses is built by jsonrpsee and can't carry extra
+//! members, so that the warning is only returned in the `data.deprecation` field of the error
+//! responses. The successful calls of a legacy name are only visible in the logs and the
+//! `deprecated_method_calls` counter.
+//!
+//! The middleware also accepts the block numbers sent by some clients to the
+//! [`BLOCK_NUMBER_METHODS`] as JSON or decimal numbers rather than hex quantities, rewriting them
+//! as hex quantities.
+
+use jsonrpsee::{
+    server::middleware::rpc::RpcServiceT,
+    types::{ErrorObject, Id, Request},
+    MethodResponse,
+};
+use pin_project_lite::pin_project;
+use serde::Serialize;
+use serde_json::{value::RawValue, Map, Value};
+use std::{
+    borrow::Cow,
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+/// Key of the deprecation warning in the data of the error objects.
+pub const DEPRECATION_KEY: &str = "deprecation";
+
+/// A deprecated method name, served by the method replacing it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeprecatedMethod {
+    /// The legacy name of the method.
+    pub method: &'static str,
+    /// The current name of the method.
+    pub replacement: &'static str,
+}
+
+impl DeprecatedMethod {
+    /// Returns the warning of the deprecation.
+    pub fn message(&self) -> String {
+        format!("{} is deprecated, use {} instead", self.method, self.replacement)
+    }
+}
+
+/// The deprecated method names. An alias must have the same parameters and result as the method
+/// replacing it, otherwise it should be kept as a method of its own.
+pub const DEPRECATED_METHODS: &[DeprecatedMethod] = &[];
+
+/// The methods taking a block number as first parameter, which some clients send as a JSON or a
+/// decimal number.
+pub const BLOCK_NUMBER_METHODS: [&str; 4] = [
+    "eth_getTransactionByBlockNumberAndIndex",
+    "eth_getRawTransactionByBlockNumberAndIndex",
+    "eth_getUncleByBlockNumberAndIndex",
+    "eth_getBlockTransactionCountByNumber",
+];
+
+/// Returns the deprecation of the method, if the method is a legacy name.
+pub fn deprecation(method: &str) -> Option<&'static DeprecatedMethod> {
+    find_deprecation(DEPRECATED_METHODS, method)
+}
+
+fn find_deprecation(methods: &'static [DeprecatedMethod], method: &str) -> Option<&'static DeprecatedMethod> {
+    methods.iter().find(|deprecated| deprecated.method == method)
+}
+
+/// Rewrites the block number of the positional parameters as a hex quantity, if it is a JSON or a
+/// decimal number. Returns `None` if the parameters are left as is.
+fn normalize_block_number(params: &str) -> Option<Box<RawValue>> {
+    let Value::Array(mut params) = serde_json::from_str(params).ok()? else { return None };
+    let number = match params.first()? {
+        Value::Number(number) => number.as_u64()?,
+        Value::String(number) if !number.is_empty() && number.bytes().all(|byte| byte.is_ascii_digit()) => {
+            number.parse().ok()?
+        }
+        _ => return None,
+    };
+    params[0] = Value::String(format!("{number:#x}"));
+    RawValue::from_string(Value::Array(params).to_string()).ok()
+}
+
+/// Deprecation layer.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct DeprecationLayer;
+
+impl<S> tower::Layer<S> for DeprecationLayer {
+    type Service = DeprecationService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        DeprecationService { service: inner }
+    }
+}
+
+/// Deprecation middleware.
+#[derive(Clone, Debug)]
+pub struct DeprecationService<S> {
+    service: S,
+}
+
+impl<'a, S> RpcServiceT<'a> for DeprecationService<S>
+where
+    S: Send + Sync + RpcServiceT<'a>,
+{
+    type Future = ResponseFuture<'a, S::Future>;
+
+    fn call(&self, mut req: Request<'a>) -> Self::Future {
+        if BLOCK_NUMBER_METHODS.contains(&req.method_name()) {
+            if let Some(params) = req.params.as_deref().and_then(|params| normalize_block_number(params.get())) {
+                req.params = Some(Cow::Owned(params));
+            }
+        }
+
+        let deprecated = deprecation(req.method_name());
+        if let Some(deprecated) = deprecated {
+            tracing::warn!(
+                target: "deprecation",
+                monotonic_counter.deprecated_method_calls = 1,
+                method = %deprecated.method,
+                replacement = %deprecated.replacement,
+                "deprecated method called"
+            );
+            req.method = Cow::Borrowed(deprecated.replacement);
+        }
+        let id = req.id();
+
+        ResponseFuture { fut: self.service.call(req), id, deprecated }
+    }
+}
+
+pin_project! {
+    /// Response future adding the deprecation warning to the error responses.
+    pub struct ResponseFuture<'a, F> {
+        #[pin]
+        fut: F,
+        id: Id<'a>,
+        deprecated: Option<&'static DeprecatedMethod>,
+    }
+}
+
+impl<'a, F> std::fmt::Debug for ResponseFuture<'a, F> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("ResponseFuture")
+    }
+}
+
+impl<'a, F: Future<Output = MethodResponse>> Future for ResponseFuture<'a, F> {
+    type Output = F::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+
+        let rp = std::task::ready!(this.fut.poll(cx));
+        let Some(deprecated) = this.deprecated else { return Poll::Ready(rp) };
+        if !rp.is_error() {
+            return Poll::Ready(rp);
+        }
+
+        match error_with_deprecation(rp.as_result(), deprecated) {
+            Some(error) => Poll::Ready(MethodResponse::error(this.id.clone().into_owned(), error)),
+            None => Poll::Ready(rp),
+        }
+    }
+}
+
+/// Rebuilds the error object of a serialized error response with the deprecation warning added to
+/// its data. Returns `None` if the response can't be parsed or if the data of the error isn't an
+/// object.
+fn error_with_deprecation(response: &str, deprecated: &DeprecatedMethod) -> Option<ErrorObject<'static>> {
+    let Value::Object(mut response) = serde_json::from_str(response).ok()? else { return None };
+    let Value::Object(mut error) = response.remove("error")? else { return None };
+
+    let code = i32::try_from(error.get("code")?.as_i64()?).ok()?;
+    let message = error.get("message")?.as_str()?.to_string();
+    let mut data = match error.remove("data") {
+        None | Some(Value::Null) => Map::new(),
+        Some(Value::Object(data)) => data,
+        Some(_) => return None,
+    };
+    let mut warning = serde_json::to_value(deprecated).ok()?;
+    warning.as_object_mut()?.insert("message".to_string(), Value::String(deprecated.message()));
+    data.insert(DEPRECATION_KEY.to_string(), warning);
+
+    Some(ErrorObject::owned(code, message, Some(Value::Object(data))))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const TEST_DEPRECATED_METHODS: &[DeprecatedMethod] =
+        &[DeprecatedMethod { method: "kakarot_legacyMethod", replacement: "kakarot_currentMethod" }];
+
+    #[test]
+    fn test_deprecation() {
+        // When
+        let deprecated = find_deprecation(TEST_DEPRECATED_METHODS, "kakarot_legacyMethod").unwrap();
+
+        // Then
+        assert_eq!(deprecated.replacement, "kakarot_currentMethod");
+        assert!(find_deprecation(TEST_DEPRECATED_METHODS, "kakarot_currentMethod").is_none());
+        assert!(deprecation("eth_blockNumber").is_none());
+    }
+
+    #[test]
+    fn test_deprecated_methods_replacements_are_current() {
+        // The replacement of an alias must not be deprecated itself
+        for deprecated in DEPRECATED_METHODS.iter().chain(TEST_DEPRECATED_METHODS) {
+            assert!(deprecation(deprecated.replacement).is_none(), "{} is deprecated", deprecated.replacement);
+        }
+    }
+
+    #[test]
+    fn test_error_with_deprecation() {
+        // Given
+        let data = json!({"requestId": "0123456789abcdef"});
+        let response = json!({"jsonrpc": "2.0", "id": 1, "error": {"code": -32000, "message": "error", "data": data}});
+        let deprecated = &TEST_DEPRECATED_METHODS[0];
+
+        // When
+        let error = error_with_deprecation(&response.to_string(), deprecated).unwrap();
+
+        // Then
+        let data: Value = serde_json::from_str(error.data().unwrap().get()).unwrap();
+        assert_eq!(
+            data,
+            json!({
+                "requestId": "0123456789abcdef",
+                "deprecation": {
+                    "method": "kakarot_legacyMethod",
+                    "replacement": "kakarot_currentMethod",
+                    "message": "kakarot_legacyMethod is deprecated, use kakarot_currentMethod instead"
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn test_normalize_block_number() {
+        // When
+        let number = normalize_block_number(r#"[12, "0x1"]"#).unwrap();
+        let decimal = normalize_block_number(r#"["255", "0x1"]"#).unwrap();
+
+        // Then
+        assert_eq!(number.get(), r#"["0xc","0x1"]"#);
+        assert_eq!(decimal.get(), r#"["0xff","0x1"]"#);
+        assert!(normalize_block_number(r#"["0xc", "0x1"]"#).is_none());
+        assert!(normalize_block_number(r#"["latest", "0x1"]"#).is_none());
+        assert!(normalize_block_number(r#"[-1, "0x1"]"#).is_none());
+        assert!(normalize_block_number(r#"{"number": 12}"#).is_none());
+    }
+}
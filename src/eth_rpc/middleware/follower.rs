@@ -0,0 +1,95 @@
+//! RPC middleware rejecting the writes on the instances which don't run the background writers.
+//!
+//! When the instances sharing a database are coordinated, only the elected instance runs the
+//! relayers, see [`crate::coordination`]. A transaction accepted by another instance would stay in
+//! its mempool without ever being relayed, so that the writes are rejected until the instance is
+//! elected, for the client (or the load balancer) to send them to the elected instance.
+
+use crate::{coordination::Leadership, providers::eth_provider::error::EthRpcErrorCode};
+use jsonrpsee::{
+    server::middleware::rpc::RpcServiceT,
+    types::{ErrorObject, Request},
+    MethodResponse,
+};
+use std::{future::Future, pin::Pin};
+
+/// The methods adding transactions to the mempool or relaying them, only served by the instance
+/// running the writers.
+pub const WRITE_METHODS: [&str; 6] = [
+    "eth_sendRawTransaction",
+    "kakarot_requestFunds",
+    "kakarot_sendMetaTransaction",
+    "kakarot_deployAccount",
+    "admin_addRelayer",
+    "admin_importPool",
+];
+
+/// Returns true if the method is only served by the instance running the writers.
+pub fn is_write(method: &str) -> bool {
+    WRITE_METHODS.contains(&method)
+}
+
+/// Follower layer.
+#[derive(Clone, Debug)]
+pub struct FollowerLayer {
+    leadership: Leadership,
+}
+
+impl FollowerLayer {
+    pub const fn new(leadership: Leadership) -> Self {
+        Self { leadership }
+    }
+}
+
+impl<S> tower::Layer<S> for FollowerLayer {
+    type Service = FollowerService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        FollowerService { service: inner, leadership: self.leadership.clone() }
+    }
+}
+
+/// Follower middleware.
+#[derive(Clone, Debug)]
+pub struct FollowerService<S> {
+    service: S,
+    leadership: Leadership,
+}
+
+impl<'a, S> RpcServiceT<'a> for FollowerService<S>
+where
+    S: Send + Sync + Clone + RpcServiceT<'a> + 'a,
+    S::Future: 'a,
+{
+    type Future = Pin<Box<dyn Future<Output = MethodResponse> + Send + 'a>>;
+
+    fn call(&self, req: Request<'a>) -> Self::Future {
+        if self.leadership.is_writer() || !is_write(req.method_name()) {
+            return Box::pin(self.service.call(req));
+        }
+
+        tracing::debug!(target: "follower", method = %req.method_name(), "write rejected on a follower instance");
+        let error = ErrorObject::owned(
+            EthRpcErrorCode::ResourceUnavailable as i32,
+            format!("{} is only served by the writer instance, retry on another instance", req.method_name()),
+            None::<()>,
+        );
+        Box::pin(std::future::ready(MethodResponse::error(req.id().into_owned(), error)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_write() {
+        assert!(is_write("eth_sendRawTransaction"));
+        assert!(is_write("kakarot_requestFunds"));
+        assert!(is_write("kakarot_sendMetaTransaction"));
+        assert!(is_write("kakarot_deployAccount"));
+        assert!(!is_write("eth_getBlockByNumber"));
+        assert!(!is_write("eth_call"));
+        assert!(!is_write("txpool_content"));
+    }
+}
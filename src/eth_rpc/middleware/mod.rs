@@ -18,7 +18,39 @@
 
 //! JSON-RPC specific middleware.
 
+/// Admin access middleware.
+pub mod admin_access;
+/// Client IP middleware.
+pub mod client_ip;
+/// Concurrency limit middleware.
+pub mod concurrency;
+/// Deprecated method names middleware.
+pub mod deprecation;
+/// Follower instance middleware.
+pub mod follower;
 /// Grafana metrics middleware.
 pub mod metrics;
+/// Allowed origins middleware.
+pub mod origins;
+/// Public tracing tier middleware.
+pub mod public_trace;
+/// Replay mode middleware.
+pub mod replay;
+/// Request id middleware.
+pub mod request_id;
+/// Server saturation metrics middleware.
+pub mod saturation;
+/// API key usage accounting middleware.
+pub mod usage;
+pub use admin_access::{AdminAccessLayer, ApiKeyLayer};
+pub use client_ip::{ClientIpLayer, PeerAddrService};
 /// Rate limit middleware.
+pub use concurrency::ConcurrencyLayer;
+pub use deprecation::DeprecationLayer;
+pub use follower::FollowerLayer;
 pub use metrics::*;
+pub use origins::{AllowedOriginsLayer, OriginLayer};
+pub use public_trace::PublicTraceLayer;
+pub use replay::ReplayLayer;
+pub use request_id::RequestIdLayer;
+pub use usage::UsageLayer;
@@ -0,0 +1,305 @@
+//! Allowed origins of the calls, per namespace or method.
+//!
+//! The `Origin` header of a request, set by the browsers, is recorded by the HTTP middleware.
+//! The RPC middleware then rejects the calls whose origin isn't allowed for their namespace
+//! (e.g. `eth` open to all origins, `debug` only to the internal dashboards). The policy of a
+//! method takes precedence over the one of its namespace, which takes precedence over the
+//! default policy `*`. The namespaces without a policy are open to all origins.
+//!
+//! The requests without an `Origin` header, which aren't sent by a browser, are never rejected:
+//! the origin isn't a credential and the non browser clients should be restricted with the API
+//! keys instead.
+
+use crate::providers::eth_provider::error::EthRpcErrorCode;
+use hyper::{
+    header::{HeaderMap, HeaderValue},
+    Request as HttpRequest,
+};
+use jsonrpsee::{
+    server::middleware::rpc::RpcServiceT,
+    types::{ErrorObject, Request},
+    MethodResponse,
+};
+use std::{
+    collections::{HashMap, HashSet},
+    future::Future,
+    pin::Pin,
+    str::FromStr,
+    sync::Arc,
+    task::{Context, Poll},
+};
+use thiserror::Error;
+use tower_http::cors::AllowOrigin;
+
+/// The key of the default policy, applying to the namespaces without a policy of their own.
+const DEFAULT_POLICY_KEY: &str = "*";
+
+/// Error returned when parsing invalid allowed origins.
+#[derive(Debug, Error, PartialEq, Eq)]
+#[error("invalid allowed origins {0}, expected <namespace or method>=<origin>|<origin>")]
+pub struct InvalidAllowedOrigins(String);
+
+/// The origin of a request, from its `Origin` header.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct RequestOrigin(pub String);
+
+impl RequestOrigin {
+    /// Returns the origin of the request from its headers.
+    pub fn from_headers(headers: &HeaderMap) -> Option<Self> {
+        headers.get("origin").and_then(|value| value.to_str().ok()).map(|origin| Self(origin.trim().to_lowercase()))
+    }
+}
+
+/// The origins allowed to call a namespace or a method.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OriginPolicy {
+    /// All origins are allowed.
+    Any,
+    /// Only the listed origins are allowed.
+    Only(HashSet<String>),
+}
+
+impl OriginPolicy {
+    /// Returns true if the origin is allowed by the policy.
+    pub fn allows(&self, origin: &RequestOrigin) -> bool {
+        match self {
+            Self::Any => true,
+            Self::Only(origins) => origins.contains(&origin.0),
+        }
+    }
+}
+
+/// The origins allowed per namespace (e.g. `debug`) or per method (e.g. `eth_sendRawTransaction`).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AllowedOrigins(HashMap<String, OriginPolicy>);
+
+impl AllowedOrigins {
+    /// Returns true if no policy is configured, all the origins being allowed.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Returns the key and the policy applying to the method, if any.
+    fn policy(&self, method: &str) -> Option<(&str, &OriginPolicy)> {
+        let namespace = method.split_once('_').map_or(method, |(namespace, _)| namespace);
+        [method, namespace, DEFAULT_POLICY_KEY]
+            .into_iter()
+            .find_map(|key| self.0.get_key_value(key))
+            .map(|(key, policy)| (key.as_str(), policy))
+    }
+
+    /// Returns true if the origin, if any, is allowed to call the method.
+    pub fn is_allowed(&self, method: &str, origin: Option<&RequestOrigin>) -> bool {
+        origin.map_or(true, |origin| self.policy(method).map_or(true, |(_, policy)| policy.allows(origin)))
+    }
+
+    /// Returns the origins allowed by the CORS layer: a browser doesn't tell the called method in
+    /// the preflight request, so that the CORS layer allows every origin allowed by at least one
+    /// policy, the calls being filtered by the RPC middleware.
+    pub fn cors_origin(&self) -> AllowOrigin {
+        let restricted = self.0.contains_key(DEFAULT_POLICY_KEY) && !self.0.values().any(|p| *p == OriginPolicy::Any);
+        if !restricted {
+            return AllowOrigin::any();
+        }
+        let origins = self
+            .0
+            .values()
+            .filter_map(|policy| match policy {
+                OriginPolicy::Only(origins) => Some(origins),
+                OriginPolicy::Any => None,
+            })
+            .flatten()
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .filter_map(|origin| HeaderValue::from_str(origin).ok())
+            .collect::<Vec<_>>();
+        AllowOrigin::list(origins)
+    }
+}
+
+impl FromStr for AllowedOrigins {
+    type Err = InvalidAllowedOrigins;
+
+    /// Parses a comma separated list of `<namespace or method>=<origin>|<origin>`, e.g.
+    /// `eth=*,debug=https://ops.kakarot.org|http://localhost:3000`, `*` allowing all origins.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.replace(' ', "")
+            .split(',')
+            .filter(|policy| !policy.is_empty())
+            .map(|policy| {
+                let invalid = || InvalidAllowedOrigins(policy.to_string());
+                let (key, origins) = policy.split_once('=').filter(|(key, _)| !key.is_empty()).ok_or_else(invalid)?;
+                if origins.split('|').any(|origin| origin == "*") {
+                    return Ok((key.to_string(), OriginPolicy::Any));
+                }
+                let origins = origins
+                    .split('|')
+                    .map(|origin| {
+                        HeaderValue::from_str(origin)
+                            .ok()
+                            .filter(|_| !origin.is_empty())
+                            .map(|_| origin.to_lowercase())
+                            .ok_or_else(invalid)
+                    })
+                    .collect::<Result<_, _>>()?;
+                Ok((key.to_string(), OriginPolicy::Only(origins)))
+            })
+            .collect::<Result<_, _>>()
+            .map(Self)
+    }
+}
+
+/// Request origin layer.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct OriginLayer;
+
+impl<S> tower::Layer<S> for OriginLayer {
+    type Service = OriginService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        OriginService { service: inner }
+    }
+}
+
+/// Request origin middleware.
+#[derive(Clone, Debug)]
+pub struct OriginService<S> {
+    service: S,
+}
+
+impl<S, B> tower::Service<HttpRequest<B>> for OriginService<S>
+where
+    S: tower::Service<HttpRequest<B>>,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = S::Future;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.service.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut request: HttpRequest<B>) -> Self::Future {
+        if let Some(origin) = RequestOrigin::from_headers(request.headers()) {
+            request.extensions_mut().insert(origin);
+        }
+        self.service.call(request)
+    }
+}
+
+/// Allowed origins layer.
+#[derive(Clone, Debug)]
+pub struct AllowedOriginsLayer {
+    origins: Arc<AllowedOrigins>,
+}
+
+impl AllowedOriginsLayer {
+    /// Creates a new layer restricting the calls to their allowed origins.
+    pub fn new(origins: AllowedOrigins) -> Self {
+        Self { origins: Arc::new(origins) }
+    }
+}
+
+impl<S> tower::Layer<S> for AllowedOriginsLayer {
+    type Service = AllowedOriginsService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        AllowedOriginsService { service: inner, origins: Arc::clone(&self.origins) }
+    }
+}
+
+/// Allowed origins middleware.
+#[derive(Clone, Debug)]
+pub struct AllowedOriginsService<S> {
+    service: S,
+    origins: Arc<AllowedOrigins>,
+}
+
+impl<'a, S> RpcServiceT<'a> for AllowedOriginsService<S>
+where
+    S: Send + Sync + Clone + RpcServiceT<'a> + 'a,
+    S::Future: 'a,
+{
+    type Future = Pin<Box<dyn Future<Output = MethodResponse> + Send + 'a>>;
+
+    fn call(&self, req: Request<'a>) -> Self::Future {
+        let origin = req.extensions().get::<RequestOrigin>();
+        if self.origins.is_allowed(req.method_name(), origin) {
+            return Box::pin(self.service.call(req));
+        }
+
+        let origin = origin.map(|origin| origin.0.clone()).unwrap_or_default();
+        tracing::warn!(
+            target: "allowed_origins",
+            monotonic_counter.origin_denied_calls = 1,
+            method = %req.method_name(),
+            %origin,
+            "call denied, origin not allowed"
+        );
+        let error = ErrorObject::owned(
+            EthRpcErrorCode::InvalidRequest as i32,
+            format!("unauthorized, origin {origin} not allowed to call {}", req.method_name()),
+            None::<()>,
+        );
+        Box::pin(std::future::ready(MethodResponse::error(req.id().into_owned(), error)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn origin(origin: &str) -> RequestOrigin {
+        RequestOrigin(origin.to_string())
+    }
+
+    #[test]
+    fn test_parse_allowed_origins() {
+        // When
+        let origins = AllowedOrigins::from_str("eth=*, debug=https://ops.kakarot.org|http://localhost:3000").unwrap();
+        let empty = AllowedOrigins::from_str("").unwrap();
+        let invalid = ["debug", "=*", "debug=", "debug=https://ops.kakarot.org|"].map(AllowedOrigins::from_str);
+
+        // Then
+        assert_eq!(origins.0.get("eth"), Some(&OriginPolicy::Any));
+        assert_eq!(
+            origins.0.get("debug"),
+            Some(&OriginPolicy::Only(HashSet::from([
+                "https://ops.kakarot.org".to_string(),
+                "http://localhost:3000".to_string()
+            ])))
+        );
+        assert!(empty.is_empty());
+        assert!(invalid.iter().all(Result::is_err));
+    }
+
+    #[test]
+    fn test_is_allowed() {
+        // Given
+        let origins =
+            AllowedOrigins::from_str("*=https://app.kakarot.org,debug=https://ops.kakarot.org,eth_call=*").unwrap();
+
+        // When
+        // Then
+        assert!(origins.is_allowed("debug_traceTransaction", Some(&origin("https://ops.kakarot.org"))));
+        assert!(!origins.is_allowed("debug_traceTransaction", Some(&origin("https://app.kakarot.org"))));
+        assert!(origins.is_allowed("eth_blockNumber", Some(&origin("https://app.kakarot.org"))));
+        assert!(!origins.is_allowed("eth_blockNumber", Some(&origin("https://evil.org"))));
+        assert!(origins.is_allowed("eth_call", Some(&origin("https://evil.org"))));
+        assert!(origins.is_allowed("debug_traceTransaction", None));
+    }
+
+    #[test]
+    fn test_request_origin_from_headers() {
+        // Given
+        let mut headers = HeaderMap::new();
+        headers.insert("origin", HeaderValue::from_static("https://App.Kakarot.org"));
+
+        // When
+        let origin = RequestOrigin::from_headers(&headers);
+
+        // Then
+        assert_eq!(origin, Some(RequestOrigin("https://app.kakarot.org".to_string())));
+        assert_eq!(RequestOrigin::from_headers(&HeaderMap::new()), None);
+    }
+}
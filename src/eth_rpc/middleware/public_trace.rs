@@ -0,0 +1,255 @@
+//! RPC middleware serving the tracing endpoints to the public users in a degraded mode.
+//!
+//! The traces requested without an API key of the node are computed with the lower limits of
+//! the public tier (see [`PUBLIC_TRACE_LIMITS`](crate::tracing::limits::PUBLIC_TRACE_LIMITS)).
+//! The heavy traces, covering whole blocks, are queued and executed a few at a time: a request
+//! is rejected if the queue is full or if it can't start before the queue timeout, along with
+//! its position in the queue, so that the public users get some tracing capability without
+//! endangering the node.
+
+use crate::{
+    eth_rpc::middleware::admin_access::{AdminApiKeys, ApiKey},
+    providers::eth_provider::error::EthRpcErrorCode,
+    tracing::limits::with_public_tier,
+};
+use jsonrpsee::{
+    server::middleware::rpc::RpcServiceT,
+    types::{ErrorObject, Request},
+    MethodResponse,
+};
+use serde::Serialize;
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+use tokio::sync::Semaphore;
+
+/// The tracing methods covering whole blocks, queued in the public tier.
+const HEAVY_TRACE_METHODS: [&str; 3] = ["debug_traceBlockByNumber", "debug_traceBlockByHash", "trace_block"];
+
+/// The tracing methods which aren't served in the public tier.
+const PRIVATE_TRACE_METHODS: [&str; 1] = ["debug_subscribeTraceBlock"];
+
+/// The configuration of the public tracing tier.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PublicTraceConfig {
+    /// Maximum number of heavy public traces computed concurrently.
+    pub concurrency: usize,
+    /// Maximum number of heavy public traces waiting in the queue.
+    pub queue_size: u64,
+    /// Duration a heavy public trace waits in the queue before being rejected.
+    pub queue_timeout: Duration,
+}
+
+impl Default for PublicTraceConfig {
+    fn default() -> Self {
+        Self { concurrency: 1, queue_size: 8, queue_timeout: Duration::from_secs(30) }
+    }
+}
+
+/// The position of a rejected trace in the public queue, returned in the data of the error.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QueuePosition {
+    /// The position of the request in the queue, 1 being the next request to start.
+    pub queue_position: u64,
+    /// The number of requests waiting in the queue.
+    pub queue_length: u64,
+}
+
+/// The FIFO queue of the heavy public traces.
+#[derive(Debug)]
+pub struct TraceQueue {
+    permits: Arc<Semaphore>,
+    enqueued: AtomicU64,
+    dequeued: AtomicU64,
+    size: u64,
+}
+
+impl TraceQueue {
+    /// Creates a new queue with the given number of concurrent traces and waiting requests.
+    pub fn new(concurrency: usize, size: u64) -> Self {
+        Self {
+            permits: Arc::new(Semaphore::new(concurrency.max(1))),
+            enqueued: AtomicU64::new(0),
+            dequeued: AtomicU64::new(0),
+            size,
+        }
+    }
+
+    /// Returns the number of requests waiting in the queue.
+    pub fn len(&self) -> u64 {
+        self.enqueued.load(Ordering::Acquire).saturating_sub(self.dequeued.load(Ordering::Acquire))
+    }
+
+    /// Returns true if no request is waiting in the queue.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Waits for the turn of the request, at most `timeout`. Returns the position of the request
+    /// if the queue is full or if the request timed out.
+    pub async fn wait(&self, timeout: Duration) -> Result<tokio::sync::OwnedSemaphorePermit, QueuePosition> {
+        // The semaphore is fair, the requests start in the order of their tickets
+        if let Ok(permit) = Arc::clone(&self.permits).try_acquire_owned() {
+            if self.is_empty() {
+                return Ok(permit);
+            }
+        }
+
+        if self.len() >= self.size {
+            return Err(QueuePosition { queue_position: self.len() + 1, queue_length: self.len() });
+        }
+        let ticket = self.enqueued.fetch_add(1, Ordering::AcqRel) + 1;
+        let permit = tokio::time::timeout(timeout, Arc::clone(&self.permits).acquire_owned()).await;
+        let position = ticket.saturating_sub(self.dequeued.fetch_add(1, Ordering::AcqRel));
+
+        match permit {
+            Ok(Ok(permit)) => Ok(permit),
+            _ => Err(QueuePosition { queue_position: position, queue_length: self.len() }),
+        }
+    }
+}
+
+/// Public tracing tier layer.
+#[derive(Clone, Debug)]
+pub struct PublicTraceLayer {
+    keys: Arc<AdminApiKeys>,
+    queue: Arc<TraceQueue>,
+    queue_timeout: Duration,
+}
+
+impl PublicTraceLayer {
+    /// Creates a new layer serving the traces of the requests without one of the API keys in the
+    /// public tier.
+    pub fn new(config: PublicTraceConfig, keys: AdminApiKeys) -> Self {
+        Self {
+            keys: Arc::new(keys),
+            queue: Arc::new(TraceQueue::new(config.concurrency, config.queue_size)),
+            queue_timeout: config.queue_timeout,
+        }
+    }
+}
+
+impl<S> tower::Layer<S> for PublicTraceLayer {
+    type Service = PublicTraceService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        PublicTraceService { service: inner, layer: self.clone() }
+    }
+}
+
+/// Public tracing tier middleware.
+#[derive(Clone, Debug)]
+pub struct PublicTraceService<S> {
+    service: S,
+    layer: PublicTraceLayer,
+}
+
+/// Returns true if the method computes traces.
+fn is_trace_method(method: &str) -> bool {
+    method.starts_with("debug_trace") || method.starts_with("trace_") || method == "debug_diffTransaction"
+}
+
+impl<'a, S> RpcServiceT<'a> for PublicTraceService<S>
+where
+    S: Send + Sync + Clone + RpcServiceT<'a> + 'a,
+    S::Future: 'a,
+{
+    type Future = Pin<Box<dyn Future<Output = MethodResponse> + Send + 'a>>;
+
+    fn call(&self, req: Request<'a>) -> Self::Future {
+        let method = req.method_name();
+        let is_public = req.extensions().get::<ApiKey>().and_then(|key| self.layer.keys.role(key)).is_none();
+        if !is_public || !(is_trace_method(method) || PRIVATE_TRACE_METHODS.contains(&method)) {
+            return Box::pin(self.service.call(req));
+        }
+
+        if PRIVATE_TRACE_METHODS.contains(&method) {
+            let error = ErrorObject::owned(
+                EthRpcErrorCode::InvalidRequest as i32,
+                format!("unauthorized, {method} requires an API key"),
+                None::<()>,
+            );
+            return Box::pin(std::future::ready(MethodResponse::error(req.id().into_owned(), error)));
+        }
+
+        if !HEAVY_TRACE_METHODS.contains(&method) {
+            return Box::pin(with_public_tier(self.service.call(req)));
+        }
+
+        let service = self.service.clone();
+        let queue = Arc::clone(&self.layer.queue);
+        let queue_timeout = self.layer.queue_timeout;
+        Box::pin(async move {
+            // The permit is held until the trace completes
+            let _permit = match queue.wait(queue_timeout).await {
+                Ok(permit) => permit,
+                Err(position) => {
+                    tracing::warn!(
+                        target: "public_trace",
+                        monotonic_counter.public_trace_rejected_calls = 1,
+                        method = %req.method_name(),
+                        queue_position = position.queue_position,
+                        "public trace rejected, queue full or timed out"
+                    );
+                    let error = ErrorObject::owned(
+                        EthRpcErrorCode::RequestLimitExceeded as i32,
+                        format!(
+                            "public trace queue busy at position {}, retry later or use an API key",
+                            position.queue_position
+                        ),
+                        Some(position),
+                    );
+                    return MethodResponse::error(req.id().into_owned(), error);
+                }
+            };
+            with_public_tier(service.call(req)).await
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_trace_method() {
+        assert!(is_trace_method("debug_traceTransaction"));
+        assert!(is_trace_method("trace_block"));
+        assert!(is_trace_method("debug_diffTransaction"));
+        assert!(!is_trace_method("debug_getRawBlock"));
+        assert!(!is_trace_method("eth_call"));
+    }
+
+    #[tokio::test]
+    async fn test_trace_queue() {
+        // Given
+        let queue = Arc::new(TraceQueue::new(1, 1));
+        let running = queue.wait(Duration::from_secs(1)).await.unwrap();
+
+        // When
+        let waiting = tokio::spawn({
+            let queue = Arc::clone(&queue);
+            async move { queue.wait(Duration::from_millis(200)).await.map(drop) }
+        });
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        let rejected = queue.wait(Duration::from_secs(1)).await.map(drop);
+
+        // Then
+        assert_eq!(rejected, Err(QueuePosition { queue_position: 2, queue_length: 1 }));
+        assert_eq!(waiting.await.unwrap(), Err(QueuePosition { queue_position: 1, queue_length: 0 }));
+
+        // When
+        drop(running);
+
+        // Then
+        assert!(queue.wait(Duration::from_secs(1)).await.is_ok());
+        assert!(queue.is_empty());
+    }
+}
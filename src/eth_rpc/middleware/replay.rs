@@ -0,0 +1,112 @@
+//! RPC middleware restricting the node to the methods served from the database, in replay mode.
+//!
+//! In replay mode, the node serves a snapshot of the database exported from another node, without
+//! accessing Starknet, so that a bug report is reproduced against the exact state it was observed
+//! on. The methods reading Starknet or the mempool, and the methods writing, are rejected, as they
+//! would answer from the live chain rather than from the snapshot.
+
+use crate::providers::eth_provider::error::EthRpcErrorCode;
+use jsonrpsee::{
+    server::middleware::rpc::RpcServiceT,
+    types::{ErrorObject, Request},
+    MethodResponse,
+};
+use std::{future::Future, pin::Pin};
+
+/// The methods served from the database only, available in replay mode.
+pub const REPLAY_METHODS: [&str; 31] = [
+    "eth_blockNumber",
+    "eth_chainId",
+    "eth_getBlockByHash",
+    "eth_getBlockByNumber",
+    "eth_getBlockTransactionCountByHash",
+    "eth_getBlockTransactionCountByNumber",
+    "eth_getUncleCountByBlockHash",
+    "eth_getUncleCountByBlockNumber",
+    "eth_getUncleByBlockHashAndIndex",
+    "eth_getUncleByBlockNumberAndIndex",
+    "eth_getTransactionByHash",
+    "eth_getTransactionByBlockHashAndIndex",
+    "eth_getTransactionByBlockNumberAndIndex",
+    "eth_getTransactionReceipt",
+    "eth_getBlockReceipts",
+    "eth_getLogs",
+    "eth_feeHistory",
+    "debug_getRawHeader",
+    "debug_getRawBlock",
+    "debug_getRawTransaction",
+    "debug_getRawTransactions",
+    "debug_getRawReceipts",
+    "kakarot_getConfig",
+    "kakarot_getAddressHistory",
+    "kakarot_getBlockByStarknetHash",
+    "kakarot_queryLogs",
+    "net_version",
+    "net_health",
+    "web3_clientVersion",
+    "web3_sha3",
+    "rpc.discover",
+];
+
+/// Returns true if the method is served from the database only.
+pub fn is_replayable(method: &str) -> bool {
+    REPLAY_METHODS.contains(&method)
+}
+
+/// Replay layer.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ReplayLayer;
+
+impl<S> tower::Layer<S> for ReplayLayer {
+    type Service = ReplayService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        ReplayService { service: inner }
+    }
+}
+
+/// Replay middleware.
+#[derive(Clone, Debug)]
+pub struct ReplayService<S> {
+    service: S,
+}
+
+impl<'a, S> RpcServiceT<'a> for ReplayService<S>
+where
+    S: Send + Sync + Clone + RpcServiceT<'a> + 'a,
+    S::Future: 'a,
+{
+    type Future = Pin<Box<dyn Future<Output = MethodResponse> + Send + 'a>>;
+
+    fn call(&self, req: Request<'a>) -> Self::Future {
+        if is_replayable(req.method_name()) {
+            return Box::pin(self.service.call(req));
+        }
+
+        tracing::debug!(target: "replay", method = %req.method_name(), "call rejected in replay mode");
+        let error = ErrorObject::owned(
+            EthRpcErrorCode::InvalidRequest as i32,
+            format!("{} is unavailable in replay mode, only the database is served", req.method_name()),
+            None::<()>,
+        );
+        Box::pin(std::future::ready(MethodResponse::error(req.id().into_owned(), error)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_replayable() {
+        assert!(is_replayable("eth_getBlockByNumber"));
+        assert!(is_replayable("eth_getLogs"));
+        assert!(is_replayable("kakarot_queryLogs"));
+        assert!(is_replayable("net_health"));
+        assert!(!is_replayable("eth_sendRawTransaction"));
+        assert!(!is_replayable("eth_call"));
+        assert!(!is_replayable("eth_getBalance"));
+        assert!(!is_replayable("txpool_content"));
+        assert!(!is_replayable("admin_addRelayer"));
+    }
+}
@@ -0,0 +1,171 @@
+//! RPC middleware assigning a unique id to each RPC call.
+//!
+//! The id is attached to the tracing span of the call, so that it appears in the logs and
+//! traces, and echoed back to the user in the `data.requestId` field of error responses.
+
+use jsonrpsee::{
+    server::middleware::rpc::RpcServiceT,
+    types::{ErrorObject, Id, Request},
+    MethodResponse,
+};
+use pin_project_lite::pin_project;
+use serde_json::{Map, Value};
+use std::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+};
+use tracing::{instrument::Instrumented, Instrument};
+
+/// Key of the request id in the data of the error objects.
+pub const REQUEST_ID_KEY: &str = "requestId";
+
+/// Generates a new random request id, formatted as 16 hexadecimal characters.
+pub fn generate_request_id() -> String {
+    format!("{:016x}", rand::random::<u64>())
+}
+
+/// Request id layer.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct RequestIdLayer;
+
+impl<S> tower::Layer<S> for RequestIdLayer {
+    type Service = RequestIdService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        RequestIdService { service: inner }
+    }
+}
+
+/// Request id middleware.
+#[derive(Clone, Debug)]
+pub struct RequestIdService<S> {
+    service: S,
+}
+
+impl<'a, S> RpcServiceT<'a> for RequestIdService<S>
+where
+    S: Send + Sync + RpcServiceT<'a>,
+{
+    type Future = ResponseFuture<'a, S::Future>;
+
+    fn call(&self, req: Request<'a>) -> Self::Future {
+        let request_id = generate_request_id();
+        let span = tracing::info_span!("rpc_call", request_id = %request_id, method = %req.method_name());
+        let id = req.id();
+
+        ResponseFuture { fut: self.service.call(req).instrument(span), id, request_id }
+    }
+}
+
+pin_project! {
+    /// Response future adding the request id to the error responses.
+    pub struct ResponseFuture<'a, F> {
+        #[pin]
+        fut: Instrumented<F>,
+        id: Id<'a>,
+        request_id: String,
+    }
+}
+
+impl<'a, F> std::fmt::Debug for ResponseFuture<'a, F> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("ResponseFuture")
+    }
+}
+
+impl<'a, F: Future<Output = MethodResponse>> Future for ResponseFuture<'a, F> {
+    type Output = F::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+
+        let rp = std::task::ready!(this.fut.poll(cx));
+        if !rp.is_error() {
+            return Poll::Ready(rp);
+        }
+
+        tracing::debug!(request_id = %this.request_id, "rpc call failed");
+        match error_with_request_id(rp.as_result(), this.request_id) {
+            Some(error) => Poll::Ready(MethodResponse::error(this.id.clone().into_owned(), error)),
+            None => Poll::Ready(rp),
+        }
+    }
+}
+
+/// Rebuilds the error object of a serialized error response with the request id added to its
+/// data. Returns `None` if the response can't be parsed or if the data of the error isn't an
+/// object (e.g. the revert data of `eth_call`), as clients rely on its format.
+fn error_with_request_id(response: &str, request_id: &str) -> Option<ErrorObject<'static>> {
+    let Value::Object(mut response) = serde_json::from_str(response).ok()? else { return None };
+    let Value::Object(mut error) = response.remove("error")? else { return None };
+
+    let code = i32::try_from(error.get("code")?.as_i64()?).ok()?;
+    let message = error.get("message")?.as_str()?.to_string();
+    let mut data = match error.remove("data") {
+        None | Some(Value::Null) => Map::new(),
+        Some(Value::Object(data)) => data,
+        Some(_) => return None,
+    };
+    data.insert(REQUEST_ID_KEY.to_string(), Value::String(request_id.to_string()));
+
+    Some(ErrorObject::owned(code, message, Some(Value::Object(data))))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_error_with_request_id_without_data() {
+        // Given
+        let response = json!({"jsonrpc": "2.0", "id": 1, "error": {"code": -32601, "message": "Method not found"}});
+
+        // When
+        let error = error_with_request_id(&response.to_string(), "0123456789abcdef").unwrap();
+
+        // Then
+        assert_eq!(error.code(), -32601);
+        assert_eq!(error.message(), "Method not found");
+        assert_eq!(error.data().unwrap().get(), r#"{"requestId":"0123456789abcdef"}"#);
+    }
+
+    #[test]
+    fn test_error_with_request_id_merges_object_data() {
+        // Given
+        let response =
+            json!({"jsonrpc": "2.0", "id": 1, "error": {"code": -32000, "message": "error", "data": {"limit": 10}}});
+
+        // When
+        let error = error_with_request_id(&response.to_string(), "0123456789abcdef").unwrap();
+
+        // Then
+        let data: Value = serde_json::from_str(error.data().unwrap().get()).unwrap();
+        assert_eq!(data, json!({"limit": 10, "requestId": "0123456789abcdef"}));
+    }
+
+    #[test]
+    fn test_error_with_request_id_keeps_revert_data() {
+        // Given
+        let response =
+            json!({"jsonrpc": "2.0", "id": 1, "error": {"code": 3, "message": "execution reverted", "data": "0x01"}});
+
+        // When
+        let error = error_with_request_id(&response.to_string(), "0123456789abcdef");
+
+        // Then
+        assert!(error.is_none());
+    }
+
+    #[test]
+    fn test_generate_request_id() {
+        // When
+        let first = generate_request_id();
+        let second = generate_request_id();
+
+        // Then
+        assert_eq!(first.len(), 16);
+        assert_ne!(first, second);
+    }
+}
@@ -0,0 +1,280 @@
+//! Middleware exposing the saturation of the RPC server as prometheus gauges.
+//!
+//! The HTTP layer counts the requests received and not answered yet, and records the instant
+//! each request was received. The RPC layer counts the calls being executed, and observes the
+//! time each call waited between the receipt of its request and the start of its execution. A
+//! background task samples the tokio runtime: the number of workers and alive tasks, and the
+//! delay before a spawned task is polled.
+//!
+//! A growing queue time or scheduling delay with a stable number of calls in flight points to
+//! the starvation of the server threads, whereas slow downstream calls (e.g. to the database or
+//! to Starknet) show as a growing number of calls in flight with a low queue time.
+
+use crate::prometheus_handler::{register, Gauge, Histogram, HistogramOpts, PrometheusError, Registry, I64, U64};
+use hyper::{header::HeaderMap, Request as HttpRequest};
+use jsonrpsee::{server::middleware::rpc::RpcServiceT, types::Request};
+use pin_project_lite::pin_project;
+use std::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+    time::{Duration, Instant},
+};
+
+/// Histogram buckets of the queue time, in microseconds.
+const QUEUE_TIME_BUCKETS: [f64; 10] =
+    [10.0, 50.0, 100.0, 500.0, 1_000.0, 5_000.0, 10_000.0, 50_000.0, 100_000.0, 1_000_000.0];
+
+/// The instant the HTTP request of a call was received.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ReceivedAt(pub Instant);
+
+/// The saturation metrics of the RPC server.
+#[derive(Debug, Clone)]
+pub struct SaturationMetrics {
+    /// Number of HTTP requests received and not answered yet.
+    http_requests_in_flight: Gauge<I64>,
+    /// Number of calls being executed.
+    calls_in_flight: Gauge<I64>,
+    /// Time [μs] between the receipt of the HTTP request and the start of the call.
+    call_queue_time: Histogram,
+    /// Number of worker threads of the runtime.
+    runtime_workers: Gauge<U64>,
+    /// Number of alive tasks of the runtime.
+    runtime_alive_tasks: Gauge<U64>,
+    /// Delay [μs] before a task spawned on the runtime is polled.
+    runtime_scheduling_delay: Gauge<U64>,
+}
+
+impl SaturationMetrics {
+    /// Creates the metrics and registers them in the registry.
+    pub fn new(registry: &Registry) -> Result<Self, PrometheusError> {
+        Ok(Self {
+            http_requests_in_flight: register(
+                Gauge::new("eth_rpc_http_requests_in_flight", "Number of HTTP requests received and not answered")?,
+                registry,
+            )?,
+            calls_in_flight: register(
+                Gauge::new("eth_rpc_calls_in_flight", "Number of RPC calls executing")?,
+                registry,
+            )?,
+            call_queue_time: register(
+                Histogram::with_opts(
+                    HistogramOpts::new(
+                        "eth_rpc_call_queue_time",
+                        "Time [μs] between the receipt of the HTTP request and the start of the RPC call",
+                    )
+                    .buckets(QUEUE_TIME_BUCKETS.to_vec()),
+                )?,
+                registry,
+            )?,
+            runtime_workers: register(
+                Gauge::new("tokio_runtime_workers", "Number of runtime worker threads")?,
+                registry,
+            )?,
+            runtime_alive_tasks: register(
+                Gauge::new("tokio_runtime_alive_tasks", "Number of alive tasks of the runtime")?,
+                registry,
+            )?,
+            runtime_scheduling_delay: register(
+                Gauge::new("tokio_runtime_scheduling_delay", "Delay [μs] before a spawned task is polled")?,
+                registry,
+            )?,
+        })
+    }
+
+    /// Returns the layer counting the HTTP requests in flight.
+    pub fn http_layer(&self) -> SaturationHttpLayer {
+        SaturationHttpLayer { metrics: self.clone() }
+    }
+
+    /// Returns the layer counting the calls in flight and observing their queue time.
+    pub fn rpc_layer(&self) -> SaturationLayer {
+        SaturationLayer { metrics: self.clone() }
+    }
+
+    /// Samples the runtime every `interval`, never returns.
+    pub async fn sample_runtime(self, interval: Duration) {
+        let mut interval = tokio::time::interval(interval);
+        loop {
+            interval.tick().await;
+
+            let runtime = tokio::runtime::Handle::current().metrics();
+            self.runtime_workers.set(runtime.num_workers() as u64);
+            self.runtime_alive_tasks.set(runtime.num_alive_tasks() as u64);
+
+            let spawned_at = Instant::now();
+            if let Ok(delay) = tokio::spawn(async move { spawned_at.elapsed() }).await {
+                self.runtime_scheduling_delay.set(delay.as_micros() as u64);
+            }
+        }
+    }
+}
+
+/// Decrements the gauge when dropped.
+#[derive(Debug)]
+struct InFlightGuard(Gauge<I64>);
+
+impl InFlightGuard {
+    fn new(gauge: &Gauge<I64>) -> Self {
+        gauge.inc();
+        Self(gauge.clone())
+    }
+}
+
+impl Drop for InFlightGuard {
+    fn drop(&mut self) {
+        self.0.dec();
+    }
+}
+
+pin_project! {
+    /// Future of a request or a call counted as in flight until it completes or is dropped.
+    pub struct InFlightFuture<F> {
+        #[pin]
+        fut: F,
+        _guard: InFlightGuard,
+    }
+}
+
+impl<F> std::fmt::Debug for InFlightFuture<F> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("InFlightFuture")
+    }
+}
+
+impl<F: Future> Future for InFlightFuture<F> {
+    type Output = F::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        self.project().fut.poll(cx)
+    }
+}
+
+/// Returns true if the request upgrades the connection to a WebSocket, whose calls are received
+/// long after the request.
+fn is_websocket_upgrade(headers: &HeaderMap) -> bool {
+    headers
+        .get("upgrade")
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.eq_ignore_ascii_case("websocket"))
+}
+
+/// Saturation HTTP layer.
+#[derive(Clone, Debug)]
+pub struct SaturationHttpLayer {
+    metrics: SaturationMetrics,
+}
+
+impl<S> tower::Layer<S> for SaturationHttpLayer {
+    type Service = SaturationHttpService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        SaturationHttpService { service: inner, metrics: self.metrics.clone() }
+    }
+}
+
+/// Saturation HTTP middleware.
+#[derive(Clone, Debug)]
+pub struct SaturationHttpService<S> {
+    service: S,
+    metrics: SaturationMetrics,
+}
+
+impl<S, B> tower::Service<HttpRequest<B>> for SaturationHttpService<S>
+where
+    S: tower::Service<HttpRequest<B>>,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = InFlightFuture<S::Future>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.service.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut request: HttpRequest<B>) -> Self::Future {
+        let guard = InFlightGuard::new(&self.metrics.http_requests_in_flight);
+        if !is_websocket_upgrade(request.headers()) {
+            request.extensions_mut().insert(ReceivedAt(Instant::now()));
+        }
+        InFlightFuture { fut: self.service.call(request), _guard: guard }
+    }
+}
+
+/// Saturation RPC layer.
+#[derive(Clone, Debug)]
+pub struct SaturationLayer {
+    metrics: SaturationMetrics,
+}
+
+impl<S> tower::Layer<S> for SaturationLayer {
+    type Service = SaturationService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        SaturationService { service: inner, metrics: self.metrics.clone() }
+    }
+}
+
+/// Saturation RPC middleware.
+#[derive(Clone, Debug)]
+pub struct SaturationService<S> {
+    service: S,
+    metrics: SaturationMetrics,
+}
+
+impl<'a, S> RpcServiceT<'a> for SaturationService<S>
+where
+    S: Send + Sync + RpcServiceT<'a>,
+{
+    type Future = InFlightFuture<S::Future>;
+
+    fn call(&self, req: Request<'a>) -> Self::Future {
+        // The calls of a batch are all timed from the receipt of the batch
+        if let Some(ReceivedAt(received_at)) = req.extensions().get::<ReceivedAt>() {
+            self.metrics.call_queue_time.observe(received_at.elapsed().as_micros() as f64);
+        }
+        let guard = InFlightGuard::new(&self.metrics.calls_in_flight);
+        InFlightFuture { fut: self.service.call(req), _guard: guard }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hyper::header::HeaderValue;
+
+    #[test]
+    fn test_is_websocket_upgrade() {
+        // Given
+        let mut headers = HeaderMap::new();
+
+        // When
+        // Then
+        assert!(!is_websocket_upgrade(&headers));
+        headers.insert("upgrade", HeaderValue::from_static("WebSocket"));
+        assert!(is_websocket_upgrade(&headers));
+    }
+
+    #[tokio::test]
+    async fn test_in_flight_future() {
+        // Given
+        let metrics = SaturationMetrics::new(&Registry::new()).unwrap();
+        let gauge = &metrics.calls_in_flight;
+
+        // When
+        let fut = InFlightFuture { fut: std::future::ready(()), _guard: InFlightGuard::new(gauge) };
+
+        // Then
+        assert_eq!(gauge.get(), 1);
+        fut.await;
+        assert_eq!(gauge.get(), 0);
+
+        // When
+        let dropped = InFlightFuture { fut: std::future::pending::<()>(), _guard: InFlightGuard::new(gauge) };
+        drop(dropped);
+
+        // Then
+        assert_eq!(gauge.get(), 0);
+    }
+}
@@ -0,0 +1,220 @@
+//! RPC middleware accounting the usage of each API key.
+//!
+//! The number of calls, the compute units (the cost of the methods, see [`compute_units`]) and
+//! the size of the responses are recorded per API key, sent in the `Authorization: Bearer <key>`
+//! or in the `X-Api-Key` header, so that the node can be operated as a service with fair-use
+//! policies. Only the keys of the configured tenants are accounted separately: the calls without
+//! API key are accounted as anonymous and the calls with an unknown key as unknown, so that random
+//! keys can't grow the usage. The usage is reported as metrics and served by `admin_apiKeyUsage`,
+//! under an id derived from the key so that the keys themselves never leave the node.
+
+use super::admin_access::ApiKey;
+use alloy_primitives::{hex, keccak256};
+use jsonrpsee::{server::middleware::rpc::RpcServiceT, types::Request, MethodResponse};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    convert::Infallible,
+    future::Future,
+    pin::Pin,
+    str::FromStr,
+    sync::{Arc, LazyLock, Mutex, MutexGuard, PoisonError},
+};
+
+/// The id under which the calls without API key are accounted.
+pub const ANONYMOUS_API_KEY_ID: &str = "anonymous";
+
+/// The id under which the calls with an API key which isn't a tenant key are accounted.
+pub const UNKNOWN_API_KEY_ID: &str = "unknown";
+
+/// The usage of the API keys since the start of the node.
+pub static API_KEY_USAGE: LazyLock<ApiKeyUsage> = LazyLock::new(ApiKeyUsage::default);
+
+/// Returns the compute units of a call to the method, weighting the calls by their cost to the
+/// node: the reads served by the database are cheap, the calls executing the EVM on Starknet, the
+/// transactions and the traces are expensive.
+pub fn compute_units(method: &str) -> u64 {
+    match method {
+        "eth_chainId" | "net_version" | "web3_clientVersion" => 1,
+        "eth_getLogs" | "kakarot_queryLogs" => 75,
+        "eth_call" | "eth_estimateGas" | "eth_createAccessList" => 100,
+        "eth_sendRawTransaction" => 250,
+        _ if method.starts_with("debug_") || method.starts_with("trace_") => 300,
+        _ => 10,
+    }
+}
+
+/// Returns the id of the API key: the first 8 bytes of its hash, hex encoded.
+pub fn api_key_id(key: &ApiKey) -> String {
+    hex::encode(&keccak256(key.0.as_bytes())[..8])
+}
+
+/// The API keys of the tenants, whose usage is accounted separately.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TenantApiKeys(HashSet<String>);
+
+impl TenantApiKeys {
+    /// Returns true if the API key is a tenant key.
+    pub fn contains(&self, key: &ApiKey) -> bool {
+        self.0.contains(&key.0)
+    }
+}
+
+impl FromStr for TenantApiKeys {
+    type Err = Infallible;
+
+    /// Parses a comma separated list of keys, e.g. `abc,def`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Self(s.split(',').map(str::trim).filter(|key| !key.is_empty()).map(ToString::to_string).collect()))
+    }
+}
+
+/// The usage of an API key.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct Usage {
+    /// The number of calls.
+    pub requests: u64,
+    /// The compute units of the calls.
+    pub compute_units: u64,
+    /// The size of the responses, in bytes.
+    pub egress_bytes: u64,
+}
+
+/// The usage of the API keys, indexed by the id of the key.
+#[derive(Debug, Default)]
+pub struct ApiKeyUsage {
+    usage: Mutex<HashMap<String, Usage>>,
+}
+
+impl ApiKeyUsage {
+    /// Records a call to the method with the API key, whose response is `egress_bytes` long.
+    /// Returns the id under which the call was accounted: the id of the key if it is a tenant key,
+    /// [`UNKNOWN_API_KEY_ID`] otherwise and [`ANONYMOUS_API_KEY_ID`] without key.
+    pub fn record(&self, key: Option<&ApiKey>, tenants: &TenantApiKeys, method: &str, egress_bytes: u64) -> String {
+        let id = match key {
+            Some(key) if tenants.contains(key) => api_key_id(key),
+            Some(_) => UNKNOWN_API_KEY_ID.to_string(),
+            None => ANONYMOUS_API_KEY_ID.to_string(),
+        };
+
+        let mut usage = self.lock();
+        let entry = usage.entry(id.clone()).or_default();
+        entry.requests += 1;
+        entry.compute_units += compute_units(method);
+        entry.egress_bytes += egress_bytes;
+        id
+    }
+
+    /// Returns the usage of each API key, by id.
+    pub fn snapshot(&self) -> BTreeMap<String, Usage> {
+        self.lock().iter().map(|(id, usage)| (id.clone(), *usage)).collect()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, HashMap<String, Usage>> {
+        self.usage.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// Usage accounting layer.
+#[derive(Clone, Debug)]
+pub struct UsageLayer {
+    tenants: Arc<TenantApiKeys>,
+}
+
+impl UsageLayer {
+    /// Creates a new layer accounting the usage of the tenant keys separately.
+    pub fn new(tenants: TenantApiKeys) -> Self {
+        Self { tenants: Arc::new(tenants) }
+    }
+}
+
+impl<S> tower::Layer<S> for UsageLayer {
+    type Service = UsageService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        UsageService { service: inner, tenants: Arc::clone(&self.tenants) }
+    }
+}
+
+/// Usage accounting middleware.
+#[derive(Clone, Debug)]
+pub struct UsageService<S> {
+    service: S,
+    tenants: Arc<TenantApiKeys>,
+}
+
+impl<'a, S> RpcServiceT<'a> for UsageService<S>
+where
+    S: Send + Sync + Clone + RpcServiceT<'a> + 'a,
+    S::Future: 'a,
+{
+    type Future = Pin<Box<dyn Future<Output = MethodResponse> + Send + 'a>>;
+
+    fn call(&self, req: Request<'a>) -> Self::Future {
+        let key = req.extensions().get::<ApiKey>().cloned();
+        let method = req.method_name().to_string();
+        let service = self.service.clone();
+        let tenants = Arc::clone(&self.tenants);
+
+        Box::pin(async move {
+            let rp = service.call(req).await;
+
+            let egress_bytes = rp.as_result().len() as u64;
+            let id = API_KEY_USAGE.record(key.as_ref(), &tenants, &method, egress_bytes);
+            tracing::info!(
+                target: "api_key_usage",
+                monotonic_counter.api_key_requests = 1,
+                monotonic_counter.api_key_compute_units = compute_units(&method),
+                monotonic_counter.api_key_egress_bytes = egress_bytes,
+                api_key = %id,
+            );
+            rp
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_usage() {
+        // Given
+        let usage = ApiKeyUsage::default();
+        let key = ApiKey("tenant".to_string());
+        let tenants = TenantApiKeys::from_str("tenant").unwrap();
+
+        // When
+        let id = usage.record(Some(&key), &tenants, "eth_blockNumber", 100);
+        usage.record(Some(&key), &tenants, "debug_traceTransaction", 1000);
+        let anonymous = usage.record(None, &tenants, "eth_call", 10);
+
+        // Then
+        let snapshot = usage.snapshot();
+        assert_eq!(id, api_key_id(&key));
+        assert_eq!(anonymous, ANONYMOUS_API_KEY_ID);
+        assert_eq!(snapshot[&id], Usage { requests: 2, compute_units: 310, egress_bytes: 1100 });
+        assert_eq!(snapshot[ANONYMOUS_API_KEY_ID], Usage { requests: 1, compute_units: 100, egress_bytes: 10 });
+    }
+
+    #[test]
+    fn test_record_usage_buckets_the_unknown_keys() {
+        // Given
+        let usage = ApiKeyUsage::default();
+        let tenants = TenantApiKeys::from_str("tenant, other").unwrap();
+
+        // When
+        let known = usage.record(Some(&ApiKey("other".to_string())), &tenants, "eth_blockNumber", 0);
+        for i in 0..100 {
+            usage.record(Some(&ApiKey(i.to_string())), &tenants, "eth_blockNumber", 0);
+        }
+
+        // Then
+        let snapshot = usage.snapshot();
+        assert_eq!(known, api_key_id(&ApiKey("other".to_string())));
+        assert_eq!(snapshot.len(), 2);
+        assert_eq!(snapshot[UNKNOWN_API_KEY_ID].requests, 100);
+    }
+}
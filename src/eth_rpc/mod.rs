@@ -4,11 +4,19 @@
 pub mod api;
 pub mod config;
 pub mod middleware;
+pub mod openrpc;
+#[cfg(feature = "mongo")]
 pub mod rpc;
+#[cfg(feature = "mongo")]
 pub mod servers;
 
 use crate::{
-    eth_rpc::middleware::{metrics::RpcMetrics, MetricsLayer},
+    constants::REPLAY_MODE,
+    eth_rpc::middleware::{
+        metrics::RpcMetrics, saturation::SaturationMetrics, AdminAccessLayer, AllowedOriginsLayer, ApiKeyLayer,
+        ClientIpLayer, ConcurrencyLayer, DeprecationLayer, FollowerLayer, MetricsLayer, OriginLayer, PeerAddrService,
+        PublicTraceLayer, ReplayLayer, RequestIdLayer, UsageLayer,
+    },
     prometheus_handler::init_prometheus,
 };
 use config::RPCConfig;
@@ -16,15 +24,25 @@ use eyre::Result;
 use jsonrpsee::{
     server::{
         middleware::http::{InvalidPath, ProxyGetRequestLayer},
-        RpcServiceBuilder, ServerBuilder, ServerHandle,
+        serve_with_graceful_shutdown, stop_channel, RpcServiceBuilder, ServerBuilder, ServerHandle,
     },
-    RpcModule,
+    Methods, RpcModule,
 };
 use prometheus::Registry;
-use std::net::{AddrParseError, Ipv4Addr, SocketAddr};
+use std::{
+    net::{AddrParseError, Ipv4Addr, SocketAddr},
+    time::Duration,
+};
 use thiserror::Error;
+use tokio::net::TcpSocket;
 use tower_http::cors::{Any, CorsLayer};
 
+/// The maximum number of pending connections in the queue of the listening socket.
+const LISTEN_BACKLOG: u32 = 1024;
+
+/// The interval between two samples of the saturation of the runtime.
+const RUNTIME_SAMPLING_INTERVAL: Duration = Duration::from_secs(5);
+
 #[derive(Error, Debug)]
 pub enum RpcError {
     #[error(transparent)]
@@ -46,17 +64,48 @@ pub async fn run_server(
     kakarot_rpc_module: RpcModule<()>,
     rpc_config: RPCConfig,
 ) -> Result<(SocketAddr, ServerHandle), RpcError> {
-    let RPCConfig { socket_addr } = rpc_config;
-
-    let cors = CorsLayer::new().allow_methods(Any).allow_origin(Any).allow_headers(Any);
+    let RPCConfig {
+        socket_addr,
+        max_request_body_size,
+        max_connections,
+        tcp_keepalive,
+        tcp_nodelay,
+        concurrency_limits,
+        concurrency_queue_timeout,
+        admin_api_keys,
+        tenant_api_keys,
+        public_trace,
+        allowed_origins,
+        trusted_proxies,
+        leadership,
+    } = rpc_config;
 
-    let http_middleware =
-        tower::ServiceBuilder::new().layer(ProxyGetRequestLayer::new("/health", "net_health")?).layer(cors);
+    // the preflight requests don't tell the called method, the cors layer allows all the origins
+    // allowed for at least one namespace and the allowed origins layer filters the calls
+    let cors = CorsLayer::new().allow_methods(Any).allow_origin(allowed_origins.cors_origin()).allow_headers(Any);
 
     // Creating the prometheus registry to register the metrics
     let registry = Registry::new();
     // register the metrics
     let metrics = RpcMetrics::new(Some(&registry))?.map(|m| MetricsLayer::new(m, "http"));
+    // the saturation metrics tell the starvation of the server threads apart from slow downstream
+    // calls, the runtime being sampled in the background
+    let saturation = SaturationMetrics::new(&registry)?;
+    tokio::spawn(saturation.clone().sample_runtime(RUNTIME_SAMPLING_INTERVAL));
+
+    // the saturation layer counts the requests in flight and records their time of receipt, the
+    // client ip layer records the ip reported by the reverse proxy or the ip of the peer, used to
+    // rate limit the requests of a client (e.g. the faucet), the api key layer records the key used to access the
+    // admin namespace and the origin layer records the origin of the browser requests. The GET
+    // requests of the health check and of the OpenRPC document are served by their methods
+    let http_middleware = tower::ServiceBuilder::new()
+        .layer(saturation.http_layer())
+        .layer(ProxyGetRequestLayer::new("/health", "net_health")?)
+        .layer(ProxyGetRequestLayer::new("/openrpc.json", "rpc.discover")?)
+        .layer(cors)
+        .layer(ClientIpLayer::new(trusted_proxies))
+        .layer(ApiKeyLayer)
+        .layer(OriginLayer);
     tokio::spawn(async move {
         // serve the prometheus metrics on the given port so that it can be read
         let _ = init_prometheus(
@@ -71,17 +120,80 @@ pub async fn run_server(
     // add the metrics as a middleware to the RPC so that every new RPC call fires prometheus metrics
     // upon start, finish etc. we don't need to manually handle each method, it should automatically
     // work for any new method.
-    let rpc_middleware = RpcServiceBuilder::new().option_layer(metrics);
+    // the request id layer assigns an id to each call, used in the logs, traces and error responses.
+    // the concurrency layer bounds the number of concurrent expensive calls (e.g. traces), so that
+    // they can't starve the cheap ones.
+    // the admin access layer restricts the admin namespace to the roles of the api keys, denying
+    // every admin call if no key is set.
+    // the allowed origins layer restricts the namespaces to their allowed origins, if any.
+    // the public trace layer serves the traces of the requests without an api key with lower limits,
+    // queuing the block traces, if enabled.
+    // the usage layer accounts the calls, compute units and response sizes of each tenant api key.
+    // the saturation layer counts the calls in flight and observes the time they waited before
+    // starting.
+    // the deprecation layer routes the deprecated method names to their current handlers and
+    // rewrites the decimal block numbers of some clients, before the other layers so that they
+    // only see the current names and the hex block numbers.
+    // the replay layer rejects the methods not served from the database, in replay mode.
+    // the follower layer rejects the writes until the instance is elected, if coordinated.
+    let public_trace = public_trace.map(|config| PublicTraceLayer::new(config, admin_api_keys.clone()));
+    let allowed_origins = (!allowed_origins.is_empty()).then(|| AllowedOriginsLayer::new(allowed_origins));
+    let rpc_middleware = RpcServiceBuilder::new()
+        .layer(saturation.rpc_layer())
+        .layer(DeprecationLayer)
+        .option_layer(REPLAY_MODE.then_some(ReplayLayer))
+        .option_layer(leadership.map(FollowerLayer::new))
+        .layer(UsageLayer::new(tenant_api_keys))
+        .layer(RequestIdLayer)
+        .layer(AdminAccessLayer::new(admin_api_keys))
+        .option_layer(allowed_origins)
+        .option_layer(public_trace)
+        .layer(ConcurrencyLayer::new(concurrency_limits, concurrency_queue_timeout))
+        .option_layer(metrics);
+
+    // HTTP/1.1 and HTTP/2 connections are both accepted by the server, the keep-alive is set on the
+    // listening socket so that it is inherited by the accepted connections. The connections are
+    // accepted here rather than by jsonrpsee, so that the address of the peer of each connection is
+    // recorded in its requests, identifying the clients which don't go through a reverse proxy
+    let socket_addr = socket_addr.parse::<SocketAddr>()?;
+    let socket = if socket_addr.is_ipv4() { TcpSocket::new_v4()? } else { TcpSocket::new_v6()? };
+    socket.set_reuseaddr(true)?;
+    socket.set_keepalive(tcp_keepalive)?;
+    socket.bind(socket_addr)?;
+    let listener = socket.listen(LISTEN_BACKLOG)?;
+    let addr = listener.local_addr()?;
 
-    let server = ServerBuilder::default()
-        .max_connections(get_env_or_default("RPC_MAX_CONNECTIONS", "100").parse().unwrap())
+    let service_builder = ServerBuilder::default()
+        .max_connections(max_connections)
+        .max_request_body_size(max_request_body_size)
         .set_http_middleware(http_middleware)
         .set_rpc_middleware(rpc_middleware)
-        .build(socket_addr.parse::<SocketAddr>()?)
-        .await?;
-
-    let addr = server.local_addr()?;
-    let handle = server.start(kakarot_rpc_module);
+        .to_service_builder();
+    let methods = Methods::from(kakarot_rpc_module);
+    let (stop_handle, handle) = stop_channel();
+    tokio::spawn(async move {
+        loop {
+            let (stream, peer) = tokio::select! {
+                accepted = listener.accept() => match accepted {
+                    Ok(accepted) => accepted,
+                    Err(err) => {
+                        tracing::warn!(%err, "failed to accept a connection");
+                        continue;
+                    }
+                },
+                () = stop_handle.clone().shutdown() => break,
+            };
+            if let Err(err) = stream.set_nodelay(tcp_nodelay) {
+                tracing::warn!(%err, %peer, "failed to configure the connection");
+            }
+            let service = service_builder.clone().build(methods.clone(), stop_handle.clone());
+            tokio::spawn(serve_with_graceful_shutdown(
+                stream,
+                PeerAddrService::new(service, peer),
+                stop_handle.clone().shutdown(),
+            ));
+        }
+    });
 
     Ok((addr, handle))
 }
@@ -0,0 +1,367 @@
+//! The `OpenRPC` document of the API, served at `/openrpc.json` and by `rpc.discover`.
+//!
+//! The methods are generated by the build script from the jsonrpsee trait definitions of
+//! [`api`](super::api), and the schemas of their parameters and results are derived with
+//! `schemars` from the Rust types of their signatures: the types of the crate derive
+//! [`JsonSchema`], the external types are mapped to the types of [`schemas`] describing their JSON
+//! encoding.
+
+pub mod schemas;
+
+use crate::{
+    eth_rpc::middleware::usage::Usage,
+    models::{
+        filter::NormalizedFilter,
+        log_subscription::LogFilterExtension,
+        token::{TokenBalances, TokenMetadata},
+        transaction_request::NormalizedTransactionRequest,
+    },
+    pool::{
+        build_stats::BlockBuildStats,
+        content::{TxpoolContentCursor, TxpoolContentPage},
+        nonce_gaps::NonceGaps,
+        ordering::PoolDrainOrder,
+        relayers::{RelayerQueue, TrafficClass},
+        snapshot::{PoolImport, PoolSnapshot},
+    },
+    providers::eth_provider::{
+        constant::{Capabilities, ChainMetadata, Constant},
+        database::types::transaction::{DroppedTransaction, PoolEvent},
+        gas::{DataAvailabilityCost, FeeSuggestions},
+        logs::{LogQuery, LogQueryResult},
+        receipts::{ReceiptProof, TransactionConfirmations, TransactionExecutionResources},
+        state::AccountType,
+        transactions::{AddressHistory, AddressHistoryRequest},
+    },
+    tracing::diff::TransactionDiff,
+};
+use schemars::{
+    gen::{SchemaGenerator, SchemaSettings},
+    schema::{InstanceType, Schema, SchemaObject},
+    JsonSchema,
+};
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    sync::LazyLock,
+};
+
+include!(concat!(env!("OUT_DIR"), "/openrpc_methods.rs"));
+
+/// The version of the `OpenRPC` specification of the document.
+const OPENRPC_VERSION: &str = "1.2.6";
+
+/// The `OpenRPC` document of the API.
+pub static OPENRPC_DOCUMENT: LazyLock<Value> = LazyLock::new(document);
+
+/// A type of the parameters or the results of the API, whose JSON schema is the schema of
+/// [`ApiSchema::Schema`].
+///
+/// The types of the crate are their own schema. The external types, which can't implement
+/// [`JsonSchema`], are mapped to a type of [`schemas`] with the same JSON encoding.
+pub trait ApiSchema {
+    /// The type deriving the JSON schema of the type.
+    type Schema: JsonSchema;
+}
+
+/// The type deriving the JSON schema of an API type, used by the `#[schemars(with = "...")]`
+/// attributes of the fields holding external types.
+pub type SchemaOf<T> = <T as ApiSchema>::Schema;
+
+/// Implements [`ApiSchema`] for types deriving [`JsonSchema`].
+macro_rules! derived_api_schema {
+    ($($ty:ty),* $(,)?) => {
+        $(
+            impl ApiSchema for $ty {
+                type Schema = Self;
+            }
+        )*
+    };
+}
+
+derived_api_schema!(
+    (),
+    bool,
+    u64,
+    f64,
+    String,
+    Value,
+    AccountType,
+    AddressHistory,
+    AddressHistoryRequest,
+    BlockBuildStats,
+    Capabilities,
+    ChainMetadata,
+    Constant,
+    DataAvailabilityCost,
+    DroppedTransaction,
+    FeeSuggestions,
+    LogFilterExtension,
+    LogQuery,
+    LogQueryResult,
+    NonceGaps,
+    PoolDrainOrder,
+    PoolEvent,
+    PoolImport,
+    PoolSnapshot,
+    ReceiptProof,
+    RelayerQueue,
+    TokenBalances,
+    TokenMetadata,
+    TrafficClass,
+    TransactionConfirmations,
+    TransactionDiff,
+    TransactionExecutionResources,
+    TxpoolContentCursor,
+    Usage,
+);
+
+#[cfg(feature = "chaos")]
+derived_api_schema!(crate::chaos::FaultConfig);
+
+#[cfg(feature = "meta-tx")]
+derived_api_schema!(crate::meta_tx::MetaTransactionRequest, crate::meta_tx::SponsorInfo);
+
+impl<T: ApiSchema> ApiSchema for Option<T> {
+    type Schema = Option<SchemaOf<T>>;
+}
+
+impl<T: ApiSchema> ApiSchema for Vec<T> {
+    type Schema = Vec<SchemaOf<T>>;
+}
+
+impl<T: ApiSchema> ApiSchema for Box<T> {
+    type Schema = SchemaOf<T>;
+}
+
+impl<T: ApiSchema> ApiSchema for BTreeSet<T> {
+    type Schema = BTreeSet<SchemaOf<T>>;
+}
+
+/// The keys of the maps are encoded as JSON strings.
+impl<K, V: ApiSchema> ApiSchema for BTreeMap<K, V> {
+    type Schema = BTreeMap<String, SchemaOf<V>>;
+}
+
+impl<T: ApiSchema> ApiSchema for TxpoolContentPage<T> {
+    type Schema = TxpoolContentPage<SchemaOf<T>>;
+}
+
+/// The requests are normalized on deserialization, their encoding is the encoding of the
+/// transaction requests.
+impl ApiSchema for NormalizedTransactionRequest {
+    type Schema = schemas::TransactionRequest;
+}
+
+/// The filters are normalized on deserialization, their encoding is the encoding of the filters.
+impl ApiSchema for NormalizedFilter {
+    type Schema = schemas::Filter;
+}
+
+/// A parameter or the result of a method.
+#[derive(Debug, Clone, Serialize)]
+struct ContentDescriptor {
+    /// The name of the parameter or the result.
+    name: String,
+    /// Whether the parameter is required.
+    required: bool,
+    /// The schema of the parameter or the result.
+    schema: Schema,
+}
+
+/// The notifications of a subscription, an extension of the `OpenRPC` specification.
+#[derive(Debug, Clone, Serialize)]
+struct Subscription {
+    /// The name of the notifications.
+    notification: String,
+    /// The name of the method cancelling the subscription.
+    unsubscribe: String,
+    /// The schema of the items of the notifications.
+    item: Schema,
+}
+
+/// A method of the `OpenRPC` document.
+#[derive(Debug, Clone, Serialize)]
+pub struct Method {
+    /// The namespace of the method.
+    #[serde(skip)]
+    namespace: Option<&'static str>,
+    /// The name of the method, prefixed with its namespace.
+    name: String,
+    /// The description of the method, from its doc comment.
+    #[serde(skip_serializing_if = "String::is_empty")]
+    description: String,
+    /// The parameters of the method.
+    params: Vec<ContentDescriptor>,
+    /// The result of the method.
+    result: ContentDescriptor,
+    /// The namespace of the method, as a tag.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    tags: Vec<Value>,
+    /// The notifications of the subscription, for the subscribe methods.
+    #[serde(rename = "x-subscription", skip_serializing_if = "Option::is_none")]
+    subscription: Option<Subscription>,
+}
+
+impl Method {
+    /// Creates a method without parameters, returning `null`.
+    pub fn new(namespace: Option<&'static str>, name: &str, description: &str) -> Self {
+        Self {
+            namespace,
+            name: full_name(namespace, name),
+            description: description.to_string(),
+            params: Vec::new(),
+            result: ContentDescriptor {
+                name: "result".to_string(),
+                required: true,
+                schema: SchemaObject { instance_type: Some(InstanceType::Null.into()), ..Default::default() }.into(),
+            },
+            tags: namespace.map(|namespace| json!({ "name": namespace })).into_iter().collect(),
+            subscription: None,
+        }
+    }
+
+    /// Creates the method cancelling the subscriptions of the subscribe method.
+    pub fn unsubscribe(namespace: Option<&'static str>, name: &str, subscribe: &str) -> Self {
+        let description = format!("Cancels a subscription of `{}`.", full_name(namespace, subscribe));
+        let mut generator = SchemaGenerator::default();
+        let mut method = Self::new(namespace, name, &description).result::<bool>(&mut generator);
+        method.params.push(ContentDescriptor {
+            name: "subscriptionId".to_string(),
+            required: true,
+            schema: schemas::SubscriptionId::json_schema(&mut generator),
+        });
+        method
+    }
+
+    /// Adds a required parameter to the method.
+    #[must_use]
+    pub fn param<T: ApiSchema>(mut self, generator: &mut SchemaGenerator, name: &str) -> Self {
+        self.params.push(ContentDescriptor {
+            name: name.to_string(),
+            required: true,
+            schema: generator.subschema_for::<SchemaOf<T>>(),
+        });
+        self
+    }
+
+    /// Adds an optional parameter to the method.
+    #[must_use]
+    pub fn optional_param<T: ApiSchema>(mut self, generator: &mut SchemaGenerator, name: &str) -> Self {
+        self.params.push(ContentDescriptor {
+            name: name.to_string(),
+            required: false,
+            schema: generator.subschema_for::<SchemaOf<T>>(),
+        });
+        self
+    }
+
+    /// Sets the result of the method.
+    #[must_use]
+    pub fn result<T: ApiSchema>(mut self, generator: &mut SchemaGenerator) -> Self {
+        self.result.schema = generator.subschema_for::<SchemaOf<T>>();
+        self
+    }
+
+    /// Makes the method a subscribe method, returning the id of the subscription and sending
+    /// notifications of items of type `T`.
+    #[must_use]
+    pub fn subscription<T: ApiSchema>(
+        mut self,
+        generator: &mut SchemaGenerator,
+        notification: &str,
+        unsubscribe: &str,
+    ) -> Self {
+        self.result = ContentDescriptor {
+            name: "subscriptionId".to_string(),
+            required: true,
+            schema: generator.subschema_for::<schemas::SubscriptionId>(),
+        };
+        self.subscription = Some(Subscription {
+            notification: full_name(self.namespace, notification),
+            unsubscribe: full_name(self.namespace, unsubscribe),
+            item: generator.subschema_for::<SchemaOf<T>>(),
+        });
+        self
+    }
+}
+
+/// Returns the name of a method prefixed with its namespace.
+fn full_name(namespace: Option<&str>, name: &str) -> String {
+    namespace.map_or_else(|| name.to_string(), |namespace| format!("{namespace}_{name}"))
+}
+
+/// Returns the `OpenRPC` document of the API, the schemas of the named types being referenced
+/// from the components of the document.
+fn document() -> Value {
+    let mut generator = SchemaSettings::draft07()
+        .with(|settings| settings.definitions_path = "#/components/schemas/".to_string())
+        .into_generator();
+    let mut methods = api_methods(&mut generator);
+    methods.sort_by(|a, b| a.name.cmp(&b.name));
+
+    json!({
+        "openrpc": OPENRPC_VERSION,
+        "info": {
+            "title": "Kakarot RPC",
+            "version": env!("CARGO_PKG_VERSION"),
+            "description": "The JSON-RPC API of the Kakarot zkEVM, including the kakarot_ extensions.",
+        },
+        "methods": methods,
+        "components": { "schemas": generator.take_definitions() },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_openrpc_document() {
+        // When
+        let document = &*OPENRPC_DOCUMENT;
+
+        // Then
+        assert_eq!(document["info"]["version"], env!("CARGO_PKG_VERSION"));
+        let methods = document["methods"].as_array().unwrap();
+        let method = |name: &str| methods.iter().find(|method| method["name"] == name).cloned();
+        let schema = |reference: &Value| {
+            let name = reference["$ref"].as_str().unwrap().trim_start_matches("#/components/schemas/");
+            document["components"]["schemas"][name].clone()
+        };
+
+        let get_balance = method("eth_getBalance").expect("missing eth_getBalance");
+        assert_eq!(get_balance["params"][0]["name"], "address");
+        assert_eq!(get_balance["params"][0]["schema"]["$ref"], "#/components/schemas/Address");
+        assert_eq!(get_balance["params"][1]["name"], "blockNumber");
+        assert_eq!(get_balance["params"][1]["required"], false);
+        assert_eq!(get_balance["result"]["schema"]["$ref"], "#/components/schemas/Quantity");
+        assert_eq!(schema(&get_balance["params"][0]["schema"])["pattern"], "^0x[0-9a-fA-F]{40}$");
+
+        let get_config = method("kakarot_getConfig").expect("missing kakarot_getConfig");
+        let constant = schema(&get_config["result"]["schema"]);
+        assert_eq!(constant["properties"]["kakarot_address"]["$ref"], "#/components/schemas/Felt");
+        assert!(constant["required"].as_array().unwrap().contains(&"starknet_network".into()));
+
+        let get_block = method("eth_getBlockByNumber").expect("missing eth_getBlockByNumber");
+        let block = &get_block["result"]["schema"]["anyOf"][0];
+        assert_eq!(block["$ref"], "#/components/schemas/Block");
+        let block = schema(block);
+        assert_eq!(block["properties"]["transactions"]["$ref"], "#/components/schemas/BlockTransactions");
+        assert!(block["properties"]["parentHash"].is_object());
+
+        let subscribe = method("eth_subscribe").expect("missing eth_subscribe");
+        assert_eq!(subscribe["x-subscription"]["notification"], "eth_subscription");
+        assert_eq!(subscribe["x-subscription"]["item"]["$ref"], "#/components/schemas/Log");
+        assert!(method("eth_unsubscribe").is_some());
+        assert!(method("rpc.discover").is_some());
+
+        // Every referenced schema is defined
+        let document = document.to_string();
+        for reference in document.split("\"$ref\":\"#/components/schemas/").skip(1) {
+            let name = &reference[..reference.find('"').unwrap()];
+            assert!(OPENRPC_DOCUMENT["components"]["schemas"].get(name).is_some(), "missing schema of {name}");
+        }
+    }
+}
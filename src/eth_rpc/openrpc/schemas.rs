@@ -0,0 +1,764 @@
+//! The types describing the JSON encoding of the external types of the API, for which
+//! [`JsonSchema`] can't be derived. Each type mirrors the serde encoding of the external type it
+//! describes, see the [`ApiSchema`] implementations at the bottom of the module.
+
+use super::{ApiSchema, SchemaOf};
+use crate::providers::eth_provider::fallback::SubmissionStatus;
+use alloy_rpc_types::{
+    pubsub::SubscriptionKind as EthSubscriptionKind, serde_helpers::JsonStorageKey,
+    state::StateOverride as EthStateOverride, AccessListResult as EthAccessListResult, Block as EthBlock,
+    BlockOverrides as EthBlockOverrides, Bundle as EthBundle, EIP1186AccountProofResponse,
+    EthCallResponse as EthEthCallResponse, FeeHistory as EthFeeHistory, Filter as EthFilter,
+    FilterChanges as EthFilterChanges, Index, Log as EthLog, StateContext as EthStateContext,
+    SyncStatus as EthSyncStatus, Transaction as EthTransaction, TransactionReceipt as EthTransactionReceipt,
+    TransactionRequest as EthTransactionRequest, Work as EthWork,
+};
+use alloy_rpc_types_trace::{
+    geth::{
+        GethDebugTracingCallOptions as EthGethDebugTracingCallOptions,
+        GethDebugTracingOptions as EthGethDebugTracingOptions, GethTrace as EthGethTrace,
+        TraceResult as EthTraceResult,
+    },
+    parity::LocalizedTransactionTrace as EthLocalizedTransactionTrace,
+};
+use alloy_rpc_types_txpool::{
+    TxpoolContent as EthTxpoolContent, TxpoolContentFrom as EthTxpoolContentFrom, TxpoolInspect as EthTxpoolInspect,
+    TxpoolStatus as EthTxpoolStatus,
+};
+use alloy_serde::WithOtherFields;
+use schemars::{
+    gen::SchemaGenerator,
+    schema::{InstanceType, Metadata, Schema, SchemaObject, StringValidation},
+    JsonSchema,
+};
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+/// Declares a string type matching a pattern.
+macro_rules! string_schema {
+    ($name:ident, $description:literal, $pattern:literal) => {
+        #[doc = $description]
+        #[derive(Debug)]
+        pub struct $name;
+
+        impl JsonSchema for $name {
+            fn schema_name() -> String {
+                stringify!($name).to_string()
+            }
+
+            fn json_schema(_: &mut SchemaGenerator) -> Schema {
+                SchemaObject {
+                    metadata: Some(Box::new(Metadata {
+                        description: Some($description.to_string()),
+                        ..Default::default()
+                    })),
+                    instance_type: Some(InstanceType::String.into()),
+                    string: Some(Box::new(StringValidation {
+                        pattern: Some($pattern.to_string()),
+                        ..Default::default()
+                    })),
+                    ..Default::default()
+                }
+                .into()
+            }
+        }
+    };
+}
+
+string_schema!(Address, "A 20 bytes address, hex encoded.", "^0x[0-9a-fA-F]{40}$");
+string_schema!(Hash, "A 32 bytes hash, hex encoded.", "^0x[0-9a-fA-F]{64}$");
+string_schema!(Nonce, "An 8 bytes proof of work nonce, hex encoded.", "^0x[0-9a-fA-F]{16}$");
+string_schema!(Bloom, "A 256 bytes bloom filter, hex encoded.", "^0x[0-9a-fA-F]{512}$");
+string_schema!(StorageKey, "A storage slot, hex encoded with up to 32 bytes.", "^0x[0-9a-fA-F]{1,64}$");
+string_schema!(Bytes, "Bytes, hex encoded.", "^0x([0-9a-fA-F]{2})*$");
+string_schema!(Felt, "A Starknet field element, hex encoded.", "^0x[0-9a-fA-F]{1,64}$");
+string_schema!(Quantity, "An unsigned integer, hex encoded without leading zeros.", "^0x(0|[1-9a-fA-F][0-9a-fA-F]*)$");
+
+/// The id of a subscription.
+#[derive(Debug, JsonSchema)]
+#[schemars(untagged)]
+pub enum SubscriptionId {
+    Number(u64),
+    String(String),
+}
+
+/// A block tag.
+#[derive(Debug, JsonSchema)]
+#[schemars(rename_all = "lowercase")]
+pub enum BlockTag {
+    Earliest,
+    Latest,
+    Pending,
+    Safe,
+    Finalized,
+}
+
+/// A block number or a block tag.
+#[derive(Debug, JsonSchema)]
+#[schemars(untagged)]
+pub enum BlockNumberOrTag {
+    Number(Quantity),
+    Tag(BlockTag),
+}
+
+/// A block number, a block tag or a block hash, possibly in the EIP-1898 object format.
+#[derive(Debug, JsonSchema)]
+#[schemars(untagged)]
+pub enum BlockId {
+    Number(BlockNumberOrTag),
+    Hash(Hash),
+    #[schemars(rename_all = "camelCase")]
+    HashObject {
+        block_hash: Hash,
+        require_canonical: Option<bool>,
+    },
+    #[schemars(rename_all = "camelCase")]
+    NumberObject {
+        block_number: BlockNumberOrTag,
+    },
+}
+
+/// A value or an array of values.
+#[derive(Debug, JsonSchema)]
+#[schemars(untagged)]
+pub enum ValueOrArray<T> {
+    Value(T),
+    Array(Vec<T>),
+}
+
+/// An entry of an access list.
+#[derive(Debug, JsonSchema)]
+#[schemars(rename_all = "camelCase")]
+pub struct AccessListItem {
+    pub address: Address,
+    pub storage_keys: Vec<Hash>,
+}
+
+/// A transaction, with the fields added by Kakarot.
+#[derive(Debug, JsonSchema)]
+#[schemars(rename_all = "camelCase")]
+pub struct Transaction {
+    pub hash: Hash,
+    pub nonce: Quantity,
+    pub block_hash: Option<Hash>,
+    pub block_number: Option<Quantity>,
+    pub transaction_index: Option<Quantity>,
+    pub from: Address,
+    pub to: Option<Address>,
+    pub value: Quantity,
+    pub gas_price: Option<Quantity>,
+    pub gas: Quantity,
+    pub max_fee_per_gas: Option<Quantity>,
+    pub max_priority_fee_per_gas: Option<Quantity>,
+    pub max_fee_per_blob_gas: Option<Quantity>,
+    pub input: Bytes,
+    pub v: Option<Quantity>,
+    pub r: Option<Quantity>,
+    pub s: Option<Quantity>,
+    pub y_parity: Option<Quantity>,
+    pub chain_id: Option<Quantity>,
+    pub access_list: Option<Vec<AccessListItem>>,
+    pub blob_versioned_hashes: Option<Vec<Hash>>,
+    #[schemars(rename = "type")]
+    pub transaction_type: Option<Quantity>,
+    /// The status of a transaction submitted to the node which isn't indexed yet.
+    pub submission_status: Option<SubmissionStatus>,
+    /// The hash of the Starknet transaction which executed the transaction.
+    #[schemars(rename = "starknet_transaction_hash")]
+    pub starknet_transaction_hash: Option<Felt>,
+}
+
+/// A request for a transaction, sent or simulated by the node.
+#[derive(Debug, JsonSchema)]
+#[schemars(rename_all = "camelCase")]
+pub struct TransactionRequest {
+    pub from: Option<Address>,
+    pub to: Option<Address>,
+    pub gas_price: Option<Quantity>,
+    pub max_fee_per_gas: Option<Quantity>,
+    pub max_priority_fee_per_gas: Option<Quantity>,
+    pub max_fee_per_blob_gas: Option<Quantity>,
+    pub gas: Option<Quantity>,
+    pub value: Option<Quantity>,
+    pub input: Option<Bytes>,
+    pub data: Option<Bytes>,
+    pub nonce: Option<Quantity>,
+    pub chain_id: Option<Quantity>,
+    pub access_list: Option<Vec<AccessListItem>>,
+    #[schemars(rename = "type")]
+    pub transaction_type: Option<Quantity>,
+    pub blob_versioned_hashes: Option<Vec<Hash>>,
+}
+
+/// A withdrawal of a block.
+#[derive(Debug, JsonSchema)]
+#[schemars(rename_all = "camelCase")]
+pub struct Withdrawal {
+    pub index: Quantity,
+    pub validator_index: Quantity,
+    pub address: Address,
+    pub amount: Quantity,
+}
+
+/// The header of a block.
+#[derive(Debug, JsonSchema)]
+#[schemars(rename_all = "camelCase")]
+pub struct Header {
+    pub hash: Hash,
+    pub parent_hash: Hash,
+    pub sha3_uncles: Hash,
+    pub miner: Address,
+    pub state_root: Hash,
+    pub transactions_root: Hash,
+    pub receipts_root: Hash,
+    pub logs_bloom: Bloom,
+    pub difficulty: Quantity,
+    pub number: Quantity,
+    pub gas_limit: Quantity,
+    pub gas_used: Quantity,
+    pub timestamp: Quantity,
+    pub extra_data: Bytes,
+    pub mix_hash: Hash,
+    pub nonce: Nonce,
+    pub base_fee_per_gas: Option<Quantity>,
+    pub withdrawals_root: Option<Hash>,
+    pub blob_gas_used: Option<Quantity>,
+    pub excess_blob_gas: Option<Quantity>,
+    pub parent_beacon_block_root: Option<Hash>,
+    pub total_difficulty: Option<Quantity>,
+}
+
+/// The transactions of a block, full or as hashes.
+#[derive(Debug, JsonSchema)]
+#[schemars(untagged)]
+pub enum BlockTransactions {
+    Hashes(Vec<Hash>),
+    Full(Vec<Transaction>),
+}
+
+/// A block, with the fields added by Kakarot.
+#[derive(Debug, JsonSchema)]
+#[schemars(rename_all = "camelCase")]
+pub struct Block {
+    #[schemars(flatten)]
+    pub header: Header,
+    pub uncles: Vec<Hash>,
+    pub transactions: BlockTransactions,
+    pub size: Option<Quantity>,
+    pub withdrawals: Option<Vec<Withdrawal>>,
+    /// The hash of the Starknet block from which the block was built.
+    pub starknet_block_hash: Option<Hash>,
+    /// The number of the Starknet block from which the block was built.
+    pub starknet_block_number: Option<Quantity>,
+}
+
+/// A log emitted by a transaction.
+#[derive(Debug, JsonSchema)]
+#[schemars(rename_all = "camelCase")]
+pub struct Log {
+    pub address: Address,
+    pub topics: Vec<Hash>,
+    pub data: Bytes,
+    pub block_hash: Option<Hash>,
+    pub block_number: Option<Quantity>,
+    pub block_timestamp: Option<Quantity>,
+    pub transaction_hash: Option<Hash>,
+    pub transaction_index: Option<Quantity>,
+    pub log_index: Option<Quantity>,
+    pub removed: bool,
+}
+
+/// A log emitted by an execution, without its position in the chain.
+#[derive(Debug, JsonSchema)]
+pub struct ExecutionLog {
+    pub address: Address,
+    pub topics: Vec<Hash>,
+    pub data: Bytes,
+}
+
+/// The receipt of a transaction, with the fields added by Kakarot.
+#[derive(Debug, JsonSchema)]
+#[schemars(rename_all = "camelCase")]
+pub struct TransactionReceipt {
+    #[schemars(rename = "type")]
+    pub transaction_type: Quantity,
+    pub status: Option<Quantity>,
+    pub cumulative_gas_used: Quantity,
+    pub logs: Vec<Log>,
+    pub logs_bloom: Bloom,
+    pub transaction_hash: Hash,
+    pub transaction_index: Option<Quantity>,
+    pub block_hash: Option<Hash>,
+    pub block_number: Option<Quantity>,
+    pub gas_used: Quantity,
+    pub effective_gas_price: Quantity,
+    pub blob_gas_used: Option<Quantity>,
+    pub blob_gas_price: Option<Quantity>,
+    pub from: Address,
+    pub to: Option<Address>,
+    pub contract_address: Option<Address>,
+    /// Whether the Starknet transaction ran out of resources, the transaction being reverted.
+    pub out_of_resources: Option<bool>,
+}
+
+/// A log filter.
+#[derive(Debug, JsonSchema)]
+#[schemars(rename_all = "camelCase")]
+pub struct Filter {
+    pub from_block: Option<BlockNumberOrTag>,
+    pub to_block: Option<BlockNumberOrTag>,
+    pub block_hash: Option<Hash>,
+    pub address: Option<ValueOrArray<Address>>,
+    pub topics: Option<Vec<Option<ValueOrArray<Hash>>>>,
+}
+
+/// The changes of a filter: logs, block hashes or transaction hashes.
+#[derive(Debug, JsonSchema)]
+#[schemars(untagged)]
+pub enum FilterChanges {
+    Logs(Vec<Log>),
+    Hashes(Vec<Hash>),
+    Transactions(Vec<Transaction>),
+}
+
+/// The progress of the synchronization of the node.
+#[derive(Debug, JsonSchema)]
+#[schemars(rename_all = "camelCase")]
+pub struct SyncInfo {
+    pub starting_block: Quantity,
+    pub current_block: Quantity,
+    pub highest_block: Quantity,
+    pub warp_chunks_amount: Option<Quantity>,
+    pub warp_chunks_processed: Option<Quantity>,
+}
+
+/// The synchronization status of the node, `false` if the node isn't syncing.
+#[derive(Debug, JsonSchema)]
+#[schemars(untagged)]
+pub enum SyncStatus {
+    Info(SyncInfo),
+    None(bool),
+}
+
+/// The fee history of a range of blocks.
+#[derive(Debug, JsonSchema)]
+#[schemars(rename_all = "camelCase")]
+pub struct FeeHistory {
+    pub base_fee_per_gas: Vec<Quantity>,
+    pub gas_used_ratio: Vec<f64>,
+    pub base_fee_per_blob_gas: Vec<Quantity>,
+    pub blob_gas_used_ratio: Vec<f64>,
+    pub oldest_block: Quantity,
+    pub reward: Option<Vec<Vec<Quantity>>>,
+}
+
+/// The Merkle proof of a storage slot.
+#[derive(Debug, JsonSchema)]
+pub struct StorageProof {
+    pub key: StorageKey,
+    pub value: Quantity,
+    pub proof: Vec<Bytes>,
+}
+
+/// The EIP-1186 Merkle proof of an account and of some of its storage slots.
+#[derive(Debug, JsonSchema)]
+#[schemars(rename_all = "camelCase")]
+pub struct AccountProof {
+    pub address: Address,
+    pub balance: Quantity,
+    pub code_hash: Hash,
+    pub nonce: Quantity,
+    pub storage_hash: Hash,
+    pub account_proof: Vec<Bytes>,
+    pub storage_proof: Vec<StorageProof>,
+}
+
+/// The access list of a transaction and the gas it uses.
+#[derive(Debug, JsonSchema)]
+#[schemars(rename_all = "camelCase")]
+pub struct AccessListResult {
+    pub access_list: Vec<AccessListItem>,
+    pub gas_used: Quantity,
+    pub error: Option<String>,
+}
+
+/// The overrides of the state of an account.
+#[derive(Debug, JsonSchema)]
+#[schemars(rename_all = "camelCase")]
+pub struct AccountOverride {
+    pub balance: Option<Quantity>,
+    pub nonce: Option<Quantity>,
+    pub code: Option<Bytes>,
+    pub state: Option<BTreeMap<String, Hash>>,
+    pub state_diff: Option<BTreeMap<String, Hash>>,
+}
+
+/// The overrides of the state of the accounts, by address.
+#[derive(Debug, JsonSchema)]
+pub struct StateOverride(pub BTreeMap<String, AccountOverride>);
+
+/// The overrides of the fields of a block.
+#[derive(Debug, JsonSchema)]
+#[schemars(rename_all = "camelCase")]
+pub struct BlockOverrides {
+    pub number: Option<Quantity>,
+    pub difficulty: Option<Quantity>,
+    pub time: Option<Quantity>,
+    pub gas_limit: Option<Quantity>,
+    pub coinbase: Option<Address>,
+    pub random: Option<Hash>,
+    pub base_fee: Option<Quantity>,
+    pub block_hash: Option<BTreeMap<String, Hash>>,
+}
+
+/// A bundle of transactions simulated on top of the same block.
+#[derive(Debug, JsonSchema)]
+#[schemars(rename_all = "camelCase")]
+pub struct Bundle {
+    pub transactions: Vec<TransactionRequest>,
+    pub block_override: Option<BlockOverrides>,
+}
+
+/// The state on top of which the bundles are simulated.
+#[derive(Debug, JsonSchema)]
+#[schemars(rename_all = "camelCase")]
+pub struct StateContext {
+    pub block_number: Option<BlockId>,
+    /// The index of the transaction after which the bundles are simulated, `-1` for the end of the
+    /// block.
+    pub transaction_index: Option<i64>,
+}
+
+/// The result of a simulated call.
+#[derive(Debug, JsonSchema)]
+pub struct EthCallResponse {
+    pub value: Option<Bytes>,
+    pub error: Option<String>,
+}
+
+/// The proof of work to solve: the header hash, the seed hash and the boundary condition.
+#[derive(Debug, JsonSchema)]
+pub struct Work(pub Vec<Hash>);
+
+/// The kind of a subscription.
+#[derive(Debug, JsonSchema)]
+#[schemars(rename_all = "camelCase")]
+pub enum SubscriptionKind {
+    NewHeads,
+    Logs,
+    NewPendingTransactions,
+    Syncing,
+}
+
+/// The number of transactions in the sub-pools of the mempool.
+#[derive(Debug, JsonSchema)]
+pub struct TxpoolStatus {
+    pub pending: Quantity,
+    pub queued: Quantity,
+}
+
+/// The summaries of the transactions of the mempool, by sub-pool, sender and nonce.
+#[derive(Debug, JsonSchema)]
+pub struct TxpoolInspect {
+    pub pending: BTreeMap<String, BTreeMap<String, String>>,
+    pub queued: BTreeMap<String, BTreeMap<String, String>>,
+}
+
+/// The transactions of the mempool, by sub-pool, sender and nonce.
+#[derive(Debug, JsonSchema)]
+pub struct TxpoolContent<T> {
+    pub pending: BTreeMap<String, BTreeMap<String, T>>,
+    pub queued: BTreeMap<String, BTreeMap<String, T>>,
+}
+
+/// The transactions of a sender in the mempool, by sub-pool and nonce.
+#[derive(Debug, JsonSchema)]
+pub struct TxpoolContentFrom<T> {
+    pub pending: BTreeMap<String, T>,
+    pub queued: BTreeMap<String, T>,
+}
+
+/// The options of the default struct logger.
+#[allow(clippy::struct_excessive_bools)]
+#[derive(Debug, JsonSchema)]
+#[schemars(rename_all = "camelCase")]
+pub struct GethDefaultTracingOptions {
+    pub enable_memory: Option<bool>,
+    pub disable_memory: Option<bool>,
+    pub disable_stack: Option<bool>,
+    pub disable_storage: Option<bool>,
+    pub enable_return_data: Option<bool>,
+    pub disable_return_data: Option<bool>,
+    pub debug: Option<bool>,
+    pub limit: Option<u64>,
+}
+
+/// The options of a debug trace.
+#[derive(Debug, JsonSchema)]
+#[schemars(rename_all = "camelCase")]
+pub struct GethDebugTracingOptions {
+    #[schemars(flatten)]
+    pub config: GethDefaultTracingOptions,
+    /// The tracer, e.g. `callTracer`, the default struct logger if unset.
+    pub tracer: Option<String>,
+    pub tracer_config: Option<Value>,
+    pub timeout: Option<String>,
+}
+
+/// The options of a debug trace of a call.
+#[derive(Debug, JsonSchema)]
+#[schemars(rename_all = "camelCase")]
+pub struct GethDebugTracingCallOptions {
+    #[schemars(flatten)]
+    pub tracing_options: GethDebugTracingOptions,
+    pub state_overrides: Option<StateOverride>,
+    pub block_overrides: Option<BlockOverrides>,
+}
+
+/// A step of the default struct logger.
+#[derive(Debug, JsonSchema)]
+#[schemars(rename_all = "camelCase")]
+pub struct StructLog {
+    pub pc: u64,
+    pub op: String,
+    pub gas: u64,
+    pub gas_cost: u64,
+    pub depth: u64,
+    pub error: Option<String>,
+    pub stack: Option<Vec<Quantity>>,
+    pub return_data: Option<Bytes>,
+    pub memory: Option<Vec<String>>,
+    pub memory_size: Option<u64>,
+    pub storage: Option<BTreeMap<String, String>>,
+    pub refund: Option<u64>,
+}
+
+/// The trace of the default struct logger.
+#[derive(Debug, JsonSchema)]
+#[schemars(rename_all = "camelCase")]
+pub struct DefaultFrame {
+    pub failed: bool,
+    pub gas: u64,
+    pub return_value: String,
+    pub struct_logs: Vec<StructLog>,
+}
+
+/// A log of the call tracer.
+#[derive(Debug, JsonSchema)]
+pub struct CallLogFrame {
+    pub address: Option<Address>,
+    pub topics: Option<Vec<Hash>>,
+    pub data: Option<Bytes>,
+    pub position: Option<Quantity>,
+}
+
+/// A call of the call tracer.
+#[derive(Debug, JsonSchema)]
+#[schemars(rename_all = "camelCase")]
+pub struct CallFrame {
+    pub from: Address,
+    pub gas: Quantity,
+    pub gas_used: Quantity,
+    pub to: Option<Address>,
+    pub input: Bytes,
+    pub output: Option<Bytes>,
+    pub error: Option<String>,
+    pub revert_reason: Option<String>,
+    pub calls: Option<Vec<CallFrame>>,
+    pub logs: Option<Vec<CallLogFrame>>,
+    pub value: Option<Quantity>,
+    #[schemars(rename = "type")]
+    pub call_type: String,
+}
+
+/// A debug trace: the trace of the default struct logger, of the call tracer, or of another
+/// tracer.
+#[derive(Debug, JsonSchema)]
+#[schemars(untagged)]
+pub enum GethTrace {
+    Default(DefaultFrame),
+    Call(CallFrame),
+    Other(Value),
+}
+
+/// The debug trace of a transaction of a block, or the error which prevented its tracing.
+#[derive(Debug, JsonSchema)]
+#[schemars(untagged)]
+pub enum TraceResult {
+    #[schemars(rename_all = "camelCase")]
+    Success { result: GethTrace, tx_hash: Option<Hash> },
+    #[schemars(rename_all = "camelCase")]
+    Error { error: String, tx_hash: Option<Hash> },
+}
+
+/// A call of a parity trace.
+#[derive(Debug, JsonSchema)]
+#[schemars(rename_all = "camelCase")]
+pub struct CallAction {
+    pub from: Address,
+    /// The kind of the call: `call`, `callcode`, `delegatecall` or `staticcall`.
+    pub call_type: String,
+    pub gas: Quantity,
+    pub input: Bytes,
+    pub to: Address,
+    pub value: Quantity,
+}
+
+/// A contract creation of a parity trace.
+#[derive(Debug, JsonSchema)]
+pub struct CreateAction {
+    pub from: Address,
+    pub gas: Quantity,
+    pub init: Bytes,
+    pub value: Quantity,
+}
+
+/// A self-destruct of a parity trace.
+#[derive(Debug, JsonSchema)]
+#[schemars(rename_all = "camelCase")]
+pub struct SelfdestructAction {
+    pub address: Address,
+    pub balance: Quantity,
+    pub refund_address: Address,
+}
+
+/// A reward of a parity trace.
+#[derive(Debug, JsonSchema)]
+#[schemars(rename_all = "camelCase")]
+pub struct RewardAction {
+    pub author: Address,
+    pub reward_type: String,
+    pub value: Quantity,
+}
+
+/// The action of a parity trace.
+#[derive(Debug, JsonSchema)]
+#[schemars(untagged)]
+pub enum Action {
+    Call(CallAction),
+    Create(CreateAction),
+    Selfdestruct(SelfdestructAction),
+    Reward(RewardAction),
+}
+
+/// The type of the action of a parity trace.
+#[derive(Debug, JsonSchema)]
+#[schemars(rename_all = "lowercase")]
+pub enum ActionType {
+    Call,
+    Create,
+    Suicide,
+    Reward,
+}
+
+/// The output of the action of a parity trace.
+#[derive(Debug, JsonSchema)]
+#[schemars(untagged)]
+pub enum TraceOutput {
+    #[schemars(rename_all = "camelCase")]
+    Call { gas_used: Quantity, output: Bytes },
+    #[schemars(rename_all = "camelCase")]
+    Create { address: Address, code: Bytes, gas_used: Quantity },
+}
+
+/// A parity trace of a transaction, with its position in the chain.
+#[derive(Debug, JsonSchema)]
+#[schemars(rename_all = "camelCase")]
+pub struct LocalizedTransactionTrace {
+    pub action: Action,
+    pub error: Option<String>,
+    pub result: Option<TraceOutput>,
+    pub subtraces: u64,
+    pub trace_address: Vec<u64>,
+    #[schemars(rename = "type")]
+    pub action_type: ActionType,
+    pub block_hash: Option<Hash>,
+    pub block_number: Option<u64>,
+    pub transaction_hash: Option<Hash>,
+    pub transaction_position: Option<u64>,
+}
+
+/// The status of a Starknet block.
+#[derive(Debug, JsonSchema)]
+#[schemars(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum BlockStatus {
+    Pending,
+    AcceptedOnL2,
+    AcceptedOnL1,
+    Rejected,
+}
+
+/// A Starknet event.
+#[derive(Debug, JsonSchema)]
+pub struct Event {
+    pub from_address: Felt,
+    pub keys: Vec<Felt>,
+    pub data: Vec<Felt>,
+}
+
+/// Implements [`ApiSchema`] for external types, described by a type of the module.
+macro_rules! external_api_schema {
+    ($($ty:ty => $schema:ty),* $(,)?) => {
+        $(
+            impl ApiSchema for $ty {
+                type Schema = $schema;
+            }
+        )*
+    };
+}
+
+external_api_schema!(
+    alloy_primitives::Address => Address,
+    alloy_primitives::B256 => Hash,
+    alloy_primitives::B64 => Nonce,
+    alloy_primitives::Bytes => Bytes,
+    alloy_primitives::U64 => Quantity,
+    alloy_primitives::U128 => Quantity,
+    alloy_primitives::U256 => Quantity,
+    alloy_primitives::Log => ExecutionLog,
+    alloy_eips::BlockId => BlockId,
+    alloy_eips::BlockNumberOrTag => BlockNumberOrTag,
+    starknet::core::types::Felt => Felt,
+    starknet::core::types::BlockStatus => BlockStatus,
+    starknet::core::types::Event => Event,
+    JsonStorageKey => StorageKey,
+    Index => Quantity,
+    EthBlock<WithOtherFields<EthTransaction>> => Block,
+    EthTransaction => Transaction,
+    EthTransactionReceipt => TransactionReceipt,
+    EthTransactionRequest => TransactionRequest,
+    EthLog => Log,
+    EthFilter => Filter,
+    EthFilterChanges => FilterChanges,
+    EthSyncStatus => SyncStatus,
+    EthFeeHistory => FeeHistory,
+    EIP1186AccountProofResponse => AccountProof,
+    EthAccessListResult => AccessListResult,
+    EthStateOverride => StateOverride,
+    EthBlockOverrides => BlockOverrides,
+    EthBundle => Bundle,
+    EthStateContext => StateContext,
+    EthEthCallResponse => EthCallResponse,
+    EthWork => Work,
+    EthSubscriptionKind => SubscriptionKind,
+    EthTxpoolStatus => TxpoolStatus,
+    EthTxpoolInspect => TxpoolInspect,
+    EthGethDebugTracingOptions => GethDebugTracingOptions,
+    EthGethDebugTracingCallOptions => GethDebugTracingCallOptions,
+    EthGethTrace => GethTrace,
+    EthTraceResult => TraceResult,
+    EthLocalizedTransactionTrace => LocalizedTransactionTrace,
+);
+
+/// The extra fields are flattened in the encoding of the inner type.
+impl<T: ApiSchema> ApiSchema for WithOtherFields<T> {
+    type Schema = SchemaOf<T>;
+}
+
+impl<T: ApiSchema> ApiSchema for EthTxpoolContent<T> {
+    type Schema = TxpoolContent<SchemaOf<T>>;
+}
+
+impl<T: ApiSchema> ApiSchema for EthTxpoolContentFrom<T> {
+    type Schema = TxpoolContentFrom<SchemaOf<T>>;
+}
@@ -2,17 +2,34 @@ use crate::{
     client::EthClient,
     eth_rpc::{
         api::{
-            alchemy_api::AlchemyApiServer, debug_api::DebugApiServer, eth_api::EthApiServer,
-            kakarot_api::KakarotApiServer, net_api::NetApiServer, trace_api::TraceApiServer,
-            txpool_api::TxPoolApiServer, web3_api::Web3ApiServer,
+            admin_api::AdminApiServer,
+            alchemy_api::AlchemyApiServer,
+            debug_api::DebugApiServer,
+            eth_api::EthApiServer,
+            kakarot_api::{KakarotAdminApiServer, KakarotApiServer, KakarotPoolApiServer},
+            net_api::NetApiServer,
+            openrpc_api::OpenRpcApiServer,
+            trace_api::TraceApiServer,
+            txpool_api::TxPoolApiServer,
+            web3_api::Web3ApiServer,
         },
         servers::{
-            alchemy_rpc::AlchemyRpc, debug_rpc::DebugRpc, eth_rpc::EthRpc, kakarot_rpc::KakarotRpc, net_rpc::NetRpc,
-            trace_rpc::TraceRpc, txpool_rpc::TxpoolRpc, web3_rpc::Web3Rpc,
+            admin_rpc::AdminRpc,
+            alchemy_rpc::AlchemyRpc,
+            debug_rpc::DebugRpc,
+            eth_rpc::EthRpc,
+            kakarot_rpc::{KakarotAdminRpc, KakarotPoolRpc, KakarotRpc},
+            net_rpc::NetRpc,
+            openrpc_rpc::OpenRpc,
+            trace_rpc::TraceRpc,
+            txpool_rpc::TxpoolRpc,
+            web3_rpc::Web3Rpc,
         },
     },
+    pool::relayers::RelayerRegistry,
     providers::{
-        alchemy_provider::AlchemyDataProvider, debug_provider::DebugDataProvider, pool_provider::PoolDataProvider,
+        alchemy_provider::AlchemyDataProvider, debug_provider::DebugDataProvider,
+        eth_provider::database::trace_cache::TRACE_CACHE_TTL, pool_provider::PoolDataProvider,
     },
 };
 use jsonrpsee::{server::RegisterMethodError, Methods, RpcModule};
@@ -30,6 +47,13 @@ pub enum KakarotRpcModule {
     Trace,
     Txpool,
     KakarotRpc,
+    KakarotPool,
+    KakarotAdmin,
+    KakarotFaucet,
+    KakarotMetaTx,
+    Admin,
+    Chaos,
+    OpenRpc,
 }
 
 #[derive(Debug)]
@@ -47,16 +71,18 @@ where
 
         let alchemy_provider = Arc::new(AlchemyDataProvider::new(eth_provider.clone()));
         let pool_provider = Arc::new(PoolDataProvider::new(eth_client.clone()));
-        let debug_provider = Arc::new(DebugDataProvider::new(eth_provider.clone()));
+        let trace_cache = (!TRACE_CACHE_TTL.is_zero()).then(|| eth_provider.database().clone());
+        let debug_provider = Arc::new(DebugDataProvider::new(eth_provider.clone()).with_trace_cache(trace_cache));
 
         let eth_rpc_module = EthRpc::new(eth_client).into_rpc();
         let alchemy_rpc_module = AlchemyRpc::new(alchemy_provider).into_rpc();
         let web3_rpc_module = Web3Rpc::default().into_rpc();
-        let net_rpc_module = NetRpc::new(eth_provider.clone()).into_rpc();
+        let net_rpc_module = NetRpc::new(eth_provider.clone(), eth_provider.chain_id).into_rpc();
         let debug_rpc_module = DebugRpc::new(debug_provider).into_rpc();
+        let kakarot_rpc_module = KakarotRpc::new(eth_provider.clone()).into_rpc();
         let trace_rpc_module = TraceRpc::new(eth_provider).into_rpc();
-        let kakarot_rpc_module = KakarotRpc.into_rpc();
         let txpool_rpc_module = TxpoolRpc::new(pool_provider).into_rpc();
+        let openrpc_rpc_module = OpenRpc::new().into_rpc();
 
         let mut modules = HashMap::new();
 
@@ -68,10 +94,66 @@ where
         modules.insert(KakarotRpcModule::Trace, trace_rpc_module.into());
         modules.insert(KakarotRpcModule::Txpool, txpool_rpc_module.into());
         modules.insert(KakarotRpcModule::KakarotRpc, kakarot_rpc_module.into());
+        modules.insert(KakarotRpcModule::OpenRpc, openrpc_rpc_module.into());
 
         Self { modules, _phantom: PhantomData }
     }
 
+    /// Adds the Kakarot module of the mempool, used to inspect the transactions waiting to be
+    /// relayed.
+    #[must_use]
+    pub fn with_pool_module(mut self, eth_client: Arc<EthClient<SP>>) -> Self {
+        let kakarot_pool_rpc_module = KakarotPoolRpc::new(eth_client).into_rpc();
+        self.modules.insert(KakarotRpcModule::KakarotPool, kakarot_pool_rpc_module.into());
+        self
+    }
+
+    /// Adds the admin module, used to operate the relayers registered in the given registry, along
+    /// with the Kakarot methods reserved to the operators (e.g. `kakarot_deployAccount`).
+    #[must_use]
+    pub fn with_admin_module(mut self, eth_client: Arc<EthClient<SP>>, relayers: Arc<RelayerRegistry>) -> Self {
+        let kakarot_admin_rpc_module = KakarotAdminRpc::new(eth_client.clone(), relayers.clone()).into_rpc();
+        self.modules.insert(KakarotRpcModule::KakarotAdmin, kakarot_admin_rpc_module.into());
+
+        let admin_rpc_module = AdminRpc::new(eth_client, relayers).into_rpc();
+        self.modules.insert(KakarotRpcModule::Admin, admin_rpc_module.into());
+        self
+    }
+
+    /// Adds the faucet module, transferring test ETH with the faucet relayers registered in the
+    /// given registry.
+    #[cfg(feature = "faucet")]
+    #[must_use]
+    pub fn with_faucet_module(mut self, eth_client: Arc<EthClient<SP>>, relayers: Arc<RelayerRegistry>) -> Self {
+        use crate::eth_rpc::{api::faucet_api::KakarotFaucetApiServer, servers::faucet_rpc::KakarotFaucetRpc};
+
+        let kakarot_faucet_rpc_module = KakarotFaucetRpc::new(eth_client, relayers).into_rpc();
+        self.modules.insert(KakarotRpcModule::KakarotFaucet, kakarot_faucet_rpc_module.into());
+        self
+    }
+
+    /// Adds the meta-transaction module, relaying the signed forward requests through the
+    /// configured trusted forwarder.
+    #[cfg(feature = "meta-tx")]
+    #[must_use]
+    pub fn with_meta_tx_module(mut self, eth_client: Arc<EthClient<SP>>, config: crate::meta_tx::MetaTxConfig) -> Self {
+        use crate::eth_rpc::{api::meta_tx_api::KakarotMetaTxApiServer, servers::meta_tx_rpc::KakarotMetaTxRpc};
+
+        let kakarot_meta_tx_rpc_module = KakarotMetaTxRpc::new(eth_client, config).into_rpc();
+        self.modules.insert(KakarotRpcModule::KakarotMetaTx, kakarot_meta_tx_rpc_module.into());
+        self
+    }
+
+    /// Adds the fault injection module, used to configure the faults injected in the node.
+    #[cfg(feature = "chaos")]
+    #[must_use]
+    pub fn with_chaos_module(mut self) -> Self {
+        use crate::eth_rpc::{api::chaos_api::ChaosApiServer, servers::chaos_rpc::ChaosRpc};
+
+        self.modules.insert(KakarotRpcModule::Chaos, ChaosRpc.into_rpc().into());
+        self
+    }
+
     pub fn rpc_module(&self) -> Result<RpcModule<()>, RegisterMethodError> {
         let mut rpc_module = RpcModule::new(());
 
@@ -0,0 +1,197 @@
+use crate::{
+    client::EthClient,
+    eth_rpc::{
+        api::admin_api::AdminApiServer,
+        middleware::usage::{Usage, API_KEY_USAGE},
+    },
+    pool::{
+        constants::ONE_TENTH_ETH,
+        mempool::TransactionOrdering,
+        ordering::PoolDrainOrder,
+        relayers::{RelayerQueue, RelayerRegistry, TrafficClass, TrafficClassifier},
+        snapshot::{import_pool_snapshot, PoolImport, PoolSnapshot},
+    },
+    providers::eth_provider::error::{EthApiError, KakarotError},
+};
+use alloy_primitives::{Address, U256};
+use jsonrpsee::{
+    core::{async_trait, RpcResult, SubscriptionResult},
+    PendingSubscriptionSink, SubscriptionMessage,
+};
+use reth_transaction_pool::TransactionPool;
+use starknet::{
+    core::types::{BlockId, BlockTag, Felt, StarknetError},
+    providers::{Provider, ProviderError},
+};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use tokio::sync::Mutex;
+use tracing::instrument;
+
+/// Default interval between two messages of the relayer queue subscription, in milliseconds.
+const DEFAULT_SUBSCRIPTION_INTERVAL_MS: u64 = 1000;
+/// Minimum interval between two messages of the relayer queue subscription, in milliseconds.
+const MIN_SUBSCRIPTION_INTERVAL_MS: u64 = 100;
+/// Duration for which the funded relayers served by `admin_poolInspectOrder` are cached.
+const FUNDED_RELAYERS_TTL: Duration = Duration::from_secs(10);
+
+/// The RPC module for implementing the Admin api
+#[derive(Debug)]
+pub struct AdminRpc<SP: Provider + Send + Sync> {
+    eth_client: Arc<EthClient<SP>>,
+    relayers: Arc<RelayerRegistry>,
+    /// The funded relayers, along with the time at which their balances were fetched.
+    funded_relayers: Mutex<Option<(Instant, Vec<Felt>)>>,
+}
+
+impl<SP> AdminRpc<SP>
+where
+    SP: Provider + Clone + Send + Sync,
+{
+    pub fn new(eth_client: Arc<EthClient<SP>>, relayers: Arc<RelayerRegistry>) -> Self {
+        Self { eth_client, relayers, funded_relayers: Mutex::new(None) }
+    }
+
+    /// Returns the relayers whose balance covers the relaying fees, see
+    /// `AccountManager::get_relayer`. The balances are fetched at most once per
+    /// [`FUNDED_RELAYERS_TTL`].
+    async fn funded_relayers(&self) -> Result<Vec<Felt>, EthApiError> {
+        let mut cached = self.funded_relayers.lock().await;
+        if let Some((fetched_at, relayers)) = cached.as_ref() {
+            if fetched_at.elapsed() < FUNDED_RELAYERS_TTL {
+                return Ok(relayers.clone());
+            }
+        }
+
+        let mut relayers = Vec::new();
+        for address in self.relayers.addresses() {
+            let balance =
+                self.eth_client.starknet_provider().balance_at(address, BlockId::Tag(BlockTag::Pending)).await?;
+            if balance >= U256::from(ONE_TENTH_ETH) {
+                relayers.push(address);
+            }
+        }
+        *cached = Some((Instant::now(), relayers.clone()));
+        Ok(relayers)
+    }
+
+    /// Checks that the relayer account is deployed and that its balance covers the relaying fees,
+    /// see `AccountManager::get_relayer`, so that no transaction is assigned to an account which
+    /// can't relay it.
+    async fn validate_relayer(&self, address: Felt) -> Result<(), EthApiError> {
+        let block_id = BlockId::Tag(BlockTag::Pending);
+        match self.eth_client.starknet_provider().get_class_hash_at(block_id, address).await {
+            Ok(_) => {}
+            Err(ProviderError::StarknetError(StarknetError::ContractNotFound)) => {
+                return Err(EthApiError::InvalidQuery(format!("relayer {address:#x} is not deployed")));
+            }
+            Err(err) => return Err(KakarotError::from(err).into()),
+        }
+
+        let balance = self.eth_client.starknet_provider().balance_at(address, block_id).await?;
+        if balance < U256::from(ONE_TENTH_ETH) {
+            return Err(EthApiError::InvalidQuery(format!("relayer {address:#x} is not funded, balance {balance}")));
+        }
+        Ok(())
+    }
+
+    /// Returns the current state of the relayer queue.
+    fn queue(&self) -> RelayerQueue {
+        let size = self.eth_client.mempool().pool_size();
+        RelayerQueue {
+            pending: size.pending,
+            queued: size.queued,
+            pending_size: size.pending_size,
+            queued_size: size.queued_size,
+            relayers: self.relayers.stats(),
+        }
+    }
+}
+
+#[async_trait]
+impl<SP> AdminApiServer for AdminRpc<SP>
+where
+    SP: Provider + Clone + Send + Sync + 'static,
+{
+    #[instrument(skip(self))]
+    async fn relayer_queue(&self) -> RpcResult<RelayerQueue> {
+        Ok(self.queue())
+    }
+
+    #[instrument(skip(self))]
+    async fn pool_inspect_order(&self) -> RpcResult<PoolDrainOrder> {
+        let mempool = self.eth_client.mempool();
+        let base_fee = mempool.block_info().pending_basefee;
+        let classifier = TrafficClassifier::default();
+        let lane_of = |sender: &Address| self.relayers.relaying_class(classifier.class_of(sender));
+        let transactions = TransactionOrdering::default().drain_order(mempool.best_transactions(), base_fee, lane_of);
+        let relayers = self.funded_relayers().await?;
+
+        Ok(PoolDrainOrder { base_fee, relayers, transactions })
+    }
+
+    #[instrument(skip(self))]
+    async fn add_relayer(&self, address: Felt, traffic_class: Option<TrafficClass>) -> RpcResult<bool> {
+        let traffic_class = traffic_class.unwrap_or_default();
+        self.validate_relayer(address).await?;
+        let registered = self.relayers.register_for(address, traffic_class);
+        if registered {
+            tracing::info!(relayer = ?address, ?traffic_class, "registered relayer");
+        }
+        Ok(registered)
+    }
+
+    #[instrument(skip(self))]
+    async fn api_key_usage(&self) -> RpcResult<BTreeMap<String, Usage>> {
+        Ok(API_KEY_USAGE.snapshot())
+    }
+
+    #[instrument(skip(self))]
+    async fn export_pool(&self) -> RpcResult<PoolSnapshot> {
+        Ok(PoolSnapshot::new(self.eth_client.mempool().as_ref()))
+    }
+
+    #[instrument(skip(self))]
+    async fn deployer_allowlist(&self) -> RpcResult<Option<BTreeSet<Address>>> {
+        Ok(self.eth_client.deployer_allowlist().map(|allowlist| allowlist.into_iter().collect()))
+    }
+
+    #[instrument(skip(self))]
+    async fn set_deployer_allowlist(&self, allowlist: Option<BTreeSet<Address>>) -> RpcResult<()> {
+        tracing::info!(?allowlist, "updated deployer allowlist");
+        self.eth_client.set_deployer_allowlist(allowlist.map(|allowlist| allowlist.into_iter().collect()));
+        Ok(())
+    }
+
+    #[instrument(skip(self, snapshot), fields(transactions = snapshot.transactions.len()))]
+    async fn import_pool(&self, snapshot: PoolSnapshot) -> RpcResult<PoolImport> {
+        Ok(import_pool_snapshot(&self.eth_client, snapshot).await?)
+    }
+
+    async fn subscribe_relayer_queue(
+        &self,
+        pending: PendingSubscriptionSink,
+        interval: Option<u64>,
+    ) -> SubscriptionResult {
+        let interval = interval.unwrap_or(DEFAULT_SUBSCRIPTION_INTERVAL_MS).max(MIN_SUBSCRIPTION_INTERVAL_MS);
+        let mut interval = tokio::time::interval(Duration::from_millis(interval));
+
+        let sink = pending.accept().await?;
+        loop {
+            tokio::select! {
+                () = sink.closed() => break,
+                _ = interval.tick() => {
+                    let message = SubscriptionMessage::from_json(&self.queue())?;
+                    if sink.send(message).await.is_err() {
+                        break;
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
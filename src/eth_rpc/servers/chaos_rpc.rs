@@ -0,0 +1,30 @@
+use crate::{
+    chaos::{FaultConfig, FAULT_INJECTOR},
+    eth_rpc::api::chaos_api::ChaosApiServer,
+    providers::eth_provider::error::EthRpcErrorCode,
+};
+use jsonrpsee::{
+    core::{async_trait, RpcResult},
+    types::ErrorObject,
+};
+use tracing::instrument;
+
+/// The RPC module for implementing the fault injection api
+#[derive(Debug, Default)]
+pub struct ChaosRpc;
+
+#[async_trait]
+impl ChaosApiServer for ChaosRpc {
+    #[instrument(skip(self))]
+    async fn fault_injection(&self) -> RpcResult<FaultConfig> {
+        Ok(FAULT_INJECTOR.config())
+    }
+
+    #[instrument(skip(self))]
+    async fn set_fault_injection(&self, config: FaultConfig) -> RpcResult<FaultConfig> {
+        FAULT_INJECTOR
+            .set_config(config)
+            .map_err(|err| ErrorObject::owned(EthRpcErrorCode::InvalidInput as i32, err.to_string(), None::<()>))?;
+        Ok(config)
+    }
+}
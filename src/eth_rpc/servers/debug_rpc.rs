@@ -1,8 +1,18 @@
-use crate::{eth_rpc::api::debug_api::DebugApiServer, providers::debug_provider::DebugProvider};
+use crate::{
+    eth_rpc::api::debug_api::DebugApiServer, models::transaction_request::NormalizedTransactionRequest,
+    providers::debug_provider::DebugProvider, tracing::diff::TransactionDiff,
+};
 use alloy_primitives::{Bytes, B256};
-use alloy_rpc_types::{BlockId, BlockNumberOrTag, TransactionRequest};
+use alloy_rpc_types::{BlockId, BlockNumberOrTag};
 use alloy_rpc_types_trace::geth::{GethDebugTracingCallOptions, GethDebugTracingOptions, GethTrace, TraceResult};
-use jsonrpsee::core::{async_trait, RpcResult};
+use jsonrpsee::{
+    core::{async_trait, RpcResult, SubscriptionResult},
+    PendingSubscriptionSink, SubscriptionMessage,
+};
+use tokio::sync::mpsc;
+
+/// Number of transaction traces buffered between the tracer and a `debug_subscribeTraceBlock` subscriber.
+const TRACE_STREAM_BUFFER_SIZE: usize = 16;
 
 /// The RPC module for the implementing Net api
 #[derive(Debug)]
@@ -76,6 +86,32 @@ where
         self.debug_provider.trace_block_by_hash(block_hash, opts).await.map_err(Into::into)
     }
 
+    /// Streams the Geth debug traces of the given block, one transaction at a time.
+    async fn subscribe_trace_block(
+        &self,
+        pending: PendingSubscriptionSink,
+        block_id: BlockId,
+        opts: Option<GethDebugTracingOptions>,
+    ) -> SubscriptionResult {
+        let sink = pending.accept().await?;
+        let (sender, mut receiver) = mpsc::channel(TRACE_STREAM_BUFFER_SIZE);
+
+        // Dropping the receiver when the subscriber is gone stops the tracing
+        let forward = async move {
+            while let Some(trace) = receiver.recv().await {
+                let Ok(message) = SubscriptionMessage::from_json(&trace) else { break };
+                if sink.send(message).await.is_err() {
+                    break;
+                }
+            }
+        };
+
+        let (result, ()) = tokio::join!(self.debug_provider.trace_block_streamed(block_id, opts, sender), forward);
+        result?;
+
+        Ok(())
+    }
+
     /// Returns the Geth debug trace for the given transaction hash.
     #[tracing::instrument(skip(self, opts), err)]
     async fn trace_transaction(
@@ -90,10 +126,16 @@ where
     #[tracing::instrument(skip(self, request, opts), err)]
     async fn trace_call(
         &self,
-        request: TransactionRequest,
+        request: NormalizedTransactionRequest,
         block_number: Option<BlockId>,
         opts: Option<GethDebugTracingCallOptions>,
     ) -> RpcResult<GethTrace> {
-        self.debug_provider.trace_call(request, block_number, opts).await.map_err(Into::into)
+        self.debug_provider.trace_call(request.into(), block_number, opts).await.map_err(Into::into)
+    }
+
+    /// Re-executes a mined transaction with the local EVM and compares it with its execution by Kakarot.
+    #[tracing::instrument(skip(self), err)]
+    async fn diff_transaction(&self, transaction_hash: B256) -> RpcResult<Option<TransactionDiff>> {
+        self.debug_provider.diff_transaction(transaction_hash).await.map_err(Into::into)
     }
 }
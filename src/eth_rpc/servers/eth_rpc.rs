@@ -1,25 +1,40 @@
 use crate::{
-    client::{EthClient, TransactionHashProvider},
+    client::{EthClient, PendingBlockProvider, TransactionHashProvider},
     eth_rpc::api::eth_api::EthApiServer,
+    models::{
+        filter::NormalizedFilter,
+        log_subscription::{AbiLogFilter, LogFilterExtension},
+        transaction::rpc_transaction_to_raw,
+        transaction_request::{normalize_transaction_request, NormalizedTransactionRequest},
+    },
     providers::eth_provider::{
-        constant::MAX_PRIORITY_FEE_PER_GAS,
+        constant::{MAX_PRIORITY_FEE_PER_GAS, PRIORITY_FEE_POLICY},
         database::types::{header::ExtendedBlock, receipt::ExtendedTxReceipt, transaction::ExtendedTransaction},
-        error::EthApiError,
+        error::{EthApiError, EthRpcErrorCode, TransactionError},
+        provider::EthApiResult,
         BlockProvider, ChainProvider, GasProvider, LogProvider, ReceiptProvider, StateProvider, TransactionProvider,
     },
 };
 use alloy_eips::{BlockId, BlockNumberOrTag};
 use alloy_primitives::{Address, Bytes, B256, B64, U256, U64};
 use alloy_rpc_types::{
-    serde_helpers::JsonStorageKey, state::StateOverride, AccessListResult, BlockOverrides, EIP1186AccountProofResponse,
-    FeeHistory, Filter, FilterChanges, Index, SyncStatus, TransactionRequest, Work,
+    pubsub::SubscriptionKind, serde_helpers::JsonStorageKey, state::StateOverride, AccessListResult, BlockOverrides,
+    Bundle, EIP1186AccountProofResponse, EthCallResponse, FeeHistory, Filter, FilterChanges, Index, Log, StateContext,
+    SyncStatus, TransactionRequest, Work,
+};
+use jsonrpsee::{
+    core::{async_trait, RpcResult, SubscriptionResult},
+    types::ErrorObject,
+    PendingSubscriptionSink, SubscriptionMessage,
 };
-use jsonrpsee::core::{async_trait, RpcResult};
 use serde_json::Value;
 use starknet::providers::Provider;
-use std::sync::Arc;
+use std::{sync::Arc, time::Duration};
 use tracing::Level;
 
+/// Interval between two polls of the indexed blocks by the log subscriptions, in milliseconds.
+const LOG_SUBSCRIPTION_INTERVAL_MS: u64 = 1000;
+
 /// The RPC module for the Ethereum protocol required by Kakarot.
 #[derive(Debug)]
 pub struct EthRpc<SP>
@@ -27,14 +42,49 @@ where
     SP: Provider + Send + Sync,
 {
     eth_client: Arc<EthClient<SP>>,
+    /// The chain id of the network, cached at construction.
+    chain_id: U64,
 }
 
 impl<SP> EthRpc<SP>
 where
-    SP: Provider + Send + Sync,
+    SP: Provider + Clone + Send + Sync,
 {
-    pub const fn new(eth_client: Arc<EthClient<SP>>) -> Self {
-        Self { eth_client }
+    pub fn new(eth_client: Arc<EthClient<SP>>) -> Self {
+        let chain_id = U64::from(eth_client.eth_provider().chain_id);
+        Self { eth_client, chain_id }
+    }
+
+    /// Returns the logs matching the filters in the blocks indexed after `last_block`, which is
+    /// moved to the latest block.
+    async fn new_logs(
+        &self,
+        filter: &Filter,
+        abi_filter: Option<&AbiLogFilter>,
+        last_block: &mut u64,
+    ) -> EthApiResult<Vec<Log>> {
+        let eth_provider = self.eth_client.eth_provider();
+        let latest = eth_provider.block_number().await?.to::<u64>();
+        if latest <= *last_block {
+            return Ok(Vec::new());
+        }
+        let range = filter.clone().from_block(*last_block + 1).to_block(latest);
+        *last_block = latest;
+
+        let FilterChanges::Logs(logs) = eth_provider.get_logs(range).await? else { return Ok(Vec::new()) };
+        Ok(logs.into_iter().filter(|log| abi_filter.map_or(true, |abi_filter| abi_filter.matches(log))).collect())
+    }
+
+    /// Returns the raw bytes of the transaction: the bytes submitted to the node if it was sent
+    /// to the node, else the encoding rebuilt from the fields of the transaction.
+    async fn raw_transaction(&self, transaction: Option<ExtendedTransaction>) -> EthApiResult<Option<Bytes>> {
+        let Some(transaction) = transaction else {
+            return Ok(None);
+        };
+        if let Some(raw) = self.eth_client.eth_provider().submitted_raw_transaction(transaction.hash).await? {
+            return Ok(Some(raw));
+        }
+        rpc_transaction_to_raw(transaction).map(Some)
     }
 }
 
@@ -54,7 +104,9 @@ where
     }
 
     async fn coinbase(&self) -> RpcResult<Address> {
-        Err(EthApiError::Unsupported("eth_coinbase").into())
+        // The fees are collected by the coinbase set in Kakarot core, which is the miner of the blocks
+        let header = self.eth_client.eth_provider().header(&BlockId::latest()).await?;
+        Ok(header.ok_or(EthApiError::UnknownBlockNumber(None))?.miner)
     }
 
     #[tracing::instrument(skip_all, ret, err)]
@@ -62,9 +114,8 @@ where
         Ok(Vec::new())
     }
 
-    #[tracing::instrument(skip_all, ret, err)]
-    async fn chain_id(&self) -> RpcResult<Option<U64>> {
-        Ok(self.eth_client.eth_provider().chain_id().await?)
+    fn chain_id(&self) -> RpcResult<Option<U64>> {
+        Ok(Some(self.chain_id))
     }
 
     #[tracing::instrument(skip(self), ret, err)]
@@ -74,16 +125,26 @@ where
 
     #[tracing::instrument(skip(self), err)]
     async fn block_by_number(&self, number: BlockNumberOrTag, full: bool) -> RpcResult<Option<ExtendedBlock>> {
+        if number.is_pending() {
+            return Ok(self.eth_client.pending_block(full).await?);
+        }
         Ok(self.eth_client.eth_provider().block_by_number(number, full).await?)
     }
 
     #[tracing::instrument(skip(self), ret, err)]
     async fn block_transaction_count_by_hash(&self, hash: B256) -> RpcResult<Option<U256>> {
+        // The pending block has a zero hash
+        if hash.is_zero() {
+            return Ok(self.eth_client.pending_block_transaction_count().await?);
+        }
         Ok(self.eth_client.eth_provider().block_transaction_count_by_hash(hash).await?)
     }
 
     #[tracing::instrument(skip(self), ret, err)]
     async fn block_transaction_count_by_number(&self, number: BlockNumberOrTag) -> RpcResult<Option<U256>> {
+        if number.is_pending() {
+            return Ok(self.eth_client.pending_block_transaction_count().await?);
+        }
         Ok(self.eth_client.eth_provider().block_transaction_count_by_number(number).await?)
     }
 
@@ -122,6 +183,10 @@ where
         hash: B256,
         index: Index,
     ) -> RpcResult<Option<ExtendedTransaction>> {
+        // The pending block has a zero hash
+        if hash.is_zero() {
+            return Ok(self.eth_client.pending_transaction_by_index(index).await?);
+        }
         Ok(self.eth_client.eth_provider().transaction_by_block_hash_and_index(hash, index).await?)
     }
 
@@ -131,12 +196,37 @@ where
         number: BlockNumberOrTag,
         index: Index,
     ) -> RpcResult<Option<ExtendedTransaction>> {
+        if number.is_pending() {
+            return Ok(self.eth_client.pending_transaction_by_index(index).await?);
+        }
         Ok(self.eth_client.eth_provider().transaction_by_block_number_and_index(number, index).await?)
     }
 
+    #[tracing::instrument(skip(self), ret, err)]
+    async fn raw_transaction_by_hash(&self, hash: B256) -> RpcResult<Option<Bytes>> {
+        let transaction = self.transaction_by_hash(hash).await?;
+        Ok(self.raw_transaction(transaction).await?)
+    }
+
+    #[tracing::instrument(skip(self), ret, err)]
+    async fn raw_transaction_by_block_hash_and_index(&self, hash: B256, index: Index) -> RpcResult<Option<Bytes>> {
+        let transaction = self.transaction_by_block_hash_and_index(hash, index).await?;
+        Ok(self.raw_transaction(transaction).await?)
+    }
+
+    #[tracing::instrument(skip(self), ret, err)]
+    async fn raw_transaction_by_block_number_and_index(
+        &self,
+        number: BlockNumberOrTag,
+        index: Index,
+    ) -> RpcResult<Option<Bytes>> {
+        let transaction = self.transaction_by_block_number_and_index(number, index).await?;
+        Ok(self.raw_transaction(transaction).await?)
+    }
+
     #[tracing::instrument(skip(self), ret, err)]
     async fn transaction_receipt(&self, hash: B256) -> RpcResult<Option<ExtendedTxReceipt>> {
-        Ok(self.eth_client.eth_provider().transaction_receipt(hash).await?)
+        Ok(self.eth_client.transaction_receipt(hash).await?)
     }
 
     #[tracing::instrument(skip(self), ret, err)]
@@ -160,7 +250,8 @@ where
     }
 
     #[tracing::instrument(skip_all, err)]
-    async fn get_logs(&self, filter: Filter) -> RpcResult<FilterChanges> {
+    async fn get_logs(&self, filter: NormalizedFilter) -> RpcResult<FilterChanges> {
+        let filter: Filter = filter.into();
         tracing::info!(?filter);
         Ok(self.eth_client.eth_provider().get_logs(filter).await?)
     }
@@ -168,12 +259,31 @@ where
     #[tracing::instrument(skip(self, request), err)]
     async fn call(
         &self,
-        request: TransactionRequest,
+        request: NormalizedTransactionRequest,
         block_id: Option<BlockId>,
         state_overrides: Option<StateOverride>,
         block_overrides: Option<Box<BlockOverrides>>,
     ) -> RpcResult<Bytes> {
-        Ok(self.eth_client.eth_provider().call(request, block_id, state_overrides, block_overrides).await?)
+        Ok(self.eth_client.eth_provider().call(request.into(), block_id, state_overrides, block_overrides).await?)
+    }
+
+    #[tracing::instrument(skip(self, bundles), err)]
+    async fn call_many(
+        &self,
+        bundles: Vec<Bundle>,
+        state_context: Option<StateContext>,
+        state_overrides: Option<StateOverride>,
+    ) -> RpcResult<Vec<Vec<EthCallResponse>>> {
+        let bundles = bundles
+            .into_iter()
+            .map(|mut bundle| {
+                bundle.transactions =
+                    bundle.transactions.into_iter().map(normalize_transaction_request).collect::<Result<_, _>>()?;
+                Ok(bundle)
+            })
+            .collect::<Result<_, TransactionError>>()
+            .map_err(EthApiError::from)?;
+        Ok(self.eth_client.eth_provider().call_many(bundles, state_context, state_overrides).await?)
     }
 
     async fn create_access_list(
@@ -185,8 +295,8 @@ where
     }
 
     #[tracing::instrument(skip(self, request), err)]
-    async fn estimate_gas(&self, request: TransactionRequest, block_id: Option<BlockId>) -> RpcResult<U256> {
-        Ok(U256::from(self.eth_client.eth_provider().estimate_gas(request, block_id).await?))
+    async fn estimate_gas(&self, request: NormalizedTransactionRequest, block_id: Option<BlockId>) -> RpcResult<U256> {
+        Ok(U256::from(self.eth_client.eth_provider().estimate_gas(request.into(), block_id).await?))
     }
 
     #[tracing::instrument(skip_all, ret, err)]
@@ -207,34 +317,38 @@ where
 
     #[tracing::instrument(skip_all, ret, err)]
     async fn max_priority_fee_per_gas(&self) -> RpcResult<U256> {
-        Ok(U256::from(*MAX_PRIORITY_FEE_PER_GAS))
+        // The priority fee only orders the mempool if it isn't charged
+        if !PRIORITY_FEE_POLICY.is_charged() {
+            return Ok(U256::from(*MAX_PRIORITY_FEE_PER_GAS));
+        }
+        Ok(self.eth_client.eth_provider().suggest_fees().await?.normal.max_priority_fee_per_gas)
     }
 
     async fn blob_base_fee(&self) -> RpcResult<U256> {
         Err(EthApiError::Unsupported("eth_blobBaseFee").into())
     }
 
+    // Kakarot blocks aren't mined: the mining endpoints answer like a node which doesn't mine,
+    // see <https://github.com/ethereum/go-ethereum/blob/master/eth/api_miner.go>
+
     async fn mining(&self) -> RpcResult<bool> {
-        tracing::warn!("Kakarot chain does not use mining");
         Ok(false)
     }
 
     async fn hashrate(&self) -> RpcResult<U256> {
-        tracing::warn!("Kakarot chain does not produce hash rate");
         Ok(U256::ZERO)
     }
 
     async fn get_work(&self) -> RpcResult<Work> {
-        tracing::warn!("Kakarot chain does not produce work");
-        Ok(Work::default())
+        Err(ErrorObject::owned(EthRpcErrorCode::InvalidInput as i32, "no mining work available yet", None::<()>))
     }
 
     async fn submit_hashrate(&self, _hashrate: U256, _id: B256) -> RpcResult<bool> {
-        Err(EthApiError::Unsupported("eth_submitHashrate").into())
+        Ok(false)
     }
 
     async fn submit_work(&self, _nonce: B64, _pow_hash: B256, _mix_digest: B256) -> RpcResult<bool> {
-        Err(EthApiError::Unsupported("eth_submitWork").into())
+        Ok(false)
     }
 
     async fn send_transaction(&self, _request: TransactionRequest) -> RpcResult<B256> {
@@ -288,7 +402,7 @@ where
         Err(EthApiError::Unsupported("eth_getProof").into())
     }
 
-    async fn new_filter(&self, _filter: Filter) -> RpcResult<U64> {
+    async fn new_filter(&self, _filter: NormalizedFilter) -> RpcResult<U64> {
         Err(EthApiError::Unsupported("eth_newFilter").into())
     }
 
@@ -315,4 +429,53 @@ where
     async fn block_receipts(&self, block_id: Option<BlockId>) -> RpcResult<Option<Vec<ExtendedTxReceipt>>> {
         Ok(self.eth_client.eth_provider().block_receipts(block_id).await?)
     }
+
+    async fn subscribe(
+        &self,
+        pending: PendingSubscriptionSink,
+        kind: SubscriptionKind,
+        filter: Option<NormalizedFilter>,
+        extension: Option<LogFilterExtension>,
+    ) -> SubscriptionResult {
+        if kind != SubscriptionKind::Logs {
+            pending.reject(EthApiError::Unsupported("eth_subscribe")).await;
+            return Ok(());
+        }
+        let abi_filter = match extension.map(AbiLogFilter::try_from).transpose() {
+            Ok(abi_filter) => abi_filter,
+            Err(err) => {
+                pending
+                    .reject(ErrorObject::owned(EthRpcErrorCode::InvalidParams as i32, err.to_string(), None::<()>))
+                    .await;
+                return Ok(());
+            }
+        };
+
+        // The topics of the event pre-filter the logs in the database, the values of the
+        // arguments held in the data of the logs are matched on the server.
+        let filter = filter.map(Filter::from).unwrap_or_default();
+        let filter = match &abi_filter {
+            Some(abi_filter) => abi_filter.apply_topics(filter),
+            None => filter,
+        };
+
+        let mut last_block = self.eth_client.eth_provider().block_number().await?.to::<u64>();
+        let mut interval = tokio::time::interval(Duration::from_millis(LOG_SUBSCRIPTION_INTERVAL_MS));
+
+        let sink = pending.accept().await?;
+        loop {
+            tokio::select! {
+                () = sink.closed() => break,
+                _ = interval.tick() => {
+                    for log in self.new_logs(&filter, abi_filter.as_ref(), &mut last_block).await? {
+                        if sink.send(SubscriptionMessage::from_json(&log)?).await.is_err() {
+                            return Ok(());
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
 }
@@ -0,0 +1,218 @@
+use crate::{
+    client::EthClient,
+    constants::{DRY_RUN, KAKAROT_RPC_CONFIG},
+    eth_rpc::{api::faucet_api::KakarotFaucetApiServer, middleware::client_ip::ClientIp},
+    into_via_try_wrapper,
+    pool::{
+        constants::ONE_TENTH_ETH,
+        relayers::{RelayerRegistry, TrafficClass},
+    },
+    providers::eth_provider::{
+        error::{EthApiError, EthRpcErrorCode},
+        starknet::relayer::Relayer,
+    },
+};
+use alloy_primitives::{Address, U256};
+use jsonrpsee::{
+    core::{async_trait, RpcResult},
+    types::ErrorObject,
+    Extensions,
+};
+use starknet::{
+    core::types::{BlockId, BlockTag, Felt},
+    providers::{jsonrpc::HttpTransport, JsonRpcClient, Provider},
+};
+use std::{
+    collections::HashMap,
+    net::IpAddr,
+    sync::{Arc, LazyLock},
+    time::{Duration, Instant},
+};
+use tokio::sync::Mutex;
+use tracing::instrument;
+
+/// The amount of test ETH transferred by the faucet, in wei. Set with the `FAUCET_AMOUNT_WEI`
+/// environment variable, defaults to 0.1 ETH.
+pub static FAUCET_AMOUNT_WEI: LazyLock<u64> =
+    LazyLock::new(|| std::env::var("FAUCET_AMOUNT_WEI").ok().and_then(|val| val.parse().ok()).unwrap_or(ONE_TENTH_ETH));
+
+/// The minimum interval between two requests of funds of an address or of a client IP. Set with
+/// the `FAUCET_INTERVAL_SECS` environment variable, defaults to a day.
+pub static FAUCET_INTERVAL: LazyLock<Duration> = LazyLock::new(|| {
+    Duration::from_secs(std::env::var("FAUCET_INTERVAL_SECS").ok().and_then(|val| val.parse().ok()).unwrap_or(86400))
+});
+
+/// A requester of funds, limited to one request per interval.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Requester {
+    Address(Address),
+    Ip(IpAddr),
+}
+
+/// Limits the requests of funds of each address and each client IP to one per interval.
+#[derive(Debug)]
+pub struct FaucetLimiter {
+    interval: Duration,
+    last_requests: HashMap<Requester, Instant>,
+}
+
+impl FaucetLimiter {
+    /// Creates a new limiter with the given interval between two requests of a requester.
+    pub fn new(interval: Duration) -> Self {
+        Self { interval, last_requests: HashMap::new() }
+    }
+
+    /// Returns the time to wait before the address or the client IP can request funds again, if
+    /// any.
+    pub fn retry_after(&mut self, address: Address, ip: IpAddr, now: Instant) -> Option<Duration> {
+        let interval = self.interval;
+        self.last_requests.retain(|_, last| now.duration_since(*last) < interval);
+
+        Self::requesters(address, ip)
+            .filter_map(|requester| self.last_requests.get(&requester))
+            .map(|last| interval.saturating_sub(now.duration_since(*last)))
+            .max()
+    }
+
+    /// Records a request of funds of the address, from the client IP.
+    pub fn record(&mut self, address: Address, ip: IpAddr, now: Instant) {
+        for requester in Self::requesters(address, ip) {
+            self.last_requests.insert(requester, now);
+        }
+    }
+
+    fn requesters(address: Address, ip: IpAddr) -> impl Iterator<Item = Requester> {
+        [Requester::Address(address), Requester::Ip(ip)].into_iter()
+    }
+}
+
+/// The RPC module for implementing the Kakarot faucet API.
+#[derive(Debug)]
+pub struct KakarotFaucetRpc<SP: Provider + Send + Sync> {
+    eth_client: Arc<EthClient<SP>>,
+    relayers: Arc<RelayerRegistry>,
+    /// The limiter of the requests, locked during the transfer so that the transfers are
+    /// sequential.
+    limiter: Mutex<FaucetLimiter>,
+}
+
+impl<SP> KakarotFaucetRpc<SP>
+where
+    SP: Provider + Clone + Send + Sync,
+{
+    pub fn new(eth_client: Arc<EthClient<SP>>, relayers: Arc<RelayerRegistry>) -> Self {
+        Self { eth_client, relayers, limiter: Mutex::new(FaucetLimiter::new(*FAUCET_INTERVAL)) }
+    }
+
+    /// Returns the first faucet relayer whose balance covers the transferred amount and the
+    /// relaying fees. Only the dedicated faucet relayers pay out, so that the faucet can't drain
+    /// the relayers paying for the transactions of the users.
+    async fn funded_relayer(&self, amount: U256) -> RpcResult<Relayer<JsonRpcClient<HttpTransport>>> {
+        let addresses = self.relayers.dedicated_addresses_for(TrafficClass::Faucet);
+        if addresses.is_empty() {
+            return Err(ErrorObject::owned(
+                EthRpcErrorCode::ResourceUnavailable as i32,
+                "the faucet has no relayer",
+                None::<()>,
+            ));
+        }
+
+        for address in addresses {
+            let balance = self
+                .eth_client
+                .starknet_provider()
+                .balance_at(address, BlockId::Tag(BlockTag::Pending))
+                .await
+                .map_err(EthApiError::from)?;
+            if balance < amount + U256::from(ONE_TENTH_ETH) {
+                continue;
+            }
+
+            let balance = into_via_try_wrapper!(balance - amount).map_err(EthApiError::from)?;
+            return Ok(Relayer::new(
+                address,
+                balance,
+                JsonRpcClient::new(HttpTransport::new(KAKAROT_RPC_CONFIG.network_url.clone())),
+                Some(Arc::new(self.eth_client.eth_provider().database().clone())),
+            )
+            .with_dry_run(*DRY_RUN));
+        }
+
+        Err(ErrorObject::owned(
+            EthRpcErrorCode::ResourceUnavailable as i32,
+            "the faucet is out of funds, retry later",
+            None::<()>,
+        ))
+    }
+}
+
+#[async_trait]
+impl<SP> KakarotFaucetApiServer for KakarotFaucetRpc<SP>
+where
+    SP: Provider + Clone + Send + Sync + 'static,
+{
+    #[instrument(skip(self, extensions))]
+    async fn request_funds(&self, extensions: &Extensions, address: Address) -> RpcResult<Felt> {
+        // The requests of an unknown client can't be rate limited
+        let Some(ClientIp(ip)) = extensions.get::<ClientIp>().copied() else {
+            return Err(ErrorObject::owned(
+                EthRpcErrorCode::InvalidRequest as i32,
+                "the client IP of the request can't be determined",
+                None::<()>,
+            ));
+        };
+
+        let mut limiter = self.limiter.lock().await;
+        if let Some(retry_after) = limiter.retry_after(address, ip, Instant::now()) {
+            return Err(ErrorObject::owned(
+                EthRpcErrorCode::RequestLimitExceeded as i32,
+                format!("funds were already requested, retry in {}s", retry_after.as_secs()),
+                None::<()>,
+            ));
+        }
+
+        let amount = U256::from(*FAUCET_AMOUNT_WEI);
+        let relayer = self.funded_relayer(amount).await?;
+        let starknet_hash = relayer.transfer(address, amount).await?;
+        limiter.record(address, ip, Instant::now());
+        drop(limiter);
+
+        tracing::info!(
+            target: "faucet",
+            monotonic_counter.faucet_transfers = 1,
+            ?address,
+            %ip,
+            ?starknet_hash,
+            relayer = ?relayer.address(),
+            "transferred test funds"
+        );
+        Ok(starknet_hash)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::Ipv4Addr;
+
+    #[test]
+    fn test_faucet_limiter() {
+        // Given
+        let mut limiter = FaucetLimiter::new(Duration::from_secs(60));
+        let now = Instant::now();
+        let (ip, other_ip) = (IpAddr::V4(Ipv4Addr::LOCALHOST), IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1)));
+        let (first, second) = (Address::left_padding_from(&[1]), Address::left_padding_from(&[2]));
+
+        // When
+        let allowed = limiter.retry_after(first, ip, now);
+        limiter.record(first, ip, now);
+
+        // Then
+        let later = now + Duration::from_secs(20);
+        assert_eq!(allowed, None);
+        assert_eq!(limiter.retry_after(first, other_ip, later), Some(Duration::from_secs(40)));
+        assert_eq!(limiter.retry_after(second, ip, later), Some(Duration::from_secs(40)));
+        assert_eq!(limiter.retry_after(second, other_ip, later), None);
+        assert_eq!(limiter.retry_after(first, ip, now + Duration::from_secs(60)), None);
+    }
+}
@@ -1,18 +1,69 @@
 use crate::{
+    client::EthClient,
     config::KakarotRpcConfig,
-    eth_rpc::api::kakarot_api::KakarotApiServer,
+    constants::{DRY_RUN, KAKAROT_RPC_CONFIG},
+    eth_rpc::api::kakarot_api::{KakarotAdminApiServer, KakarotApiServer, KakarotPoolApiServer},
+    into_via_try_wrapper,
+    pool::{
+        build_stats::{BlockBuildStats, BLOCK_BUILD_STATS},
+        constants::ONE_TENTH_ETH,
+        content::{TxpoolContentCursor, TxpoolContentPage},
+        nonce_gaps::NonceGaps,
+        relayers::RelayerRegistry,
+    },
     providers::eth_provider::{
-        constant::{Constant, MAX_LOGS},
-        starknet::kakarot_core::{get_white_listed_eip_155_transaction_hashes, MAX_FELTS_IN_CALLDATA},
+        constant::{
+            Capabilities, ChainMetadata, Constant, CHAIN_METADATA, KAKAROT_CAPABILITIES, MAX_LOGS, PRIORITY_FEE_POLICY,
+        },
+        database::types::{
+            header::ExtendedBlock,
+            transaction::{DroppedTransaction, ExtendedTransaction, PoolEvent},
+        },
+        error::{EthApiError, EthRpcErrorCode},
+        gas::{DataAvailabilityCost, FeeSuggestions},
+        logs::{LogQuery, LogQueryResult},
+        provider::EthereumProvider,
+        receipts::{ReceiptProof, TransactionConfirmations, TransactionExecutionResources},
+        starknet::{
+            kakarot_core::{get_white_listed_eip_155_transaction_hashes, MAX_FELTS_IN_CALLDATA},
+            relayer::Relayer,
+        },
+        state::AccountType,
+        transactions::{AddressHistory, AddressHistoryRequest},
+        LogProvider, StateProvider, TransactionProvider, TxPoolProvider,
     },
 };
-use jsonrpsee::core::{async_trait, RpcResult};
+use alloy_primitives::{Address, B256, U256, U64};
+use alloy_rpc_types::TransactionRequest;
+use jsonrpsee::{
+    core::{async_trait, RpcResult},
+    types::ErrorObject,
+};
+use reth_transaction_pool::TransactionPool;
+use starknet::{
+    core::types::{BlockId, BlockTag, Felt},
+    providers::{jsonrpc::HttpTransport, JsonRpcClient, Provider},
+};
+use std::{
+    sync::{Arc, LazyLock},
+    time::{Duration, Instant},
+};
+use tokio::sync::Mutex;
+use tracing::instrument;
 
 #[derive(Debug)]
-pub struct KakarotRpc;
+pub struct KakarotRpc<P: EthereumProvider> {
+    eth_provider: P,
+}
+
+impl<P: EthereumProvider> KakarotRpc<P> {
+    pub const fn new(eth_provider: P) -> Self {
+        Self { eth_provider }
+    }
+}
 
 #[async_trait]
-impl KakarotApiServer for KakarotRpc {
+impl<P: EthereumProvider + Send + Sync + 'static> KakarotApiServer for KakarotRpc<P> {
     async fn get_config(&self) -> RpcResult<Constant> {
         let starknet_config = KakarotRpcConfig::from_env().expect("Failed to load Kakarot RPC config");
         Ok(Constant {
@@ -21,6 +72,208 @@ impl KakarotApiServer for KakarotRpc {
             max_felts_in_calldata: *MAX_FELTS_IN_CALLDATA,
             white_listed_eip_155_transaction_hashes: get_white_listed_eip_155_transaction_hashes(),
             kakarot_address: starknet_config.kakarot_address,
+            priority_fee_policy: *PRIORITY_FEE_POLICY,
         })
     }
+
+    async fn get_transaction_confirmations(&self, hash: B256) -> RpcResult<Option<TransactionConfirmations>> {
+        Ok(self.eth_provider.transaction_confirmations(hash).await?)
+    }
+
+    async fn get_address_history(&self, request: AddressHistoryRequest) -> RpcResult<AddressHistory> {
+        Ok(self.eth_provider.address_history(request).await?)
+    }
+
+    async fn get_dropped_transaction(&self, hash: B256) -> RpcResult<Option<DroppedTransaction>> {
+        Ok(self.eth_provider.dropped_transaction(hash).await?)
+    }
+
+    async fn get_transaction_timeline(&self, hash: B256) -> RpcResult<Vec<PoolEvent>> {
+        Ok(self.eth_provider.transaction_timeline(hash).await?)
+    }
+
+    async fn suggest_fees(&self, request: Option<TransactionRequest>) -> RpcResult<FeeSuggestions> {
+        let mut suggestions = self.eth_provider.suggest_fees().await?;
+        if let Some(request) = request {
+            suggestions.data_availability = Some(self.eth_provider.data_availability_cost(request).await?);
+        }
+        Ok(suggestions)
+    }
+
+    async fn estimate_data_availability_cost(&self, request: TransactionRequest) -> RpcResult<DataAvailabilityCost> {
+        Ok(self.eth_provider.data_availability_cost(request).await?)
+    }
+
+    async fn get_receipt_proof(&self, hash: B256) -> RpcResult<Option<ReceiptProof>> {
+        Ok(self.eth_provider.receipt_proof(hash).await?)
+    }
+
+    #[instrument(skip(self))]
+    async fn get_execution_resources(&self, hash: B256) -> RpcResult<Option<TransactionExecutionResources>> {
+        Ok(self.eth_provider.execution_resources(hash).await?)
+    }
+
+    async fn get_capabilities(&self) -> RpcResult<Capabilities> {
+        Ok(KAKAROT_CAPABILITIES.clone())
+    }
+
+    async fn get_chain_metadata(&self) -> RpcResult<ChainMetadata> {
+        Ok(CHAIN_METADATA.clone())
+    }
+
+    async fn get_account_type(
+        &self,
+        address: Address,
+        block_id: Option<alloy_eips::BlockId>,
+    ) -> RpcResult<AccountType> {
+        Ok(self.eth_provider.account_type(address, block_id).await?)
+    }
+
+    async fn get_block_by_starknet_hash(&self, hash: B256, full: bool) -> RpcResult<Option<ExtendedBlock>> {
+        Ok(self.eth_provider.block_by_starknet_hash(hash, full).await?)
+    }
+
+    async fn query_logs(&self, query: LogQuery) -> RpcResult<LogQueryResult> {
+        Ok(self.eth_provider.query_logs(query).await?)
+    }
+}
+
+/// The RPC module for implementing the Kakarot API of the mempool.
+#[derive(Debug)]
+pub struct KakarotPoolRpc<SP: Provider + Send + Sync> {
+    eth_client: Arc<EthClient<SP>>,
+}
+
+impl<SP> KakarotPoolRpc<SP>
+where
+    SP: Provider + Clone + Send + Sync,
+{
+    pub const fn new(eth_client: Arc<EthClient<SP>>) -> Self {
+        Self { eth_client }
+    }
+}
+
+#[async_trait]
+impl<SP> KakarotPoolApiServer for KakarotPoolRpc<SP>
+where
+    SP: Provider + Clone + Send + Sync + 'static,
+{
+    #[instrument(skip(self))]
+    async fn get_nonce_gaps(&self, address: Address) -> RpcResult<NonceGaps> {
+        let block_id = alloy_eips::BlockId::Number(alloy_eips::BlockNumberOrTag::Pending);
+        let onchain_nonce = self.eth_client.eth_provider().transaction_count(address, Some(block_id)).await?;
+        let pool_nonces =
+            self.eth_client.mempool().get_transactions_by_sender(address).into_iter().map(|tx| tx.nonce());
+
+        Ok(NonceGaps::new(address, onchain_nonce.saturating_to(), pool_nonces))
+    }
+
+    #[instrument(skip(self))]
+    async fn get_block_build_stats(&self, block_number: U64) -> RpcResult<Option<BlockBuildStats>> {
+        Ok(BLOCK_BUILD_STATS.get(block_number.to()))
+    }
+
+    #[instrument(skip(self))]
+    async fn txpool_content_paginated(
+        &self,
+        cursor: Option<TxpoolContentCursor>,
+        limit: Option<U64>,
+    ) -> RpcResult<TxpoolContentPage<ExtendedTransaction>> {
+        Ok(TxpoolContentPage::new(self.eth_client.content(), cursor, limit.map(|limit| limit.to())))
+    }
+}
+
+/// Minimum interval between two account deployments, paid by the relayers. Set with the
+/// `DEPLOY_ACCOUNT_INTERVAL_SECS` environment variable, defaults to 10 seconds.
+pub static DEPLOY_ACCOUNT_INTERVAL: LazyLock<Duration> = LazyLock::new(|| {
+    Duration::from_secs(
+        std::env::var("DEPLOY_ACCOUNT_INTERVAL_SECS").ok().and_then(|val| val.parse().ok()).unwrap_or(10),
+    )
+});
+
+/// The RPC module for implementing the Kakarot API reserved to the operators of the node.
+#[derive(Debug)]
+pub struct KakarotAdminRpc<SP: Provider + Send + Sync> {
+    eth_client: Arc<EthClient<SP>>,
+    relayers: Arc<RelayerRegistry>,
+    /// The time of the last account deployment, used to rate limit the deployments.
+    last_deployment: Mutex<Option<Instant>>,
+}
+
+impl<SP> KakarotAdminRpc<SP>
+where
+    SP: Provider + Clone + Send + Sync,
+{
+    pub fn new(eth_client: Arc<EthClient<SP>>, relayers: Arc<RelayerRegistry>) -> Self {
+        Self { eth_client, relayers, last_deployment: Mutex::new(None) }
+    }
+
+    /// Returns the first relayer whose balance covers the relaying fees, see
+    /// `AccountManager::get_relayer`.
+    async fn funded_relayer(&self) -> RpcResult<Relayer<JsonRpcClient<HttpTransport>>> {
+        for address in self.relayers.addresses() {
+            let balance = self
+                .eth_client
+                .starknet_provider()
+                .balance_at(address, BlockId::Tag(BlockTag::Pending))
+                .await
+                .map_err(EthApiError::from)?;
+            if balance < U256::from(ONE_TENTH_ETH) {
+                continue;
+            }
+
+            let balance = into_via_try_wrapper!(balance).map_err(EthApiError::from)?;
+            return Ok(Relayer::new(
+                address,
+                balance,
+                JsonRpcClient::new(HttpTransport::new(KAKAROT_RPC_CONFIG.network_url.clone())),
+                Some(Arc::new(self.eth_client.eth_provider().database().clone())),
+            )
+            .with_dry_run(*DRY_RUN));
+        }
+
+        Err(ErrorObject::owned(
+            EthRpcErrorCode::ResourceUnavailable as i32,
+            "no relayer is funded to deploy the account",
+            None::<()>,
+        ))
+    }
+}
+
+#[async_trait]
+impl<SP> KakarotAdminApiServer for KakarotAdminRpc<SP>
+where
+    SP: Provider + Clone + Send + Sync + 'static,
+{
+    #[instrument(skip(self))]
+    async fn deploy_account(&self, address: Address) -> RpcResult<Felt> {
+        let account_type = self.eth_client.eth_provider().account_type(address, None).await?;
+        if account_type != AccountType::Uninitialized {
+            return Err(ErrorObject::owned(
+                EthRpcErrorCode::InvalidInput as i32,
+                format!("account {address} is already deployed"),
+                None::<()>,
+            ));
+        }
+
+        // The lock is held during the deployment, so that the deployments are sequential
+        let mut last_deployment = self.last_deployment.lock().await;
+        if let Some(elapsed) = last_deployment.map(|last| last.elapsed()) {
+            if elapsed < *DEPLOY_ACCOUNT_INTERVAL {
+                return Err(ErrorObject::owned(
+                    EthRpcErrorCode::RequestLimitExceeded as i32,
+                    format!("an account was deployed {}s ago, retry later", elapsed.as_secs()),
+                    None::<()>,
+                ));
+            }
+        }
+
+        let relayer = self.funded_relayer().await?;
+        let starknet_hash = relayer.deploy_account(address).await?;
+        *last_deployment = Some(Instant::now());
+        drop(last_deployment);
+
+        tracing::info!(?address, ?starknet_hash, relayer = ?relayer.address(), "deployed account");
+        Ok(starknet_hash)
+    }
 }
@@ -0,0 +1,44 @@
+use crate::{
+    client::EthClient,
+    eth_rpc::api::meta_tx_api::KakarotMetaTxApiServer,
+    meta_tx::{MetaTransactionRequest, MetaTxConfig, MetaTxRelay, SponsorInfo},
+};
+use alloy_primitives::{Address, B256, U256};
+use jsonrpsee::core::{async_trait, RpcResult};
+use starknet::providers::Provider;
+use std::sync::Arc;
+use tracing::instrument;
+
+/// The RPC module for implementing the Kakarot meta-transaction API.
+#[derive(Debug)]
+pub struct KakarotMetaTxRpc<SP: Provider + Send + Sync> {
+    relay: MetaTxRelay<SP>,
+}
+
+impl<SP> KakarotMetaTxRpc<SP>
+where
+    SP: Provider + Clone + Send + Sync + 'static,
+{
+    pub fn new(eth_client: Arc<EthClient<SP>>, config: MetaTxConfig) -> Self {
+        Self { relay: MetaTxRelay::new(config, eth_client) }
+    }
+}
+
+#[async_trait]
+impl<SP> KakarotMetaTxApiServer for KakarotMetaTxRpc<SP>
+where
+    SP: Provider + Clone + Send + Sync + 'static,
+{
+    #[instrument(skip(self))]
+    async fn send_meta_transaction(&self, request: MetaTransactionRequest) -> RpcResult<B256> {
+        Ok(self.relay.relay(request).await?)
+    }
+
+    async fn get_meta_transaction_nonce(&self, address: Address) -> RpcResult<U256> {
+        Ok(self.relay.nonce(address).await?)
+    }
+
+    async fn get_meta_transaction_sponsors(&self) -> RpcResult<Vec<SponsorInfo>> {
+        Ok(self.relay.sponsors().await)
+    }
+}
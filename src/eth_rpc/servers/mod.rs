@@ -1,8 +1,16 @@
+pub mod admin_rpc;
 pub mod alchemy_rpc;
+#[cfg(feature = "chaos")]
+pub mod chaos_rpc;
 pub mod debug_rpc;
 pub mod eth_rpc;
+#[cfg(feature = "faucet")]
+pub mod faucet_rpc;
 pub mod kakarot_rpc;
+#[cfg(feature = "meta-tx")]
+pub mod meta_tx_rpc;
 pub mod net_rpc;
+pub mod openrpc_rpc;
 pub mod trace_rpc;
 pub mod txpool_rpc;
 pub mod web3_rpc;
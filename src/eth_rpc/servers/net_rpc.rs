@@ -6,18 +6,20 @@ use jsonrpsee::core::{async_trait, RpcResult};
 #[derive(Debug)]
 pub struct NetRpc<P: EthereumProvider> {
     eth_provider: P,
+    /// The chain id of the network, cached at construction.
+    chain_id: U64,
 }
 
 impl<P: EthereumProvider> NetRpc<P> {
-    pub const fn new(eth_provider: P) -> Self {
-        Self { eth_provider }
+    pub fn new(eth_provider: P, chain_id: u64) -> Self {
+        Self { eth_provider, chain_id: U64::from(chain_id) }
     }
 }
 
 #[async_trait]
 impl<P: EthereumProvider + Send + Sync + 'static> NetApiServer for NetRpc<P> {
-    async fn version(&self) -> RpcResult<U64> {
-        Ok(self.eth_provider.chain_id().await?.unwrap_or_default())
+    fn version(&self) -> RpcResult<U64> {
+        Ok(self.chain_id)
     }
 
     fn peer_count(&self) -> RpcResult<U64> {
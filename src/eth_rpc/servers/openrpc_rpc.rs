@@ -0,0 +1,20 @@
+use crate::eth_rpc::{api::openrpc_api::OpenRpcApiServer, openrpc::OPENRPC_DOCUMENT};
+use jsonrpsee::core::{async_trait, RpcResult};
+use serde_json::Value;
+
+/// The RPC module for the implementing `OpenRPC` Api { i.e rpc endpoints prefixed with rpc. }
+#[derive(Default, Debug)]
+pub struct OpenRpc {}
+
+impl OpenRpc {
+    pub const fn new() -> Self {
+        Self {}
+    }
+}
+
+#[async_trait]
+impl OpenRpcApiServer for OpenRpc {
+    fn discover(&self) -> RpcResult<Value> {
+        Ok(OPENRPC_DOCUMENT.clone())
+    }
+}
@@ -0,0 +1,273 @@
+//! Firehose-like export stream of the chain, for the streaming indexers (e.g. The Graph) which
+//! can't rely on polling the JSON-RPC.
+//!
+//! Once the stream is opened, `FIRE INIT 3.0 kakarot.v1.Block` is written, followed by one line
+//! per indexed block, in order:
+//!
+//! `FIRE BLOCK <number> <hash> <parent number> <parent hash> <lib number> <timestamp nanos> <payload>`
+//!
+//! The payload is the hex encoded JSON of the block with its transactions, its receipts and, if
+//! enabled, the call traces of its transactions. It is deterministic: the transactions, receipts
+//! and traces are ordered by transaction index. The blocks are indexed once accepted on
+//! Starknet, so each block is its own last irreversible block.
+
+use crate::{
+    client::EthClient,
+    providers::eth_provider::{
+        database::types::{header::ExtendedBlock, receipt::ExtendedTxReceipt},
+        BlockProvider, ReceiptProvider,
+    },
+    tracing::builder::TracerBuilder,
+};
+use alloy_eips::{BlockId, BlockNumberOrTag};
+use alloy_primitives::hex;
+use alloy_rpc_types_trace::geth::{
+    GethDebugBuiltInTracerType, GethDebugTracerType, GethDebugTracingOptions, TraceResult,
+};
+use eyre::{eyre, Result};
+use serde::Serialize;
+use std::{
+    path::PathBuf,
+    str::FromStr,
+    sync::{Arc, LazyLock},
+    time::Duration,
+};
+use tokio::{
+    fs::OpenOptions,
+    io::{AsyncWrite, AsyncWriteExt, BufWriter},
+    net::TcpStream,
+};
+
+/// The configuration of the export stream, read from the environment. The stream is disabled if
+/// `FIREHOSE_OUTPUT` is not set.
+pub static FIREHOSE_CONFIG: LazyLock<Option<FirehoseConfig>> = LazyLock::new(FirehoseConfig::from_env);
+
+/// The line opening the stream, with the version of the protocol and the type of the payloads.
+pub const FIREHOSE_INIT: &str = "FIRE INIT 3.0 kakarot.v1.Block";
+
+/// The destination of the export stream.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FirehoseOutput {
+    /// The blocks are appended to the file.
+    File(PathBuf),
+    /// The blocks are sent to the TCP socket at the address.
+    Tcp(String),
+}
+
+impl FromStr for FirehoseOutput {
+    type Err = eyre::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if s.is_empty() {
+            return Err(eyre!("empty firehose output"));
+        }
+        Ok(s.strip_prefix("tcp://").map_or_else(
+            || Self::File(PathBuf::from(s.strip_prefix("file://").unwrap_or(s))),
+            |address| Self::Tcp(address.to_string()),
+        ))
+    }
+}
+
+/// Configuration of the export stream.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FirehoseConfig {
+    /// The destination of the stream.
+    pub output: FirehoseOutput,
+    /// The first block of the stream. Defaults to the latest block when the stream starts.
+    pub start_block: Option<u64>,
+    /// Whether the call traces of the transactions are included in the payloads.
+    pub traces: bool,
+    /// The interval at which the new blocks are polled.
+    pub poll_interval: Duration,
+}
+
+impl FirehoseConfig {
+    /// Reads the configuration from the environment. Returns None if the stream is disabled.
+    pub fn from_env() -> Option<Self> {
+        fn var<T: FromStr>(key: &str) -> Option<T> {
+            std::env::var(key).ok().and_then(|val| T::from_str(&val).ok())
+        }
+
+        Some(Self {
+            output: var("FIREHOSE_OUTPUT")?,
+            start_block: var("FIREHOSE_START_BLOCK"),
+            traces: var("FIREHOSE_TRACES").unwrap_or(true),
+            poll_interval: Duration::from_millis(var("FIREHOSE_POLL_INTERVAL_MS").unwrap_or(1000)),
+        })
+    }
+}
+
+/// The payload of a block in the export stream.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FirehoseBlock {
+    /// The block, with its full transactions.
+    pub block: ExtendedBlock,
+    /// The receipts of the transactions of the block.
+    pub receipts: Vec<ExtendedTxReceipt>,
+    /// The call traces of the transactions of the block, if enabled.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub traces: Option<Vec<TraceResult>>,
+}
+
+impl FirehoseBlock {
+    /// Returns the line of the block in the export stream, without line feed.
+    pub fn to_line(&self) -> Result<String> {
+        let header = &self.block.header;
+        let payload = hex::encode(serde_json::to_vec(self)?);
+        Ok(format!(
+            "FIRE BLOCK {number} {hash} {parent_number} {parent_hash} {number} {timestamp} {payload}",
+            number = header.number,
+            hash = header.hash,
+            parent_number = header.number.saturating_sub(1),
+            parent_hash = header.parent_hash,
+            timestamp = u128::from(header.timestamp) * 1_000_000_000,
+        ))
+    }
+}
+
+/// Starts the export stream if `FIREHOSE_OUTPUT` is set. The stream is opened again after a
+/// failure, resuming from the block which couldn't be exported.
+pub fn start_firehose<SP>(eth_client: Arc<EthClient<SP>>)
+where
+    SP: starknet::providers::Provider + Send + Sync + Clone + 'static,
+{
+    let Some(config) = FIREHOSE_CONFIG.clone() else {
+        return;
+    };
+
+    tokio::spawn(async move {
+        let mut next_block = config.start_block;
+        loop {
+            if let Err(err) = stream(&eth_client, &config, &mut next_block).await {
+                tracing::warn!(target: "firehose", ?err, ?next_block, "firehose stream interrupted");
+            }
+            tokio::time::sleep(config.poll_interval).await;
+        }
+    });
+}
+
+/// Opens the output and writes the indexed blocks, starting from the next block, as they come.
+async fn stream<SP>(eth_client: &EthClient<SP>, config: &FirehoseConfig, next_block: &mut Option<u64>) -> Result<()>
+where
+    SP: starknet::providers::Provider + Send + Sync + Clone + 'static,
+{
+    let eth_provider = eth_client.eth_provider();
+    let mut output = open(&config.output).await?;
+    output.write_all(format!("{FIREHOSE_INIT}\n").as_bytes()).await?;
+    output.flush().await?;
+
+    loop {
+        let head = eth_provider.block_number().await?.to::<u64>();
+        let next = next_block.get_or_insert(head);
+        while *next <= head {
+            let block = firehose_block(eth_client, *next, config.traces).await?;
+            output.write_all(format!("{}\n", block.to_line()?).as_bytes()).await?;
+            tracing::info!(target: "firehose", monotonic_counter.firehose_blocks = 1, block_number = *next);
+            *next += 1;
+        }
+        output.flush().await?;
+
+        tokio::time::sleep(config.poll_interval).await;
+    }
+}
+
+/// Opens the output of the stream.
+async fn open(output: &FirehoseOutput) -> Result<BufWriter<Box<dyn AsyncWrite + Unpin + Send>>> {
+    let writer: Box<dyn AsyncWrite + Unpin + Send> = match output {
+        FirehoseOutput::File(path) => Box::new(OpenOptions::new().create(true).append(true).open(path).await?),
+        FirehoseOutput::Tcp(address) => Box::new(TcpStream::connect(address).await?),
+    };
+    Ok(BufWriter::new(writer))
+}
+
+/// Returns the payload of the indexed block, with its receipts and, if enabled, its call traces.
+pub async fn firehose_block<SP>(eth_client: &EthClient<SP>, number: u64, traces: bool) -> Result<FirehoseBlock>
+where
+    SP: starknet::providers::Provider + Send + Sync + Clone + 'static,
+{
+    let eth_provider = eth_client.eth_provider();
+    let block = eth_provider
+        .block_by_number(BlockNumberOrTag::Number(number), true)
+        .await?
+        .ok_or_else(|| eyre!("missing block {number}"))?;
+    let mut receipts = eth_provider.block_receipts(Some(BlockId::number(number))).await?.unwrap_or_default();
+    receipts.sort_by_key(|receipt| receipt.transaction_index);
+
+    let traces = if traces && !block.transactions.is_empty() {
+        let tracing_options = GethDebugTracingOptions::default()
+            .with_tracer(GethDebugTracerType::BuiltInTracer(GethDebugBuiltInTracerType::CallTracer));
+        let tracer = TracerBuilder::new(Arc::new(eth_provider))
+            .await?
+            .with_block_id(BlockId::number(number))
+            .await?
+            .with_tracing_options(tracing_options.into())
+            .build()?;
+        Some(tracer.debug_block()?)
+    } else if traces {
+        Some(Vec::new())
+    } else {
+        None
+    };
+
+    Ok(FirehoseBlock { block, receipts, traces })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::B256;
+    use alloy_rpc_types::{Block, Header};
+    use alloy_serde::WithOtherFields;
+
+    #[test]
+    fn test_firehose_output_from_str() {
+        // When
+        let outputs = ["tcp://127.0.0.1:9000", "file:///tmp/firehose.log", "firehose.log"]
+            .map(|output| FirehoseOutput::from_str(output).unwrap());
+
+        // Then
+        assert_eq!(
+            outputs,
+            [
+                FirehoseOutput::Tcp("127.0.0.1:9000".to_string()),
+                FirehoseOutput::File(PathBuf::from("/tmp/firehose.log")),
+                FirehoseOutput::File(PathBuf::from("firehose.log")),
+            ]
+        );
+        assert!(FirehoseOutput::from_str(" ").is_err());
+    }
+
+    #[test]
+    fn test_firehose_block_to_line() {
+        // Given
+        let header = Header {
+            number: 10,
+            hash: B256::repeat_byte(1),
+            parent_hash: B256::repeat_byte(2),
+            timestamp: 3,
+            ..Default::default()
+        };
+        let block = FirehoseBlock {
+            block: WithOtherFields::new(Block { header, ..Default::default() }),
+            receipts: Vec::new(),
+            traces: None,
+        };
+
+        // When
+        let line = block.to_line().unwrap();
+
+        // Then
+        let fields = line.split(' ').collect::<Vec<_>>();
+        assert_eq!(fields[..3], ["FIRE", "BLOCK", "10"]);
+        assert_eq!(fields[3], B256::repeat_byte(1).to_string());
+        assert_eq!(fields[4], "9");
+        assert_eq!(fields[5], B256::repeat_byte(2).to_string());
+        assert_eq!(fields[6..8], ["10", "3000000000"]);
+        let payload: serde_json::Value = serde_json::from_slice(&hex::decode(fields[8]).unwrap()).unwrap();
+        assert_eq!(payload["block"]["number"], "0xa");
+        assert!(payload.get("traces").is_none());
+        assert_eq!(line, block.to_line().unwrap());
+    }
+}
@@ -0,0 +1,320 @@
+//! Post-processing hooks on the indexed blocks, in the spirit of the execution extensions of
+//! reth.
+//!
+//! A plugin implements [`BlockHook`] and is registered in the [`BlockHooks`] of the node. The
+//! indexed blocks are then delivered in order to each hook, along with their receipts, so that
+//! small analytics (e.g. the aggregates of a protocol) can run inside the RPC process instead of
+//! a separate indexer service.
+//!
+//! Each hook consumes its own bounded queue: the blocks are only read from the database once
+//! every hook has room for them, so that a slow hook slows down the delivery of the blocks
+//! instead of buffering them in memory. A block whose processing fails is retried a few times,
+//! then skipped.
+
+use crate::{
+    client::EthClient,
+    providers::eth_provider::{
+        database::types::{header::ExtendedBlock, receipt::ExtendedTxReceipt},
+        BlockProvider, ReceiptProvider,
+    },
+};
+use alloy_eips::{BlockId, BlockNumberOrTag};
+use async_trait::async_trait;
+use eyre::{eyre, Result};
+use std::{str::FromStr, sync::Arc, time::Duration};
+use tokio::sync::mpsc::{channel, Receiver, Sender};
+
+/// The number of attempts to process a block before it is skipped by a hook.
+const MAX_HOOK_ATTEMPTS: usize = 3;
+
+/// An indexed block, delivered to the hooks.
+#[derive(Debug, Clone)]
+pub struct IndexedBlock {
+    /// The block, with its full transactions.
+    pub block: ExtendedBlock,
+    /// The receipts of the transactions of the block, ordered by transaction index.
+    pub receipts: Vec<ExtendedTxReceipt>,
+}
+
+impl IndexedBlock {
+    /// Returns the number of the block.
+    pub fn number(&self) -> u64 {
+        self.block.header.number
+    }
+}
+
+/// A post-processing hook, called on each indexed block in order.
+#[async_trait]
+pub trait BlockHook: Send + Sync + 'static {
+    /// Returns the name of the hook, used in the logs and metrics.
+    fn name(&self) -> &'static str;
+
+    /// Processes the indexed block. The block is retried on error.
+    async fn on_block(&self, block: &IndexedBlock) -> Result<()>;
+}
+
+/// Configuration of the delivery of the blocks to the hooks.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlockHooksConfig {
+    /// The first block delivered to the hooks. Defaults to the latest block when the node starts.
+    pub start_block: Option<u64>,
+    /// The number of blocks waiting to be processed by a hook, above which the delivery of the
+    /// blocks waits for the hook.
+    pub queue_size: usize,
+    /// The interval at which the new blocks are polled, also used as the delay before a retry.
+    pub poll_interval: Duration,
+}
+
+impl Default for BlockHooksConfig {
+    fn default() -> Self {
+        Self { start_block: None, queue_size: 16, poll_interval: Duration::from_millis(1000) }
+    }
+}
+
+impl BlockHooksConfig {
+    /// Reads the configuration from the environment.
+    pub fn from_env() -> Self {
+        fn var<T: FromStr>(key: &str) -> Option<T> {
+            std::env::var(key).ok().and_then(|val| T::from_str(&val).ok())
+        }
+
+        let default = Self::default();
+        Self {
+            start_block: var("BLOCK_HOOKS_START_BLOCK"),
+            queue_size: var("BLOCK_HOOKS_QUEUE_SIZE").unwrap_or(default.queue_size).max(1),
+            poll_interval: var("BLOCK_HOOKS_POLL_INTERVAL_MS").map_or(default.poll_interval, Duration::from_millis),
+        }
+    }
+}
+
+/// The hooks registered in the node.
+#[derive(Default)]
+pub struct BlockHooks {
+    hooks: Vec<Arc<dyn BlockHook>>,
+}
+
+impl std::fmt::Debug for BlockHooks {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(self.hooks.iter().map(|hook| hook.name())).finish()
+    }
+}
+
+impl BlockHooks {
+    /// Creates an empty set of hooks.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers the hook.
+    #[must_use]
+    pub fn with_hook(mut self, hook: impl BlockHook) -> Self {
+        self.hooks.push(Arc::new(hook));
+        self
+    }
+
+    /// Returns true if no hook is registered.
+    pub fn is_empty(&self) -> bool {
+        self.hooks.is_empty()
+    }
+
+    /// Starts delivering the indexed blocks to the hooks, if any.
+    pub fn start<SP>(self, eth_client: Arc<EthClient<SP>>, config: BlockHooksConfig)
+    where
+        SP: starknet::providers::Provider + Send + Sync + Clone + 'static,
+    {
+        if self.is_empty() {
+            return;
+        }
+
+        let mut senders = self.spawn(&config);
+        tokio::spawn(async move {
+            let mut next_block = config.start_block;
+            loop {
+                if let Err(err) = deliver(&eth_client, &mut senders, &mut next_block).await {
+                    tracing::warn!(target: "block_hooks", ?err, ?next_block, "failed to deliver the blocks");
+                }
+                if senders.is_empty() {
+                    return;
+                }
+                tokio::time::sleep(config.poll_interval).await;
+            }
+        });
+    }
+
+    /// Spawns the task of each hook, returning the senders of their queues.
+    fn spawn(self, config: &BlockHooksConfig) -> Vec<(&'static str, Sender<Arc<IndexedBlock>>)> {
+        self.hooks
+            .into_iter()
+            .map(|hook| {
+                let (sender, receiver) = channel(config.queue_size);
+                let name = hook.name();
+                tokio::spawn(run_hook(hook, receiver, config.poll_interval));
+                (name, sender)
+            })
+            .collect()
+    }
+}
+
+/// Delivers the indexed blocks, from the next block to the latest one, to the hooks.
+async fn deliver<SP>(
+    eth_client: &EthClient<SP>,
+    senders: &mut Vec<(&'static str, Sender<Arc<IndexedBlock>>)>,
+    next_block: &mut Option<u64>,
+) -> Result<()>
+where
+    SP: starknet::providers::Provider + Send + Sync + Clone + 'static,
+{
+    let eth_provider = eth_client.eth_provider();
+    let head = eth_provider.block_number().await?.to::<u64>();
+    let next = next_block.get_or_insert(head);
+    while *next <= head && !senders.is_empty() {
+        let block = Arc::new(indexed_block(eth_client, *next).await?);
+        dispatch(senders, &block).await;
+        *next += 1;
+    }
+    Ok(())
+}
+
+/// Sends the block to the queue of each hook, waiting for the hooks whose queue is full. The
+/// hooks whose task stopped are dropped.
+async fn dispatch(senders: &mut Vec<(&'static str, Sender<Arc<IndexedBlock>>)>, block: &Arc<IndexedBlock>) {
+    let mut stopped = Vec::new();
+    for (index, (name, sender)) in senders.iter().enumerate() {
+        if sender.send(Arc::clone(block)).await.is_err() {
+            tracing::warn!(target: "block_hooks", hook = *name, "hook stopped");
+            stopped.push(index);
+        }
+    }
+    for index in stopped.into_iter().rev() {
+        senders.remove(index);
+    }
+}
+
+/// Processes the blocks of the queue with the hook, retrying each block up to
+/// [`MAX_HOOK_ATTEMPTS`] times.
+async fn run_hook(hook: Arc<dyn BlockHook>, mut receiver: Receiver<Arc<IndexedBlock>>, retry_delay: Duration) {
+    while let Some(block) = receiver.recv().await {
+        for attempt in 1..=MAX_HOOK_ATTEMPTS {
+            match hook.on_block(&block).await {
+                Ok(()) => {
+                    tracing::debug!(
+                        target: "block_hooks",
+                        monotonic_counter.block_hooks_processed_blocks = 1,
+                        hook = hook.name(),
+                        block_number = block.number()
+                    );
+                    break;
+                }
+                Err(err) if attempt == MAX_HOOK_ATTEMPTS => {
+                    tracing::error!(
+                        target: "block_hooks",
+                        monotonic_counter.block_hooks_skipped_blocks = 1,
+                        hook = hook.name(),
+                        block_number = block.number(),
+                        ?err,
+                        "skipped block"
+                    );
+                }
+                Err(err) => {
+                    tracing::warn!(
+                        target: "block_hooks",
+                        hook = hook.name(),
+                        block_number = block.number(),
+                        attempt,
+                        ?err,
+                        "hook failed, retrying"
+                    );
+                    tokio::time::sleep(retry_delay).await;
+                }
+            }
+        }
+    }
+}
+
+/// Returns the indexed block with its receipts.
+async fn indexed_block<SP>(eth_client: &EthClient<SP>, number: u64) -> Result<IndexedBlock>
+where
+    SP: starknet::providers::Provider + Send + Sync + Clone + 'static,
+{
+    let eth_provider = eth_client.eth_provider();
+    let block = eth_provider
+        .block_by_number(BlockNumberOrTag::Number(number), true)
+        .await?
+        .ok_or_else(|| eyre!("missing block {number}"))?;
+    let mut receipts = eth_provider.block_receipts(Some(BlockId::number(number))).await?.unwrap_or_default();
+    receipts.sort_by_key(|receipt| receipt.transaction_index);
+    Ok(IndexedBlock { block, receipts })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_rpc_types::{Block, Header};
+    use alloy_serde::WithOtherFields;
+    use std::sync::Mutex;
+
+    /// Records the numbers of the blocks, failing once on the odd blocks.
+    #[derive(Default)]
+    struct RecordingHook {
+        blocks: Arc<Mutex<Vec<u64>>>,
+        failed: Arc<Mutex<Vec<u64>>>,
+    }
+
+    #[async_trait]
+    impl BlockHook for RecordingHook {
+        fn name(&self) -> &'static str {
+            "recording"
+        }
+
+        async fn on_block(&self, block: &IndexedBlock) -> Result<()> {
+            let mut failed = self.failed.lock().unwrap();
+            if block.number() % 2 == 1 && !failed.contains(&block.number()) {
+                failed.push(block.number());
+                return Err(eyre!("odd block"));
+            }
+            self.blocks.lock().unwrap().push(block.number());
+            Ok(())
+        }
+    }
+
+    fn block(number: u64) -> Arc<IndexedBlock> {
+        let header = Header { number, ..Default::default() };
+        Arc::new(IndexedBlock {
+            block: WithOtherFields::new(Block { header, ..Default::default() }),
+            receipts: Vec::new(),
+        })
+    }
+
+    #[tokio::test]
+    async fn test_block_hooks_dispatch() {
+        // Given
+        let hook = RecordingHook::default();
+        let blocks = Arc::clone(&hook.blocks);
+        let config = BlockHooksConfig { queue_size: 1, poll_interval: Duration::from_millis(1), ..Default::default() };
+        let mut senders = BlockHooks::new().with_hook(hook).spawn(&config);
+
+        // When
+        for number in 0..4 {
+            dispatch(&mut senders, &block(number)).await;
+        }
+        drop(senders);
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        // Then
+        assert_eq!(*blocks.lock().unwrap(), vec![0, 1, 2, 3]);
+    }
+
+    #[tokio::test]
+    async fn test_block_hooks_drop_stopped_hooks() {
+        // Given
+        let (sender, receiver) = channel(1);
+        let mut senders = vec![("stopped", sender)];
+        drop(receiver);
+
+        // When
+        dispatch(&mut senders, &block(0)).await;
+
+        // Then
+        assert!(senders.is_empty());
+    }
+}
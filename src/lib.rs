@@ -6,19 +6,33 @@ use tracing_opentelemetry as _;
 use tracing_subscriber as _;
 
 pub mod providers {
+    #[cfg(feature = "mongo")]
     pub mod alchemy_provider;
+    #[cfg(feature = "mongo")]
     pub mod debug_provider;
     pub mod eth_provider;
     pub mod pool_provider;
     pub mod sn_provider;
 }
+#[cfg(feature = "chaos")]
+pub mod chaos;
+#[cfg(feature = "mongo")]
 pub mod client;
 pub mod config;
 pub mod constants;
+pub mod coordination;
 pub mod eth_rpc;
+#[cfg(feature = "firehose")]
+pub mod firehose;
+#[cfg(feature = "mongo")]
+pub mod hooks;
+#[cfg(feature = "meta-tx")]
+pub mod meta_tx;
 pub mod models;
 pub mod pool;
 pub mod prometheus_handler;
+#[cfg(feature = "mongo")]
+pub mod startup;
 #[cfg(feature = "testing")]
 pub mod test_utils;
 pub mod tracing;
@@ -1,28 +1,45 @@
 use dotenvy::dotenv;
 use eyre::Result;
 use kakarot_rpc::{
-    client::EthClient,
-    constants::{KAKAROT_RPC_CONFIG, KKRT_BLOCK_GAS_LIMIT, RPC_CONFIG},
-    eth_rpc::{rpc::KakarotRpcModuleBuilder, run_server},
+    client::EthClientBuilder,
+    constants::{ADMIN_API_ENABLED, DRY_RUN, KAKAROT_RPC_CONFIG, KKRT_BLOCK_GAS_LIMIT, REPLAY_MODE, RPC_CONFIG},
+    coordination::{CoordinationConfig, InstanceCoordinator},
+    eth_rpc::{config::RPCConfig, rpc::KakarotRpcModuleBuilder, run_server},
+    hooks::{BlockHooks, BlockHooksConfig},
     pool::{
-        constants::PRUNE_DURATION,
-        mempool::{maintain_transaction_pool, AccountManager},
+        constants::POOL_MAX_SIZE_BYTES,
+        mempool::{
+            maintain_transaction_pool, poll_pending_block, watch_mined_transactions, watch_pool_events,
+            with_memory_budget, AccountManager,
+        },
+        probe::start_probe,
+        prune::PruneSchedule,
+        reconcile::reconcile_relayed_transactions,
+        relayers::TrafficClass,
     },
-    providers::eth_provider::{
-        database::Database,
-        starknet::kakarot_core::{core::KakarotCoreReader, KAKAROT_ADDRESS},
+    providers::{
+        eth_provider::{
+            database::archive::start_archival,
+            starknet::kakarot_core::{core::KakarotCoreReader, KAKAROT_ADDRESS},
+        },
+        sn_provider::{
+            batch::STARKNET_BATCH_CONFIG, circuit_breaker::STARKNET_CIRCUIT_BREAKER_CONFIG,
+            routing::STARKNET_ROUTING_CONFIG, BatchingProvider, CircuitBreakerProvider, OfflineProvider,
+            RoutingProvider,
+        },
     },
+    startup::validate_startup,
 };
-use mongodb::options::{DatabaseOptions, ReadConcern, WriteConcern};
 use opentelemetry_sdk::runtime::Tokio;
 use reth_transaction_pool::PoolConfig;
 use starknet::{
-    core::types::{BlockId, BlockTag, Felt},
+    core::types::{BlockId, BlockTag},
     providers::{jsonrpc::HttpTransport, JsonRpcClient},
 };
-use std::{env::var, str::FromStr, sync::Arc};
+use std::{env::var, sync::Arc};
 use tracing_opentelemetry::MetricsLayer;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, EnvFilter, Layer};
+use url::Url;
 
 #[tokio::main]
 async fn main() -> Result<()> {
@@ -31,54 +48,159 @@ async fn main() -> Result<()> {
 
     setup_tracing().expect("failed to start tracing and metrics");
 
-    let starknet_provider = JsonRpcClient::new(HttpTransport::new(KAKAROT_RPC_CONFIG.network_url.clone()));
-
-    // Setup the database
-    let db_client =
-        mongodb::Client::with_uri_str(var("MONGO_CONNECTION_STRING").expect("Missing MONGO_CONNECTION_STRING .env"))
-            .await?;
-    let db = Database::new(
-        db_client.database_with_options(
-            &var("MONGO_DATABASE_NAME").expect("Missing MONGO_DATABASE_NAME from .env"),
-            DatabaseOptions::builder()
-                .read_concern(ReadConcern::majority())
-                .write_concern(WriteConcern::majority())
-                .build(),
+    // Validate the whole configuration before binding the port
+    let startup_config = validate_startup().await.inspect_err(|err| tracing::error!("{err}"))?;
+
+    // Setup the eth provider, batching the concurrent Starknet reads if configured and failing fast
+    // while the Starknet node is unavailable. The historical blocks are served by the archive
+    // Starknet node, if any
+    let upstream = |url: Url| {
+        let provider = Arc::new(JsonRpcClient::new(HttpTransport::new(url)));
+        #[cfg(feature = "chaos")]
+        let provider = kakarot_rpc::providers::sn_provider::FaultInjectionProvider::new(provider);
+        let provider = BatchingProvider::new(provider, *STARKNET_BATCH_CONFIG);
+        CircuitBreakerProvider::new(provider, *STARKNET_CIRCUIT_BREAKER_CONFIG)
+    };
+    let archive_provider = STARKNET_ROUTING_CONFIG.archive_url.clone().map(upstream);
+    // In replay mode, the calls to Starknet fail without being sent, so that nothing is served
+    // from the live chain
+    let starknet_provider = Arc::new(OfflineProvider::new(
+        RoutingProvider::new(
+            upstream(KAKAROT_RPC_CONFIG.network_url.clone()),
+            archive_provider,
+            STARKNET_ROUTING_CONFIG.threshold,
         ),
-    );
-
-    // Setup the eth provider
-    let starknet_provider = Arc::new(starknet_provider);
+        *REPLAY_MODE,
+    ));
 
-    // Get the pool config
+    // Get the pool config. In replay mode, the mempool isn't used and Starknet isn't accessed
     let contract_reader = KakarotCoreReader::new(*KAKAROT_ADDRESS, starknet_provider.clone());
-    let base_fee = contract_reader.get_base_fee().block_id(BlockId::Tag(BlockTag::Pending)).call().await?.base_fee;
-    let base_fee = base_fee.try_into()?;
-    let config =
-        PoolConfig { minimal_protocol_basefee: base_fee, gas_limit: KKRT_BLOCK_GAS_LIMIT, ..Default::default() };
+    let (base_fee, gas_limit) = if *REPLAY_MODE {
+        tracing::warn!("running in replay mode, only the database is served and Starknet isn't accessed");
+        (0, KKRT_BLOCK_GAS_LIMIT)
+    } else {
+        let base_fee = contract_reader.get_base_fee().block_id(BlockId::Tag(BlockTag::Pending)).call().await?.base_fee;
+        let gas_limit =
+            match contract_reader.get_block_gas_limit().block_id(BlockId::Tag(BlockTag::Pending)).call().await {
+                Ok(block_gas_limit) => block_gas_limit.block_gas_limit.try_into()?,
+                Err(err) => {
+                    tracing::warn!(
+                        ?err,
+                        "failed to fetch the block gas limit, using the default of {KKRT_BLOCK_GAS_LIMIT}"
+                    );
+                    KKRT_BLOCK_GAS_LIMIT
+                }
+            };
+        (base_fee.try_into()?, gas_limit)
+    };
+    // Each sub-pool is bounded by its number of transactions and by its memory budget
+    let config = with_memory_budget(
+        PoolConfig { minimal_protocol_basefee: base_fee, gas_limit, ..Default::default() },
+        *POOL_MAX_SIZE_BYTES,
+    );
 
     // Init the Ethereum Client
-    let eth_client = EthClient::new(starknet_provider, config, db.clone());
-    let eth_client = Arc::new(eth_client);
-
-    // Start the relayer manager
-    let addresses =
-        var("RELAYERS_ADDRESSES")?.split(',').filter_map(|addr| Felt::from_str(addr).ok()).collect::<Vec<_>>();
-    AccountManager::new(addresses, Arc::clone(&eth_client)).start();
-
-    // Start the maintenance of the mempool
-    maintain_transaction_pool(Arc::clone(&eth_client), PRUNE_DURATION);
+    let mut eth_client_builder = EthClientBuilder::new(starknet_provider)
+        .with_mongo_read_config(
+            &startup_config.mongo_connection_string,
+            &startup_config.mongo_database_name,
+            &startup_config.mongo_read_config,
+        )
+        .await?;
+    if let Some(archive_config) = &startup_config.mongo_archive_config {
+        eth_client_builder =
+            eth_client_builder.with_mongo_archive(&startup_config.mongo_connection_string, archive_config).await?;
+    }
+    let eth_client = Arc::new(eth_client_builder.with_pool_config(config).build()?);
+
+    // Setup the relayer manager
+    if *DRY_RUN {
+        tracing::warn!("running in dry run mode, transactions will not be submitted to Starknet");
+    }
+    let account_manager = AccountManager::new(startup_config.relayers_addresses, Arc::clone(&eth_client))
+        .with_relayers(TrafficClass::Keeper, startup_config.keeper_relayers_addresses)
+        .with_relayers(TrafficClass::Faucet, startup_config.faucet_relayers_addresses)
+        .with_dry_run(*DRY_RUN);
+    let relayers = account_manager.relayers();
+
+    // Start the background tasks, which write to the database or access Starknet, except in
+    // replay mode where the database is a snapshot served as is
+    let coordination_config = CoordinationConfig::from_env();
+    let coordinated = coordination_config.enabled;
+    let coordinator = InstanceCoordinator::new(eth_client.eth_provider().database().clone(), coordination_config);
+    if !*REPLAY_MODE {
+        // Start the background writers once the instance is elected among the instances sharing the
+        // database, if coordinated
+        let writers_client = Arc::clone(&eth_client);
+        coordinator.run_writers(async move {
+            // Reconcile the transactions which were being relayed when the node stopped, before the
+            // relayers start, so that they aren't relayed twice
+            if !*DRY_RUN {
+                if let Err(err) = reconcile_relayed_transactions(&writers_client).await {
+                    tracing::warn!(?err, "failed to reconcile the transactions relayed before the restart");
+                }
+            }
+            account_manager.start();
+
+            // Probe the transaction pipeline end to end with self-transfers of the probe EOA, if any
+            start_probe(Arc::clone(&writers_client));
+
+            // Move the logs and receipts out of the hot window to the archive, if any
+            start_archival(writers_client.eth_provider().database().clone());
+        });
+
+        // Start the maintenance of the mempool
+        maintain_transaction_pool(Arc::clone(&eth_client), PruneSchedule::default(), coordinator.leadership());
+
+        // Record the lifecycle of the transactions in the mempool for their timeline
+        watch_pool_events(Arc::clone(&eth_client));
+
+        // Evict the transactions from the mempool as soon as they are indexed
+        watch_mined_transactions(Arc::clone(&eth_client));
+
+        // Serve the receipts of the relayed transactions from the pending block until they are indexed
+        poll_pending_block(Arc::clone(&eth_client));
+
+        // Stream the indexed blocks to the streaming indexers, if configured
+        #[cfg(feature = "firehose")]
+        kakarot_rpc::firehose::start_firehose(Arc::clone(&eth_client));
+
+        // Deliver the indexed blocks to the post-processing hooks, registered here with `with_hook`
+        BlockHooks::new().start(Arc::clone(&eth_client), BlockHooksConfig::from_env());
+    }
 
     // Setup the RPC module
-    let kakarot_rpc_module = KakarotRpcModuleBuilder::new(eth_client).rpc_module()?;
+    let mut kakarot_rpc_module_builder =
+        KakarotRpcModuleBuilder::new(Arc::clone(&eth_client)).with_pool_module(Arc::clone(&eth_client));
+    #[cfg(feature = "faucet")]
+    {
+        kakarot_rpc_module_builder =
+            kakarot_rpc_module_builder.with_faucet_module(Arc::clone(&eth_client), Arc::clone(&relayers));
+    }
+    #[cfg(feature = "meta-tx")]
+    if let Some(meta_tx_config) = kakarot_rpc::meta_tx::MetaTxConfig::from_env() {
+        kakarot_rpc_module_builder =
+            kakarot_rpc_module_builder.with_meta_tx_module(Arc::clone(&eth_client), meta_tx_config);
+    }
+    if *ADMIN_API_ENABLED {
+        #[cfg(feature = "chaos")]
+        {
+            kakarot_rpc_module_builder = kakarot_rpc_module_builder.with_chaos_module();
+        }
+        kakarot_rpc_module_builder = kakarot_rpc_module_builder.with_admin_module(eth_client, relayers);
+    }
+    let kakarot_rpc_module = kakarot_rpc_module_builder.rpc_module()?;
 
     // Start the RPC server
-    let (socket_addr, server_handle) = run_server(kakarot_rpc_module, RPC_CONFIG.clone()).await?;
+    // The followers reject the writes, which would never be relayed from their mempool
+    let rpc_config = RPCConfig { leadership: coordinated.then(|| coordinator.leadership()), ..RPC_CONFIG.clone() };
+    let (socket_addr, server_handle) = run_server(kakarot_rpc_module, rpc_config).await?;
     let url = format!("http://{socket_addr}");
 
     tracing::info!("RPC Server running on {url}...");
 
     server_handle.stopped().await;
+    coordinator.release().await;
 
     Ok(())
 }
@@ -0,0 +1,606 @@
+//! Relay of the EIP-712 signed meta-transactions, in the EIP-2771 style.
+//!
+//! The sender of a meta-transaction signs a forward request instead of a transaction. The node
+//! checks the signature, the deadline and the nonce of the request, then a sponsor, an EOA whose
+//! key is held by the node, wraps it into a call to `execute` of the trusted forwarder contract,
+//! which calls the target with the address of the sender appended to the calldata. The sponsor
+//! pays the gas, within the limits of its fee policy.
+//!
+//! Only compiled with the `meta-tx` feature, served by the nodes where `META_TX_FORWARDER` is set.
+
+use crate::{
+    client::{EthClient, KakarotTransactions},
+    eth_rpc::openrpc::SchemaOf,
+    providers::eth_provider::{
+        contracts::forwarder::{
+            ERC2771Forwarder::{executeCall, ForwardRequestData},
+            EthereumForwarder, ForwardRequest,
+        },
+        error::{EthApiError, EthRpcErrorCode, SignatureError},
+        GasProvider, TransactionProvider,
+    },
+};
+use alloy_consensus::TxEip1559;
+use alloy_eips::{eip2718::Encodable2718, BlockId};
+use alloy_primitives::{aliases::U48, Address, Bytes, Signature, TxKind, B256, U256, U64};
+use alloy_sol_types::{Eip712Domain, SolCall, SolStruct};
+use jsonrpsee::types::ErrorObject;
+use reth_primitives::{sign_message, Transaction, TransactionSigned};
+use reth_transaction_pool::TransactionPool;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::{
+    borrow::Cow,
+    collections::HashMap,
+    str::FromStr,
+    sync::Arc,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+use tokio::sync::Mutex;
+
+/// Gas added to the gas of the forward request, covering the verification of the request by the
+/// forwarder.
+pub const FORWARDER_GAS_OVERHEAD: u64 = 100_000;
+
+/// The configuration of the meta-transaction relay.
+#[derive(Debug, Clone)]
+pub struct MetaTxConfig {
+    /// The address of the trusted forwarder contract.
+    pub forwarder: Address,
+    /// The name of the EIP-712 domain of the forwarder.
+    pub forwarder_name: String,
+    /// The sponsors paying the gas of the meta-transactions.
+    pub sponsors: Vec<SponsorConfig>,
+    /// The interval over which the budget of a sponsor is spent.
+    pub budget_interval: Duration,
+}
+
+impl MetaTxConfig {
+    /// Reads the configuration from the environment. Returns `None` if no forwarder is set, the
+    /// invalid sponsors being ignored.
+    pub fn from_env() -> Option<Self> {
+        let forwarder = std::env::var("META_TX_FORWARDER").ok().and_then(|val| Address::from_str(&val).ok())?;
+        Some(Self {
+            forwarder,
+            forwarder_name: std::env::var("META_TX_FORWARDER_NAME").unwrap_or_else(|_| "ERC2771Forwarder".to_string()),
+            sponsors: std::env::var("META_TX_SPONSORS")
+                .ok()
+                .and_then(|val| parse_sponsors(&val).ok())
+                .unwrap_or_default(),
+            budget_interval: Duration::from_secs(
+                std::env::var("META_TX_BUDGET_INTERVAL_SECS").ok().and_then(|val| val.parse().ok()).unwrap_or(86400),
+            ),
+        })
+    }
+}
+
+/// Parses a comma separated list of sponsors, each with the `<private key>:<max gas>:<budget>`
+/// format.
+pub fn parse_sponsors(sponsors: &str) -> Result<Vec<SponsorConfig>, String> {
+    sponsors.replace(' ', "").split(',').filter(|sponsor| !sponsor.is_empty()).map(SponsorConfig::from_str).collect()
+}
+
+/// A sponsor of the meta-transactions and its fee policy.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SponsorConfig {
+    /// The private key of the sponsor EOA.
+    pub private_key: B256,
+    /// The address of the sponsor EOA.
+    pub address: Address,
+    /// The maximum gas of a sponsored forward request.
+    pub max_gas: u64,
+    /// The maximum fees paid by the sponsor over the budget interval, in wei.
+    pub budget: U256,
+}
+
+impl FromStr for SponsorConfig {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.split(':');
+        let (Some(private_key), Some(max_gas), Some(budget), None) =
+            (parts.next(), parts.next(), parts.next(), parts.next())
+        else {
+            return Err("invalid sponsor, expected <private key>:<max gas>:<budget>".to_string());
+        };
+
+        let private_key = B256::from_str(private_key).map_err(|_| "invalid sponsor private key".to_string())?;
+        // The address of the sponsor EOA is recovered from a signed transaction
+        let transaction = Transaction::Eip1559(TxEip1559::default());
+        let address = sign_message(private_key, transaction.signature_hash())
+            .ok()
+            .and_then(|signature| {
+                TransactionSigned::from_transaction_and_signature(transaction, signature).recover_signer()
+            })
+            .ok_or_else(|| "invalid sponsor private key".to_string())?;
+
+        Ok(Self {
+            private_key,
+            address,
+            max_gas: max_gas.parse().map_err(|_| format!("invalid sponsor max gas {max_gas}"))?,
+            budget: U256::from_str(budget).map_err(|_| format!("invalid sponsor budget {budget}"))?,
+        })
+    }
+}
+
+/// A meta-transaction: a forward request along with the signature of its sender.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct MetaTransactionRequest {
+    /// The sender of the meta-transaction, which signed the request.
+    #[schemars(with = "SchemaOf<Address>")]
+    pub from: Address,
+    /// The target of the call.
+    #[schemars(with = "SchemaOf<Address>")]
+    pub to: Address,
+    /// The value transferred to the target, must be zero.
+    #[serde(default)]
+    #[schemars(with = "SchemaOf<U256>")]
+    pub value: U256,
+    /// The gas forwarded to the call.
+    #[schemars(with = "SchemaOf<U64>")]
+    pub gas: U64,
+    /// The nonce of the request in the forwarder.
+    #[schemars(with = "SchemaOf<U256>")]
+    pub nonce: U256,
+    /// The timestamp after which the request can't be executed.
+    #[schemars(with = "SchemaOf<U64>")]
+    pub deadline: U64,
+    /// The calldata of the call.
+    #[schemars(with = "SchemaOf<Bytes>")]
+    pub data: Bytes,
+    /// The EIP-712 signature of the request.
+    #[schemars(with = "SchemaOf<Bytes>")]
+    pub signature: Bytes,
+    /// The sponsor paying the gas, any sponsor whose policy covers the request if unset.
+    #[serde(default)]
+    #[schemars(with = "SchemaOf<Option<Address>>")]
+    pub sponsor: Option<Address>,
+}
+
+impl MetaTransactionRequest {
+    /// Returns the deadline of the request as the `uint48` of the forwarder, or an error if it
+    /// doesn't fit.
+    fn forwarder_deadline(&self) -> Result<U48, MetaTxError> {
+        self.deadline.uint_try_to().map_err(|_| MetaTxError::InvalidDeadline(self.deadline))
+    }
+
+    /// Returns the EIP-712 typed request signed by the sender.
+    pub fn forward_request(&self) -> Result<ForwardRequest, MetaTxError> {
+        Ok(ForwardRequest {
+            from: self.from,
+            to: self.to,
+            value: self.value,
+            gas: U256::from(self.gas),
+            nonce: self.nonce,
+            deadline: self.forwarder_deadline()?,
+            data: self.data.clone(),
+        })
+    }
+
+    /// Recovers the signer of the request in the domain of the forwarder.
+    pub fn recover_signer(&self, domain: &Eip712Domain) -> Result<Address, MetaTxError> {
+        let signature = Signature::try_from(self.signature.as_ref()).map_err(|_| MetaTxError::InvalidSignature)?;
+        signature
+            .recover_address_from_prehash(&self.forward_request()?.eip712_signing_hash(domain))
+            .map_err(|_| MetaTxError::InvalidSignature)
+    }
+
+    /// Returns the calldata of the call to `execute` of the forwarder.
+    fn execute_calldata(&self) -> Result<Bytes, MetaTxError> {
+        Ok(executeCall {
+            request: ForwardRequestData {
+                from: self.from,
+                to: self.to,
+                value: self.value,
+                gas: U256::from(self.gas),
+                deadline: self.forwarder_deadline()?,
+                data: self.data.clone(),
+                signature: self.signature.clone(),
+            },
+        }
+        .abi_encode()
+        .into())
+    }
+}
+
+/// A sponsor of the meta-transactions, as served by `kakarot_getMetaTransactionSponsors`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SponsorInfo {
+    /// The address of the sponsor EOA.
+    #[schemars(with = "SchemaOf<Address>")]
+    pub address: Address,
+    /// The maximum gas of a sponsored forward request.
+    #[schemars(with = "SchemaOf<U64>")]
+    pub max_gas: U64,
+    /// The maximum fees paid by the sponsor over the budget interval, in wei.
+    #[schemars(with = "SchemaOf<U256>")]
+    pub budget: U256,
+    /// The fees the sponsor can still pay in the current interval, in wei.
+    #[schemars(with = "SchemaOf<U256>")]
+    pub remaining_budget: U256,
+}
+
+/// The errors raised while relaying a meta-transaction.
+#[derive(Debug, thiserror::Error)]
+pub enum MetaTxError {
+    /// The signature isn't a valid signature.
+    #[error("invalid meta-transaction signature")]
+    InvalidSignature,
+    /// The request wasn't signed by its sender.
+    #[error("meta-transaction signed by {signer}, expected {from}")]
+    SignerMismatch { signer: Address, from: Address },
+    /// The deadline of the request has passed.
+    #[error("meta-transaction expired at {0}")]
+    Expired(u64),
+    /// The deadline of the request doesn't fit in the `uint48` of the forwarder.
+    #[error("invalid meta-transaction deadline {0}, above the maximum uint48")]
+    InvalidDeadline(U64),
+    /// The nonce of the request isn't the next nonce of the sender in the forwarder.
+    #[error("invalid meta-transaction nonce {got}, expected {expected}")]
+    InvalidNonce { got: U256, expected: U256 },
+    /// The request transfers value, which the sponsors don't pay.
+    #[error("meta-transactions can't transfer value")]
+    ValueTransfer,
+    /// The requested sponsor isn't a sponsor of the node.
+    #[error("unknown sponsor {0}")]
+    UnknownSponsor(Address),
+    /// No sponsor policy covers the gas and the fees of the request.
+    #[error("no sponsor covers a meta-transaction with {0} gas, retry later")]
+    NotSponsored(u64),
+    /// The relay of the wrapping transaction failed.
+    #[error(transparent)]
+    Eth(#[from] EthApiError),
+}
+
+impl From<MetaTxError> for ErrorObject<'static> {
+    fn from(err: MetaTxError) -> Self {
+        let code = match err {
+            MetaTxError::Eth(err) => return err.into(),
+            MetaTxError::NotSponsored(_) => EthRpcErrorCode::ResourceUnavailable,
+            _ => EthRpcErrorCode::InvalidInput,
+        };
+        Self::owned(code as i32, err.to_string(), None::<()>)
+    }
+}
+
+/// Tracks the fees paid by a sponsor over the budget interval.
+#[derive(Debug)]
+pub struct SponsorBudget {
+    budget: U256,
+    interval: Duration,
+    window_start: Instant,
+    spent: U256,
+}
+
+impl SponsorBudget {
+    /// Creates a new budget, spent over the given interval.
+    pub const fn new(budget: U256, interval: Duration, now: Instant) -> Self {
+        Self { budget, interval, window_start: now, spent: U256::ZERO }
+    }
+
+    /// Returns the fees which can still be paid in the current interval.
+    pub fn remaining(&mut self, now: Instant) -> U256 {
+        if now.duration_since(self.window_start) >= self.interval {
+            self.window_start = now;
+            self.spent = U256::ZERO;
+        }
+        self.budget.saturating_sub(self.spent)
+    }
+
+    /// Records the fees paid by the sponsor, returns false if they exceed the remaining budget.
+    pub fn spend(&mut self, fees: U256, now: Instant) -> bool {
+        if fees > self.remaining(now) {
+            return false;
+        }
+        self.spent += fees;
+        true
+    }
+
+    /// Returns the fees of a transaction which wasn't submitted.
+    pub fn refund(&mut self, fees: U256) {
+        self.spent = self.spent.saturating_sub(fees);
+    }
+}
+
+/// The state of the relay, locked during the relay of a meta-transaction so that the relays are
+/// sequential.
+#[derive(Debug)]
+struct RelayState {
+    budgets: HashMap<Address, SponsorBudget>,
+    /// The nonce of the last relayed request of each sender, with the hash of the transaction
+    /// wrapping it.
+    last_relayed: HashMap<Address, (U256, B256)>,
+}
+
+/// Relays the meta-transactions through the trusted forwarder, the sponsors paying the gas.
+#[derive(Debug)]
+pub struct MetaTxRelay<SP: starknet::providers::Provider + Send + Sync> {
+    eth_client: Arc<EthClient<SP>>,
+    forwarder: Address,
+    domain: Eip712Domain,
+    sponsors: Vec<SponsorConfig>,
+    state: Mutex<RelayState>,
+}
+
+impl<SP> MetaTxRelay<SP>
+where
+    SP: starknet::providers::Provider + Send + Sync + Clone + 'static,
+{
+    /// Creates the relay of the configured forwarder.
+    pub fn new(config: MetaTxConfig, eth_client: Arc<EthClient<SP>>) -> Self {
+        let domain = Eip712Domain::new(
+            Some(Cow::Owned(config.forwarder_name)),
+            Some(Cow::Borrowed("1")),
+            Some(U256::from(eth_client.eth_provider().chain_id)),
+            Some(config.forwarder),
+            None,
+        );
+        let now = Instant::now();
+        let budgets = config
+            .sponsors
+            .iter()
+            .map(|sponsor| (sponsor.address, SponsorBudget::new(sponsor.budget, config.budget_interval, now)))
+            .collect();
+
+        Self {
+            eth_client,
+            forwarder: config.forwarder,
+            domain,
+            sponsors: config.sponsors,
+            state: Mutex::new(RelayState { budgets, last_relayed: HashMap::new() }),
+        }
+    }
+
+    /// Returns the nonce of the next request of the sender, accounting for its requests relayed
+    /// by the node which are still in the mempool.
+    pub async fn nonce(&self, from: Address) -> Result<U256, MetaTxError> {
+        let state = self.state.lock().await;
+        self.next_nonce(&state, from).await
+    }
+
+    /// Returns the sponsors of the node, with their remaining budget.
+    pub async fn sponsors(&self) -> Vec<SponsorInfo> {
+        let mut state = self.state.lock().await;
+        let now = Instant::now();
+        self.sponsors
+            .iter()
+            .map(|sponsor| SponsorInfo {
+                address: sponsor.address,
+                max_gas: U64::from(sponsor.max_gas),
+                budget: sponsor.budget,
+                remaining_budget: state
+                    .budgets
+                    .get_mut(&sponsor.address)
+                    .map_or(U256::ZERO, |budget| budget.remaining(now)),
+            })
+            .collect()
+    }
+
+    /// Checks the meta-transaction, then wraps it into a call to the forwarder sent by a sponsor.
+    /// Returns the hash of the wrapping transaction.
+    pub async fn relay(&self, request: MetaTransactionRequest) -> Result<B256, MetaTxError> {
+        let signer = request.recover_signer(&self.domain)?;
+        if signer != request.from {
+            return Err(MetaTxError::SignerMismatch { signer, from: request.from });
+        }
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+        if request.deadline.to::<u64>() <= now {
+            return Err(MetaTxError::Expired(request.deadline.to()));
+        }
+        if !request.value.is_zero() {
+            return Err(MetaTxError::ValueTransfer);
+        }
+
+        let mut state = self.state.lock().await;
+        let expected = self.next_nonce(&state, request.from).await?;
+        if request.nonce != expected {
+            return Err(MetaTxError::InvalidNonce { got: request.nonce, expected });
+        }
+
+        let eth_provider = self.eth_client.eth_provider();
+        let gas = request.gas.to::<u64>();
+        let gas_limit = gas.saturating_add(FORWARDER_GAS_OVERHEAD);
+        let max_fee_per_gas = eth_provider.gas_price().await?;
+        let fees = U256::from(gas_limit).saturating_mul(max_fee_per_gas);
+
+        let now = Instant::now();
+        let sponsor = self
+            .sponsors
+            .iter()
+            .filter(|sponsor| request.sponsor.map_or(true, |address| address == sponsor.address))
+            .find(|sponsor| {
+                gas <= sponsor.max_gas
+                    && state.budgets.get_mut(&sponsor.address).is_some_and(|budget| budget.spend(fees, now))
+            })
+            .ok_or_else(|| match request.sponsor {
+                Some(address) if !self.sponsors.iter().any(|sponsor| sponsor.address == address) => {
+                    MetaTxError::UnknownSponsor(address)
+                }
+                _ => MetaTxError::NotSponsored(gas),
+            })?;
+
+        let result = self.send(sponsor, &request, gas_limit, max_fee_per_gas).await;
+        match result {
+            Ok(hash) => {
+                state.last_relayed.insert(request.from, (request.nonce, hash));
+                tracing::info!(
+                    target: "meta_tx",
+                    monotonic_counter.meta_transactions_relayed = 1,
+                    from = ?request.from,
+                    to = ?request.to,
+                    sponsor = ?sponsor.address,
+                    ?hash,
+                    "relayed meta-transaction"
+                );
+                Ok(hash)
+            }
+            Err(err) => {
+                if let Some(budget) = state.budgets.get_mut(&sponsor.address) {
+                    budget.refund(fees);
+                }
+                Err(err)
+            }
+        }
+    }
+
+    /// Signs and sends the transaction of the sponsor calling the forwarder.
+    async fn send(
+        &self,
+        sponsor: &SponsorConfig,
+        request: &MetaTransactionRequest,
+        gas_limit: u64,
+        max_fee_per_gas: U256,
+    ) -> Result<B256, MetaTxError> {
+        let eth_provider = self.eth_client.eth_provider();
+        // The transactions of the sponsor which weren't relayed yet are still in the mempool
+        let onchain_nonce = eth_provider.transaction_count(sponsor.address, Some(BlockId::pending())).await?;
+        let pool_nonce = self
+            .eth_client
+            .mempool()
+            .get_transactions_by_sender(sponsor.address)
+            .into_iter()
+            .map(|transaction| transaction.nonce() + 1)
+            .max()
+            .unwrap_or_default();
+        let nonce = onchain_nonce.saturating_to::<u64>().max(pool_nonce);
+
+        let transaction = Transaction::Eip1559(TxEip1559 {
+            chain_id: eth_provider.chain_id,
+            nonce,
+            gas_limit,
+            max_fee_per_gas: max_fee_per_gas.saturating_to(),
+            max_priority_fee_per_gas: 0,
+            to: TxKind::Call(self.forwarder),
+            value: U256::ZERO,
+            input: request.execute_calldata()?,
+            ..Default::default()
+        });
+        let signature = sign_message(sponsor.private_key, transaction.signature_hash())
+            .map_err(|_| EthApiError::from(SignatureError::SigningFailure))?;
+        let transaction = TransactionSigned::from_transaction_and_signature(transaction, signature);
+
+        Ok(self.eth_client.send_raw_transaction(transaction.encoded_2718().into()).await?)
+    }
+
+    /// Returns the next nonce of the sender in the forwarder, or the one following its last
+    /// relayed request while the transaction wrapping it is in the mempool.
+    async fn next_nonce(&self, state: &RelayState, from: Address) -> Result<U256, MetaTxError> {
+        let forwarder = EthereumForwarder::new(self.forwarder, self.eth_client.eth_provider().clone());
+        let onchain_nonce = forwarder.nonces(from, BlockId::pending()).await?;
+        let relayed_nonce = state
+            .last_relayed
+            .get(&from)
+            .filter(|(_, hash)| self.eth_client.mempool().contains(hash))
+            .map(|(nonce, _)| *nonce + U256::from(1));
+        Ok(relayed_nonce.map_or(onchain_nonce, |nonce| nonce.max(onchain_nonce)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sponsor_config_from_str() {
+        // Given
+        let private_key = B256::from(U256::from(1));
+
+        // When
+        let sponsor = SponsorConfig::from_str(&format!("{private_key}:500000:1000000000000000000")).unwrap();
+
+        // Then
+        assert_eq!(sponsor.private_key, private_key);
+        assert_eq!(sponsor.max_gas, 500_000);
+        assert_eq!(sponsor.budget, U256::from(10).pow(U256::from(18)));
+        assert!(SponsorConfig::from_str(&format!("{private_key}:500000")).is_err());
+        assert!(SponsorConfig::from_str("0x1:500000:1").is_err());
+        assert!(
+            parse_sponsors(&format!("{private_key}:1:1, {private_key}:2:2")).is_ok_and(|sponsors| sponsors.len() == 2)
+        );
+    }
+
+    #[test]
+    fn test_meta_transaction_recover_signer() {
+        // Given
+        let private_key = B256::from(U256::from(1));
+        let from = SponsorConfig::from_str(&format!("{private_key}:1:1")).unwrap().address;
+        let domain = Eip712Domain::new(
+            Some(Cow::Borrowed("ERC2771Forwarder")),
+            Some(Cow::Borrowed("1")),
+            Some(U256::from(1_802_203_764)),
+            Some(Address::left_padding_from(&[1])),
+            None,
+        );
+        let mut request = MetaTransactionRequest {
+            from,
+            to: Address::left_padding_from(&[2]),
+            value: U256::ZERO,
+            gas: U64::from(100_000),
+            nonce: U256::ZERO,
+            deadline: U64::from(u32::MAX),
+            data: Bytes::from_static(&[0xca, 0xfe]),
+            signature: Bytes::new(),
+            sponsor: None,
+        };
+
+        // When
+        let hash = request.forward_request().unwrap().eip712_signing_hash(&domain);
+        request.signature = sign_message(private_key, hash).unwrap().as_bytes().into();
+
+        // Then
+        assert_eq!(request.recover_signer(&domain).unwrap(), from);
+        request.nonce = U256::from(1);
+        assert_ne!(request.recover_signer(&domain).unwrap(), from);
+        request.signature = Bytes::from_static(&[1, 2, 3]);
+        assert!(matches!(request.recover_signer(&domain), Err(MetaTxError::InvalidSignature)));
+    }
+
+    #[test]
+    fn test_meta_transaction_invalid_deadline() {
+        // Given
+        let mut request = MetaTransactionRequest {
+            from: Address::left_padding_from(&[1]),
+            to: Address::left_padding_from(&[2]),
+            value: U256::ZERO,
+            gas: U64::from(100_000),
+            nonce: U256::ZERO,
+            deadline: U64::from(1u64 << 48),
+            data: Bytes::new(),
+            signature: Bytes::from(vec![0x1b; 65]),
+            sponsor: None,
+        };
+        let domain = Eip712Domain::new(None, None, Some(U256::from(1)), Some(Address::left_padding_from(&[1])), None);
+
+        // When
+        let recovered = request.recover_signer(&domain);
+        let calldata = request.execute_calldata();
+        request.deadline = U64::from((1u64 << 48) - 1);
+
+        // Then
+        assert!(matches!(recovered, Err(MetaTxError::InvalidDeadline(deadline)) if deadline == U64::from(1u64 << 48)));
+        assert!(matches!(calldata, Err(MetaTxError::InvalidDeadline(_))));
+        assert_eq!(request.forward_request().unwrap().deadline, U48::MAX);
+    }
+
+    #[test]
+    fn test_sponsor_budget() {
+        // Given
+        let now = Instant::now();
+        let mut budget = SponsorBudget::new(U256::from(100), Duration::from_secs(60), now);
+
+        // When
+        let spent = budget.spend(U256::from(70), now);
+        let overspent = budget.spend(U256::from(40), now);
+
+        // Then
+        assert!(spent);
+        assert!(!overspent);
+        assert_eq!(budget.remaining(now), U256::from(30));
+        budget.refund(U256::from(70));
+        assert_eq!(budget.remaining(now), U256::from(100));
+        assert!(budget.spend(U256::from(100), now));
+        assert_eq!(budget.remaining(now + Duration::from_secs(60)), U256::from(100));
+    }
+}
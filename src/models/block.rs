@@ -62,9 +62,81 @@ impl From<EthBlockNumberOrTag> for StarknetBlockId {
                 // With a centralized sequencer, the latest block is the pending block being filled.
                 Self::Tag(BlockTag::Pending)
             }
+            // The safe blocks are the blocks accepted on L2. The finalized blocks, accepted on L1,
+            // have no Starknet tag and are resolved to their number by the Ethereum provider.
             BlockNumberOrTag::Safe | BlockNumberOrTag::Finalized => Self::Tag(BlockTag::Latest),
             BlockNumberOrTag::Earliest => Self::Number(0),
             BlockNumberOrTag::Number(number) => Self::Number(number),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::B256;
+    use proptest::prelude::*;
+    use starknet::core::types::Felt;
+
+    #[test]
+    fn test_block_hash_to_starknet_block_id() {
+        proptest!(|(hash in any::<B256>())| {
+            // When
+            let block_id = StarknetBlockId::try_from(EthBlockId::new(EthereumBlockId::from(hash)));
+
+            // Then
+            // The hash is reduced modulo the Starknet prime instead of failing
+            let expected = U256::from_be_slice(hash.as_slice()).wrapping_rem(STARKNET_MODULUS);
+            match block_id {
+                Ok(StarknetBlockId::Hash(felt)) => prop_assert_eq!(U256::from_be_bytes(felt.to_bytes_be()), expected),
+                other => prop_assert!(false, "unexpected block id {:?}", other),
+            }
+        });
+    }
+
+    #[test]
+    fn test_block_number_to_starknet_block_id() {
+        proptest!(|(number in any::<u64>())| {
+            // When
+            let block_id = StarknetBlockId::try_from(EthBlockId::new(EthereumBlockId::number(number))).unwrap();
+
+            // Then
+            prop_assert_eq!(block_id, StarknetBlockId::Number(number));
+        });
+    }
+
+    #[test]
+    fn test_block_tag_to_starknet_block_id() {
+        // Given
+        let cases = [
+            (BlockNumberOrTag::Latest, StarknetBlockId::Tag(BlockTag::Pending)),
+            (BlockNumberOrTag::Pending, StarknetBlockId::Tag(BlockTag::Pending)),
+            (BlockNumberOrTag::Safe, StarknetBlockId::Tag(BlockTag::Latest)),
+            (BlockNumberOrTag::Finalized, StarknetBlockId::Tag(BlockTag::Latest)),
+            (BlockNumberOrTag::Earliest, StarknetBlockId::Number(0)),
+        ];
+
+        for (tag, expected) in cases {
+            // When
+            let block_id: StarknetBlockId = EthBlockNumberOrTag::from(tag).into();
+
+            // Then
+            assert_eq!(block_id, expected);
+        }
+    }
+
+    #[test]
+    fn test_block_hash_below_modulus_is_preserved() {
+        proptest!(|(bytes in any::<[u8; 32]>())| {
+            // Given
+            let felt = Felt::from_bytes_be(&bytes);
+            let hash = B256::from(felt.to_bytes_be());
+
+            // When
+            let block_id = StarknetBlockId::try_from(EthBlockId::new(EthereumBlockId::from(hash))).unwrap();
+
+            // Then
+            prop_assert_eq!(block_id, StarknetBlockId::Hash(felt));
+        });
+    }
+}
@@ -1,4 +1,4 @@
-use crate::providers::eth_provider::error::EthereumDataFormatError;
+use crate::providers::eth_provider::{constant::STARKNET_MODULUS, error::EthereumDataFormatError};
 use alloy_primitives::{Address, B256, U256, U64};
 use starknet::core::types::{EthAddress, Felt};
 use std::ops::{Deref, DerefMut};
@@ -58,9 +58,15 @@ impl From<B256> for Felt252Wrapper {
     }
 }
 
-impl From<U256> for Felt252Wrapper {
-    fn from(u256: U256) -> Self {
-        Self(Felt::from_bytes_be(&u256.to_be_bytes()))
+impl TryFrom<U256> for Felt252Wrapper {
+    type Error = EthereumDataFormatError;
+
+    fn try_from(u256: U256) -> Result<Self, Self::Error> {
+        // Reject values which would silently be reduced modulo the Starknet prime
+        if u256 >= STARKNET_MODULUS {
+            return Err(EthereumDataFormatError::Primitive);
+        }
+        Ok(Self(Felt::from_bytes_be(&u256.to_be_bytes())))
     }
 }
 
@@ -114,6 +120,7 @@ mod tests {
     use std::str::FromStr;
 
     use hex::FromHex;
+    use proptest::prelude::*;
 
     use super::*;
 
@@ -176,7 +183,7 @@ mod tests {
         let hash = U256::try_from_be_slice(&Felt::MAX.to_bytes_be()).unwrap();
 
         // When
-        let hash = Felt252Wrapper::from(hash);
+        let hash = Felt252Wrapper::try_from(hash).unwrap();
 
         // Then
         let expected_hash = Felt::MAX;
@@ -189,6 +196,66 @@ mod tests {
         let hash = U256::from_str_radix(OVERFLOW_FELT, 16).unwrap();
 
         // When
-        assert_eq!(Felt252Wrapper::from(hash).0, Felt::ZERO,);
+        let result = Felt252Wrapper::try_from(hash);
+
+        // Then
+        assert!(matches!(result, Err(EthereumDataFormatError::Primitive)));
+    }
+
+    #[test]
+    fn test_felt_u256_roundtrip() {
+        proptest!(|(value in any::<U256>())| {
+            // When
+            let felt = Felt252Wrapper::try_from(value);
+
+            // Then
+            // Values below the modulus roundtrip, others are rejected instead of being reduced
+            if value < STARKNET_MODULUS {
+                prop_assert_eq!(U256::from(felt.unwrap()), value);
+            } else {
+                prop_assert!(felt.is_err());
+            }
+        });
+    }
+
+    #[test]
+    fn test_felt_to_u256_roundtrip() {
+        proptest!(|(bytes in any::<[u8; 32]>())| {
+            // Given
+            let felt = Felt::from_bytes_be(&bytes);
+
+            // When
+            let value: U256 = Felt252Wrapper::from(felt).into();
+
+            // Then
+            prop_assert!(value < STARKNET_MODULUS);
+            prop_assert_eq!(Felt::from(Felt252Wrapper::try_from(value).unwrap()), felt);
+        });
+    }
+
+    #[test]
+    fn test_address_felt_roundtrip() {
+        proptest!(|(address in any::<Address>())| {
+            // When
+            let felt = Felt252Wrapper::from(address);
+
+            // Then
+            prop_assert_eq!(Address::try_from(felt).unwrap(), address);
+        });
+    }
+
+    #[test]
+    fn test_address_try_from_felt_above_160_bits_should_fail() {
+        proptest!(|(low in any::<Address>(), high in 1u64..)| {
+            // Given
+            // A felt with at least one bit set above the 160 bits of an address
+            let felt = Felt::from_bytes_be_slice(low.as_slice()) + Felt::from(high) * Felt::TWO.pow(160u32);
+
+            // When
+            let address = Address::try_from(Felt252Wrapper::from(felt));
+
+            // Then
+            prop_assert!(address.is_err());
+        });
     }
 }
@@ -0,0 +1,97 @@
+use alloy_rpc_types::Filter;
+use serde::{Deserialize, Deserializer};
+use serde_json::Value;
+
+/// The maximum number of topics of a log.
+const MAX_TOPICS: usize = 4;
+
+/// A log filter normalized on deserialization, in order to accept the same topics as geth:
+/// - each position holds `null` (any topic), a topic, or an array of topics matched with OR
+///   semantics;
+/// - an array holding `null` matches any topic at its position;
+/// - at most 4 positions can be set.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NormalizedFilter(Filter);
+
+impl From<NormalizedFilter> for Filter {
+    fn from(filter: NormalizedFilter) -> Self {
+        filter.0
+    }
+}
+
+impl<'de> Deserialize<'de> for NormalizedFilter {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let mut value = Value::deserialize(deserializer)?;
+        normalize_topics(&mut value).map_err(serde::de::Error::custom)?;
+
+        Filter::deserialize(value).map(Self).map_err(serde::de::Error::custom)
+    }
+}
+
+/// Replaces the topic arrays holding `null` with `null`, which matches any topic.
+fn normalize_topics(value: &mut Value) -> Result<(), String> {
+    let Some(Value::Array(topics)) = value.get_mut("topics") else { return Ok(()) };
+
+    if topics.len() > MAX_TOPICS {
+        return Err(format!("too many topics, expected at most {MAX_TOPICS}, got {}", topics.len()));
+    }
+
+    for topic in topics {
+        if matches!(topic, Value::Array(alternatives) if alternatives.iter().any(Value::is_null)) {
+            *topic = Value::Null;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::{b256, B256};
+    use alloy_rpc_types::Topic;
+    use serde_json::json;
+
+    const TOPIC_A: B256 = b256!("000000000000000000000000000000000000000000000000000000000000000a");
+    const TOPIC_B: B256 = b256!("000000000000000000000000000000000000000000000000000000000000000b");
+
+    fn deserialize(value: Value) -> Result<Filter, serde_json::Error> {
+        serde_json::from_value::<NormalizedFilter>(value).map(Into::into)
+    }
+
+    #[test]
+    fn test_normalize_ored_topics() {
+        // When
+        let filter = deserialize(json!({"topics": [[TOPIC_A, TOPIC_B], null, TOPIC_A]})).unwrap();
+
+        // Then
+        assert_eq!(filter.topics[0], Topic::from(vec![TOPIC_A, TOPIC_B]));
+        assert!(filter.topics[1].is_empty());
+        assert_eq!(filter.topics[2], Topic::from(TOPIC_A));
+        assert!(filter.topics[3].is_empty());
+    }
+
+    #[test]
+    fn test_normalize_null_in_ored_topics() {
+        // When
+        let filter = deserialize(json!({"topics": [[TOPIC_A, null], [TOPIC_B]]})).unwrap();
+        let empty = deserialize(json!({"topics": [[], TOPIC_B]})).unwrap();
+
+        // Then
+        assert!(filter.topics[0].is_empty());
+        assert_eq!(filter.topics[1], Topic::from(TOPIC_B));
+        assert!(empty.topics[0].is_empty());
+    }
+
+    #[test]
+    fn test_normalize_too_many_topics() {
+        // When
+        let filter = deserialize(json!({"topics": [null, null, null, null, TOPIC_A]}));
+
+        // Then
+        assert!(filter.is_err());
+    }
+}
@@ -0,0 +1,270 @@
+use alloy_dyn_abi::{DynSolType, DynSolValue};
+use alloy_primitives::{keccak256, B256};
+use alloy_rpc_types::{Filter, Log};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// The maximum number of indexed parameters of an event, the first topic holding its selector.
+const MAX_INDEXED_PARAMS: usize = 3;
+
+/// The errors raised when parsing the ABI filter of a log subscription.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum LogFilterError {
+    /// The event signature can't be parsed.
+    #[error("invalid event signature {0}")]
+    InvalidSignature(String),
+    /// A parameter of the event has an unsupported or invalid type.
+    #[error("invalid type {0} of an event parameter")]
+    InvalidType(String),
+    /// The event has more indexed parameters than topics.
+    #[error("too many indexed parameters, expected at most {MAX_INDEXED_PARAMS}")]
+    TooManyIndexedParams,
+    /// The filter references a parameter which isn't part of the event.
+    #[error("unknown event parameter {0}")]
+    UnknownParam(String),
+    /// The value of a parameter can't be coerced to its type.
+    #[error("invalid value {value} for the parameter {param}")]
+    InvalidValue { param: String, value: String },
+}
+
+/// The optional extension of the `logs` subscriptions, filtering the logs of an event on the
+/// values of its arguments on the server side.
+///
+/// The event is given by its human readable signature, e.g.
+/// `Transfer(address indexed from, address indexed to, uint256 value)`, and the arguments by
+/// their name. The filters of the indexed arguments are matched on the topics of the logs, the
+/// filters of the other arguments on the decoded data of the logs.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct LogFilterExtension {
+    /// The signature of the event.
+    pub event: String,
+    /// The values of the arguments of the event, by name.
+    #[serde(default)]
+    pub args: BTreeMap<String, String>,
+}
+
+/// A parameter of an event.
+#[derive(Debug, Clone)]
+struct EventParam {
+    name: Option<String>,
+    ty: DynSolType,
+    indexed: bool,
+}
+
+/// The ABI filter of a log subscription, parsed from its [`LogFilterExtension`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct AbiLogFilter {
+    /// The selector of the event, matched on the first topic.
+    pub selector: B256,
+    /// The topics of the indexed arguments, `None` matching any value.
+    pub topics: Vec<Option<B256>>,
+    /// The types of the arguments held in the data of the logs.
+    data_types: Vec<DynSolType>,
+    /// The values of the arguments held in the data of the logs, by position in the data.
+    data_values: BTreeMap<usize, DynSolValue>,
+}
+
+impl TryFrom<LogFilterExtension> for AbiLogFilter {
+    type Error = LogFilterError;
+
+    fn try_from(extension: LogFilterExtension) -> Result<Self, Self::Error> {
+        let (name, params) = parse_event(&extension.event)?;
+        if params.iter().filter(|param| param.indexed).count() > MAX_INDEXED_PARAMS {
+            return Err(LogFilterError::TooManyIndexedParams);
+        }
+
+        let types = params.iter().map(|param| param.ty.sol_type_name()).collect::<Vec<_>>().join(",");
+        let selector = keccak256(format!("{name}({types})"));
+
+        let mut args = extension.args;
+        let mut topics = Vec::new();
+        let mut data_types = Vec::new();
+        let mut data_values = BTreeMap::new();
+        for param in params {
+            let value = param
+                .name
+                .as_ref()
+                .and_then(|name| args.remove_entry(name))
+                .map(|(name, value)| {
+                    param.ty.coerce_str(&value).map_err(|_| LogFilterError::InvalidValue { param: name, value })
+                })
+                .transpose()?;
+
+            if param.indexed {
+                topics.push(value.as_ref().map(encode_topic));
+            } else {
+                if let Some(value) = value {
+                    data_values.insert(data_types.len(), value);
+                }
+                data_types.push(param.ty);
+            }
+        }
+
+        if let Some(name) = args.into_keys().next() {
+            return Err(LogFilterError::UnknownParam(name));
+        }
+
+        Ok(Self { selector, topics, data_types, data_values })
+    }
+}
+
+impl AbiLogFilter {
+    /// Restricts the topics of the filter to the event and to the values of its indexed
+    /// arguments, so that the logs are pre-filtered by the database.
+    #[must_use]
+    pub fn apply_topics(&self, filter: Filter) -> Filter {
+        let filter = filter.event_signature(self.selector);
+        self.topics.iter().enumerate().fold(filter, |filter, (position, topic)| match (position, topic) {
+            (0, Some(topic)) => filter.topic1(*topic),
+            (1, Some(topic)) => filter.topic2(*topic),
+            (2, Some(topic)) => filter.topic3(*topic),
+            _ => filter,
+        })
+    }
+
+    /// Returns true if the log is emitted by the event and matches the values of its arguments.
+    pub fn matches(&self, log: &Log) -> bool {
+        let topics = log.topics();
+        if topics.first() != Some(&self.selector) || topics.len() != self.topics.len() + 1 {
+            return false;
+        }
+        let topics_match =
+            self.topics.iter().zip(&topics[1..]).all(|(expected, topic)| expected.map_or(true, |t| t == *topic));
+        if !topics_match {
+            return false;
+        }
+        if self.data_values.is_empty() {
+            return true;
+        }
+
+        let Ok(DynSolValue::Tuple(values)) =
+            DynSolType::Tuple(self.data_types.clone()).abi_decode_sequence(&log.data().data)
+        else {
+            return false;
+        };
+        self.data_values.iter().all(|(position, expected)| values.get(*position) == Some(expected))
+    }
+}
+
+/// Parses the human readable signature of an event.
+fn parse_event(signature: &str) -> Result<(&str, Vec<EventParam>), LogFilterError> {
+    let invalid = || LogFilterError::InvalidSignature(signature.to_string());
+    let signature = signature.trim().trim_start_matches("event ");
+    let (name, params) = signature.split_once('(').ok_or_else(invalid)?;
+    let params = params.trim_end().strip_suffix(')').ok_or_else(invalid)?;
+    let name = name.trim();
+    if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+        return Err(invalid());
+    }
+    if params.trim().is_empty() {
+        return Ok((name, Vec::new()));
+    }
+
+    let params = params
+        .split(',')
+        .map(|param| {
+            let mut tokens = param.split_whitespace();
+            let ty = tokens.next().ok_or_else(invalid)?;
+            let ty = DynSolType::parse(ty).map_err(|_| LogFilterError::InvalidType(ty.to_string()))?;
+            let (indexed, name) = match (tokens.next(), tokens.next()) {
+                (Some("indexed"), name) => (true, name),
+                (name, None) => (false, name),
+                _ => return Err(invalid()),
+            };
+            if tokens.next().is_some() {
+                return Err(invalid());
+            }
+            Ok(EventParam { name: name.map(ToString::to_string), ty, indexed })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok((name, params))
+}
+
+/// Encodes the value of an indexed argument as a topic: the values fitting in a word are stored
+/// as is, the other values are hashed.
+fn encode_topic(value: &DynSolValue) -> B256 {
+    value.as_word().unwrap_or_else(|| keccak256(value.abi_encode_packed()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::{address, Address, Bytes, LogData, U256};
+
+    const TRANSFER: &str = "Transfer(address indexed from, address indexed to, uint256 value)";
+    const SENDER: Address = address!("1111111111111111111111111111111111111111");
+    const RECIPIENT: Address = address!("2222222222222222222222222222222222222222");
+
+    fn transfer_log(to: Address, value: u64) -> Log {
+        let data = LogData::new_unchecked(
+            vec![keccak256("Transfer(address,address,uint256)"), SENDER.into_word(), to.into_word()],
+            Bytes::from(U256::from(value).to_be_bytes::<32>()),
+        );
+        Log { inner: alloy_primitives::Log { address: Address::ZERO, data }, ..Default::default() }
+    }
+
+    #[test]
+    fn test_abi_log_filter_indexed_args() {
+        // Given
+        let extension = LogFilterExtension {
+            event: TRANSFER.to_string(),
+            args: BTreeMap::from([("to".to_string(), RECIPIENT.to_string())]),
+        };
+
+        // When
+        let filter = AbiLogFilter::try_from(extension).unwrap();
+
+        // Then
+        assert_eq!(filter.selector, keccak256("Transfer(address,address,uint256)"));
+        assert_eq!(filter.topics, vec![None, Some(RECIPIENT.into_word())]);
+        assert!(filter.matches(&transfer_log(RECIPIENT, 1)));
+        assert!(!filter.matches(&transfer_log(SENDER, 1)));
+
+        let topics = filter.apply_topics(Filter::default()).topics;
+        assert!(topics[0].matches(&filter.selector));
+        assert!(topics[1].is_empty());
+        assert!(topics[2].matches(&RECIPIENT.into_word()));
+    }
+
+    #[test]
+    fn test_abi_log_filter_data_args() {
+        // Given
+        let extension = LogFilterExtension {
+            event: TRANSFER.to_string(),
+            args: BTreeMap::from([("value".to_string(), "1000".to_string())]),
+        };
+
+        // When
+        let filter = AbiLogFilter::try_from(extension).unwrap();
+
+        // Then
+        assert!(filter.matches(&transfer_log(RECIPIENT, 1000)));
+        assert!(!filter.matches(&transfer_log(RECIPIENT, 999)));
+    }
+
+    #[test]
+    fn test_abi_log_filter_invalid() {
+        // Given
+        let unknown_param = LogFilterExtension {
+            event: TRANSFER.to_string(),
+            args: BTreeMap::from([("amount".to_string(), "1".to_string())]),
+        };
+        let invalid_value = LogFilterExtension {
+            event: TRANSFER.to_string(),
+            args: BTreeMap::from([("to".to_string(), "0x12".to_string())]),
+        };
+        let invalid_signature = LogFilterExtension { event: "Transfer(address".to_string(), ..Default::default() };
+
+        // When
+        // Then
+        assert_eq!(AbiLogFilter::try_from(unknown_param), Err(LogFilterError::UnknownParam("amount".to_string())));
+        assert_eq!(
+            AbiLogFilter::try_from(invalid_value),
+            Err(LogFilterError::InvalidValue { param: "to".to_string(), value: "0x12".to_string() })
+        );
+        assert!(matches!(AbiLogFilter::try_from(invalid_signature), Err(LogFilterError::InvalidSignature(_))));
+    }
+}
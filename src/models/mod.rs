@@ -1,4 +1,7 @@
 pub mod block;
 pub mod felt;
+pub mod filter;
+pub mod log_subscription;
 pub mod token;
 pub mod transaction;
+pub mod transaction_request;
@@ -1,10 +1,16 @@
 use crate::providers::eth_provider::{
+    database::types::transaction::ExtendedTransaction,
+    error::SignatureError,
     provider::EthApiResult,
-    starknet::kakarot_core::{ETH_SEND_TRANSACTION, KAKAROT_ADDRESS},
+    starknet::kakarot_core::{ETH_SEND_TRANSACTION, EXECUTE_FROM_OUTSIDE, KAKAROT_ADDRESS},
     utils::split_u256,
 };
-use alloy_consensus::transaction::Transaction as _;
-use alloy_rlp::Encodable;
+use alloy_consensus::{transaction::Transaction as _, TxEip1559, TxEip2930, TxLegacy};
+use alloy_eips::eip2718::Encodable2718;
+use alloy_primitives::{Address, Bytes, Signature, U256};
+use alloy_rlp::{Decodable, Encodable, Header};
+use num_traits::ToPrimitive;
+use rayon::prelude::*;
 use reth_primitives::{transaction::legacy_parity, Transaction, TransactionSigned};
 use starknet::core::types::Felt;
 #[cfg(not(feature = "hive"))]
@@ -37,6 +43,43 @@ pub(crate) fn transaction_signature_to_field_elements(transaction_signed: &Trans
     signature
 }
 
+/// Returns the signed transaction, rebuilt from the fields of the transaction served by the RPC.
+pub fn rpc_transaction_to_signed(transaction: ExtendedTransaction) -> EthApiResult<TransactionSigned> {
+    let signature = transaction.signature.ok_or(SignatureError::MissingSignature)?;
+    // The parity of the legacy transactions is recovered from `v` if `yParity` isn't set
+    let signature = Signature::try_from(signature).map_err(|_| SignatureError::InvalidParity)?;
+    Ok(TransactionSigned::from_transaction_and_signature(transaction.try_into()?, signature))
+}
+
+/// Minimum number of transactions whose signers are recovered in parallel, the overhead of the
+/// thread pool outweighing the gain for the smaller batches.
+pub const PARALLEL_RECOVERY_THRESHOLD: usize = 16;
+
+/// Recovers the signers of the transactions, in the order of the transactions, `None` for the
+/// transactions whose signature is invalid.
+///
+/// The secp256k1 recovery dominates the CPU time of the ingestion of a batch of transactions,
+/// the batches of at least [`PARALLEL_RECOVERY_THRESHOLD`] transactions are recovered in parallel
+/// on the rayon thread pool. The recovery is blocking, call it from a blocking task.
+pub fn recover_signers(transactions: &[TransactionSigned]) -> Vec<Option<Address>> {
+    if transactions.len() < PARALLEL_RECOVERY_THRESHOLD {
+        return transactions.iter().map(TransactionSigned::recover_signer).collect();
+    }
+    transactions.par_iter().map(TransactionSigned::recover_signer).collect()
+}
+
+/// Returns the EIP-2718 encoding of the signed transaction, rebuilt from the fields of the
+/// transaction served by the RPC.
+pub fn rpc_transaction_to_raw(transaction: ExtendedTransaction) -> EthApiResult<Bytes> {
+    Ok(rpc_transaction_to_signed(transaction)?.encoded_2718().into())
+}
+
+/// Returns the number of felts of the [`execute_from_outside`] calldata relaying the transaction,
+/// see [`transaction_data_to_starknet_calldata`].
+pub fn starknet_calldata_len(transaction_signed: &TransactionSigned) -> usize {
+    execute_from_outside_calldata(transaction_signed, Felt::ZERO).len()
+}
+
 /// Returns the transaction's data and signature combined into a
 /// [`execute_from_outside`] type transaction. The payload still needs
 /// to be signed by the relayer before broadcasting.
@@ -44,6 +87,20 @@ pub fn transaction_data_to_starknet_calldata(
     transaction_signed: &TransactionSigned,
     relayer_address: Felt,
 ) -> EthApiResult<Vec<Felt>> {
+    let execute_from_outside_calldata = execute_from_outside_calldata(transaction_signed, relayer_address);
+
+    // Check if call data is too large
+    #[cfg(not(feature = "hive"))]
+    if execute_from_outside_calldata.len() > *MAX_FELTS_IN_CALLDATA {
+        return Err(EthApiError::CalldataExceededLimit(*MAX_FELTS_IN_CALLDATA, execute_from_outside_calldata.len()));
+    }
+
+    Ok(execute_from_outside_calldata)
+}
+
+/// Builds the [`execute_from_outside`] calldata relaying the transaction, without checking its
+/// length.
+fn execute_from_outside_calldata(transaction_signed: &TransactionSigned, relayer_address: Felt) -> Vec<Felt> {
     let mut signed_data = Vec::with_capacity(transaction_signed.transaction.length());
     transaction_signed.transaction.encode_without_signature(&mut signed_data);
 
@@ -57,13 +114,6 @@ pub fn transaction_data_to_starknet_calldata(
 
     // Prepare the calldata for the Starknet invoke transaction
     let capacity = 10 + signed_data.len() + signature.len() + 1;
-
-    // Check if call data is too large
-    #[cfg(not(feature = "hive"))]
-    if capacity > *MAX_FELTS_IN_CALLDATA {
-        return Err(EthApiError::CalldataExceededLimit(*MAX_FELTS_IN_CALLDATA, capacity));
-    }
-
     let mut execute_from_outside_calldata = Vec::with_capacity(capacity);
 
     // Construct the execute from outside calldata
@@ -84,17 +134,172 @@ pub fn transaction_data_to_starknet_calldata(
     execute_from_outside_calldata.push(signature.len().into());
     execute_from_outside_calldata.append(&mut signature);
 
-    Ok(execute_from_outside_calldata)
+    execute_from_outside_calldata
+}
+
+/// Returns the Ethereum transaction relayed by the calldata of a Starknet invoke transaction,
+/// the inverse of [`transaction_data_to_starknet_calldata`]. The calldata is the one of the
+/// `__execute__` entrypoint of the relayer, holding a single call to the `execute_from_outside`
+/// entrypoint of the Kakarot account of the sender.
+///
+/// Returns `None` if the calldata doesn't relay an Ethereum transaction.
+pub fn starknet_calldata_to_transaction(calldata: &[Felt]) -> Option<TransactionSigned> {
+    // Calls of the relayer: [calls_len, to, selector, calldata_len, calldata...]
+    let [_, _, selector, calldata_len, calldata @ ..] = calldata else {
+        return None;
+    };
+    if *selector != *EXECUTE_FROM_OUTSIDE {
+        return None;
+    }
+    let calldata = calldata.get(..calldata_len.to_usize()?)?;
+
+    // Skip the outside execution and the call array, up to the signed data
+    let data_len = calldata.get(8)?.to_usize()?;
+    let signed_data = calldata.get(10..10 + data_len)?;
+    let signature = calldata.get(10 + data_len + 1..)?;
+
+    // Unpack the signed data from the 31-byte chunks, the last chunk being left padded
+    let (len, chunks) = signed_data.split_first()?;
+    let len = len.to_usize()?;
+    let mut data = Vec::with_capacity(len);
+    for (i, chunk) in chunks.iter().enumerate() {
+        let chunk_len = len.checked_sub(31 * i)?.min(31);
+        data.extend_from_slice(&chunk.to_bytes_be()[32 - chunk_len..]);
+    }
+    if data.len() != len {
+        return None;
+    }
+
+    let transaction = decode_unsigned_transaction(&data).ok()?;
+    let signature = field_elements_to_transaction_signature(&transaction, signature)?;
+
+    Some(TransactionSigned::from_transaction_and_signature(transaction, signature))
+}
+
+/// Decodes a transaction encoded without its signature. Only the transaction types supported by
+/// Kakarot (legacy, EIP-2930 and EIP-1559) are decoded.
+fn decode_unsigned_transaction(data: &[u8]) -> alloy_rlp::Result<Transaction> {
+    let (tx_type, mut buf) = match data.split_first() {
+        Some((&tx_type, payload)) if tx_type < 0xc0 => (Some(tx_type), payload),
+        _ => (None, data),
+    };
+    let buf = &mut buf;
+
+    let header = Header::decode(buf)?;
+    if !header.list {
+        return Err(alloy_rlp::Error::UnexpectedString);
+    }
+    if buf.len() != header.payload_length {
+        return Err(alloy_rlp::Error::UnexpectedLength);
+    }
+
+    let transaction = match tx_type {
+        None => {
+            let nonce = u64::decode(buf)?;
+            let gas_price = u128::decode(buf)?;
+            let gas_limit = u64::decode(buf)?;
+            let to = Decodable::decode(buf)?;
+            let value = U256::decode(buf)?;
+            let input = Decodable::decode(buf)?;
+            // EIP-155 transactions are signed with the chain id followed by two zeros
+            let chain_id = if buf.is_empty() {
+                None
+            } else {
+                let chain_id = u64::decode(buf)?;
+                if u8::decode(buf)? != 0 || u8::decode(buf)? != 0 {
+                    return Err(alloy_rlp::Error::Custom("invalid EIP-155 signing fields"));
+                }
+                Some(chain_id)
+            };
+            Transaction::Legacy(TxLegacy { chain_id, nonce, gas_price, gas_limit, to, value, input })
+        }
+        Some(1) => {
+            let chain_id = u64::decode(buf)?;
+            let nonce = u64::decode(buf)?;
+            let gas_price = u128::decode(buf)?;
+            let gas_limit = u64::decode(buf)?;
+            let to = Decodable::decode(buf)?;
+            let value = U256::decode(buf)?;
+            let input = Decodable::decode(buf)?;
+            let access_list = Decodable::decode(buf)?;
+            Transaction::Eip2930(TxEip2930 { chain_id, nonce, gas_price, gas_limit, to, value, access_list, input })
+        }
+        Some(2) => {
+            let chain_id = u64::decode(buf)?;
+            let nonce = u64::decode(buf)?;
+            let max_priority_fee_per_gas = u128::decode(buf)?;
+            let max_fee_per_gas = u128::decode(buf)?;
+            let gas_limit = u64::decode(buf)?;
+            let to = Decodable::decode(buf)?;
+            let value = U256::decode(buf)?;
+            let input = Decodable::decode(buf)?;
+            let access_list = Decodable::decode(buf)?;
+            Transaction::Eip1559(TxEip1559 {
+                chain_id,
+                nonce,
+                gas_limit,
+                max_fee_per_gas,
+                max_priority_fee_per_gas,
+                to,
+                value,
+                access_list,
+                input,
+            })
+        }
+        Some(_) => return Err(alloy_rlp::Error::Custom("unsupported transaction type")),
+    };
+
+    if !buf.is_empty() {
+        return Err(alloy_rlp::Error::UnexpectedLength);
+    }
+
+    Ok(transaction)
+}
+
+/// Returns the signature of the transaction from its [`Felt`] representation, the inverse of
+/// [`transaction_signature_to_field_elements`].
+fn field_elements_to_transaction_signature(transaction: &Transaction, signature: &[Felt]) -> Option<Signature> {
+    let [r_low, r_high, s_low, s_high, v] = signature else {
+        return None;
+    };
+    let recombine = |low: &Felt, high: &Felt| -> Option<U256> {
+        Some(U256::from(low.to_u128()?) + (U256::from(high.to_u128()?) << 128))
+    };
+    let v = v.to_u64()?;
+
+    // Legacy transactions are signed with v := {0, 1} + chain_id * 2 + 35 or {0, 1} + 27
+    let odd_y_parity = match (transaction, transaction.chain_id()) {
+        (Transaction::Legacy(_), Some(chain_id)) => v.checked_sub(35 + 2 * chain_id)?,
+        (Transaction::Legacy(_), None) => v.checked_sub(27)?,
+        _ => v,
+    };
+    if odd_y_parity > 1 {
+        return None;
+    }
+
+    Signature::from_rs_and_parity(recombine(r_low, r_high)?, recombine(s_low, s_high)?, odd_y_parity == 1).ok()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use alloy_consensus::TxEip2930;
-    use alloy_primitives::{bytes, hex, Signature, TxKind, U256};
-    use alloy_rlp::Decodable;
+    use alloy_primitives::{bytes, hex, Address, Bytes, TxKind};
+    use proptest::prelude::*;
     use std::str::FromStr;
 
+    /// Rebuilds the signed data from the 31-byte chunks packed in the calldata.
+    fn unpack_signed_data(packed: &[Felt]) -> Vec<u8> {
+        let len = packed[0].to_usize().expect("Failed to convert length");
+        let mut data: Vec<u8> = packed[1..].iter().flat_map(|felt| felt.to_bytes_be()[1..].to_vec()).collect();
+        // The last chunk might be shorter than 31 bytes, in which case it was left padded
+        let last_chunk_len = len % 31;
+        if last_chunk_len != 0 {
+            data.drain(data.len() - 31..data.len() - last_chunk_len);
+        }
+        data.truncate(len);
+        data
+    }
+
     #[test]
     fn test_transaction_data_to_starknet_calldata() {
         // Define a sample signed transaction.
@@ -118,6 +323,7 @@ mod tests {
         // Assert the length of calldata.
         // We must adapt the check as we pack the calldata in 31-byte chunks.
         assert_eq!(calldata.len(), 59);
+        assert_eq!(starknet_calldata_len(&transaction), 59);
 
         // Assert the first 6 elements of calldata.
         assert_eq!(
@@ -158,4 +364,152 @@ mod tests {
         // Attempt to convert the transaction into a Starknet transaction
         transaction_data_to_starknet_calldata(&transaction, Felt::ZERO).unwrap();
     }
+
+    #[test]
+    fn test_transaction_data_to_starknet_calldata_roundtrip() {
+        proptest!(|(
+            input in proptest::collection::vec(any::<u8>(), 0..2048),
+            nonce in any::<u64>(),
+            value in any::<U256>(),
+            to in any::<Address>(),
+            r in any::<U256>(),
+            s in any::<U256>(),
+            parity in any::<bool>(),
+        )| {
+            // Given
+            let transaction = TransactionSigned::from_transaction_and_signature(
+                Transaction::Eip1559(TxEip1559 {
+                    chain_id: 1_802_203_764,
+                    nonce,
+                    gas_limit: 21_000,
+                    to: TxKind::Call(to),
+                    value,
+                    input: Bytes::from(input),
+                    ..Default::default()
+                }),
+                Signature::from_rs_and_parity(r, s, parity).unwrap(),
+            );
+            let mut expected_signed_data = Vec::new();
+            transaction.transaction.encode_without_signature(&mut expected_signed_data);
+
+            // When
+            let calldata = transaction_data_to_starknet_calldata(&transaction, Felt::ZERO).unwrap();
+
+            // Then
+            // The signed data is packed without loss
+            let data_len = calldata[8].to_usize().unwrap();
+            let packed = &calldata[10..10 + data_len];
+            prop_assert_eq!(unpack_signed_data(packed), expected_signed_data);
+
+            // The signature is split in low and high parts without loss
+            let signature = &calldata[10 + data_len + 1..];
+            let recombine = |low: Felt, high: Felt| {
+                U256::from_be_bytes(low.to_bytes_be()) + (U256::from_be_bytes(high.to_bytes_be()) << 128)
+            };
+            prop_assert_eq!(calldata[10 + data_len], Felt::from(signature.len()));
+            prop_assert_eq!(recombine(signature[0], signature[1]), r);
+            prop_assert_eq!(recombine(signature[2], signature[3]), s);
+            prop_assert_eq!(signature[4], Felt::from(u64::from(parity)));
+        });
+    }
+
+    #[test]
+    fn test_starknet_calldata_to_transaction_roundtrip() {
+        proptest!(|(
+            input in proptest::collection::vec(any::<u8>(), 0..2048),
+            nonce in any::<u64>(),
+            value in any::<U256>(),
+            to in any::<Address>(),
+            r in any::<U256>(),
+            s in any::<U256>(),
+            parity in any::<bool>(),
+        )| {
+            // Given
+            let input = Bytes::from(input);
+            let transactions = [
+                Transaction::Legacy(TxLegacy {
+                    chain_id: Some(1_802_203_764),
+                    nonce,
+                    gas_price: 10,
+                    gas_limit: 21_000,
+                    to: TxKind::Call(to),
+                    value,
+                    input: input.clone(),
+                }),
+                Transaction::Legacy(TxLegacy { nonce, gas_limit: 21_000, to: TxKind::Create, ..Default::default() }),
+                Transaction::Eip2930(TxEip2930 {
+                    chain_id: 1_802_203_764,
+                    nonce,
+                    gas_limit: 21_000,
+                    to: TxKind::Call(to),
+                    value,
+                    input: input.clone(),
+                    ..Default::default()
+                }),
+                Transaction::Eip1559(TxEip1559 {
+                    chain_id: 1_802_203_764,
+                    nonce,
+                    gas_limit: 21_000,
+                    max_fee_per_gas: 2,
+                    max_priority_fee_per_gas: 1,
+                    to: TxKind::Call(to),
+                    value,
+                    input,
+                    ..Default::default()
+                }),
+            ];
+
+            for transaction in transactions {
+                let transaction = TransactionSigned::from_transaction_and_signature(
+                    transaction,
+                    Signature::from_rs_and_parity(r, s, parity).unwrap(),
+                );
+                let execute_from_outside = transaction_data_to_starknet_calldata(&transaction, Felt::ZERO).unwrap();
+                let calldata: Vec<_> = [Felt::ONE, Felt::TWO, *EXECUTE_FROM_OUTSIDE, execute_from_outside.len().into()]
+                    .into_iter()
+                    .chain(execute_from_outside)
+                    .collect();
+
+                // When
+                let decoded = starknet_calldata_to_transaction(&calldata);
+
+                // Then
+                prop_assert_eq!(decoded.as_ref().map(TransactionSigned::hash), Some(transaction.hash()));
+                prop_assert_eq!(decoded, Some(transaction));
+            }
+        });
+    }
+
+    #[test]
+    fn test_recover_signers() {
+        // Given
+        let mut rng = reth_testing_utils::generators::rng();
+        let transactions: Vec<_> = (0..2 * PARALLEL_RECOVERY_THRESHOLD as u64)
+            .map(|nonce| {
+                let transaction = Transaction::Eip1559(TxEip1559 { nonce, ..Default::default() });
+                reth_testing_utils::generators::sign_tx_with_random_key_pair(&mut rng, transaction)
+            })
+            .collect();
+        let signers: Vec<_> = transactions.iter().map(TransactionSigned::recover_signer).collect();
+
+        // When
+        let recovered = recover_signers(&transactions);
+
+        // Then
+        assert!(signers.iter().all(Option::is_some));
+        assert_eq!(recovered, signers);
+        assert_eq!(recover_signers(&transactions[..2]), signers[..2]);
+    }
+
+    #[test]
+    fn test_starknet_calldata_to_transaction_other_call() {
+        // Given
+        let calldata = vec![Felt::ONE, *KAKAROT_ADDRESS, *ETH_SEND_TRANSACTION, Felt::ONE, Felt::ZERO];
+
+        // When
+        let decoded = starknet_calldata_to_transaction(&calldata);
+
+        // Then
+        assert_eq!(decoded, None);
+    }
 }
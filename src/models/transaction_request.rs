@@ -0,0 +1,167 @@
+use crate::providers::eth_provider::error::TransactionError;
+use alloy_rpc_types::{request::TransactionInput, TransactionRequest};
+use serde::{Deserialize, Deserializer};
+use serde_json::Value;
+
+/// The fields of a transaction request holding a quantity.
+const QUANTITY_FIELDS: [&str; 9] = [
+    "gas",
+    "gasPrice",
+    "maxFeePerGas",
+    "maxPriorityFeePerGas",
+    "maxFeePerBlobGas",
+    "value",
+    "nonce",
+    "chainId",
+    "type",
+];
+
+/// A transaction request normalized on deserialization, in order to accept the same requests
+/// as geth:
+/// - the call data can be passed in either `input` or `data`, or both if they are equal;
+/// - the gas limit can be passed in either `gas` or `gasLimit`;
+/// - the hex quantities can have leading zeros, and `0x` is read as zero;
+/// - an empty `to` is read as a contract creation;
+/// - the legacy `gasPrice` and the EIP-1559 fee fields can't be mixed.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NormalizedTransactionRequest(TransactionRequest);
+
+impl From<NormalizedTransactionRequest> for TransactionRequest {
+    fn from(request: NormalizedTransactionRequest) -> Self {
+        request.0
+    }
+}
+
+impl<'de> Deserialize<'de> for NormalizedTransactionRequest {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let mut value = Value::deserialize(deserializer)?;
+        normalize_request_fields(&mut value);
+
+        let request = TransactionRequest::deserialize(value).map_err(serde::de::Error::custom)?;
+        normalize_transaction_request(request).map(Self).map_err(serde::de::Error::custom)
+    }
+}
+
+/// Normalizes the call data and checks the consistency of the fee fields of the request.
+pub fn normalize_transaction_request(mut request: TransactionRequest) -> Result<TransactionRequest, TransactionError> {
+    let input = request.input.try_into_unique_input().map_err(|_| TransactionError::ConflictingInput)?;
+    request.input = TransactionInput { input, data: None };
+
+    if request.gas_price.is_some() && (request.max_fee_per_gas.is_some() || request.max_priority_fee_per_gas.is_some())
+    {
+        return Err(TransactionError::ConflictingFeeFields);
+    }
+    if let (Some(max_fee_per_gas), Some(max_priority_fee_per_gas)) =
+        (request.max_fee_per_gas, request.max_priority_fee_per_gas)
+    {
+        if max_priority_fee_per_gas > max_fee_per_gas {
+            return Err(TransactionError::TipAboveFeeCap(max_fee_per_gas, max_priority_fee_per_gas));
+        }
+    }
+
+    Ok(request)
+}
+
+/// Normalizes the JSON fields of a transaction request before its deserialization.
+fn normalize_request_fields(value: &mut Value) {
+    let Value::Object(fields) = value else { return };
+
+    if let Some(gas_limit) = fields.remove("gasLimit") {
+        fields.entry("gas").or_insert(gas_limit);
+    }
+
+    if matches!(fields.get("to"), Some(Value::String(to)) if to.is_empty() || to == "0x") {
+        fields.remove("to");
+    }
+
+    for field in QUANTITY_FIELDS {
+        if let Some(Value::String(quantity)) = fields.get_mut(field) {
+            if let Some(normalized) = normalize_quantity(quantity) {
+                *quantity = normalized;
+            }
+        }
+    }
+}
+
+/// Strips the leading zeros of a hex quantity. Returns `None` if the quantity isn't prefixed
+/// by `0x`, in which case it is left to the deserialization of the request.
+fn normalize_quantity(quantity: &str) -> Option<String> {
+    let digits = quantity.strip_prefix("0x").or_else(|| quantity.strip_prefix("0X"))?;
+    let digits = digits.trim_start_matches('0');
+    Some(if digits.is_empty() { "0x0".to_string() } else { format!("0x{digits}") })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::{bytes, Address, TxKind};
+    use serde_json::json;
+
+    fn deserialize(value: Value) -> Result<TransactionRequest, serde_json::Error> {
+        serde_json::from_value::<NormalizedTransactionRequest>(value).map(Into::into)
+    }
+
+    #[test]
+    fn test_normalize_data_and_input() {
+        // When
+        let data = deserialize(json!({"data": "0x1234"})).unwrap();
+        let input = deserialize(json!({"input": "0x1234"})).unwrap();
+        let both = deserialize(json!({"input": "0x1234", "data": "0x1234"})).unwrap();
+        let conflicting = deserialize(json!({"input": "0x1234", "data": "0x5678"}));
+
+        // Then
+        for request in [data, input, both] {
+            assert_eq!(request.input, TransactionInput { input: Some(bytes!("1234")), data: None });
+        }
+        assert!(conflicting.is_err());
+    }
+
+    #[test]
+    fn test_normalize_quantities() {
+        // When
+        let request = deserialize(json!({
+            "gasLimit": "0x05208",
+            "value": "0x",
+            "nonce": "0x0001",
+            "maxFeePerGas": "0X0a",
+        }))
+        .unwrap();
+
+        // Then
+        assert_eq!(request.gas, Some(21000));
+        assert_eq!(request.value, Some(Default::default()));
+        assert_eq!(request.nonce, Some(1));
+        assert_eq!(request.max_fee_per_gas, Some(10));
+    }
+
+    #[test]
+    fn test_normalize_missing_from_and_empty_to() {
+        // When
+        let request = deserialize(json!({"to": "0x", "input": "0x00"})).unwrap();
+        let call = deserialize(json!({"to": Address::ZERO})).unwrap();
+
+        // Then
+        assert_eq!(request.from, None);
+        assert_eq!(request.to, None);
+        assert_eq!(call.to, Some(TxKind::Call(Address::ZERO)));
+    }
+
+    #[test]
+    fn test_normalize_fee_fields() {
+        // When
+        let legacy = deserialize(json!({"gasPrice": "0x1"})).unwrap();
+        let eip1559 = deserialize(json!({"maxFeePerGas": "0x2", "maxPriorityFeePerGas": "0x1"})).unwrap();
+        let mixed = deserialize(json!({"gasPrice": "0x1", "maxFeePerGas": "0x2"}));
+        let tip_above_fee_cap = deserialize(json!({"maxFeePerGas": "0x1", "maxPriorityFeePerGas": "0x2"}));
+
+        // Then
+        assert_eq!(legacy.gas_price, Some(1));
+        assert_eq!(eip1559.max_fee_per_gas, Some(2));
+        assert_eq!(eip1559.max_priority_fee_per_gas, Some(1));
+        assert!(mixed.is_err());
+        assert!(tip_above_fee_cap.is_err());
+    }
+}
@@ -0,0 +1,142 @@
+use crate::eth_rpc::openrpc::SchemaOf;
+use alloy_primitives::U256;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use starknet::core::types::Felt;
+use std::{
+    collections::BTreeMap,
+    sync::{LazyLock, Mutex, MutexGuard, PoisonError},
+};
+
+/// The number of blocks whose build stats are kept, the stats of the older blocks are dropped.
+const MAX_TRACKED_BLOCKS: usize = 1024;
+
+/// The build stats of the latest blocks built by the relay loop of the node.
+pub static BLOCK_BUILD_STATS: LazyLock<BlockBuildStatsStore> = LazyLock::new(BlockBuildStatsStore::default);
+
+/// The stats of the transactions pulled from the mempool by the relay loop while the block was
+/// pending.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockBuildStats {
+    /// The number of the block.
+    pub block_number: u64,
+    /// The number of transactions pulled from the mempool to be relayed.
+    pub pulled_transactions: u64,
+    /// The number of transactions left in the mempool as they exceeded the remaining gas of the
+    /// block.
+    pub skipped_gas_transactions: u64,
+    /// The sum of the gas limits of the pulled transactions.
+    pub gas_limit_used: u64,
+    /// The number of transactions submitted by each relayer.
+    #[schemars(with = "SchemaOf<BTreeMap<Felt, u64>>")]
+    pub relayers: BTreeMap<Felt, u64>,
+    /// The number of submissions which failed, the transactions being re-inserted in the mempool.
+    pub failed_submissions: u64,
+    /// The maximum fees paid by the pulled transactions at the pending base fee, in wei.
+    #[schemars(with = "SchemaOf<U256>")]
+    pub total_fees: U256,
+}
+
+/// Records the build stats of the latest blocks.
+#[derive(Debug, Default)]
+pub struct BlockBuildStatsStore {
+    blocks: Mutex<BTreeMap<u64, BlockBuildStats>>,
+}
+
+impl BlockBuildStatsStore {
+    /// Returns the gas of the block left to the transactions which aren't pulled yet.
+    pub fn remaining_gas(&self, block_number: u64, block_gas_limit: u64) -> u64 {
+        let used = self.lock().get(&block_number).map_or(0, |stats| stats.gas_limit_used);
+        block_gas_limit.saturating_sub(used)
+    }
+
+    /// Records a transaction pulled from the mempool for the block.
+    pub fn record_pulled(&self, block_number: u64, gas_limit: u64, fees: U256) {
+        self.update(block_number, |stats| {
+            stats.pulled_transactions += 1;
+            stats.gas_limit_used = stats.gas_limit_used.saturating_add(gas_limit);
+            stats.total_fees = stats.total_fees.saturating_add(fees);
+        });
+    }
+
+    /// Records a transaction left in the mempool as it exceeded the remaining gas of the block.
+    pub fn record_skipped(&self, block_number: u64) {
+        self.update(block_number, |stats| stats.skipped_gas_transactions += 1);
+    }
+
+    /// Records the submission of a transaction of the block by the relayer.
+    pub fn record_submission(&self, block_number: u64, relayer: Felt, success: bool) {
+        self.update(block_number, |stats| {
+            if success {
+                *stats.relayers.entry(relayer).or_default() += 1;
+            } else {
+                stats.failed_submissions += 1;
+            }
+        });
+    }
+
+    /// Returns the build stats of the block, if it was built by the node and is still tracked.
+    pub fn get(&self, block_number: u64) -> Option<BlockBuildStats> {
+        self.lock().get(&block_number).cloned()
+    }
+
+    fn update(&self, block_number: u64, update: impl FnOnce(&mut BlockBuildStats)) {
+        let mut blocks = self.lock();
+        update(blocks.entry(block_number).or_insert_with(|| BlockBuildStats { block_number, ..Default::default() }));
+        while blocks.len() > MAX_TRACKED_BLOCKS {
+            blocks.pop_first();
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, BTreeMap<u64, BlockBuildStats>> {
+        self.blocks.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_block_build_stats() {
+        // Given
+        let store = BlockBuildStatsStore::default();
+        let relayer = Felt::from(1u8);
+
+        // When
+        store.record_pulled(10, 21_000, U256::from(42_000));
+        store.record_pulled(10, 100_000, U256::from(200_000));
+        store.record_skipped(10);
+        store.record_submission(10, relayer, true);
+        store.record_submission(10, relayer, false);
+
+        // Then
+        let stats = store.get(10).unwrap();
+        assert_eq!(stats.block_number, 10);
+        assert_eq!(stats.pulled_transactions, 2);
+        assert_eq!(stats.skipped_gas_transactions, 1);
+        assert_eq!(stats.gas_limit_used, 121_000);
+        assert_eq!(stats.relayers, BTreeMap::from([(relayer, 1)]));
+        assert_eq!(stats.failed_submissions, 1);
+        assert_eq!(stats.total_fees, U256::from(242_000));
+        assert_eq!(store.remaining_gas(10, 150_000), 29_000);
+        assert_eq!(store.remaining_gas(11, 150_000), 150_000);
+        assert!(store.get(11).is_none());
+    }
+
+    #[test]
+    fn test_block_build_stats_drops_oldest_blocks() {
+        // Given
+        let store = BlockBuildStatsStore::default();
+
+        // When
+        for block_number in 0..=MAX_TRACKED_BLOCKS as u64 {
+            store.record_skipped(block_number);
+        }
+
+        // Then
+        assert!(store.get(0).is_none());
+        assert!(store.get(MAX_TRACKED_BLOCKS as u64).is_some());
+    }
+}
@@ -1,6 +1,74 @@
-use std::time::Duration;
+use alloy_primitives::Address;
+use std::{collections::HashSet, str::FromStr, sync::LazyLock, time::Duration};
 
-pub(super) static ONE_TENTH_ETH: u64 = 10u64.pow(17);
+pub(crate) static ONE_TENTH_ETH: u64 = 10u64.pow(17);
 
 // Transactions should be pruned after 5 minutes in the mempool
-pub const PRUNE_DURATION: Duration = Duration::from_secs(300);
+pub const MAX_PRUNE_DURATION: Duration = Duration::from_secs(300);
+
+/// Under heavy load, transactions are pruned faster, but never before 30 seconds in the mempool.
+pub const MIN_PRUNE_DURATION: Duration = Duration::from_secs(30);
+
+/// The number of transactions in the mempool above which transactions are pruned faster.
+pub const PRUNE_TARGET_POOL_SIZE: usize = 1_000;
+
+/// The insertion rate in the mempool, in transactions per second, above which transactions are
+/// pruned faster.
+pub const PRUNE_TARGET_INSERTION_RATE: f64 = 20.;
+
+/// Default number of transactions returned by a page of the mempool content.
+pub const TXPOOL_CONTENT_DEFAULT_PAGE_SIZE: u64 = 1_000;
+
+/// Maximum number of transactions returned by a page of the mempool content.
+pub const TXPOOL_CONTENT_MAX_PAGE_SIZE: u64 = 5_000;
+
+/// The senders whose transactions are relayed first, read from the comma separated
+/// `PRIORITY_SENDERS` environment variable.
+pub static PRIORITY_SENDERS: LazyLock<HashSet<Address>> = LazyLock::new(|| senders_from_env("PRIORITY_SENDERS"));
+
+/// The senders of the protocol keepers (e.g. oracle updaters, liquidators), whose transactions
+/// are relayed by the keeper relayers, read from the comma separated `KEEPER_SENDERS`
+/// environment variable.
+pub static KEEPER_SENDERS: LazyLock<HashSet<Address>> = LazyLock::new(|| senders_from_env("KEEPER_SENDERS"));
+
+/// The senders of the faucet, whose transactions are relayed by the faucet relayers, read from
+/// the comma separated `FAUCET_SENDERS` environment variable.
+pub static FAUCET_SENDERS: LazyLock<HashSet<Address>> = LazyLock::new(|| senders_from_env("FAUCET_SENDERS"));
+
+/// The senders allowed to deploy contracts, read from the comma separated `DEPLOYER_ALLOWLIST`
+/// environment variable. If unset or empty, anyone can deploy contracts.
+pub static DEPLOYER_ALLOWLIST: LazyLock<Option<HashSet<Address>>> = LazyLock::new(|| {
+    std::env::var("DEPLOYER_ALLOWLIST")
+        .is_ok_and(|val| !val.trim().is_empty())
+        .then(|| senders_from_env("DEPLOYER_ALLOWLIST"))
+});
+
+/// If set, which is the default, the pool only accepts replay protected (EIP-155) transactions
+/// signed for the chain id of the node, apart from the white listed pre EIP-155 transactions.
+/// Can be disabled with the `STRICT_EIP155` environment variable on development networks.
+pub static STRICT_EIP155: LazyLock<bool> =
+    LazyLock::new(|| std::env::var("STRICT_EIP155").ok().and_then(|val| val.parse::<bool>().ok()).unwrap_or(true));
+
+/// The memory budget of each sub-pool (pending, base fee and queued transactions), in bytes,
+/// read from the `POOL_MAX_SIZE_BYTES` environment variable. The size of a transaction includes
+/// its calldata, so that a few large transactions can't exhaust the memory of the node while
+/// staying below the count limit of the sub-pool. Above the budget, the transactions of lowest
+/// priority are evicted. Defaults to 20 MB, the default budget of the reth sub-pools.
+pub static POOL_MAX_SIZE_BYTES: LazyLock<usize> = LazyLock::new(|| {
+    std::env::var("POOL_MAX_SIZE_BYTES").ok().and_then(|val| val.parse().ok()).unwrap_or(20 * 1024 * 1024)
+});
+
+/// The share of the memory budget of a sub-pool above which the maintenance of the pool warns
+/// that transactions are about to be evicted.
+pub const POOL_SIZE_WARNING_RATIO: f64 = 0.9;
+
+/// Reads the comma separated list of Ethereum addresses of the environment variable, skipping
+/// the invalid addresses.
+fn senders_from_env(name: &str) -> HashSet<Address> {
+    std::env::var(name)
+        .unwrap_or_default()
+        .replace(' ', "")
+        .split(',')
+        .filter_map(|address| Address::from_str(address).ok())
+        .collect()
+}
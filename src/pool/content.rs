@@ -0,0 +1,155 @@
+use super::constants::{TXPOOL_CONTENT_DEFAULT_PAGE_SIZE, TXPOOL_CONTENT_MAX_PAGE_SIZE};
+use crate::eth_rpc::openrpc::SchemaOf;
+use alloy_primitives::{Address, U64};
+use alloy_rpc_types_txpool::TxpoolContent;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// The position of the last transaction of a page of the mempool content.
+///
+/// The transactions are iterated by sender then nonce, whatever their sub-pool, so that the
+/// position of a transaction doesn't depend on the other transactions of the mempool: the
+/// transactions added or removed between two pages don't shift the following pages, and a
+/// transaction promoted from the queued to the pending sub-pool is returned once.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct TxpoolContentCursor {
+    /// The sender of the transaction.
+    #[schemars(with = "SchemaOf<Address>")]
+    pub sender: Address,
+    /// The nonce of the transaction.
+    #[schemars(with = "SchemaOf<U64>")]
+    pub nonce: U64,
+}
+
+/// A page of the mempool content, grouped by sub-pool, sender and nonce like `txpool_content`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct TxpoolContentPage<T> {
+    /// The pending transactions of the page.
+    #[schemars(with = "BTreeMap<String, BTreeMap<String, T>>")]
+    pub pending: BTreeMap<Address, BTreeMap<String, T>>,
+    /// The queued transactions of the page.
+    #[schemars(with = "BTreeMap<String, BTreeMap<String, T>>")]
+    pub queued: BTreeMap<Address, BTreeMap<String, T>>,
+    /// The cursor to use to fetch the next page. `None` if this is the last page.
+    pub next_cursor: Option<TxpoolContentCursor>,
+}
+
+impl<T> Default for TxpoolContentPage<T> {
+    fn default() -> Self {
+        Self { pending: BTreeMap::new(), queued: BTreeMap::new(), next_cursor: None }
+    }
+}
+
+impl<T> TxpoolContentPage<T> {
+    /// Returns the page of the content following the cursor, with at most `limit` transactions.
+    /// The limit defaults to [`TXPOOL_CONTENT_DEFAULT_PAGE_SIZE`] and is capped to
+    /// [`TXPOOL_CONTENT_MAX_PAGE_SIZE`].
+    pub fn new(content: TxpoolContent<T>, cursor: Option<TxpoolContentCursor>, limit: Option<u64>) -> Self {
+        let limit = limit.unwrap_or(TXPOOL_CONTENT_DEFAULT_PAGE_SIZE).clamp(1, TXPOOL_CONTENT_MAX_PAGE_SIZE);
+        let limit = usize::try_from(limit).unwrap_or(usize::MAX);
+
+        // The nonces are keyed by their decimal representation, which doesn't sort numerically
+        let mut transactions = [(true, content.pending), (false, content.queued)]
+            .into_iter()
+            .flat_map(|(pending, senders)| {
+                senders.into_iter().flat_map(move |(sender, transactions)| {
+                    transactions.into_iter().filter_map(move |(nonce, tx)| {
+                        let position = TxpoolContentCursor { sender, nonce: U64::from(nonce.parse::<u64>().ok()?) };
+                        Some((position, pending, tx))
+                    })
+                })
+            })
+            .filter(|(position, _, _)| cursor.map_or(true, |cursor| *position > cursor))
+            .collect::<Vec<_>>();
+        transactions.sort_unstable_by_key(|(position, _, _)| *position);
+
+        let has_next_page = transactions.len() > limit;
+        let mut page = Self::default();
+        for (position, pending, tx) in transactions.into_iter().take(limit) {
+            let sub_pool = if pending { &mut page.pending } else { &mut page.queued };
+            sub_pool.entry(position.sender).or_default().insert(position.nonce.to_string(), tx);
+            page.next_cursor = Some(position);
+        }
+        if !has_next_page {
+            page.next_cursor = None;
+        }
+        page
+    }
+
+    /// Returns the number of transactions of the page.
+    pub fn len(&self) -> usize {
+        self.pending.values().chain(self.queued.values()).map(BTreeMap::len).sum()
+    }
+
+    /// Returns true if the page has no transaction.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn content(pending: &[(u8, u64)], queued: &[(u8, u64)]) -> TxpoolContent<u64> {
+        let group = |transactions: &[(u8, u64)]| {
+            let mut senders = BTreeMap::<Address, BTreeMap<String, u64>>::new();
+            for (sender, nonce) in transactions {
+                senders.entry(Address::with_last_byte(*sender)).or_default().insert(nonce.to_string(), *nonce);
+            }
+            senders
+        };
+        TxpoolContent { pending: group(pending), queued: group(queued) }
+    }
+
+    fn cursor(sender: u8, nonce: u64) -> TxpoolContentCursor {
+        TxpoolContentCursor { sender: Address::with_last_byte(sender), nonce: U64::from(nonce) }
+    }
+
+    #[test]
+    fn test_txpool_content_page() {
+        // Given
+        let content = content(&[(1, 9), (1, 10), (2, 0)], &[(1, 12), (3, 4)]);
+
+        // When
+        let first = TxpoolContentPage::new(content.clone(), None, Some(2));
+        let second = TxpoolContentPage::new(content.clone(), first.next_cursor, Some(2));
+        let last = TxpoolContentPage::new(content, second.next_cursor, Some(2));
+
+        // Then
+        assert_eq!(first.len(), 2);
+        assert_eq!(first.pending[&Address::with_last_byte(1)].keys().collect::<Vec<_>>(), ["10", "9"]);
+        assert_eq!(first.next_cursor, Some(cursor(1, 10)));
+
+        assert_eq!(second.queued[&Address::with_last_byte(1)]["12"], 12);
+        assert_eq!(second.pending[&Address::with_last_byte(2)]["0"], 0);
+        assert_eq!(second.next_cursor, Some(cursor(2, 0)));
+
+        assert_eq!(last.queued[&Address::with_last_byte(3)]["4"], 4);
+        assert_eq!(last.len(), 1);
+        assert_eq!(last.next_cursor, None);
+    }
+
+    #[test]
+    fn test_txpool_content_page_stable_under_changes() {
+        // Given
+        let before = content(&[(1, 0), (2, 0)], &[(3, 0)]);
+        let first = TxpoolContentPage::new(before, None, Some(1));
+
+        // When
+        // The first transaction is mined, a transaction is added before the cursor and the queued
+        // transaction is promoted
+        let after = content(&[(0, 0), (2, 0), (3, 0)], &[]);
+        let second = TxpoolContentPage::new(after, first.next_cursor, Some(5));
+
+        // Then
+        assert_eq!(first.next_cursor, Some(cursor(1, 0)));
+        assert_eq!(second.len(), 2);
+        assert!(second.pending.contains_key(&Address::with_last_byte(2)));
+        assert!(second.pending.contains_key(&Address::with_last_byte(3)));
+        assert_eq!(second.next_cursor, None);
+    }
+}
@@ -1,28 +1,55 @@
 #![allow(clippy::significant_drop_tightening)]
 
-use super::validate::KakarotTransactionValidator;
+use super::{
+    build_stats::BLOCK_BUILD_STATS,
+    relayers::{RelayerRegistry, TrafficClass, TrafficClassifier},
+    validate::KakarotTransactionValidator,
+};
 use crate::{
     client::EthClient,
-    constants::{KAKAROT_RPC_CONFIG, KKRT_BLOCK_GAS_LIMIT},
+    constants::{BLOCK_GAS_LIMIT_REFRESH_INTERVAL, KAKAROT_RPC_CONFIG, PENDING_BLOCK_POLL_INTERVAL},
+    coordination::Leadership,
     into_via_try_wrapper,
-    pool::constants::ONE_TENTH_ETH,
-    providers::eth_provider::{database::state::EthDatabase, starknet::relayer::Relayer, BlockProvider},
+    pool::{
+        constants::{ONE_TENTH_ETH, POOL_MAX_SIZE_BYTES, POOL_SIZE_WARNING_RATIO},
+        ordering::PriorityLaneOrdering,
+        prune::PruneSchedule,
+    },
+    providers::eth_provider::{
+        database::{
+            relayer::{RelayerNonceStore, NONCE_LEASE_TIMEOUT},
+            state::EthDatabase,
+            types::transaction::{DropReason, DroppedTransaction, PoolEvent, PoolEventKind, StoredTransaction},
+        },
+        provider::EthApiResult,
+        starknet::{kakarot_core::KAKAROT_ADDRESS, relayer::Relayer},
+        BlockProvider, GasProvider,
+    },
 };
 use alloy_eips::BlockNumberOrTag;
-use alloy_primitives::{Address, U256};
+use alloy_primitives::{Address, B256, U256};
+use futures::{StreamExt, TryStreamExt};
+use mongodb::{
+    bson::{doc, Document},
+    options::FullDocumentType,
+};
 use rand::{seq::SliceRandom, SeedableRng};
 use reth_chainspec::ChainSpec;
 use reth_execution_types::ChangedAccount;
 use reth_revm::DatabaseRef;
 use reth_transaction_pool::{
-    blobstore::NoopBlobStore, BlockInfo, CanonicalStateUpdate, CoinbaseTipOrdering, EthPooledTransaction, Pool,
-    TransactionOrigin, TransactionPool, TransactionPoolExt,
+    blobstore::NoopBlobStore, BlockInfo, CanonicalStateUpdate, EthPooledTransaction, FullTransactionEvent, Pool,
+    PoolConfig, SubPoolLimit, TransactionOrigin, TransactionPool, TransactionPoolExt, ValidPoolTransaction,
 };
 use starknet::{
-    core::types::{BlockTag, Felt},
+    core::types::{BlockId, BlockTag, Felt},
     providers::{jsonrpc::HttpTransport, JsonRpcClient},
 };
-use std::{collections::HashMap, sync::Arc, time::Duration};
+use std::{
+    collections::{hash_map::Entry, HashMap, HashSet},
+    sync::Arc,
+    time::Duration,
+};
 use tokio::time::Instant;
 use tracing::instrument;
 
@@ -31,28 +58,75 @@ use tracing::instrument;
 pub type Validator<Client> = KakarotTransactionValidator<Client, EthPooledTransaction>;
 
 /// A type alias for the Kakarot Transaction Ordering.
-/// Uses the Reth implementation [`CoinbaseTipOrdering`].
-pub type TransactionOrdering = CoinbaseTipOrdering<EthPooledTransaction>;
+/// Uses the [`PriorityLaneOrdering`], which orders the transactions of the priority senders first.
+pub type TransactionOrdering = PriorityLaneOrdering;
 
 /// A type alias for the Kakarot Sequencer Mempool.
 pub type KakarotPool<Client> = Pool<Validator<Client>, TransactionOrdering, NoopBlobStore>;
 
+/// Bounds each sub-pool of the pool configuration (pending, base fee and queued transactions) by
+/// the memory budget, in bytes, on top of its number of transactions. Above the budget, the
+/// transactions of lowest priority are evicted.
+pub fn with_memory_budget(config: PoolConfig, max_size: usize) -> PoolConfig {
+    let limit = |limit: SubPoolLimit| SubPoolLimit { max_size, ..limit };
+    PoolConfig {
+        pending_limit: limit(config.pending_limit),
+        basefee_limit: limit(config.basefee_limit),
+        queued_limit: limit(config.queued_limit),
+        ..config
+    }
+}
+
 /// Manages a collection of accounts addresses, interfacing with an Ethereum client.
 ///
 /// This struct provides functionality to initialize account data from a file, monitor account balances,
 /// and process transactions for accounts with sufficient balance.
 #[derive(Debug)]
 pub struct AccountManager<SP: starknet::providers::Provider + Send + Sync + Clone + 'static> {
-    /// A collection of account addresses.
-    accounts: Vec<Felt>,
+    /// The registry of the relayer accounts.
+    relayers: Arc<RelayerRegistry>,
+    /// Assigns the transactions to the relayers of their traffic class.
+    classifier: TrafficClassifier,
     /// The Ethereum client used to interact with the blockchain.
     eth_client: Arc<EthClient<SP>>,
+    /// If set, the transactions are not submitted to Starknet.
+    dry_run: bool,
 }
 
 impl<SP: starknet::providers::Provider + Send + Sync + Clone + 'static> AccountManager<SP> {
-    /// Initialize the account manager with a set of passed accounts.
-    pub const fn new(accounts: Vec<Felt>, eth_client: Arc<EthClient<SP>>) -> Self {
-        Self { accounts, eth_client }
+    /// Initialize the account manager with a set of passed accounts, serving the users.
+    pub fn new(accounts: Vec<Felt>, eth_client: Arc<EthClient<SP>>) -> Self {
+        Self {
+            relayers: Arc::new(RelayerRegistry::new(accounts)),
+            classifier: TrafficClassifier::default(),
+            eth_client,
+            dry_run: false,
+        }
+    }
+
+    /// Adds the accounts serving the traffic class. The transactions of a class without
+    /// accounts are relayed by the accounts serving the users.
+    #[must_use]
+    pub fn with_relayers(self, traffic_class: TrafficClass, accounts: Vec<Felt>) -> Self {
+        for account in accounts {
+            self.relayers.register_for(account, traffic_class);
+        }
+        self
+    }
+
+    /// Sets the dry run mode of the manager. In dry run mode, the transactions are picked from
+    /// the pool and prepared by the relayers as usual, but are logged instead of being submitted
+    /// to Starknet.
+    #[must_use]
+    pub const fn with_dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    /// Returns the registry of the relayer accounts, which can be used to register new
+    /// relayers while the manager is running.
+    pub fn relayers(&self) -> Arc<RelayerRegistry> {
+        Arc::clone(&self.relayers)
     }
 
     /// Starts the account manager task that periodically checks account balances and processes transactions.
@@ -61,59 +135,51 @@ impl<SP: starknet::providers::Provider + Send + Sync + Clone + 'static> AccountM
         let this = Arc::new(self);
 
         tokio::spawn(async move {
+            let ordering = TransactionOrdering::default();
             loop {
                 // TODO: add a listener on the pool and only try to call [`best_transaction`]
                 // TODO: when we are sure there is a transaction in the pool. This avoids an
                 // TODO: constant loop which rarely yields to the executor combined with a
                 // TODO: sleep which could sleep for a while before handling transactions.
-                let best_hashes =
-                    this.eth_client.mempool().as_ref().best_transactions().map(|x| *x.hash()).collect::<Vec<_>>();
-                if let Some(best_hash) = best_hashes.first() {
-                    let transaction = this.eth_client.mempool().get(best_hash);
-                    if transaction.is_none() {
-                        // Probably a race condition here
+                let best_transactions = this.eth_client.mempool().as_ref().best_transactions().collect::<Vec<_>>();
+
+                // The transactions are relayed to the pending Starknet block, whose gas is limited
+                let block_info = this.eth_client.mempool().block_info();
+                let block_number = match this.eth_client.starknet_provider().block_number().await {
+                    Ok(latest) => latest + 1,
+                    Err(_) => block_info.last_seen_block_number + 1,
+                };
+                let block_gas_limit = this.eth_client.block_gas_limit();
+
+                // The transactions of the priority lane are ordered first by the pool and are all
+                // relayed in this iteration, at most one per sender in order to keep the nonces
+                // ordered. The other transactions are relayed one at a time. Each traffic class
+                // with its own relayers is drained independently.
+                let lane_of = |sender: &Address| this.relayers.relaying_class(this.classifier.class_of(sender));
+                for transaction in ordering.drain_lanes(best_transactions, lane_of) {
+                    // The transactions which don't fit in the remaining gas of the block are kept
+                    // in the pool for the next block
+                    let gas_limit = transaction.gas_limit();
+                    if gas_limit > BLOCK_BUILD_STATS.remaining_gas(block_number, block_gas_limit) {
+                        BLOCK_BUILD_STATS.record_skipped(block_number);
                         continue;
                     }
-                    let transaction = transaction.expect("not None");
 
                     // We remove the transaction to avoid another relayer from picking it up.
-                    this.eth_client.mempool().as_ref().remove_transactions(vec![*best_hash]);
-
-                    // Spawn a task for the transaction to be sent
-                    let manager = this.clone();
-                    tokio::spawn(async move {
-                        // Lock the relayer account
-                        let hash = transaction.hash();
-                        let maybe_relayer = manager.get_relayer().await;
-                        if maybe_relayer.is_err() {
-                            // If we fail to fetch a relayer, we need to re-insert the transaction in the pool
-                            tracing::error!(target: "account_manager", err = ?maybe_relayer.unwrap_err(), ?hash, "failed to fetch relayer");
-                            let _ = manager
-                                .eth_client
-                                .mempool()
-                                .add_transaction(TransactionOrigin::Local, transaction.transaction.clone())
-                                .await;
-                            return;
-                        }
-                        let relayer = maybe_relayer.expect("not error");
-
-                        // Send the Ethereum transaction using the relayer
-                        let transaction_signed = transaction.to_recovered_transaction().into_signed();
-
-                        let res = relayer.relay_transaction(&transaction_signed).await;
-                        if res.is_err() {
-                            // If the relayer failed to relay the transaction, we need to reposition it in the mempool
-                            tracing::error!(target: "account_manager", err = ?res.unwrap_err(), ?hash, "failed to relay transaction");
-                            let _ = manager
-                                .eth_client
-                                .mempool()
-                                .add_transaction(TransactionOrigin::Local, transaction.transaction.clone())
-                                .await;
-                            return;
-                        }
+                    if this.eth_client.mempool().as_ref().remove_transactions(vec![*transaction.hash()]).is_empty() {
+                        // Probably a race condition here
+                        continue;
+                    }
 
-                        tracing::info!(target: "account_manager", starknet_hash = ?res.expect("not error"), ethereum_hash = ?transaction_signed.hash());
-                    });
+                    let base_fee = block_info.pending_basefee;
+                    let gas_price = transaction
+                        .transaction
+                        .effective_tip_per_gas(base_fee)
+                        .map_or(0, |tip| tip.saturating_add(u128::from(base_fee)));
+                    let fees = U256::from(gas_limit) * U256::from(gas_price);
+                    BLOCK_BUILD_STATS.record_pulled(block_number, gas_limit, fees);
+
+                    this.relay(transaction, block_number);
                 }
 
                 tokio::time::sleep(Duration::from_secs(1)).await;
@@ -121,21 +187,112 @@ impl<SP: starknet::providers::Provider + Send + Sync + Clone + 'static> AccountM
         });
     }
 
-    /// Returns the next available account from the manager.
-    pub async fn get_relayer(&self) -> eyre::Result<Relayer<JsonRpcClient<HttpTransport>>>
+    /// Spawns a task relaying the transaction of the block to Starknet. If the transaction can't be
+    /// relayed, it is re-inserted in the pool.
+    fn relay(self: &Arc<Self>, transaction: Arc<ValidPoolTransaction<EthPooledTransaction>>, block_number: u64) {
+        let manager = Arc::clone(self);
+        tokio::spawn(async move {
+            // Lock a relayer account of the traffic class of the sender
+            let hash = transaction.hash();
+            let traffic_class = manager.classifier.class_of(&transaction.sender());
+            let relayer = match manager.get_relayer(traffic_class).await {
+                Ok(relayer) => relayer,
+                Err(err) => {
+                    // If we fail to fetch a relayer, we need to re-insert the transaction in the pool
+                    tracing::error!(target: "account_manager", ?err, ?hash, ?traffic_class, "failed to fetch relayer");
+                    manager.reinsert_transaction(&transaction, format!("failed to fetch relayer: {err}")).await;
+                    return;
+                }
+            };
+            let relayer_address = relayer.address();
+
+            // Send the Ethereum transaction using the relayer
+            let transaction_signed = transaction.to_recovered_transaction().into_signed();
+
+            manager.relayers.start_submission(relayer_address);
+            tracing::info!(
+                target: "account_manager",
+                counter.relayer_queue_depth = 1,
+                relayer = ?relayer_address,
+                ?traffic_class
+            );
+            let start = Instant::now();
+
+            let res = relayer.relay_transaction(&transaction_signed).await;
+
+            let latency = start.elapsed();
+            manager.relayers.end_submission(relayer_address, latency, res.is_ok());
+            BLOCK_BUILD_STATS.record_submission(block_number, relayer_address, res.is_ok());
+            tracing::info!(
+                target: "account_manager",
+                counter.relayer_queue_depth = -1,
+                histogram.relayer_submission_latency_ms = latency.as_millis() as u64,
+                relayer = ?relayer_address,
+                ?traffic_class,
+                success = res.is_ok()
+            );
+
+            match res {
+                Ok(starknet_hash) => {
+                    tracing::info!(target: "account_manager", ?starknet_hash, ethereum_hash = ?transaction_signed.hash());
+                    #[cfg(feature = "chaos")]
+                    if crate::chaos::FAULT_INJECTOR.duplicate_submission() {
+                        let duplicate = relayer.relay_transaction(&transaction_signed).await;
+                        tracing::warn!(target: "account_manager", ?hash, ?duplicate, "submitted duplicate transaction");
+                    }
+                    if !manager.dry_run {
+                        let kind = PoolEventKind::Relayed { relayer: relayer_address, starknet_hash };
+                        manager.eth_client.record_pool_event(PoolEvent::new(*hash, kind)).await;
+                        manager.eth_client.eth_provider().pending_receipts().insert_relayed(
+                            starknet_hash,
+                            transaction_signed,
+                            transaction.sender(),
+                        );
+                    }
+                }
+                Err(err) => {
+                    // If the relayer failed to relay the transaction, we need to reposition it in the mempool
+                    tracing::error!(target: "account_manager", ?err, ?hash, "failed to relay transaction");
+                    manager.reinsert_transaction(&transaction, format!("failed to relay transaction: {err}")).await;
+                }
+            }
+        });
+    }
+
+    /// Re-inserts a transaction which couldn't be relayed in the pool. If the pool rejects it,
+    /// the transaction is dropped and the reason is stored.
+    async fn reinsert_transaction(
+        &self,
+        transaction: &ValidPoolTransaction<EthPooledTransaction>,
+        relay_error: String,
+    ) {
+        let result =
+            self.eth_client.mempool().add_transaction(TransactionOrigin::Local, transaction.transaction.clone()).await;
+        if let Err(err) = result {
+            let message = format!("{relay_error}, failed to re-insert the transaction in the pool: {err}");
+            self.eth_client
+                .record_dropped_transaction(DroppedTransaction::new(
+                    *transaction.hash(),
+                    DropReason::RelayFailed,
+                    message,
+                ))
+                .await;
+        }
+    }
+
+    /// Returns the next available account serving the traffic class from the manager.
+    pub async fn get_relayer(&self, traffic_class: TrafficClass) -> eyre::Result<Relayer<JsonRpcClient<HttpTransport>>>
     where
         SP: starknet::providers::Provider + Send + Sync + Clone + 'static,
     {
         // Use `StdRng` instead of `ThreadRng` as it is `Send`
         let mut rng = rand::rngs::StdRng::from_entropy();
 
-        // Shuffle indices of accounts randomly
-        let mut account_indices: Vec<_> = (0..self.accounts.len()).collect();
-        account_indices.shuffle(&mut rng);
-
-        for index in account_indices {
-            let account_address = self.accounts[index];
+        // Shuffle the accounts randomly
+        let mut accounts = self.relayers.addresses_for(traffic_class);
+        accounts.shuffle(&mut rng);
 
+        for account_address in accounts {
             // Retrieve the balance of the selected account
             let balance = self.get_balance(account_address).await?;
 
@@ -153,7 +310,8 @@ impl<SP: starknet::providers::Provider + Send + Sync + Clone + 'static> AccountM
                 balance,
                 JsonRpcClient::new(HttpTransport::new(KAKAROT_RPC_CONFIG.network_url.clone())),
                 Some(Arc::new(self.eth_client.eth_provider().database().clone())),
-            );
+            )
+            .with_dry_run(self.dry_run);
 
             // Return the locked relayer instance
             return Ok(account);
@@ -211,124 +369,459 @@ where
 
 /// Maintains the transaction pool by periodically polling the database in order to
 /// fetch the latest block and mark the block's transactions as mined by the node.
-pub fn maintain_transaction_pool<SP>(eth_client: Arc<EthClient<SP>>, prune_duration: Duration)
-where
+pub fn maintain_transaction_pool<SP>(
+    eth_client: Arc<EthClient<SP>>,
+    prune_schedule: PruneSchedule,
+    leadership: Leadership,
+) where
     SP: starknet::providers::Provider + Send + Sync + Clone + 'static,
 {
     tokio::spawn(async move {
-        let mut block_number = 0u64;
+        let mut maintainer = PoolMaintainer::new(eth_client, prune_schedule).with_leadership(leadership);
+        loop {
+            maintainer.maintain().await;
+            tokio::time::sleep(Duration::from_secs(1)).await;
+        }
+    });
+}
 
-        // Mapping to store the transactions in the mempool with a timestamp to potentially prune them
-        let mut mempool_transactions = HashMap::new();
+/// Evicts the transactions from the pool and from the pending receipts as soon as the indexer
+/// stores them, by watching the transactions collection. This shrinks the window during which
+/// the pool serves already mined transactions, until the next maintenance iteration.
+///
+/// Change streams require a replica set: if the stream can't be opened, the mined transactions
+/// are only pruned by [`maintain_transaction_pool`].
+pub fn watch_mined_transactions<SP>(eth_client: Arc<EthClient<SP>>)
+where
+    SP: starknet::providers::Provider + Send + Sync + Clone + 'static,
+{
+    tokio::spawn(async move {
+        let collection = eth_client.eth_provider().database().collection::<StoredTransaction>().clone_with_type();
+        let pipeline = [doc! { "$match": { "operationType": { "$in": ["insert", "replace", "update"] } } }];
 
+        let mut opened = false;
         loop {
-            // Adding the transactions to the mempool mapping with a timestamp
-            for tx in eth_client
-                .mempool()
-                .queued_transactions()
-                .into_iter()
-                .chain(eth_client.mempool().pending_transactions())
-            {
-                mempool_transactions.entry(*tx.hash()).or_insert_with(Instant::now);
+            let mut stream =
+                match collection.watch().pipeline(pipeline.clone()).full_document(FullDocumentType::UpdateLookup).await
+                {
+                    Ok(stream) => stream,
+                    Err(err) if !opened => {
+                        tracing::warn!(target: "watch_mined_transactions", ?err, "failed to watch the transactions");
+                        return;
+                    }
+                    Err(err) => {
+                        tracing::warn!(target: "watch_mined_transactions", ?err, "failed to reopen the stream");
+                        tokio::time::sleep(Duration::from_secs(5)).await;
+                        continue;
+                    }
+                };
+            opened = true;
+
+            loop {
+                match stream.try_next().await {
+                    Ok(Some(event)) => {
+                        let Some(document) = event.full_document.as_ref() else { continue };
+                        let Some(hash) = indexed_transaction_hash(document) else { continue };
+                        if eth_client.eth_provider().pending_receipts().forget(&hash) {
+                            if let Some(block_hash) = indexed_transaction_block_hash(document) {
+                                let event = PoolEvent::new(hash, PoolEventKind::Mined { block_hash });
+                                eth_client.record_pool_event(event).await;
+                            }
+                        }
+                        if !eth_client.mempool().remove_transactions(vec![hash]).is_empty() {
+                            tracing::info!(
+                                target: "watch_mined_transactions",
+                                monotonic_counter.mempool_mined_evictions = 1,
+                                ?hash,
+                                "evicted mined transaction"
+                            );
+                        }
+                    }
+                    Ok(None) => break,
+                    Err(err) => {
+                        tracing::warn!(target: "watch_mined_transactions", ?err, "transactions stream failed");
+                        break;
+                    }
+                }
             }
+            tokio::time::sleep(Duration::from_secs(1)).await;
+        }
+    });
+}
 
-            // Fetch the latest block number
-            let Ok(current_block_number) = eth_client.eth_provider().block_number().await else {
-                tracing::error!(target: "maintain_transaction_pool", "failed to fetch current block number");
-                tokio::time::sleep(Duration::from_secs(1)).await;
-                continue;
+/// Returns the hash of the transaction stored by the indexer.
+fn indexed_transaction_hash(document: &Document) -> Option<B256> {
+    document.get_document("tx").ok()?.get_str("hash").ok()?.parse().ok()
+}
+
+/// Returns the hash of the block of the transaction stored by the indexer.
+fn indexed_transaction_block_hash(document: &Document) -> Option<B256> {
+    document.get_document("tx").ok()?.get_str("blockHash").ok()?.parse().ok()
+}
+
+/// Records the events of the transactions in the pool (added, queued, promoted, replaced and
+/// removed), so that the timeline of a transaction can be reconstructed after the fact. The
+/// drops, relays and mining of the transactions are recorded where they happen.
+pub fn watch_pool_events<SP>(eth_client: Arc<EthClient<SP>>)
+where
+    SP: starknet::providers::Provider + Send + Sync + Clone + 'static,
+{
+    tokio::spawn(async move {
+        let mut events = eth_client.mempool().all_transactions_event_listener();
+        // The transactions queued in the pool, which are promoted when they become pending
+        let mut queued = HashSet::new();
+        while let Some(event) = events.next().await {
+            let (hash, kind) = match event {
+                FullTransactionEvent::Pending(hash) if queued.remove(&hash) => (hash, PoolEventKind::Promoted),
+                FullTransactionEvent::Pending(hash) => (hash, PoolEventKind::Added),
+                FullTransactionEvent::Queued(hash) => {
+                    queued.insert(hash);
+                    (hash, PoolEventKind::Queued)
+                }
+                FullTransactionEvent::Replaced { transaction, replaced_by } => {
+                    queued.remove(transaction.hash());
+                    (*transaction.hash(), PoolEventKind::Replaced { replaced_by })
+                }
+                FullTransactionEvent::Discarded(hash) | FullTransactionEvent::Invalid(hash) => {
+                    queued.remove(&hash);
+                    (hash, PoolEventKind::Removed)
+                }
+                FullTransactionEvent::Mined { tx_hash, block_hash } => {
+                    queued.remove(&tx_hash);
+                    (tx_hash, PoolEventKind::Mined { block_hash })
+                }
+                FullTransactionEvent::Propagated(_) => continue,
             };
+            eth_client.record_pool_event(PoolEvent::new(hash, kind)).await;
+        }
+    });
+}
 
-            if current_block_number.to::<u64>() > block_number {
-                // Fetch the block by number for the latest block
-                if let Ok(Some(latest_block)) =
-                    eth_client.eth_provider().block_by_number(BlockNumberOrTag::Latest, true).await
-                {
-                    let hash = latest_block.header.hash;
-
-                    // If we can convert the RPC block to a primitive block, we proceed
-                    if let Ok(latest_block) = TryInto::<reth_primitives::Block>::try_into(latest_block.inner) {
-                        let latest_header = latest_block.header.clone().seal(hash);
-
-                        // Update the block information in the pool
-                        let chain_spec = ChainSpec {
-                            chain: eth_client.eth_provider().chain_id.into(),
-                            max_gas_limit: KKRT_BLOCK_GAS_LIMIT,
-                            ..Default::default()
-                        };
-                        let info = BlockInfo {
-                            block_gas_limit: KKRT_BLOCK_GAS_LIMIT,
-                            last_seen_block_hash: hash,
-                            last_seen_block_number: latest_header.number,
-                            pending_basefee: latest_header
-                                .next_block_base_fee(
-                                    chain_spec.base_fee_params_at_timestamp(latest_header.timestamp + 12),
-                                )
-                                .unwrap_or_default(),
-                            pending_blob_fee: None,
-                        };
-                        eth_client.mempool().set_block_info(info);
-
-                        // Fetch unique senders from the mempool that are out of sync
-                        let dirty_addresses = eth_client.mempool().unique_senders();
-
-                        let mut changed_accounts = Vec::new();
-
-                        // if we have accounts that are out of sync with the pool, we reload them in chunks
-                        if !dirty_addresses.is_empty() {
-                            // can fetch all dirty accounts at once
-                            let reloaded = load_accounts(&eth_client.clone(), dirty_addresses);
-                            changed_accounts.extend(reloaded.accounts);
-                            // update the pool with the loaded accounts
-                            eth_client.mempool().update_accounts(changed_accounts.clone());
-                        }
+/// Polls the latest and pending Starknet blocks while transactions relayed by the node await
+/// their inclusion, in order to serve their receipts before the indexer stores them.
+pub fn poll_pending_block<SP>(eth_client: Arc<EthClient<SP>>)
+where
+    SP: starknet::providers::Provider + Send + Sync + Clone + 'static,
+{
+    tokio::spawn(async move {
+        loop {
+            tokio::time::sleep(*PENDING_BLOCK_POLL_INTERVAL).await;
 
-                        let sealed_block = latest_block.seal(hash);
-                        let mut mined_transactions: Vec<_> =
-                            sealed_block.body.transactions.iter().map(|tx| tx.hash).collect();
+            let pending_receipts = eth_client.eth_provider().pending_receipts();
+            pending_receipts.evict_expired();
+            if pending_receipts.is_empty() {
+                continue;
+            }
 
-                        // Prune mined transactions from the mempool mapping
-                        for tx_hash in &mined_transactions {
-                            mempool_transactions.remove(tx_hash);
-                        }
+            match apply_latest_blocks(&eth_client).await {
+                Ok(applied) if applied > 0 => tracing::info!(
+                    target: "pending_block",
+                    monotonic_counter.pending_block_receipts = applied as u64,
+                    "built receipts from the pending block"
+                ),
+                Ok(_) => {}
+                Err(err) => tracing::warn!(target: "pending_block", ?err, "failed to poll the pending block"),
+            }
+        }
+    });
+}
 
-                        // Prune transactions that have been in the mempool for more than 5 minutes
-                        let now = Instant::now();
+/// Builds the receipts of the relayed transactions included in the latest or in the pending
+/// Starknet block, and returns their number. The latest block is also checked so that a
+/// transaction isn't missed if its block was closed between two polls. The blocks are cached,
+/// the Starknet fallback serving the receipts from the same fetches.
+async fn apply_latest_blocks<SP>(eth_client: &EthClient<SP>) -> EthApiResult<usize>
+where
+    SP: starknet::providers::Provider + Send + Sync + Clone + 'static,
+{
+    let eth_provider = eth_client.eth_provider();
+    let latest = eth_provider.starknet_block(BlockId::Tag(BlockTag::Latest), None).await?;
+    let pending = eth_provider.starknet_block(BlockId::Tag(BlockTag::Pending), None).await?;
 
-                        for (tx_hash, timestamp) in mempool_transactions.clone() {
-                            // - If the transaction has been in the mempool for more than 5 minutes
-                            // - And the transaction is in the mempool right now
-                            if now.duration_since(timestamp) > prune_duration && eth_client.mempool().contains(&tx_hash)
-                            {
-                                tracing::warn!(target: "maintain_transaction_pool", ?tx_hash, "pruning");
+    Ok([latest, pending].iter().map(|block| eth_provider.pending_receipts().apply_block(block, *KAKAROT_ADDRESS)).sum())
+}
 
-                                // Add the transaction to the mined transactions so that it can be pruned
-                                mined_transactions.push(tx_hash);
+/// Performs the maintenance of the transaction pool, one iteration at a time.
+#[derive(Debug)]
+pub struct PoolMaintainer<SP: starknet::providers::Provider + Send + Sync + Clone + 'static> {
+    /// The Ethereum client used to interact with the blockchain.
+    eth_client: Arc<EthClient<SP>>,
+    /// The schedule of the duration after which a transaction is pruned from the mempool.
+    prune_schedule: PruneSchedule,
+    /// The current duration after which a transaction is pruned from the mempool.
+    prune_duration: Duration,
+    /// The last block number seen by the maintainer.
+    block_number: u64,
+    /// Mapping to store the transactions in the mempool with a timestamp to potentially prune them
+    mempool_transactions: HashMap<B256, Instant>,
+    /// The instant of the last maintenance iteration, used to compute the insertion rate.
+    last_maintenance: Option<Instant>,
+    /// The instant of the last refresh of the block gas limit from Kakarot core.
+    last_gas_limit_refresh: Option<Instant>,
+    /// Whether a sub-pool was close to its memory budget at the last maintenance iteration.
+    close_to_memory_budget: bool,
+    /// Whether the instance runs the background writers, the nonce leases being shared by the
+    /// instances and only released by the writer.
+    leadership: Leadership,
+}
 
-                                // Remove the transaction from the mempool mapping
-                                mempool_transactions.remove(&tx_hash);
-                            }
-                        }
+impl<SP> PoolMaintainer<SP>
+where
+    SP: starknet::providers::Provider + Send + Sync + Clone + 'static,
+{
+    /// Creates a new [`PoolMaintainer`].
+    pub fn new(eth_client: Arc<EthClient<SP>>, prune_schedule: PruneSchedule) -> Self {
+        Self {
+            eth_client,
+            prune_schedule,
+            prune_duration: prune_schedule.max_duration,
+            block_number: 0,
+            mempool_transactions: HashMap::new(),
+            last_maintenance: None,
+            last_gas_limit_refresh: None,
+            close_to_memory_budget: false,
+            leadership: Leadership::writer(),
+        }
+    }
 
-                        // Canonical update
-                        let update = CanonicalStateUpdate {
-                            new_tip: &sealed_block,
-                            pending_block_base_fee: info.pending_basefee,
-                            pending_block_blob_fee: None,
-                            changed_accounts,
-                            mined_transactions,
-                        };
-                        eth_client.mempool().on_canonical_state_change(update);
-
-                        block_number = current_block_number.to();
-                    } else {
-                        tracing::error!(target: "maintain_transaction_pool", "failed to convert block");
-                    }
-                } else {
-                    tracing::error!(target: "maintain_transaction_pool", "failed to fetch latest block");
+    /// Sets the leadership of the instance, the shared state (e.g. the nonce leases) only being
+    /// maintained by the instance running the writers.
+    #[must_use]
+    pub fn with_leadership(mut self, leadership: Leadership) -> Self {
+        self.leadership = leadership;
+        self
+    }
+
+    /// Returns the current duration after which a transaction is pruned from the mempool.
+    pub const fn prune_duration(&self) -> Duration {
+        self.prune_duration
+    }
+
+    /// Runs a single maintenance iteration: updates the block information of the pool if a new
+    /// block was produced, reloads the out of sync accounts and prunes the mined and expired
+    /// transactions.
+    pub async fn maintain(&mut self) {
+        let eth_client = &self.eth_client;
+
+        // Adding the transactions to the mempool mapping with a timestamp
+        let now = Instant::now();
+        let mut pool_size = 0usize;
+        let mut inserted = 0usize;
+        for tx in
+            eth_client.mempool().queued_transactions().into_iter().chain(eth_client.mempool().pending_transactions())
+        {
+            pool_size += 1;
+            if let Entry::Vacant(entry) = self.mempool_transactions.entry(*tx.hash()) {
+                entry.insert(now);
+                inserted += 1;
+            }
+        }
+
+        // Adapt the prune duration to the size of the pool and the insertion rate
+        let elapsed = self.last_maintenance.map_or(Duration::ZERO, |last| now.duration_since(last));
+        self.last_maintenance = Some(now);
+        let insertion_rate = if elapsed.is_zero() { 0. } else { inserted as f64 / elapsed.as_secs_f64() };
+        let prune_duration = self.prune_schedule.prune_duration(pool_size, insertion_rate);
+        if prune_duration != self.prune_duration {
+            tracing::info!(
+                target: "maintain_transaction_pool",
+                pool_size,
+                insertion_rate,
+                ?prune_duration,
+                "adapted prune duration"
+            );
+            self.prune_duration = prune_duration;
+        }
+
+        // Warn once before the transactions are evicted from a sub-pool over its memory budget,
+        // until the sub-pools shrink back below the warning threshold
+        let size = eth_client.mempool().pool_size();
+        let max_sub_pool_size = size.pending_size.max(size.basefee_size).max(size.queued_size);
+        let close_to_memory_budget = max_sub_pool_size as f64 >= *POOL_MAX_SIZE_BYTES as f64 * POOL_SIZE_WARNING_RATIO;
+        if close_to_memory_budget != self.close_to_memory_budget {
+            if close_to_memory_budget {
+                tracing::warn!(
+                    target: "maintain_transaction_pool",
+                    pending_size = size.pending_size,
+                    basefee_size = size.basefee_size,
+                    queued_size = size.queued_size,
+                    budget = *POOL_MAX_SIZE_BYTES,
+                    "pool close to its memory budget"
+                );
+            } else {
+                tracing::info!(target: "maintain_transaction_pool", max_sub_pool_size, "pool back below its memory budget");
+            }
+            self.close_to_memory_budget = close_to_memory_budget;
+        }
+
+        // Refresh the block gas limit, which can be updated by the owner of Kakarot core
+        if self
+            .last_gas_limit_refresh
+            .map_or(true, |last| now.duration_since(last) >= *BLOCK_GAS_LIMIT_REFRESH_INTERVAL)
+        {
+            self.last_gas_limit_refresh = Some(now);
+            match eth_client.eth_provider().block_gas_limit().await {
+                Ok(block_gas_limit) if block_gas_limit != eth_client.block_gas_limit() => {
+                    tracing::info!(target: "maintain_transaction_pool", block_gas_limit, "updated block gas limit");
+                    eth_client.set_block_gas_limit(block_gas_limit);
+                }
+                Ok(_) => {}
+                Err(err) => {
+                    tracing::warn!(target: "maintain_transaction_pool", ?err, "failed to fetch block gas limit");
                 }
             }
-            tokio::time::sleep(Duration::from_secs(1)).await;
         }
-    });
+
+        // Fetch the latest block number
+        let Ok(current_block_number) = eth_client.eth_provider().block_number().await else {
+            tracing::error!(target: "maintain_transaction_pool", "failed to fetch current block number");
+            return;
+        };
+
+        if current_block_number.to::<u64>() <= self.block_number {
+            return;
+        }
+
+        // Fetch the block by number for the latest block
+        let Ok(Some(latest_block)) = eth_client.eth_provider().block_by_number(BlockNumberOrTag::Latest, true).await
+        else {
+            tracing::error!(target: "maintain_transaction_pool", "failed to fetch latest block");
+            return;
+        };
+        let hash = latest_block.header.hash;
+
+        // If we can convert the RPC block to a primitive block, we proceed
+        let Ok(latest_block) = TryInto::<reth_primitives::Block>::try_into(latest_block.inner) else {
+            tracing::error!(target: "maintain_transaction_pool", "failed to convert block");
+            return;
+        };
+        let latest_header = latest_block.header.clone().seal(hash);
+
+        // Update the block information in the pool
+        let block_gas_limit = eth_client.block_gas_limit();
+        let chain_spec = ChainSpec {
+            chain: eth_client.eth_provider().chain_id.into(),
+            max_gas_limit: block_gas_limit,
+            ..Default::default()
+        };
+        let info = BlockInfo {
+            block_gas_limit,
+            last_seen_block_hash: hash,
+            last_seen_block_number: latest_header.number,
+            pending_basefee: latest_header
+                .next_block_base_fee(chain_spec.base_fee_params_at_timestamp(latest_header.timestamp + 12))
+                .unwrap_or_default(),
+            pending_blob_fee: None,
+        };
+        eth_client.mempool().set_block_info(info);
+
+        // Fetch unique senders from the mempool that are out of sync
+        let dirty_addresses = eth_client.mempool().unique_senders();
+
+        let mut changed_accounts = Vec::new();
+
+        // if we have accounts that are out of sync with the pool, we reload them in chunks
+        if !dirty_addresses.is_empty() {
+            // can fetch all dirty accounts at once
+            let reloaded = load_accounts(&eth_client.clone(), dirty_addresses);
+            changed_accounts.extend(reloaded.accounts);
+            // update the pool with the loaded accounts
+            eth_client.mempool().update_accounts(changed_accounts.clone());
+        }
+
+        let sealed_block = latest_block.seal(hash);
+        let mut mined_transactions: Vec<_> = sealed_block.body.transactions.iter().map(|tx| tx.hash).collect();
+
+        // Prune mined transactions from the mempool mapping
+        for tx_hash in &mined_transactions {
+            self.mempool_transactions.remove(tx_hash);
+        }
+
+        // Prune transactions that have been in the mempool for more than the prune duration
+        let now = Instant::now();
+        let mut pruned = 0u64;
+
+        for (tx_hash, timestamp) in self.mempool_transactions.clone() {
+            // - If the transaction has been in the mempool for more than the prune duration
+            // - And the transaction is in the mempool right now
+            if now.duration_since(timestamp) > self.prune_duration && eth_client.mempool().contains(&tx_hash) {
+                tracing::warn!(target: "maintain_transaction_pool", ?tx_hash, "pruning");
+                let message = format!("transaction not relayed after {:?}", self.prune_duration);
+                eth_client
+                    .record_dropped_transaction(DroppedTransaction::new(tx_hash, DropReason::Expired, message))
+                    .await;
+
+                // Add the transaction to the mined transactions so that it can be pruned
+                mined_transactions.push(tx_hash);
+
+                // Remove the transaction from the mempool mapping
+                self.mempool_transactions.remove(&tx_hash);
+                pruned += 1;
+            }
+        }
+
+        // Forget the expired transactions which left the pool without being mined (e.g. relayed but
+        // never included), so that the mapping doesn't grow forever
+        let tracked = self.mempool_transactions.len();
+        self.mempool_transactions.retain(|tx_hash, timestamp| {
+            now.duration_since(*timestamp) <= self.prune_duration || eth_client.mempool().contains(tx_hash)
+        });
+        let orphaned = tracked - self.mempool_transactions.len();
+
+        tracing::info!(
+            target: "maintain_transaction_pool",
+            monotonic_counter.mempool_pruned_transactions = pruned,
+            monotonic_counter.mempool_orphaned_transactions = orphaned as u64,
+            histogram.mempool_prune_duration_ms = self.prune_duration.as_millis() as u64,
+            "pruned expired transactions"
+        );
+
+        // Release the nonce leases abandoned by crashed relaying tasks, including the ones of the
+        // relayers which no longer relay transactions. The leases are shared by the instances, only
+        // the instance running the relayers releases them
+        if self.leadership.is_writer() {
+            match eth_client.eth_provider().database().release_expired_nonce_leases(*NONCE_LEASE_TIMEOUT).await {
+                Ok(released) => tracing::info!(
+                    target: "maintain_transaction_pool",
+                    monotonic_counter.expired_nonce_leases = released,
+                    "released expired nonce leases"
+                ),
+                Err(err) => {
+                    tracing::warn!(target: "maintain_transaction_pool", ?err, "failed to release nonce leases");
+                }
+            }
+        }
+
+        // Canonical update
+        let update = CanonicalStateUpdate {
+            new_tip: &sealed_block,
+            pending_block_base_fee: info.pending_basefee,
+            pending_block_blob_fee: None,
+            changed_accounts,
+            mined_transactions,
+        };
+        eth_client.mempool().on_canonical_state_change(update);
+
+        self.block_number = current_block_number.to();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_indexed_transaction_hash() {
+        // Given
+        let hash = B256::repeat_byte(0xab);
+        let document = doc! { "tx": { "hash": hash.to_string(), "blockNumber": "0x1" } };
+
+        // When
+        let indexed = indexed_transaction_hash(&document);
+
+        // Then
+        assert_eq!(indexed, Some(hash));
+        assert_eq!(indexed_transaction_hash(&doc! { "tx": { "hash": 1 } }), None);
+        assert_eq!(indexed_transaction_hash(&doc! {}), None);
+    }
 }
@@ -1,3 +1,16 @@
+pub mod build_stats;
 pub mod constants;
+pub mod content;
+#[cfg(feature = "mongo")]
 pub mod mempool;
+pub mod nonce_gaps;
+pub mod ordering;
+#[cfg(feature = "mongo")]
+pub mod probe;
+pub mod prune;
+#[cfg(feature = "mongo")]
+pub mod reconcile;
+pub mod relayers;
+pub mod snapshot;
+#[cfg(feature = "mongo")]
 pub mod validate;
@@ -0,0 +1,87 @@
+use crate::eth_rpc::openrpc::SchemaOf;
+use alloy_primitives::Address;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+
+/// A range of consecutive nonces missing from the mempool, inclusive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct NonceGap {
+    /// The first missing nonce.
+    pub from: u64,
+    /// The last missing nonce.
+    pub to: u64,
+}
+
+/// The nonces of the transactions of a sender in the mempool, compared to its on-chain nonce.
+/// Transactions can only be relayed in nonce order, so that a transaction queued after a gap is
+/// stuck until the missing nonces are submitted.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct NonceGaps {
+    /// The address of the sender.
+    #[schemars(with = "SchemaOf<Address>")]
+    pub address: Address,
+    /// The nonce of the sender in the pending block.
+    pub onchain_nonce: u64,
+    /// The highest nonce of the transactions of the sender in the mempool, if any.
+    pub highest_pool_nonce: Option<u64>,
+    /// The nonces missing between the on-chain nonce and the highest nonce of the mempool.
+    pub gaps: Vec<NonceGap>,
+    /// The nonce blocking the transactions of the sender, i.e. the first missing nonce, if any.
+    pub blocking_nonce: Option<u64>,
+}
+
+impl NonceGaps {
+    /// Returns the gaps between the on-chain nonce of the sender and the nonces of its
+    /// transactions in the mempool. The nonces below the on-chain nonce are ignored.
+    pub fn new(address: Address, onchain_nonce: u64, pool_nonces: impl IntoIterator<Item = u64>) -> Self {
+        let pool_nonces: BTreeSet<_> = pool_nonces.into_iter().filter(|nonce| *nonce >= onchain_nonce).collect();
+
+        let mut gaps = Vec::new();
+        let mut next = onchain_nonce;
+        for nonce in &pool_nonces {
+            if *nonce > next {
+                gaps.push(NonceGap { from: next, to: nonce - 1 });
+            }
+            next = nonce + 1;
+        }
+
+        Self {
+            address,
+            onchain_nonce,
+            highest_pool_nonce: pool_nonces.last().copied(),
+            blocking_nonce: gaps.first().map(|gap| gap.from),
+            gaps,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_nonce_gaps() {
+        // When
+        let gaps = NonceGaps::new(Address::ZERO, 5, [3, 6, 7, 9, 12]);
+        let contiguous = NonceGaps::new(Address::ZERO, 5, [5, 6, 7]);
+        let empty = NonceGaps::new(Address::ZERO, 5, []);
+
+        // Then
+        assert_eq!(gaps.highest_pool_nonce, Some(12));
+        assert_eq!(
+            gaps.gaps,
+            vec![NonceGap { from: 5, to: 5 }, NonceGap { from: 8, to: 8 }, NonceGap { from: 10, to: 11 }]
+        );
+        assert_eq!(gaps.blocking_nonce, Some(5));
+
+        assert_eq!(contiguous.highest_pool_nonce, Some(7));
+        assert!(contiguous.gaps.is_empty());
+        assert_eq!(contiguous.blocking_nonce, None);
+
+        assert_eq!(empty.highest_pool_nonce, None);
+        assert!(empty.gaps.is_empty());
+    }
+}
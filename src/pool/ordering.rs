@@ -0,0 +1,393 @@
+use super::{constants::PRIORITY_SENDERS, relayers::TrafficClass};
+use crate::eth_rpc::openrpc::SchemaOf;
+use alloy_primitives::{Address, B256, U256};
+use reth_transaction_pool::{
+    EthPooledTransaction, PoolTransaction, Priority, TransactionOrdering, ValidPoolTransaction,
+};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use starknet::core::types::Felt;
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
+
+/// The priority of a transaction in the pool.
+///
+/// Transactions of the priority lane are always ordered before the other transactions,
+/// then transactions are ordered by their coinbase tip.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct LanePriority {
+    /// Whether the transaction was sent by a priority sender.
+    pub priority_lane: bool,
+    /// The effective tip per gas of the transaction.
+    pub tip: U256,
+}
+
+/// Orders the transactions of the priority senders (e.g. keeper bots or oracle updaters) first,
+/// then the remaining transactions by their coinbase tip, like Reth's `CoinbaseTipOrdering`.
+#[derive(Debug, Clone)]
+pub struct PriorityLaneOrdering {
+    /// The senders whose transactions bypass the ordering by tip.
+    priority_senders: HashSet<Address>,
+}
+
+impl PriorityLaneOrdering {
+    /// Creates a new ordering with the given priority senders.
+    pub fn new(priority_senders: impl IntoIterator<Item = Address>) -> Self {
+        Self { priority_senders: priority_senders.into_iter().collect() }
+    }
+
+    /// Returns true if the address is a priority sender.
+    pub fn is_priority_sender(&self, address: &Address) -> bool {
+        self.priority_senders.contains(address)
+    }
+
+    /// Returns the transactions relayed in a single iteration of the relay loop, out of the best
+    /// transactions of the pool: the transactions of the priority lane, at most one per sender in
+    /// order to keep the nonces ordered, followed by a single transaction of the other senders.
+    pub fn drain_round(
+        &self,
+        best_transactions: impl IntoIterator<Item = Arc<ValidPoolTransaction<EthPooledTransaction>>>,
+    ) -> Vec<Arc<ValidPoolTransaction<EthPooledTransaction>>> {
+        let mut senders = HashSet::new();
+        let mut drained = Vec::new();
+        for transaction in best_transactions {
+            let sender = transaction.sender();
+            if !senders.insert(sender) {
+                continue;
+            }
+            drained.push(transaction);
+
+            if !self.is_priority_sender(&sender) {
+                break;
+            }
+        }
+        drained
+    }
+
+    /// Returns the transactions relayed in a single iteration of the relay loop, draining a round
+    /// of each lane independently. The lane of a transaction is the traffic class whose relayers
+    /// relay it, so that a flood of transactions of a class (e.g. the faucet) doesn't delay the
+    /// transactions relayed by the other relayers.
+    pub fn drain_lanes(
+        &self,
+        best_transactions: impl IntoIterator<Item = Arc<ValidPoolTransaction<EthPooledTransaction>>>,
+        lane_of: impl Fn(&Address) -> TrafficClass,
+    ) -> Vec<Arc<ValidPoolTransaction<EthPooledTransaction>>> {
+        let mut lanes: Vec<(TrafficClass, Vec<_>)> = Vec::new();
+        for transaction in best_transactions {
+            let lane = lane_of(&transaction.sender());
+            match lanes.iter_mut().find(|(class, _)| *class == lane) {
+                Some((_, transactions)) => transactions.push(transaction),
+                None => lanes.push((lane, vec![transaction])),
+            }
+        }
+
+        lanes.into_iter().flat_map(|(_, transactions)| self.drain_round(transactions)).collect()
+    }
+
+    /// Returns the order in which the best transactions of the pool would be drained by the
+    /// relay loop, assuming no new transaction enters the pool, ordered by round and then by
+    /// position in the best transactions.
+    ///
+    /// The rounds of [`Self::drain_lanes`] are computed in a single pass: in each lane, a round
+    /// drains the next transaction of the other senders, along with the transactions of the
+    /// priority lane which precede it, at most one per sender.
+    pub fn drain_order(
+        &self,
+        best_transactions: impl IntoIterator<Item = Arc<ValidPoolTransaction<EthPooledTransaction>>>,
+        base_fee: u64,
+        lane_of: impl Fn(&Address) -> TrafficClass,
+    ) -> Vec<DrainedTransaction> {
+        // The number of transactions of the other senders already met in each lane, and the
+        // round of the last transaction of each sender
+        let mut regular_counts: HashMap<TrafficClass, u64> = HashMap::new();
+        let mut sender_rounds: HashMap<Address, u64> = HashMap::new();
+        let mut rounds: Vec<Vec<DrainedTransaction>> = Vec::new();
+
+        for transaction in best_transactions {
+            let sender = transaction.sender();
+            let priority_lane = self.is_priority_sender(&sender);
+            let regular_count = regular_counts.entry(lane_of(&sender)).or_default();
+
+            // The k-th transaction of the other senders of a lane is drained in round k, a
+            // transaction of the priority lane once the transactions of the other senders which
+            // precede it are drained, and after the previous transaction of its sender
+            let round = if priority_lane {
+                let after_sender = sender_rounds.get(&sender).map_or(0, |round| round + 1);
+                (*regular_count).max(after_sender)
+            } else {
+                *regular_count += 1;
+                *regular_count - 1
+            };
+            sender_rounds.insert(sender, round);
+
+            let index = usize::try_from(round).unwrap_or(usize::MAX);
+            if rounds.len() <= index {
+                rounds.resize_with(index + 1, Vec::new);
+            }
+            rounds[index].push(DrainedTransaction {
+                hash: *transaction.hash(),
+                sender,
+                nonce: transaction.nonce(),
+                priority_lane,
+                effective_tip: transaction.transaction.effective_tip_per_gas(base_fee).map(U256::from),
+                round,
+            });
+        }
+
+        rounds.into_iter().flatten().collect()
+    }
+}
+
+impl Default for PriorityLaneOrdering {
+    /// Creates an ordering with the priority senders read from the environment.
+    fn default() -> Self {
+        Self::new(PRIORITY_SENDERS.iter().copied())
+    }
+}
+
+/// A pending transaction of the pool, with the rationale of its position in the drain order.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct DrainedTransaction {
+    /// The hash of the transaction.
+    #[schemars(with = "SchemaOf<B256>")]
+    pub hash: B256,
+    /// The sender of the transaction.
+    #[schemars(with = "SchemaOf<Address>")]
+    pub sender: Address,
+    /// The nonce of the transaction.
+    pub nonce: u64,
+    /// Whether the transaction was sent by a priority sender.
+    pub priority_lane: bool,
+    /// The effective tip per gas of the transaction at the pending base fee.
+    #[schemars(with = "SchemaOf<Option<U256>>")]
+    pub effective_tip: Option<U256>,
+    /// The iteration of the relay loop in which the transaction would be relayed, starting at 0.
+    /// The relay loop runs once per second.
+    pub round: u64,
+}
+
+/// The current drain order of the pending sub-pool.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct PoolDrainOrder {
+    /// The pending base fee used to compute the effective tips.
+    pub base_fee: u64,
+    /// The funded relayers. Each transaction is assigned to one of these relayers, picked at
+    /// random when the transaction is drained. An empty list means no transaction can be relayed.
+    #[schemars(with = "SchemaOf<Vec<Felt>>")]
+    pub relayers: Vec<Felt>,
+    /// The pending transactions, in drain order.
+    pub transactions: Vec<DrainedTransaction>,
+}
+
+impl TransactionOrdering for PriorityLaneOrdering {
+    type PriorityValue = LanePriority;
+    type Transaction = EthPooledTransaction;
+
+    fn priority(&self, transaction: &Self::Transaction, base_fee: u64) -> Priority<Self::PriorityValue> {
+        transaction
+            .effective_tip_per_gas(base_fee)
+            .map(|tip| LanePriority {
+                priority_lane: self.is_priority_sender(&transaction.sender()),
+                tip: U256::from(tip),
+            })
+            .into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_consensus::TxEip1559;
+    use alloy_eips::eip2718::Encodable2718;
+    use alloy_primitives::{Signature, TxKind};
+    use reth_primitives::{Transaction, TransactionSigned, TransactionSignedEcRecovered};
+    use reth_transaction_pool::{
+        identifier::{SenderId, TransactionId},
+        TransactionOrigin,
+    };
+    use std::time::Instant;
+
+    fn pooled_transaction(sender: Address, max_priority_fee_per_gas: u128) -> EthPooledTransaction {
+        pooled_transaction_with_nonce(sender, 0, max_priority_fee_per_gas)
+    }
+
+    fn pooled_transaction_with_nonce(
+        sender: Address,
+        nonce: u64,
+        max_priority_fee_per_gas: u128,
+    ) -> EthPooledTransaction {
+        let transaction = Transaction::Eip1559(TxEip1559 {
+            nonce,
+            gas_limit: 21000,
+            to: TxKind::Call(Address::ZERO),
+            max_fee_per_gas: 10 + max_priority_fee_per_gas,
+            max_priority_fee_per_gas,
+            ..Default::default()
+        });
+        let signature = Signature::from_rs_and_parity(U256::from(1), U256::from(1), false).expect("valid signature");
+        let transaction = TransactionSignedEcRecovered::from_signed_transaction(
+            TransactionSigned::from_transaction_and_signature(transaction, signature),
+            sender,
+        );
+        let encoded_length = transaction.encode_2718_len();
+        EthPooledTransaction::new(transaction, encoded_length)
+    }
+
+    fn valid_transaction(
+        sender: Address,
+        nonce: u64,
+        max_priority_fee_per_gas: u128,
+    ) -> Arc<ValidPoolTransaction<EthPooledTransaction>> {
+        Arc::new(ValidPoolTransaction {
+            transaction: pooled_transaction_with_nonce(sender, nonce, max_priority_fee_per_gas),
+            transaction_id: TransactionId::new(SenderId::from(u64::from(sender.0[19])), nonce),
+            propagate: true,
+            timestamp: Instant::now(),
+            origin: TransactionOrigin::External,
+        })
+    }
+
+    #[test]
+    fn test_priority_sender_ordered_before_higher_tip() {
+        // Given
+        let priority_sender = Address::left_padding_from(&[1]);
+        let ordering = PriorityLaneOrdering::new([priority_sender]);
+        let priority_transaction = pooled_transaction(priority_sender, 1);
+        let regular_transaction = pooled_transaction(Address::left_padding_from(&[2]), 100);
+
+        // When
+        let priority = ordering.priority(&priority_transaction, 10);
+        let regular = ordering.priority(&regular_transaction, 10);
+
+        // Then
+        assert_eq!(priority, Priority::Value(LanePriority { priority_lane: true, tip: U256::from(1) }));
+        assert_eq!(regular, Priority::Value(LanePriority { priority_lane: false, tip: U256::from(100) }));
+        assert!(priority > regular);
+    }
+
+    #[test]
+    fn test_regular_senders_ordered_by_tip() {
+        // Given
+        let ordering = PriorityLaneOrdering::new([]);
+        let low_tip = pooled_transaction(Address::left_padding_from(&[1]), 1);
+        let high_tip = pooled_transaction(Address::left_padding_from(&[2]), 2);
+
+        // When
+        let low = ordering.priority(&low_tip, 10);
+        let high = ordering.priority(&high_tip, 10);
+
+        // Then
+        assert!(high > low);
+    }
+
+    #[test]
+    fn test_drain_order() {
+        // Given
+        let priority_sender = Address::left_padding_from(&[1]);
+        let ordering = PriorityLaneOrdering::new([priority_sender]);
+        let best_transactions = vec![
+            valid_transaction(priority_sender, 0, 1),
+            valid_transaction(priority_sender, 1, 1),
+            valid_transaction(Address::left_padding_from(&[2]), 0, 100),
+            valid_transaction(Address::left_padding_from(&[3]), 0, 50),
+        ];
+
+        // When
+        let order = ordering.drain_order(best_transactions.clone(), 10, |_| TrafficClass::User);
+
+        // Then
+        let hashes: Vec<_> = order.iter().map(|transaction| transaction.hash).collect();
+        let rounds: Vec<_> = order.iter().map(|transaction| transaction.round).collect();
+        assert_eq!(
+            hashes,
+            vec![
+                *best_transactions[0].hash(),
+                *best_transactions[2].hash(),
+                *best_transactions[1].hash(),
+                *best_transactions[3].hash()
+            ]
+        );
+        assert_eq!(rounds, vec![0, 0, 1, 1]);
+        assert!(order[0].priority_lane);
+        assert!(!order[1].priority_lane);
+        assert_eq!(order[1].effective_tip, Some(U256::from(100)));
+    }
+
+    #[test]
+    fn test_drain_order_matches_the_relay_rounds() {
+        // Given
+        // Two priority senders and three other senders with several nonces, over two lanes
+        let priority_senders = [Address::left_padding_from(&[1]), Address::left_padding_from(&[2])];
+        let ordering = PriorityLaneOrdering::new(priority_senders);
+        let senders: Vec<_> = (1..=5).map(|i| Address::left_padding_from(&[i])).collect();
+        let mut nonces = HashMap::new();
+        let best_transactions: Vec<_> = [0, 2, 0, 3, 1, 4, 2, 0, 1, 3, 4, 1, 0, 2, 4]
+            .into_iter()
+            .map(|i| {
+                let nonce = nonces.entry(i).or_insert(0);
+                *nonce += 1;
+                valid_transaction(senders[i], *nonce - 1, i as u128 + 1)
+            })
+            .collect();
+        let lane_of = |sender: &Address| if sender == &senders[4] { TrafficClass::Faucet } else { TrafficClass::User };
+
+        // When
+        let order = ordering.drain_order(best_transactions.clone(), 10, lane_of);
+
+        // Then
+        // The rounds are the ones of the relay loop draining the pool one round at a time
+        let mut remaining = best_transactions;
+        let mut expected = HashMap::new();
+        let mut round = 0;
+        while !remaining.is_empty() {
+            let drained = ordering.drain_lanes(remaining.iter().cloned(), lane_of);
+            remaining.retain(|transaction| !drained.iter().any(|d| d.hash() == transaction.hash()));
+            expected.extend(drained.iter().map(|transaction| (*transaction.hash(), round)));
+            round += 1;
+        }
+        let rounds: HashMap<_, _> = order.iter().map(|transaction| (transaction.hash, transaction.round)).collect();
+        assert_eq!(rounds, expected);
+        assert!(order.windows(2).all(|pair| pair[0].round <= pair[1].round));
+    }
+
+    #[test]
+    fn test_drain_lanes_under_flood() {
+        // Given
+        // A flood of the faucet, relayed by its own relayers, ordered before the users by tip
+        let faucet_senders: Vec<_> = (10..60).map(|i| Address::left_padding_from(&[i])).collect();
+        let user = Address::left_padding_from(&[1]);
+        let ordering = PriorityLaneOrdering::new([]);
+        let mut best_transactions: Vec<_> =
+            faucet_senders.iter().map(|sender| valid_transaction(*sender, 0, 100)).collect();
+        best_transactions.push(valid_transaction(user, 0, 1));
+        let lane_of =
+            |sender: &Address| if faucet_senders.contains(sender) { TrafficClass::Faucet } else { TrafficClass::User };
+
+        // When
+        let drained = ordering.drain_lanes(best_transactions.clone(), lane_of);
+        let order = ordering.drain_order(best_transactions.clone(), 10, lane_of);
+
+        // Then
+        // The transaction of the user is relayed in the first round, next to a single faucet transaction
+        let senders: Vec<_> = drained.iter().map(|transaction| transaction.sender()).collect();
+        assert_eq!(senders, vec![faucet_senders[0], user]);
+        let user_round =
+            order.iter().find(|transaction| transaction.sender == user).map(|transaction| transaction.round);
+        assert_eq!(user_round, Some(0));
+        assert_eq!(order.iter().map(|transaction| transaction.round).max(), Some(49));
+
+        // When
+        // The faucet shares the relayers of the users
+        let order = ordering.drain_order(best_transactions, 10, |_| TrafficClass::User);
+
+        // Then
+        let user_round =
+            order.iter().find(|transaction| transaction.sender == user).map(|transaction| transaction.round);
+        assert_eq!(user_round, Some(50));
+    }
+}
@@ -0,0 +1,150 @@
+//! End-to-end probe of the transaction pipeline.
+//!
+//! A dedicated probe EOA periodically sends a self-transfer through the node, from the pool to
+//! the relayers, Starknet and the receipts. The success and the latency of each probe are
+//! recorded as metrics, which tell whether the users can transact.
+
+use crate::{
+    client::{EthClient, KakarotTransactions},
+    providers::eth_provider::{GasProvider, ReceiptProvider, TransactionProvider},
+};
+use alloy_consensus::TxEip1559;
+use alloy_eips::{eip2718::Encodable2718, BlockId};
+use alloy_primitives::{Address, TxKind, B256, U256};
+use eyre::{eyre, Result};
+use reth_primitives::{sign_message, Transaction, TransactionSigned};
+use std::{
+    str::FromStr,
+    sync::{Arc, LazyLock},
+    time::Duration,
+};
+use tokio::time::Instant;
+
+/// The private key of the probe EOA. Set with the `PROBE_PRIVATE_KEY` environment variable, the
+/// probe is disabled if not set.
+pub static PROBE_PRIVATE_KEY: LazyLock<Option<B256>> =
+    LazyLock::new(|| std::env::var("PROBE_PRIVATE_KEY").ok().and_then(|val| B256::from_str(&val).ok()));
+
+/// The interval between two probes. Set with the `PROBE_INTERVAL_SECS` environment variable,
+/// defaults to a minute.
+pub static PROBE_INTERVAL: LazyLock<Duration> = LazyLock::new(|| {
+    Duration::from_secs(std::env::var("PROBE_INTERVAL_SECS").ok().and_then(|val| val.parse().ok()).unwrap_or(60))
+});
+
+/// The duration after which a probe whose receipt isn't available is failed. Set with the
+/// `PROBE_TIMEOUT_SECS` environment variable, defaults to 2 minutes.
+pub static PROBE_TIMEOUT: LazyLock<Duration> = LazyLock::new(|| {
+    Duration::from_secs(std::env::var("PROBE_TIMEOUT_SECS").ok().and_then(|val| val.parse().ok()).unwrap_or(120))
+});
+
+/// The interval at which the receipt of the probe transaction is polled.
+const RECEIPT_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// The gas limit of a transfer.
+const TRANSFER_GAS_LIMIT: u64 = 21_000;
+
+/// The probe EOA, sending self-transfers through the node.
+#[derive(Debug)]
+pub struct Probe<SP: starknet::providers::Provider + Send + Sync> {
+    private_key: B256,
+    address: Address,
+    eth_client: Arc<EthClient<SP>>,
+}
+
+impl<SP> Probe<SP>
+where
+    SP: starknet::providers::Provider + Send + Sync + Clone + 'static,
+{
+    /// Creates the probe of the EOA with the given private key.
+    pub fn new(private_key: B256, eth_client: Arc<EthClient<SP>>) -> Result<Self> {
+        // The address of the probe EOA is recovered from a signed transaction
+        let transaction = Transaction::Eip1559(TxEip1559::default());
+        let signature = sign_message(private_key, transaction.signature_hash())?;
+        let address = TransactionSigned::from_transaction_and_signature(transaction, signature)
+            .recover_signer()
+            .ok_or_else(|| eyre!("invalid probe private key"))?;
+
+        Ok(Self { private_key, address, eth_client })
+    }
+
+    /// Returns the address of the probe EOA.
+    pub const fn address(&self) -> Address {
+        self.address
+    }
+
+    /// Sends a self-transfer and waits for its receipt. Returns the hash of the transaction.
+    pub async fn run(&self) -> Result<B256> {
+        let eth_provider = self.eth_client.eth_provider();
+        let nonce = eth_provider.transaction_count(self.address, Some(BlockId::pending())).await?;
+        let max_fee_per_gas = eth_provider.gas_price().await?;
+
+        let transaction = Transaction::Eip1559(TxEip1559 {
+            chain_id: eth_provider.chain_id,
+            nonce: nonce.saturating_to(),
+            gas_limit: TRANSFER_GAS_LIMIT,
+            max_fee_per_gas: max_fee_per_gas.saturating_to(),
+            max_priority_fee_per_gas: 0,
+            to: TxKind::Call(self.address),
+            value: U256::from(1),
+            ..Default::default()
+        });
+        let signature = sign_message(self.private_key, transaction.signature_hash())?;
+        let transaction = TransactionSigned::from_transaction_and_signature(transaction, signature);
+
+        let hash = self.eth_client.send_raw_transaction(transaction.encoded_2718().into()).await?;
+
+        let deadline = Instant::now() + *PROBE_TIMEOUT;
+        while Instant::now() < deadline {
+            tokio::time::sleep(RECEIPT_POLL_INTERVAL).await;
+            if let Some(receipt) = eth_provider.transaction_receipt(hash).await? {
+                if !receipt.inner.status() {
+                    return Err(eyre!("probe transaction {hash} reverted"));
+                }
+                return Ok(hash);
+            }
+        }
+
+        Err(eyre!("no receipt for probe transaction {hash} after {}s", PROBE_TIMEOUT.as_secs()))
+    }
+}
+
+/// Periodically sends a probe transaction through the node if a probe EOA is configured, and
+/// records its success and its end-to-end latency.
+pub fn start_probe<SP>(eth_client: Arc<EthClient<SP>>)
+where
+    SP: starknet::providers::Provider + Send + Sync + Clone + 'static,
+{
+    let Some(private_key) = *PROBE_PRIVATE_KEY else {
+        return;
+    };
+    let probe = match Probe::new(private_key, eth_client) {
+        Ok(probe) => probe,
+        Err(err) => {
+            tracing::error!(target: "probe", ?err, "failed to start the probe");
+            return;
+        }
+    };
+
+    tokio::spawn(async move {
+        loop {
+            let start = Instant::now();
+            match probe.run().await {
+                Ok(hash) => tracing::info!(
+                    target: "probe",
+                    monotonic_counter.probe_successes = 1,
+                    histogram.probe_latency_ms = start.elapsed().as_millis() as u64,
+                    ?hash,
+                    "probe transaction included"
+                ),
+                Err(err) => tracing::warn!(
+                    target: "probe",
+                    monotonic_counter.probe_failures = 1,
+                    address = ?probe.address(),
+                    ?err,
+                    "probe transaction failed"
+                ),
+            }
+            tokio::time::sleep(*PROBE_INTERVAL).await;
+        }
+    });
+}
@@ -0,0 +1,107 @@
+use super::constants::{MAX_PRUNE_DURATION, MIN_PRUNE_DURATION, PRUNE_TARGET_INSERTION_RATE, PRUNE_TARGET_POOL_SIZE};
+use std::time::Duration;
+
+/// The schedule of the pruning of the mempool, which adapts the duration after which a transaction
+/// is pruned to the load of the pool.
+///
+/// While the number of transactions and the insertion rate stay below their targets, transactions
+/// are pruned after the maximum duration. Above the targets, the duration is divided by the load
+/// factor of the pool, down to the minimum duration, so that the pool doesn't keep growing.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PruneSchedule {
+    /// The duration after which a transaction is pruned when the pool isn't under load.
+    pub max_duration: Duration,
+    /// The lower bound of the duration after which a transaction is pruned.
+    pub min_duration: Duration,
+    /// The number of transactions in the pool above which the pool is under load.
+    pub target_pool_size: usize,
+    /// The insertion rate, in transactions per second, above which the pool is under load.
+    pub target_insertion_rate: f64,
+}
+
+impl PruneSchedule {
+    /// Creates a schedule pruning the transactions after the given duration, whatever the load.
+    pub const fn fixed(duration: Duration) -> Self {
+        Self {
+            max_duration: duration,
+            min_duration: duration,
+            target_pool_size: PRUNE_TARGET_POOL_SIZE,
+            target_insertion_rate: PRUNE_TARGET_INSERTION_RATE,
+        }
+    }
+
+    /// Returns the duration after which a transaction is pruned, given the number of transactions
+    /// in the pool and the insertion rate in transactions per second.
+    pub fn prune_duration(&self, pool_size: usize, insertion_rate: f64) -> Duration {
+        let size_load = pool_size as f64 / self.target_pool_size.max(1) as f64;
+        let rate_load = insertion_rate / self.target_insertion_rate;
+        let load = size_load.max(rate_load);
+        if !load.is_finite() || load <= 1. {
+            return self.max_duration;
+        }
+        self.max_duration.div_f64(load).max(self.min_duration)
+    }
+}
+
+impl Default for PruneSchedule {
+    fn default() -> Self {
+        Self {
+            max_duration: MAX_PRUNE_DURATION,
+            min_duration: MIN_PRUNE_DURATION,
+            target_pool_size: PRUNE_TARGET_POOL_SIZE,
+            target_insertion_rate: PRUNE_TARGET_INSERTION_RATE,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prune_duration_below_targets() {
+        // Given
+        let schedule = PruneSchedule::default();
+
+        // When
+        let idle = schedule.prune_duration(0, 0.);
+        let at_target = schedule.prune_duration(PRUNE_TARGET_POOL_SIZE, PRUNE_TARGET_INSERTION_RATE);
+
+        // Then
+        assert_eq!(idle, MAX_PRUNE_DURATION);
+        assert_eq!(at_target, MAX_PRUNE_DURATION);
+    }
+
+    #[test]
+    fn test_prune_duration_under_load() {
+        // Given
+        let schedule = PruneSchedule {
+            max_duration: Duration::from_secs(300),
+            min_duration: Duration::from_secs(30),
+            target_pool_size: 1000,
+            target_insertion_rate: 10.,
+        };
+
+        // When
+        let large_pool = schedule.prune_duration(2000, 0.);
+        let high_rate = schedule.prune_duration(0, 40.);
+        let overloaded = schedule.prune_duration(100_000, 1000.);
+
+        // Then
+        assert_eq!(large_pool, Duration::from_secs(150));
+        assert_eq!(high_rate, Duration::from_secs(75));
+        assert_eq!(overloaded, Duration::from_secs(30));
+    }
+
+    #[test]
+    fn test_fixed_prune_duration() {
+        // Given
+        let schedule = PruneSchedule::fixed(Duration::from_millis(100));
+
+        // When
+        let duration = schedule.prune_duration(100_000, 1000.);
+
+        // Then
+        assert_eq!(duration, Duration::from_millis(100));
+    }
+}
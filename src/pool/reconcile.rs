@@ -0,0 +1,142 @@
+//! Reconciliation of the transactions relayed before a restart of the node.
+//!
+//! A relayer leases its nonce before submitting a transaction to Starknet, and stores the hash
+//! mapping of the transaction once it is submitted. If the node stops in between, the transaction
+//! isn't known by the node after the restart and would be relayed a second time if its sender
+//! sends it again. On startup, the leases of the transactions without hash mapping are reconciled
+//! against the Starknet node before the relayers start:
+//! - the transactions accepted by Starknet get their hash mapping back;
+//! - the transactions whose submission outcome is unknown but whose relayer nonce was consumed
+//!   keep their lease, which marks them as known until it is released;
+//! - the leases of the other transactions are released, as they were never submitted.
+
+use crate::{
+    client::EthClient,
+    models::transaction::recover_signers,
+    providers::eth_provider::{
+        database::{
+            ethereum::EthereumTransactionStore,
+            relayer::RelayerNonceStore,
+            types::{relayer::NonceLease, transaction::EthStarknetHashes},
+        },
+        error::{EthApiError, KakarotError, SignatureError},
+        provider::EthApiResult,
+    },
+};
+use alloy_primitives::B256;
+use alloy_rlp::Decodable;
+use reth_primitives::TransactionSigned;
+use reth_transaction_pool::TransactionPool;
+use starknet::{
+    core::types::{BlockId, BlockTag, Felt, TransactionStatus},
+    providers::Provider,
+};
+use std::collections::{hash_map::Entry, HashMap};
+
+/// The outcome of the reconciliation of the transactions relayed before a restart.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Reconciliation {
+    /// The transactions submitted to Starknet, whose hash mapping was restored.
+    pub recovered: Vec<B256>,
+    /// The transactions whose submission outcome is unknown but whose relayer nonce was
+    /// consumed. Their leases are kept, so that they aren't relayed again.
+    pub in_flight: Vec<B256>,
+    /// The transactions which weren't submitted, whose leases were released.
+    pub released: Vec<B256>,
+}
+
+/// Reconciles the nonce leases of the Ethereum transactions sent to the node against the hash
+/// mappings and the Starknet node, in order not to relay twice the transactions which were
+/// being relayed when the node stopped. The reconciled transactions are removed from the pool.
+pub async fn reconcile_relayed_transactions<SP>(eth_client: &EthClient<SP>) -> EthApiResult<Reconciliation>
+where
+    SP: Provider + Send + Sync + Clone,
+{
+    let eth_provider = eth_client.eth_provider();
+    let database = eth_provider.database();
+    let starknet_provider = eth_provider.starknet_provider_inner();
+
+    let mut reconciliation = Reconciliation::default();
+    let mut chain_nonces = HashMap::new();
+    let mut relayed = Vec::new();
+    for lease in database.nonce_leases().await? {
+        let eth_hash = lease.eth_hash;
+
+        // The leases of the account deployments and transfers have no raw transaction
+        let Some(raw) = database.raw_transaction(&eth_hash).await? else {
+            continue;
+        };
+        if database.starknet_transaction_hash(&eth_hash).await?.is_some() {
+            continue;
+        }
+
+        if let Some(starknet_hash) = lease.starknet_hash {
+            if is_known_by_starknet(starknet_provider, starknet_hash).await {
+                database.upsert_transaction_hashes(EthStarknetHashes { eth_hash, starknet_hash }).await?;
+
+                relayed.push((starknet_hash, TransactionSigned::decode(&mut raw.as_ref())?));
+                reconciliation.recovered.push(eth_hash);
+                continue;
+            }
+        } else if relayer_nonce_consumed(starknet_provider, &mut chain_nonces, &lease).await? {
+            reconciliation.in_flight.push(eth_hash);
+            continue;
+        }
+
+        database.release_nonce_lease(lease.relayer, lease.nonce).await?;
+        reconciliation.released.push(eth_hash);
+    }
+
+    // Serve the receipts from the pending block until the transactions are indexed, the senders
+    // being recovered in a batch off the runtime
+    let (starknet_hashes, transactions): (Vec<_>, Vec<_>) = relayed.into_iter().unzip();
+    let (transactions, senders) = tokio::task::spawn_blocking(move || {
+        let senders = recover_signers(&transactions);
+        (transactions, senders)
+    })
+    .await
+    .map_err(|_| EthApiError::from("failed to recover the senders of the relayed transactions"))?;
+    for ((starknet_hash, transaction), sender) in starknet_hashes.into_iter().zip(transactions).zip(senders) {
+        let sender = sender.ok_or(SignatureError::Recovery)?;
+        eth_provider.pending_receipts().insert_relayed(starknet_hash, transaction, sender);
+    }
+
+    let reconciled = reconciliation.recovered.iter().chain(&reconciliation.in_flight).copied().collect();
+    let _ = eth_client.mempool().remove_transactions(reconciled);
+
+    tracing::info!(
+        target: "reconcile",
+        recovered = reconciliation.recovered.len(),
+        in_flight = reconciliation.in_flight.len(),
+        released = reconciliation.released.len(),
+        "reconciled the transactions relayed before the restart"
+    );
+    Ok(reconciliation)
+}
+
+/// Returns true if the Starknet transaction was received and not rejected by the Starknet node.
+async fn is_known_by_starknet<SP: Provider + Send + Sync>(starknet_provider: &SP, starknet_hash: Felt) -> bool {
+    starknet_provider
+        .get_transaction_status(starknet_hash)
+        .await
+        .is_ok_and(|status| !matches!(status, TransactionStatus::Rejected))
+}
+
+/// Returns true if the nonce of the lease was consumed on chain by the relayer. The nonces of the
+/// relayers are fetched once.
+async fn relayer_nonce_consumed<SP: Provider + Send + Sync>(
+    starknet_provider: &SP,
+    chain_nonces: &mut HashMap<Felt, Felt>,
+    lease: &NonceLease,
+) -> EthApiResult<bool> {
+    let chain_nonce = match chain_nonces.entry(lease.relayer) {
+        Entry::Occupied(entry) => *entry.get(),
+        Entry::Vacant(entry) => *entry.insert(
+            starknet_provider
+                .get_nonce(BlockId::Tag(BlockTag::Pending), lease.relayer)
+                .await
+                .map_err(KakarotError::from)?,
+        ),
+    };
+    Ok(chain_nonce > Felt::from(lease.nonce))
+}
@@ -0,0 +1,313 @@
+use super::constants::{FAUCET_SENDERS, KEEPER_SENDERS};
+use crate::eth_rpc::openrpc::SchemaOf;
+use alloy_primitives::Address;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use starknet::core::types::Felt;
+use std::{
+    collections::HashSet,
+    sync::{PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard},
+    time::Duration,
+};
+
+/// The class of the traffic served by a pool of relayers.
+///
+/// Each class is relayed by its own relayers, so that a flood of transactions of a class (e.g.
+/// the faucet) can't exhaust the nonce throughput of the relayers serving the users.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum TrafficClass {
+    /// The transactions of the users, which is the class of all the senders not listed in
+    /// another class.
+    #[default]
+    User,
+    /// The transactions of the protocol keepers, sent by the `KEEPER_SENDERS`.
+    Keeper,
+    /// The transactions of the faucet, sent by the `FAUCET_SENDERS`.
+    Faucet,
+}
+
+/// Assigns the senders of the transactions to their traffic class.
+#[derive(Debug, Clone)]
+pub struct TrafficClassifier {
+    keeper_senders: HashSet<Address>,
+    faucet_senders: HashSet<Address>,
+}
+
+impl TrafficClassifier {
+    /// Creates a new classifier with the given keeper and faucet senders.
+    pub fn new(
+        keeper_senders: impl IntoIterator<Item = Address>,
+        faucet_senders: impl IntoIterator<Item = Address>,
+    ) -> Self {
+        Self {
+            keeper_senders: keeper_senders.into_iter().collect(),
+            faucet_senders: faucet_senders.into_iter().collect(),
+        }
+    }
+
+    /// Returns the traffic class of the sender. A sender listed in both the keeper and the faucet
+    /// senders is a keeper.
+    pub fn class_of(&self, sender: &Address) -> TrafficClass {
+        if self.keeper_senders.contains(sender) {
+            TrafficClass::Keeper
+        } else if self.faucet_senders.contains(sender) {
+            TrafficClass::Faucet
+        } else {
+            TrafficClass::User
+        }
+    }
+}
+
+impl Default for TrafficClassifier {
+    /// Creates a classifier with the keeper and faucet senders read from the environment.
+    fn default() -> Self {
+        Self::new(KEEPER_SENDERS.iter().copied(), FAUCET_SENDERS.iter().copied())
+    }
+}
+
+/// The statistics of a relayer account.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RelayerStats {
+    /// The Starknet address of the relayer.
+    #[schemars(with = "SchemaOf<Felt>")]
+    pub address: Felt,
+    /// The traffic class served by the relayer.
+    pub traffic_class: TrafficClass,
+    /// The number of transactions currently being relayed by the relayer.
+    pub queue_depth: u64,
+    /// The number of transactions successfully relayed.
+    pub submitted: u64,
+    /// The number of transactions which failed to be relayed.
+    pub failed: u64,
+    /// The latency of the last submission, in milliseconds.
+    pub last_submission_latency_ms: Option<u64>,
+    /// The average latency of the submissions, in milliseconds.
+    pub average_submission_latency_ms: Option<u64>,
+}
+
+/// The queue of transactions waiting to be relayed, used to decide when to add relayers.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RelayerQueue {
+    /// The number of pending transactions in the mempool, waiting for a relayer.
+    pub pending: usize,
+    /// The number of queued transactions in the mempool.
+    pub queued: usize,
+    /// The size of the pending transactions in the mempool, in bytes.
+    pub pending_size: usize,
+    /// The size of the queued transactions in the mempool, in bytes.
+    pub queued_size: usize,
+    /// The statistics of each relayer.
+    pub relayers: Vec<RelayerStats>,
+}
+
+#[derive(Debug)]
+struct RelayerEntry {
+    stats: RelayerStats,
+    total_latency: Duration,
+}
+
+/// The registry of the relayer accounts used by the [`AccountManager`](super::mempool::AccountManager).
+///
+/// Relayers can be registered at runtime and track their queue depth and submission latency.
+/// Each relayer serves a single traffic class.
+#[derive(Debug, Default)]
+pub struct RelayerRegistry {
+    relayers: RwLock<Vec<RelayerEntry>>,
+}
+
+impl RelayerRegistry {
+    /// Creates a new registry with the given relayer addresses, serving the users.
+    pub fn new(addresses: impl IntoIterator<Item = Felt>) -> Self {
+        let registry = Self::default();
+        for address in addresses {
+            registry.register(address);
+        }
+        registry
+    }
+
+    /// Returns the addresses of the registered relayers, of all the traffic classes.
+    pub fn addresses(&self) -> Vec<Felt> {
+        self.read().iter().map(|entry| entry.stats.address).collect()
+    }
+
+    /// Returns the addresses of the relayers serving the traffic class. A class without relayers
+    /// is served by the relayers of the users.
+    pub fn addresses_for(&self, traffic_class: TrafficClass) -> Vec<Felt> {
+        let addresses = self.dedicated_addresses_for(traffic_class);
+        if addresses.is_empty() && traffic_class != TrafficClass::User {
+            return self.dedicated_addresses_for(TrafficClass::User);
+        }
+        addresses
+    }
+
+    /// Returns the traffic class whose relayers serve the traffic class: the class itself if it
+    /// has dedicated relayers, the users otherwise.
+    pub fn relaying_class(&self, traffic_class: TrafficClass) -> TrafficClass {
+        if self.read().iter().any(|entry| entry.stats.traffic_class == traffic_class) {
+            traffic_class
+        } else {
+            TrafficClass::User
+        }
+    }
+
+    /// Returns the addresses of the relayers registered for the traffic class, without falling
+    /// back to the relayers of the users.
+    pub fn dedicated_addresses_for(&self, traffic_class: TrafficClass) -> Vec<Felt> {
+        self.read()
+            .iter()
+            .filter(|entry| entry.stats.traffic_class == traffic_class)
+            .map(|entry| entry.stats.address)
+            .collect()
+    }
+
+    /// Registers a new relayer serving the users. Returns false if the relayer was already
+    /// registered.
+    pub fn register(&self, address: Felt) -> bool {
+        self.register_for(address, TrafficClass::User)
+    }
+
+    /// Registers a new relayer serving the traffic class. Returns false if the relayer was
+    /// already registered, for any class.
+    pub fn register_for(&self, address: Felt, traffic_class: TrafficClass) -> bool {
+        let mut relayers = self.write();
+        if relayers.iter().any(|entry| entry.stats.address == address) {
+            return false;
+        }
+        relayers.push(RelayerEntry {
+            stats: RelayerStats { address, traffic_class, ..Default::default() },
+            total_latency: Duration::ZERO,
+        });
+        true
+    }
+
+    /// Returns the statistics of the registered relayers.
+    pub fn stats(&self) -> Vec<RelayerStats> {
+        self.read().iter().map(|entry| entry.stats.clone()).collect()
+    }
+
+    /// Marks the start of a transaction submission by the relayer.
+    pub fn start_submission(&self, address: Felt) {
+        if let Some(entry) = self.write().iter_mut().find(|entry| entry.stats.address == address) {
+            entry.stats.queue_depth += 1;
+        }
+    }
+
+    /// Marks the end of a transaction submission by the relayer, recording its latency.
+    pub fn end_submission(&self, address: Felt, latency: Duration, success: bool) {
+        if let Some(entry) = self.write().iter_mut().find(|entry| entry.stats.address == address) {
+            let stats = &mut entry.stats;
+            stats.queue_depth = stats.queue_depth.saturating_sub(1);
+            if success {
+                stats.submitted += 1;
+            } else {
+                stats.failed += 1;
+            }
+
+            entry.total_latency += latency;
+            let count = u32::try_from(stats.submitted + stats.failed).unwrap_or(u32::MAX);
+            stats.last_submission_latency_ms = Some(latency.as_millis() as u64);
+            stats.average_submission_latency_ms = Some((entry.total_latency / count).as_millis() as u64);
+        }
+    }
+
+    fn read(&self) -> RwLockReadGuard<'_, Vec<RelayerEntry>> {
+        self.relayers.read().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn write(&self) -> RwLockWriteGuard<'_, Vec<RelayerEntry>> {
+        self.relayers.write().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_register_relayer() {
+        // Given
+        let registry = RelayerRegistry::new([Felt::ONE]);
+
+        // When
+        let registered = registry.register(Felt::TWO);
+        let duplicate = registry.register(Felt::ONE);
+
+        // Then
+        assert!(registered);
+        assert!(!duplicate);
+        assert_eq!(registry.addresses(), vec![Felt::ONE, Felt::TWO]);
+    }
+
+    #[test]
+    fn test_relayers_per_traffic_class() {
+        // Given
+        let registry = RelayerRegistry::new([Felt::ONE]);
+        registry.register_for(Felt::TWO, TrafficClass::Faucet);
+
+        // When
+        let duplicate = registry.register_for(Felt::ONE, TrafficClass::Keeper);
+
+        // Then
+        assert!(!duplicate);
+        assert_eq!(registry.addresses_for(TrafficClass::User), vec![Felt::ONE]);
+        assert_eq!(registry.addresses_for(TrafficClass::Faucet), vec![Felt::TWO]);
+        assert_eq!(registry.addresses_for(TrafficClass::Keeper), vec![Felt::ONE]);
+        assert!(registry.dedicated_addresses_for(TrafficClass::Keeper).is_empty());
+        assert_eq!(registry.relaying_class(TrafficClass::Faucet), TrafficClass::Faucet);
+        assert_eq!(registry.relaying_class(TrafficClass::Keeper), TrafficClass::User);
+        assert_eq!(registry.addresses(), vec![Felt::ONE, Felt::TWO]);
+    }
+
+    #[test]
+    fn test_classify_senders() {
+        // Given
+        let keeper = Address::left_padding_from(&[1]);
+        let faucet = Address::left_padding_from(&[2]);
+        let classifier = TrafficClassifier::new([keeper], [faucet, keeper]);
+
+        // When
+        let classes = [keeper, faucet, Address::left_padding_from(&[3])].map(|sender| classifier.class_of(&sender));
+
+        // Then
+        assert_eq!(classes, [TrafficClass::Keeper, TrafficClass::Faucet, TrafficClass::User]);
+    }
+
+    #[test]
+    fn test_relayer_submission_stats() {
+        // Given
+        let registry = RelayerRegistry::new([Felt::ONE]);
+
+        // When
+        registry.start_submission(Felt::ONE);
+        registry.start_submission(Felt::ONE);
+        registry.end_submission(Felt::ONE, Duration::from_millis(100), true);
+
+        // Then
+        let stats = registry.stats();
+        assert_eq!(
+            stats,
+            vec![RelayerStats {
+                address: Felt::ONE,
+                traffic_class: TrafficClass::User,
+                queue_depth: 1,
+                submitted: 1,
+                failed: 0,
+                last_submission_latency_ms: Some(100),
+                average_submission_latency_ms: Some(100),
+            }]
+        );
+
+        // When
+        registry.end_submission(Felt::ONE, Duration::from_millis(300), false);
+
+        // Then
+        let stats = &registry.stats()[0];
+        assert_eq!(stats.queue_depth, 0);
+        assert_eq!(stats.failed, 1);
+        assert_eq!(stats.last_submission_latency_ms, Some(300));
+        assert_eq!(stats.average_submission_latency_ms, Some(200));
+    }
+}
@@ -0,0 +1,214 @@
+//! Snapshots of the mempool, used to reproduce locally the state of the mempool of a node.
+//!
+//! A snapshot holds the raw bytes of the pending and queued transactions of the mempool along
+//! with their validation metadata. It is exported with `admin_exportPool`, saved to a JSON file,
+//! and imported into a dev instance with `admin_importPool`, which validates each transaction
+//! again as if it was submitted with `eth_sendRawTransaction`.
+
+use crate::eth_rpc::openrpc::SchemaOf;
+#[cfg(feature = "mongo")]
+use crate::{
+    client::EthClient,
+    models::transaction::recover_signers,
+    providers::eth_provider::{
+        error::{EthApiError, SignatureError},
+        provider::EthApiResult,
+    },
+};
+use alloy_eips::eip2718::Encodable2718;
+use alloy_primitives::{Address, Bytes, B256, U256};
+#[cfg(feature = "mongo")]
+use alloy_rlp::Decodable;
+#[cfg(feature = "mongo")]
+use reth_primitives::TransactionSigned;
+use reth_primitives::TransactionSignedEcRecovered;
+use reth_transaction_pool::{
+    AllPoolTransactions, PoolTransaction, TransactionOrigin, TransactionPool, ValidPoolTransaction,
+};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::{
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// The sub-pool of a transaction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum SubPool {
+    /// The transaction can be relayed.
+    Pending,
+    /// The transaction waits for a missing nonce or for its fee to cover the base fee.
+    Queued,
+}
+
+/// The origin of a transaction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum SnapshotOrigin {
+    /// Submitted to the node.
+    Local,
+    /// Received from another node.
+    External,
+    /// Submitted to the node and never propagated.
+    Private,
+}
+
+impl From<TransactionOrigin> for SnapshotOrigin {
+    fn from(origin: TransactionOrigin) -> Self {
+        match origin {
+            TransactionOrigin::Local => Self::Local,
+            TransactionOrigin::External => Self::External,
+            TransactionOrigin::Private => Self::Private,
+        }
+    }
+}
+
+/// A transaction of the mempool along with its validation metadata.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SnapshotTransaction {
+    /// The hash of the transaction.
+    #[schemars(with = "SchemaOf<B256>")]
+    pub hash: B256,
+    /// The recovered sender of the transaction.
+    #[schemars(with = "SchemaOf<Address>")]
+    pub sender: Address,
+    /// The nonce of the transaction.
+    pub nonce: u64,
+    /// The sub-pool of the transaction.
+    pub sub_pool: SubPool,
+    /// The origin of the transaction.
+    pub origin: SnapshotOrigin,
+    /// Whether the transaction is propagated to the other nodes.
+    pub propagate: bool,
+    /// The maximum cost of the transaction, as validated against the balance of the sender.
+    #[schemars(with = "SchemaOf<U256>")]
+    pub cost: U256,
+    /// The maximum fee per gas of the transaction.
+    pub max_fee_per_gas: u128,
+    /// The length of the encoded transaction, in bytes.
+    pub encoded_length: usize,
+    /// The time spent by the transaction in the mempool, in milliseconds.
+    pub age_ms: u64,
+    /// The EIP-2718 encoding of the transaction.
+    #[schemars(with = "SchemaOf<Bytes>")]
+    pub raw: Bytes,
+}
+
+impl SnapshotTransaction {
+    fn new<T>(transaction: &Arc<ValidPoolTransaction<T>>, sub_pool: SubPool) -> Self
+    where
+        T: PoolTransaction<Consensus = TransactionSignedEcRecovered>,
+    {
+        Self {
+            hash: *transaction.hash(),
+            sender: transaction.sender(),
+            nonce: transaction.nonce(),
+            sub_pool,
+            origin: transaction.origin.into(),
+            propagate: transaction.propagate,
+            cost: transaction.cost().to_owned(),
+            max_fee_per_gas: transaction.max_fee_per_gas(),
+            encoded_length: transaction.encoded_length(),
+            age_ms: transaction.timestamp.elapsed().as_millis() as u64,
+            raw: transaction.transaction.clone().into_consensus().into_signed().encoded_2718().into(),
+        }
+    }
+}
+
+/// A snapshot of the mempool.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct PoolSnapshot {
+    /// The time at which the snapshot was taken, in seconds since the Unix epoch.
+    pub timestamp: u64,
+    /// The pending and queued transactions of the mempool.
+    pub transactions: Vec<SnapshotTransaction>,
+}
+
+impl PoolSnapshot {
+    /// Takes a snapshot of the pending and queued transactions of the pool.
+    pub fn new<P>(pool: &P) -> Self
+    where
+        P: TransactionPool,
+        P::Transaction: PoolTransaction<Consensus = TransactionSignedEcRecovered>,
+    {
+        let AllPoolTransactions { pending, queued } = pool.all_transactions();
+        let transactions = pending
+            .iter()
+            .map(|transaction| SnapshotTransaction::new(transaction, SubPool::Pending))
+            .chain(queued.iter().map(|transaction| SnapshotTransaction::new(transaction, SubPool::Queued)))
+            .collect();
+
+        Self { timestamp: SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs(), transactions }
+    }
+}
+
+/// A transaction of a snapshot which couldn't be imported.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportFailure {
+    /// The hash of the transaction.
+    #[schemars(with = "SchemaOf<B256>")]
+    pub hash: B256,
+    /// The reason why the transaction was rejected.
+    pub error: String,
+}
+
+/// The result of the import of a snapshot.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct PoolImport {
+    /// The hashes of the imported transactions.
+    #[schemars(with = "SchemaOf<Vec<B256>>")]
+    pub imported: Vec<B256>,
+    /// The transactions rejected by the mempool.
+    pub failed: Vec<ImportFailure>,
+}
+
+/// Imports the transactions of the snapshot into the mempool of the client, in nonce order for
+/// each sender. The transactions are validated again and added as local transactions.
+///
+/// The signers of the whole snapshot are recovered in a batch off the runtime before the
+/// transactions are added, see [`recover_signers`].
+#[cfg(feature = "mongo")]
+pub async fn import_pool_snapshot<SP>(eth_client: &EthClient<SP>, snapshot: PoolSnapshot) -> EthApiResult<PoolImport>
+where
+    SP: starknet::providers::Provider + Send + Sync + Clone,
+{
+    let mut transactions = snapshot.transactions;
+    transactions.sort_by_key(|transaction| (transaction.sender, transaction.nonce));
+
+    let mut import = PoolImport::default();
+    let mut decoded = Vec::with_capacity(transactions.len());
+    for transaction in transactions {
+        match TransactionSigned::decode(&mut transaction.raw.as_ref()) {
+            Ok(transaction_signed) => decoded.push((transaction.raw, transaction_signed)),
+            Err(err) => import.failed.push(ImportFailure { hash: transaction.hash, error: err.to_string() }),
+        }
+    }
+
+    let (raws, transactions): (Vec<_>, Vec<_>) = decoded.into_iter().unzip();
+    let (transactions, signers) = tokio::task::spawn_blocking(move || {
+        let signers = recover_signers(&transactions);
+        (transactions, signers)
+    })
+    .await
+    .map_err(|_| EthApiError::from("failed to recover the signers of the mempool snapshot"))?;
+
+    for ((raw, transaction_signed), signer) in raws.into_iter().zip(transactions).zip(signers) {
+        let hash = transaction_signed.hash();
+        let Some(signer) = signer else {
+            import.failed.push(ImportFailure { hash, error: SignatureError::Recovery.to_string() });
+            continue;
+        };
+        match eth_client.add_recovered_transaction(transaction_signed, signer, raw).await {
+            Ok(hash) => import.imported.push(hash),
+            Err(err) => import.failed.push(ImportFailure { hash, error: err.to_string() }),
+        }
+    }
+
+    tracing::info!(imported = import.imported.len(), failed = import.failed.len(), "imported mempool snapshot");
+    Ok(import)
+}
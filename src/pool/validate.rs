@@ -1,23 +1,91 @@
 #![allow(unused_variables, clippy::struct_excessive_bools)]
 
-use crate::providers::eth_provider::{
-    database::state::EthDatabase, provider::EthereumProvider,
-    starknet::kakarot_core::get_white_listed_eip_155_transaction_hashes,
+use crate::{
+    models::transaction::starknet_calldata_len,
+    providers::eth_provider::{
+        database::state::EthDatabase, provider::EthereumProvider,
+        starknet::kakarot_core::get_white_listed_eip_155_transaction_hashes,
+    },
 };
 use alloy_consensus::constants::{EIP1559_TX_TYPE_ID, EIP2930_TX_TYPE_ID, EIP4844_TX_TYPE_ID, LEGACY_TX_TYPE_ID};
+use alloy_primitives::Address;
 use alloy_rpc_types::BlockNumberOrTag;
 use reth_chainspec::ChainSpec;
-use reth_primitives::{GotExpected, InvalidTransactionError, SealedBlock};
-use reth_revm::DatabaseRef;
+use reth_primitives::{GotExpected, InvalidTransactionError, SealedBlock, TransactionSignedEcRecovered};
+use reth_revm::{primitives::MAX_INITCODE_SIZE, DatabaseRef};
 use reth_transaction_pool::{
-    error::InvalidPoolTransactionError,
+    error::{InvalidPoolTransactionError, PoolTransactionError},
     validate::{ensure_intrinsic_gas, ForkTracker, ValidTransaction, DEFAULT_MAX_TX_INPUT_BYTES},
     EthPoolTransaction, TransactionOrigin, TransactionValidationOutcome, TransactionValidator,
 };
 use std::{
+    collections::HashSet,
     marker::PhantomData,
-    sync::{atomic::AtomicBool, Arc},
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc, PoisonError, RwLock,
+    },
 };
+use thiserror::Error;
+
+/// Error thrown by the pool when a transaction isn't replay protected for the chain of the node.
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum Eip155Error {
+    /// Thrown when a legacy transaction doesn't commit to a chain id.
+    #[error("only replay-protected (EIP-155) transactions allowed, sign the transaction with chain id {0}")]
+    NotReplayProtected(u64),
+    /// Thrown when the transaction is signed for another chain.
+    #[error("invalid chain id {got}, expected chain id {expected}")]
+    ChainIdMismatch { got: u64, expected: u64 },
+}
+
+impl PoolTransactionError for Eip155Error {
+    fn is_bad_transaction(&self) -> bool {
+        false
+    }
+}
+
+/// Error thrown by the pool when a contract creation is sent by a sender which isn't allowed to
+/// deploy contracts.
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum DeployerError {
+    /// Thrown when the sender of a contract creation isn't in the deployer allowlist.
+    #[error("contract creation not allowed: {0} isn't in the deployer allowlist of the network")]
+    NotAllowed(Address),
+}
+
+impl PoolTransactionError for DeployerError {
+    fn is_bad_transaction(&self) -> bool {
+        false
+    }
+}
+
+/// Ensures the sender of a contract creation is allowed to deploy contracts. Anyone can deploy
+/// contracts if there is no allowlist.
+pub(crate) fn ensure_allowed_deployer(
+    sender: Address,
+    is_create: bool,
+    allowlist: Option<&HashSet<Address>>,
+) -> Result<(), DeployerError> {
+    match allowlist {
+        Some(allowlist) if is_create && !allowlist.contains(&sender) => Err(DeployerError::NotAllowed(sender)),
+        _ => Ok(()),
+    }
+}
+
+/// Ensures the transaction is replay protected (EIP-155) and signed for the chain id. Legacy
+/// transactions without chain id are only accepted if they are white listed.
+pub(crate) fn ensure_eip155(
+    transaction_chain_id: Option<u64>,
+    chain_id: u64,
+    white_listed: bool,
+) -> Result<(), Eip155Error> {
+    match transaction_chain_id {
+        None if !white_listed => Err(Eip155Error::NotReplayProtected(chain_id)),
+        Some(got) if got != chain_id => Err(Eip155Error::ChainIdMismatch { got, expected: chain_id }),
+        _ => Ok(()),
+    }
+}
 
 #[derive(Debug, Clone)]
 pub struct KakarotTransactionValidatorBuilder {
@@ -38,6 +106,12 @@ pub struct KakarotTransactionValidatorBuilder {
     pub block_gas_limit: u64,
     /// Max size in bytes of a single transaction allowed
     pub max_tx_input_bytes: usize,
+    /// Whether only replay protected (EIP-155) transactions signed for the chain are allowed
+    pub strict_eip155: bool,
+    /// Max number of felts in the calldata of the Starknet transaction relaying a transaction
+    pub max_felts_in_calldata: Option<usize>,
+    /// The senders allowed to deploy contracts, anyone if unset
+    pub deployer_allowlist: Option<HashSet<Address>>,
 }
 
 impl KakarotTransactionValidatorBuilder {
@@ -48,6 +122,8 @@ impl KakarotTransactionValidatorBuilder {
     ///  - Legacy
     ///  - EIP-2718
     ///  - EIP-1559
+    ///
+    /// Transactions which aren't replay protected (EIP-155) for the chain are rejected.
     pub fn new(chain_spec: &Arc<ChainSpec>) -> Self {
         Self {
             chain_spec: chain_spec.clone(),
@@ -67,9 +143,44 @@ impl KakarotTransactionValidatorBuilder {
 
             // prague not yet activated
             prague: false,
+
+            // only replay protected transactions are allowed by default
+            strict_eip155: true,
+
+            // the size of the relaying Starknet transaction isn't bounded by default
+            max_felts_in_calldata: None,
+
+            // anyone can deploy contracts by default
+            deployer_allowlist: None,
         }
     }
 
+    /// Sets whether only replay protected (EIP-155) transactions signed for the chain are
+    /// allowed. If unset, the legacy transactions without chain id and the transactions signed
+    /// for another chain are accepted, which should only be used on development networks.
+    #[must_use]
+    pub const fn with_strict_eip155(mut self, strict_eip155: bool) -> Self {
+        self.strict_eip155 = strict_eip155;
+        self
+    }
+
+    /// Sets the maximum number of felts in the calldata of the Starknet transaction relaying a
+    /// transaction. Transactions above the limit, e.g. large contract deployments, are rejected
+    /// by the pool instead of failing when they are relayed.
+    #[must_use]
+    pub const fn with_max_felts_in_calldata(mut self, max_felts_in_calldata: Option<usize>) -> Self {
+        self.max_felts_in_calldata = max_felts_in_calldata;
+        self
+    }
+
+    /// Restricts the contract creations to the senders of the allowlist, e.g. on permissioned
+    /// deployments. The contracts deployed by other contracts aren't restricted.
+    #[must_use]
+    pub fn with_deployer_allowlist(mut self, deployer_allowlist: Option<HashSet<Address>>) -> Self {
+        self.deployer_allowlist = deployer_allowlist;
+        self
+    }
+
     /// Builds the [`EthTransactionValidator`] without spawning validator tasks.
     pub fn build<P, Tx>(self, provider: P) -> KakarotTransactionValidator<P, Tx>
     where
@@ -85,6 +196,9 @@ impl KakarotTransactionValidatorBuilder {
             eip4844,
             block_gas_limit,
             max_tx_input_bytes,
+            strict_eip155,
+            max_felts_in_calldata,
+            deployer_allowlist,
             ..
         } = self;
 
@@ -100,8 +214,11 @@ impl KakarotTransactionValidatorBuilder {
             eip2718,
             eip1559,
             eip4844,
-            block_gas_limit,
+            block_gas_limit: AtomicU64::new(block_gas_limit),
             max_tx_input_bytes,
+            strict_eip155,
+            max_felts_in_calldata,
+            deployer_allowlist: RwLock::new(deployer_allowlist),
             fork_tracker,
             _marker: Default::default(),
         };
@@ -133,6 +250,26 @@ where
     pub fn provider(&self) -> &P {
         &self.inner.provider
     }
+
+    /// Returns the current max gas limit
+    pub fn block_gas_limit(&self) -> u64 {
+        self.inner.block_gas_limit.load(Ordering::Relaxed)
+    }
+
+    /// Updates the max gas limit, e.g. after a change of the block gas limit of Kakarot core
+    pub fn set_block_gas_limit(&self, block_gas_limit: u64) {
+        self.inner.block_gas_limit.store(block_gas_limit, Ordering::Relaxed);
+    }
+
+    /// Returns the senders allowed to deploy contracts, anyone if unset
+    pub fn deployer_allowlist(&self) -> Option<HashSet<Address>> {
+        self.inner.deployer_allowlist.read().unwrap_or_else(PoisonError::into_inner).clone()
+    }
+
+    /// Updates the senders allowed to deploy contracts, unset to allow anyone
+    pub fn set_deployer_allowlist(&self, deployer_allowlist: Option<HashSet<Address>>) {
+        *self.inner.deployer_allowlist.write().unwrap_or_else(PoisonError::into_inner) = deployer_allowlist;
+    }
 }
 
 impl<P, Tx> KakarotTransactionValidator<P, Tx>
@@ -199,9 +336,15 @@ where
     /// Fork indicator whether we are using EIP-4844 blob transactions.
     eip4844: bool,
     /// The current max gas limit
-    block_gas_limit: u64,
+    block_gas_limit: AtomicU64,
     /// Maximum size in bytes a single transaction can have in order to be accepted into the pool.
     max_tx_input_bytes: usize,
+    /// Whether only replay protected (EIP-155) transactions signed for the chain are allowed.
+    strict_eip155: bool,
+    /// Maximum number of felts in the calldata of the Starknet transaction relaying a transaction.
+    max_felts_in_calldata: Option<usize>,
+    /// The senders allowed to deploy contracts, anyone if unset.
+    deployer_allowlist: RwLock<Option<HashSet<Address>>>,
     /// tracks activated forks relevant for transaction validation
     fork_tracker: ForkTracker,
     /// Marker for the transaction type
@@ -228,16 +371,8 @@ where
     fn validate_one(&self, transaction: Tx) -> TransactionValidationOutcome<Tx> {
         // Checks for tx_type
         match transaction.tx_type() {
-            LEGACY_TX_TYPE_ID => {
-                if transaction.chain_id().is_none()
-                    && !get_white_listed_eip_155_transaction_hashes().contains(transaction.hash())
-                {
-                    return TransactionValidationOutcome::Invalid(
-                        transaction,
-                        InvalidTransactionError::TxTypeNotSupported.into(),
-                    );
-                }
-            }
+            // The replay protection of legacy transactions is checked with the chain id
+            LEGACY_TX_TYPE_ID => {}
             EIP2930_TX_TYPE_ID => {
                 // Accept only legacy transactions until EIP-2718/2930 activates
                 if !self.eip2718 {
@@ -273,6 +408,19 @@ where
             }
         };
 
+        // Reject the contract creations of the senders which aren't allowed to deploy contracts
+        let allowed = ensure_allowed_deployer(
+            transaction.sender(),
+            transaction.kind().is_create(),
+            self.deployer_allowlist.read().unwrap_or_else(PoisonError::into_inner).as_ref(),
+        );
+        if let Err(err) = allowed {
+            return TransactionValidationOutcome::Invalid(
+                transaction,
+                InvalidPoolTransactionError::Other(Box::new(err)),
+            );
+        }
+
         // Reject transactions over defined size to prevent DOS attacks
         let transaction_size = transaction.size();
         if transaction_size > self.max_tx_input_bytes {
@@ -282,12 +430,30 @@ where
             );
         }
 
+        // EIP-3860: reject the contract creations whose init code exceeds the size limit
+        if let Err(err) = transaction.ensure_max_init_code_size(MAX_INITCODE_SIZE) {
+            return TransactionValidationOutcome::Invalid(transaction, err);
+        }
+
+        // Reject transactions whose relaying Starknet transaction exceeds the Starknet limits
+        if let Some(max_felts_in_calldata) = self.max_felts_in_calldata {
+            let recovered: TransactionSignedEcRecovered = transaction.clone().into_consensus().into();
+            let calldata_len = starknet_calldata_len(&recovered.into_signed());
+            if calldata_len > max_felts_in_calldata {
+                return TransactionValidationOutcome::Invalid(
+                    transaction,
+                    InvalidPoolTransactionError::OversizedData(calldata_len, max_felts_in_calldata),
+                );
+            }
+        }
+
         // Checks for gas limit
         let transaction_gas_limit = transaction.gas_limit();
-        if transaction_gas_limit > self.block_gas_limit {
+        let block_gas_limit = self.block_gas_limit.load(Ordering::Relaxed);
+        if transaction_gas_limit > block_gas_limit {
             return TransactionValidationOutcome::Invalid(
                 transaction,
-                InvalidPoolTransactionError::ExceedsGasLimit(transaction_gas_limit, self.block_gas_limit),
+                InvalidPoolTransactionError::ExceedsGasLimit(transaction_gas_limit, block_gas_limit),
             );
         }
 
@@ -296,12 +462,14 @@ where
             return TransactionValidationOutcome::Invalid(transaction, InvalidTransactionError::TipAboveFeeCap.into());
         }
 
-        // Checks for chainid
-        if let Some(chain_id) = transaction.chain_id() {
-            if chain_id != self.chain_id() {
+        // Checks for the replay protection and the chain id
+        if self.strict_eip155 {
+            let white_listed = transaction.chain_id().is_none()
+                && get_white_listed_eip_155_transaction_hashes().contains(transaction.hash());
+            if let Err(err) = ensure_eip155(transaction.chain_id(), self.chain_id(), white_listed) {
                 return TransactionValidationOutcome::Invalid(
                     transaction,
-                    InvalidTransactionError::ChainIdMismatch.into(),
+                    InvalidPoolTransactionError::Other(Box::new(err)),
                 );
             }
         }
@@ -358,3 +526,45 @@ where
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ensure_allowed_deployer() {
+        // Given
+        let deployer = Address::with_last_byte(1);
+        let other = Address::with_last_byte(2);
+        let allowlist = HashSet::from([deployer]);
+
+        // When
+        // Then
+        assert_eq!(ensure_allowed_deployer(deployer, true, Some(&allowlist)), Ok(()));
+        assert_eq!(ensure_allowed_deployer(other, true, Some(&allowlist)), Err(DeployerError::NotAllowed(other)));
+        assert_eq!(ensure_allowed_deployer(other, false, Some(&allowlist)), Ok(()));
+        assert_eq!(ensure_allowed_deployer(other, true, None), Ok(()));
+        assert_eq!(
+            ensure_allowed_deployer(deployer, true, Some(&HashSet::new())),
+            Err(DeployerError::NotAllowed(deployer))
+        );
+    }
+
+    #[test]
+    fn test_ensure_eip155() {
+        // Given
+        let chain_id = 1_802_203_764;
+
+        // When
+        let replay_protected = ensure_eip155(Some(chain_id), chain_id, false);
+        let white_listed = ensure_eip155(None, chain_id, true);
+        let not_replay_protected = ensure_eip155(None, chain_id, false);
+        let wrong_chain_id = ensure_eip155(Some(1), chain_id, false);
+
+        // Then
+        assert_eq!(replay_protected, Ok(()));
+        assert_eq!(white_listed, Ok(()));
+        assert_eq!(not_replay_protected, Err(Eip155Error::NotReplayProtected(chain_id)));
+        assert_eq!(wrong_chain_id, Err(Eip155Error::ChainIdMismatch { got: 1, expected: chain_id }));
+    }
+}
@@ -1,19 +1,30 @@
 use crate::{
+    models::transaction::rpc_transaction_to_raw,
     providers::eth_provider::{
-        error::{EthApiError, SignatureError},
+        database::{
+            trace_cache::{trace_cache_key, TraceCacheStore, TRACE_CACHE_TTL},
+            types::{receipt::is_deposit_receipt, transaction::is_deposit},
+            Database,
+        },
         provider::{EthApiResult, EthereumProvider},
+        receipts::encode_receipt,
+    },
+    tracing::{
+        builder::TracerBuilder,
+        diff::{transaction_executed_response, ExecutionOutcome, TransactionDiff},
+        limits::is_public_tier,
     },
-    tracing::builder::TracerBuilder,
 };
-use alloy_eips::{eip2718::Encodable2718, BlockId, BlockNumberOrTag};
+use alloy_eips::{BlockId, BlockNumberOrTag};
 use alloy_primitives::{Bytes, B256};
 use alloy_rlp::Encodable;
 use alloy_rpc_types::TransactionRequest;
 use alloy_rpc_types_trace::geth::{GethDebugTracingCallOptions, GethDebugTracingOptions, GethTrace, TraceResult};
 use async_trait::async_trait;
 use auto_impl::auto_impl;
-use reth_primitives::{Block, Header, Log, Receipt, ReceiptWithBloom, TransactionSigned};
+use reth_primitives::{Block, Header};
 use std::sync::Arc;
+use tokio::sync::mpsc;
 
 #[async_trait]
 #[auto_impl(Arc, &)]
@@ -33,6 +44,14 @@ pub trait DebugProvider {
         block_hash: B256,
         opts: Option<GethDebugTracingOptions>,
     ) -> EthApiResult<Vec<TraceResult>>;
+    /// Traces the block one transaction at a time, sending the traces to the sender as soon as they
+    /// are computed. Stops early if the receiver is dropped.
+    async fn trace_block_streamed(
+        &self,
+        block_id: BlockId,
+        opts: Option<GethDebugTracingOptions>,
+        sender: mpsc::Sender<TraceResult>,
+    ) -> EthApiResult<()>;
     async fn trace_transaction(
         &self,
         transaction_hash: B256,
@@ -44,16 +63,46 @@ pub trait DebugProvider {
         block_number: Option<BlockId>,
         opts: Option<GethDebugTracingCallOptions>,
     ) -> EthApiResult<GethTrace>;
+    /// Re-executes the mined transaction with the local EVM and compares the outcome with the
+    /// execution by Kakarot. Returns `None` if the transaction is not mined.
+    async fn diff_transaction(&self, transaction_hash: B256) -> EthApiResult<Option<TransactionDiff>>;
 }
 
 #[derive(Debug, Clone)]
 pub struct DebugDataProvider<P: EthereumProvider> {
     eth_provider: P,
+    /// The database caching the transaction traces, if the cache is enabled.
+    trace_cache: Option<Database>,
 }
 
 impl<P: EthereumProvider> DebugDataProvider<P> {
     pub const fn new(eth_provider: P) -> Self {
-        Self { eth_provider }
+        Self { eth_provider, trace_cache: None }
+    }
+
+    /// Caches the transaction traces in the database, if any.
+    #[must_use]
+    pub fn with_trace_cache(mut self, database: Option<Database>) -> Self {
+        self.trace_cache = database;
+        self
+    }
+}
+
+impl<P: EthereumProvider + Send + Sync + 'static> DebugDataProvider<P> {
+    /// Traces the transaction by re-executing its block up to it.
+    async fn compute_transaction_trace(
+        &self,
+        transaction_hash: B256,
+        opts: GethDebugTracingOptions,
+    ) -> EthApiResult<GethTrace> {
+        let tracer = TracerBuilder::new(Arc::new(&self.eth_provider))
+            .await?
+            .with_transaction_hash(transaction_hash)
+            .await?
+            .with_tracing_options(opts.into())
+            .build()?;
+
+        Ok(tracer.debug_transaction(transaction_hash)?)
     }
 }
 
@@ -83,87 +132,25 @@ impl<P: EthereumProvider + Send + Sync + 'static> DebugProvider for DebugDataPro
 
     async fn raw_transaction(&self, hash: B256) -> EthApiResult<Option<Bytes>> {
         let transaction = self.eth_provider.transaction_by_hash(hash).await?;
-
-        if let Some(tx) = transaction {
-            let signature = tx.signature.ok_or_else(|| EthApiError::from(SignatureError::MissingSignature))?;
-            let bytes = TransactionSigned::from_transaction_and_signature(
-                tx.try_into()?,
-                alloy_primitives::Signature::from_rs_and_parity(
-                    signature.r,
-                    signature.s,
-                    signature.y_parity.map_or(false, |v| v.0),
-                )
-                .expect("Invalid signature"),
-            )
-            .encoded_2718()
-            .into();
-            Ok(Some(bytes))
-        } else {
-            Ok(None)
+        // Serve the bytes as submitted if the transaction was sent to the node
+        if transaction.is_some() {
+            if let Some(raw) = self.eth_provider.submitted_raw_transaction(hash).await? {
+                return Ok(Some(raw));
+            }
         }
+        transaction.map(rpc_transaction_to_raw).transpose()
     }
 
     async fn raw_transactions(&self, block_id: BlockId) -> EthApiResult<Vec<Bytes>> {
         let transactions = self.eth_provider.block_transactions(Some(block_id)).await?.unwrap_or_default();
-        let mut raw_transactions = Vec::with_capacity(transactions.len());
-
-        for t in transactions {
-            let signature = t.signature.ok_or_else(|| EthApiError::from(SignatureError::MissingSignature))?;
-            let bytes = TransactionSigned::from_transaction_and_signature(
-                t.try_into()?,
-                alloy_primitives::Signature::from_rs_and_parity(
-                    signature.r,
-                    signature.s,
-                    signature.y_parity.map_or(false, |v| v.0),
-                )
-                .expect("Invalid signature"),
-            )
-            .encoded_2718()
-            .into();
-            raw_transactions.push(bytes);
-        }
-
-        Ok(raw_transactions)
+        // The deposit transactions have no encoding
+        transactions.into_iter().filter(|transaction| !is_deposit(transaction)).map(rpc_transaction_to_raw).collect()
     }
 
     async fn raw_receipts(&self, block_id: BlockId) -> EthApiResult<Vec<Bytes>> {
         let receipts = self.eth_provider.block_receipts(Some(block_id)).await?.unwrap_or_default();
-
-        // Initializes an empty vector to store the raw receipts
-        let mut raw_receipts = Vec::with_capacity(receipts.len());
-
-        // Iterates through the receipts of the block using the `block_receipts` method of the Ethereum API
-        for receipt in receipts {
-            // Converts the transaction type to a u8 and then tries to convert it into TxType
-            let tx_type = Into::<u8>::into(receipt.transaction_type()).try_into()?;
-
-            // Tries to convert the cumulative gas used to u64
-            let cumulative_gas_used = receipt.inner.inner.cumulative_gas_used().try_into()?;
-
-            // Creates a ReceiptWithBloom from the receipt data
-            raw_receipts.push(
-                ReceiptWithBloom {
-                    receipt: Receipt {
-                        tx_type,
-                        success: receipt.inner.status(),
-                        cumulative_gas_used,
-                        logs: receipt
-                            .inner
-                            .inner
-                            .logs()
-                            .iter()
-                            .filter_map(|log| Log::new(log.address(), log.topics().to_vec(), log.data().data.clone()))
-                            .collect(),
-                    },
-                    bloom: *receipt.inner.inner.logs_bloom(),
-                }
-                .encoded_2718()
-                .into(),
-            );
-        }
-
-        // Returns the vector containing the raw receipts
-        Ok(raw_receipts)
+        // The deposit receipts have no encoding
+        receipts.iter().filter(|receipt| !is_deposit_receipt(receipt)).map(encode_receipt).collect()
     }
 
     async fn trace_block_by_number(
@@ -197,19 +184,74 @@ impl<P: EthereumProvider + Send + Sync + 'static> DebugProvider for DebugDataPro
         Ok(tracer.debug_block()?)
     }
 
-    async fn trace_transaction(
+    async fn trace_block_streamed(
         &self,
-        transaction_hash: B256,
+        block_id: BlockId,
         opts: Option<GethDebugTracingOptions>,
-    ) -> EthApiResult<GethTrace> {
+        sender: mpsc::Sender<TraceResult>,
+    ) -> EthApiResult<()> {
         let tracer = TracerBuilder::new(Arc::new(&self.eth_provider))
             .await?
-            .with_transaction_hash(transaction_hash)
+            .with_block_id(block_id)
             .await?
             .with_tracing_options(opts.unwrap_or_default().into())
             .build()?;
 
-        Ok(tracer.debug_transaction(transaction_hash)?)
+        for traces in tracer.debug_block_traces() {
+            for trace in traces? {
+                if sender.send(trace).await.is_err() {
+                    // The subscriber is gone, no need to trace the remaining transactions
+                    return Ok(());
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn trace_transaction(
+        &self,
+        transaction_hash: B256,
+        opts: Option<GethDebugTracingOptions>,
+    ) -> EthApiResult<GethTrace> {
+        let opts = opts.unwrap_or_default();
+        // The traces of the public tier are computed with lower limits, they are never cached
+        let Some(cache) = self.trace_cache.as_ref().filter(|_| !is_public_tier()) else {
+            return self.compute_transaction_trace(transaction_hash, opts).await;
+        };
+        // Only the traces of the mined transactions are cached
+        let Some(block_hash) = self
+            .eth_provider
+            .transaction_by_hash(transaction_hash)
+            .await?
+            .and_then(|transaction| transaction.block_hash)
+        else {
+            return self.compute_transaction_trace(transaction_hash, opts).await;
+        };
+
+        let key = trace_cache_key(transaction_hash, block_hash, &opts);
+        match cache.cached_trace(key).await {
+            Ok(Some(trace)) => {
+                tracing::info!(target: "trace_cache", monotonic_counter.trace_cache_hits = 1, ?transaction_hash);
+                return Ok(trace);
+            }
+            Ok(None) => {
+                tracing::info!(target: "trace_cache", monotonic_counter.trace_cache_misses = 1, ?transaction_hash);
+            }
+            Err(err) => tracing::warn!(target: "trace_cache", ?err, ?transaction_hash, "failed to read cached trace"),
+        }
+
+        // The traces cached while the transaction was in a block since reorged out are stale
+        if let Err(err) = cache.invalidate_traces(transaction_hash, block_hash).await {
+            tracing::warn!(target: "trace_cache", ?err, ?transaction_hash, "failed to invalidate cached traces");
+        }
+
+        let trace = self.compute_transaction_trace(transaction_hash, opts).await?;
+        if let Err(err) = cache.cache_trace(key, transaction_hash, block_hash, &trace, *TRACE_CACHE_TTL).await {
+            tracing::warn!(target: "trace_cache", ?err, ?transaction_hash, "failed to cache trace");
+        }
+
+        Ok(trace)
     }
 
     async fn trace_call(
@@ -218,13 +260,45 @@ impl<P: EthereumProvider + Send + Sync + 'static> DebugProvider for DebugDataPro
         block_number: Option<BlockId>,
         opts: Option<GethDebugTracingCallOptions>,
     ) -> EthApiResult<GethTrace> {
-        let tracer = TracerBuilder::new(Arc::new(&self.eth_provider))
+        let opts = opts.unwrap_or_default();
+        let mut builder = TracerBuilder::new(Arc::new(&self.eth_provider))
             .await?
             .with_block_id(block_number.unwrap_or_default())
+            .await?;
+        if let Some(block_overrides) = opts.block_overrides.clone() {
+            builder = builder.with_block_overrides(block_overrides);
+        }
+        let tracer = builder.with_tracing_options(opts.into()).build()?;
+
+        Ok(tracer.debug_transaction_request(&request)?)
+    }
+
+    async fn diff_transaction(&self, transaction_hash: B256) -> EthApiResult<Option<TransactionDiff>> {
+        let Some(receipt) = self.eth_provider.transaction_receipt(transaction_hash).await? else {
+            return Ok(None);
+        };
+
+        let tracer = TracerBuilder::new(Arc::new(&self.eth_provider))
+            .await?
+            .with_transaction_hash(transaction_hash)
             .await?
-            .with_tracing_options(opts.unwrap_or_default().into())
             .build()?;
+        let local = tracer.execute_transaction(transaction_hash)?.into();
 
-        Ok(tracer.debug_transaction_request(&request)?)
+        // The return data is only known for the transactions relayed by this node
+        let return_data = self
+            .eth_provider
+            .receipt_proof(transaction_hash)
+            .await?
+            .and_then(|proof| proof.starknet)
+            .and_then(|starknet| transaction_executed_response(&starknet.events));
+        let kakarot = ExecutionOutcome {
+            success: receipt.inner.status(),
+            gas_used: receipt.inner.gas_used.try_into()?,
+            return_data,
+            logs: receipt.inner.inner.logs().iter().map(|log| log.inner.clone()).collect(),
+        };
+
+        Ok(Some(TransactionDiff::new(transaction_hash, local, kakarot)))
     }
 }
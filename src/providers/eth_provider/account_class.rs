@@ -0,0 +1,74 @@
+//! Class of the Starknet contracts of the accounts at the historical blocks.
+//!
+//! Kakarot upgrades the class of its accounts, the entrypoints of a legacy class differing from
+//! the ones of the current class. The class of an account at a historical block is recorded in
+//! the database each time it is fetched from Starknet, along with the range of blocks it was
+//! observed over, so that the historical queries keep detecting the legacy accounts once their
+//! state is no longer served by the Starknet node.
+
+use super::{
+    database::account::AccountClassStore,
+    error::KakarotError,
+    provider::{EthApiResult, EthDataProvider},
+    starknet::kakarot_core::starknet_address,
+};
+use crate::constants::KAKAROT_RPC_CONFIG;
+use alloy_eips::BlockId;
+use alloy_primitives::Address;
+use starknet::{
+    core::types::{Felt, StarknetError},
+    providers::{Provider, ProviderError},
+};
+use tracing::Instrument;
+
+impl<SP> EthDataProvider<SP>
+where
+    SP: Provider + Send + Sync,
+{
+    /// Returns the class hash of the contract of the account at the given block, or `None` if the
+    /// contract isn't deployed at the block.
+    pub(crate) async fn account_class_hash(
+        &self,
+        address: Address,
+        block_id: Option<BlockId>,
+    ) -> EthApiResult<Option<Felt>> {
+        let starknet_address = starknet_address(address);
+        let block_number = self.historical_block_number(block_id).await?;
+        if let Some(number) = block_number {
+            if let Some(class_hash) = self.database().observed_class_hash(starknet_address, number).await? {
+                return Ok(Some(class_hash));
+            }
+        }
+
+        let starknet_block_id = self.to_starknet_block_id(block_id).await?;
+        let span = tracing::span!(tracing::Level::INFO, "sn::class_hash_at");
+        let class_hash = match self
+            .starknet_provider_inner()
+            .get_class_hash_at(starknet_block_id, starknet_address)
+            .instrument(span)
+            .await
+        {
+            Ok(class_hash) => class_hash,
+            Err(ProviderError::StarknetError(StarknetError::ContractNotFound)) => return Ok(None),
+            Err(err) => return Err(KakarotError::from(err).into()),
+        };
+
+        // The observation only saves a later call to Starknet, failing to record it isn't fatal
+        if let Some(number) = block_number {
+            if let Err(err) = self.database().observe_class_hash(starknet_address, class_hash, number).await {
+                tracing::warn!(target: "account_class", ?err, "failed to record the class hash");
+            }
+        }
+
+        Ok(Some(class_hash))
+    }
+
+    /// Returns true if the contract of the account has a legacy class at the given block, i.e. a
+    /// class which is neither the current account class nor the uninitialized account class.
+    pub(crate) async fn is_legacy_account(&self, address: Address, block_id: Option<BlockId>) -> EthApiResult<bool> {
+        Ok(self.account_class_hash(address, block_id).await?.is_some_and(|class_hash| {
+            class_hash != KAKAROT_RPC_CONFIG.account_contract_class_hash
+                && class_hash != KAKAROT_RPC_CONFIG.uninitialized_account_class_hash
+        }))
+    }
+}
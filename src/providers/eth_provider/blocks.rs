@@ -1,21 +1,26 @@
 use super::{
-    database::{
-        ethereum::EthereumBlockStore,
-        types::{header::ExtendedBlock, transaction::ExtendedTransaction},
-    },
-    error::KakarotError,
-};
-use crate::providers::eth_provider::{
-    database::ethereum::EthereumTransactionStore,
-    provider::{EthApiResult, EthDataProvider},
+    database::types::{header::ExtendedBlock, transaction::ExtendedTransaction},
+    provider::EthApiResult,
 };
 use alloy_eips::{BlockId, BlockNumberOrTag};
 use alloy_primitives::{B256, U256, U64};
 use alloy_rpc_types::Header;
 use async_trait::async_trait;
 use auto_impl::auto_impl;
-use mongodb::bson::doc;
-use tracing::Instrument;
+#[cfg(feature = "mongo")]
+use {
+    super::{
+        database::{
+            ethereum::{EthereumBlockStore, EthereumTransactionStore},
+            types::header::STARKNET_BLOCK_HASH_FIELD,
+        },
+        error::KakarotError,
+        provider::EthDataProvider,
+    },
+    mongodb::bson::doc,
+    tracing::Instrument,
+};
+
 /// Ethereum block provider trait.
 #[async_trait]
 #[auto_impl(Arc, &)]
@@ -33,6 +38,10 @@ pub trait BlockProvider {
     async fn block_by_number(&self, number_or_tag: BlockNumberOrTag, full: bool)
         -> EthApiResult<Option<ExtendedBlock>>;
 
+    /// Returns a block by the hash of its originating Starknet block, whether or not blocks are
+    /// served with their EVM-style hash. Block can be full or just the hashes of the transactions.
+    async fn block_by_starknet_hash(&self, starknet_hash: B256, full: bool) -> EthApiResult<Option<ExtendedBlock>>;
+
     /// Returns the transaction count for a block by hash.
     async fn block_transaction_count_by_hash(&self, hash: B256) -> EthApiResult<Option<U256>>;
 
@@ -43,6 +52,7 @@ pub trait BlockProvider {
     async fn block_transactions(&self, block_id: Option<BlockId>) -> EthApiResult<Option<Vec<ExtendedTransaction>>>;
 }
 
+#[cfg(feature = "mongo")]
 #[async_trait]
 impl<SP> BlockProvider for EthDataProvider<SP>
 where
@@ -71,7 +81,8 @@ where
     }
 
     async fn block_by_hash(&self, hash: B256, full: bool) -> EthApiResult<Option<ExtendedBlock>> {
-        Ok(self.database().block(hash.into(), full).await?)
+        let block = self.database().block(hash.into(), full).await?;
+        self.with_starknet_block(block).await
     }
 
     async fn block_by_number(
@@ -80,7 +91,13 @@ where
         full: bool,
     ) -> EthApiResult<Option<ExtendedBlock>> {
         let block_number = self.tag_into_block_number(number_or_tag).await?;
-        Ok(self.database().block(block_number.into(), full).await?)
+        let block = self.database().block(block_number.into(), full).await?;
+        self.with_starknet_block(block).await
+    }
+
+    async fn block_by_starknet_hash(&self, starknet_hash: B256, full: bool) -> EthApiResult<Option<ExtendedBlock>> {
+        let block = self.database().block_by_starknet_hash(&starknet_hash, full).await?;
+        self.with_starknet_block(block).await
     }
 
     async fn block_transaction_count_by_hash(&self, hash: B256) -> EthApiResult<Option<U256>> {
@@ -103,3 +120,40 @@ where
         Ok(Some(self.database().transactions(block_hash_or_number).await?))
     }
 }
+
+#[cfg(feature = "mongo")]
+impl<SP> EthDataProvider<SP>
+where
+    SP: starknet::providers::Provider + Send + Sync,
+{
+    /// Adds the hash and the number of the originating Starknet block to the `starknetBlockHash`
+    /// and `starknetBlockNumber` extra fields of the block.
+    ///
+    /// The Starknet hash is the hash of the block, unless the indexer replaced it with the keccak
+    /// hash of its RLP encoded header and stored the Starknet hash aside, see
+    /// [`EVM_BLOCK_HASH`](super::constant::EVM_BLOCK_HASH).
+    async fn with_starknet_block(&self, block: Option<ExtendedBlock>) -> EthApiResult<Option<ExtendedBlock>> {
+        let Some(mut block) = block else {
+            return Ok(None);
+        };
+
+        // Pending blocks don't have a hash
+        if block.header.hash.is_zero() {
+            return Ok(Some(block));
+        }
+
+        // Kakarot blocks have the hash and the number of the Starknet block they are built from
+        if !block.other.contains_key(STARKNET_BLOCK_HASH_FIELD) {
+            block.other.insert(
+                STARKNET_BLOCK_HASH_FIELD.to_string(),
+                serde_json::Value::String(block.header.hash.to_string()),
+            );
+        }
+        block.other.insert(
+            "starknetBlockNumber".to_string(),
+            serde_json::Value::String(format!("{:#x}", block.header.number)),
+        );
+
+        Ok(Some(block))
+    }
+}
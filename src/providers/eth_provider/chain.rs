@@ -1,13 +1,16 @@
-use crate::providers::eth_provider::{
-    error::KakarotError,
-    provider::{EthApiResult, EthDataProvider},
-};
-use alloy_primitives::{U256, U64};
-use alloy_rpc_types::{SyncInfo, SyncStatus};
+use crate::providers::eth_provider::provider::EthApiResult;
+use alloy_primitives::U64;
+use alloy_rpc_types::SyncStatus;
 use async_trait::async_trait;
 use auto_impl::auto_impl;
-use starknet::core::types::SyncStatusType;
-use tracing::Instrument;
+#[cfg(feature = "mongo")]
+use {
+    crate::providers::eth_provider::{error::KakarotError, provider::EthDataProvider},
+    alloy_primitives::U256,
+    alloy_rpc_types::SyncInfo,
+    starknet::core::types::SyncStatusType,
+    tracing::Instrument,
+};
 
 #[async_trait]
 #[auto_impl(Arc, &)]
@@ -19,6 +22,7 @@ pub trait ChainProvider {
     async fn chain_id(&self) -> EthApiResult<Option<U64>>;
 }
 
+#[cfg(feature = "mongo")]
 #[async_trait]
 impl<SP> ChainProvider for EthDataProvider<SP>
 where
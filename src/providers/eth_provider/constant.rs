@@ -1,15 +1,197 @@
-use alloy_primitives::{B256, U256};
+use super::{error::EthApiError, starknet::NATIVE_TOKEN};
+use crate::{constants::ETH_CHAIN_ID, eth_rpc::openrpc::SchemaOf};
+use alloy_consensus::constants::{
+    EIP1559_TX_TYPE_ID, EIP2930_TX_TYPE_ID, EIP4844_TX_TYPE_ID, EIP7702_TX_TYPE_ID, LEGACY_TX_TYPE_ID,
+};
+use alloy_primitives::{address, Address, B256, U256, U64};
+use reth_revm::primitives::{MAX_CODE_SIZE, MAX_INITCODE_SIZE};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use starknet::core::types::Felt;
-use std::{str::FromStr, sync::LazyLock};
+use std::{str::FromStr, sync::LazyLock, time::Duration};
 
-/// Maximum priority fee per gas
-pub static MAX_PRIORITY_FEE_PER_GAS: LazyLock<u64> = LazyLock::new(|| 0);
+/// Maximum priority fee per gas, read from the `MAX_PRIORITY_FEE_PER_GAS` environment variable.
+/// The priority fees suggested by `kakarot_suggestFees` are clamped to it. Defaults to 0.
+pub static MAX_PRIORITY_FEE_PER_GAS: LazyLock<u64> = LazyLock::new(|| {
+    std::env::var("MAX_PRIORITY_FEE_PER_GAS").ok().and_then(|val| u64::from_str(&val).ok()).unwrap_or_default()
+});
 
 /// Maximum number of logs that can be fetched in a single request
 pub static MAX_LOGS: LazyLock<Option<u64>> =
     LazyLock::new(|| std::env::var("MAX_LOGS").ok().and_then(|val| u64::from_str(&val).ok()));
 
+/// If set, blocks are identified by the keccak hash of their RLP encoded header instead of the
+/// Starknet block hash, which is always returned in the `starknetBlockHash` extra field. The
+/// hashes are computed and stored by the indexer, which must be run with the same setting.
+pub static EVM_BLOCK_HASH: LazyLock<bool> =
+    LazyLock::new(|| std::env::var("EVM_BLOCK_HASH").ok().and_then(|val| bool::from_str(&val).ok()).unwrap_or(false));
+
+/// The mode of the node, read from the `NODE_MODE` environment variable. Defaults to [`NodeMode::Archive`].
+pub static NODE_MODE: LazyLock<NodeMode> =
+    LazyLock::new(|| std::env::var("NODE_MODE").ok().and_then(|val| NodeMode::from_str(&val).ok()).unwrap_or_default());
+
+/// Number of blocks for which the state is kept by a node running in [`NodeMode::Full`].
+pub static HISTORY_RETENTION_BLOCKS: LazyLock<u64> = LazyLock::new(|| {
+    std::env::var("HISTORY_RETENTION_BLOCKS").ok().and_then(|val| u64::from_str(&val).ok()).unwrap_or(128)
+});
+
+/// The handling of the priority fee of the transactions, read from the `PRIORITY_FEE_POLICY`
+/// environment variable. Defaults to [`PriorityFeePolicy::Ignored`], an invalid policy is rejected
+/// rather than read as the default.
+pub static PRIORITY_FEE_POLICY: LazyLock<PriorityFeePolicy> = LazyLock::new(|| {
+    std::env::var("PRIORITY_FEE_POLICY")
+        .map(|val| PriorityFeePolicy::from_str(&val).expect("invalid PRIORITY_FEE_POLICY"))
+        .unwrap_or_default()
+});
+
+/// Interval after which the number of the last block accepted on L1 is looked up again.
+pub const FINALIZED_BLOCK_REFRESH_INTERVAL: Duration = Duration::from_secs(30);
+
+/// The mode of the node, which defines for which blocks the state can be queried.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NodeMode {
+    /// Only the state of the last [`HISTORY_RETENTION_BLOCKS`] blocks is served.
+    Full,
+    /// The state of every block is served.
+    #[default]
+    Archive,
+}
+
+impl NodeMode {
+    /// Ensures the state of the block `number` is served by a node in this mode, `latest` being
+    /// the number of the latest block and `retention` the number of blocks whose state is kept in
+    /// [`NodeMode::Full`].
+    pub fn ensure_state_available(self, number: u64, latest: u64, retention: u64) -> Result<(), EthApiError> {
+        if self == Self::Full && number.saturating_add(retention) < latest {
+            return Err(EthApiError::HistoryPruned(number));
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for NodeMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "full" => Ok(Self::Full),
+            "archive" => Ok(Self::Archive),
+            _ => Err(format!("invalid node mode {s}, expected one of: full, archive")),
+        }
+    }
+}
+
+/// The handling of the `maxPriorityFeePerGas` of the transactions by Kakarot, which must match
+/// the configuration of the deployed Kakarot contracts.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum PriorityFeePolicy {
+    /// The priority fee isn't charged, it only orders the transactions in the mempool.
+    #[default]
+    Ignored,
+    /// The priority fee is charged on top of the base fee and forwarded to the relayer of the
+    /// transaction, as a bonus covering its Starknet fees.
+    Forwarded,
+    /// The priority fee is charged on top of the base fee and burned.
+    Burned,
+}
+
+impl PriorityFeePolicy {
+    /// Returns true if the priority fee is charged to the sender of the transaction.
+    pub const fn is_charged(self) -> bool {
+        !matches!(self, Self::Ignored)
+    }
+
+    /// Returns the priority fee per gas charged to a transaction included in a block of the given
+    /// base fee: the priority fee capped by the maximum fee above the base fee, as in EIP-1559. The
+    /// priority fee of a legacy transaction, without a maximum priority fee, is its gas price
+    /// above the base fee.
+    pub fn effective_priority_fee(
+        self,
+        max_fee_per_gas: u128,
+        max_priority_fee_per_gas: Option<u128>,
+        base_fee: u128,
+    ) -> u128 {
+        if !self.is_charged() {
+            return 0;
+        }
+        let headroom = max_fee_per_gas.saturating_sub(base_fee);
+        max_priority_fee_per_gas.map_or(headroom, |tip| tip.min(headroom))
+    }
+}
+
+impl FromStr for PriorityFeePolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "ignored" => Ok(Self::Ignored),
+            "forwarded" => Ok(Self::Forwarded),
+            "burned" => Ok(Self::Burned),
+            _ => Err(format!("invalid priority fee policy {s}, expected one of: ignored, forwarded, burned")),
+        }
+    }
+}
+
+/// Default number of transactions returned by a page of the address history
+pub const ADDRESS_HISTORY_DEFAULT_PAGE_SIZE: u64 = 100;
+
+/// Maximum number of transactions returned by a page of the address history
+pub const ADDRESS_HISTORY_MAX_PAGE_SIZE: u64 = 1000;
+
+/// Default number of logs returned by a page of a log query
+pub const LOG_QUERY_DEFAULT_LIMIT: u64 = 100;
+
+/// Maximum number of logs returned by a page of a log query
+pub const LOG_QUERY_MAX_LIMIT: u64 = 1000;
+
+/// Maximum number of blocks covered by a log query
+pub const LOG_QUERY_MAX_BLOCK_RANGE: u64 = 100_000;
+
+/// Maximum number of addresses, or of topics at a position, matched by a log query
+pub const LOG_QUERY_MAX_SET_SIZE: usize = 100;
+
+/// Gas added to the estimate of a contract creation for each contract deployed, covering the
+/// deployment of the Starknet account backing the EVM contract
+pub const CONTRACT_DEPLOYMENT_GAS_OVERHEAD: u128 = 50_000;
+
+/// If set, the gas estimates include the overhead of the Starknet invoke transaction of the
+/// relayer wrapping the Ethereum transaction, for the users paying their own relayer.
+pub static ESTIMATE_GAS_WITH_RELAYER_OVERHEAD: LazyLock<bool> = LazyLock::new(|| {
+    std::env::var("ESTIMATE_GAS_WITH_RELAYER_OVERHEAD").ok().and_then(|val| bool::from_str(&val).ok()).unwrap_or(false)
+});
+
+/// Gas of the Starknet invoke transaction of the relayer, regardless of its calldata: the
+/// validation of the relayer account and the `execute_from_outside` call to the Kakarot account
+pub const RELAYER_INVOKE_GAS_OVERHEAD: u128 = 21_000;
+
+/// Gas of each felt of the calldata of the Starknet invoke transaction of the relayer
+pub const RELAYER_CALLDATA_FELT_GAS: u128 = 128;
+
+/// Felts of the state diff posted to L1 for any relayed transaction: the nonce of the relayer,
+/// the nonce of the Kakarot account and the fee token balances of the relayer and the sequencer
+pub const DA_BASE_FELTS: u64 = 12;
+
+/// Felts of the state diff posted to L1 for each 32 bytes word of calldata. The storage writes of
+/// a transaction are only known once executed, they are approximated by one slot (key and value)
+/// per word of calldata, e.g. the balances updated by a token transfer
+pub const DA_FELTS_PER_CALLDATA_WORD: u64 = 2;
+
+/// L1 data gas (blob gas) of each felt posted to L1 in the blob data availability mode
+pub const DA_DATA_GAS_PER_FELT: u64 = 32;
+
+/// L1 gas of each felt posted to L1 in the calldata data availability mode
+pub const DA_L1_GAS_PER_FELT: u64 = 512;
+
+/// Number of recent blocks used to measure the fullness and the priority fees of the chain when
+/// suggesting fees
+pub const FEE_SUGGESTION_BLOCK_COUNT: u64 = 20;
+
+/// Maximum number of blocks returned by `eth_feeHistory`, the older blocks of a larger range being
+/// left out
+pub const FEE_HISTORY_MAX_BLOCK_COUNT: u64 = 1024;
+
 /// Gas limit for estimate gas and call
 pub const CALL_REQUEST_GAS_LIMIT: u64 = 50_000_000;
 /// Number of characters for representing a U256 in a hex string form. Used for padding hashes
@@ -26,7 +208,7 @@ pub const ADDRESS_HEX_STRING_LEN: usize = 40;
 pub const STARKNET_MODULUS: U256 = U256::from_limbs([0x1, 0, 0, 0x0800_0000_0000_0011]);
 
 /// Struct used to return the constant values from the `kakarot_getConfig` endpoint
-#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
 pub struct Constant {
     /// Maximum number of logs to output for `eth_getLogs` RPC Method
     pub max_logs: Option<u64>,
@@ -35,9 +217,128 @@ pub struct Constant {
     /// Maximum number of Felts in calldata.
     pub max_felts_in_calldata: usize,
     /// List of whitelisted hashes allow to submit pre EIP-155 transactions.
+    #[schemars(with = "SchemaOf<Vec<B256>>")]
     pub white_listed_eip_155_transaction_hashes: Vec<B256>,
     /// Kakarot address the RPC points to.
+    #[schemars(with = "SchemaOf<Felt>")]
     pub kakarot_address: Felt,
+    /// The handling of the priority fee of the transactions.
+    #[serde(default)]
+    pub priority_fee_policy: PriorityFeePolicy,
+}
+
+/// The EVM capabilities of Kakarot, returned by the `kakarot_getCapabilities` endpoint.
+pub static KAKAROT_CAPABILITIES: LazyLock<Capabilities> = LazyLock::new(|| Capabilities {
+    hardfork: "cancun".to_string(),
+    precompiles: [
+        (address!("0000000000000000000000000000000000000001"), "ecrecover"),
+        (address!("0000000000000000000000000000000000000002"), "sha256"),
+        (address!("0000000000000000000000000000000000000003"), "ripemd160"),
+        (address!("0000000000000000000000000000000000000004"), "identity"),
+        (address!("0000000000000000000000000000000000000005"), "modexp"),
+        (address!("0000000000000000000000000000000000000006"), "ecAdd"),
+        (address!("0000000000000000000000000000000000000007"), "ecMul"),
+        (address!("0000000000000000000000000000000000000009"), "blake2f"),
+        (address!("0000000000000000000000000000000000000100"), "p256Verify"),
+        (address!("0000000000000000000000000000000000075001"), "cairoCall"),
+        (address!("0000000000000000000000000000000000075002"), "cairoMessage"),
+        (address!("0000000000000000000000000000000000075003"), "cairoMulticall"),
+    ]
+    .into_iter()
+    .map(|(address, name)| Precompile { address, name: name.to_string() })
+    .collect(),
+    max_code_size: MAX_CODE_SIZE,
+    max_init_code_size: MAX_INITCODE_SIZE,
+    supported_transaction_types: vec![LEGACY_TX_TYPE_ID, EIP2930_TX_TYPE_ID, EIP1559_TX_TYPE_ID],
+    unsupported_transaction_types: vec![EIP4844_TX_TYPE_ID, EIP7702_TX_TYPE_ID],
+});
+
+/// Struct used to return the EVM capabilities from the `kakarot_getCapabilities` endpoint
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct Capabilities {
+    /// The EVM hardfork supported by Kakarot.
+    pub hardfork: String,
+    /// The enabled precompiles, including the Kakarot specific Cairo precompiles.
+    pub precompiles: Vec<Precompile>,
+    /// Maximum size of the code of a contract, in bytes.
+    pub max_code_size: usize,
+    /// Maximum size of the init code of a contract creation, in bytes.
+    pub max_init_code_size: usize,
+    /// The types of the transactions accepted by the node. Legacy transactions must be EIP-155
+    /// protected, unless their hash is whitelisted.
+    pub supported_transaction_types: Vec<u8>,
+    /// The types of the transactions rejected by the node.
+    pub unsupported_transaction_types: Vec<u8>,
+}
+
+/// An enabled precompile.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct Precompile {
+    /// The address of the precompile.
+    #[schemars(with = "SchemaOf<Address>")]
+    pub address: Address,
+    /// The name of the precompile.
+    pub name: String,
+}
+
+/// The metadata of the chain, returned by the `kakarot_getChainMetadata` endpoint. The chain name,
+/// the native currency name and the urls are set with the `CHAIN_NAME`,
+/// `CHAIN_NATIVE_CURRENCY_NAME`, `CHAIN_RPC_URLS` and `CHAIN_BLOCK_EXPLORER_URLS` environment
+/// variables.
+pub static CHAIN_METADATA: LazyLock<ChainMetadata> = LazyLock::new(|| {
+    let urls = |key: &str| {
+        std::env::var(key)
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|url| !url.is_empty())
+            .map(ToString::to_string)
+            .collect()
+    };
+    let symbol = NATIVE_TOKEN.symbol.clone();
+    ChainMetadata {
+        chain_id: U64::from(*ETH_CHAIN_ID),
+        chain_name: std::env::var("CHAIN_NAME").unwrap_or_else(|_| "Kakarot".to_string()),
+        native_currency: NativeCurrency {
+            name: std::env::var("CHAIN_NATIVE_CURRENCY_NAME").unwrap_or_else(|_| symbol.clone()),
+            symbol,
+            // The balances are always served in wei
+            decimals: 18,
+        },
+        rpc_urls: urls("CHAIN_RPC_URLS"),
+        block_explorer_urls: urls("CHAIN_BLOCK_EXPLORER_URLS"),
+    }
+});
+
+/// Struct used to return the metadata of the chain from the `kakarot_getChainMetadata` endpoint, in
+/// the EIP-3085 format of the `wallet_addEthereumChain` parameters.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ChainMetadata {
+    /// The hex encoded chain id.
+    #[schemars(with = "SchemaOf<U64>")]
+    pub chain_id: U64,
+    /// The name of the chain.
+    pub chain_name: String,
+    /// The native currency of the chain.
+    pub native_currency: NativeCurrency,
+    /// The urls of the RPC endpoints of the chain.
+    pub rpc_urls: Vec<String>,
+    /// The urls of the block explorers of the chain, omitted if none is configured.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub block_explorer_urls: Vec<String>,
+}
+
+/// The native currency of the chain, as displayed by the wallets.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct NativeCurrency {
+    /// The name of the currency.
+    pub name: String,
+    /// The symbol of the currency.
+    pub symbol: String,
+    /// The number of decimals of the balances.
+    pub decimals: u8,
 }
 
 #[cfg(feature = "hive")]
@@ -91,3 +392,75 @@ pub mod forwarding {
             .expect("Invalid MAIN_RPC_URL environment variable")
     });
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_node_mode_from_str() {
+        assert_eq!(NodeMode::from_str("full").unwrap(), NodeMode::Full);
+        assert_eq!(NodeMode::from_str("Archive").unwrap(), NodeMode::Archive);
+        assert!(NodeMode::from_str("light").is_err());
+    }
+
+    #[test]
+    fn test_node_mode_full_history_pruned() {
+        // Given
+        let latest = 1000;
+        let retention = 128;
+
+        // When
+        let boundary = NodeMode::Full.ensure_state_available(latest - retention, latest, retention);
+        let pruned = NodeMode::Full.ensure_state_available(latest - retention - 1, latest, retention);
+        let genesis = NodeMode::Full.ensure_state_available(0, latest, retention);
+
+        // Then
+        assert!(boundary.is_ok());
+        assert!(matches!(pruned, Err(EthApiError::HistoryPruned(number)) if number == latest - retention - 1));
+        assert!(matches!(genesis, Err(EthApiError::HistoryPruned(0))));
+        assert!(NodeMode::Full.ensure_state_available(latest, latest, retention).is_ok());
+    }
+
+    #[test]
+    fn test_node_mode_archive_serves_history() {
+        // Given
+        let latest = 1000;
+        let retention = 128;
+
+        // When
+        let genesis = NodeMode::Archive.ensure_state_available(0, latest, retention);
+        let pruned_in_full = NodeMode::Archive.ensure_state_available(latest - retention - 1, latest, retention);
+
+        // Then
+        assert!(genesis.is_ok());
+        assert!(pruned_in_full.is_ok());
+    }
+
+    #[test]
+    fn test_priority_fee_policy_from_str() {
+        assert_eq!(PriorityFeePolicy::from_str("Burned").unwrap(), PriorityFeePolicy::Burned);
+        assert_eq!(PriorityFeePolicy::from_str("forwarded").unwrap(), PriorityFeePolicy::Forwarded);
+        assert!(PriorityFeePolicy::from_str("refunded").is_err());
+    }
+
+    #[test]
+    fn test_effective_priority_fee() {
+        // Given
+        let base_fee = 100;
+
+        // When
+        let ignored = PriorityFeePolicy::Ignored.effective_priority_fee(150, Some(10), base_fee);
+        let tip = PriorityFeePolicy::Burned.effective_priority_fee(150, Some(10), base_fee);
+        let capped = PriorityFeePolicy::Forwarded.effective_priority_fee(105, Some(10), base_fee);
+        let legacy = PriorityFeePolicy::Forwarded.effective_priority_fee(120, None, base_fee);
+        let underpriced = PriorityFeePolicy::Forwarded.effective_priority_fee(90, Some(10), base_fee);
+
+        // Then
+        assert_eq!(ignored, 0);
+        assert_eq!(tip, 10);
+        assert_eq!(capped, 5);
+        assert_eq!(legacy, 20);
+        assert_eq!(underpriced, 0);
+    }
+}
@@ -0,0 +1,90 @@
+#![allow(clippy::pub_underscore_fields)]
+
+use crate::providers::eth_provider::{
+    error::ExecutionError,
+    provider::{EthApiResult, EthereumProvider},
+};
+use alloy_eips::BlockId;
+use alloy_primitives::{Address, Bytes, TxKind, U256};
+use alloy_rpc_types::{request::TransactionInput, TransactionRequest};
+use alloy_sol_types::{sol, SolCall};
+
+sol! {
+    /// The EIP-712 typed request signed by the sender of a meta-transaction.
+    #[derive(Debug)]
+    struct ForwardRequest {
+        address from;
+        address to;
+        uint256 value;
+        uint256 gas;
+        uint256 nonce;
+        uint48 deadline;
+        bytes data;
+    }
+
+    #[sol(rpc)]
+    contract ERC2771Forwarder {
+        #[derive(Debug)]
+        struct ForwardRequestData {
+            address from;
+            address to;
+            uint256 value;
+            uint256 gas;
+            uint48 deadline;
+            bytes data;
+            bytes signature;
+        }
+
+        function execute(ForwardRequestData calldata request) external payable;
+        function nonces(address owner) external view returns (uint256);
+    }
+}
+
+/// Abstraction for a trusted forwarder contract (EIP-2771), with the interface of the
+/// `ERC2771Forwarder` of `OpenZeppelin`.
+#[derive(Debug)]
+pub struct EthereumForwarder<P: EthereumProvider> {
+    /// The address of the forwarder contract.
+    pub address: Address,
+    /// The provider for interacting with the Ethereum network.
+    pub provider: P,
+}
+
+impl<P: EthereumProvider> EthereumForwarder<P> {
+    /// Creates a new instance of [`EthereumForwarder`].
+    pub const fn new(address: Address, provider: P) -> Self {
+        Self { address, provider }
+    }
+
+    /// Gets the nonce of the next request of the owner.
+    pub async fn nonces(&self, owner: Address, block_id: BlockId) -> EthApiResult<U256> {
+        // Encode the calldata for the nonces function call
+        let calldata = ERC2771Forwarder::noncesCall { owner }.abi_encode();
+        // Call the contract with the encoded calldata
+        let ret = self.call_contract(calldata, block_id).await?;
+        // Deserialize the returned bytes into a U256 nonce
+        let nonce = U256::try_from_be_slice(&ret)
+            .ok_or_else(|| ExecutionError::Other("failed to deserialize nonce".to_string()))?;
+        Ok(nonce)
+    }
+
+    /// Calls the contract with the given calldata.
+    async fn call_contract(&self, calldata: Vec<u8>, block_id: BlockId) -> EthApiResult<Bytes> {
+        self.provider
+            .call(
+                TransactionRequest {
+                    from: Some(Address::default()),
+                    to: Some(TxKind::Call(self.address)),
+                    gas_price: Some(0),
+                    gas: Some(1_000_000),
+                    value: Some(U256::ZERO),
+                    input: TransactionInput { input: Some(calldata.into()), data: None },
+                    ..Default::default()
+                },
+                Some(block_id),
+                None,
+                None,
+            )
+            .await
+    }
+}
@@ -0,0 +1,82 @@
+use super::{
+    relayer::to_bson,
+    types::account::{ClassHashObservation, StoredClassHashObservation},
+    Database,
+};
+use crate::providers::eth_provider::error::{EthApiError, KakarotError};
+use async_trait::async_trait;
+use mongodb::{bson::doc, options::UpdateOptions};
+use starknet::core::types::Felt;
+use tracing::instrument;
+
+/// Trait for recording the classes of the Starknet contracts of the accounts over the blocks, so
+/// that the historical queries remain correct after an upgrade of the account classes.
+#[async_trait]
+pub trait AccountClassStore {
+    /// Records that the contract of the account had the class at the block, extending the range
+    /// of blocks the class was observed over.
+    async fn observe_class_hash(&self, address: Felt, class_hash: Felt, block_number: u64) -> Result<(), EthApiError>;
+    /// Returns the class observed for the contract of the account at the block, if any. If the
+    /// ranges of several classes cover the block, the class observed first the latest wins.
+    async fn observed_class_hash(&self, address: Felt, block_number: u64) -> Result<Option<Felt>, EthApiError>;
+}
+
+#[async_trait]
+impl AccountClassStore for Database {
+    #[instrument(skip_all, name = "db::observe_class_hash", err)]
+    async fn observe_class_hash(&self, address: Felt, class_hash: Felt, block_number: u64) -> Result<(), EthApiError> {
+        let filter = doc! {"observation.address": to_bson(&address)?, "observation.classHash": to_bson(&class_hash)?};
+        let block_number = to_bson(&block_number)?;
+        let update = doc! {
+            "$min": {"observation.fromBlock": block_number.clone()},
+            "$max": {"observation.toBlock": block_number},
+        };
+        self.collection::<StoredClassHashObservation>()
+            .update_one(filter, update)
+            .with_options(UpdateOptions::builder().upsert(true).build())
+            .await
+            .map_err(KakarotError::from)?;
+        Ok(())
+    }
+
+    #[instrument(skip_all, name = "db::observed_class_hash", err)]
+    async fn observed_class_hash(&self, address: Felt, block_number: u64) -> Result<Option<Felt>, EthApiError> {
+        let block_number = to_bson(&block_number)?;
+        let filter = doc! {
+            "observation.address": to_bson(&address)?,
+            "observation.fromBlock": {"$lte": block_number.clone()},
+            "observation.toBlock": {"$gte": block_number},
+        };
+        Ok(self
+            .get_one::<StoredClassHashObservation>(filter, doc! {"observation.fromBlock": -1})
+            .await?
+            .map(|stored| ClassHashObservation::from(stored).class_hash))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::mongo::{MongoFuzzer, RANDOM_BYTES_SIZE};
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_observed_class_hash() {
+        // Given
+        let mut mongo_fuzzer = MongoFuzzer::new(RANDOM_BYTES_SIZE).await;
+        let database = mongo_fuzzer.mock_database(1).await;
+        database.create_indexes().await.expect("Failed to create indexes");
+        let (account, legacy_class, upgraded_class) = (Felt::from(1), Felt::from(0xa), Felt::from(0xb));
+
+        // When
+        database.observe_class_hash(account, legacy_class, 20).await.unwrap();
+        database.observe_class_hash(account, legacy_class, 10).await.unwrap();
+        database.observe_class_hash(account, upgraded_class, 30).await.unwrap();
+
+        // Then
+        assert_eq!(database.observed_class_hash(account, 9).await.unwrap(), None);
+        assert_eq!(database.observed_class_hash(account, 15).await.unwrap(), Some(legacy_class));
+        assert_eq!(database.observed_class_hash(account, 25).await.unwrap(), None);
+        assert_eq!(database.observed_class_hash(account, 30).await.unwrap(), Some(upgraded_class));
+        assert_eq!(database.observed_class_hash(Felt::from(2), 15).await.unwrap(), None);
+    }
+}
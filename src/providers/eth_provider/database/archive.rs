@@ -0,0 +1,263 @@
+//! Archival of the logs and receipts older than the hot window to a second, cheaper, database.
+//!
+//! The logs and receipts of the blocks older than [`ARCHIVE_HOT_WINDOW_BLOCKS`] are moved, block
+//! range by block range, from the hot database to the archive database. The checkpoint of the
+//! archival, stored in the hot database, tells the readers which blocks are only found in the
+//! archive.
+
+use super::{
+    ethereum::EthereumBlockStore,
+    filter::{self, BlockFiltering, EthDatabaseFilterBuilder},
+    types::{
+        archive::{ArchiveCheckpoint, StoredArchiveCheckpoint},
+        log::StoredLog,
+        receipt::StoredTransactionReceipt,
+    },
+    CollectionName, Database, DatabaseResult,
+};
+use crate::providers::eth_provider::error::EthApiError;
+use alloy_primitives::U64;
+use async_trait::async_trait;
+use futures::TryStreamExt;
+use mongodb::bson::{doc, Document};
+use std::{fmt::Display, sync::LazyLock, time::Duration};
+use tracing::instrument;
+
+/// The number of most recent blocks whose logs and receipts are kept in the hot database. Set
+/// with the `ARCHIVE_HOT_WINDOW_BLOCKS` environment variable, defaults to 100 000 blocks.
+pub static ARCHIVE_HOT_WINDOW_BLOCKS: LazyLock<u64> = LazyLock::new(|| {
+    std::env::var("ARCHIVE_HOT_WINDOW_BLOCKS").ok().and_then(|val| val.parse().ok()).unwrap_or(100_000)
+});
+
+/// The maximum number of blocks whose logs and receipts are moved at once. Set with the
+/// `ARCHIVE_BATCH_BLOCKS` environment variable, defaults to 1000 blocks.
+pub static ARCHIVE_BATCH_BLOCKS: LazyLock<u64> =
+    LazyLock::new(|| std::env::var("ARCHIVE_BATCH_BLOCKS").ok().and_then(|val| val.parse().ok()).unwrap_or(1000));
+
+/// The interval between two runs of the archival. Set with the `ARCHIVE_INTERVAL_SECS`
+/// environment variable, defaults to 10 minutes.
+pub static ARCHIVE_INTERVAL: LazyLock<Duration> = LazyLock::new(|| {
+    Duration::from_secs(std::env::var("ARCHIVE_INTERVAL_SECS").ok().and_then(|val| val.parse().ok()).unwrap_or(600))
+});
+
+/// The configuration of the archive database.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArchiveConfig {
+    /// The connection string of the archive. Defaults to the connection string of the hot database.
+    pub connection_string: Option<String>,
+    /// The name of the archive database.
+    pub database_name: String,
+}
+
+/// Trait for moving the logs and receipts out of the hot window to the archive database.
+#[async_trait]
+pub trait ArchiveStore {
+    /// Returns the last block whose logs and receipts were moved to the archive, if any.
+    async fn last_archived_block(&self) -> Result<Option<u64>, EthApiError>;
+    /// Moves the logs and receipts of the blocks following the last archived block, up to the
+    /// given block and at most `batch_blocks` blocks, to the archive. Returns the number of
+    /// archived blocks, zero if the archive is up to date or if no archive is set.
+    async fn archive_blocks(&self, up_to_block: u64, batch_blocks: u64) -> Result<u64, EthApiError>;
+}
+
+#[async_trait]
+impl ArchiveStore for Database {
+    #[instrument(skip_all, name = "db::last_archived_block", err)]
+    async fn last_archived_block(&self) -> Result<Option<u64>, EthApiError> {
+        Ok(self
+            .get_one::<StoredArchiveCheckpoint>(doc! {}, None)
+            .await?
+            .map(|stored| stored.checkpoint.last_archived_block.to()))
+    }
+
+    #[instrument(skip(self), name = "db::archive_blocks", err)]
+    async fn archive_blocks(&self, up_to_block: u64, batch_blocks: u64) -> Result<u64, EthApiError> {
+        let Some(archive) = self.archive() else {
+            return Ok(0);
+        };
+        let from = match self.last_archived_block().await? {
+            Some(last_archived_block) if last_archived_block >= up_to_block => return Ok(0),
+            Some(last_archived_block) => last_archived_block + 1,
+            None => 0,
+        };
+        let to = up_to_block.min(from.saturating_add(batch_blocks.max(1) - 1));
+
+        // Copy the documents first, so that an interrupted run only leads to a new copy
+        let logs = copy_range::<StoredLog, filter::Log>(self, archive, from, to).await?;
+        let receipts = copy_range::<StoredTransactionReceipt, filter::Receipt>(self, archive, from, to).await?;
+
+        // Advance the checkpoint before deleting the documents, the readers then query the
+        // archive for the range
+        let checkpoint = ArchiveCheckpoint { last_archived_block: U64::from(to) };
+        self.update_one(StoredArchiveCheckpoint::from(checkpoint), doc! {}, true).await?;
+
+        // Delete all the documents up to the checkpoint, including the ones left by an
+        // interrupted run
+        let archived = EthDatabaseFilterBuilder::<filter::Log>::default().with_block_number_range(0, to).build();
+        self.delete_many::<StoredLog>(archived).await?;
+        let archived = EthDatabaseFilterBuilder::<filter::Receipt>::default().with_block_number_range(0, to).build();
+        self.delete_many::<StoredTransactionReceipt>(archived).await?;
+
+        tracing::info!(
+            target: "archive",
+            monotonic_counter.archived_logs = logs,
+            monotonic_counter.archived_receipts = receipts,
+            from,
+            to,
+            "archived logs and receipts"
+        );
+
+        Ok((to - from).saturating_add(1))
+    }
+}
+
+/// Copies the documents of the blocks in the range from the hot database to the archive, replacing
+/// the documents of the range already in the archive. Returns the number of copied documents.
+async fn copy_range<T, F>(hot: &Database, archive: &Database, from: u64, to: u64) -> DatabaseResult<u64>
+where
+    T: CollectionName + Sync + Send,
+    F: BlockFiltering + Display + Default,
+{
+    let filter = EthDatabaseFilterBuilder::<F>::default().with_block_number_range(from, to).build();
+    let documents: Vec<Document> =
+        hot.inner().collection::<Document>(T::collection_name()).find(filter.clone()).await?.try_collect().await?;
+
+    let collection = archive.inner().collection::<Document>(T::collection_name());
+    collection.delete_many(filter).await?;
+    if !documents.is_empty() {
+        collection.insert_many(&documents).await?;
+    }
+
+    Ok(documents.len() as u64)
+}
+
+/// Splits the block range between the archive and the hot database, given the last archived
+/// block. Returns the range to query in the archive and the range to query in the hot database.
+pub fn split_block_range(
+    last_archived_block: Option<u64>,
+    from: u64,
+    to: u64,
+) -> (Option<(u64, u64)>, Option<(u64, u64)>) {
+    match last_archived_block {
+        Some(last) if from <= last => {
+            let hot = (to > last).then_some((last + 1, to));
+            (Some((from, to.min(last))), hot)
+        }
+        _ => (None, Some((from, to))),
+    }
+}
+
+/// The range of blocks to query in the archive, with the archive, and the range of blocks to
+/// query in the hot database.
+pub type ArchiveRanges<'a> = (Option<(&'a Database, (u64, u64))>, Option<(u64, u64)>);
+
+/// Splits the block range between the archive and the hot database, see [`split_block_range`].
+/// Returns an error if blocks of the range were archived while no archive is configured, rather
+/// than incomplete results.
+pub async fn archive_ranges(database: &Database, from: u64, to: u64) -> Result<ArchiveRanges<'_>, EthApiError> {
+    let last_archived_block = database.last_archived_block().await?;
+    let (archived_range, hot_range) = split_block_range(last_archived_block, from, to);
+    match (archived_range, database.archive()) {
+        (None, _) => Ok((None, hot_range)),
+        (Some(range), Some(archive)) => Ok((Some((archive, range)), hot_range)),
+        (Some(_), None) => Err(EthApiError::ArchiveUnavailable(last_archived_block.unwrap_or_default())),
+    }
+}
+
+/// Periodically moves the logs and receipts out of the hot window to the archive of the
+/// database, if any.
+pub fn start_archival(database: Database) {
+    if database.archive().is_none() {
+        return;
+    }
+
+    tokio::spawn(async move {
+        loop {
+            if let Err(err) = archive(&database).await {
+                tracing::warn!(target: "archive", ?err, "failed to archive logs and receipts");
+            }
+            tokio::time::sleep(*ARCHIVE_INTERVAL).await;
+        }
+    });
+}
+
+/// Archives the blocks out of the hot window, one batch at a time.
+async fn archive(database: &Database) -> Result<(), EthApiError> {
+    let Some(latest) = database.latest_header().await? else {
+        return Ok(());
+    };
+    let Some(up_to_block) = latest.number.checked_sub(*ARCHIVE_HOT_WINDOW_BLOCKS) else {
+        return Ok(());
+    };
+
+    while database.archive_blocks(up_to_block, *ARCHIVE_BATCH_BLOCKS).await? > 0 {}
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::mongo::{MongoFuzzer, RANDOM_BYTES_SIZE};
+
+    #[test]
+    fn test_split_block_range() {
+        assert_eq!(split_block_range(None, 10, 20), (None, Some((10, 20))));
+        assert_eq!(split_block_range(Some(5), 10, 20), (None, Some((10, 20))));
+        assert_eq!(split_block_range(Some(15), 10, 20), (Some((10, 15)), Some((16, 20))));
+        assert_eq!(split_block_range(Some(20), 10, 20), (Some((10, 20)), None));
+        assert_eq!(split_block_range(Some(30), 10, 20), (Some((10, 20)), None));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_archive_ranges() {
+        // Given
+        let mut mongo_fuzzer = MongoFuzzer::new(RANDOM_BYTES_SIZE).await;
+        let database = mongo_fuzzer.mock_database(1).await;
+        let checkpoint = ArchiveCheckpoint { last_archived_block: U64::from(15) };
+        database.update_one(StoredArchiveCheckpoint::from(checkpoint), doc! {}, true).await.unwrap();
+        let archive = Database::from(database.inner().client().database("kakarot_archive"));
+        let with_archive = database.clone().with_archive(archive);
+
+        // When
+        let (no_archived, hot_only) = archive_ranges(&database, 16, 20).await.unwrap();
+        let missing_archive = archive_ranges(&database, 10, 20).await.unwrap_err();
+        let (archived, hot) = archive_ranges(&with_archive, 10, 20).await.unwrap();
+
+        // Then
+        assert!(no_archived.is_none());
+        assert_eq!(hot_only, Some((16, 20)));
+        assert!(matches!(missing_archive, EthApiError::ArchiveUnavailable(15)));
+        assert_eq!(archived.map(|(_, range)| range), Some((10, 15)));
+        assert_eq!(hot, Some((16, 20)));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_archive_blocks() {
+        // Given
+        let mut mongo_fuzzer = MongoFuzzer::new(RANDOM_BYTES_SIZE).await;
+        let database = mongo_fuzzer.mock_database(20).await;
+        let archive = Database::from(database.inner().client().database("kakarot_archive"));
+        let database = database.with_archive(archive);
+
+        let mut block_numbers: Vec<_> = mongo_fuzzer.logs.iter().filter_map(|stored| stored.log.block_number).collect();
+        block_numbers.sort_unstable();
+        let up_to_block = block_numbers[block_numbers.len() / 2];
+        let expected_logs = block_numbers.iter().filter(|number| **number <= up_to_block).count() as u64;
+
+        // When
+        let archived = database.archive_blocks(up_to_block, u64::MAX).await.unwrap();
+        let archived_again = database.archive_blocks(up_to_block, u64::MAX).await.unwrap();
+
+        // Then
+        let archive = database.archive().unwrap();
+        assert_eq!(archived, up_to_block + 1);
+        assert_eq!(archived_again, 0);
+        assert_eq!(database.last_archived_block().await.unwrap(), Some(up_to_block));
+        assert_eq!(archive.count::<StoredLog>(doc! {}).await.unwrap(), expected_logs);
+        assert_eq!(database.count::<StoredLog>(doc! {}).await.unwrap(), mongo_fuzzer.logs.len() as u64 - expected_logs);
+        let hot_receipts =
+            EthDatabaseFilterBuilder::<filter::Receipt>::default().with_block_number_range(0, up_to_block).build();
+        assert_eq!(database.count::<StoredTransactionReceipt>(hot_receipts).await.unwrap(), 0);
+    }
+}
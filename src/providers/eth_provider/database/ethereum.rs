@@ -2,23 +2,37 @@ use super::{
     filter,
     filter::EthDatabaseFilterBuilder,
     types::{
-        header::{ExtendedBlock, StoredHeader},
-        transaction::{ExtendedTransaction, StoredTransaction},
+        balance::StoredBalanceSnapshot,
+        header::{
+            ExtendedBlock, StoredBlock, StoredHeader, StoredStarknetBlockHash, FEE_RECIPIENT_FIELD,
+            SKIPPED_TRANSACTIONS_FIELD, STARKNET_BLOCK_HASH_FIELD,
+        },
+        receipt::{ExtendedTxReceipt, StoredBlockReceipts, StoredTransactionReceipt},
+        transaction::{is_deposit, ExtendedTransaction, StoredTransaction},
     },
-    Database,
+    CollectionName, Database, FindOpts,
 };
 use crate::providers::eth_provider::{
-    database::types::transaction::{EthStarknetHashes, StoredEthStarknetTransactionHash},
+    constant::HASH_HEX_STRING_LEN,
+    database::{
+        filter::format_hex,
+        types::transaction::{
+            DroppedTransaction, EthStarknetHashes, PoolEvent, RawTransaction, StoredDroppedTransaction,
+            StoredEthStarknetTransactionHash, StoredPoolEvent, StoredRawTransaction,
+        },
+    },
     error::EthApiError,
 };
 use alloy_consensus::constants::EMPTY_ROOT_HASH;
-use alloy_primitives::{B256, U256};
+use alloy_primitives::{Bytes, B256, U256};
 use alloy_rlp::Encodable;
 use alloy_rpc_types::{Block, BlockHashOrNumber, BlockTransactions, Header};
 use alloy_serde::WithOtherFields;
 use async_trait::async_trait;
-use mongodb::bson::doc;
+use mongodb::bson::{doc, Document};
 use reth_primitives::BlockBody;
+use starknet::core::types::Felt;
+use std::collections::{BTreeSet, HashMap};
 use tracing::instrument;
 
 /// Trait for interacting with a database that stores Ethereum typed
@@ -37,6 +51,24 @@ pub trait EthereumTransactionStore {
     async fn upsert_transaction(&self, transaction: ExtendedTransaction) -> Result<(), EthApiError>;
     /// Upserts the given transaction hash mapping (Ethereum -> Starknet).
     async fn upsert_transaction_hashes(&self, transaction_hashes: EthStarknetHashes) -> Result<(), EthApiError>;
+    /// Returns the hash of the Starknet transaction which relayed the Ethereum transaction with
+    /// the given hash. Returns None if the transaction wasn't relayed by the node.
+    async fn starknet_transaction_hash(&self, eth_hash: &B256) -> Result<Option<Felt>, EthApiError>;
+    /// Returns the dropped transaction with the given hash. Returns None if the
+    /// transaction was not dropped.
+    async fn dropped_transaction(&self, hash: &B256) -> Result<Option<DroppedTransaction>, EthApiError>;
+    /// Upserts the given dropped transaction.
+    async fn upsert_dropped_transaction(&self, dropped: DroppedTransaction) -> Result<(), EthApiError>;
+    /// Returns the events of the transaction with the given hash in the pool, in the order they
+    /// happened. Only the events of the recent transactions are kept.
+    async fn pool_events(&self, hash: &B256) -> Result<Vec<PoolEvent>, EthApiError>;
+    /// Inserts an event of a transaction in the pool.
+    async fn insert_pool_event(&self, event: PoolEvent) -> Result<(), EthApiError>;
+    /// Returns the raw bytes of the transaction with the given hash, as submitted to the node.
+    /// Returns None if the transaction wasn't submitted to the node.
+    async fn raw_transaction(&self, hash: &B256) -> Result<Option<Bytes>, EthApiError>;
+    /// Upserts the raw bytes of a transaction submitted to the node.
+    async fn upsert_raw_transaction(&self, raw: RawTransaction) -> Result<(), EthApiError>;
 }
 
 #[async_trait]
@@ -44,7 +76,8 @@ impl EthereumTransactionStore for Database {
     #[instrument(skip_all, name = "db::transaction", err)]
     async fn transaction(&self, hash: &B256) -> Result<Option<ExtendedTransaction>, EthApiError> {
         let filter = EthDatabaseFilterBuilder::<filter::Transaction>::default().with_tx_hash(hash).build();
-        Ok(self.get_one::<StoredTransaction>(filter, None).await?.map(Into::into))
+        let transactions = self.get_one::<StoredTransaction>(filter, None).await?.into_iter().collect();
+        Ok(self.with_effective_gas_prices(transactions).await?.pop())
     }
 
     #[instrument(skip_all, name = "db::transactions", err)]
@@ -56,7 +89,8 @@ impl EthereumTransactionStore for Database {
             .with_block_hash_or_number(block_hash_or_number)
             .build();
 
-        Ok(self.get::<StoredTransaction>(filter, None).await?.into_iter().map(Into::into).collect())
+        let transactions = self.get::<StoredTransaction>(filter, None).await?;
+        self.with_effective_gas_prices(transactions).await
     }
 
     #[instrument(skip_all, name = "db::upsert_transaction", err)]
@@ -72,6 +106,54 @@ impl EthereumTransactionStore for Database {
             .build();
         Ok(self.update_one(StoredEthStarknetTransactionHash::from(transaction_hashes), filter, true).await?)
     }
+
+    #[instrument(skip_all, name = "db::starknet_transaction_hash", err)]
+    async fn starknet_transaction_hash(&self, eth_hash: &B256) -> Result<Option<Felt>, EthApiError> {
+        let filter =
+            EthDatabaseFilterBuilder::<filter::EthStarknetTransactionHash>::default().with_tx_hash(eth_hash).build();
+        Ok(self
+            .get_one::<StoredEthStarknetTransactionHash>(filter, None)
+            .await?
+            .map(|stored| stored.hashes.starknet_hash))
+    }
+
+    #[instrument(skip_all, name = "db::dropped_transaction", err)]
+    async fn dropped_transaction(&self, hash: &B256) -> Result<Option<DroppedTransaction>, EthApiError> {
+        let filter = EthDatabaseFilterBuilder::<filter::DroppedTransaction>::default().with_tx_hash(hash).build();
+        Ok(self.get_one::<StoredDroppedTransaction>(filter, None).await?.map(Into::into))
+    }
+
+    #[instrument(skip_all, name = "db::upsert_dropped_transaction", err)]
+    async fn upsert_dropped_transaction(&self, dropped: DroppedTransaction) -> Result<(), EthApiError> {
+        let filter =
+            EthDatabaseFilterBuilder::<filter::DroppedTransaction>::default().with_tx_hash(&dropped.hash).build();
+        Ok(self.update_one(StoredDroppedTransaction::from(dropped), filter, true).await?)
+    }
+
+    #[instrument(skip_all, name = "db::pool_events", err)]
+    async fn pool_events(&self, hash: &B256) -> Result<Vec<PoolEvent>, EthApiError> {
+        let filter = EthDatabaseFilterBuilder::<filter::PoolEvent>::default().with_tx_hash(hash).build();
+        // The capped collections keep the documents in their insertion order
+        let events = self.get::<StoredPoolEvent>(filter, FindOpts::default().with_sort(doc! {"$natural": 1})).await?;
+        Ok(events.into_iter().map(Into::into).collect())
+    }
+
+    #[instrument(skip_all, name = "db::insert_pool_event", err)]
+    async fn insert_pool_event(&self, event: PoolEvent) -> Result<(), EthApiError> {
+        Ok(self.insert_one(StoredPoolEvent::from(event)).await?)
+    }
+
+    #[instrument(skip_all, name = "db::raw_transaction", err)]
+    async fn raw_transaction(&self, hash: &B256) -> Result<Option<Bytes>, EthApiError> {
+        let filter = EthDatabaseFilterBuilder::<filter::RawTransaction>::default().with_tx_hash(hash).build();
+        Ok(self.get_one::<StoredRawTransaction>(filter, None).await?.map(|stored| stored.raw.bytes))
+    }
+
+    #[instrument(skip_all, name = "db::upsert_raw_transaction", err)]
+    async fn upsert_raw_transaction(&self, raw: RawTransaction) -> Result<(), EthApiError> {
+        let filter = EthDatabaseFilterBuilder::<filter::RawTransaction>::default().with_tx_hash(&raw.hash).build();
+        Ok(self.update_one(StoredRawTransaction::from(raw), filter, true).await?)
+    }
 }
 
 /// Trait for interacting with a database that stores Ethereum typed
@@ -98,6 +180,43 @@ pub trait EthereumBlockStore {
     /// Returns the transaction count for the given block hash or number. Returns None if the
     /// block is not found.
     async fn transaction_count(&self, block_hash_or_number: BlockHashOrNumber) -> Result<Option<U256>, EthApiError>;
+    /// Returns the receipts of the block for the given hash or number, with their logs. Returns
+    /// None if the block is not found.
+    async fn block_receipts(
+        &self,
+        block_hash_or_number: BlockHashOrNumber,
+    ) -> Result<Option<Vec<ExtendedTxReceipt>>, EthApiError>;
+    /// Returns the block built from the Starknet block with the given hash, whether the block is
+    /// identified by its Starknet hash or by its EVM-style hash. Returns None if the block is not
+    /// found.
+    async fn block_by_starknet_hash(
+        &self,
+        starknet_hash: &B256,
+        full: bool,
+    ) -> Result<Option<ExtendedBlock>, EthApiError>;
+    /// Returns the Starknet block hash for the given EVM-style block hash, as stored by the
+    /// indexer. Returns None if the block is not found.
+    async fn starknet_block_hash(&self, evm_hash: &B256) -> Result<Option<B256>, EthApiError>;
+    /// Returns the base fees of the blocks with the given numbers, by block number. The blocks
+    /// which are not found or have no base fee are omitted.
+    async fn base_fees(&self, block_numbers: &[u64]) -> Result<HashMap<u64, u64>, EthApiError>;
+    /// Converts the stored transactions to their geth formatted representation, the gas price of
+    /// the EIP-1559 transactions being their effective gas price under the base fee of their block.
+    async fn with_effective_gas_prices(
+        &self,
+        transactions: Vec<StoredTransaction>,
+    ) -> Result<Vec<ExtendedTransaction>, EthApiError> {
+        let block_numbers: Vec<_> =
+            transactions.iter().filter_map(|tx| tx.block_number).collect::<BTreeSet<_>>().into_iter().collect();
+        let base_fees = if block_numbers.is_empty() { HashMap::new() } else { self.base_fees(&block_numbers).await? };
+        Ok(transactions
+            .into_iter()
+            .map(|tx| {
+                let base_fee = tx.block_number.and_then(|number| base_fees.get(&number).copied());
+                tx.with_base_fee(base_fee)
+            })
+            .collect())
+    }
 }
 
 #[async_trait]
@@ -128,11 +247,137 @@ impl EthereumBlockStore for Database {
         block_hash_or_number: BlockHashOrNumber,
         full: bool,
     ) -> Result<Option<ExtendedBlock>, EthApiError> {
-        let maybe_header = self.header(block_hash_or_number).await?;
-        if maybe_header.is_none() {
+        let filter = EthDatabaseFilterBuilder::<filter::Header>::default()
+            .with_block_hash_or_number(block_hash_or_number)
+            .build();
+        self.block_matching(filter, block_hash_or_number, full).await
+    }
+
+    #[instrument(skip_all, name = "db::transaction_count", err)]
+    async fn transaction_count(&self, block_hash_or_number: BlockHashOrNumber) -> Result<Option<U256>, EthApiError> {
+        if !self.block_exists(block_hash_or_number).await? {
             return Ok(None);
         }
-        let header = maybe_header.unwrap();
+
+        let filter = EthDatabaseFilterBuilder::<filter::Transaction>::default()
+            .with_block_hash_or_number(block_hash_or_number)
+            .build();
+        let count = self.count::<StoredTransaction>(filter).await?;
+        Ok(Some(U256::from(count)))
+    }
+
+    #[instrument(skip_all, name = "db::block_receipts", err)]
+    async fn block_receipts(
+        &self,
+        block_hash_or_number: BlockHashOrNumber,
+    ) -> Result<Option<Vec<ExtendedTxReceipt>>, EthApiError> {
+        // Match the header and join its receipts in a single round trip, so that an unknown
+        // block is told apart from a block without transactions.
+        let filter = EthDatabaseFilterBuilder::<filter::Header>::default()
+            .with_block_hash_or_number(block_hash_or_number)
+            .build();
+        let pipeline = [
+            doc! { "$match": filter },
+            doc! { "$limit": 1 },
+            doc! {
+                "$lookup": {
+                    "from": StoredTransactionReceipt::collection_name(),
+                    "localField": "header.hash",
+                    "foreignField": "receipt.blockHash",
+                    "as": "receipts"
+                }
+            },
+            doc! { "$project": { "_id": 0, "receipts": 1 } },
+        ];
+
+        let Some(block) = self.get_one_aggregate::<StoredBlockReceipts>(pipeline).await? else {
+            return Ok(None);
+        };
+
+        // The receipts of a block out of the hot window are only found in the archive
+        let receipts = match self.archive() {
+            Some(archive) if block.receipts.is_empty() => {
+                let filter = EthDatabaseFilterBuilder::<filter::Receipt>::default()
+                    .with_block_hash_or_number(block_hash_or_number)
+                    .build();
+                archive.get::<StoredTransactionReceipt>(filter, None).await?
+            }
+            _ => block.receipts,
+        };
+
+        Ok(Some(receipts.into_iter().map(Into::into).collect()))
+    }
+
+    #[instrument(skip_all, name = "db::block_by_starknet_hash", err)]
+    async fn block_by_starknet_hash(
+        &self,
+        starknet_hash: &B256,
+        full: bool,
+    ) -> Result<Option<ExtendedBlock>, EthApiError> {
+        // The Starknet hash is the hash of the header, unless the indexer replaced it with the
+        // EVM-style hash of the block and kept the Starknet hash aside
+        let hash = format_hex(starknet_hash, HASH_HEX_STRING_LEN);
+        let filter = doc! { "$or": [{ "header.hash": &hash }, { STARKNET_BLOCK_HASH_FIELD: &hash }] };
+        self.block_matching(filter, (*starknet_hash).into(), full).await
+    }
+
+    #[instrument(skip_all, name = "db::starknet_block_hash", err)]
+    async fn starknet_block_hash(&self, evm_hash: &B256) -> Result<Option<B256>, EthApiError> {
+        let filter = doc! {
+            "header.hash": format_hex(evm_hash, HASH_HEX_STRING_LEN),
+            STARKNET_BLOCK_HASH_FIELD: { "$exists": true }
+        };
+        Ok(self.get_one::<StoredStarknetBlockHash>(filter, None).await?.map(|stored| stored.starknet_block_hash))
+    }
+
+    #[instrument(skip_all, name = "db::base_fees", err)]
+    async fn base_fees(&self, block_numbers: &[u64]) -> Result<HashMap<u64, u64>, EthApiError> {
+        let filter = EthDatabaseFilterBuilder::<filter::Header>::default().with_block_numbers(block_numbers).build();
+        Ok(self
+            .get::<StoredHeader>(filter, None)
+            .await?
+            .into_iter()
+            .filter_map(|stored| Some((stored.header.number, stored.header.base_fee_per_gas?)))
+            .collect())
+    }
+}
+
+impl Database {
+    /// Returns the block whose header matches the filter, with its transactions. The block is
+    /// reported as unknown with the given hash or number if the read fails.
+    async fn block_matching(
+        &self,
+        filter: Document,
+        block_hash_or_number: BlockHashOrNumber,
+        full: bool,
+    ) -> Result<Option<ExtendedBlock>, EthApiError> {
+        // Match the header and join the transactions carrying its hash in a single read, so that
+        // a block being indexed concurrently, or replaced by another block with the same number,
+        // never mixes the header of a block with the transactions of another one.
+        let pipeline = [
+            doc! { "$match": filter },
+            doc! { "$limit": 1 },
+            doc! {
+                "$lookup": {
+                    "from": StoredTransaction::collection_name(),
+                    "localField": "header.hash",
+                    "foreignField": "tx.blockHash",
+                    "as": "transactions"
+                }
+            },
+            doc! {
+                "$project": { "_id": 0, "header": 1, "transactions": 1, "feeRecipient": 1, "skippedTransactions": 1,
+                    "starknetBlockHash": 1 }
+            },
+        ];
+
+        let Some(StoredBlock { header, transactions, fee_recipient, skipped_transactions, starknet_block_hash }) = self
+            .get_one_aggregate::<StoredBlock>(pipeline)
+            .await
+            .map_err(|_| EthApiError::UnknownBlock(block_hash_or_number))?
+        else {
+            return Ok(None);
+        };
 
         // The withdrawals are not supported, hence the withdrawals_root should always be empty.
         if let Some(withdrawals_root) = header.withdrawals_root {
@@ -141,7 +386,8 @@ impl EthereumBlockStore for Database {
             }
         }
 
-        let transactions = self.transactions(block_hash_or_number).await?;
+        let transactions: Vec<ExtendedTransaction> =
+            transactions.into_iter().map(|tx| tx.with_base_fee(header.base_fee_per_gas)).collect();
 
         let block_transactions = if full {
             BlockTransactions::Full(transactions.clone())
@@ -149,9 +395,14 @@ impl EthereumBlockStore for Database {
             BlockTransactions::Hashes(transactions.iter().map(|tx| tx.hash).collect())
         };
 
+        // The deposit transactions have no encoding, they aren't counted in the size of the block
         let block = reth_primitives::Block {
             body: BlockBody {
-                transactions: transactions.into_iter().map(TryFrom::try_from).collect::<Result<_, _>>()?,
+                transactions: transactions
+                    .into_iter()
+                    .filter(|transaction| !is_deposit(transaction))
+                    .map(TryFrom::try_from)
+                    .collect::<Result<_, _>>()?,
                 withdrawals: Some(Default::default()),
                 ..Default::default()
             },
@@ -162,36 +413,69 @@ impl EthereumBlockStore for Database {
         // `https://github.com/paradigmxyz/reth/blob/v0.2.0-beta.5/crates/rpc/rpc-types-compat/src/block.rs#L66`
         let size = block.length();
 
-        Ok(Some(WithOtherFields::new(Block {
+        let mut block = WithOtherFields::new(Block {
             header,
             transactions: block_transactions,
             size: Some(U256::from(size)),
             withdrawals: Some(Default::default()),
             ..Default::default()
-        })))
+        });
+        if let Some(fee_recipient) = fee_recipient {
+            block
+                .other
+                .insert(FEE_RECIPIENT_FIELD.to_string(), serde_json::to_value(fee_recipient).unwrap_or_default());
+        }
+        if let Some(skipped_transactions) = skipped_transactions {
+            block.other.insert(
+                SKIPPED_TRANSACTIONS_FIELD.to_string(),
+                serde_json::to_value(skipped_transactions).unwrap_or_default(),
+            );
+        }
+        if let Some(starknet_block_hash) = starknet_block_hash {
+            block.other.insert(
+                STARKNET_BLOCK_HASH_FIELD.to_string(),
+                serde_json::Value::String(starknet_block_hash.to_string()),
+            );
+        }
+
+        Ok(Some(block))
     }
+}
 
-    #[instrument(skip_all, name = "db::transaction_count", err)]
-    async fn transaction_count(&self, block_hash_or_number: BlockHashOrNumber) -> Result<Option<U256>, EthApiError> {
-        if !self.block_exists(block_hash_or_number).await? {
-            return Ok(None);
-        }
+/// Trait for interacting with a database that stores snapshots of the native token balances.
+#[async_trait]
+pub trait EthereumBalanceStore {
+    /// Returns the native token balance of the Starknet account at the end of the given block,
+    /// from the last snapshot taken at or before the block. Returns None if no snapshot was taken,
+    /// or if the balance of the last snapshot couldn't be fetched by the indexer, in which case the
+    /// previous snapshots are stale.
+    async fn native_balance(&self, starknet_address: &Felt, block_number: u64) -> Result<Option<U256>, EthApiError>;
+}
 
-        let filter = EthDatabaseFilterBuilder::<filter::Transaction>::default()
-            .with_block_hash_or_number(block_hash_or_number)
+#[async_trait]
+impl EthereumBalanceStore for Database {
+    #[instrument(skip_all, name = "db::native_balance", err)]
+    async fn native_balance(&self, starknet_address: &Felt, block_number: u64) -> Result<Option<U256>, EthApiError> {
+        let filter = EthDatabaseFilterBuilder::<filter::Balance>::default()
+            .with_starknet_address(starknet_address)
+            .with_block_number_range(0, block_number)
             .build();
-        let count = self.count::<StoredTransaction>(filter).await?;
-        Ok(Some(U256::from(count)))
+        Ok(self
+            .get_one::<StoredBalanceSnapshot>(filter, doc! {"balance.blockNumber": -1})
+            .await?
+            .and_then(|stored| stored.balance.balance))
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::test_utils::mongo::{MongoFuzzer, RANDOM_BYTES_SIZE};
+    use crate::{
+        providers::eth_provider::database::types::transaction::{DropReason, PoolEventKind},
+        test_utils::mongo::{MongoFuzzer, RANDOM_BYTES_SIZE},
+    };
     use arbitrary::Arbitrary;
     use rand::{self, Rng};
-    use starknet::core::types::Felt;
 
     #[tokio::test(flavor = "multi_thread")]
     async fn test_ethereum_transaction_store() {
@@ -226,8 +510,17 @@ mod tests {
         // Fetch the first transaction from the mock database
         let first_transaction = mongo_fuzzer.transactions.first().unwrap();
 
+        let base_fee = mongo_fuzzer
+            .headers
+            .iter()
+            .find(|header| Some(header.number) == first_transaction.block_number)
+            .and_then(|header| header.base_fee_per_gas);
+
         // Test retrieving an existing transaction by its hash
-        assert_eq!(database.transaction(&first_transaction.hash).await.unwrap(), Some(first_transaction.into()));
+        assert_eq!(
+            database.transaction(&first_transaction.hash).await.unwrap(),
+            Some(first_transaction.clone().with_base_fee(base_fee))
+        );
 
         // Generate a transaction not present in the database
         let unstored_transaction = StoredTransaction::arbitrary(unstructured).unwrap();
@@ -238,14 +531,15 @@ mod tests {
 
     async fn test_get_transactions_by_block_hash(database: &Database, mongo_fuzzer: &MongoFuzzer) {
         // Fetch the first block hash from the mock database
-        let first_block_hash = mongo_fuzzer.headers.first().unwrap().hash;
+        let first_header = mongo_fuzzer.headers.first().unwrap();
+        let first_block_hash = first_header.hash;
 
         // Fetch transactions belonging to the first block hash
         let transactions_first_block_hash = mongo_fuzzer
             .transactions
             .iter()
             .filter(|tx| tx.block_hash.unwrap() == first_block_hash)
-            .map(Into::into)
+            .map(|tx| tx.clone().with_base_fee(first_header.base_fee_per_gas))
             .collect::<Vec<_>>();
 
         // Test retrieving transactions by block hash
@@ -254,14 +548,15 @@ mod tests {
 
     async fn test_get_transactions_by_block_number(database: &Database, mongo_fuzzer: &MongoFuzzer) {
         // Fetch the first block number from the mock database
-        let first_block_number = mongo_fuzzer.headers.first().unwrap().number;
+        let first_header = mongo_fuzzer.headers.first().unwrap();
+        let first_block_number = first_header.number;
 
         // Fetch transactions belonging to the first block number
         let transactions_first_block_number = mongo_fuzzer
             .transactions
             .iter()
             .filter(|tx| tx.block_number.unwrap() == first_block_number)
-            .map(Into::into)
+            .map(|tx| tx.clone().with_base_fee(first_header.base_fee_per_gas))
             .collect::<Vec<_>>();
 
         // Test retrieving transactions by block number
@@ -297,6 +592,9 @@ mod tests {
 
         // Test fetching existing and none existing transaction counts via blockhash and blocknumber from database
         test_get_transaction_count(&database, &mongo_fuzzer).await;
+
+        // Test fetching existing and none existing block receipts via blockhash and blocknumber from database
+        test_get_block_receipts(&database, &mongo_fuzzer).await;
     }
 
     async fn test_get_header(database: &Database, mongo_fuzzer: &MongoFuzzer) {
@@ -408,6 +706,32 @@ mod tests {
         assert_eq!(database.transaction_count(rng.gen::<u64>().into()).await.unwrap(), None);
     }
 
+    async fn test_get_block_receipts(database: &Database, mongo_fuzzer: &MongoFuzzer) {
+        let header = &mongo_fuzzer.headers.first().unwrap().header;
+
+        let receipts: Vec<ExtendedTxReceipt> = mongo_fuzzer
+            .receipts
+            .iter()
+            .filter(|receipt| receipt.receipt.block_hash == Some(header.hash))
+            .cloned()
+            .map(Into::into)
+            .collect();
+
+        // Test retrieving block receipts by block hash
+        assert_eq!(database.block_receipts(header.hash.into()).await.unwrap().unwrap(), receipts);
+
+        // Test retrieving block receipts by block number
+        assert_eq!(database.block_receipts(header.number.into()).await.unwrap().unwrap(), receipts);
+
+        let mut rng = rand::thread_rng();
+
+        // Test retrieving non-existing block receipts by block hash
+        assert_eq!(database.block_receipts(rng.gen::<B256>().into()).await.unwrap(), None);
+
+        // Test retrieving non-existing block receipts by block number
+        assert_eq!(database.block_receipts(rng.gen::<u64>().into()).await.unwrap(), None);
+    }
+
     #[tokio::test(flavor = "multi_thread")]
     async fn test_upsert_transaction_hashes() {
         // Initialize MongoDB fuzzer
@@ -461,5 +785,130 @@ mod tests {
             Some(StoredEthStarknetTransactionHash::from(updated_transaction_hashes)),
             "The transaction hash mapping was not updated correctly"
         );
+
+        // The Starknet hash is returned for the Ethereum hash, and none for an unknown hash
+        assert_eq!(database.starknet_transaction_hash(&eth_hash).await.unwrap(), Some(new_starknet_hash));
+        assert_eq!(database.starknet_transaction_hash(&B256::random()).await.unwrap(), None);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_starknet_block_hash() {
+        // Given
+        let mut mongo_fuzzer = MongoFuzzer::new(RANDOM_BYTES_SIZE).await;
+        let database = mongo_fuzzer.mock_database(1).await;
+        let evm_hash = mongo_fuzzer.headers[0].header.hash;
+        let starknet_hash = B256::random();
+
+        // When
+        database
+            .inner()
+            .collection::<Document>(StoredHeader::collection_name())
+            .update_one(
+                doc! { "header.hash": format_hex(evm_hash, HASH_HEX_STRING_LEN) },
+                doc! { "$set": { STARKNET_BLOCK_HASH_FIELD: format_hex(starknet_hash, HASH_HEX_STRING_LEN) } },
+            )
+            .await
+            .expect("Failed to store the Starknet block hash");
+
+        // Then
+        assert_eq!(database.starknet_block_hash(&evm_hash).await.unwrap(), Some(starknet_hash));
+        assert_eq!(database.starknet_block_hash(&B256::random()).await.unwrap(), None);
+        let block = database.block_by_starknet_hash(&starknet_hash, false).await.unwrap().unwrap();
+        assert_eq!(block.header.hash, evm_hash);
+        assert_eq!(block.other[STARKNET_BLOCK_HASH_FIELD], serde_json::Value::String(starknet_hash.to_string()));
+        let block = database.block_by_starknet_hash(&evm_hash, false).await.unwrap().unwrap();
+        assert_eq!(block.header.hash, evm_hash);
+        assert!(database.block_by_starknet_hash(&B256::random(), false).await.unwrap().is_none());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_upsert_dropped_transaction() {
+        // Given
+        let mut mongo_fuzzer = MongoFuzzer::new(RANDOM_BYTES_SIZE).await;
+        let database = mongo_fuzzer.mock_database(1).await;
+        let dropped = DroppedTransaction::new(B256::random(), DropReason::Underpriced, "transaction underpriced");
+
+        // When
+        database.upsert_dropped_transaction(dropped.clone()).await.expect("Failed to upsert dropped transaction");
+
+        // Then
+        assert_eq!(database.dropped_transaction(&dropped.hash).await.unwrap(), Some(dropped));
+        assert_eq!(database.dropped_transaction(&B256::random()).await.unwrap(), None);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_pool_events() {
+        // Given
+        let mut mongo_fuzzer = MongoFuzzer::new(RANDOM_BYTES_SIZE).await;
+        let database = mongo_fuzzer.mock_database(1).await;
+        database.create_indexes().await.expect("Failed to create indexes");
+        let hash = B256::random();
+        let events = vec![
+            PoolEvent::new(hash, PoolEventKind::Queued),
+            PoolEvent::new(hash, PoolEventKind::Promoted),
+            PoolEvent::new(hash, PoolEventKind::Relayed { relayer: Felt::ONE, starknet_hash: Felt::TWO }),
+        ];
+
+        // When
+        for event in events.clone() {
+            database.insert_pool_event(event).await.expect("Failed to insert pool event");
+            database.insert_pool_event(PoolEvent::new(B256::random(), PoolEventKind::Added)).await.unwrap();
+        }
+
+        // Then
+        assert_eq!(database.pool_events(&hash).await.unwrap(), events);
+        assert!(database.pool_events(&B256::random()).await.unwrap().is_empty());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_upsert_raw_transaction() {
+        // Given
+        let mut mongo_fuzzer = MongoFuzzer::new(RANDOM_BYTES_SIZE).await;
+        let database = mongo_fuzzer.mock_database(1).await;
+        let raw = RawTransaction { hash: B256::random(), bytes: Bytes::from_static(&[0x02, 0xf8, 0x6c]) };
+
+        // When
+        database.upsert_raw_transaction(raw.clone()).await.expect("Failed to upsert raw transaction");
+
+        // Then
+        assert_eq!(database.raw_transaction(&raw.hash).await.unwrap(), Some(raw.bytes));
+        assert_eq!(database.raw_transaction(&B256::random()).await.unwrap(), None);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_native_balance() {
+        // Given
+        let mut mongo_fuzzer = MongoFuzzer::new(RANDOM_BYTES_SIZE).await;
+        let database = mongo_fuzzer.mock_database(1).await;
+        let starknet_address = Felt::from(0x1234);
+        let snapshots = [(1u64, Some(100u64)), (5, Some(50)), (8, None)].map(|(block_number, balance)| {
+            doc! {"balance": {
+                "address": format!("0x{:0>64}", "1234"),
+                "blockNumber": format!("0x{block_number:016x}"),
+                "balance": balance.map(|balance| format!("{balance:#x}")),
+            }}
+        });
+        database
+            .inner()
+            .collection::<mongodb::bson::Document>("balances")
+            .insert_many(snapshots)
+            .await
+            .expect("Failed to insert snapshots");
+
+        // When
+        let before_first = database.native_balance(&starknet_address, 0).await.unwrap();
+        let at_first = database.native_balance(&starknet_address, 1).await.unwrap();
+        let between = database.native_balance(&starknet_address, 4).await.unwrap();
+        let before_stale = database.native_balance(&starknet_address, 7).await.unwrap();
+        let after_stale = database.native_balance(&starknet_address, 10).await.unwrap();
+        let other_account = database.native_balance(&Felt::ONE, 10).await.unwrap();
+
+        // Then
+        assert_eq!(before_first, None);
+        assert_eq!(at_first, Some(U256::from(100)));
+        assert_eq!(between, Some(U256::from(100)));
+        assert_eq!(before_stale, Some(U256::from(50)));
+        assert_eq!(after_stale, None);
+        assert_eq!(other_account, None);
     }
 }
@@ -5,6 +5,7 @@ use crate::providers::eth_provider::constant::{
 use alloy_primitives::{Address, B256};
 use alloy_rpc_types::{BlockHashOrNumber, Index, Topic};
 use mongodb::bson::{doc, Document};
+use starknet::core::types::Felt;
 use std::fmt::{Display, LowerHex};
 
 /// A trait that defines possible key filters for blocks in the
@@ -30,6 +31,8 @@ pub trait TransactionFiltering {
 pub trait LogFiltering {
     /// Returns the key for the transaction hash.
     fn address(&self) -> &'static str;
+    /// Returns the key for the log index in the block.
+    fn log_index(&self) -> &'static str;
 }
 
 /// A type used for a mapping between:
@@ -54,6 +57,66 @@ impl TransactionFiltering for EthStarknetTransactionHash {
     }
 }
 
+/// A dropped transaction type used as a target for the filter.
+#[derive(Debug, Default)]
+pub struct DroppedTransaction;
+
+impl Display for DroppedTransaction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "dropped")
+    }
+}
+
+impl TransactionFiltering for DroppedTransaction {
+    fn transaction_hash(&self) -> &'static str {
+        "hash"
+    }
+
+    fn transaction_index(&self) -> &'static str {
+        ""
+    }
+}
+
+/// A pool event type used as a target for the filter.
+#[derive(Debug, Default)]
+pub struct PoolEvent;
+
+impl Display for PoolEvent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "event")
+    }
+}
+
+impl TransactionFiltering for PoolEvent {
+    fn transaction_hash(&self) -> &'static str {
+        "hash"
+    }
+
+    fn transaction_index(&self) -> &'static str {
+        ""
+    }
+}
+
+/// A raw transaction type used as a target for the filter.
+#[derive(Debug, Default)]
+pub struct RawTransaction;
+
+impl Display for RawTransaction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "raw")
+    }
+}
+
+impl TransactionFiltering for RawTransaction {
+    fn transaction_hash(&self) -> &'static str {
+        "hash"
+    }
+
+    fn transaction_index(&self) -> &'static str {
+        ""
+    }
+}
+
 /// A transaction type used as a target for the filter.
 #[derive(Debug, Default)]
 pub struct Transaction;
@@ -154,10 +217,44 @@ impl BlockFiltering for Log {
     }
 }
 
+impl TransactionFiltering for Log {
+    fn transaction_hash(&self) -> &'static str {
+        "transactionHash"
+    }
+
+    fn transaction_index(&self) -> &'static str {
+        "transactionIndex"
+    }
+}
+
 impl LogFiltering for Log {
     fn address(&self) -> &'static str {
         "address"
     }
+
+    fn log_index(&self) -> &'static str {
+        "logIndex"
+    }
+}
+
+/// A native token balance snapshot type used as a target for the filter.
+#[derive(Debug, Default)]
+pub struct Balance;
+
+impl Display for Balance {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "balance")
+    }
+}
+
+impl BlockFiltering for Balance {
+    fn block_hash(&self) -> &'static str {
+        ""
+    }
+
+    fn block_number(&self) -> &'static str {
+        "blockNumber"
+    }
 }
 
 /// Builder for creating a filter on the Ethereum database.
@@ -186,6 +283,17 @@ impl<T: BlockFiltering + Display + Default> EthDatabaseFilterBuilder<T> {
         self
     }
 
+    /// Adds a filter on a set of block numbers.
+    #[must_use]
+    pub fn with_block_numbers(mut self, numbers: &[u64]) -> Self {
+        let key = format!("{}.{}", self.target, self.target.block_number());
+        self.filter.insert(
+            key,
+            doc! {"$in": numbers.iter().map(|n| format_hex(n, BLOCK_NUMBER_HEX_STRING_LEN)).collect::<Vec<_>>()},
+        );
+        self
+    }
+
     /// Adds a filter on the block hash or number.
     #[must_use]
     pub fn with_block_hash_or_number(self, block_hash_or_number: BlockHashOrNumber) -> Self {
@@ -194,6 +302,17 @@ impl<T: BlockFiltering + Display + Default> EthDatabaseFilterBuilder<T> {
             BlockHashOrNumber::Number(number) => self.with_block_number(number),
         }
     }
+
+    /// Adds a filter on the block number range.
+    #[must_use]
+    pub fn with_block_number_range(mut self, from: u64, to: u64) -> Self {
+        let key = format!("{}.{}", self.target, self.target.block_number());
+        self.filter.insert(
+            key,
+            doc! {"$gte": format_hex(from, BLOCK_NUMBER_HEX_STRING_LEN), "$lte": format_hex(to, BLOCK_NUMBER_HEX_STRING_LEN)},
+        );
+        self
+    }
 }
 
 impl<T: TransactionFiltering + Display + Default> EthDatabaseFilterBuilder<T> {
@@ -205,6 +324,15 @@ impl<T: TransactionFiltering + Display + Default> EthDatabaseFilterBuilder<T> {
         self
     }
 
+    /// Adds a filter on a set of transaction hashes.
+    #[must_use]
+    pub fn with_tx_hashes(mut self, hashes: &[B256]) -> Self {
+        let key = format!("{}.{}", self.target, self.target.transaction_hash());
+        self.filter
+            .insert(key, doc! {"$in": hashes.iter().map(|h| format_hex(h, HASH_HEX_STRING_LEN)).collect::<Vec<_>>()});
+        self
+    }
+
     /// Adds a filter on the transaction index in the block.
     #[must_use]
     pub fn with_tx_index(mut self, index: &Index) -> Self {
@@ -215,6 +343,52 @@ impl<T: TransactionFiltering + Display + Default> EthDatabaseFilterBuilder<T> {
     }
 }
 
+impl<T: BlockFiltering + TransactionFiltering + Display + Default> EthDatabaseFilterBuilder<T> {
+    /// Adds a filter on the position in the chain, keeping only the documents located strictly
+    /// after the transaction at `transaction_index` in block `block_number`.
+    #[must_use]
+    pub fn with_position_after(mut self, block_number: u64, transaction_index: u64) -> Self {
+        let number_key = format!("{}.{}", self.target, self.target.block_number());
+        let index_key = format!("{}.{}", self.target, self.target.transaction_index());
+        let block_number = format_hex(block_number, BLOCK_NUMBER_HEX_STRING_LEN);
+        let transaction_index = format_hex(transaction_index, U64_HEX_STRING_LEN);
+        let next_blocks = doc! {&number_key: {"$gt": block_number.clone()}};
+        let next_transactions = doc! {number_key: block_number, index_key: {"$gt": transaction_index}};
+        self.filter.insert("$and", vec![doc! {"$or": [next_blocks, next_transactions]}]);
+        self
+    }
+}
+
+impl EthDatabaseFilterBuilder<Transaction> {
+    /// Adds a filter on the transactions sent by or sent to the address.
+    #[must_use]
+    pub fn with_sender_or_recipient(mut self, address: &Address) -> Self {
+        let address = format_hex(address, ADDRESS_HEX_STRING_LEN);
+        let from_key = format!("{}.from", self.target);
+        let to_key = format!("{}.to", self.target);
+        self.filter.insert("$or", vec![doc! {from_key: address.clone()}, doc! {to_key: address}]);
+        self
+    }
+
+    /// Adds a filter on the transactions sent by the address.
+    #[must_use]
+    pub fn with_sender(mut self, address: &Address) -> Self {
+        let key = format!("{}.from", self.target);
+        self.filter.insert(key, format_hex(address, ADDRESS_HEX_STRING_LEN));
+        self
+    }
+}
+
+impl EthDatabaseFilterBuilder<Balance> {
+    /// Adds a filter on the Starknet address of the account.
+    #[must_use]
+    pub fn with_starknet_address(mut self, address: &Felt) -> Self {
+        let key = format!("{}.address", self.target);
+        self.filter.insert(key, format_hex(B256::from(address.to_bytes_be()), HASH_HEX_STRING_LEN));
+        self
+    }
+}
+
 impl<T: LogFiltering + BlockFiltering + Display + Default> EthDatabaseFilterBuilder<T> {
     /// Adds a filter on the log address.
     #[must_use]
@@ -230,17 +404,6 @@ impl<T: LogFiltering + BlockFiltering + Display + Default> EthDatabaseFilterBuil
         self
     }
 
-    /// Adds a filter on the block number range.
-    #[must_use]
-    pub fn with_block_number_range(mut self, from: u64, to: u64) -> Self {
-        let key = format!("{}.{}", self.target, self.target.block_number());
-        self.filter.insert(
-            key,
-            doc! {"$gte": format_hex(from, BLOCK_NUMBER_HEX_STRING_LEN), "$lte": format_hex(to, BLOCK_NUMBER_HEX_STRING_LEN)},
-        );
-        self
-    }
-
     /// Adds a filter on the topics.
     #[must_use]
     pub fn with_topics(mut self, topics: &[Topic; 4]) -> Self {
@@ -254,8 +417,11 @@ impl<T: LogFiltering + BlockFiltering + Display + Default> EthDatabaseFilterBuil
         // Iterate over the topics and add the filter to the filter vector
         for (index, topic_set) in topics.iter().enumerate() {
             let key = format!("log.topics.{index}");
-            let topics: Vec<_> =
+            // The topics of a position are matched with OR semantics, and sorted so that the
+            // query doesn't depend on the iteration order of the set
+            let mut topics: Vec<_> =
                 topic_set.clone().into_iter().map(|t| format_hex(t, LOGS_TOPICS_HEX_STRING_LEN)).collect();
+            topics.sort();
 
             if topics.len() == 1 {
                 // If the topic array has only one element, use an equality filter
@@ -269,6 +435,27 @@ impl<T: LogFiltering + BlockFiltering + Display + Default> EthDatabaseFilterBuil
         self.filter.extend(doc! {"$and": filter});
         self
     }
+
+    /// Adds a filter on the position in the chain, keeping only the logs located strictly after
+    /// the log at `log_index` in block `block_number`. Must be added after the topics, which
+    /// replace the conditions of the filter.
+    #[must_use]
+    pub fn with_log_position_after(mut self, block_number: u64, log_index: u64) -> Self {
+        let number_key = format!("{}.{}", self.target, self.target.block_number());
+        let index_key = format!("{}.{}", self.target, self.target.log_index());
+        let block_number = format_hex(block_number, BLOCK_NUMBER_HEX_STRING_LEN);
+        let log_index = format_hex(log_index, U64_HEX_STRING_LEN);
+        let next_blocks = doc! {&number_key: {"$gt": block_number.clone()}};
+        let next_logs = doc! {number_key: block_number, index_key: {"$gt": log_index}};
+        let position = doc! {"$or": [next_blocks, next_logs]};
+        match self.filter.get_array_mut("$and") {
+            Ok(conditions) => conditions.push(position.into()),
+            Err(_) => {
+                self.filter.insert("$and", vec![position]);
+            }
+        }
+        self
+    }
 }
 
 impl<T: Default> EthDatabaseFilterBuilder<T> {
@@ -473,6 +660,131 @@ mod tests {
         assert_eq!(filter, doc! {"log.blockNumber": {"$gte": "0x0000000000000001", "$lte": "0x000000000000000a"}});
     }
 
+    #[test]
+    fn test_transaction_block_number_range_filter() {
+        // Given
+        let builder = EthDatabaseFilterBuilder::<Transaction>::default();
+
+        // When
+        let filter = builder.with_block_number_range(1, 10).build();
+
+        // Then
+        assert_eq!(filter, doc! {"tx.blockNumber": {"$gte": "0x0000000000000001", "$lte": "0x000000000000000a"}});
+    }
+
+    #[test]
+    fn test_transaction_sender_or_recipient_filter() {
+        // Given
+        let builder = EthDatabaseFilterBuilder::<Transaction>::default();
+
+        // When
+        let filter = builder.with_sender_or_recipient(&Address::left_padding_from(&[1])).build();
+
+        // Then
+        assert_eq!(
+            filter,
+            doc! {
+                "$or": [
+                    {"tx.from": "0x0000000000000000000000000000000000000001"},
+                    {"tx.to": "0x0000000000000000000000000000000000000001"}
+                ]
+            }
+        );
+    }
+
+    #[test]
+    fn test_transaction_sender_filter() {
+        // Given
+        let builder = EthDatabaseFilterBuilder::<Transaction>::default();
+
+        // When
+        let filter = builder.with_sender(&Address::left_padding_from(&[1])).build();
+
+        // Then
+        assert_eq!(filter, doc! {"tx.from": "0x0000000000000000000000000000000000000001"});
+    }
+
+    #[test]
+    fn test_balance_filter() {
+        // Given
+        let builder = EthDatabaseFilterBuilder::<Balance>::default();
+
+        // When
+        let filter = builder.with_starknet_address(&Felt::ONE).with_block_number_range(0, 10).build();
+
+        // Then
+        assert_eq!(
+            filter,
+            doc! {
+                "balance.address": "0x0000000000000000000000000000000000000000000000000000000000000001",
+                "balance.blockNumber": {"$gte": "0x0000000000000000", "$lte": "0x000000000000000a"}
+            }
+        );
+    }
+
+    #[test]
+    fn test_transaction_hashes_filter() {
+        // Given
+        let builder = EthDatabaseFilterBuilder::<Transaction>::default();
+
+        // When
+        let filter = builder.with_tx_hashes(&[B256::left_padding_from(&[1])]).build();
+
+        // Then
+        assert_eq!(
+            filter,
+            doc! {"tx.hash": {"$in": ["0x0000000000000000000000000000000000000000000000000000000000000001"]}}
+        );
+    }
+
+    #[test]
+    fn test_log_position_after_filter() {
+        // Given
+        let builder = EthDatabaseFilterBuilder::<Log>::default();
+
+        // When
+        let filter = builder.with_position_after(2, 3).build();
+
+        // Then
+        assert_eq!(
+            filter,
+            doc! {
+                "$and": [{
+                    "$or": [
+                        {"log.blockNumber": {"$gt": "0x0000000000000002"}},
+                        {"log.blockNumber": "0x0000000000000002", "log.transactionIndex": {"$gt": "0x0000000000000003"}}
+                    ]
+                }]
+            }
+        );
+    }
+
+    #[test]
+    fn test_log_position_after_topics_filter() {
+        // Given
+        let builder = EthDatabaseFilterBuilder::<Log>::default();
+        let topics = [B256::left_padding_from(&[1]).into(), Topic::default(), Topic::default(), Topic::default()];
+
+        // When
+        let filter = builder.with_topics(&topics).with_log_position_after(2, 3).build();
+
+        // Then
+        assert_eq!(
+            filter,
+            doc! {
+                "$and": [
+                    { "log.topics.0": "0x0000000000000000000000000000000000000000000000000000000000000001" },
+                    {
+                        "$or": [
+                            {"log.blockNumber": {"$gt": "0x0000000000000002"}},
+                            {"log.blockNumber": "0x0000000000000002", "log.logIndex": {"$gt": "0x0000000000000003"}}
+                        ]
+                    }
+                ]
+            }
+        );
+    }
+
     #[test]
     fn test_log_empty_addresses_filter() {
         // Given
@@ -557,4 +869,30 @@ mod tests {
                     == doc! { "log.topics.3": {"$in": ["0x0000000000000000000000000000000000000000000000000000000000000006", "0x0000000000000000000000000000000000000000000000000000000000000005"]} }
         );
     }
+
+    #[test]
+    fn test_log_ored_topics_filter() {
+        // Given
+        let builder = EthDatabaseFilterBuilder::<Log>::default();
+        let topics: [FilterSet<B256>; 4] = [
+            vec![B256::left_padding_from(&[2]), B256::left_padding_from(&[1])].into(),
+            Topic::default(),
+            B256::left_padding_from(&[3]).into(),
+            Topic::default(),
+        ];
+
+        // When
+        let filter = builder.with_topics(&topics).build();
+
+        // Then
+        assert_eq!(
+            filter,
+            doc! {
+                "$and": [
+                    { "log.topics.0": {"$in": ["0x0000000000000000000000000000000000000000000000000000000000000001", "0x0000000000000000000000000000000000000000000000000000000000000002"]} },
+                    { "log.topics.2": "0x0000000000000000000000000000000000000000000000000000000000000003" }
+                ]
+            }
+        );
+    }
 }
@@ -0,0 +1,115 @@
+use super::{
+    relayer::is_duplicate_key,
+    types::instance::{InstanceLease, StoredInstanceLease},
+    Database,
+};
+use crate::providers::eth_provider::error::{EthApiError, KakarotError};
+use async_trait::async_trait;
+use mongodb::{bson::doc, options::UpdateOptions};
+use std::time::Duration;
+use tracing::instrument;
+
+/// Trait for electing a single instance of the RPC for a role, among the instances sharing the
+/// database.
+#[async_trait]
+pub trait InstanceLeaseStore {
+    /// Acquires or renews the lease of the role for the instance, for the given duration. The
+    /// lease is only acquired if it isn't held by another instance or if it expired. Returns true
+    /// if the instance holds the lease.
+    async fn acquire_instance_lease(&self, role: &str, holder: &str, ttl: Duration) -> Result<bool, EthApiError>;
+    /// Releases the lease of the role, if held by the instance.
+    async fn release_instance_lease(&self, role: &str, holder: &str) -> Result<(), EthApiError>;
+    /// Returns the lease of the role, if any, expired or not.
+    async fn instance_lease(&self, role: &str) -> Result<Option<InstanceLease>, EthApiError>;
+}
+
+#[async_trait]
+impl InstanceLeaseStore for Database {
+    #[instrument(skip_all, name = "db::acquire_instance_lease", err)]
+    async fn acquire_instance_lease(&self, role: &str, holder: &str, ttl: Duration) -> Result<bool, EthApiError> {
+        let ttl = i64::try_from(ttl.as_millis()).unwrap_or(i64::MAX);
+
+        // The lease is taken over if it is held by the instance or expired, and created if missing.
+        // If the lease is held by another instance, the upsert violates the unique index on the
+        // role. The expiry is measured by the clock of the database ($$NOW), the clocks of the
+        // instances possibly being skewed.
+        let filter = doc! {
+            "lease.role": role,
+            "$or": [{"lease.holder": holder}, {"$expr": {"$lt": ["$lease.expiresAt", "$$NOW"]}}],
+        };
+        let update = vec![doc! {"$set": {"lease.holder": holder, "lease.expiresAt": {"$add": ["$$NOW", ttl]}}}];
+        match self
+            .collection::<StoredInstanceLease>()
+            .update_one(filter, update)
+            .with_options(UpdateOptions::builder().upsert(true).build())
+            .await
+        {
+            Ok(_) => Ok(true),
+            Err(err) if is_duplicate_key(&err) => Ok(false),
+            Err(err) => Err(KakarotError::from(err).into()),
+        }
+    }
+
+    #[instrument(skip_all, name = "db::release_instance_lease", err)]
+    async fn release_instance_lease(&self, role: &str, holder: &str) -> Result<(), EthApiError> {
+        Ok(self.delete_one::<StoredInstanceLease>(doc! {"lease.role": role, "lease.holder": holder}).await?)
+    }
+
+    #[instrument(skip_all, name = "db::instance_lease", err)]
+    async fn instance_lease(&self, role: &str) -> Result<Option<InstanceLease>, EthApiError> {
+        Ok(self.get_one::<StoredInstanceLease>(doc! {"lease.role": role}, None).await?.map(Into::into))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::mongo::{MongoFuzzer, RANDOM_BYTES_SIZE};
+
+    const TTL: Duration = Duration::from_secs(30);
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_acquire_instance_lease() {
+        // Given
+        let mut mongo_fuzzer = MongoFuzzer::new(RANDOM_BYTES_SIZE).await;
+        let database = mongo_fuzzer.mock_database(1).await;
+        database.create_indexes().await.expect("Failed to create indexes");
+
+        // When
+        let first = database.acquire_instance_lease("writer", "a", TTL).await.unwrap();
+        let other = database.acquire_instance_lease("writer", "b", TTL).await.unwrap();
+        let renewed = database.acquire_instance_lease("writer", "a", TTL).await.unwrap();
+        let other_role = database.acquire_instance_lease("indexer", "b", TTL).await.unwrap();
+
+        // Then
+        assert!(first);
+        assert!(!other);
+        assert!(renewed);
+        assert!(other_role);
+        let lease = database.instance_lease("writer").await.unwrap().expect("Missing lease");
+        assert_eq!(lease.holder, "a");
+        assert!(lease.expires_at > mongodb::bson::DateTime::now());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_instance_lease_takeover() {
+        // Given
+        let mut mongo_fuzzer = MongoFuzzer::new(RANDOM_BYTES_SIZE).await;
+        let database = mongo_fuzzer.mock_database(1).await;
+        database.create_indexes().await.expect("Failed to create indexes");
+        database.acquire_instance_lease("writer", "a", Duration::ZERO).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(5)).await;
+
+        // When
+        let expired = database.acquire_instance_lease("writer", "b", TTL).await.unwrap();
+        database.release_instance_lease("writer", "a").await.unwrap();
+        let held = database.instance_lease("writer").await.unwrap();
+        database.release_instance_lease("writer", "b").await.unwrap();
+        let released = database.acquire_instance_lease("writer", "c", TTL).await.unwrap();
+
+        // Then
+        assert!(expired);
+        assert_eq!(held.map(|lease| lease.holder), Some("b".to_string()));
+        assert!(released);
+    }
+}
@@ -0,0 +1,716 @@
+//! The Mongo database holding the blocks, transactions and receipts indexed from Starknet, along
+//! with the state of the node (relayer nonces, instance leases, cached traces...).
+
+use crate::providers::eth_provider::{
+    database::types::{
+        account::StoredClassHashObservation,
+        archive::StoredArchiveCheckpoint,
+        balance::StoredBalanceSnapshot,
+        header::{StoredBlock, StoredHeader, StoredStarknetBlockHash},
+        instance::StoredInstanceLease,
+        log::StoredLog,
+        receipt::{StoredBlockReceipts, StoredTransactionReceipt},
+        relayer::StoredNonceLease,
+        trace::StoredTrace,
+        transaction::{
+            StoredDroppedTransaction, StoredEthStarknetTransactionHash, StoredPoolEvent, StoredRawTransaction,
+            StoredTransaction, StoredTransactionFees,
+        },
+    },
+    error::KakarotError,
+};
+use futures::TryStreamExt;
+use itertools::Itertools;
+use mongodb::{
+    bson::{doc, Document},
+    error::ErrorKind,
+    options::{
+        CollectionOptions, DatabaseOptions, FindOneOptions, FindOptions, IndexOptions, ReadConcern, ReadPreference,
+        SelectionCriteria, UpdateModifications, UpdateOptions, WriteConcern,
+    },
+    Client, Collection, Database as MongoDatabase, IndexModel,
+};
+use serde::{de::DeserializeOwned, Serialize};
+use std::{
+    collections::HashMap,
+    sync::{Arc, LazyLock},
+    time::Duration,
+};
+
+pub(super) type DatabaseResult<T> = eyre::Result<T, KakarotError>;
+
+/// Maximum size, in bytes, of the capped collection of the pool events, the oldest events being
+/// overwritten once it is full
+pub const POOL_EVENTS_COLLECTION_SIZE: u64 = 256 * 1024 * 1024;
+
+/// Error code of `MongoDB` when creating a collection which already exists
+const NAMESPACE_EXISTS_ERROR_CODE: i32 = 48;
+
+/// Error code of `MongoDB` when creating an index which already exists with other options
+const INDEX_OPTIONS_CONFLICT_ERROR_CODE: i32 = 85;
+
+/// Duration for which the raw bytes of the transactions submitted to the node are kept. Set
+/// with the `RAW_TRANSACTIONS_RETENTION_SECS` environment variable, defaults to 30 days.
+pub static RAW_TRANSACTIONS_RETENTION: LazyLock<Duration> = LazyLock::new(|| {
+    Duration::from_secs(
+        std::env::var("RAW_TRANSACTIONS_RETENTION_SECS").ok().and_then(|val| val.parse().ok()).unwrap_or(30 * 86400),
+    )
+});
+
+/// Duration for which the reasons why the transactions were dropped are kept. Set with the
+/// `DROPPED_TRANSACTIONS_RETENTION_SECS` environment variable, defaults to 7 days.
+pub static DROPPED_TRANSACTIONS_RETENTION: LazyLock<Duration> = LazyLock::new(|| {
+    Duration::from_secs(
+        std::env::var("DROPPED_TRANSACTIONS_RETENTION_SECS").ok().and_then(|val| val.parse().ok()).unwrap_or(7 * 86400),
+    )
+});
+
+/// Struct for encapsulating find options for `MongoDB` queries.
+#[derive(Clone, Debug, Default)]
+pub struct FindOpts(FindOptions);
+
+impl FindOpts {
+    /// Sets the limit for the number of documents to retrieve.
+    #[must_use]
+    pub fn with_limit(mut self, limit: u64) -> Self {
+        self.0.limit = Some(i64::try_from(limit).unwrap_or(i64::MAX));
+        self
+    }
+
+    /// Sets the projection for the documents to retrieve.
+    #[must_use]
+    pub fn with_projection(mut self, projection: Document) -> Self {
+        self.0.projection = Some(projection);
+        self
+    }
+
+    /// Sets the sort order of the documents to retrieve.
+    #[must_use]
+    pub fn with_sort(mut self, sort: Document) -> Self {
+        self.0.sort = Some(sort);
+        self
+    }
+
+    /// Builds and returns the `FindOptions`.
+    pub fn build(self) -> FindOptions {
+        self.0
+    }
+}
+
+/// The configuration of the reads of the database.
+///
+/// Allows large deployments using replica sets to send the heavy reads (e.g. logs and traces)
+/// to secondaries, while the writes always go to the primary.
+#[derive(Debug, Clone, Default)]
+pub struct ReadConfig {
+    /// The connection string used for the reads. Defaults to the connection string of the writes.
+    pub connection_string: Option<String>,
+    /// The default read preference. Defaults to the primary.
+    pub read_preference: Option<ReadPreference>,
+    /// The read preferences of specific collections, overriding the default read preference.
+    pub collection_read_preferences: HashMap<String, ReadPreference>,
+}
+
+/// Parses a read preference mode, e.g. `secondaryPreferred`.
+pub fn parse_read_preference(mode: &str) -> Result<ReadPreference, String> {
+    match mode.to_lowercase().as_str() {
+        "primary" => Ok(ReadPreference::Primary),
+        "primarypreferred" => Ok(ReadPreference::PrimaryPreferred { options: None }),
+        "secondary" => Ok(ReadPreference::Secondary { options: None }),
+        "secondarypreferred" => Ok(ReadPreference::SecondaryPreferred { options: None }),
+        "nearest" => Ok(ReadPreference::Nearest { options: None }),
+        _ => Err(format!(
+            "invalid read preference {mode}, expected one of: primary, primaryPreferred, secondary, \
+             secondaryPreferred, nearest"
+        )),
+    }
+}
+
+/// Parses a comma separated list of `collection:mode` read preferences, e.g. `logs:secondary,receipts:nearest`.
+pub fn parse_collection_read_preferences(value: &str) -> Result<HashMap<String, ReadPreference>, String> {
+    value
+        .replace(' ', "")
+        .split(',')
+        .filter(|entry| !entry.is_empty())
+        .map(|entry| {
+            let (collection, mode) =
+                entry.split_once(':').ok_or_else(|| format!("invalid entry {entry}, expected collection:mode"))?;
+            Ok((collection.to_string(), parse_read_preference(mode)?))
+        })
+        .collect()
+}
+
+/// Wrapper around a `MongoDB` database
+///
+/// The writes are always sent to the database, while the reads can be sent to a distinct
+/// deployment or with a distinct read preference, see [`ReadConfig`].
+#[derive(Clone, Debug)]
+pub struct Database {
+    /// The database used for the writes.
+    database: MongoDatabase,
+    /// The database used for the reads.
+    read_database: MongoDatabase,
+    /// The read preferences overriding the one of the read database for specific collections.
+    collection_read_preferences: Arc<HashMap<String, ReadPreference>>,
+    /// The database holding the logs and receipts moved out of the hot window, if any.
+    archive: Option<Arc<Database>>,
+}
+
+impl Database {
+    pub fn new(database: MongoDatabase) -> Self {
+        Self {
+            read_database: database.clone(),
+            database,
+            collection_read_preferences: Default::default(),
+            archive: None,
+        }
+    }
+
+    /// Sets the database holding the logs and receipts moved out of the hot window.
+    #[must_use]
+    pub fn with_archive(mut self, archive: Self) -> Self {
+        self.archive = Some(Arc::new(archive));
+        self
+    }
+
+    /// Returns the database holding the logs and receipts moved out of the hot window, if any.
+    pub fn archive(&self) -> Option<&Self> {
+        self.archive.as_deref()
+    }
+
+    /// Connects to the database `database_name` using the provided `MongoDB` connection string.
+    ///
+    /// Reads and writes are performed with a majority concern.
+    pub async fn connect(connection_string: &str, database_name: &str) -> DatabaseResult<Self> {
+        Self::connect_with_read_config(connection_string, database_name, &ReadConfig::default()).await
+    }
+
+    /// Connects to the database `database_name` using the provided `MongoDB` connection string for
+    /// the writes, and the read configuration for the reads.
+    ///
+    /// Reads and writes are performed with a majority concern.
+    pub async fn connect_with_read_config(
+        connection_string: &str,
+        database_name: &str,
+        read_config: &ReadConfig,
+    ) -> DatabaseResult<Self> {
+        let database = Self::connect_database(connection_string, database_name, None).await?;
+        let read_database = if read_config.connection_string.is_none() && read_config.read_preference.is_none() {
+            database.clone()
+        } else {
+            let read_connection_string = read_config.connection_string.as_deref().unwrap_or(connection_string);
+            Self::connect_database(read_connection_string, database_name, read_config.read_preference.clone()).await?
+        };
+
+        Ok(Self {
+            database,
+            read_database,
+            collection_read_preferences: Arc::new(read_config.collection_read_preferences.clone()),
+            archive: None,
+        })
+    }
+
+    async fn connect_database(
+        connection_string: &str,
+        database_name: &str,
+        read_preference: Option<ReadPreference>,
+    ) -> DatabaseResult<MongoDatabase> {
+        let client = Client::with_uri_str(connection_string).await?;
+        Ok(client.database_with_options(
+            database_name,
+            DatabaseOptions::builder()
+                .read_concern(ReadConcern::majority())
+                .write_concern(WriteConcern::majority())
+                .selection_criteria(read_preference.map(SelectionCriteria::ReadPreference))
+                .build(),
+        ))
+    }
+
+    /// Creates the indexes used by the queries of the RPC, and the capped collections, if they
+    /// don't exist yet.
+    pub async fn create_indexes(&self) -> DatabaseResult<()> {
+        // Indexes used to retrieve the activity of an address, ordered by position in the chain
+        let transaction_indexes = ["tx.from", "tx.to"].map(|key| {
+            IndexModel::builder().keys(doc! {key: 1, "tx.blockNumber": 1, "tx.transactionIndex": 1}).build()
+        });
+        self.collection::<StoredTransaction>().create_indexes(transaction_indexes).await?;
+
+        // Index used to join the transactions of a block to its header
+        let block_transactions_index = IndexModel::builder().keys(doc! {"tx.blockHash": 1}).build();
+        self.collection::<StoredTransaction>().create_index(block_transactions_index).await?;
+
+        let log_index = IndexModel::builder()
+            .keys(doc! {"log.address": 1, "log.blockNumber": 1, "log.transactionIndex": 1, "log.logIndex": 1})
+            .build();
+        self.collection::<StoredLog>().create_index(log_index).await?;
+
+        // Indexes used to retrieve a block by its Starknet hash, or by its EVM-style hash
+        let header_index = IndexModel::builder().keys(doc! {"header.hash": 1}).build();
+        self.collection::<StoredHeader>().create_index(header_index).await?;
+        let starknet_block_hash_index = IndexModel::builder()
+            .keys(doc! {"starknetBlockHash": 1})
+            .options(IndexOptions::builder().sparse(true).build())
+            .build();
+        self.collection::<StoredHeader>().create_index(starknet_block_hash_index).await?;
+
+        // Index used to join the receipts of a block to its header
+        let receipt_index = IndexModel::builder().keys(doc! {"receipt.blockHash": 1}).build();
+        self.collection::<StoredTransactionReceipt>().create_index(receipt_index).await?;
+
+        let dropped_index = IndexModel::builder().keys(doc! {"dropped.hash": 1}).build();
+        self.collection::<StoredDroppedTransaction>().create_index(dropped_index).await?;
+        // Index deleting the dropped transactions once the retention elapsed
+        self.create_ttl_index::<StoredDroppedTransaction>("storedAt", *DROPPED_TRANSACTIONS_RETENTION).await?;
+        let raw_index = IndexModel::builder().keys(doc! {"raw.hash": 1}).build();
+        self.collection::<StoredRawTransaction>().create_index(raw_index).await?;
+        // Index deleting the raw transactions once the retention elapsed
+        self.create_ttl_index::<StoredRawTransaction>("storedAt", *RAW_TRANSACTIONS_RETENTION).await?;
+
+        // Index used to retrieve a cached trace, and index deleting the traces once expired
+        let trace_index = IndexModel::builder().keys(doc! {"trace.key": 1}).build();
+        self.collection::<StoredTrace>().create_index(trace_index).await?;
+        let trace_expiry_index = IndexModel::builder()
+            .keys(doc! {"trace.expiresAt": 1})
+            .options(IndexOptions::builder().expire_after(Duration::ZERO).build())
+            .build();
+        self.collection::<StoredTrace>().create_index(trace_expiry_index).await?;
+
+        // Index used to retrieve the last balance snapshot of an account before a block
+        let balance_index = IndexModel::builder().keys(doc! {"balance.address": 1, "balance.blockNumber": -1}).build();
+        self.collection::<StoredBalanceSnapshot>().create_index(balance_index).await?;
+
+        // Unique index guaranteeing that a nonce can only be leased once by a relayer
+        let lease_index = IndexModel::builder()
+            .keys(doc! {"lease.relayer": 1, "lease.nonce": 1})
+            .options(IndexOptions::builder().unique(true).build())
+            .build();
+        self.collection::<StoredNonceLease>().create_index(lease_index).await?;
+        // Index used to retrieve the lease of a relayed transaction
+        let lease_eth_hash_index = IndexModel::builder().keys(doc! {"lease.ethHash": 1}).build();
+        self.collection::<StoredNonceLease>().create_index(lease_eth_hash_index).await?;
+
+        // Unique index guaranteeing that a role can only be held by one instance
+        let instance_lease_index = IndexModel::builder()
+            .keys(doc! {"lease.role": 1})
+            .options(IndexOptions::builder().unique(true).build())
+            .build();
+        self.collection::<StoredInstanceLease>().create_index(instance_lease_index).await?;
+
+        // Index used to retrieve the class of an account at a block
+        let class_hash_index =
+            IndexModel::builder().keys(doc! {"observation.address": 1, "observation.fromBlock": -1}).build();
+        self.collection::<StoredClassHashObservation>().create_index(class_hash_index).await?;
+
+        // The pool events are only kept for the post-mortems of the recent transactions, the
+        // collection must be created before its index
+        let created = self
+            .database
+            .create_collection(StoredPoolEvent::collection_name())
+            .capped(true)
+            .size(POOL_EVENTS_COLLECTION_SIZE)
+            .await;
+        if let Err(err) = created {
+            if !matches!(err.kind.as_ref(), ErrorKind::Command(error) if error.code == NAMESPACE_EXISTS_ERROR_CODE) {
+                return Err(err.into());
+            }
+        }
+        let pool_event_index = IndexModel::builder().keys(doc! {"event.hash": 1}).build();
+        self.collection::<StoredPoolEvent>().create_index(pool_event_index).await?;
+
+        Ok(())
+    }
+
+    /// Creates the index deleting the documents of the collection once the duration elapsed since
+    /// the date of the key. The expiry of an existing index is updated if the duration changed.
+    async fn create_ttl_index<T>(&self, key: &str, expire_after: Duration) -> DatabaseResult<()>
+    where
+        T: CollectionName + Sync + Send,
+    {
+        let index = IndexModel::builder()
+            .keys(doc! {key: 1})
+            .options(IndexOptions::builder().expire_after(expire_after).build())
+            .build();
+        let Err(err) = self.collection::<T>().create_index(index).await else {
+            return Ok(());
+        };
+        if !matches!(err.kind.as_ref(), ErrorKind::Command(error) if error.code == INDEX_OPTIONS_CONFLICT_ERROR_CODE) {
+            return Err(err.into());
+        }
+
+        // The duration was changed since the index was created
+        let expire_after_seconds = i64::try_from(expire_after.as_secs()).unwrap_or(i64::MAX);
+        self.database
+            .run_command(doc! {
+                "collMod": T::collection_name(),
+                "index": {"keyPattern": {key: 1}, "expireAfterSeconds": expire_after_seconds},
+            })
+            .await?;
+        Ok(())
+    }
+
+    /// Get a reference to the inner `MongoDatabase`, used for the writes
+    pub const fn inner(&self) -> &MongoDatabase {
+        &self.database
+    }
+
+    /// Get a mutable reference to the inner `MongoDatabase`, used for the writes
+    pub fn inner_mut(&mut self) -> &mut MongoDatabase {
+        &mut self.database
+    }
+
+    /// Returns a collection from the database.
+    pub fn collection<T>(&self) -> Collection<T>
+    where
+        T: CollectionName + Sync + Send,
+    {
+        self.database.collection::<T>(T::collection_name())
+    }
+
+    /// Returns a collection from the read database, with the read preference of the collection if any.
+    pub fn read_collection<T>(&self) -> Collection<T>
+    where
+        T: CollectionName + Sync + Send,
+    {
+        let name = T::collection_name();
+        match self.collection_read_preferences.get(name) {
+            Some(read_preference) => self.read_database.collection_with_options::<T>(
+                name,
+                CollectionOptions::builder()
+                    .selection_criteria(SelectionCriteria::ReadPreference(read_preference.clone()))
+                    .build(),
+            ),
+            None => self.read_database.collection::<T>(name),
+        }
+    }
+
+    /// Get a list of documents from a collection
+    pub async fn get<T>(
+        &self,
+        filter: impl Into<Option<Document>>,
+        find_options: impl Into<Option<FindOpts>>,
+    ) -> DatabaseResult<Vec<T>>
+    where
+        T: DeserializeOwned + CollectionName + Sync + Send,
+    {
+        let find_options = find_options.into();
+        Ok(self
+            .read_collection::<T>()
+            .find(Into::<Option<Document>>::into(filter).unwrap_or_default())
+            .with_options(find_options.unwrap_or_default().build())
+            .await?
+            .try_collect()
+            .await?)
+    }
+
+    /// Get all documents from a collection
+    pub async fn get_all<T>(&self) -> DatabaseResult<Vec<T>>
+    where
+        T: DeserializeOwned + CollectionName + Sync + Send,
+    {
+        let find_options = FindOpts::default().build();
+
+        Ok(self.read_collection::<T>().find(Default::default()).with_options(find_options).await?.try_collect().await?)
+    }
+
+    /// Retrieves documents from a collection and converts them into another type.
+    ///
+    /// Returns a vector of documents of type `D` if successful, or an error.
+    pub async fn get_and_map_to<D, T>(
+        &self,
+        filter: impl Into<Option<Document>>,
+        find_options: Option<FindOpts>,
+    ) -> DatabaseResult<Vec<D>>
+    where
+        T: DeserializeOwned + CollectionName + Sync + Send,
+        D: From<T>,
+    {
+        let stored_data: Vec<T> = self.get(filter, find_options).await?;
+        Ok(stored_data.into_iter().map_into().collect())
+    }
+
+    /// Retrieves all documents from a collection and converts them into another type.
+    ///
+    /// Returns a vector of documents of type `D` if successful, or an error.
+    pub async fn get_all_and_map_to<D, T>(&self) -> DatabaseResult<Vec<D>>
+    where
+        T: DeserializeOwned + CollectionName + Sync + Send,
+        D: From<T>,
+    {
+        let stored_data: Vec<T> = self.get_all().await?;
+        Ok(stored_data.into_iter().map_into().collect())
+    }
+
+    /// Get a single document from a collection
+    pub async fn get_one<T>(
+        &self,
+        filter: impl Into<Option<Document>>,
+        sort: impl Into<Option<Document>>,
+    ) -> DatabaseResult<Option<T>>
+    where
+        T: DeserializeOwned + Unpin + Send + Sync + CollectionName,
+    {
+        let find_one_options = FindOneOptions::builder().sort(sort).build();
+        Ok(self
+            .read_collection::<T>()
+            .find_one(Into::<Option<Document>>::into(filter).unwrap_or_default())
+            .with_options(find_one_options)
+            .await?)
+    }
+
+    /// Get the first document from a collection
+    pub async fn get_first<T>(&self) -> DatabaseResult<Option<T>>
+    where
+        T: DeserializeOwned + Unpin + Send + Sync + CollectionName,
+    {
+        Ok(self.read_collection::<T>().find_one(Default::default()).await?)
+    }
+
+    /// Get a single document from aggregated collections
+    pub async fn get_one_aggregate<T>(&self, pipeline: impl IntoIterator<Item = Document>) -> DatabaseResult<Option<T>>
+    where
+        T: DeserializeOwned + CollectionName + Sync + Send,
+    {
+        let mut cursor = self.read_collection::<T>().aggregate(pipeline).await?;
+
+        Ok(cursor.try_next().await?.map(|doc| mongodb::bson::de::from_document(doc)).transpose()?)
+    }
+
+    /// Update a single document in a collection
+    pub async fn update_one<T>(&self, doc: T, filter: impl Into<Document>, upsert: bool) -> DatabaseResult<()>
+    where
+        T: Serialize + CollectionName + Sync + Send,
+    {
+        #[cfg(feature = "chaos")]
+        crate::chaos::FAULT_INJECTOR.database_write().map_err(mongodb::error::Error::from)?;
+        let doc = mongodb::bson::to_document(&doc).map_err(mongodb::error::Error::custom)?;
+        let update_options = UpdateOptions::builder().upsert(upsert).build();
+
+        self.collection::<T>()
+            .update_one(filter.into(), UpdateModifications::Document(doc! {"$set": doc}))
+            .with_options(update_options)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Insert a single document in a collection
+    pub async fn insert_one<T>(&self, doc: T) -> DatabaseResult<()>
+    where
+        T: Serialize + CollectionName + Sync + Send,
+    {
+        #[cfg(feature = "chaos")]
+        crate::chaos::FAULT_INJECTOR.database_write().map_err(mongodb::error::Error::from)?;
+        self.collection::<T>().insert_one(doc).await?;
+        Ok(())
+    }
+
+    /// Delete a single document from a collection
+    pub async fn delete_one<T>(&self, filter: impl Into<Document>) -> DatabaseResult<()>
+    where
+        T: CollectionName + Sync + Send,
+    {
+        #[cfg(feature = "chaos")]
+        crate::chaos::FAULT_INJECTOR.database_write().map_err(mongodb::error::Error::from)?;
+        self.collection::<T>().delete_one(filter.into()).await?;
+        Ok(())
+    }
+
+    /// Delete all the documents from a collection matching the filter, returning their count
+    pub async fn delete_many<T>(&self, filter: impl Into<Document>) -> DatabaseResult<u64>
+    where
+        T: CollectionName + Sync + Send,
+    {
+        #[cfg(feature = "chaos")]
+        crate::chaos::FAULT_INJECTOR.database_write().map_err(mongodb::error::Error::from)?;
+        Ok(self.collection::<T>().delete_many(filter.into()).await?.deleted_count)
+    }
+
+    /// Count the number of documents in a collection matching the filter
+    pub async fn count<T>(&self, filter: Document) -> DatabaseResult<u64>
+    where
+        T: CollectionName + Sync + Send,
+    {
+        Ok(self.read_collection::<T>().count_documents(filter).await?)
+    }
+}
+
+impl From<MongoDatabase> for Database {
+    fn from(database: MongoDatabase) -> Self {
+        Self::new(database)
+    }
+}
+
+/// Trait for associating a type with its collection name
+pub trait CollectionName {
+    /// Returns the name of the collection associated with the type
+    fn collection_name() -> &'static str;
+}
+
+/// Implement [`CollectionName`] for [`StoredHeader`]
+impl CollectionName for StoredHeader {
+    fn collection_name() -> &'static str {
+        "headers"
+    }
+}
+
+/// Implement [`CollectionName`] for [`StoredStarknetBlockHash`], read from the headers
+impl CollectionName for StoredStarknetBlockHash {
+    fn collection_name() -> &'static str {
+        "headers"
+    }
+}
+
+/// Implement [`CollectionName`] for [`StoredTransaction`]
+impl CollectionName for StoredTransaction {
+    fn collection_name() -> &'static str {
+        "transactions"
+    }
+}
+
+/// Implement [`CollectionName`] for [`StoredTransactionFees`], read from the transactions
+impl CollectionName for StoredTransactionFees {
+    fn collection_name() -> &'static str {
+        StoredTransaction::collection_name()
+    }
+}
+
+/// Implement [`CollectionName`] for [`StoredTransactionReceipt`]
+impl CollectionName for StoredTransactionReceipt {
+    fn collection_name() -> &'static str {
+        "receipts"
+    }
+}
+
+/// Implement [`CollectionName`] for [`StoredBlock`], aggregated from the headers
+impl CollectionName for StoredBlock {
+    fn collection_name() -> &'static str {
+        StoredHeader::collection_name()
+    }
+}
+
+/// Implement [`CollectionName`] for [`StoredBlockReceipts`], aggregated from the headers
+impl CollectionName for StoredBlockReceipts {
+    fn collection_name() -> &'static str {
+        StoredHeader::collection_name()
+    }
+}
+
+/// Implement [`CollectionName`] for [`StoredLog`]
+impl CollectionName for StoredLog {
+    fn collection_name() -> &'static str {
+        "logs"
+    }
+}
+
+/// Implement [`CollectionName`] for [`StoredEthStarknetTransactionHash`]
+impl CollectionName for StoredEthStarknetTransactionHash {
+    fn collection_name() -> &'static str {
+        "transaction_hashes"
+    }
+}
+
+/// Implement [`CollectionName`] for [`StoredDroppedTransaction`]
+impl CollectionName for StoredDroppedTransaction {
+    fn collection_name() -> &'static str {
+        "dropped_transactions"
+    }
+}
+
+/// Implement [`CollectionName`] for [`StoredPoolEvent`]
+impl CollectionName for StoredPoolEvent {
+    fn collection_name() -> &'static str {
+        "pool_events"
+    }
+}
+
+/// Implement [`CollectionName`] for [`StoredRawTransaction`]
+impl CollectionName for StoredRawTransaction {
+    fn collection_name() -> &'static str {
+        "raw_transactions"
+    }
+}
+
+/// Implement [`CollectionName`] for [`StoredTrace`]
+impl CollectionName for StoredTrace {
+    fn collection_name() -> &'static str {
+        "traces"
+    }
+}
+
+/// Implement [`CollectionName`] for [`StoredBalanceSnapshot`]
+impl CollectionName for StoredBalanceSnapshot {
+    fn collection_name() -> &'static str {
+        "balances"
+    }
+}
+
+/// Implement [`CollectionName`] for [`StoredNonceLease`]
+impl CollectionName for StoredNonceLease {
+    fn collection_name() -> &'static str {
+        "nonce_leases"
+    }
+}
+
+/// Implement [`CollectionName`] for [`StoredInstanceLease`]
+impl CollectionName for StoredInstanceLease {
+    fn collection_name() -> &'static str {
+        "instance_leases"
+    }
+}
+
+/// Implement [`CollectionName`] for [`StoredClassHashObservation`]
+impl CollectionName for StoredClassHashObservation {
+    fn collection_name() -> &'static str {
+        "class_hashes"
+    }
+}
+
+/// Implement [`CollectionName`] for [`StoredArchiveCheckpoint`]
+impl CollectionName for StoredArchiveCheckpoint {
+    fn collection_name() -> &'static str {
+        "archive_checkpoints"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::mongo::{MongoFuzzer, RANDOM_BYTES_SIZE};
+
+    #[test]
+    fn test_parse_read_preference() {
+        assert!(matches!(parse_read_preference("primary"), Ok(ReadPreference::Primary)));
+        assert!(matches!(parse_read_preference("secondaryPreferred"), Ok(ReadPreference::SecondaryPreferred { .. })));
+        assert!(parse_read_preference("tertiary").is_err());
+    }
+
+    #[test]
+    fn test_parse_collection_read_preferences() {
+        // When
+        let preferences = parse_collection_read_preferences("logs:secondary, receipts:nearest,").unwrap();
+
+        // Then
+        assert_eq!(preferences.len(), 2);
+        assert!(matches!(preferences["logs"], ReadPreference::Secondary { .. }));
+        assert!(matches!(preferences["receipts"], ReadPreference::Nearest { .. }));
+        assert!(parse_collection_read_preferences("logs").is_err());
+        assert!(parse_collection_read_preferences("logs:tertiary").is_err());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_create_ttl_index_updates_the_expiry() {
+        // Given
+        let mut mongo_fuzzer = MongoFuzzer::new(RANDOM_BYTES_SIZE).await;
+        let database = mongo_fuzzer.mock_database(1).await;
+        database.create_ttl_index::<StoredRawTransaction>("storedAt", Duration::from_secs(60)).await.unwrap();
+
+        // When
+        database.create_ttl_index::<StoredRawTransaction>("storedAt", Duration::from_secs(120)).await.unwrap();
+
+        // Then
+        let indexes: Vec<IndexModel> =
+            database.collection::<StoredRawTransaction>().list_indexes().await.unwrap().try_collect().await.unwrap();
+        let ttl_index = indexes.iter().find(|index| index.keys == doc! {"storedAt": 1}).expect("Missing TTL index");
+        assert_eq!(ttl_index.options.as_ref().and_then(|options| options.expire_after), Some(Duration::from_secs(120)));
+    }
+}
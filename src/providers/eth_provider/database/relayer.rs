@@ -0,0 +1,326 @@
+use super::{
+    types::relayer::{NonceLease, NonceLeaseStatus, StoredNonceLease},
+    Database, FindOpts,
+};
+use crate::providers::eth_provider::error::{EthApiError, KakarotError};
+use alloy_primitives::B256;
+use async_trait::async_trait;
+use mongodb::{
+    bson::{doc, Bson, Document},
+    error::{ErrorKind, WriteFailure},
+};
+use serde::Serialize;
+use starknet::core::types::Felt;
+use std::{
+    sync::LazyLock,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use tracing::instrument;
+
+/// Duration after which a pending lease, whose submission outcome is unknown (e.g. because the
+/// node crashed during the submission), is considered abandoned and its nonce can be leased again.
+/// Set with the `NONCE_LEASE_TIMEOUT_SECS` environment variable, defaults to 5 minutes.
+pub static NONCE_LEASE_TIMEOUT: LazyLock<Duration> = LazyLock::new(|| {
+    Duration::from_secs(
+        std::env::var("NONCE_LEASE_TIMEOUT_SECS").ok().and_then(|val| val.parse::<u64>().ok()).unwrap_or(300),
+    )
+});
+
+/// Duration after which the transaction of a submitted lease is checked against Starknet, in order
+/// to release the nonce of a transaction dropped after its submission.
+pub const SUBMITTED_NONCE_LEASE_GRACE: Duration = Duration::from_secs(30);
+
+/// Maximum number of attempts to lease a nonce when racing with other relaying tasks.
+const MAX_LEASE_ATTEMPTS: u64 = 16;
+
+/// Error code returned by `MongoDB` when a write violates a unique index.
+const DUPLICATE_KEY_ERROR_CODE: i32 = 11000;
+
+/// Trait for persisting the nonces used by the relayers, ahead of the submission of their
+/// transactions to Starknet.
+#[async_trait]
+pub trait RelayerNonceStore {
+    /// Leases the next nonce of the relayer for the Ethereum transaction with the given hash.
+    ///
+    /// The leased nonce is the lowest nonce from the nonce of the relayer on chain which isn't
+    /// leased, so that the nonces of the released leases are leased again rather than left as a
+    /// gap. Leases of nonces already consumed on chain, as well as pending leases older than
+    /// [`NONCE_LEASE_TIMEOUT`], are released first.
+    async fn lease_nonce(&self, relayer: Felt, chain_nonce: u64, eth_hash: B256) -> Result<u64, EthApiError>;
+    /// Marks the lease of the nonce as submitted with the given Starknet transaction hash.
+    async fn submit_nonce_lease(&self, relayer: Felt, nonce: u64, starknet_hash: Felt) -> Result<(), EthApiError>;
+    /// Returns the submitted leases of the relayer from the given nonce, by increasing nonce.
+    async fn submitted_nonce_leases(&self, relayer: Felt, from_nonce: u64) -> Result<Vec<NonceLease>, EthApiError>;
+    /// Releases the lease of the nonce, making it available again.
+    async fn release_nonce_lease(&self, relayer: Felt, nonce: u64) -> Result<(), EthApiError>;
+    /// Releases the pending leases of all the relayers older than the timeout, including the
+    /// ones of relayers which no longer lease nonces. Returns the number of released leases.
+    async fn release_expired_nonce_leases(&self, timeout: Duration) -> Result<u64, EthApiError>;
+    /// Returns the leases of all the relayers.
+    async fn nonce_leases(&self) -> Result<Vec<NonceLease>, EthApiError>;
+    /// Returns the lease of the nonce used to relay the Ethereum transaction with the given hash.
+    /// Returns None if the transaction isn't being relayed.
+    async fn nonce_lease_of(&self, eth_hash: &B256) -> Result<Option<NonceLease>, EthApiError>;
+}
+
+#[async_trait]
+impl RelayerNonceStore for Database {
+    #[instrument(skip_all, name = "db::lease_nonce", err)]
+    async fn lease_nonce(&self, relayer: Felt, chain_nonce: u64, eth_hash: B256) -> Result<u64, EthApiError> {
+        let relayer_filter = doc! {"lease.relayer": to_bson(&relayer)?};
+
+        // Release the leases of the nonces consumed on chain and the abandoned pending leases
+        let mut consumed = relayer_filter.clone();
+        consumed.insert("lease.nonce", doc! {"$lt": to_bson(&chain_nonce)?});
+        self.delete_many::<StoredNonceLease>(consumed).await?;
+
+        let mut expired = expired_filter(*NONCE_LEASE_TIMEOUT)?;
+        expired.insert("lease.relayer", to_bson(&relayer)?);
+        self.delete_many::<StoredNonceLease>(expired).await?;
+
+        // Lease the first nonce which isn't leased, moving on to the next nonce if it was leased
+        // concurrently
+        let leased = self
+            .get::<StoredNonceLease>(relayer_filter, FindOpts::default().with_sort(doc! {"lease.nonce": 1}))
+            .await?
+            .into_iter()
+            .map(|stored| stored.lease.nonce);
+        let mut nonce = first_unleased_nonce(chain_nonce, leased);
+
+        for _ in 0..MAX_LEASE_ATTEMPTS {
+            match self.insert_one(StoredNonceLease::from(NonceLease::new(relayer, nonce, eth_hash))).await {
+                Ok(()) => return Ok(nonce),
+                Err(KakarotError::Database(err)) if is_duplicate_key(&err) => nonce = nonce.saturating_add(1),
+                Err(err) => return Err(err.into()),
+            }
+        }
+
+        Err(KakarotError::Database(mongodb::error::Error::custom(format!(
+            "failed to lease a nonce for relayer {relayer:#x} after {MAX_LEASE_ATTEMPTS} attempts"
+        )))
+        .into())
+    }
+
+    #[instrument(skip_all, name = "db::submit_nonce_lease", err)]
+    async fn submit_nonce_lease(&self, relayer: Felt, nonce: u64, starknet_hash: Felt) -> Result<(), EthApiError> {
+        let update = doc! {"$set": {
+            "lease.status": to_bson(&NonceLeaseStatus::Submitted)?,
+            "lease.starknetHash": to_bson(&starknet_hash)?,
+        }};
+        self.collection::<StoredNonceLease>()
+            .update_one(lease_filter(relayer, nonce)?, update)
+            .await
+            .map_err(KakarotError::from)?;
+        Ok(())
+    }
+
+    #[instrument(skip_all, name = "db::submitted_nonce_leases", err)]
+    async fn submitted_nonce_leases(&self, relayer: Felt, from_nonce: u64) -> Result<Vec<NonceLease>, EthApiError> {
+        let filter = doc! {
+            "lease.relayer": to_bson(&relayer)?,
+            "lease.status": to_bson(&NonceLeaseStatus::Submitted)?,
+            "lease.nonce": {"$gte": to_bson(&from_nonce)?},
+        };
+        let leases =
+            self.get::<StoredNonceLease>(filter, FindOpts::default().with_sort(doc! {"lease.nonce": 1})).await?;
+        Ok(leases.into_iter().map(Into::into).collect())
+    }
+
+    #[instrument(skip_all, name = "db::release_nonce_lease", err)]
+    async fn release_nonce_lease(&self, relayer: Felt, nonce: u64) -> Result<(), EthApiError> {
+        Ok(self.delete_one::<StoredNonceLease>(lease_filter(relayer, nonce)?).await?)
+    }
+
+    #[instrument(skip_all, name = "db::release_expired_nonce_leases", err)]
+    async fn release_expired_nonce_leases(&self, timeout: Duration) -> Result<u64, EthApiError> {
+        Ok(self.delete_many::<StoredNonceLease>(expired_filter(timeout)?).await?)
+    }
+
+    #[instrument(skip_all, name = "db::nonce_leases", err)]
+    async fn nonce_leases(&self) -> Result<Vec<NonceLease>, EthApiError> {
+        Ok(self.get_all::<StoredNonceLease>().await?.into_iter().map(Into::into).collect())
+    }
+
+    #[instrument(skip_all, name = "db::nonce_lease_of", err)]
+    async fn nonce_lease_of(&self, eth_hash: &B256) -> Result<Option<NonceLease>, EthApiError> {
+        let filter = doc! {"lease.ethHash": to_bson(eth_hash)?};
+        Ok(self.get_one::<StoredNonceLease>(filter, None).await?.map(Into::into))
+    }
+}
+
+/// Returns the lowest nonce from the chain nonce which isn't in the leased nonces, sorted by
+/// increasing nonce.
+fn first_unleased_nonce(chain_nonce: u64, leased: impl IntoIterator<Item = u64>) -> u64 {
+    let mut nonce = chain_nonce;
+    for leased in leased {
+        match leased.cmp(&nonce) {
+            std::cmp::Ordering::Less => {}
+            std::cmp::Ordering::Equal => nonce = nonce.saturating_add(1),
+            std::cmp::Ordering::Greater => break,
+        }
+    }
+    nonce
+}
+
+/// Returns the filter on the pending leases older than the timeout.
+fn expired_filter(timeout: Duration) -> Result<Document, KakarotError> {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+    Ok(doc! {
+        "lease.status": to_bson(&NonceLeaseStatus::Pending)?,
+        "lease.timestamp": {"$lt": to_bson(&now.saturating_sub(timeout.as_secs()))?},
+    })
+}
+
+/// Returns the filter on the lease of the nonce by the relayer.
+fn lease_filter(relayer: Felt, nonce: u64) -> Result<Document, KakarotError> {
+    Ok(doc! {"lease.relayer": to_bson(&relayer)?, "lease.nonce": to_bson(&nonce)?})
+}
+
+/// Converts the value to the BSON value it is stored as.
+pub(super) fn to_bson<T: Serialize>(value: &T) -> Result<Bson, KakarotError> {
+    mongodb::bson::to_bson(value).map_err(|err| mongodb::error::Error::custom(err).into())
+}
+
+/// Returns true if the error was caused by a write violating a unique index.
+pub(super) fn is_duplicate_key(err: &mongodb::error::Error) -> bool {
+    matches!(
+        err.kind.as_ref(),
+        ErrorKind::Write(WriteFailure::WriteError(error)) if error.code == DUPLICATE_KEY_ERROR_CODE
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::mongo::{MongoFuzzer, RANDOM_BYTES_SIZE};
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_lease_nonce() {
+        // Given
+        let mut mongo_fuzzer = MongoFuzzer::new(RANDOM_BYTES_SIZE).await;
+        let database = mongo_fuzzer.mock_database(1).await;
+        database.create_indexes().await.expect("Failed to create indexes");
+        let relayer = Felt::from(1);
+
+        // When
+        let first = database.lease_nonce(relayer, 3, B256::random()).await.unwrap();
+        let second = database.lease_nonce(relayer, 3, B256::random()).await.unwrap();
+        let other_relayer = database.lease_nonce(Felt::from(2), 3, B256::random()).await.unwrap();
+
+        // Then
+        assert_eq!(first, 3);
+        assert_eq!(second, 4);
+        assert_eq!(other_relayer, 3);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_lease_nonce_after_release_and_chain_progress() {
+        // Given
+        let mut mongo_fuzzer = MongoFuzzer::new(RANDOM_BYTES_SIZE).await;
+        let database = mongo_fuzzer.mock_database(1).await;
+        database.create_indexes().await.expect("Failed to create indexes");
+        let relayer = Felt::from(1);
+        let first = database.lease_nonce(relayer, 0, B256::random()).await.unwrap();
+        let second = database.lease_nonce(relayer, 0, B256::random()).await.unwrap();
+
+        // When
+        database.submit_nonce_lease(relayer, first, Felt::from(0xdead)).await.unwrap();
+        database.release_nonce_lease(relayer, second).await.unwrap();
+        let third = database.lease_nonce(relayer, 0, B256::random()).await.unwrap();
+        let submitted: NonceLease = database
+            .get_one::<StoredNonceLease>(lease_filter(relayer, first).unwrap(), None)
+            .await
+            .unwrap()
+            .unwrap()
+            .into();
+        let after_chain_progress = database.lease_nonce(relayer, 5, B256::random()).await.unwrap();
+
+        // Then
+        assert_eq!(third, 1);
+        assert_eq!(submitted.status, NonceLeaseStatus::Submitted);
+        assert_eq!(submitted.starknet_hash, Some(Felt::from(0xdead)));
+        assert_eq!(after_chain_progress, 5);
+        assert_eq!(database.count::<StoredNonceLease>(doc! {}).await.unwrap(), 1);
+    }
+
+    #[test]
+    fn test_first_unleased_nonce() {
+        assert_eq!(first_unleased_nonce(3, []), 3);
+        assert_eq!(first_unleased_nonce(3, [3, 4, 5]), 6);
+        assert_eq!(first_unleased_nonce(3, [3, 5, 6]), 4);
+        assert_eq!(first_unleased_nonce(3, [1, 2, 4]), 3);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_lease_nonce_of_rejected_submission() {
+        // Given
+        let mut mongo_fuzzer = MongoFuzzer::new(RANDOM_BYTES_SIZE).await;
+        let database = mongo_fuzzer.mock_database(1).await;
+        database.create_indexes().await.expect("Failed to create indexes");
+        let relayer = Felt::from(1);
+        for nonce in 0..3 {
+            let leased = database.lease_nonce(relayer, 0, B256::random()).await.unwrap();
+            database.submit_nonce_lease(relayer, leased, Felt::from(nonce)).await.unwrap();
+            assert_eq!(leased, nonce);
+        }
+
+        // When
+        // The transaction submitted with the nonce 1 is rejected by Starknet after its submission
+        let submitted = database.submitted_nonce_leases(relayer, 1).await.unwrap();
+        database.release_nonce_lease(relayer, 1).await.unwrap();
+        let released = database.lease_nonce(relayer, 1, B256::random()).await.unwrap();
+        let next = database.lease_nonce(relayer, 1, B256::random()).await.unwrap();
+
+        // Then
+        assert_eq!(submitted.iter().map(|lease| lease.nonce).collect::<Vec<_>>(), vec![1, 2]);
+        assert_eq!(released, 1);
+        assert_eq!(next, 3);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_release_expired_nonce_leases() {
+        // Given
+        let mut mongo_fuzzer = MongoFuzzer::new(RANDOM_BYTES_SIZE).await;
+        let database = mongo_fuzzer.mock_database(1).await;
+        database.create_indexes().await.expect("Failed to create indexes");
+        let expired = NonceLease { timestamp: 0, ..NonceLease::new(Felt::from(1), 0, B256::random()) };
+        let submitted = NonceLease {
+            status: NonceLeaseStatus::Submitted,
+            timestamp: 0,
+            ..NonceLease::new(Felt::from(2), 0, B256::random())
+        };
+        database.insert_one(StoredNonceLease::from(expired)).await.unwrap();
+        database.insert_one(StoredNonceLease::from(submitted)).await.unwrap();
+        database.lease_nonce(Felt::from(3), 0, B256::random()).await.unwrap();
+
+        // When
+        let released = database.release_expired_nonce_leases(*NONCE_LEASE_TIMEOUT).await.unwrap();
+
+        // Then
+        assert_eq!(released, 1);
+        assert_eq!(database.count::<StoredNonceLease>(doc! {}).await.unwrap(), 2);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_nonce_lease_of() {
+        // Given
+        let mut mongo_fuzzer = MongoFuzzer::new(RANDOM_BYTES_SIZE).await;
+        let database = mongo_fuzzer.mock_database(1).await;
+        database.create_indexes().await.expect("Failed to create indexes");
+        let eth_hash = B256::random();
+        let nonce = database.lease_nonce(Felt::from(1), 2, eth_hash).await.unwrap();
+        database.lease_nonce(Felt::from(1), 2, B256::random()).await.unwrap();
+
+        // When
+        let lease = database.nonce_lease_of(&eth_hash).await.unwrap();
+        let missing = database.nonce_lease_of(&B256::random()).await.unwrap();
+        let leases = database.nonce_leases().await.unwrap();
+
+        // Then
+        let lease = lease.expect("Missing lease");
+        assert_eq!(lease.nonce, nonce);
+        assert_eq!(lease.eth_hash, eth_hash);
+        assert!(missing.is_none());
+        assert_eq!(leases.len(), 2);
+    }
+}
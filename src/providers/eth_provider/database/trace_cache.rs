@@ -0,0 +1,152 @@
+//! Content-addressable cache of the transaction traces.
+//!
+//! The traces computed by `debug_traceTransaction` are stored under the hash of the transaction,
+//! of the block including it and of the tracing options, and expire after [`TRACE_CACHE_TTL`].
+//! A trace cached for a block which was since reorged out is never served, as the key of the
+//! transaction in its new block differs, and is deleted on the next request of the transaction.
+
+use super::{
+    filter::format_hex,
+    types::trace::{CachedTrace, StoredTrace},
+    Database,
+};
+use crate::providers::eth_provider::{constant::HASH_HEX_STRING_LEN, error::EthApiError};
+use alloy_primitives::{keccak256, B256};
+use alloy_rpc_types_trace::geth::{GethDebugTracingOptions, GethTrace};
+use async_trait::async_trait;
+use mongodb::bson::{doc, DateTime};
+use std::{
+    sync::LazyLock,
+    time::{Duration, SystemTime},
+};
+use tracing::instrument;
+
+/// The duration for which the traces are cached. Set with the `TRACE_CACHE_TTL_SECS`
+/// environment variable, defaults to an hour. The cache is disabled if set to 0.
+pub static TRACE_CACHE_TTL: LazyLock<Duration> = LazyLock::new(|| {
+    Duration::from_secs(std::env::var("TRACE_CACHE_TTL_SECS").ok().and_then(|val| val.parse().ok()).unwrap_or(3600))
+});
+
+/// Returns the key of the trace of the transaction included in the block, traced with the
+/// options.
+pub fn trace_cache_key(transaction_hash: B256, block_hash: B256, opts: &GethDebugTracingOptions) -> B256 {
+    let opts = serde_json::to_vec(opts).unwrap_or_default();
+    keccak256([transaction_hash.as_slice(), block_hash.as_slice(), &opts].concat())
+}
+
+/// Trait for caching the transaction traces.
+#[async_trait]
+pub trait TraceCacheStore {
+    /// Returns the trace cached under the key, if any and not expired.
+    async fn cached_trace(&self, key: B256) -> Result<Option<GethTrace>, EthApiError>;
+    /// Caches the trace of the transaction included in the block under the key, for `ttl`.
+    async fn cache_trace(
+        &self,
+        key: B256,
+        transaction_hash: B256,
+        block_hash: B256,
+        trace: &GethTrace,
+        ttl: Duration,
+    ) -> Result<(), EthApiError>;
+    /// Deletes the traces of the transaction cached while it was included in another block than
+    /// the given one. Returns the number of deleted traces.
+    async fn invalidate_traces(&self, transaction_hash: B256, block_hash: B256) -> Result<u64, EthApiError>;
+}
+
+#[async_trait]
+impl TraceCacheStore for Database {
+    #[instrument(skip(self), name = "db::cached_trace", err)]
+    async fn cached_trace(&self, key: B256) -> Result<Option<GethTrace>, EthApiError> {
+        let filter = doc! {
+            "trace.key": format_hex(key, HASH_HEX_STRING_LEN),
+            "trace.expiresAt": {"$gt": DateTime::now()},
+        };
+        let Some(stored) = self.get_one::<StoredTrace>(filter, None).await? else {
+            return Ok(None);
+        };
+
+        // A trace which can't be deserialized is recomputed
+        Ok(serde_json::from_str(&stored.trace.result).ok())
+    }
+
+    #[instrument(skip(self, trace), name = "db::cache_trace", err)]
+    async fn cache_trace(
+        &self,
+        key: B256,
+        transaction_hash: B256,
+        block_hash: B256,
+        trace: &GethTrace,
+        ttl: Duration,
+    ) -> Result<(), EthApiError> {
+        let Ok(result) = serde_json::to_string(trace) else {
+            return Ok(());
+        };
+        let trace = CachedTrace {
+            key,
+            transaction_hash,
+            block_hash,
+            result,
+            expires_at: DateTime::from_system_time(SystemTime::now() + ttl),
+        };
+
+        let filter = doc! {"trace.key": format_hex(key, HASH_HEX_STRING_LEN)};
+        Ok(self.update_one(StoredTrace::from(trace), filter, true).await?)
+    }
+
+    #[instrument(skip(self), name = "db::invalidate_traces", err)]
+    async fn invalidate_traces(&self, transaction_hash: B256, block_hash: B256) -> Result<u64, EthApiError> {
+        let filter = doc! {
+            "trace.transactionHash": format_hex(transaction_hash, HASH_HEX_STRING_LEN),
+            "trace.blockHash": {"$ne": format_hex(block_hash, HASH_HEX_STRING_LEN)},
+        };
+        Ok(self.delete_many::<StoredTrace>(filter).await?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::mongo::{MongoFuzzer, RANDOM_BYTES_SIZE};
+    use alloy_rpc_types_trace::geth::{CallFrame, GethDebugBuiltInTracerType, GethDebugTracerType};
+
+    #[test]
+    fn test_trace_cache_key() {
+        // Given
+        let (transaction_hash, block_hash) = (B256::random(), B256::random());
+        let default_opts = GethDebugTracingOptions::default();
+        let call_opts = GethDebugTracingOptions::default()
+            .with_tracer(GethDebugTracerType::BuiltInTracer(GethDebugBuiltInTracerType::CallTracer));
+
+        // When
+        let key = trace_cache_key(transaction_hash, block_hash, &default_opts);
+
+        // Then
+        assert_eq!(key, trace_cache_key(transaction_hash, block_hash, &default_opts));
+        assert_ne!(key, trace_cache_key(transaction_hash, block_hash, &call_opts));
+        assert_ne!(key, trace_cache_key(transaction_hash, B256::random(), &default_opts));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_cache_trace() {
+        // Given
+        let mut mongo_fuzzer = MongoFuzzer::new(RANDOM_BYTES_SIZE).await;
+        let database = mongo_fuzzer.mock_database(1).await;
+        let (transaction_hash, block_hash, reorged_block_hash) = (B256::random(), B256::random(), B256::random());
+        let trace = GethTrace::CallTracer(CallFrame { typ: "CALL".to_string(), ..Default::default() });
+        let key = trace_cache_key(transaction_hash, block_hash, &GethDebugTracingOptions::default());
+        let expired_key = B256::random();
+
+        // When
+        let ttl = Duration::from_secs(60);
+        database.cache_trace(key, transaction_hash, block_hash, &trace, ttl).await.unwrap();
+        database.cache_trace(expired_key, transaction_hash, block_hash, &trace, Duration::ZERO).await.unwrap();
+
+        // Then
+        assert_eq!(database.cached_trace(key).await.unwrap(), Some(trace));
+        assert_eq!(database.cached_trace(expired_key).await.unwrap(), None);
+        assert_eq!(database.cached_trace(B256::random()).await.unwrap(), None);
+        assert_eq!(database.invalidate_traces(transaction_hash, block_hash).await.unwrap(), 0);
+        assert_eq!(database.invalidate_traces(transaction_hash, reorged_block_hash).await.unwrap(), 2);
+        assert_eq!(database.cached_trace(key).await.unwrap(), None);
+    }
+}
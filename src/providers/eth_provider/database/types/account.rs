@@ -0,0 +1,33 @@
+use serde::{Deserialize, Serialize};
+use starknet::core::types::Felt;
+
+/// An observation of the class of the Starknet contract of an account, as stored in the database.
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+pub struct StoredClassHashObservation {
+    /// The class and the range of blocks it was observed over.
+    pub observation: ClassHashObservation,
+}
+
+impl From<StoredClassHashObservation> for ClassHashObservation {
+    fn from(stored: StoredClassHashObservation) -> Self {
+        stored.observation
+    }
+}
+
+/// The class of the Starknet contract of an account, observed between two blocks.
+///
+/// Kakarot upgrades the class of the accounts, so the class of an account depends on the block.
+/// The range is extended each time the class is observed, the account having the class at least
+/// between the first and the last observation.
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ClassHashObservation {
+    /// The Starknet address of the account.
+    pub address: Felt,
+    /// The class hash of the contract of the account.
+    pub class_hash: Felt,
+    /// The first block the class was observed at.
+    pub from_block: u64,
+    /// The last block the class was observed at.
+    pub to_block: u64,
+}
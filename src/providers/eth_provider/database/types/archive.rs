@@ -0,0 +1,30 @@
+use alloy_primitives::U64;
+use serde::{Deserialize, Serialize};
+
+/// The checkpoint of the archival of the logs and receipts, as stored in the hot database.
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+pub struct StoredArchiveCheckpoint {
+    /// The progress of the archival.
+    pub checkpoint: ArchiveCheckpoint,
+}
+
+impl From<ArchiveCheckpoint> for StoredArchiveCheckpoint {
+    fn from(checkpoint: ArchiveCheckpoint) -> Self {
+        Self { checkpoint }
+    }
+}
+
+impl From<StoredArchiveCheckpoint> for ArchiveCheckpoint {
+    fn from(stored: StoredArchiveCheckpoint) -> Self {
+        stored.checkpoint
+    }
+}
+
+/// The progress of the archival, used as a stub index of the archive: the logs and receipts of
+/// the blocks up to the last archived block are only found in the archive database.
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone, Copy)]
+#[serde(rename_all = "camelCase")]
+pub struct ArchiveCheckpoint {
+    /// The last block whose logs and receipts were moved to the archive.
+    pub last_archived_block: U64,
+}
@@ -0,0 +1,32 @@
+use alloy_primitives::{B256, U256};
+use serde::{Deserialize, Serialize};
+
+/// A snapshot of the native token balance of an account, as stored in the database.
+///
+/// A snapshot is indexed for every account involved in a native token `Transfer` event, at the
+/// end of the block containing the event.
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+pub struct StoredBalanceSnapshot {
+    #[serde(deserialize_with = "crate::providers::eth_provider::database::types::serde::deserialize_intermediate")]
+    pub balance: BalanceSnapshot,
+}
+
+impl From<BalanceSnapshot> for StoredBalanceSnapshot {
+    fn from(balance: BalanceSnapshot) -> Self {
+        Self { balance }
+    }
+}
+
+/// The native token balance of an account at the end of a block.
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct BalanceSnapshot {
+    /// The Starknet address of the account, padded to 32 bytes.
+    pub address: B256,
+    /// The number of the block at the end of which the balance was taken.
+    #[serde(with = "alloy_serde::quantity")]
+    pub block_number: u64,
+    /// The balance of the account, None if the indexer couldn't fetch it. Such a snapshot marks
+    /// the previous snapshots of the account as stale.
+    pub balance: Option<U256>,
+}
@@ -1,15 +1,28 @@
-use super::transaction::ExtendedTransaction;
+use super::transaction::{ExtendedTransaction, StoredTransaction};
+use alloy_primitives::{Address, B256};
 use alloy_rpc_types::{Block, Header};
 use alloy_serde::WithOtherFields;
 use serde::{Deserialize, Serialize};
-use std::ops::Deref;
+use std::{collections::BTreeMap, ops::Deref};
 #[cfg(any(test, feature = "arbitrary", feature = "testing"))]
 use {
     alloy_consensus::constants::EMPTY_ROOT_HASH,
-    alloy_primitives::{B256, B64, U256},
+    alloy_primitives::{B64, U256},
     arbitrary::Arbitrary,
 };
 
+/// The extra field of the blocks holding their [`FeeRecipient`].
+pub const FEE_RECIPIENT_FIELD: &str = "feeRecipient";
+
+/// The extra field of the blocks holding the hash of the Starknet block they are built from.
+/// Also the field of the header documents holding it, when the blocks are identified by their
+/// EVM-style hash.
+pub const STARKNET_BLOCK_HASH_FIELD: &str = "starknetBlockHash";
+
+/// The extra field of the blocks holding the number of their non-Invoke Starknet transactions
+/// which weren't converted to Ethereum transactions, by Starknet transaction type.
+pub const SKIPPED_TRANSACTIONS_FIELD: &str = "skippedTransactions";
+
 /// Type alias for a block that contains extended transactions and additional fields.
 pub type ExtendedBlock = WithOtherFields<Block<ExtendedTransaction>>;
 
@@ -40,6 +53,49 @@ impl Deref for StoredHeader {
     }
 }
 
+/// A header with the transactions of its block, joined to it by the aggregation pipeline
+/// retrieving them.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct StoredBlock {
+    #[serde(deserialize_with = "crate::providers::eth_provider::database::types::serde::deserialize_intermediate")]
+    pub header: Header,
+    pub transactions: Vec<StoredTransaction>,
+    /// The recipients of the fees of the block, missing for the blocks indexed before they were
+    /// recorded.
+    #[serde(default, rename = "feeRecipient")]
+    pub fee_recipient: Option<FeeRecipient>,
+    /// The number of non-Invoke Starknet transactions of the block which weren't converted, by
+    /// Starknet transaction type (e.g. `l1Handler`), missing if none was skipped.
+    #[serde(default, rename = "skippedTransactions")]
+    pub skipped_transactions: Option<BTreeMap<String, u64>>,
+    /// The hash of the Starknet block the block is built from, only stored when the blocks are
+    /// identified by their EVM-style hash.
+    #[serde(default, rename = "starknetBlockHash")]
+    pub starknet_block_hash: Option<B256>,
+}
+
+/// The recipients of the fees of a block, stored along with its header by the indexer.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct FeeRecipient {
+    /// The coinbase of Kakarot core at the block, receiving the EVM fees. Also the miner of the
+    /// header.
+    pub coinbase: Address,
+    /// The Starknet sequencer of the block, receiving the Starknet fees.
+    pub sequencer_address: B256,
+    /// True if the coinbase was read from Kakarot core at the block and is a valid EVM address.
+    /// Otherwise, the coinbase defaults to the zero address.
+    pub verified: bool,
+}
+
+/// The hash of the Starknet block a block is built from, stored by the indexer in the header
+/// document when the blocks are identified by their EVM-style hash.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub struct StoredStarknetBlockHash {
+    #[serde(rename = "starknetBlockHash")]
+    pub starknet_block_hash: B256,
+}
+
 #[cfg(any(test, feature = "arbitrary", feature = "testing"))]
 impl Arbitrary<'_> for StoredHeader {
     fn arbitrary(u: &mut arbitrary::Unstructured<'_>) -> arbitrary::Result<Self> {
@@ -65,6 +121,8 @@ impl Arbitrary<'_> for StoredHeader {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use alloy_consensus::constants::EMPTY_OMMER_ROOT_HASH;
+    use alloy_primitives::b256;
     use arbitrary::Arbitrary;
     use rand::Rng;
 
@@ -75,4 +133,55 @@ mod tests {
 
         let _ = StoredHeader::arbitrary(&mut arbitrary::Unstructured::new(&bytes)).unwrap();
     }
+
+    #[test]
+    fn test_evm_block_hash() {
+        // Given
+        let header = Header {
+            parent_hash: b256!("14e330b38856cb7e21b04db7e9fd6872840333ecf753050fe421dfb42b3a3486"),
+            uncles_hash: EMPTY_OMMER_ROOT_HASH,
+            state_root: b256!("3485c8e91ae6107202df98042de861c3229618b46a4bd03407edd84bafeb452f"),
+            transactions_root: EMPTY_ROOT_HASH,
+            receipts_root: EMPTY_ROOT_HASH,
+            number: 1,
+            gas_limit: 7_000_000,
+            gas_used: 10,
+            timestamp: 0x64a2_bf90,
+            mix_hash: Some(B256::ZERO),
+            nonce: Some(B64::ZERO),
+            base_fee_per_gas: Some(1),
+            withdrawals_root: Some(EMPTY_ROOT_HASH),
+            ..Default::default()
+        };
+
+        // When
+        let hash = reth_primitives::Header::try_from(header).unwrap().hash_slow();
+
+        // Then
+        // The indexer computes the same hash, see the tests of the indexer header types
+        assert_eq!(hash, b256!("15bb90d9a0bd7c984b8c2f798274b82264bac69582dcdd83e0541e0677c67657"));
+    }
+
+    #[test]
+    fn test_fee_recipient_deserialization() {
+        // Given
+        let fee_recipient = serde_json::json!({
+            "coinbase": "0x0000000000000000000000000000000000000001",
+            "sequencerAddress": "0x0000000000000000000000000000000000000000000000000000000000000002",
+            "verified": true
+        });
+
+        // When
+        let fee_recipient: FeeRecipient = serde_json::from_value(fee_recipient).unwrap();
+
+        // Then
+        assert_eq!(
+            fee_recipient,
+            FeeRecipient {
+                coinbase: Address::with_last_byte(1),
+                sequencer_address: B256::with_last_byte(2),
+                verified: true
+            }
+        );
+    }
 }
@@ -0,0 +1,34 @@
+use mongodb::bson::DateTime;
+use serde::{Deserialize, Serialize};
+
+/// An instance lease, as stored in the database.
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+pub struct StoredInstanceLease {
+    /// The role and the instance holding it.
+    pub lease: InstanceLease,
+}
+
+impl From<InstanceLease> for StoredInstanceLease {
+    fn from(lease: InstanceLease) -> Self {
+        Self { lease }
+    }
+}
+
+impl From<StoredInstanceLease> for InstanceLease {
+    fn from(stored: StoredInstanceLease) -> Self {
+        stored.lease
+    }
+}
+
+/// A role (e.g. running the background writers) held by a single instance of the RPC among the
+/// instances sharing the database, until the lease expires.
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct InstanceLease {
+    /// The leased role.
+    pub role: String,
+    /// The id of the instance holding the role.
+    pub holder: String,
+    /// The time at which the lease expires unless renewed, by the clock of the database.
+    pub expires_at: DateTime,
+}
@@ -58,3 +58,25 @@ impl<'a> arbitrary::Arbitrary<'a> for StoredLog {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arbitrary::Arbitrary;
+    use proptest::prelude::*;
+
+    #[test]
+    fn test_stored_log_serde_roundtrip() {
+        proptest!(|(bytes in proptest::collection::vec(any::<u8>(), 512))| {
+            // Given
+            let log = StoredLog::arbitrary(&mut arbitrary::Unstructured::new(&bytes)).unwrap();
+
+            // When
+            let serialized = serde_json::to_string(&log).unwrap();
+            let deserialized: StoredLog = serde_json::from_str(&serialized).unwrap();
+
+            // Then
+            prop_assert_eq!(deserialized, log);
+        });
+    }
+}
@@ -1,5 +1,13 @@
+pub mod account;
+pub mod archive;
+pub mod balance;
 pub mod header;
+#[cfg(feature = "mongo")]
+pub mod instance;
 pub mod log;
 pub mod receipt;
+pub mod relayer;
 pub mod serde;
+#[cfg(feature = "mongo")]
+pub mod trace;
 pub mod transaction;
@@ -2,24 +2,68 @@ use alloy_rpc_types::TransactionReceipt;
 use alloy_serde::WithOtherFields;
 #[cfg(any(test, feature = "arbitrary", feature = "testing"))]
 use reth_primitives::Receipt;
-use serde::{Deserialize, Serialize};
+use serde::{de::value::MapDeserializer, Deserialize, Deserializer, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// The field set to `true` by the indexer on the transactions and the receipts of the EVM
+/// transactions whose Starknet transaction ran out of Cairo resources.
+pub const OUT_OF_RESOURCES_FIELD: &str = "outOfResources";
+
+/// The field set on the receipts built from the Starknet receipts, reporting the Starknet
+/// execution resources consumed by the transaction.
+pub const EXECUTION_RESOURCES_FIELD: &str = "executionResources";
+
+/// The field set by the indexer on the receipts of the deposit transactions, holding the nonce
+/// of their L1 message.
+pub const DEPOSIT_NONCE_FIELD: &str = "depositNonce";
 
 /// Type alias for a transaction receipt with additional fields.
 pub type ExtendedTxReceipt = WithOtherFields<TransactionReceipt>;
 
+/// Returns true if the receipt is the receipt of a deposit transaction. The deposit receipts
+/// aren't part of the receipt trie of the block, they can't be encoded.
+pub fn is_deposit_receipt(receipt: &ExtendedTxReceipt) -> bool {
+    receipt.other.contains_key(DEPOSIT_NONCE_FIELD)
+}
+
 /// A transaction receipt as stored in the database
 #[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
 pub struct StoredTransactionReceipt {
-    #[serde(deserialize_with = "crate::providers::eth_provider::database::types::serde::deserialize_intermediate")]
+    #[serde(deserialize_with = "deserialize_receipt")]
     pub receipt: WithOtherFields<TransactionReceipt>,
 }
 
+/// Deserializes a receipt stored in the database, see
+/// [`deserialize_intermediate`](crate::providers::eth_provider::database::types::serde::deserialize_intermediate).
+///
+/// The receipt envelope only knows the Ethereum transaction types: the deposit receipts (type
+/// 0x7e) are served with the legacy type, and told apart by their `depositNonce` field.
+fn deserialize_receipt<'de, D>(deserializer: D) -> Result<ExtendedTxReceipt, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let mut receipt: HashMap<String, Value> = HashMap::deserialize(deserializer)?;
+    if receipt.contains_key(DEPOSIT_NONCE_FIELD) {
+        receipt.insert("type".to_string(), Value::String("0x0".to_string()));
+    }
+    let deserializer = MapDeserializer::new(receipt.into_iter());
+    ExtendedTxReceipt::deserialize(deserializer)
+        .map_err(|err: serde_json::Error| serde::de::Error::custom(err.to_string()))
+}
+
 impl From<StoredTransactionReceipt> for WithOtherFields<TransactionReceipt> {
     fn from(receipt: StoredTransactionReceipt) -> Self {
         receipt.receipt
     }
 }
 
+/// The receipts of a block, joined to its header by the aggregation pipeline retrieving them.
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+pub struct StoredBlockReceipts {
+    pub receipts: Vec<StoredTransactionReceipt>,
+}
+
 #[cfg(any(test, feature = "arbitrary", feature = "testing"))]
 impl<'a> arbitrary::Arbitrary<'a> for StoredTransactionReceipt {
     fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
@@ -80,7 +124,10 @@ impl<'a> arbitrary::Arbitrary<'a> for StoredTransactionReceipt {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::providers::eth_provider::database::types::log::StoredLog;
+    use alloy_rpc_types::Log;
     use arbitrary::Arbitrary;
+    use proptest::prelude::*;
     use rand::Rng;
 
     #[test]
@@ -90,4 +137,67 @@ mod tests {
 
         let _ = StoredTransactionReceipt::arbitrary(&mut arbitrary::Unstructured::new(&bytes)).unwrap();
     }
+
+    #[test]
+    fn test_stored_transaction_receipt_to_logs() {
+        proptest!(|(bytes in proptest::collection::vec(any::<u8>(), 1024))| {
+            // Given
+            let receipt = StoredTransactionReceipt::arbitrary(&mut arbitrary::Unstructured::new(&bytes)).unwrap();
+            let expected_logs = receipt.receipt.inner.inner.logs().to_vec();
+
+            // When
+            let logs: Vec<StoredLog> = receipt.into();
+
+            // Then
+            prop_assert_eq!(logs.into_iter().map(Log::from).collect::<Vec<_>>(), expected_logs);
+        });
+    }
+
+    #[test]
+    fn test_stored_transaction_receipt_deposit() {
+        // Given
+        let receipt = serde_json::json!({
+            "receipt": {
+                "transactionHash": "0x0000000000000000000000000000000000000000000000000000000000000001",
+                "transactionIndex": "0x3",
+                "blockHash": "0x0000000000000000000000000000000000000000000000000000000000000abc",
+                "blockNumber": "0x1",
+                "from": "0x000000000000000000000000000000000000000b",
+                "to": "0x000000000000000000000000000000000000000c",
+                "cumulativeGasUsed": "0x64",
+                "gasUsed": "0x0",
+                "effectiveGasPrice": "0x0",
+                "contractAddress": null,
+                "logs": [],
+                "logsBloom": format!("0x{}", "0".repeat(512)),
+                "status": "0x1",
+                "type": "0x7e",
+                "depositNonce": "0x5",
+                "depositReceiptVersion": "0x1"
+            }
+        });
+
+        // When
+        let receipt: StoredTransactionReceipt = serde_json::from_value(receipt).unwrap();
+
+        // Then
+        assert!(is_deposit_receipt(&receipt.receipt));
+        assert!(receipt.receipt.inner.status());
+        assert_eq!(receipt.receipt.inner.inner.cumulative_gas_used(), 100);
+    }
+
+    #[test]
+    fn test_stored_transaction_receipt_serde_roundtrip() {
+        proptest!(|(bytes in proptest::collection::vec(any::<u8>(), 1024))| {
+            // Given
+            let receipt = StoredTransactionReceipt::arbitrary(&mut arbitrary::Unstructured::new(&bytes)).unwrap();
+
+            // When
+            let serialized = serde_json::to_string(&receipt).unwrap();
+            let deserialized: StoredTransactionReceipt = serde_json::from_str(&serialized).unwrap();
+
+            // Then
+            prop_assert_eq!(deserialized, receipt);
+        });
+    }
 }
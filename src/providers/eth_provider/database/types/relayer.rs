@@ -0,0 +1,62 @@
+use alloy_primitives::B256;
+use serde::{Deserialize, Serialize};
+use starknet::core::types::Felt;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// A relayer nonce lease, as stored in the database.
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+pub struct StoredNonceLease {
+    /// The leased nonce and the transaction it was leased for.
+    pub lease: NonceLease,
+}
+
+impl From<NonceLease> for StoredNonceLease {
+    fn from(lease: NonceLease) -> Self {
+        Self { lease }
+    }
+}
+
+impl From<StoredNonceLease> for NonceLease {
+    fn from(stored: StoredNonceLease) -> Self {
+        stored.lease
+    }
+}
+
+/// The status of a relayer nonce lease.
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone, Copy)]
+#[serde(rename_all = "camelCase")]
+pub enum NonceLeaseStatus {
+    /// The nonce is leased but the transaction wasn't acknowledged by Starknet yet.
+    Pending,
+    /// The transaction using the nonce was submitted to Starknet.
+    Submitted,
+}
+
+/// A nonce reserved by a relayer before submitting a transaction to Starknet.
+///
+/// The lease is written before the submission, so that a crash of the node between the
+/// submission and the storage of the transaction hashes can't lead to the nonce being used twice.
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct NonceLease {
+    /// The Starknet address of the relayer.
+    pub relayer: Felt,
+    /// The leased nonce.
+    pub nonce: u64,
+    /// The hash of the Ethereum transaction relayed with the nonce.
+    pub eth_hash: B256,
+    /// The hash of the Starknet transaction, set once submitted.
+    pub starknet_hash: Option<Felt>,
+    /// The status of the lease.
+    pub status: NonceLeaseStatus,
+    /// The UNIX timestamp, in seconds, at which the nonce was leased.
+    pub timestamp: u64,
+}
+
+impl NonceLease {
+    /// Creates a new pending lease, leased now.
+    pub fn new(relayer: Felt, nonce: u64, eth_hash: B256) -> Self {
+        let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+        Self { relayer, nonce, eth_hash, starknet_hash: None, status: NonceLeaseStatus::Pending, timestamp }
+    }
+}
@@ -0,0 +1,33 @@
+use alloy_primitives::B256;
+use mongodb::bson::DateTime;
+use serde::{Deserialize, Serialize};
+
+/// A transaction trace cached in the database.
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+pub struct StoredTrace {
+    /// The cached trace.
+    pub trace: CachedTrace,
+}
+
+impl From<CachedTrace> for StoredTrace {
+    fn from(trace: CachedTrace) -> Self {
+        Self { trace }
+    }
+}
+
+/// A trace of a transaction, addressed by the hash of the transaction, of its block and of the
+/// tracing options.
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CachedTrace {
+    /// The key of the trace.
+    pub key: B256,
+    /// The hash of the traced transaction.
+    pub transaction_hash: B256,
+    /// The hash of the block including the transaction when it was traced.
+    pub block_hash: B256,
+    /// The JSON serialized trace.
+    pub result: String,
+    /// The date after which the trace is deleted.
+    pub expires_at: DateTime,
+}
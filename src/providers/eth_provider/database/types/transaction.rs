@@ -1,13 +1,19 @@
-use alloy_primitives::B256;
-use alloy_rpc_types::Transaction;
+use crate::eth_rpc::openrpc::SchemaOf;
+use alloy_primitives::{Bytes, B256, U256};
+use alloy_rpc_types::{Parity, Transaction};
 use alloy_serde::WithOtherFields;
+#[cfg(feature = "mongo")]
+use mongodb::bson::{doc, DateTime, Document};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use starknet::core::types::Felt;
-use std::ops::Deref;
+use std::{
+    ops::Deref,
+    time::{SystemTime, UNIX_EPOCH},
+};
 #[cfg(any(test, feature = "arbitrary", feature = "testing"))]
 use {
     alloy_consensus::Transaction as _,
-    alloy_primitives::U256,
     arbitrary::Arbitrary,
     rand::Rng,
     reth_primitives::transaction::legacy_parity,
@@ -17,6 +23,16 @@ use {
 /// Type alias for a transaction with additional fields.
 pub type ExtendedTransaction = WithOtherFields<Transaction>;
 
+/// The type of the deposit transactions, converted by the indexer from the Starknet L1 handler
+/// transactions executing L1 messages on Kakarot, as the deposit transactions of the OP stack.
+pub const DEPOSIT_TX_TYPE_ID: u8 = 0x7e;
+
+/// Returns true if the transaction is a deposit transaction. The deposit transactions aren't
+/// signed Ethereum transactions, they can't be encoded nor replayed in the EVM.
+pub fn is_deposit(transaction: &ExtendedTransaction) -> bool {
+    transaction.transaction_type == Some(DEPOSIT_TX_TYPE_ID)
+}
+
 /// A mapping between an Ethereum transaction hash and a Starknet transaction hash.
 #[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
 pub struct StoredEthStarknetTransactionHash {
@@ -40,6 +56,184 @@ pub struct EthStarknetHashes {
     pub starknet_hash: Felt,
 }
 
+/// A transaction dropped by the node, as stored in the database.
+#[cfg(feature = "mongo")]
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+pub struct StoredDroppedTransaction {
+    /// The dropped transaction and the reason it was dropped.
+    pub dropped: DroppedTransaction,
+    /// The date at which the transaction was stored, after which it is kept for
+    /// [`DROPPED_TRANSACTIONS_RETENTION`](crate::providers::eth_provider::database::DROPPED_TRANSACTIONS_RETENTION).
+    #[serde(rename = "storedAt", default = "DateTime::now")]
+    pub stored_at: DateTime,
+}
+
+#[cfg(feature = "mongo")]
+impl From<DroppedTransaction> for StoredDroppedTransaction {
+    fn from(dropped: DroppedTransaction) -> Self {
+        Self { dropped, stored_at: DateTime::now() }
+    }
+}
+
+#[cfg(feature = "mongo")]
+impl From<StoredDroppedTransaction> for DroppedTransaction {
+    fn from(stored: StoredDroppedTransaction) -> Self {
+        stored.dropped
+    }
+}
+
+/// The reason why a transaction was dropped by the node.
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone, Copy, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum DropReason {
+    /// The transaction failed the validation of the pool.
+    Invalid,
+    /// The fees of the transaction are too low.
+    Underpriced,
+    /// The relayer failed to relay the transaction and it couldn't be re-inserted in the pool.
+    RelayFailed,
+    /// The transaction stayed in the pool for longer than the prune duration.
+    Expired,
+}
+
+/// A transaction dropped by the pool or the relayer.
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct DroppedTransaction {
+    /// The Ethereum transaction hash.
+    #[schemars(with = "SchemaOf<B256>")]
+    pub hash: B256,
+    /// The reason why the transaction was dropped.
+    pub reason: DropReason,
+    /// The error which caused the transaction to be dropped.
+    pub message: String,
+    /// The UNIX timestamp, in seconds, at which the transaction was dropped.
+    pub timestamp: u64,
+}
+
+impl DroppedTransaction {
+    /// Creates a new dropped transaction, dropped now.
+    pub fn new(hash: B256, reason: DropReason, message: impl Into<String>) -> Self {
+        let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+        Self { hash, reason, message: message.into(), timestamp }
+    }
+}
+
+/// An event of the lifecycle of a transaction in the pool, as stored in the database.
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+pub struct StoredPoolEvent {
+    /// The hash of the transaction and the event.
+    pub event: PoolEvent,
+}
+
+impl From<PoolEvent> for StoredPoolEvent {
+    fn from(event: PoolEvent) -> Self {
+        Self { event }
+    }
+}
+
+impl From<StoredPoolEvent> for PoolEvent {
+    fn from(stored: StoredPoolEvent) -> Self {
+        stored.event
+    }
+}
+
+/// The kind of an event of the lifecycle of a transaction in the pool.
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone, JsonSchema)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum PoolEventKind {
+    /// The transaction was added to the pool, ready to be relayed.
+    Added,
+    /// The transaction was added to the pool, waiting for a missing nonce or for funds.
+    Queued,
+    /// The queued transaction became ready to be relayed.
+    Promoted,
+    /// The transaction was replaced by a transaction of the same sender and nonce.
+    #[serde(rename_all = "camelCase")]
+    Replaced {
+        /// The hash of the replacing transaction.
+        #[schemars(with = "SchemaOf<B256>")]
+        replaced_by: B256,
+    },
+    /// The transaction was removed from the pool, to be relayed or by the maintenance of the pool.
+    Removed,
+    /// The transaction was dropped, see [`DroppedTransaction`].
+    #[serde(rename_all = "camelCase")]
+    Dropped {
+        /// The reason why the transaction was dropped.
+        reason: DropReason,
+        /// The error which caused the transaction to be dropped.
+        message: String,
+    },
+    /// The transaction was relayed to Starknet.
+    #[serde(rename_all = "camelCase")]
+    Relayed {
+        /// The address of the relayer account.
+        #[schemars(with = "SchemaOf<Felt>")]
+        relayer: Felt,
+        /// The hash of the Starknet transaction.
+        #[schemars(with = "SchemaOf<Felt>")]
+        starknet_hash: Felt,
+    },
+    /// The transaction was mined.
+    #[serde(rename_all = "camelCase")]
+    Mined {
+        /// The hash of the block of the transaction.
+        #[schemars(with = "SchemaOf<B256>")]
+        block_hash: B256,
+    },
+}
+
+/// An event of the lifecycle of a transaction in the pool, from its submission to its mining.
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct PoolEvent {
+    /// The Ethereum transaction hash.
+    #[schemars(with = "SchemaOf<B256>")]
+    pub hash: B256,
+    /// The event.
+    pub kind: PoolEventKind,
+    /// The UNIX timestamp, in milliseconds, at which the event happened.
+    pub timestamp: u64,
+}
+
+impl PoolEvent {
+    /// Creates a new event, happening now.
+    pub fn new(hash: B256, kind: PoolEventKind) -> Self {
+        let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_millis() as u64;
+        Self { hash, kind, timestamp }
+    }
+}
+
+/// The raw bytes of a transaction submitted to the node, as stored in the database.
+#[cfg(feature = "mongo")]
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+pub struct StoredRawTransaction {
+    /// The hash and the raw bytes of the transaction.
+    pub raw: RawTransaction,
+    /// The date at which the transaction was stored, after which it is kept for
+    /// [`RAW_TRANSACTIONS_RETENTION`](crate::providers::eth_provider::database::RAW_TRANSACTIONS_RETENTION).
+    #[serde(rename = "storedAt", default = "DateTime::now")]
+    pub stored_at: DateTime,
+}
+
+#[cfg(feature = "mongo")]
+impl From<RawTransaction> for StoredRawTransaction {
+    fn from(raw: RawTransaction) -> Self {
+        Self { raw, stored_at: DateTime::now() }
+    }
+}
+
+/// The exact bytes of a transaction received by `eth_sendRawTransaction`.
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RawTransaction {
+    /// The Ethereum transaction hash.
+    pub hash: B256,
+    /// The EIP-2718 encoded transaction, as submitted.
+    pub bytes: Bytes,
+}
+
 /// A full transaction as stored in the database
 #[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
 pub struct StoredTransaction {
@@ -47,18 +241,117 @@ pub struct StoredTransaction {
     pub tx: WithOtherFields<Transaction>,
 }
 
+impl StoredTransaction {
+    /// Converts the transaction to its geth formatted representation, see [`into_geth_format`].
+    /// The base fee is the one of the block of the transaction, if known.
+    pub fn with_base_fee(self, base_fee: Option<u64>) -> ExtendedTransaction {
+        let WithOtherFields { inner, other } = self.tx;
+        WithOtherFields { inner: into_geth_format(inner, base_fee), other }
+    }
+}
+
+/// The fee fields of a transaction, read with a projection of the transactions collection.
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone, Copy)]
+pub struct StoredTransactionFees {
+    #[serde(deserialize_with = "crate::providers::eth_provider::database::types::serde::deserialize_intermediate")]
+    pub tx: TransactionFees,
+}
+
+/// The block number and the fees per gas of a transaction.
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone, Copy, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct TransactionFees {
+    /// The number of the block including the transaction.
+    #[serde(default, with = "alloy_serde::quantity::opt")]
+    pub block_number: Option<u64>,
+    /// The gas price of the legacy and EIP-2930 transactions.
+    #[serde(default, with = "alloy_serde::quantity::opt")]
+    pub gas_price: Option<u128>,
+    /// The maximum fee per gas of the EIP-1559 transactions.
+    #[serde(default, with = "alloy_serde::quantity::opt")]
+    pub max_fee_per_gas: Option<u128>,
+    /// The maximum priority fee per gas of the EIP-1559 transactions.
+    #[serde(default, with = "alloy_serde::quantity::opt")]
+    pub max_priority_fee_per_gas: Option<u128>,
+}
+
+#[cfg(feature = "mongo")]
+impl StoredTransactionFees {
+    /// Returns the projection of the transactions collection on the fee fields.
+    pub fn projection() -> Document {
+        doc! {"tx.blockNumber": 1, "tx.gasPrice": 1, "tx.maxFeePerGas": 1, "tx.maxPriorityFeePerGas": 1}
+    }
+}
+
+/// Converts the transaction without the base fee of its block, the gas price of EIP-1559
+/// transactions being their `maxFeePerGas`. Use [`StoredTransaction::with_base_fee`] to serve
+/// mined transactions.
 impl From<StoredTransaction> for WithOtherFields<Transaction> {
     fn from(tx: StoredTransaction) -> Self {
-        tx.tx
+        tx.with_base_fee(None)
     }
 }
 
 impl From<&StoredTransaction> for WithOtherFields<Transaction> {
     fn from(tx: &StoredTransaction) -> Self {
-        tx.tx.clone()
+        tx.clone().into()
     }
 }
 
+/// Formats the transaction the way geth does, filling the fields which can be missing
+/// from the indexed transactions and are expected by tools such as `cast` or `forge`:
+/// - the transaction `type`;
+/// - the `chainId` of EIP-155 legacy transactions, encoded in `v`;
+/// - the `yParity` of typed transactions, with `v` set to the same value, and their `accessList`;
+/// - the `gasPrice` of EIP-1559 transactions, set to their effective gas price under the given
+///   base fee, or to their `maxFeePerGas` if the base fee isn't known, as for pending transactions.
+///
+/// Legacy transactions are served without `yParity` nor `accessList`.
+fn into_geth_format(mut tx: Transaction, base_fee: Option<u64>) -> Transaction {
+    let transaction_type = tx.transaction_type.unwrap_or_else(|| {
+        if tx.max_fee_per_gas.is_some() {
+            2
+        } else {
+            u8::from(tx.access_list.is_some())
+        }
+    });
+    tx.transaction_type = Some(transaction_type);
+
+    if transaction_type == 0 {
+        if let Some(signature) = tx.signature.as_mut() {
+            signature.y_parity = None;
+            if tx.chain_id.is_none() && signature.v >= U256::from(35) {
+                tx.chain_id = ((signature.v - U256::from(35)) / U256::from(2)).try_into().ok();
+            }
+        }
+        tx.access_list = None;
+    } else {
+        if let Some(signature) = tx.signature.as_mut() {
+            // Some typed transactions are indexed with a pre EIP-155 `v` of 27 or 28
+            let y_parity = signature
+                .y_parity
+                .map_or(signature.v == U256::from(1) || signature.v == U256::from(28), |parity| parity.0);
+            signature.v = U256::from(y_parity);
+            signature.y_parity = Some(Parity(y_parity));
+        }
+        tx.access_list.get_or_insert_with(Default::default);
+        if let Some(max_fee_per_gas) = tx.max_fee_per_gas {
+            tx.gas_price =
+                Some(effective_gas_price(max_fee_per_gas, tx.max_priority_fee_per_gas.unwrap_or_default(), base_fee));
+        }
+    }
+
+    tx
+}
+
+/// Returns the price per gas paid by an EIP-1559 transaction under the given base fee, capped
+/// by its `maxFeePerGas`. Returns the `maxFeePerGas` if the base fee isn't known.
+fn effective_gas_price(max_fee_per_gas: u128, max_priority_fee_per_gas: u128, base_fee: Option<u64>) -> u128 {
+    base_fee.map_or(max_fee_per_gas, |base_fee| {
+        u128::from(base_fee).saturating_add(max_priority_fee_per_gas).min(max_fee_per_gas)
+    })
+}
+
 impl From<WithOtherFields<Transaction>> for StoredTransaction {
     fn from(tx: WithOtherFields<Transaction>) -> Self {
         Self { tx }
@@ -188,4 +481,100 @@ mod tests {
             assert_eq!(transaction.from, transaction_signed.recover_signer().unwrap());
         }
     }
+
+    #[test]
+    fn test_into_geth_format_legacy() {
+        // Given
+        // An EIP-155 legacy transaction on chain 1 indexed with a y parity and without chain id
+        let tx = Transaction {
+            signature: Some(alloy_rpc_types::Signature {
+                r: U256::from(1),
+                s: U256::from(2),
+                v: U256::from(38),
+                y_parity: Some(Parity(true)),
+            }),
+            gas_price: Some(1),
+            ..Default::default()
+        };
+
+        // When
+        let tx = into_geth_format(tx, Some(1));
+
+        // Then
+        assert_eq!(tx.transaction_type, Some(0));
+        assert_eq!(tx.chain_id, Some(1));
+        assert_eq!(tx.access_list, None);
+        let signature = tx.signature.unwrap();
+        assert_eq!(signature.v, U256::from(38));
+        assert_eq!(signature.y_parity, None);
+    }
+
+    #[test]
+    fn test_into_geth_format_eip1559() {
+        // Given
+        // An EIP-1559 transaction indexed with a pre EIP-155 v, without y parity, access list nor gas price
+        let tx = Transaction {
+            signature: Some(alloy_rpc_types::Signature {
+                r: U256::from(1),
+                s: U256::from(2),
+                v: U256::from(28),
+                y_parity: None,
+            }),
+            max_fee_per_gas: Some(10),
+            max_priority_fee_per_gas: Some(1),
+            chain_id: Some(1),
+            ..Default::default()
+        };
+
+        // When
+        let mined = into_geth_format(tx.clone(), Some(5));
+        let capped = into_geth_format(tx.clone(), Some(20));
+        let tx = into_geth_format(tx, None);
+
+        // Then
+        assert_eq!(mined.gas_price, Some(6));
+        assert_eq!(capped.gas_price, Some(10));
+        assert_eq!(tx.transaction_type, Some(2));
+        assert_eq!(tx.access_list, Some(Default::default()));
+        assert_eq!(tx.gas_price, Some(10));
+        let signature = tx.signature.unwrap();
+        assert_eq!(signature.v, U256::from(1));
+        assert_eq!(signature.y_parity, Some(Parity(true)));
+    }
+
+    #[test]
+    fn test_into_geth_format_is_idempotent_on_arbitrary_transactions() {
+        for _ in 0..10 {
+            // Given
+            let mut bytes = [0u8; 1024];
+            rand::thread_rng().fill(bytes.as_mut_slice());
+            let transaction = StoredTransaction::arbitrary(&mut arbitrary::Unstructured::new(&bytes)).unwrap();
+            let base_fee = Some(u64::from(rand::random::<u32>()));
+
+            // When
+            let formatted = transaction.with_base_fee(base_fee);
+            let reformatted = StoredTransaction::from(formatted.clone()).with_base_fee(base_fee);
+
+            // Then
+            assert_eq!(reformatted, formatted);
+        }
+    }
+
+    #[test]
+    fn test_stored_transaction_fees() {
+        // Given
+        let mut bytes = [0u8; 1024];
+        rand::thread_rng().fill(bytes.as_mut_slice());
+        let transaction = StoredTransaction::arbitrary(&mut arbitrary::Unstructured::new(&bytes)).unwrap();
+        let document = mongodb::bson::to_document(&transaction).unwrap();
+
+        // When
+        let StoredTransactionFees { tx: fees } = mongodb::bson::from_document(document).unwrap();
+
+        // Then
+        assert_eq!(fees.block_number, transaction.tx.block_number);
+        assert_eq!(fees.gas_price, transaction.tx.gas_price);
+        assert_eq!(fees.max_fee_per_gas, transaction.tx.max_fee_per_gas);
+        assert_eq!(fees.max_priority_fee_per_gas, transaction.tx.max_priority_fee_per_gas);
+    }
 }
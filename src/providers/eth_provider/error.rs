@@ -8,6 +8,9 @@ use reth_transaction_pool::error::PoolError;
 use starknet::core::types::Felt;
 use thiserror::Error;
 
+/// The revert reason of the Starknet transactions which ran out of Cairo resources.
+pub const OUT_OF_RESOURCES_REVERT_REASON: &str = "RunResources has no remaining steps";
+
 /// List of JSON-RPC error codes from ETH rpc spec.
 /// <https://github.com/ethereum/EIPs/blob/master/EIPS/eip-1474.md>
 #[derive(Debug, Copy, PartialEq, Eq, Clone)]
@@ -35,7 +38,10 @@ impl From<&EthApiError> for EthRpcErrorCode {
             EthApiError::UnknownBlock(_) | EthApiError::UnknownBlockNumber(_) | EthApiError::TransactionNotFound(_) => {
                 Self::ResourceNotFound
             }
+            EthApiError::HistoryPruned(_) => Self::InvalidInput,
+            EthApiError::ArchiveUnavailable(_) => Self::ResourceUnavailable,
             EthApiError::Signature(_)
+            | EthApiError::InvalidQuery(_)
             | EthApiError::EthereumDataFormat(_)
             | EthApiError::CalldataExceededLimit(_, _)
             | EthApiError::RethEthApi(_) => Self::InvalidParams,
@@ -62,6 +68,11 @@ pub enum EthApiError {
     UnknownBlockNumber(Option<u64>),
     /// When a transaction is not found
     TransactionNotFound(B256),
+    /// When the state of a block is no longer available on a full node
+    HistoryPruned(u64),
+    /// When the logs and receipts of the blocks up to the given block were moved to an archive
+    /// which isn't configured
+    ArchiveUnavailable(u64),
     /// Error related to transaction
     Transaction(#[from] TransactionError),
     /// Error related to transaction pool
@@ -80,6 +91,8 @@ pub enum EthApiError {
     CalldataExceededLimit(usize, usize),
     /// Reth Eth API error
     RethEthApi(#[from] RethEthApiError),
+    /// Query exceeding the limits of the node
+    InvalidQuery(String),
 }
 
 impl std::fmt::Display for EthApiError {
@@ -88,6 +101,12 @@ impl std::fmt::Display for EthApiError {
             Self::UnknownBlock(block) => write!(f, "unknown block {block}"),
             Self::UnknownBlockNumber(block) => write!(f, "unknown block number {block:?}"),
             Self::TransactionNotFound(tx) => write!(f, "transaction not found {tx}"),
+            Self::HistoryPruned(block) => {
+                write!(f, "missing trie node: history pruned, state of block {block} is not available")
+            }
+            Self::ArchiveUnavailable(block) => {
+                write!(f, "the logs and receipts up to block {block} are archived and no archive is configured")
+            }
             Self::Transaction(err) => write!(f, "{err}"),
             Self::Pool(err) => write!(f, "{err}"),
             Self::Signature(err) => write!(f, "{err}"),
@@ -103,6 +122,7 @@ impl std::fmt::Display for EthApiError {
             Self::CalldataExceededLimit(limit, actual) => {
                 write!(f, "calldata exceeded limit of {limit}: {actual}")
             }
+            Self::InvalidQuery(reason) => write!(f, "invalid query: {reason}"),
         }
     }
 }
@@ -142,9 +162,11 @@ pub enum KakarotError {
     #[error(transparent)]
     Provider(#[from] starknet::providers::ProviderError),
     /// Error related to the database.
+    #[cfg(feature = "mongo")]
     #[error(transparent)]
     Database(#[from] mongodb::error::Error),
     /// Error related to the database deserialization.
+    #[cfg(feature = "mongo")]
     #[error(transparent)]
     DatabaseDeserialization(#[from] mongodb::bson::de::Error),
 }
@@ -169,7 +191,7 @@ pub enum ExecutionError {
 impl From<cainome::cairo_serde::Error> for ExecutionError {
     fn from(error: cainome::cairo_serde::Error) -> Self {
         let error = error.to_string();
-        if error.contains("RunResources has no remaining steps.") {
+        if error.contains(OUT_OF_RESOURCES_REVERT_REASON) {
             return Self::CairoVm(CairoError::VmOutOfResources);
         }
         Self::Other(error)
@@ -301,9 +323,18 @@ pub enum TransactionError {
     /// Thrown when the max fee per gas is lower than the max priority fee per gas.
     #[error("max fee per gas {0} lower than max priority fee per gas {1}")]
     TipAboveFeeCap(u128, u128),
+    /// Thrown when the call data of a request is set in both `input` and `data` with different values.
+    #[error(r#"both "data" and "input" are set and not equal. Please use "input" to pass transaction call data"#)]
+    ConflictingInput,
+    /// Thrown when a request sets both the legacy gas price and the EIP-1559 fee fields.
+    #[error("both gasPrice and (maxFeePerGas or maxPriorityFeePerGas) specified")]
+    ConflictingFeeFields,
     /// Thrown when the gas limit exceeds the block's gas limit.
     #[error("transaction gas limit {0} exceeds block gas limit {1}")]
     ExceedsBlockGasLimit(u128, u128),
+    /// Thrown when the init code of a contract creation exceeds the EIP-3860 limit.
+    #[error("max initcode size exceeded: code size {0}, limit {1}")]
+    MaxInitCodeSizeExceeded(usize, usize),
     /// Thrown when the transaction isn't the
     /// [`BlockTransactions::FullTransactions`] variant.
     #[error("expected full transactions")]
@@ -322,7 +353,11 @@ pub enum TransactionError {
 impl From<&TransactionError> for EthRpcErrorCode {
     fn from(error: &TransactionError) -> Self {
         match error {
-            TransactionError::InvalidChainId | TransactionError::InvalidTransactionType => Self::InvalidInput,
+            TransactionError::InvalidChainId
+            | TransactionError::InvalidTransactionType
+            | TransactionError::ConflictingInput
+            | TransactionError::ConflictingFeeFields
+            | TransactionError::MaxInitCodeSizeExceeded(_, _) => Self::InvalidInput,
             TransactionError::GasOverflow
             | TransactionError::FeeCapTooLow(_, _)
             | TransactionError::TipAboveFeeCap(_, _) => Self::TransactionRejected,
@@ -0,0 +1,445 @@
+//! Read path serving the transactions relayed by the node from Starknet while the indexer lags.
+//!
+//! When a transaction or its receipt is missing from the database but the hash mapping of the
+//! relayer (Ethereum -> Starknet) exists, the Starknet transaction is fetched and converted on
+//! the fly, as the indexer does. If `STARKNET_FALLBACK_PERSIST` is set, the converted documents
+//! are stored, to be overwritten by the indexer once it catches up.
+//!
+//! The transactions found this way are served with their [`SubmissionStatus`], so that a wallet
+//! polling after a submission sees the transaction as soon as it is relayed and its receipt as
+//! soon as it is in the Starknet pending block, rather than `null` until the indexer catches up.
+//! Until then, the receipt lookups get a receipt without block carrying the status, see
+//! [`submission_receipt`]. The Starknet blocks are cached, so that the polls of the receipts of a
+//! block fetch it once, and the receipts are indexed among the Kakarot transactions only.
+
+use super::database::types::transaction::ExtendedTransaction;
+use schemars::JsonSchema;
+#[cfg(feature = "mongo")]
+use {
+    super::{
+        database::{
+            ethereum::EthereumTransactionStore,
+            filter::{self, EthDatabaseFilterBuilder},
+            types::receipt::{ExtendedTxReceipt, StoredTransactionReceipt, OUT_OF_RESOURCES_FIELD},
+        },
+        error::KakarotError,
+        pending::{
+            invoke_content_calldata, kakarot_positions, out_of_resources, receipt_envelope, to_eth_receipt,
+            PendingBlockInfo, TransactionPosition,
+        },
+        provider::{EthApiResult, EthDataProvider},
+        starknet::kakarot_core::KAKAROT_ADDRESS,
+    },
+    crate::{
+        constants::{PENDING_BLOCK_POLL_INTERVAL, REPLAY_MODE, STARKNET_FALLBACK_PERSIST},
+        models::transaction::starknet_calldata_to_transaction,
+    },
+    alloy_consensus::{Eip658Value, Transaction as _},
+    alloy_primitives::{Bloom, B256},
+    alloy_rpc_types::{Receipt, ReceiptWithBloom, TransactionReceipt},
+    alloy_serde::WithOtherFields,
+    reth_primitives::TransactionSignedEcRecovered,
+    reth_rpc::eth::EthTxBuilder,
+    reth_rpc_eth_types::TransactionSource,
+    starknet::{
+        core::types::{
+            BlockId, BlockTag, ExecutionResult, Felt, InvokeTransaction, MaybePendingBlockWithReceipts, ReceiptBlock,
+            StarknetError, Transaction, TransactionWithReceipt,
+        },
+        providers::{Provider, ProviderError},
+    },
+    std::{
+        collections::VecDeque,
+        sync::{Arc, Mutex, MutexGuard, PoisonError},
+        time::Instant,
+    },
+};
+
+/// Field of a transaction submitted to the node, served while the transaction isn't indexed, with
+/// its [`SubmissionStatus`].
+pub const SUBMISSION_STATUS_FIELD: &str = "submissionStatus";
+
+/// The status of a transaction submitted to the node which isn't indexed yet, so that the clients
+/// polling for a transaction after its submission can tell how far it went.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, JsonSchema)]
+#[schemars(rename_all = "lowercase")]
+pub enum SubmissionStatus {
+    /// The transaction is in the mempool of the node, waiting to be relayed.
+    Pending,
+    /// The transaction was relayed to Starknet, waiting to be executed.
+    Relayed,
+    /// The transaction is included in a Starknet block, pending or not, waiting to be indexed.
+    Included,
+}
+
+impl SubmissionStatus {
+    /// Returns the name of the status.
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Pending => "pending",
+            Self::Relayed => "relayed",
+            Self::Included => "included",
+        }
+    }
+
+    /// Sets the status in the `submissionStatus` field of the transaction.
+    pub fn set(self, transaction: &mut ExtendedTransaction) {
+        transaction.other.insert(SUBMISSION_STATUS_FIELD.to_string(), self.as_str().into());
+    }
+}
+
+/// The number of closed Starknet blocks kept by [`FallbackBlocks`].
+#[cfg(feature = "mongo")]
+const FALLBACK_BLOCKS_CAPACITY: usize = 16;
+
+/// A Starknet block fetched by the fallback, with the positions of its Kakarot transactions.
+#[cfg(feature = "mongo")]
+#[derive(Debug)]
+pub struct FallbackBlock {
+    pub(super) info: PendingBlockInfo,
+    pub(super) transactions: Vec<TransactionWithReceipt>,
+    pub(super) positions: Vec<Option<TransactionPosition>>,
+}
+
+#[cfg(feature = "mongo")]
+impl FallbackBlock {
+    pub(super) fn new(
+        info: PendingBlockInfo,
+        transactions: Vec<TransactionWithReceipt>,
+        kakarot_address: Felt,
+    ) -> Self {
+        let positions = kakarot_positions(&transactions, kakarot_address);
+        Self { info, transactions, positions }
+    }
+
+    /// Returns the index of the Starknet transaction in the block.
+    fn index_of(&self, starknet_hash: Felt) -> Option<usize> {
+        self.transactions.iter().position(|transaction| *transaction.receipt.transaction_hash() == starknet_hash)
+    }
+}
+
+/// The Starknet blocks fetched by the fallback and by the poll of the pending block, so that the
+/// polls of the receipts of the transactions of a block cost a single fetch of the block. The last
+/// closed blocks are kept, and the pending block for [`PENDING_BLOCK_POLL_INTERVAL`].
+#[cfg(feature = "mongo")]
+#[derive(Debug, Default)]
+pub struct FallbackBlocks {
+    closed: Mutex<VecDeque<Arc<FallbackBlock>>>,
+    pending: Mutex<Option<(Arc<FallbackBlock>, Instant)>>,
+}
+
+#[cfg(feature = "mongo")]
+impl FallbackBlocks {
+    /// Returns the cached block, if any. The block must include the Starknet transaction, if set.
+    /// The latest block is never cached under its tag.
+    fn get(&self, block_id: BlockId, starknet_hash: Option<Felt>) -> Option<Arc<FallbackBlock>> {
+        let block = match block_id {
+            BlockId::Number(number) => lock(&self.closed).iter().find(|block| block.info.number == number).cloned(),
+            BlockId::Tag(BlockTag::Pending) => lock(&self.pending)
+                .as_ref()
+                .filter(|(_, fetched_at)| fetched_at.elapsed() <= *PENDING_BLOCK_POLL_INTERVAL)
+                .map(|(block, _)| Arc::clone(block)),
+            _ => None,
+        };
+        block.filter(|block| starknet_hash.map_or(true, |starknet_hash| block.index_of(starknet_hash).is_some()))
+    }
+
+    /// Returns the number of the cached closed block with the given hash, if any.
+    fn number_of(&self, hash: Felt) -> Option<u64> {
+        let hash = B256::from(hash.to_bytes_be());
+        lock(&self.closed).iter().find(|block| block.info.hash == hash).map(|block| block.info.number)
+    }
+
+    /// Caches the block, evicting the oldest closed block if the cache is full.
+    fn insert(&self, block: Arc<FallbackBlock>) {
+        if block.info.hash.is_zero() {
+            *lock(&self.pending) = Some((block, Instant::now()));
+            return;
+        }
+        let mut closed = lock(&self.closed);
+        closed.retain(|cached| cached.info.number != block.info.number);
+        if closed.len() >= FALLBACK_BLOCKS_CAPACITY {
+            closed.pop_front();
+        }
+        closed.push_back(block);
+    }
+}
+
+#[cfg(feature = "mongo")]
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Returns the receipt served for a transaction submitted to the node which isn't executed yet,
+/// with its [`SubmissionStatus`]. The receipt has no block and no status, the clients waiting for
+/// the execution of the transaction must wait for its block number.
+#[cfg(feature = "mongo")]
+pub fn submission_receipt(transaction: &TransactionSignedEcRecovered, status: SubmissionStatus) -> ExtendedTxReceipt {
+    let receipt = ReceiptWithBloom {
+        receipt: Receipt { status: Eip658Value::PostState(B256::ZERO), cumulative_gas_used: 0, logs: Vec::new() },
+        logs_bloom: Bloom::ZERO,
+    };
+    let mut receipt = WithOtherFields::new(TransactionReceipt {
+        inner: receipt_envelope(transaction.tx_type(), receipt),
+        transaction_hash: transaction.hash(),
+        transaction_index: None,
+        block_hash: None,
+        block_number: None,
+        gas_used: 0,
+        effective_gas_price: transaction.max_fee_per_gas(),
+        blob_gas_used: None,
+        blob_gas_price: None,
+        from: transaction.signer(),
+        to: transaction.to(),
+        contract_address: None,
+        authorization_list: None,
+    });
+    receipt.other.insert(SUBMISSION_STATUS_FIELD.to_string(), status.as_str().into());
+    receipt
+}
+
+#[cfg(feature = "mongo")]
+impl<SP> EthDataProvider<SP>
+where
+    SP: Provider + Send + Sync,
+{
+    /// Returns the transaction relayed by the node with the given hash and its receipt, built
+    /// from the Starknet chain. The receipt is `None` if the Starknet transaction was received by
+    /// the sequencer but isn't executed yet, and is built from the pending block if the Starknet
+    /// transaction is only included in it. Returns `None` if the transaction wasn't relayed by the
+    /// node, or in replay mode.
+    pub(crate) async fn starknet_fallback(
+        &self,
+        hash: B256,
+    ) -> EthApiResult<Option<(ExtendedTransaction, Option<ExtendedTxReceipt>)>> {
+        // The snapshot is served as is in replay mode
+        if *REPLAY_MODE {
+            return Ok(None);
+        }
+
+        let Some(starknet_hash) = self.database().starknet_transaction_hash(&hash).await? else {
+            return Ok(None);
+        };
+
+        let receipt = match self.starknet_provider_inner().get_transaction_receipt(starknet_hash).await {
+            Ok(receipt) => receipt,
+            // The Starknet transaction isn't executed yet, it may only be received by the sequencer
+            Err(ProviderError::StarknetError(StarknetError::TransactionHashNotFound)) => {
+                return self.relayed_fallback(hash, starknet_hash).await;
+            }
+            Err(err) => return Err(KakarotError::from(err).into()),
+        };
+
+        let block_id = match receipt.block {
+            ReceiptBlock::Block { block_number, .. } => BlockId::Number(block_number),
+            ReceiptBlock::Pending => BlockId::Tag(BlockTag::Pending),
+        };
+        let block = self.starknet_block(block_id, Some(starknet_hash)).await?;
+        // The pending block can be closed between the two calls, the transaction is then found at
+        // the next call
+        let Some(index) = block.index_of(starknet_hash) else {
+            return Ok(None);
+        };
+        let Some(position) = block.positions[index] else {
+            return Ok(None);
+        };
+        let starknet_transaction = &block.transactions[index];
+
+        // The relayed Ethereum transaction must match the requested hash
+        let Some(transaction) =
+            invoke_content_calldata(&starknet_transaction.transaction).and_then(starknet_calldata_to_transaction)
+        else {
+            return Ok(None);
+        };
+        if transaction.hash() != hash {
+            return Ok(None);
+        }
+        let Some(recovered) = transaction.clone().into_ecrecovered() else {
+            return Ok(None);
+        };
+
+        let info = block.info;
+        let receipt = to_eth_receipt(
+            &transaction,
+            recovered.signer(),
+            &starknet_transaction.receipt,
+            info,
+            position,
+            *KAKAROT_ADDRESS,
+        );
+        let mut transaction = WithOtherFields::new(
+            TransactionSource::Block {
+                transaction: recovered,
+                index: position.index,
+                block_hash: info.hash,
+                block_number: info.number,
+                base_fee: None,
+            }
+            .into_transaction(&EthTxBuilder {}),
+        );
+        // As the indexer, the transactions reverted on Starknet are flagged with their revert reason
+        let execution_result = starknet_transaction.receipt.execution_result();
+        if let ExecutionResult::Reverted { reason } = execution_result {
+            transaction.other.insert("reverted".to_string(), reason.clone().into());
+        }
+        if out_of_resources(execution_result) {
+            transaction.other.insert(OUT_OF_RESOURCES_FIELD.to_string(), true.into());
+        }
+
+        tracing::info!(
+            target: "starknet_fallback",
+            monotonic_counter.starknet_fallback_reads = 1,
+            ?hash,
+            ?starknet_hash,
+            "served transaction missing from the database from Starknet"
+        );
+        // The documents of the pending block would be stored with a zero block hash
+        if *STARKNET_FALLBACK_PERSIST && !info.hash.is_zero() {
+            self.persist_fallback(&transaction, &receipt).await;
+        }
+        SubmissionStatus::Included.set(&mut transaction);
+
+        Ok(Some((transaction, Some(receipt))))
+    }
+
+    /// Returns the transaction relayed by the node with the given hash, without receipt, if its
+    /// Starknet transaction was received by the sequencer but isn't executed yet.
+    async fn relayed_fallback(
+        &self,
+        hash: B256,
+        starknet_hash: Felt,
+    ) -> EthApiResult<Option<(ExtendedTransaction, Option<ExtendedTxReceipt>)>> {
+        let transaction = match self.starknet_provider_inner().get_transaction_by_hash(starknet_hash).await {
+            Ok(transaction) => transaction,
+            Err(ProviderError::StarknetError(StarknetError::TransactionHashNotFound)) => return Ok(None),
+            Err(err) => return Err(KakarotError::from(err).into()),
+        };
+
+        // The relayed Ethereum transaction must match the requested hash
+        let Some(transaction) = invoke_calldata(&transaction).and_then(starknet_calldata_to_transaction) else {
+            return Ok(None);
+        };
+        if transaction.hash() != hash {
+            return Ok(None);
+        }
+        let Some(recovered) = transaction.into_ecrecovered() else {
+            return Ok(None);
+        };
+
+        let mut transaction =
+            WithOtherFields::new(TransactionSource::Pool(recovered).into_transaction(&EthTxBuilder {}));
+        SubmissionStatus::Relayed.set(&mut transaction);
+        Ok(Some((transaction, None)))
+    }
+
+    /// Returns the Starknet block, from the cache if it was already fetched. A cached block which
+    /// doesn't include the Starknet transaction, if set, is fetched again.
+    pub async fn starknet_block(
+        &self,
+        block_id: BlockId,
+        starknet_hash: Option<Felt>,
+    ) -> EthApiResult<Arc<FallbackBlock>> {
+        if let Some(block) = self.fallback_blocks().get(block_id, starknet_hash) {
+            return Ok(block);
+        }
+
+        // The pending block is numbered after its parent, usually cached, with a zero hash
+        let starknet_provider = self.starknet_provider_inner();
+        let block = match starknet_provider.get_block_with_receipts(block_id).await.map_err(KakarotError::from)? {
+            MaybePendingBlockWithReceipts::Block(block) => FallbackBlock::new(
+                PendingBlockInfo { number: block.block_number, hash: B256::from(block.block_hash.to_bytes_be()) },
+                block.transactions,
+                *KAKAROT_ADDRESS,
+            ),
+            MaybePendingBlockWithReceipts::PendingBlock(block) => {
+                let parent_number = match self.fallback_blocks().number_of(block.parent_hash) {
+                    Some(number) => number,
+                    None => starknet_provider.block_number().await.map_err(KakarotError::from)?,
+                };
+                let number = parent_number + 1;
+                FallbackBlock::new(PendingBlockInfo { number, hash: B256::ZERO }, block.transactions, *KAKAROT_ADDRESS)
+            }
+        };
+        let block = Arc::new(block);
+        self.fallback_blocks().insert(Arc::clone(&block));
+        Ok(block)
+    }
+
+    /// Stores the transaction and the receipt built from Starknet. A failure only delays the
+    /// availability of the documents until the indexer stores them.
+    async fn persist_fallback(&self, transaction: &ExtendedTransaction, receipt: &ExtendedTxReceipt) {
+        if let Err(err) = self.database().upsert_transaction(transaction.clone()).await {
+            tracing::warn!(target: "starknet_fallback", ?err, "failed to persist transaction");
+        }
+
+        let filter =
+            EthDatabaseFilterBuilder::<filter::Receipt>::default().with_tx_hash(&receipt.transaction_hash).build();
+        let receipt = StoredTransactionReceipt { receipt: receipt.clone() };
+        if let Err(err) = self.database().update_one(receipt, filter, true).await {
+            tracing::warn!(target: "starknet_fallback", ?err, "failed to persist receipt");
+        }
+    }
+}
+
+/// Returns the calldata of the Starknet transaction if it is an invoke transaction.
+#[cfg(feature = "mongo")]
+fn invoke_calldata(transaction: &Transaction) -> Option<&[Felt]> {
+    match transaction {
+        Transaction::Invoke(InvokeTransaction::V1(transaction)) => Some(&transaction.calldata),
+        Transaction::Invoke(InvokeTransaction::V3(transaction)) => Some(&transaction.calldata),
+        _ => None,
+    }
+}
+
+#[cfg(all(test, feature = "mongo"))]
+mod tests {
+    use super::*;
+    use alloy_rpc_types::Transaction;
+
+    #[test]
+    fn test_submission_status() {
+        // Given
+        let mut transaction = WithOtherFields::new(Transaction::default());
+
+        // When
+        SubmissionStatus::Pending.set(&mut transaction);
+        SubmissionStatus::Relayed.set(&mut transaction);
+
+        // Then
+        assert_eq!(transaction.other[SUBMISSION_STATUS_FIELD], "relayed");
+        assert_eq!(SubmissionStatus::Included.as_str(), "included");
+    }
+
+    #[test]
+    fn test_fallback_blocks() {
+        // Given
+        let blocks = FallbackBlocks::default();
+        let block = |number: u64, hash: B256| {
+            let transaction = serde_json::from_value(serde_json::json!({
+                "transaction": { "type": "L1_HANDLER", "version": "0x0", "nonce": "0x0", "contract_address": "0x1",
+                    "entry_point_selector": "0x1", "calldata": [] },
+                "receipt": { "type": "L1_HANDLER", "transaction_hash": Felt::from(number), "message_hash": "0x0",
+                    "actual_fee": { "amount": "0x0", "unit": "WEI" }, "finality_status": "ACCEPTED_ON_L2",
+                    "execution_status": "SUCCEEDED", "messages_sent": [], "events": [],
+                    "execution_resources": { "steps": 1, "data_availability": { "l1_gas": 0, "l1_data_gas": 0 } } },
+            }))
+            .unwrap();
+            Arc::new(FallbackBlock::new(PendingBlockInfo { number, hash }, vec![transaction], Felt::ZERO))
+        };
+
+        // When
+        for number in 0..=FALLBACK_BLOCKS_CAPACITY as u64 {
+            blocks.insert(block(number, B256::with_last_byte(1)));
+        }
+        blocks.insert(block(100, B256::ZERO));
+
+        // Then
+        assert!(blocks.get(BlockId::Number(0), None).is_none());
+        assert!(blocks.get(BlockId::Number(1), Some(Felt::ONE)).is_some());
+        assert!(blocks.get(BlockId::Number(1), Some(Felt::TWO)).is_none());
+        assert!(blocks.get(BlockId::Tag(BlockTag::Latest), None).is_none());
+        assert!(blocks.get(BlockId::Tag(BlockTag::Pending), Some(Felt::from(100))).is_some());
+        assert_eq!(blocks.get(BlockId::Tag(BlockTag::Pending), None).unwrap().positions, vec![None]);
+        assert_eq!(blocks.number_of(Felt::ONE), Some(FALLBACK_BLOCKS_CAPACITY as u64));
+    }
+}
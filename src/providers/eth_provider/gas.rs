@@ -1,23 +1,174 @@
 use super::{
-    constant::BLOCK_NUMBER_HEX_STRING_LEN,
-    error::{ExecutionError, KakarotError},
-    starknet::kakarot_core::{core::KakarotCoreReader, KAKAROT_ADDRESS},
-};
-use crate::{
-    into_via_wrapper,
-    providers::eth_provider::{
-        database::{filter::format_hex, types::header::StoredHeader},
-        provider::{EthApiResult, EthDataProvider},
+    constant::{
+        PriorityFeePolicy, DA_BASE_FELTS, DA_DATA_GAS_PER_FELT, DA_FELTS_PER_CALLDATA_WORD, DA_L1_GAS_PER_FELT,
+        PRIORITY_FEE_POLICY, RELAYER_CALLDATA_FELT_GAS, RELAYER_INVOKE_GAS_OVERHEAD,
     },
+    database::types::transaction::ExtendedTransaction,
+    provider::EthApiResult,
 };
+use crate::eth_rpc::openrpc::SchemaOf;
 use alloy_eips::{BlockId, BlockNumberOrTag};
 use alloy_primitives::{U256, U64};
 use alloy_rpc_types::{FeeHistory, TransactionRequest};
 use async_trait::async_trait;
 use auto_impl::auto_impl;
-use eyre::eyre;
-use mongodb::bson::doc;
-use tracing::Instrument;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "mongo")]
+use {
+    super::{
+        constant::{
+            BLOCK_NUMBER_HEX_STRING_LEN, CONTRACT_DEPLOYMENT_GAS_OVERHEAD, ESTIMATE_GAS_WITH_RELAYER_OVERHEAD,
+            FEE_HISTORY_MAX_BLOCK_COUNT, FEE_SUGGESTION_BLOCK_COUNT, MAX_PRIORITY_FEE_PER_GAS,
+        },
+        database::{
+            filter::{self, format_hex, EthDatabaseFilterBuilder},
+            types::{
+                header::StoredHeader,
+                transaction::{StoredTransactionFees, TransactionFees},
+            },
+            FindOpts,
+        },
+        error::{EthApiError, ExecutionError, KakarotError},
+        provider::EthDataProvider,
+        starknet::kakarot_core::{core::KakarotCoreReader, KAKAROT_ADDRESS},
+    },
+    crate::into_via_wrapper,
+    alloy_primitives::TxKind,
+    eyre::eyre,
+    mongodb::bson::doc,
+    starknet::core::types::{
+        BlockId as StarknetBlockId, BlockTag, L1DataAvailabilityMode, MaybePendingBlockWithTxHashes, ResourcePrice,
+    },
+    std::collections::HashMap,
+    tracing::Instrument,
+};
+
+/// A recommendation of EIP-1559 fees for a speed tier.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct FeeSuggestion {
+    /// The suggested maximum fee per gas.
+    #[schemars(with = "SchemaOf<U256>")]
+    pub max_fee_per_gas: U256,
+    /// The suggested maximum priority fee per gas.
+    #[schemars(with = "SchemaOf<U256>")]
+    pub max_priority_fee_per_gas: U256,
+}
+
+/// The EIP-1559 fee recommendations returned by `kakarot_suggestFees`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct FeeSuggestions {
+    /// The current base fee per gas, as returned by the gas oracle.
+    #[schemars(with = "SchemaOf<U256>")]
+    pub base_fee_per_gas: U256,
+    /// The average gas used ratio of the recent blocks.
+    pub gas_used_ratio: f64,
+    /// Fees for a transaction which can wait for the load of the chain to decrease.
+    pub slow: FeeSuggestion,
+    /// Fees for a transaction to be included in the next few blocks.
+    pub normal: FeeSuggestion,
+    /// Fees for a transaction to be included as soon as possible.
+    pub fast: FeeSuggestion,
+    /// The estimated L1 data availability cost of the transaction, if one was given.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data_availability: Option<DataAvailabilityCost>,
+    /// The handling of the priority fee. Under [`PriorityFeePolicy::Ignored`], the suggested
+    /// priority fees only order the transactions in the mempool and aren't charged.
+    #[serde(default)]
+    pub priority_fee_policy: PriorityFeePolicy,
+}
+
+impl FeeSuggestions {
+    /// Derives the fee suggestions from the base fee, the average gas used ratio and the priority
+    /// fee observed in the recent blocks.
+    ///
+    /// The priority fee is the observed one, clamped to the maximum priority fee, and is the same
+    /// for every tier: paying more than the others doesn't speed up the inclusion once the
+    /// transaction is relayed. The tiers only differ by the headroom of their maximum fee, which
+    /// covers the base fee increasing by 12.5% (the maximum EIP-1559 increase) over 1, 2 and 4
+    /// blocks.
+    pub fn new(base_fee: u128, gas_used_ratio: f64, observed_priority_fee: u128, max_priority_fee: u128) -> Self {
+        let gas_used_ratio = if gas_used_ratio.is_finite() { gas_used_ratio.clamp(0., 1.) } else { 0. };
+        let tip = observed_priority_fee.min(max_priority_fee);
+
+        let suggestion = |headroom_blocks: u32| {
+            let max_base_fee = (0..headroom_blocks).fold(base_fee, |fee, _| fee.saturating_add(fee.div_ceil(8)));
+            FeeSuggestion {
+                max_fee_per_gas: U256::from(max_base_fee.saturating_add(tip)),
+                max_priority_fee_per_gas: U256::from(tip),
+            }
+        };
+
+        Self {
+            base_fee_per_gas: U256::from(base_fee),
+            gas_used_ratio,
+            slow: suggestion(1),
+            normal: suggestion(2),
+            fast: suggestion(4),
+            data_availability: None,
+            priority_fee_policy: *PRIORITY_FEE_POLICY,
+        }
+    }
+}
+
+/// The mode in which Starknet posts its state diffs to L1.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum DataAvailabilityMode {
+    /// The state diffs are posted in blobs (EIP-4844), paid in L1 data gas.
+    #[default]
+    Blob,
+    /// The state diffs are posted in the calldata of the L1 transactions, paid in L1 gas.
+    Calldata,
+}
+
+/// The estimated share of a transaction in the cost of posting the state diffs of Starknet to
+/// L1, returned by `kakarot_estimateDataAvailabilityCost`. The cost is included in the fees paid
+/// by the relayer, it isn't charged separately to the sender.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct DataAvailabilityCost {
+    /// The current data availability mode of Starknet.
+    pub mode: DataAvailabilityMode,
+    /// The estimated number of felts of the state diff of the transaction.
+    pub felts: u64,
+    /// The L1 gas of the state diff, in the calldata mode.
+    pub l1_gas: u64,
+    /// The L1 data gas of the state diff, in the blob mode.
+    pub l1_data_gas: u64,
+    /// The current price of the L1 gas, in wei.
+    #[schemars(with = "SchemaOf<U256>")]
+    pub l1_gas_price: U256,
+    /// The current price of the L1 data gas, in wei.
+    #[schemars(with = "SchemaOf<U256>")]
+    pub l1_data_gas_price: U256,
+    /// The estimated cost, in wei.
+    #[schemars(with = "SchemaOf<U256>")]
+    pub cost: U256,
+}
+
+impl DataAvailabilityCost {
+    /// Returns the data availability cost of a state diff of the given number of felts, at the
+    /// current L1 prices.
+    pub fn new(mode: DataAvailabilityMode, felts: u64, l1_gas_price: U256, l1_data_gas_price: U256) -> Self {
+        let (l1_gas, l1_data_gas) = match mode {
+            DataAvailabilityMode::Blob => (0, felts.saturating_mul(DA_DATA_GAS_PER_FELT)),
+            DataAvailabilityMode::Calldata => (felts.saturating_mul(DA_L1_GAS_PER_FELT), 0),
+        };
+        let cost = l1_gas_price
+            .saturating_mul(U256::from(l1_gas))
+            .saturating_add(l1_data_gas_price.saturating_mul(U256::from(l1_data_gas)));
+        Self { mode, felts, l1_gas, l1_data_gas, l1_gas_price, l1_data_gas_price, cost }
+    }
+}
+
+/// Returns the estimated number of felts of the state diff of a transaction with an input of the
+/// given length (see [`DA_FELTS_PER_CALLDATA_WORD`]).
+pub fn data_availability_felts(input_len: usize) -> u64 {
+    DA_BASE_FELTS + input_len.div_ceil(32) as u64 * DA_FELTS_PER_CALLDATA_WORD
+}
 
 #[async_trait]
 #[auto_impl(Arc, &)]
@@ -35,8 +186,20 @@ pub trait GasProvider {
 
     /// Returns the current gas price.
     async fn gas_price(&self) -> EthApiResult<U256>;
+
+    /// Returns the EIP-1559 fee recommendations for the slow, normal and fast tiers, derived
+    /// from the current gas price and the priority fees of the recent blocks.
+    async fn suggest_fees(&self) -> EthApiResult<FeeSuggestions>;
+
+    /// Returns the estimated L1 data availability cost of the transaction, at the L1 prices of
+    /// the pending Starknet block.
+    async fn data_availability_cost(&self, request: TransactionRequest) -> EthApiResult<DataAvailabilityCost>;
+
+    /// Returns the block gas limit of Kakarot core.
+    async fn block_gas_limit(&self) -> EthApiResult<u64>;
 }
 
+#[cfg(feature = "mongo")]
 #[async_trait]
 impl<SP> GasProvider for EthDataProvider<SP>
 where
@@ -46,12 +209,26 @@ where
         // Set a high gas limit to make sure the transaction will not fail due to gas.
         let request = TransactionRequest { gas: Some(u64::MAX), ..request };
 
+        // The contract creations deploy a Starknet account for each EVM contract, the number of
+        // contracts deployed by the constructor being overestimated
+        let deployments = match request.to {
+            Some(TxKind::Call(_)) => 0,
+            _ => 1 + contract_creations(&request.input.input().cloned().unwrap_or_default()),
+        };
+
+        let input_len = request.input.input().map_or(0, |input| input.len());
         let gas_used = self.estimate_gas_inner(request, block_id).await?;
 
         // Increase the gas used by 40% to make sure the transaction will not fail due to gas.
         // This is a temporary solution until we have a proper gas estimation.
         // Does not apply to Hive feature otherwise end2end tests will fail.
-        let gas_used = if cfg!(feature = "hive") { gas_used } else { gas_used * 140 / 100 };
+        let gas_used = if cfg!(feature = "hive") {
+            gas_used
+        } else {
+            gas_used * 140 / 100 + u128::from(deployments) * CONTRACT_DEPLOYMENT_GAS_OVERHEAD
+        };
+        let gas_used =
+            if *ESTIMATE_GAS_WITH_RELAYER_OVERHEAD { gas_used + relayer_gas_overhead(input_len) } else { gas_used };
         Ok(U256::from(gas_used))
     }
 
@@ -59,7 +236,7 @@ where
         &self,
         block_count: U64,
         newest_block: BlockNumberOrTag,
-        _reward_percentiles: Option<Vec<f64>>,
+        reward_percentiles: Option<Vec<f64>>,
     ) -> EthApiResult<FeeHistory> {
         if block_count == U64::ZERO {
             return Ok(FeeHistory::default());
@@ -69,7 +246,8 @@ where
         let end_block_plus_one = end_block.saturating_add(1);
 
         // 0 <= start_block <= end_block
-        let start_block = end_block_plus_one.saturating_sub(block_count.to());
+        let block_count = block_count.saturating_to::<u64>().min(FEE_HISTORY_MAX_BLOCK_COUNT);
+        let start_block = end_block_plus_one.saturating_sub(block_count);
 
         let header_filter = doc! {"$and": [ { "header.number": { "$gte": format_hex(start_block, BLOCK_NUMBER_HEX_STRING_LEN) } }, { "header.number": { "$lte": format_hex(end_block, BLOCK_NUMBER_HEX_STRING_LEN) } } ] };
         let blocks: Vec<StoredHeader> = self.database().get(header_filter, None).await?;
@@ -92,6 +270,42 @@ where
             })
             .collect();
 
+        // The rewards are the priority fees charged to the transactions of each block, which are
+        // zero if the priority fee isn't charged
+        let reward = match reward_percentiles.filter(|percentiles| !percentiles.is_empty()) {
+            Some(percentiles) if PRIORITY_FEE_POLICY.is_charged() => {
+                // Only the fee fields of the transactions are read
+                let filter = EthDatabaseFilterBuilder::<filter::Transaction>::default()
+                    .with_block_number_range(start_block, end_block)
+                    .build();
+                let opts = FindOpts::default().with_projection(StoredTransactionFees::projection());
+                let mut fees = HashMap::<u64, Vec<TransactionFees>>::new();
+                for StoredTransactionFees { tx } in self.database().get::<StoredTransactionFees>(filter, opts).await? {
+                    fees.entry(tx.block_number.unwrap_or_default()).or_default().push(tx);
+                }
+                let rewards = blocks.iter().map(|header| {
+                    let base_fee = u128::from(header.base_fee_per_gas.unwrap_or_default());
+                    let tips = fees
+                        .get(&header.number)
+                        .into_iter()
+                        .flatten()
+                        .map(|fees| {
+                            let max_fee_per_gas = fees.max_fee_per_gas.or(fees.gas_price).unwrap_or_default();
+                            PRIORITY_FEE_POLICY.effective_priority_fee(
+                                max_fee_per_gas,
+                                fees.max_priority_fee_per_gas,
+                                base_fee,
+                            )
+                        })
+                        .collect();
+                    rewards_at_percentiles(tips, &percentiles)
+                });
+                Some(rewards.collect())
+            }
+            Some(percentiles) => Some(vec![vec![0; percentiles.len()]; blocks.len()]),
+            None => Some(vec![]),
+        };
+
         let mut base_fee_per_gas =
             blocks.iter().map(|header| header.base_fee_per_gas.unwrap_or_default()).collect::<Vec<_>>();
         // TODO(EIP1559): Remove this when proper base fee computation: if gas_ratio > 50%, increase base_fee_per_gas
@@ -101,7 +315,7 @@ where
             base_fee_per_gas: base_fee_per_gas.into_iter().map(Into::into).collect(),
             gas_used_ratio,
             oldest_block: start_block,
-            reward: Some(vec![]),
+            reward,
             ..Default::default()
         })
     }
@@ -113,4 +327,297 @@ where
             kakarot_contract.get_base_fee().call().instrument(span).await.map_err(ExecutionError::from)?.base_fee;
         Ok(into_via_wrapper!(gas_price))
     }
+
+    async fn suggest_fees(&self) -> EthApiResult<FeeSuggestions> {
+        let base_fee = self.gas_price().await?;
+        let fee_history =
+            self.fee_history(U64::from(FEE_SUGGESTION_BLOCK_COUNT), BlockNumberOrTag::Latest, Some(vec![50.])).await?;
+
+        let ratios = &fee_history.gas_used_ratio;
+        let gas_used_ratio = if ratios.is_empty() { 0. } else { ratios.iter().sum::<f64>() / ratios.len() as f64 };
+
+        // The median of the median priority fees of the recent blocks, zero if it isn't charged
+        let block_medians =
+            fee_history.reward.unwrap_or_default().into_iter().filter_map(|rewards| rewards.first().copied());
+        let observed_priority_fee = rewards_at_percentiles(block_medians.collect(), &[50.])[0];
+
+        Ok(FeeSuggestions::new(
+            base_fee.saturating_to(),
+            gas_used_ratio,
+            observed_priority_fee,
+            u128::from(*MAX_PRIORITY_FEE_PER_GAS),
+        ))
+    }
+
+    async fn data_availability_cost(&self, request: TransactionRequest) -> EthApiResult<DataAvailabilityCost> {
+        let span = tracing::span!(tracing::Level::INFO, "sn::pending_block");
+        let block = self
+            .starknet_provider_inner()
+            .get_block_with_tx_hashes(StarknetBlockId::Tag(BlockTag::Pending))
+            .instrument(span)
+            .await
+            .map_err(KakarotError::from)?;
+        let (l1_gas_price, l1_data_gas_price, da_mode) = match block {
+            MaybePendingBlockWithTxHashes::Block(block) => {
+                (block.l1_gas_price, block.l1_data_gas_price, block.l1_da_mode)
+            }
+            MaybePendingBlockWithTxHashes::PendingBlock(block) => {
+                (block.l1_gas_price, block.l1_data_gas_price, block.l1_da_mode)
+            }
+        };
+        let mode = match da_mode {
+            L1DataAvailabilityMode::Blob => DataAvailabilityMode::Blob,
+            L1DataAvailabilityMode::Calldata => DataAvailabilityMode::Calldata,
+        };
+
+        let felts = data_availability_felts(request.input.input().map_or(0, |input| input.len()));
+        let price_in_wei = |price: ResourcePrice| -> U256 { into_via_wrapper!(price.price_in_wei) };
+        Ok(DataAvailabilityCost::new(mode, felts, price_in_wei(l1_gas_price), price_in_wei(l1_data_gas_price)))
+    }
+
+    async fn block_gas_limit(&self) -> EthApiResult<u64> {
+        let kakarot_contract = KakarotCoreReader::new(*KAKAROT_ADDRESS, self.starknet_provider_inner());
+        let span = tracing::span!(tracing::Level::INFO, "sn::block_gas_limit");
+        let block_gas_limit = kakarot_contract
+            .get_block_gas_limit()
+            .call()
+            .instrument(span)
+            .await
+            .map_err(ExecutionError::from)?
+            .block_gas_limit;
+        Ok(u64::try_from(block_gas_limit).map_err(|_| EthApiError::from("invalid block gas limit"))?)
+    }
+}
+
+/// Returns the priority fee per gas charged to the transaction under the policy, in a block of
+/// the given base fee.
+pub fn charged_priority_fee(policy: PriorityFeePolicy, transaction: &ExtendedTransaction, base_fee: u128) -> u128 {
+    let max_fee_per_gas = transaction.max_fee_per_gas.or(transaction.gas_price).unwrap_or_default();
+    policy.effective_priority_fee(max_fee_per_gas, transaction.max_priority_fee_per_gas, base_fee)
+}
+
+/// Returns the priority fees at the given percentiles, as returned in the rewards of
+/// `eth_feeHistory`. Unlike Geth, the transactions aren't weighted by their gas used, the
+/// receipts of the blocks not being read. Returns zeros for a block without transactions.
+pub fn rewards_at_percentiles(mut tips: Vec<u128>, percentiles: &[f64]) -> Vec<u128> {
+    if tips.is_empty() {
+        return vec![0; percentiles.len()];
+    }
+    tips.sort_unstable();
+    percentiles
+        .iter()
+        .map(|percentile| {
+            let percentile = if percentile.is_finite() { percentile.clamp(0., 100.) } else { 0. };
+            #[allow(clippy::cast_sign_loss, clippy::cast_possible_truncation)]
+            let index = (percentile / 100. * tips.len() as f64).ceil() as usize;
+            tips[index.saturating_sub(1).min(tips.len() - 1)]
+        })
+        .collect()
+}
+
+/// Returns the number of `CREATE` and `CREATE2` opcodes in the code, skipping the data of the
+/// `PUSH` opcodes. Used to account for the contracts deployed by the constructor of a factory.
+///
+/// The count is an upper bound of the deployments of the constructor: the init code can't be
+/// told apart from the runtime code and the init codes of the children appended to it, whose
+/// `CREATE` bytes are counted as well. The overestimate only raises the gas limit of the
+/// estimate, the unused gas isn't charged.
+pub fn contract_creations(code: &[u8]) -> u64 {
+    const PUSH1: u8 = 0x60;
+    const PUSH32: u8 = 0x7f;
+    const CREATE: u8 = 0xf0;
+    const CREATE2: u8 = 0xf5;
+
+    let mut creations = 0;
+    let mut pc = 0;
+    while let Some(&opcode) = code.get(pc) {
+        match opcode {
+            CREATE | CREATE2 => creations += 1,
+            PUSH1..=PUSH32 => pc += usize::from(opcode - PUSH1 + 1),
+            _ => {}
+        }
+        pc += 1;
+    }
+    creations
+}
+
+/// Returns the gas overhead of the Starknet invoke transaction relaying a transaction with an
+/// input of the given length, growing with the number of felts of its calldata (see
+/// [`starknet_calldata_len`](crate::models::transaction::starknet_calldata_len)).
+pub fn relayer_gas_overhead(input_len: usize) -> u128 {
+    // Upper bound of the length of the RLP encoding of the fields other than the input
+    const ENVELOPE_LEN: usize = 140;
+    // Call of the relayer to the account: calls length, account, selector and calldata length
+    const RELAYER_CALL_LEN: usize = 4;
+    // Outside execution and call array, signed data length, signature length and signature
+    const EXECUTE_FROM_OUTSIDE_LEN: usize = 10 + 1 + 1 + 5;
+
+    let felts = RELAYER_CALL_LEN + EXECUTE_FROM_OUTSIDE_LEN + (input_len + ENVELOPE_LEN).div_ceil(31);
+    RELAYER_INVOKE_GAS_OVERHEAD + felts as u128 * RELAYER_CALLDATA_FELT_GAS
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_rpc_types::Transaction;
+    use alloy_serde::WithOtherFields;
+
+    #[test]
+    fn test_contract_creations() {
+        // Given
+        // PUSH2 0xf0f5, CREATE, PUSH1 0x00, CREATE2, STOP
+        let code = [0x61, 0xf0, 0xf5, 0xf0, 0x60, 0x00, 0xf5, 0x00];
+
+        // When
+        let creations = contract_creations(&code);
+
+        // Then
+        // The CREATE and CREATE2 bytes pushed as data aren't counted
+        assert_eq!(creations, 2);
+        assert_eq!(contract_creations(&[]), 0);
+        // A truncated PUSH at the end of the code
+        assert_eq!(contract_creations(&[0xf0, 0x7f, 0xf0]), 1);
+        // The CREATE of the runtime code appended after the RETURN of the constructor is counted
+        assert_eq!(contract_creations(&[0xf0, 0xf3, 0xfe, 0xf0]), 2);
+    }
+
+    #[test]
+    fn test_relayer_gas_overhead() {
+        // Given
+        // The envelope of a transfer fits in 5 felts
+        let transfer_felts = 4 + 17 + 5;
+
+        // When
+        let transfer = relayer_gas_overhead(0);
+        let call = relayer_gas_overhead(31 * 10);
+
+        // Then
+        assert_eq!(transfer, RELAYER_INVOKE_GAS_OVERHEAD + transfer_felts * RELAYER_CALLDATA_FELT_GAS);
+        assert_eq!(call - transfer, 10 * RELAYER_CALLDATA_FELT_GAS);
+    }
+
+    #[test]
+    fn test_data_availability_cost() {
+        // Given
+        // A token transfer: selector, recipient and amount
+        let felts = data_availability_felts(4 + 32 + 32);
+        let l1_gas_price = U256::from(30_000_000_000u64);
+        let l1_data_gas_price = U256::from(1_000u64);
+
+        // When
+        let blob = DataAvailabilityCost::new(DataAvailabilityMode::Blob, felts, l1_gas_price, l1_data_gas_price);
+        let calldata =
+            DataAvailabilityCost::new(DataAvailabilityMode::Calldata, felts, l1_gas_price, l1_data_gas_price);
+
+        // Then
+        assert_eq!(felts, DA_BASE_FELTS + 3 * DA_FELTS_PER_CALLDATA_WORD);
+        assert_eq!(blob.l1_gas, 0);
+        assert_eq!(blob.l1_data_gas, felts * DA_DATA_GAS_PER_FELT);
+        assert_eq!(blob.cost, U256::from(blob.l1_data_gas) * l1_data_gas_price);
+        assert_eq!(calldata.l1_data_gas, 0);
+        assert_eq!(calldata.cost, U256::from(felts * DA_L1_GAS_PER_FELT) * l1_gas_price);
+        assert_eq!(data_availability_felts(0), DA_BASE_FELTS);
+    }
+
+    #[test]
+    fn test_charged_priority_fee() {
+        // Given
+        let eip1559 = WithOtherFields::new(Transaction {
+            max_fee_per_gas: Some(150),
+            max_priority_fee_per_gas: Some(10),
+            gas_price: Some(150),
+            ..Default::default()
+        });
+        let legacy = WithOtherFields::new(Transaction { gas_price: Some(120), ..Default::default() });
+
+        // When
+        let ignored = charged_priority_fee(PriorityFeePolicy::Ignored, &eip1559, 100);
+        let burned = charged_priority_fee(PriorityFeePolicy::Burned, &eip1559, 100);
+        let legacy = charged_priority_fee(PriorityFeePolicy::Forwarded, &legacy, 100);
+
+        // Then
+        assert_eq!(ignored, 0);
+        assert_eq!(burned, 10);
+        assert_eq!(legacy, 20);
+    }
+
+    #[test]
+    fn test_rewards_at_percentiles() {
+        // Given
+        let tips = vec![30, 10, 20, 40];
+
+        // When
+        let rewards = rewards_at_percentiles(tips, &[0., 25., 50., 90., 100.]);
+
+        // Then
+        assert_eq!(rewards, vec![10, 10, 20, 40, 40]);
+        assert_eq!(rewards_at_percentiles(vec![], &[10., 50.]), vec![0, 0]);
+    }
+
+    #[test]
+    fn test_fee_suggestions_idle_chain() {
+        // Given
+        let base_fee = 1_000_000_000;
+
+        // When
+        let suggestions = FeeSuggestions::new(base_fee, 0., 0, 100_000_000);
+
+        // Then
+        assert_eq!(suggestions.base_fee_per_gas, U256::from(base_fee));
+        assert_eq!(suggestions.slow.max_fee_per_gas, U256::from(1_125_000_000u64));
+        assert_eq!(suggestions.normal.max_fee_per_gas, U256::from(1_265_625_000u64));
+        assert_eq!(suggestions.fast.max_fee_per_gas, U256::from(1_601_806_641u64));
+        for suggestion in [suggestions.slow, suggestions.normal, suggestions.fast] {
+            assert_eq!(suggestion.max_priority_fee_per_gas, U256::ZERO);
+        }
+    }
+
+    #[test]
+    fn test_fee_suggestions_full_blocks() {
+        // Given
+        let base_fee = 1_000_000_000;
+
+        // When
+        let suggestions = FeeSuggestions::new(base_fee, 1., 50_000_000, 100_000_000);
+
+        // Then
+        for suggestion in [suggestions.slow, suggestions.normal, suggestions.fast] {
+            assert_eq!(suggestion.max_priority_fee_per_gas, U256::from(50_000_000u64));
+        }
+        assert_eq!(suggestions.fast.max_fee_per_gas, U256::from(1_651_806_641u64));
+        assert!(suggestions.slow.max_fee_per_gas < suggestions.normal.max_fee_per_gas);
+        assert!(suggestions.normal.max_fee_per_gas < suggestions.fast.max_fee_per_gas);
+    }
+
+    #[test]
+    fn test_fee_suggestions_tips_above_cap() {
+        // Given
+        let base_fee = 1_000_000_000;
+        let max_priority_fee = 2_000_000;
+
+        // When
+        let suggestions = FeeSuggestions::new(base_fee, 1., 50_000_000, max_priority_fee);
+        let uncharged = FeeSuggestions::new(base_fee, 1., 50_000_000, 0);
+
+        // Then
+        // Every tier suggests the capped priority fee, only the headroom of the maximum fee varies
+        for suggestion in [suggestions.slow, suggestions.normal, suggestions.fast] {
+            assert_eq!(suggestion.max_priority_fee_per_gas, U256::from(max_priority_fee));
+        }
+        assert_eq!(suggestions.slow.max_fee_per_gas, U256::from(1_125_000_000u64 + max_priority_fee));
+        assert_eq!(suggestions.normal.max_fee_per_gas, U256::from(1_265_625_000u64 + max_priority_fee));
+        assert_eq!(suggestions.fast.max_fee_per_gas, U256::from(1_601_806_641u64 + max_priority_fee));
+        assert_eq!(uncharged.fast.max_priority_fee_per_gas, U256::ZERO);
+        assert_eq!(uncharged.fast.max_fee_per_gas, U256::from(1_601_806_641u64));
+    }
+
+    #[test]
+    fn test_fee_suggestions_invalid_ratio() {
+        // When
+        let suggestions = FeeSuggestions::new(100, f64::NAN, 0, 0);
+
+        // Then
+        assert!(suggestions.gas_used_ratio.abs() < f64::EPSILON);
+        assert_eq!(suggestions.fast.max_priority_fee_per_gas, U256::ZERO);
+    }
 }
@@ -1,26 +1,118 @@
-use super::{
-    constant::MAX_LOGS,
-    database::{filter::EthDatabaseFilterBuilder, types::log::StoredLog},
-    error::EthApiError,
-};
-use crate::providers::eth_provider::{
-    database::{
-        filter::{self},
-        FindOpts,
-    },
-    provider::{EthApiResult, EthDataProvider},
-    BlockProvider,
-};
-use alloy_rpc_types::{Filter, FilterChanges};
+use super::{provider::EthApiResult, BlockProvider};
+use crate::eth_rpc::openrpc::SchemaOf;
+use alloy_eips::BlockNumberOrTag;
+use alloy_primitives::{Address, B256, U64};
+use alloy_rpc_types::{Filter, FilterChanges, Log};
 use async_trait::async_trait;
 use auto_impl::auto_impl;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "mongo")]
+use {
+    super::{
+        constant::{
+            LOG_QUERY_DEFAULT_LIMIT, LOG_QUERY_MAX_BLOCK_RANGE, LOG_QUERY_MAX_LIMIT, LOG_QUERY_MAX_SET_SIZE, MAX_LOGS,
+        },
+        database::{
+            archive::archive_ranges,
+            ethereum::EthereumBlockStore,
+            filter::{self, EthDatabaseFilterBuilder},
+            types::log::StoredLog,
+            Database, FindOpts,
+        },
+        error::EthApiError,
+        provider::EthDataProvider,
+    },
+    alloy_rpc_types::Topic,
+    mongodb::bson::{doc, Document},
+};
+
+/// A structured query of the indexed logs.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct LogQuery {
+    /// The emitters of the logs. Matches any emitter if empty.
+    #[serde(default)]
+    #[schemars(with = "SchemaOf<Vec<Address>>")]
+    pub addresses: Vec<Address>,
+    /// The topics at each of the (up to 4) positions, any of which must match. `null` or an empty
+    /// list matches any topic.
+    #[serde(default)]
+    #[schemars(with = "SchemaOf<Vec<Option<Vec<B256>>>>")]
+    pub topics: Vec<Option<Vec<B256>>>,
+    /// The first block of the range. Defaults to the earliest block.
+    #[schemars(with = "SchemaOf<Option<BlockNumberOrTag>>")]
+    pub from_block: Option<BlockNumberOrTag>,
+    /// The last block of the range. Defaults to the latest block.
+    #[schemars(with = "SchemaOf<Option<BlockNumberOrTag>>")]
+    pub to_block: Option<BlockNumberOrTag>,
+    /// The cursor returned by the previous page, if any.
+    pub cursor: Option<LogQueryCursor>,
+    /// The maximum number of logs to return.
+    pub limit: Option<u64>,
+}
+
+#[cfg(feature = "mongo")]
+impl LogQuery {
+    /// Returns the topics of the query, or an error if the query matches more topics or
+    /// addresses than allowed.
+    fn checked_topics(&self) -> EthApiResult<[Topic; 4]> {
+        if self.addresses.len() > LOG_QUERY_MAX_SET_SIZE {
+            return Err(EthApiError::InvalidQuery(format!("more than {LOG_QUERY_MAX_SET_SIZE} addresses")));
+        }
+        if self.topics.len() > 4 {
+            return Err(EthApiError::InvalidQuery("more than 4 topic positions".to_string()));
+        }
+
+        let mut topics: [Topic; 4] = Default::default();
+        for (topic, set) in topics.iter_mut().zip(&self.topics) {
+            let set = set.clone().unwrap_or_default();
+            if set.len() > LOG_QUERY_MAX_SET_SIZE {
+                return Err(EthApiError::InvalidQuery(format!(
+                    "more than {LOG_QUERY_MAX_SET_SIZE} topics at a position"
+                )));
+            }
+            *topic = set.into();
+        }
+        Ok(topics)
+    }
+}
+
+/// The position of the last log of a page of a log query.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct LogQueryCursor {
+    /// The block number of the log.
+    #[schemars(with = "SchemaOf<U64>")]
+    pub block_number: U64,
+    /// The index of the log in the block.
+    #[schemars(with = "SchemaOf<U64>")]
+    pub log_index: U64,
+}
+
+/// A page of the logs matching a log query.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct LogQueryResult {
+    /// The logs, ordered by position in the chain.
+    #[schemars(with = "SchemaOf<Vec<Log>>")]
+    pub logs: Vec<Log>,
+    /// The cursor to use to fetch the next page. `None` if this is the last page.
+    pub next_cursor: Option<LogQueryCursor>,
+}
 
 #[async_trait]
 #[auto_impl(Arc, &)]
 pub trait LogProvider: BlockProvider {
     async fn get_logs(&self, filter: Filter) -> EthApiResult<FilterChanges>;
+
+    /// Returns a page of the logs matching the query, ordered by position in the chain. The
+    /// query is bounded in addresses, topics, blocks and logs so that it can't overload the
+    /// database.
+    async fn query_logs(&self, query: LogQuery) -> EthApiResult<LogQueryResult>;
 }
 
+#[cfg(feature = "mongo")]
 #[async_trait]
 impl<SP> LogProvider for EthDataProvider<SP>
 where
@@ -29,47 +121,129 @@ where
     async fn get_logs(&self, filter: Filter) -> EthApiResult<FilterChanges> {
         let block_hash = filter.get_block_hash();
 
-        // Create the database filter.
-        let mut builder = EthDatabaseFilterBuilder::<filter::Log>::default();
-        builder = if block_hash.is_some() {
-            // We filter by block hash on matching the exact block hash.
-            builder.with_block_hash(&block_hash.unwrap())
-        } else {
-            let current_block = self.block_number().await?;
-            let current_block =
-                current_block.try_into().map_err(|_| EthApiError::UnknownBlockNumber(Some(current_block.to())))?;
-
-            let from = filter.get_from_block().unwrap_or_default();
-            let to = filter.get_to_block().unwrap_or(current_block);
-
-            let (from, to) = match (from, to) {
-                (from, to) if from > current_block || to < from => return Ok(FilterChanges::Empty),
-                (from, to) if to > current_block => (from, current_block),
-                other => other,
-            };
-            // We filter by block number using $gte and $lte.
-            builder.with_block_number_range(from, to)
-        };
-
         // TODO: this will work for now but isn't very efficient. Would need to:
         // 1. Create the bloom filter from the topics
         // 2. Query the database for logs within block range with the bloom filter
         // 3. Filter this reduced set of logs by the topics
         // 4. Limit the number of logs returned
 
-        // Convert the topics to a MongoDB filter and add it to the database filter
-        builder = builder.with_topics(&filter.topics);
+        // Create the database filter, with the topics converted to a MongoDB filter and the addresses.
+        let addresses = filter.address.clone().into_iter().collect::<Vec<_>>();
+        let builder = || {
+            EthDatabaseFilterBuilder::<filter::Log>::default().with_topics(&filter.topics).with_addresses(&addresses)
+        };
+
+        if let Some(block_hash) = block_hash {
+            // We filter by block hash on matching the exact block hash. The logs of an archived
+            // block are only found in the archive.
+            let filter = builder().with_block_hash(&block_hash).build();
+            let mut logs = find_logs(self.database(), filter.clone(), 0).await?;
+            if logs.is_empty() {
+                if let Some(header) = self.database().header(block_hash.into()).await? {
+                    if let (Some((archive, _)), _) =
+                        archive_ranges(self.database(), header.number, header.number).await?
+                    {
+                        logs = find_logs(archive, filter, 0).await?;
+                    }
+                }
+            }
+            return Ok(FilterChanges::Logs(logs));
+        }
 
-        // Add the addresses
-        builder = builder.with_addresses(&filter.address.into_iter().collect::<Vec<_>>());
+        let current_block = self.block_number().await?;
+        let current_block =
+            current_block.try_into().map_err(|_| EthApiError::UnknownBlockNumber(Some(current_block.to())))?;
+
+        let from = filter.get_from_block().unwrap_or_default();
+        let to = filter.get_to_block().unwrap_or(current_block);
+
+        let (from, to) = match (from, to) {
+            (from, to) if from > current_block || to < from => return Ok(FilterChanges::Empty),
+            (from, to) if to > current_block => (from, current_block),
+            other => other,
+        };
+
+        // The blocks up to the last archived block are queried in the archive
+        let (archived, hot_range) = archive_ranges(self.database(), from, to).await?;
+
+        let mut logs = Vec::new();
+        if let Some((archive, (from, to))) = archived {
+            // We filter by block number using $gte and $lte.
+            logs = find_logs(archive, builder().with_block_number_range(from, to).build(), 0).await?;
+        }
+        if let Some((from, to)) = hot_range {
+            let filter = builder().with_block_number_range(from, to).build();
+            logs.extend(find_logs(self.database(), filter, logs.len()).await?);
+        }
+
+        Ok(FilterChanges::Logs(logs))
+    }
+
+    async fn query_logs(&self, query: LogQuery) -> EthApiResult<LogQueryResult> {
+        let topics = query.checked_topics()?;
+        let from = self.tag_into_block_number(query.from_block.unwrap_or(BlockNumberOrTag::Earliest)).await?;
+        let to = self.tag_into_block_number(query.to_block.unwrap_or(BlockNumberOrTag::Latest)).await?;
+        if to.saturating_sub(from) >= LOG_QUERY_MAX_BLOCK_RANGE {
+            return Err(EthApiError::InvalidQuery(format!("more than {LOG_QUERY_MAX_BLOCK_RANGE} blocks")));
+        }
+
+        let limit = query.limit.unwrap_or(LOG_QUERY_DEFAULT_LIMIT).clamp(1, LOG_QUERY_MAX_LIMIT);
+        let from = query.cursor.map_or(from, |cursor| from.max(cursor.block_number.to()));
+        if from > to {
+            return Ok(LogQueryResult::default());
+        }
+
+        let build_filter = |from, to| {
+            let builder = EthDatabaseFilterBuilder::<filter::Log>::default()
+                .with_block_number_range(from, to)
+                .with_addresses(&query.addresses)
+                .with_topics(&topics);
+            match query.cursor {
+                Some(cursor) => builder.with_log_position_after(cursor.block_number.to(), cursor.log_index.to()),
+                None => builder,
+            }
+            .build()
+        };
+
+        // Fetch one more log than the limit in order to know if another page follows. The blocks
+        // up to the last archived block are queried in the archive, which precede the hot ones.
+        let (archived, hot_range) = archive_ranges(self.database(), from, to).await?;
+
+        let mut logs = Vec::new();
+        if let Some((archive, (from, to))) = archived {
+            logs = query_page(archive, build_filter(from, to), limit + 1).await?;
+        }
+        if let Some((from, to)) = hot_range.filter(|_| logs.len() as u64 <= limit) {
+            logs.extend(query_page(self.database(), build_filter(from, to), limit + 1 - logs.len() as u64).await?);
+        }
+
+        let has_next_page = logs.len() as u64 > limit;
+        logs.truncate(usize::try_from(limit).unwrap_or(usize::MAX));
+        let next_cursor = logs.last().filter(|_| has_next_page).map(|log| LogQueryCursor {
+            block_number: U64::from(log.block_number.unwrap_or_default()),
+            log_index: U64::from(log.log_index.unwrap_or_default()),
+        });
+
+        Ok(LogQueryResult { logs, next_cursor })
+    }
+}
+
+/// Returns the first logs of the database matching the filter, ordered by position in the chain.
+#[cfg(feature = "mongo")]
+async fn query_page(database: &Database, filter: Document, limit: u64) -> EthApiResult<Vec<Log>> {
+    let opts = FindOpts::default().with_limit(limit).with_sort(doc! {"log.blockNumber": 1, "log.logIndex": 1});
+    Ok(database.get_and_map_to::<_, StoredLog>(filter, Some(opts)).await?)
+}
 
-        Ok(FilterChanges::Logs(
-            self.database()
-                .get_and_map_to::<_, StoredLog>(
-                    builder.build(),
-                    (*MAX_LOGS).map(|limit| FindOpts::default().with_limit(limit)),
-                )
-                .await?,
-        ))
+/// Returns the logs of the database matching the filter, up to the maximum number of logs minus
+/// the number of logs already found.
+#[cfg(feature = "mongo")]
+async fn find_logs(database: &Database, filter: Document, found: usize) -> EthApiResult<Vec<Log>> {
+    let limit = (*MAX_LOGS).map(|limit| limit.saturating_sub(found as u64));
+    if limit == Some(0) {
+        return Ok(Vec::new());
     }
+    Ok(database
+        .get_and_map_to::<_, StoredLog>(filter, limit.map(|limit| FindOpts::default().with_limit(limit)))
+        .await?)
 }
@@ -1,13 +1,20 @@
+#[cfg(feature = "mongo")]
+pub mod account_class;
 pub mod blocks;
 pub mod chain;
 pub mod constant;
 pub mod contracts;
 pub mod database;
 pub mod error;
+pub mod fallback;
 pub mod gas;
 pub mod logs;
+#[cfg(feature = "mongo")]
+pub mod pending;
 pub mod provider;
 pub mod receipts;
+#[cfg(feature = "mongo")]
+pub mod repair;
 pub mod starknet;
 pub mod state;
 pub mod transactions;
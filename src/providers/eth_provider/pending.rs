@@ -0,0 +1,516 @@
+//! Short-lived cache of the receipts of the transactions relayed by the node, built from the
+//! Starknet pending block before the indexer stores them.
+//!
+//! Once a relayed transaction is included in a Starknet block, its receipt is served from this
+//! cache until the indexer catches up, so that a user doesn't see `null` for a transaction
+//! already visible on Starknet. The receipts follow the conversion of the indexer.
+
+use super::{
+    database::types::receipt::{ExtendedTxReceipt, EXECUTION_RESOURCES_FIELD, OUT_OF_RESOURCES_FIELD},
+    error::OUT_OF_RESOURCES_REVERT_REASON,
+    fallback::FallbackBlock,
+    receipts::TransactionExecutionResources,
+    starknet::kakarot_core::TRANSACTION_EXECUTED,
+};
+use crate::constants::PENDING_RECEIPTS_TTL;
+use alloy_consensus::Transaction as _;
+use alloy_primitives::{logs_bloom, Address, Bytes, Log, LogData, B256, U256};
+use alloy_rpc_types::TransactionReceipt;
+use alloy_serde::WithOtherFields;
+use reth_primitives::{TransactionSigned, TxType};
+use starknet::{
+    core::types::{
+        Event, ExecutionResult, Felt, InvokeTransactionContent, TransactionContent,
+        TransactionReceipt as StarknetReceipt, TransactionWithReceipt,
+    },
+    macros::selector,
+};
+use std::{
+    collections::HashMap,
+    sync::{LazyLock, Mutex, MutexGuard, PoisonError},
+    time::{Duration, Instant},
+};
+
+/// The selectors of the events emitted by Kakarot which aren't EVM logs.
+static IGNORED_KEYS: LazyLock<[Felt; 5]> = LazyLock::new(|| {
+    [
+        *TRANSACTION_EXECUTED,
+        selector!("evm_contract_deployed"),
+        selector!("Transfer"),
+        selector!("Approval"),
+        selector!("OwnershipTransferred"),
+    ]
+});
+
+/// A transaction relayed by the node, awaiting its inclusion in a Starknet block.
+#[derive(Debug, Clone)]
+struct RelayedTransaction {
+    transaction: TransactionSigned,
+    sender: Address,
+    relayed_at: Instant,
+}
+
+/// The block in which the pending receipts were found.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) struct PendingBlockInfo {
+    /// The number of the block.
+    pub(super) number: u64,
+    /// The hash of the block, zero if the block is pending.
+    pub(super) hash: B256,
+}
+
+/// The receipts of the relayed transactions included in a Starknet block which isn't indexed
+/// yet, kept for a limited duration.
+#[derive(Debug)]
+pub struct PendingReceipts {
+    relayed: Mutex<HashMap<Felt, RelayedTransaction>>,
+    receipts: Mutex<HashMap<B256, (ExtendedTxReceipt, Instant)>>,
+    ttl: Duration,
+}
+
+impl Default for PendingReceipts {
+    fn default() -> Self {
+        Self::new(*PENDING_RECEIPTS_TTL)
+    }
+}
+
+impl PendingReceipts {
+    /// Creates a cache keeping the relayed transactions and their receipts for `ttl`.
+    pub fn new(ttl: Duration) -> Self {
+        Self { relayed: Mutex::default(), receipts: Mutex::default(), ttl }
+    }
+
+    /// Tracks a transaction relayed by the node under the hash of its Starknet transaction.
+    pub fn insert_relayed(&self, starknet_hash: Felt, transaction: TransactionSigned, sender: Address) {
+        lock(&self.relayed)
+            .insert(starknet_hash, RelayedTransaction { transaction, sender, relayed_at: Instant::now() });
+    }
+
+    /// Returns true if no relayed transaction awaits its inclusion in a block.
+    pub fn is_empty(&self) -> bool {
+        lock(&self.relayed).is_empty()
+    }
+
+    /// Returns the cached receipt of the transaction, if it hasn't expired.
+    pub fn receipt(&self, hash: &B256) -> Option<ExtendedTxReceipt> {
+        lock(&self.receipts)
+            .get(hash)
+            .filter(|(_, cached_at)| cached_at.elapsed() <= self.ttl)
+            .map(|(receipt, _)| receipt.clone())
+    }
+
+    /// Builds the receipts of the relayed transactions included in the block, and returns the
+    /// number of new receipts.
+    pub fn apply_block(&self, block: &FallbackBlock, kakarot_address: Felt) -> usize {
+        let FallbackBlock { info, transactions, positions } = block;
+        let mut relayed = lock(&self.relayed);
+        let mut receipts = lock(&self.receipts);
+        let mut applied = 0;
+
+        // Only the Kakarot transactions are part of the EVM block
+        for (transaction, position) in transactions.iter().zip(positions) {
+            let Some(position) = position else {
+                continue;
+            };
+            let receipt = &transaction.receipt;
+            let Some(relayed) = relayed.remove(receipt.transaction_hash()) else {
+                continue;
+            };
+
+            if out_of_resources(receipt.execution_result()) {
+                tracing::warn!(
+                    target: "pending_receipts",
+                    monotonic_counter.out_of_resources_transactions = 1,
+                    hash = ?relayed.transaction.hash(),
+                    starknet_hash = ?receipt.transaction_hash(),
+                    "relayed transaction ran out of resources"
+                );
+            }
+            let receipt =
+                to_eth_receipt(&relayed.transaction, relayed.sender, receipt, *info, *position, kakarot_address);
+            receipts.insert(relayed.transaction.hash(), (receipt, Instant::now()));
+            applied += 1;
+        }
+
+        applied
+    }
+
+    /// Forgets the relayed transaction and its receipt once the transaction is indexed, the
+    /// indexed receipt being served from then on. Returns true if the transaction was relayed by
+    /// the node and awaited its indexing.
+    pub fn forget(&self, hash: &B256) -> bool {
+        let mut relayed = lock(&self.relayed);
+        let len = relayed.len();
+        relayed.retain(|_, relayed| relayed.transaction.hash() != *hash);
+        let forgotten = relayed.len() < len;
+        drop(relayed);
+        lock(&self.receipts).remove(hash).is_some() || forgotten
+    }
+
+    /// Evicts the relayed transactions and the receipts older than the time to live of the cache.
+    pub fn evict_expired(&self) {
+        lock(&self.relayed).retain(|_, relayed| relayed.relayed_at.elapsed() <= self.ttl);
+        lock(&self.receipts).retain(|_, (_, cached_at)| cached_at.elapsed() <= self.ttl);
+    }
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Returns the calldata of the Starknet transaction of a block if it is an invoke transaction.
+pub(super) fn invoke_content_calldata(transaction: &TransactionContent) -> Option<&[Felt]> {
+    match transaction {
+        TransactionContent::Invoke(InvokeTransactionContent::V1(transaction)) => Some(&transaction.calldata),
+        TransactionContent::Invoke(InvokeTransactionContent::V3(transaction)) => Some(&transaction.calldata),
+        _ => None,
+    }
+}
+
+/// Returns true if the Starknet transaction relays an EVM transaction to Kakarot: as checked by the
+/// indexer, the call of the relayer to the account targets Kakarot.
+pub(super) fn is_kakarot_transaction(transaction: &TransactionContent, kakarot_address: Felt) -> bool {
+    invoke_content_calldata(transaction).and_then(|calldata| calldata.get(9)) == Some(&kakarot_address)
+}
+
+/// The position of a Kakarot transaction in its block, the other Starknet transactions of the
+/// block not being part of the EVM block.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(super) struct TransactionPosition {
+    /// The index of the transaction among the Kakarot transactions of the block.
+    pub(super) index: u64,
+    /// The gas used by the Kakarot transactions before it.
+    pub(super) cumulative_gas_used: u128,
+    /// The number of logs emitted by the Kakarot transactions before it, the index of its first
+    /// log in the block.
+    pub(super) log_index: u64,
+}
+
+/// Returns the position of each transaction of the block, `None` for the transactions which aren't
+/// Kakarot transactions.
+pub(super) fn kakarot_positions(
+    transactions: &[TransactionWithReceipt],
+    kakarot_address: Felt,
+) -> Vec<Option<TransactionPosition>> {
+    let mut next = TransactionPosition::default();
+    transactions
+        .iter()
+        .map(|transaction| {
+            if !is_kakarot_transaction(&transaction.transaction, kakarot_address) {
+                return None;
+            }
+            let position = next;
+            next.index += 1;
+            if let Some((_, gas_used)) = transaction_executed(&transaction.receipt) {
+                next.cumulative_gas_used += gas_used;
+                next.log_index += eth_logs(&transaction.receipt, kakarot_address).count() as u64;
+            }
+            Some(position)
+        })
+        .collect()
+}
+
+/// Returns the events of the Starknet receipt.
+fn events(receipt: &StarknetReceipt) -> &[Event] {
+    match receipt {
+        StarknetReceipt::Invoke(receipt) => &receipt.events,
+        StarknetReceipt::L1Handler(receipt) => &receipt.events,
+        StarknetReceipt::Declare(receipt) => &receipt.events,
+        StarknetReceipt::Deploy(receipt) => &receipt.events,
+        StarknetReceipt::DeployAccount(receipt) => &receipt.events,
+    }
+}
+
+/// Returns the status and the gas used by the EVM transaction, read from the last two felts of
+/// the `transaction_executed` event. Returns `None` if the Starknet transaction reverted, e.g. if
+/// it ran out of resources, or didn't execute an EVM transaction.
+pub(super) fn transaction_executed(receipt: &StarknetReceipt) -> Option<(bool, u128)> {
+    if matches!(receipt.execution_result(), ExecutionResult::Reverted { .. }) {
+        return None;
+    }
+    let event = events(receipt).iter().find(|event| event.keys.first() == Some(&*TRANSACTION_EXECUTED))?;
+    let [.., status, gas_used] = event.data.as_slice() else {
+        return None;
+    };
+    Some((*status != Felt::ZERO, u128::try_from(*gas_used).ok()?))
+}
+
+/// Returns true if the Starknet transaction reverted because it ran out of Cairo resources.
+pub(super) fn out_of_resources(result: &ExecutionResult) -> bool {
+    matches!(result, ExecutionResult::Reverted { reason } if reason.contains(OUT_OF_RESOURCES_REVERT_REASON))
+}
+
+/// Converts the Starknet event into an EVM log. Returns `None` if the event isn't an EVM log
+/// emitted by Kakarot: the first key is the address of the emitter, each topic is split in two
+/// keys of 128 bits and each felt of the data is a byte.
+fn to_eth_log(event: &Event, kakarot_address: Felt) -> Option<Log> {
+    if event.from_address != kakarot_address || event.keys.len() % 2 != 1 || IGNORED_KEYS.contains(&event.keys[0]) {
+        return None;
+    }
+
+    let address = Address::from_slice(&event.keys[0].to_bytes_be()[12..]);
+    let topics = event.keys[1..]
+        .chunks_exact(2)
+        .map(|topic| {
+            let (low, high) = (u128::try_from(topic[0]).ok()?, u128::try_from(topic[1]).ok()?);
+            Some(B256::from((U256::from(high) << 128) | U256::from(low)))
+        })
+        .collect::<Option<Vec<_>>>()?;
+    let data = event.data.iter().map(|byte| u8::try_from(*byte).ok()).collect::<Option<Bytes>>()?;
+
+    Some(Log { address, data: LogData::new_unchecked(topics, data) })
+}
+
+/// Wraps the receipt in the envelope of the transaction type.
+pub(super) fn receipt_envelope(
+    tx_type: TxType,
+    receipt: alloy_rpc_types::ReceiptWithBloom<alloy_rpc_types::Log>,
+) -> alloy_rpc_types::ReceiptEnvelope<alloy_rpc_types::Log> {
+    match tx_type {
+        TxType::Legacy => alloy_rpc_types::ReceiptEnvelope::Legacy(receipt),
+        TxType::Eip2930 => alloy_rpc_types::ReceiptEnvelope::Eip2930(receipt),
+        TxType::Eip1559 => alloy_rpc_types::ReceiptEnvelope::Eip1559(receipt),
+        TxType::Eip4844 => alloy_rpc_types::ReceiptEnvelope::Eip4844(receipt),
+        TxType::Eip7702 => alloy_rpc_types::ReceiptEnvelope::Eip7702(receipt),
+    }
+}
+
+/// Returns the EVM logs of the Starknet receipt.
+fn eth_logs(receipt: &StarknetReceipt, kakarot_address: Felt) -> impl Iterator<Item = Log> + '_ {
+    events(receipt).iter().filter_map(move |event| to_eth_log(event, kakarot_address))
+}
+
+/// Converts the Starknet receipt of a relayed transaction into the receipt of the EVM
+/// transaction, as the indexer does, tagging the transactions which ran out of resources. The
+/// transaction and its logs are indexed by their position among the Kakarot transactions of the
+/// block.
+pub(super) fn to_eth_receipt(
+    transaction: &TransactionSigned,
+    sender: Address,
+    receipt: &StarknetReceipt,
+    block: PendingBlockInfo,
+    position: TransactionPosition,
+    kakarot_address: Felt,
+) -> ExtendedTxReceipt {
+    let transaction_hash = transaction.hash();
+    let transaction_index = position.index;
+    let ran_out_of_resources = out_of_resources(receipt.execution_result());
+    let (status, gas_used, logs) = match transaction_executed(receipt) {
+        Some((status, gas_used)) => (status, gas_used, eth_logs(receipt, kakarot_address).collect()),
+        None => (false, 0, Vec::new()),
+    };
+
+    let logs_bloom = logs_bloom(&logs);
+    let logs = logs
+        .into_iter()
+        .zip(position.log_index..)
+        .map(|(inner, log_index)| alloy_rpc_types::Log {
+            inner,
+            block_hash: Some(block.hash),
+            block_number: Some(block.number),
+            block_timestamp: None,
+            transaction_hash: Some(transaction_hash),
+            transaction_index: Some(transaction_index),
+            log_index: Some(log_index),
+            removed: false,
+        })
+        .collect();
+    let receipt = alloy_rpc_types::ReceiptWithBloom {
+        receipt: alloy_rpc_types::Receipt {
+            status: status.into(),
+            cumulative_gas_used: position.cumulative_gas_used + gas_used,
+            logs,
+        },
+        logs_bloom,
+    };
+
+    let mut eth_receipt = WithOtherFields::new(TransactionReceipt {
+        inner: receipt_envelope(transaction.tx_type(), receipt),
+        transaction_hash,
+        transaction_index: Some(transaction_index),
+        block_hash: Some(block.hash),
+        block_number: Some(block.number),
+        gas_used,
+        // As the indexer, which doesn't know the base fee of the block
+        effective_gas_price: transaction.max_fee_per_gas(),
+        blob_gas_used: None,
+        blob_gas_price: None,
+        from: sender,
+        to: transaction.to(),
+        contract_address: transaction.to().is_none().then(|| sender.create(transaction.nonce())),
+        authorization_list: None,
+    });
+    if ran_out_of_resources {
+        eth_receipt.other.insert(OUT_OF_RESOURCES_FIELD.to_string(), true.into());
+    }
+    if let Ok(resources) = serde_json::to_value(TransactionExecutionResources::from_receipt(receipt)) {
+        eth_receipt.other.insert(EXECUTION_RESOURCES_FIELD.to_string(), resources);
+    }
+    eth_receipt
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_consensus::TxLegacy;
+    use alloy_primitives::{address, b256, bytes, Signature};
+    use reth_primitives::Transaction;
+    use serde_json::json;
+
+    /// Returns a Starknet invoke transaction relaying a call to `to`, which executed an EVM
+    /// transaction using `gas_used` and emitting `logs` logs if set.
+    fn starknet_transaction(hash: u64, to: Felt, executed: Option<(u64, usize)>) -> TransactionWithReceipt {
+        let mut calldata = vec![Felt::ZERO; 10];
+        calldata[9] = to;
+        let mut events = Vec::new();
+        if let Some((gas_used, logs)) = executed {
+            let log = Event { from_address: to, keys: vec![Felt::ONE, Felt::TWO, Felt::ZERO], data: Vec::new() };
+            events.extend(std::iter::repeat(log).take(logs));
+            events.push(Event {
+                from_address: to,
+                keys: vec![*TRANSACTION_EXECUTED],
+                data: vec![Felt::ONE, Felt::from(gas_used)],
+            });
+        }
+
+        serde_json::from_value(json!({
+            "transaction": {
+                "type": "INVOKE",
+                "version": "0x1",
+                "sender_address": Felt::ONE,
+                "calldata": calldata,
+                "max_fee": Felt::ZERO,
+                "signature": [],
+                "nonce": Felt::ZERO,
+            },
+            "receipt": {
+                "type": "INVOKE",
+                "transaction_hash": Felt::from(hash),
+                "actual_fee": { "amount": Felt::ZERO, "unit": "WEI" },
+                "finality_status": "ACCEPTED_ON_L2",
+                "execution_status": "SUCCEEDED",
+                "messages_sent": [],
+                "events": events,
+                "execution_resources": { "steps": 1, "data_availability": { "l1_gas": 0, "l1_data_gas": 0 } },
+            },
+        }))
+        .expect("valid Starknet transaction")
+    }
+
+    #[test]
+    fn test_to_eth_log() {
+        // Given
+        let kakarot_address = Felt::from(0x1234);
+        let emitter = Felt::from_hex("0xdeadbeef00000000000000000000000000000001").unwrap();
+        let topic = b256!("00000000000000000000000000000002000000000000000000000000000000ff");
+        let event = |from_address: Felt, keys: Vec<Felt>| Event {
+            from_address,
+            keys,
+            data: vec![Felt::from(0xca), Felt::from(0xfe)],
+        };
+        let log = event(kakarot_address, vec![emitter, Felt::from(0xff), Felt::TWO]);
+
+        // When
+        let converted = to_eth_log(&log, kakarot_address);
+        let foreign = to_eth_log(&event(Felt::ONE, log.keys.clone()), kakarot_address);
+        let ignored = to_eth_log(&event(kakarot_address, vec![*TRANSACTION_EXECUTED]), kakarot_address);
+        let split_topic = to_eth_log(&event(kakarot_address, vec![emitter, Felt::ONE]), kakarot_address);
+
+        // Then
+        let converted = converted.unwrap();
+        assert_eq!(converted.address, address!("deadbeef00000000000000000000000000000001"));
+        assert_eq!(converted.topics(), &[topic]);
+        assert_eq!(converted.data.data, bytes!("cafe"));
+        assert_eq!(foreign, None);
+        assert_eq!(ignored, None);
+        assert_eq!(split_topic, None);
+    }
+
+    #[test]
+    fn test_out_of_resources() {
+        // Given
+        let reverted = |reason: &str| ExecutionResult::Reverted { reason: reason.to_string() };
+
+        // When
+        let exhausted = reverted("Could not reach the end of the program. RunResources has no remaining steps.");
+        let other_revert = reverted("Error in the called contract");
+
+        // Then
+        assert!(out_of_resources(&exhausted));
+        assert!(!out_of_resources(&other_revert));
+        assert!(!out_of_resources(&ExecutionResult::Succeeded));
+    }
+
+    #[test]
+    fn test_kakarot_positions() {
+        // Given
+        let kakarot_address = Felt::from(0x1234);
+        let transactions = vec![
+            starknet_transaction(1, kakarot_address, Some((21_000, 2))),
+            starknet_transaction(2, Felt::ONE, Some((30_000, 1))),
+            starknet_transaction(3, kakarot_address, None),
+            starknet_transaction(4, kakarot_address, Some((50_000, 0))),
+        ];
+
+        // When
+        let positions = kakarot_positions(&transactions, kakarot_address);
+
+        // Then
+        let position =
+            |index, cumulative_gas_used, log_index| Some(TransactionPosition { index, cumulative_gas_used, log_index });
+        assert_eq!(positions, vec![position(0, 0, 0), None, position(1, 21_000, 2), position(2, 21_000, 2)]);
+    }
+
+    #[test]
+    fn test_apply_block() {
+        // Given
+        let kakarot_address = Felt::from(0x1234);
+        let pending_receipts = PendingReceipts::new(Duration::from_secs(60));
+        let transaction = TransactionSigned::from_transaction_and_signature(
+            Transaction::Legacy(TxLegacy::default()),
+            Signature::test_signature(),
+        );
+        pending_receipts.insert_relayed(Felt::from(3), transaction.clone(), Address::ZERO);
+        let transactions = vec![
+            starknet_transaction(1, kakarot_address, Some((21_000, 2))),
+            starknet_transaction(2, Felt::ONE, Some((30_000, 1))),
+            starknet_transaction(3, kakarot_address, Some((50_000, 2))),
+        ];
+        let block = FallbackBlock::new(PendingBlockInfo { number: 1, hash: B256::ZERO }, transactions, kakarot_address);
+
+        // When
+        let applied = pending_receipts.apply_block(&block, kakarot_address);
+        let applied_again = pending_receipts.apply_block(&block, kakarot_address);
+
+        // Then
+        assert_eq!((applied, applied_again), (1, 0));
+        assert!(pending_receipts.is_empty());
+        let receipt = pending_receipts.receipt(&transaction.hash()).expect("missing receipt");
+        assert_eq!(receipt.transaction_index, Some(1));
+        assert_eq!(receipt.block_number, Some(1));
+        assert_eq!(receipt.gas_used, 50_000);
+        assert_eq!(receipt.inner.cumulative_gas_used(), 71_000);
+        let log_indices = receipt.inner.logs().iter().map(|log| log.log_index).collect::<Vec<_>>();
+        assert_eq!(log_indices, vec![Some(2), Some(3)]);
+    }
+
+    #[test]
+    fn test_forget() {
+        // Given
+        let pending_receipts = PendingReceipts::new(Duration::from_secs(60));
+        let transaction = TransactionSigned::from_transaction_and_signature(
+            Transaction::Legacy(TxLegacy::default()),
+            Signature::test_signature(),
+        );
+        pending_receipts.insert_relayed(Felt::ONE, transaction.clone(), Address::ZERO);
+
+        // When
+        let forgotten = pending_receipts.forget(&transaction.hash());
+        let forgotten_again = pending_receipts.forget(&transaction.hash());
+
+        // Then
+        assert!(forgotten);
+        assert!(!forgotten_again);
+        assert!(pending_receipts.is_empty());
+    }
+}
@@ -1,32 +1,51 @@
-use super::{
-    constant::CALL_REQUEST_GAS_LIMIT,
-    database::{ethereum::EthereumBlockStore, Database},
-    error::{EthApiError, EvmError, ExecutionError, TransactionError},
-    starknet::kakarot_core::{
-        self,
-        core::{CallInput, KakarotCoreReader, Uint256},
-        KAKAROT_ADDRESS,
-    },
+use super::error::EthApiError;
+use crate::providers::eth_provider::{
+    BlockProvider, GasProvider, LogProvider, ReceiptProvider, StateProvider, TransactionProvider,
 };
-use crate::{
-    constants::ETH_CHAIN_ID,
-    into_via_try_wrapper, into_via_wrapper,
-    models::block::{EthBlockId, EthBlockNumberOrTag},
-    providers::{
-        eth_provider::{BlockProvider, GasProvider, LogProvider, ReceiptProvider, StateProvider, TransactionProvider},
-        sn_provider::StarknetProvider,
+use std::sync::Arc;
+#[cfg(feature = "mongo")]
+use {
+    super::{
+        constant::{
+            NodeMode, CALL_REQUEST_GAS_LIMIT, EVM_BLOCK_HASH, FINALIZED_BLOCK_REFRESH_INTERVAL,
+            HISTORY_RETENTION_BLOCKS, NODE_MODE,
+        },
+        database::{ethereum::EthereumBlockStore, Database},
+        error::{EvmError, ExecutionError, KakarotError, TransactionError},
+        fallback::FallbackBlocks,
+        pending::PendingReceipts,
+        starknet::kakarot_core::{
+            self,
+            core::{CallInput, KakarotCoreReader, Uint256},
+            KAKAROT_ADDRESS,
+        },
+        utils::split_u256,
+    },
+    crate::{
+        constants::ETH_CHAIN_ID,
+        into_via_try_wrapper, into_via_wrapper,
+        models::block::{EthBlockId, EthBlockNumberOrTag},
+        providers::sn_provider::StarknetProvider,
+    },
+    alloy_eips::{BlockId, BlockNumberOrTag},
+    alloy_primitives::{TxKind, U256},
+    alloy_rpc_types::{BlockHashOrNumber, TransactionRequest},
+    cainome::cairo_serde::CairoArrayLegacy,
+    eyre::Result,
+    itertools::Itertools,
+    mongodb::bson::doc,
+    num_traits::cast::ToPrimitive,
+    reth_revm::primitives::MAX_INITCODE_SIZE,
+    starknet::{
+        core::types::{BlockStatus, Felt, MaybePendingBlockWithTxHashes, StarknetError},
+        providers::ProviderError,
+    },
+    std::{
+        sync::{Mutex, PoisonError},
+        time::Instant,
     },
+    tracing::{instrument, Instrument},
 };
-use alloy_eips::{BlockId, BlockNumberOrTag};
-use alloy_primitives::{TxKind, U256};
-use alloy_rpc_types::{BlockHashOrNumber, TransactionRequest};
-use cainome::cairo_serde::CairoArrayLegacy;
-use eyre::Result;
-use itertools::Itertools;
-use mongodb::bson::doc;
-use num_traits::cast::ToPrimitive;
-use starknet::core::types::Felt;
-use tracing::{instrument, Instrument};
 #[cfg(feature = "hive")]
 use {
     crate::providers::eth_provider::error::SignatureError,
@@ -43,7 +62,12 @@ use {
 /// with an [`EthApiError`] as the error type.
 pub type EthApiResult<T> = Result<T, EthApiError>;
 
+/// A shareable, type-erased [`EthereumProvider`].
+pub type DynEthereumProvider = Arc<dyn EthereumProvider + Send + Sync>;
+
 /// A trait that defines the interface for an Ethereum Provider.
+///
+/// The trait is object safe and can be used through a [`DynEthereumProvider`].
 pub trait EthereumProvider:
     GasProvider + StateProvider + TransactionProvider + ReceiptProvider + LogProvider + BlockProvider
 {
@@ -57,13 +81,19 @@ impl<T> EthereumProvider for T where
 /// Structure that implements the `EthereumProvider` trait.
 /// Uses access to a database for certain data, while
 /// the rest is fetched from the Starknet Provider.
+#[cfg(feature = "mongo")]
 #[derive(Debug, Clone)]
 pub struct EthDataProvider<SP: starknet::providers::Provider + Send + Sync> {
     database: Database,
     starknet_provider: StarknetProvider<SP>,
+    pending_receipts: Arc<PendingReceipts>,
+    fallback_blocks: Arc<FallbackBlocks>,
+    /// The number of the last block accepted on L1 and the instant at which it was looked up.
+    finalized_block: Arc<Mutex<Option<(Instant, u64)>>>,
     pub chain_id: u64,
 }
 
+#[cfg(feature = "mongo")]
 impl<SP> EthDataProvider<SP>
 where
     SP: starknet::providers::Provider + Send + Sync,
@@ -78,18 +108,36 @@ where
         &self.starknet_provider
     }
 
+    /// Returns the receipts of the relayed transactions built from the Starknet pending block.
+    pub fn pending_receipts(&self) -> &PendingReceipts {
+        &self.pending_receipts
+    }
+
+    /// Returns the Starknet blocks fetched to serve the relayed transactions from Starknet.
+    pub fn fallback_blocks(&self) -> &FallbackBlocks {
+        &self.fallback_blocks
+    }
+
     /// Returns a reference to the underlying SP provider.
     pub fn starknet_provider_inner(&self) -> &SP {
         &self.starknet_provider
     }
 }
 
+#[cfg(feature = "mongo")]
 impl<SP> EthDataProvider<SP>
 where
     SP: starknet::providers::Provider + Send + Sync,
 {
     pub fn new(database: Database, starknet_provider: StarknetProvider<SP>) -> Self {
-        Self { database, starknet_provider, chain_id: *ETH_CHAIN_ID }
+        Self {
+            database,
+            starknet_provider,
+            pending_receipts: Arc::default(),
+            fallback_blocks: Arc::default(),
+            finalized_block: Arc::default(),
+            chain_id: *ETH_CHAIN_ID,
+        }
     }
 
     /// Prepare the call input for an estimate gas or call from a transaction request.
@@ -113,21 +161,31 @@ where
         let from = into_via_wrapper!(request.from.unwrap_or_default());
 
         let data = request.input.into_input().unwrap_or_default();
+        // EIP-3860: the init code of a contract creation is limited in size
+        if to.is_some == Felt::ZERO && data.len() > MAX_INITCODE_SIZE {
+            return Err(TransactionError::MaxInitCodeSizeExceeded(data.len(), MAX_INITCODE_SIZE).into());
+        }
         let calldata: Vec<Felt> = data.into_iter().map_into().collect();
 
         let gas_limit = into_via_try_wrapper!(request.gas.unwrap_or(CALL_REQUEST_GAS_LIMIT))?;
 
         // We cannot unwrap_or_default() here because Kakarot.eth_call will
-        // Reject transactions with gas_price < Kakarot.base_fee
+        // Reject transactions with gas_price < Kakarot.base_fee.
+        // For EIP-1559 requests, the effective gas price is used, like geth does.
         let gas_price = {
-            let gas_price = match request.gas_price {
-                Some(gas_price) => U256::from(gas_price),
-                None => self.gas_price().await?,
+            let gas_price = match (request.gas_price, request.max_fee_per_gas) {
+                (Some(gas_price), _) => U256::from(gas_price),
+                (None, Some(max_fee_per_gas)) => {
+                    let tip = U256::from(request.max_priority_fee_per_gas.unwrap_or_default());
+                    U256::from(max_fee_per_gas).min(self.gas_price().await?.saturating_add(tip))
+                }
+                (None, None) => self.gas_price().await?,
             };
             into_via_try_wrapper!(gas_price)?
         };
 
-        let value = Uint256 { low: into_via_try_wrapper!(request.value.unwrap_or_default())?, high: Felt::ZERO };
+        let [low, high] = split_u256(request.value.unwrap_or_default());
+        let value = Uint256 { low, high };
 
         // TODO: replace this by into_via_wrapper!(request.nonce.unwrap_or_default())
         //  when we can simulate the transaction instead of calling `eth_call`
@@ -227,7 +285,18 @@ where
         &self,
         block_id: Option<BlockId>,
     ) -> EthApiResult<starknet::core::types::BlockId> {
+        self.ensure_state_available(block_id).await?;
+
         match block_id {
+            // Starknet only knows the blocks by their Starknet hash
+            Some(BlockId::Hash(mut hash)) if *EVM_BLOCK_HASH => {
+                hash.block_hash = self
+                    .database
+                    .starknet_block_hash(&hash.block_hash)
+                    .await?
+                    .ok_or(EthApiError::UnknownBlock(hash.block_hash.into()))?;
+                Ok(EthBlockId::new(BlockId::Hash(hash)).try_into()?)
+            }
             Some(BlockId::Hash(hash)) => Ok(EthBlockId::new(BlockId::Hash(hash)).try_into()?),
             Some(BlockId::Number(number_or_tag)) => {
                 // There is a need to separate the BlockNumberOrTag case into three subcases
@@ -249,6 +318,10 @@ where
                             Ok(starknet::core::types::BlockId::Number(number))
                         }
                     }
+                    // The finalized blocks are the blocks accepted on L1, which have no Starknet tag
+                    BlockNumberOrTag::Finalized => {
+                        Ok(starknet::core::types::BlockId::Number(self.finalized_block_number().await?))
+                    }
                     _ => Ok(EthBlockNumberOrTag::from(number_or_tag).into()),
                 }
             }
@@ -256,6 +329,35 @@ where
         }
     }
 
+    /// Ensures the state at the given block is still served by the node.
+    ///
+    /// In [`NodeMode::Full`], the state of the blocks older than [`HISTORY_RETENTION_BLOCKS`]
+    /// is considered pruned and an [`EthApiError::HistoryPruned`] error is returned.
+    async fn ensure_state_available(&self, block_id: Option<BlockId>) -> EthApiResult<()> {
+        if *NODE_MODE == NodeMode::Archive {
+            return Ok(());
+        }
+
+        // Unknown blocks are handled by the conversion to a Starknet block id
+        let Some(number) = self.historical_block_number(block_id).await? else { return Ok(()) };
+
+        let latest = self.block_number().await?.to::<u64>();
+        NODE_MODE.ensure_state_available(number, latest, *HISTORY_RETENTION_BLOCKS)
+    }
+
+    /// Returns the number of the block targeted by the block id, if it targets a specific block
+    /// rather than the head of the chain. Returns None for the tags and the unknown block hashes.
+    pub(crate) async fn historical_block_number(&self, block_id: Option<BlockId>) -> EthApiResult<Option<u64>> {
+        match block_id {
+            Some(BlockId::Number(BlockNumberOrTag::Number(number))) => Ok(Some(number)),
+            Some(BlockId::Number(BlockNumberOrTag::Earliest)) => Ok(Some(0)),
+            Some(BlockId::Hash(hash)) => {
+                Ok(self.database.header(hash.block_hash.into()).await?.map(|header| header.number))
+            }
+            _ => Ok(None),
+        }
+    }
+
     /// Converts the given [`BlockNumberOrTag`] into a block number.
     #[instrument(skip(self))]
     pub(crate) async fn tag_into_block_number(&self, tag: BlockNumberOrTag) -> EthApiResult<u64> {
@@ -264,15 +366,58 @@ where
             BlockNumberOrTag::Earliest => Ok(0),
             // Converts the tag containing a specific block number into a `U64`.
             BlockNumberOrTag::Number(number) => Ok(number),
-            // Returns `self.block_number()` which is the block number of the latest finalized block.
-            BlockNumberOrTag::Latest | BlockNumberOrTag::Finalized | BlockNumberOrTag::Safe => {
-                self.block_number().await.map(|x| x.to())
-            }
+            // Returns `self.block_number()` which is the block number of the latest block accepted on L2.
+            BlockNumberOrTag::Latest | BlockNumberOrTag::Safe => self.block_number().await.map(|x| x.to()),
+            // Returns the block number of the latest block accepted on L1.
+            BlockNumberOrTag::Finalized => self.finalized_block_number().await,
             // Adds 1 to the block number of the latest finalized block.
             BlockNumberOrTag::Pending => Ok(self.block_number().await?.to::<u64>().saturating_add(1)),
         }
     }
 
+    /// Returns the number of the last indexed block accepted on L1, which is the block returned
+    /// for the `finalized` tag. The `safe` tag maps to the last block accepted on L2.
+    ///
+    /// The finality being monotonic, the block is found by a binary search on the status of the
+    /// Starknet blocks between the last known finalized block and the last indexed block. The
+    /// result is cached for [`FINALIZED_BLOCK_REFRESH_INTERVAL`].
+    pub(crate) async fn finalized_block_number(&self) -> EthApiResult<u64> {
+        let cached = *self.finalized_block.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Some((looked_up_at, number)) = cached {
+            if looked_up_at.elapsed() < FINALIZED_BLOCK_REFRESH_INTERVAL {
+                return Ok(number);
+            }
+        }
+
+        let mut low = cached.map_or(0, |(_, number)| number);
+        let mut high = self.block_number().await?.to::<u64>().max(low);
+        while low < high {
+            let mid = low + (high - low).div_ceil(2);
+            if self.is_accepted_on_l1(mid).await? {
+                low = mid;
+            } else {
+                high = mid - 1;
+            }
+        }
+
+        *self.finalized_block.lock().unwrap_or_else(PoisonError::into_inner) = Some((Instant::now(), low));
+        Ok(low)
+    }
+
+    /// Returns true if the Starknet block was accepted on L1.
+    async fn is_accepted_on_l1(&self, number: u64) -> EthApiResult<bool> {
+        match self
+            .starknet_provider_inner()
+            .get_block_with_tx_hashes(starknet::core::types::BlockId::Number(number))
+            .await
+        {
+            Ok(MaybePendingBlockWithTxHashes::Block(block)) => Ok(block.status == BlockStatus::AcceptedOnL1),
+            Ok(MaybePendingBlockWithTxHashes::PendingBlock(_))
+            | Err(ProviderError::StarknetError(StarknetError::BlockNotFound)) => Ok(false),
+            Err(err) => Err(KakarotError::from(err).into()),
+        }
+    }
+
     /// Converts the given [`BlockId`] into a [`BlockHashOrNumber`].
     #[instrument(skip_all, ret)]
     pub(crate) async fn block_id_into_block_number_or_hash(
@@ -297,13 +442,11 @@ where
         use crate::providers::eth_provider::{
             constant::hive::{DEPLOY_WALLET, DEPLOY_WALLET_NONCE},
             error::EthereumDataFormatError,
+            starknet::kakarot_core::DEPLOY_EXTERNALLY_OWNED_ACCOUNT,
         };
         use starknet::{
             accounts::ExecutionV1,
-            core::{
-                types::{BlockTag, Call},
-                utils::get_selector_from_name,
-            },
+            core::types::{BlockTag, Call},
         };
 
         let signer_starknet_address = starknet_address(signer);
@@ -318,7 +461,7 @@ where
             let execution = ExecutionV1::new(
                 vec![Call {
                     to: *KAKAROT_ADDRESS,
-                    selector: get_selector_from_name("deploy_externally_owned_account").unwrap(),
+                    selector: *DEPLOY_EXTERNALLY_OWNED_ACCOUNT,
                     calldata: vec![into_via_wrapper!(signer)],
                 }],
                 &*DEPLOY_WALLET,
@@ -1,17 +1,340 @@
-use super::database::{filter::EthDatabaseFilterBuilder, types::receipt::StoredTransactionReceipt};
-use crate::providers::eth_provider::{
-    database::{
-        ethereum::EthereumBlockStore,
-        filter::{self},
-        types::receipt::ExtendedTxReceipt,
-    },
-    provider::{EthApiResult, EthDataProvider},
-};
-use alloy_eips::{BlockId, BlockNumberOrTag};
-use alloy_primitives::B256;
+use super::{database::types::receipt::ExtendedTxReceipt, provider::EthApiResult};
+use crate::eth_rpc::openrpc::SchemaOf;
+use alloy_eips::{eip2718::Encodable2718, BlockId};
+use alloy_primitives::{Bytes, B256};
 use async_trait::async_trait;
 use auto_impl::auto_impl;
-use mongodb::bson::doc;
+use reth_primitives::{Log, Receipt, ReceiptWithBloom};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use starknet::core::types::{BlockStatus, Event, Felt, TransactionReceipt};
+use std::collections::BTreeMap;
+#[cfg(feature = "mongo")]
+use {
+    super::{
+        constant::PRIORITY_FEE_POLICY,
+        database::{
+            ethereum::{EthereumBlockStore, EthereumTransactionStore},
+            filter::{self, EthDatabaseFilterBuilder},
+            types::{
+                receipt::{is_deposit_receipt, StoredTransactionReceipt},
+                transaction::{ExtendedTransaction, StoredTransaction},
+            },
+        },
+        error::KakarotError,
+        gas::charged_priority_fee,
+        provider::EthDataProvider,
+        BlockProvider,
+    },
+    alloy_eips::BlockNumberOrTag,
+    alloy_trie::{proof::ProofRetainer, HashBuilder, Nibbles},
+    mongodb::bson::doc,
+    starknet::core::types::{BlockId as StarknetBlockId, BlockTag, MaybePendingBlockWithTxHashes},
+    std::collections::{BTreeSet, HashMap},
+};
+
+/// The confirmation status of a mined transaction.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct TransactionConfirmations {
+    /// The hash of the transaction.
+    #[schemars(with = "SchemaOf<B256>")]
+    pub transaction_hash: B256,
+    /// The hash of the block containing the transaction.
+    #[schemars(with = "SchemaOf<B256>")]
+    pub block_hash: B256,
+    /// The number of the block containing the transaction.
+    pub block_number: u64,
+    /// The number of blocks mined on top of the block containing the transaction, plus one.
+    pub confirmations: u64,
+    /// The finality status of the Starknet block containing the transaction.
+    #[schemars(with = "SchemaOf<BlockStatus>")]
+    pub finality_status: BlockStatus,
+}
+
+/// The proof of a receipt against the receipts root of the header of its block.
+///
+/// The receipts trie of a block maps the RLP encoding of the index of each transaction to the
+/// EIP-2718 encoding of its receipt. The proof holds the nodes of the path from the root of the
+/// trie to the receipt, so that the receipt can be verified against a trusted header.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ReceiptProof {
+    /// The hash of the Ethereum transaction.
+    #[schemars(with = "SchemaOf<B256>")]
+    pub transaction_hash: B256,
+    /// The hash of the block containing the transaction.
+    #[schemars(with = "SchemaOf<B256>")]
+    pub block_hash: B256,
+    /// The number of the block containing the transaction.
+    pub block_number: u64,
+    /// The receipts root of the header of the block.
+    #[schemars(with = "SchemaOf<B256>")]
+    pub receipts_root: B256,
+    /// The key of the receipt in the receipts trie.
+    #[schemars(with = "SchemaOf<Bytes>")]
+    pub key: Bytes,
+    /// The EIP-2718 encoding of the receipt.
+    #[schemars(with = "SchemaOf<Bytes>")]
+    pub receipt: Bytes,
+    /// The RLP encoded nodes of the path from the root of the trie to the receipt.
+    #[schemars(with = "SchemaOf<Vec<Bytes>>")]
+    pub proof: Vec<Bytes>,
+    /// The inclusion data of the transaction in the Starknet chain, if the transaction was
+    /// relayed by the node.
+    pub starknet: Option<StarknetInclusion>,
+}
+
+/// The data needed to verify the inclusion of a Kakarot transaction against the Starknet chain.
+///
+/// The Starknet block hash commits to the header fields and the transaction hashes of the block,
+/// so the inclusion of the Starknet transaction (and the Kakarot events it emitted) can be checked
+/// against a trusted Starknet block hash or state root, without trusting the RPC.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct StarknetInclusion {
+    /// The hash of the Starknet transaction which executed the Ethereum transaction.
+    #[schemars(with = "SchemaOf<Felt>")]
+    pub starknet_transaction_hash: Felt,
+    /// The hash of the Starknet block containing the transaction.
+    #[schemars(with = "SchemaOf<Felt>")]
+    pub starknet_block_hash: Felt,
+    /// The number of the Starknet block containing the transaction.
+    pub starknet_block_number: u64,
+    /// The hash of the parent of the Starknet block.
+    #[schemars(with = "SchemaOf<Felt>")]
+    pub parent_hash: Felt,
+    /// The Starknet state commitment after the block.
+    #[schemars(with = "SchemaOf<Felt>")]
+    pub new_root: Felt,
+    /// The timestamp of the Starknet block.
+    pub timestamp: u64,
+    /// The address of the sequencer of the Starknet block.
+    #[schemars(with = "SchemaOf<Felt>")]
+    pub sequencer_address: Felt,
+    /// The Starknet version of the block.
+    pub starknet_version: String,
+    /// The finality status of the Starknet block.
+    #[schemars(with = "SchemaOf<BlockStatus>")]
+    pub finality_status: BlockStatus,
+    /// The index of the Starknet transaction in the block.
+    pub transaction_index: u64,
+    /// The hashes of all the transactions of the Starknet block, from which the transaction
+    /// commitment of the block is computed.
+    #[schemars(with = "SchemaOf<Vec<Felt>>")]
+    pub block_transaction_hashes: Vec<Felt>,
+    /// The Starknet events emitted by the transaction, including the Kakarot EVM logs.
+    #[schemars(with = "SchemaOf<Vec<Event>>")]
+    pub events: Vec<Event>,
+}
+
+/// Returns the EIP-2718 encoding of the receipt, as stored in the receipts trie of its block.
+pub fn encode_receipt(receipt: &ExtendedTxReceipt) -> EthApiResult<Bytes> {
+    // Converts the transaction type to a u8 and then tries to convert it into TxType
+    let tx_type = Into::<u8>::into(receipt.transaction_type()).try_into()?;
+
+    // Tries to convert the cumulative gas used to u64
+    let cumulative_gas_used = receipt.inner.inner.cumulative_gas_used().try_into()?;
+
+    Ok(ReceiptWithBloom {
+        receipt: Receipt {
+            tx_type,
+            success: receipt.inner.status(),
+            cumulative_gas_used,
+            logs: receipt
+                .inner
+                .inner
+                .logs()
+                .iter()
+                .filter_map(|log| Log::new(log.address(), log.topics().to_vec(), log.data().data.clone()))
+                .collect(),
+        },
+        bloom: *receipt.inner.inner.logs_bloom(),
+    }
+    .encoded_2718()
+    .into())
+}
+
+/// Returns the key of a receipt in the receipts trie of its block, the RLP encoding of the index
+/// of its transaction.
+#[cfg(feature = "mongo")]
+fn receipt_trie_key(transaction_index: u64) -> Bytes {
+    alloy_rlp::encode(transaction_index).into()
+}
+
+/// Builds the receipts trie of a block from its encoded receipts, indexed by transaction, and
+/// returns its root along with the proof of the receipt of the given transaction.
+#[cfg(feature = "mongo")]
+fn receipt_trie_proof(receipts: &[(u64, Bytes)], transaction_index: u64) -> (B256, Vec<Bytes>) {
+    let target = Nibbles::unpack(receipt_trie_key(transaction_index));
+
+    // The leaves are added in the order of their keys
+    let mut leaves = receipts
+        .iter()
+        .map(|(index, receipt)| (Nibbles::unpack(receipt_trie_key(*index)), receipt))
+        .collect::<Vec<_>>();
+    leaves.sort_unstable_by(|(a, _), (b, _)| a.cmp(b));
+
+    let mut hash_builder = HashBuilder::default().with_proof_retainer(ProofRetainer::new(vec![target.clone()]));
+    for (key, receipt) in leaves {
+        hash_builder.add_leaf(key, receipt);
+    }
+    let root = hash_builder.root();
+    let proof =
+        hash_builder.take_proof_nodes().matching_nodes_sorted(&target).into_iter().map(|(_, node)| node).collect();
+
+    (root, proof)
+}
+
+/// The Starknet execution resources consumed by the Starknet transaction which executed an
+/// Ethereum transaction, i.e. the Cairo level cost of the transaction.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct TransactionExecutionResources {
+    /// The hash of the Starknet transaction which executed the Ethereum transaction.
+    #[schemars(with = "SchemaOf<Felt>")]
+    pub starknet_transaction_hash: Felt,
+    /// The number of Cairo steps.
+    pub steps: u64,
+    /// The number of unused memory cells.
+    pub memory_holes: u64,
+    /// The number of applications of each builtin used by the transaction, by name.
+    pub builtins: BTreeMap<String, u64>,
+    /// The L1 gas consumed by the data availability of the transaction.
+    pub l1_gas: u64,
+    /// The L1 data gas (blob gas) consumed by the data availability of the transaction.
+    pub l1_data_gas: u64,
+}
+
+impl TransactionExecutionResources {
+    /// Returns the execution resources of the Starknet receipt.
+    pub fn from_receipt(receipt: &TransactionReceipt) -> Self {
+        let resources = match receipt {
+            TransactionReceipt::Invoke(receipt) => &receipt.execution_resources,
+            TransactionReceipt::L1Handler(receipt) => &receipt.execution_resources,
+            TransactionReceipt::Declare(receipt) => &receipt.execution_resources,
+            TransactionReceipt::Deploy(receipt) => &receipt.execution_resources,
+            TransactionReceipt::DeployAccount(receipt) => &receipt.execution_resources,
+        };
+        let computation = &resources.computation_resources;
+        let builtins = [
+            ("range_check", computation.range_check_builtin_applications),
+            ("pedersen", computation.pedersen_builtin_applications),
+            ("poseidon", computation.poseidon_builtin_applications),
+            ("ec_op", computation.ec_op_builtin_applications),
+            ("ecdsa", computation.ecdsa_builtin_applications),
+            ("bitwise", computation.bitwise_builtin_applications),
+            ("keccak", computation.keccak_builtin_applications),
+            ("segment_arena", computation.segment_arena_builtin),
+        ]
+        .into_iter()
+        .filter_map(|(name, applications)| Some((name.to_string(), applications.filter(|count| *count > 0)?)))
+        .collect();
+
+        Self {
+            starknet_transaction_hash: *receipt.transaction_hash(),
+            steps: computation.steps,
+            memory_holes: computation.memory_holes.unwrap_or_default(),
+            builtins,
+            l1_gas: resources.data_resources.data_availability.l1_gas,
+            l1_data_gas: resources.data_resources.data_availability.l1_data_gas,
+        }
+    }
+}
+
+#[cfg(feature = "mongo")]
+impl<SP> EthDataProvider<SP>
+where
+    SP: starknet::providers::Provider + Send + Sync,
+{
+    /// Returns the inclusion data of a transaction relayed by the node in its Starknet block.
+    /// Returns `None` if the transaction wasn't relayed by the node or if its Starknet block is
+    /// pending.
+    async fn starknet_inclusion(&self, hash: B256, block_number: u64) -> EthApiResult<Option<StarknetInclusion>> {
+        let Some(starknet_transaction_hash) = self.database().starknet_transaction_hash(&hash).await? else {
+            return Ok(None);
+        };
+
+        let starknet_provider = self.starknet_provider_inner();
+        let MaybePendingBlockWithTxHashes::Block(block) = starknet_provider
+            .get_block_with_tx_hashes(StarknetBlockId::Number(block_number))
+            .await
+            .map_err(KakarotError::from)?
+        else {
+            return Ok(None);
+        };
+
+        // The Starknet transaction must be part of the block of the Ethereum transaction
+        let Some(transaction_index) = block.transactions.iter().position(|hash| *hash == starknet_transaction_hash)
+        else {
+            return Ok(None);
+        };
+
+        let receipt =
+            starknet_provider.get_transaction_receipt(starknet_transaction_hash).await.map_err(KakarotError::from)?;
+        let events = match receipt.receipt {
+            TransactionReceipt::Invoke(receipt) => receipt.events,
+            TransactionReceipt::L1Handler(receipt) => receipt.events,
+            TransactionReceipt::Declare(receipt) => receipt.events,
+            TransactionReceipt::Deploy(receipt) => receipt.events,
+            TransactionReceipt::DeployAccount(receipt) => receipt.events,
+        };
+
+        Ok(Some(StarknetInclusion {
+            starknet_transaction_hash,
+            starknet_block_hash: block.block_hash,
+            starknet_block_number: block.block_number,
+            parent_hash: block.parent_hash,
+            new_root: block.new_root,
+            timestamp: block.timestamp,
+            sequencer_address: block.sequencer_address,
+            starknet_version: block.starknet_version,
+            finality_status: block.status,
+            transaction_index: transaction_index as u64,
+            block_transaction_hashes: block.transactions,
+            events,
+        }))
+    }
+
+    /// Sets the effective gas price of the receipts to the base fee of their block plus the
+    /// priority fee charged under the [`PRIORITY_FEE_POLICY`]. The receipts are left as indexed
+    /// if the priority fee isn't charged, or if their transaction or block isn't indexed yet.
+    async fn charge_priority_fees(&self, receipts: &mut [ExtendedTxReceipt]) -> EthApiResult<()> {
+        if !PRIORITY_FEE_POLICY.is_charged() || receipts.is_empty() {
+            return Ok(());
+        }
+
+        let hashes = receipts.iter().map(|receipt| receipt.transaction_hash).collect::<Vec<_>>();
+        let filter = EthDatabaseFilterBuilder::<filter::Transaction>::default().with_tx_hashes(&hashes).build();
+        let transactions = self
+            .database()
+            .get::<StoredTransaction>(filter, None)
+            .await?
+            .into_iter()
+            .map(|transaction| (transaction.hash, ExtendedTransaction::from(transaction)))
+            .collect::<HashMap<_, _>>();
+
+        let mut base_fees = HashMap::new();
+        for block_number in receipts.iter().filter_map(|receipt| receipt.block_number).collect::<BTreeSet<_>>() {
+            let header = self.database().header(block_number.into()).await?;
+            if let Some(base_fee) = header.and_then(|header| header.base_fee_per_gas) {
+                base_fees.insert(block_number, u128::from(base_fee));
+            }
+        }
+
+        for receipt in receipts {
+            let Some(transaction) = transactions.get(&receipt.transaction_hash) else {
+                continue;
+            };
+            let Some(base_fee) = receipt.block_number.and_then(|number| base_fees.get(&number)) else {
+                continue;
+            };
+            receipt.inner.effective_gas_price =
+                base_fee.saturating_add(charged_priority_fee(*PRIORITY_FEE_POLICY, transaction, *base_fee));
+        }
+        Ok(())
+    }
+}
 
 #[async_trait]
 #[auto_impl(Arc, &)]
@@ -21,8 +344,22 @@ pub trait ReceiptProvider {
 
     /// Returns the block receipts for a block.
     async fn block_receipts(&self, block_id: Option<BlockId>) -> EthApiResult<Option<Vec<ExtendedTxReceipt>>>;
+
+    /// Returns the confirmation depth and the Starknet finality status of a mined transaction.
+    /// Returns `None` if the transaction is not mined or if its block is no longer part of the chain.
+    async fn transaction_confirmations(&self, hash: B256) -> EthApiResult<Option<TransactionConfirmations>>;
+
+    /// Returns the proof of the receipt of a mined transaction against the receipts root of its
+    /// block, along with the Starknet inclusion data of the transaction if it was relayed by the
+    /// node. Returns `None` if the transaction is not mined.
+    async fn receipt_proof(&self, hash: B256) -> EthApiResult<Option<ReceiptProof>>;
+
+    /// Returns the Starknet execution resources consumed by the Starknet transaction which
+    /// executed a mined transaction. Returns `None` if the transaction is not mined.
+    async fn execution_resources(&self, hash: B256) -> EthApiResult<Option<TransactionExecutionResources>>;
 }
 
+#[cfg(feature = "mongo")]
 #[async_trait]
 impl<SP> ReceiptProvider for EthDataProvider<SP>
 where
@@ -30,30 +367,193 @@ where
 {
     async fn transaction_receipt(&self, hash: B256) -> EthApiResult<Option<ExtendedTxReceipt>> {
         let filter = EthDatabaseFilterBuilder::<filter::Receipt>::default().with_tx_hash(&hash).build();
-        Ok(self.database().get_one::<StoredTransactionReceipt>(filter, None).await?.map(Into::into))
+        let mut receipt = self.database().get_one::<StoredTransactionReceipt>(filter.clone(), None).await?;
+
+        // The receipts out of the hot window are only found in the archive
+        if let (None, Some(archive)) = (&receipt, self.database().archive()) {
+            receipt = archive.get_one::<StoredTransactionReceipt>(filter, None).await?;
+        }
+        let receipt = receipt.map(Into::into);
+
+        // Until the indexer catches up, the receipt of a transaction relayed by the node can be
+        // built from the Starknet pending block, or from its Starknet block
+        let receipt = match receipt.or_else(|| self.pending_receipts().receipt(&hash)) {
+            Some(receipt) => Some(receipt),
+            None => self.starknet_fallback(hash).await?.and_then(|(_, receipt)| receipt),
+        };
+        let Some(mut receipt) = receipt else {
+            return Ok(None);
+        };
+        self.charge_priority_fees(std::slice::from_mut(&mut receipt)).await?;
+        Ok(Some(receipt))
     }
 
     async fn block_receipts(&self, block_id: Option<BlockId>) -> EthApiResult<Option<Vec<ExtendedTxReceipt>>> {
-        match block_id.unwrap_or_else(|| BlockNumberOrTag::Latest.into()) {
-            BlockId::Number(number_or_tag) => {
-                let block_number = self.tag_into_block_number(number_or_tag).await?;
-                if !self.database().block_exists(block_number.into()).await? {
-                    return Ok(None);
-                }
+        let block_hash_or_number = match block_id.unwrap_or_else(|| BlockNumberOrTag::Latest.into()) {
+            BlockId::Number(number_or_tag) => self.tag_into_block_number(number_or_tag).await?.into(),
+            BlockId::Hash(hash) => hash.block_hash.into(),
+        };
+        let Some(mut receipts) = self.database().block_receipts(block_hash_or_number).await? else {
+            return Ok(None);
+        };
+        self.charge_priority_fees(&mut receipts).await?;
+        Ok(Some(receipts))
+    }
 
-                let filter =
-                    EthDatabaseFilterBuilder::<filter::Receipt>::default().with_block_number(block_number).build();
-                let tx: Vec<StoredTransactionReceipt> = self.database().get(filter, None).await?;
-                Ok(Some(tx.into_iter().map(Into::into).collect()))
-            }
-            BlockId::Hash(hash) => {
-                if !self.database().block_exists(hash.block_hash.into()).await? {
+    async fn transaction_confirmations(&self, hash: B256) -> EthApiResult<Option<TransactionConfirmations>> {
+        let Some(receipt) = self.transaction_receipt(hash).await? else {
+            return Ok(None);
+        };
+        let (Some(block_hash), Some(block_number)) = (receipt.block_hash, receipt.block_number) else {
+            return Ok(None);
+        };
+
+        // The block of the transaction must still be the canonical block at this height
+        let header = self.database().header(block_number.into()).await?;
+        if header.map(|header| header.hash) != Some(block_hash) {
+            return Ok(None);
+        }
+
+        let latest = self.block_number().await?.to::<u64>();
+        let confirmations = if block_number > latest { 0 } else { latest - block_number + 1 };
+
+        let finality_status = match self
+            .starknet_provider_inner()
+            .get_block_with_tx_hashes(StarknetBlockId::Number(block_number))
+            .await
+            .map_err(KakarotError::from)?
+        {
+            MaybePendingBlockWithTxHashes::Block(block) => block.status,
+            MaybePendingBlockWithTxHashes::PendingBlock(_) => BlockStatus::Pending,
+        };
+
+        Ok(Some(TransactionConfirmations {
+            transaction_hash: hash,
+            block_hash,
+            block_number,
+            confirmations,
+            finality_status,
+        }))
+    }
+
+    async fn receipt_proof(&self, hash: B256) -> EthApiResult<Option<ReceiptProof>> {
+        let Some(receipt) = self.transaction_receipt(hash).await? else {
+            return Ok(None);
+        };
+        let (Some(block_hash), Some(block_number), Some(transaction_index)) =
+            (receipt.block_hash, receipt.block_number, receipt.transaction_index)
+        else {
+            return Ok(None);
+        };
+
+        // The block of the transaction must still be the canonical block at this height
+        let Some(header) = self.database().header(block_number.into()).await? else {
+            return Ok(None);
+        };
+        if header.hash != block_hash {
+            return Ok(None);
+        }
+
+        let receipts = self.block_receipts(Some(block_hash.into())).await?.unwrap_or_default();
+        // The deposit receipts aren't part of the receipt trie
+        let receipts = receipts
+            .iter()
+            .filter(|receipt| !is_deposit_receipt(receipt))
+            .filter_map(|receipt| Some((receipt.transaction_index?, receipt)))
+            .map(|(index, receipt)| Ok((index, encode_receipt(receipt)?)))
+            .collect::<EthApiResult<Vec<_>>>()?;
+        let Some(encoded_receipt) =
+            receipts.iter().find(|(index, _)| *index == transaction_index).map(|(_, receipt)| receipt.clone())
+        else {
+            return Ok(None);
+        };
+
+        // A proof which doesn't verify against the header is of no use to the client
+        let (receipts_root, proof) = receipt_trie_proof(&receipts, transaction_index);
+        if receipts_root != header.receipts_root {
+            tracing::warn!(
+                block_number,
+                expected = ?header.receipts_root,
+                computed = ?receipts_root,
+                "the indexed receipts don't match the receipts root of the header"
+            );
+            return Ok(None);
+        }
+
+        Ok(Some(ReceiptProof {
+            transaction_hash: hash,
+            block_hash,
+            block_number,
+            receipts_root,
+            key: receipt_trie_key(transaction_index),
+            receipt: encoded_receipt,
+            proof,
+            starknet: self.starknet_inclusion(hash, block_number).await?,
+        }))
+    }
+
+    async fn execution_resources(&self, hash: B256) -> EthApiResult<Option<TransactionExecutionResources>> {
+        let Some(receipt) = self.transaction_receipt(hash).await? else {
+            return Ok(None);
+        };
+
+        // The Starknet transaction is found by hash if it was relayed by the node, by its index in
+        // the Starknet block otherwise, as the index of a Kakarot transaction is the index of its
+        // Starknet transaction
+        let starknet_provider = self.starknet_provider_inner();
+        let starknet_transaction_hash = match self.database().starknet_transaction_hash(&hash).await? {
+            Some(starknet_transaction_hash) => starknet_transaction_hash,
+            None => {
+                let (Some(block_number), Some(index)) = (receipt.block_number, receipt.transaction_index) else {
+                    return Ok(None);
+                };
+                // The pending receipts have a zero block hash
+                let block_id = match receipt.block_hash {
+                    Some(block_hash) if !block_hash.is_zero() => StarknetBlockId::Number(block_number),
+                    _ => StarknetBlockId::Tag(BlockTag::Pending),
+                };
+                let transactions =
+                    match starknet_provider.get_block_with_tx_hashes(block_id).await.map_err(KakarotError::from)? {
+                        MaybePendingBlockWithTxHashes::Block(block) => block.transactions,
+                        MaybePendingBlockWithTxHashes::PendingBlock(block) => block.transactions,
+                    };
+                let Some(starknet_transaction_hash) =
+                    usize::try_from(index).ok().and_then(|index| transactions.get(index)).copied()
+                else {
                     return Ok(None);
-                }
-                let filter =
-                    EthDatabaseFilterBuilder::<filter::Receipt>::default().with_block_hash(&hash.block_hash).build();
-                Ok(Some(self.database().get_and_map_to::<_, StoredTransactionReceipt>(filter, None).await?))
+                };
+                starknet_transaction_hash
             }
+        };
+
+        let receipt =
+            starknet_provider.get_transaction_receipt(starknet_transaction_hash).await.map_err(KakarotError::from)?;
+        Ok(Some(TransactionExecutionResources::from_receipt(&receipt.receipt)))
+    }
+}
+
+#[cfg(all(test, feature = "mongo"))]
+mod tests {
+    use super::*;
+    use alloy_trie::{proof::verify_proof, root::ordered_trie_root_with_encoder};
+
+    #[test]
+    fn test_receipt_trie_proof() {
+        // Given
+        let receipts = (0..200u64).map(|index| (index, Bytes::from(index.to_be_bytes().repeat(8)))).collect::<Vec<_>>();
+        let expected_root =
+            ordered_trie_root_with_encoder(&receipts, |(_, receipt), buf| buf.extend_from_slice(receipt));
+
+        for index in [0, 1, 127, 128, 199] {
+            // When
+            let (root, proof) = receipt_trie_proof(&receipts, index);
+
+            // Then
+            let key = Nibbles::unpack(receipt_trie_key(index));
+            let receipt = receipts[index as usize].1.to_vec();
+            assert_eq!(root, expected_root);
+            assert!(verify_proof(root, key.clone(), Some(receipt), &proof).is_ok());
+            assert!(verify_proof(root, key, Some(vec![0x42; 64]), &proof).is_err());
         }
     }
 }
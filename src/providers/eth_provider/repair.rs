@@ -0,0 +1,160 @@
+//! Repair of the receipts of the reverted transactions indexed with a wrong status or gas used.
+//!
+//! The receipt of an EVM transaction reverted by Kakarot, or whose Starknet transaction reverted,
+//! must have a `0x0` status. The receipts stored for a block range are compared to the outcome of
+//! the Starknet transactions, read from the `transaction_executed` event as the indexer does, and
+//! the receipts which are missing or whose status or gas used differ are rebuilt from Starknet.
+
+use super::{
+    database::{
+        archive::archive_ranges,
+        filter::{self, EthDatabaseFilterBuilder},
+        types::{
+            receipt::{StoredTransactionReceipt, OUT_OF_RESOURCES_FIELD},
+            transaction::{is_deposit, StoredTransaction},
+        },
+        Database,
+    },
+    error::KakarotError,
+    pending::{to_eth_receipt, transaction_executed, PendingBlockInfo},
+    provider::{EthApiResult, EthDataProvider},
+    starknet::kakarot_core::KAKAROT_ADDRESS,
+};
+use crate::models::transaction::rpc_transaction_to_signed;
+use alloy_primitives::B256;
+use serde::{Deserialize, Serialize};
+use starknet::{
+    core::types::{BlockId, MaybePendingBlockWithReceipts},
+    providers::Provider,
+};
+use std::collections::HashMap;
+
+/// The outcome of the repair of the receipts of a block range.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReceiptRepair {
+    /// The number of indexed transactions whose receipt was verified.
+    pub verified: u64,
+    /// The transactions whose receipt was missing.
+    pub missing: Vec<B256>,
+    /// The transactions whose receipt had a wrong status or gas used.
+    pub mismatched: Vec<B256>,
+}
+
+impl<SP> EthDataProvider<SP>
+where
+    SP: Provider + Send + Sync,
+{
+    /// Verifies the receipts of the transactions indexed in the blocks `from..=to` against the
+    /// Starknet chain, and rebuilds the missing or mismatched receipts unless `dry_run` is set.
+    /// The receipts of the blocks moved to the archive are verified and rebuilt in the archive.
+    pub async fn repair_receipts(&self, from: u64, to: u64, dry_run: bool) -> EthApiResult<ReceiptRepair> {
+        let (archived, hot_range) = archive_ranges(self.database(), from, to).await?;
+
+        let mut repair = ReceiptRepair::default();
+        for (receipts_database, (from, to)) in
+            archived.into_iter().chain(hot_range.map(|range| (self.database(), range)))
+        {
+            for number in from..=to {
+                self.repair_block_receipts(receipts_database, number, dry_run, &mut repair).await?;
+            }
+        }
+
+        tracing::info!(
+            target: "repair_receipts",
+            from,
+            to,
+            verified = repair.verified,
+            missing = repair.missing.len(),
+            mismatched = repair.mismatched.len(),
+            dry_run,
+            "verified the receipts"
+        );
+        Ok(repair)
+    }
+
+    /// Verifies and rebuilds the receipts of the block, the receipts being read from and written
+    /// to `receipts_database`.
+    async fn repair_block_receipts(
+        &self,
+        receipts_database: &Database,
+        number: u64,
+        dry_run: bool,
+        repair: &mut ReceiptRepair,
+    ) -> EthApiResult<()> {
+        let filter = EthDatabaseFilterBuilder::<filter::Transaction>::default().with_block_number(number).build();
+        let transactions: Vec<StoredTransaction> = self.database().get(filter, None).await?;
+        if transactions.is_empty() {
+            return Ok(());
+        }
+
+        let filter = EthDatabaseFilterBuilder::<filter::Receipt>::default().with_block_number(number).build();
+        let receipts: HashMap<_, _> = receipts_database
+            .get::<StoredTransactionReceipt>(filter, None)
+            .await?
+            .into_iter()
+            .map(|stored| (stored.receipt.transaction_hash, stored.receipt))
+            .collect();
+
+        let MaybePendingBlockWithReceipts::Block(block) = self
+            .starknet_provider_inner()
+            .get_block_with_receipts(BlockId::Number(number))
+            .await
+            .map_err(KakarotError::from)?
+        else {
+            return Ok(());
+        };
+        let info = PendingBlockInfo { number, hash: B256::from(block.block_hash.to_bytes_be()) };
+
+        // The deposit transactions aren't EVM transactions, their receipt can't be rebuilt
+        for transaction in transactions.into_iter().map(|stored| stored.tx).filter(|tx| !is_deposit(tx)) {
+            // The transactions of the pending blocks have no index
+            let Some((index, position)) =
+                transaction.transaction_index.and_then(|index| Some((index, usize::try_from(index).ok()?)))
+            else {
+                continue;
+            };
+            let Some(starknet_receipt) = block.transactions.get(position).map(|tx| &tx.receipt) else {
+                continue;
+            };
+            repair.verified += 1;
+
+            // The EVM transactions whose Starknet transaction reverted failed and used no gas
+            let (status, gas_used) = transaction_executed(starknet_receipt).unwrap_or((false, 0));
+            let hash = transaction.hash;
+            match receipts.get(&hash) {
+                Some(receipt) if receipt.status() == status && receipt.gas_used == gas_used => continue,
+                Some(_) => repair.mismatched.push(hash),
+                None => repair.missing.push(hash),
+            }
+            tracing::warn!(target: "repair_receipts", ?hash, number, status, gas_used, "receipt to repair");
+            if dry_run {
+                continue;
+            }
+
+            let cumulative_gas_used = block.transactions[..position]
+                .iter()
+                .filter_map(|tx| transaction_executed(&tx.receipt))
+                .map(|(_, gas_used)| gas_used)
+                .sum();
+            let sender = transaction.from;
+            let signed = rpc_transaction_to_signed(transaction)?;
+            if signed.hash() != hash {
+                tracing::warn!(target: "repair_receipts", ?hash, number, "indexed transaction doesn't match its hash");
+                continue;
+            }
+            let mut receipt =
+                to_eth_receipt(&signed, sender, starknet_receipt, info, index, cumulative_gas_used, *KAKAROT_ADDRESS);
+            // The other fields set by the indexer are kept, the out of resources flag is set from Starknet
+            if let Some(stored) = receipts.get(&hash) {
+                receipt.other.extend(stored.other.clone().into_iter().filter(|(key, _)| key != OUT_OF_RESOURCES_FIELD));
+            }
+
+            let filter = EthDatabaseFilterBuilder::<filter::Receipt>::default().with_tx_hash(&hash).build();
+            receipts_database.update_one(StoredTransactionReceipt { receipt }, filter, true).await?;
+            tracing::info!(target: "repair_receipts", monotonic_counter.repaired_receipts = 1, ?hash, number);
+        }
+
+        Ok(())
+    }
+}
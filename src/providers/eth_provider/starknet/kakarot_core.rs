@@ -53,13 +53,21 @@ pub static ETH_SEND_TRANSACTION: LazyLock<Felt> = LazyLock::new(|| selector!("et
 /// Execute from outside selector
 pub static EXECUTE_FROM_OUTSIDE: LazyLock<Felt> = LazyLock::new(|| selector!("execute_from_outside"));
 
+/// Deploy externally owned account selector
+pub static DEPLOY_EXTERNALLY_OWNED_ACCOUNT: LazyLock<Felt> =
+    LazyLock::new(|| selector!("deploy_externally_owned_account"));
+
+/// Transaction executed event selector
+pub static TRANSACTION_EXECUTED: LazyLock<Felt> = LazyLock::new(|| selector!("transaction_executed"));
+
+/// Default maximum number of felts in calldata, used when `MAX_FELTS_IN_CALLDATA` is unset
+pub const DEFAULT_MAX_FELTS_IN_CALLDATA: usize = 22_500;
+
 /// Maximum number of felts in calldata
 pub static MAX_FELTS_IN_CALLDATA: LazyLock<usize> = LazyLock::new(|| {
-    usize::from_str(
-        &std::env::var("MAX_FELTS_IN_CALLDATA")
-            .unwrap_or_else(|_| panic!("Missing environment variable MAX_FELTS_IN_CALLDATA")),
-    )
-    .expect("Failed to parse MAX_FELTS_IN_CALLDATA")
+    std::env::var("MAX_FELTS_IN_CALLDATA").map_or(DEFAULT_MAX_FELTS_IN_CALLDATA, |max_felts| {
+        usize::from_str(&max_felts).expect("Failed to parse MAX_FELTS_IN_CALLDATA")
+    })
 });
 
 pub fn get_white_listed_eip_155_transaction_hashes() -> Vec<B256> {
@@ -67,6 +75,7 @@ pub fn get_white_listed_eip_155_transaction_hashes() -> Vec<B256> {
         .unwrap_or_else(|_| panic!("Missing environment variable WHITE_LISTED_EIP_155_TRANSACTION_HASHES"))
         .replace(' ', "")
         .split(',')
+        .filter(|hash| !hash.is_empty())
         .map(|hash| B256::from_str(hash).unwrap())
         .collect()
 }
@@ -1,13 +1,93 @@
 #![allow(non_snake_case, clippy::derive_partial_eq_without_eq)]
 pub mod kakarot_core;
+#[cfg(feature = "mongo")]
 pub mod relayer;
 
+use alloy_primitives::U256;
 use cainome::rs::abigen_legacy;
 use starknet::core::types::Felt;
-use std::sync::LazyLock;
+use std::{cmp::Ordering, sync::LazyLock};
 
 abigen_legacy!(ERC20, "./.kakarot/artifacts/ERC20.json");
 
 /// Starknet native token address
 pub static STARKNET_NATIVE_TOKEN: LazyLock<Felt> =
     LazyLock::new(|| Felt::from_hex("0x49d36570d4e46f48e99674bd3fcc84644ddd6b96f7c741b1562b82f9e004dc7").unwrap());
+
+/// The native token of the Starknet chain, in which the balances of the accounts are held. Set
+/// with the `NATIVE_TOKEN_SYMBOL` and `NATIVE_TOKEN_DECIMALS` environment variables, defaults to
+/// ETH.
+pub static NATIVE_TOKEN: LazyLock<NativeToken> = LazyLock::new(|| {
+    let symbol = std::env::var("NATIVE_TOKEN_SYMBOL").unwrap_or_else(|_| "ETH".to_string());
+    let decimals = std::env::var("NATIVE_TOKEN_DECIMALS").ok().and_then(|val| val.parse().ok());
+    NativeToken::new(&symbol, decimals).expect("invalid native token")
+});
+
+/// The number of decimals of wei-denominated amounts.
+const WEI_DECIMALS: u8 = 18;
+
+/// The maximum number of decimals of a native token, above which the scaling factor overflows.
+const MAX_NATIVE_TOKEN_DECIMALS: u8 = 77;
+
+/// The symbol and the decimals of the native token.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NativeToken {
+    pub symbol: String,
+    pub decimals: u8,
+}
+
+impl NativeToken {
+    /// Creates the native token. The decimals default to the ones of the known tokens with the
+    /// symbol.
+    pub fn new(symbol: &str, decimals: Option<u8>) -> Result<Self, String> {
+        let decimals = decimals
+            .or_else(|| Self::known_decimals(symbol))
+            .ok_or_else(|| format!("unknown decimals of the native token {symbol}"))?;
+        if decimals > MAX_NATIVE_TOKEN_DECIMALS {
+            return Err(format!("native token decimals {decimals} above {MAX_NATIVE_TOKEN_DECIMALS}"));
+        }
+        Ok(Self { symbol: symbol.to_string(), decimals })
+    }
+
+    /// Returns the decimals of the known native tokens.
+    fn known_decimals(symbol: &str) -> Option<u8> {
+        match symbol.to_ascii_uppercase().as_str() {
+            "ETH" | "STRK" => Some(18),
+            "USDC" | "USDT" => Some(6),
+            _ => None,
+        }
+    }
+
+    /// Converts an amount of native token to wei, truncating the amounts below a wei.
+    pub fn to_wei(&self, amount: U256) -> U256 {
+        let scale = |decimals: u8| U256::from(10).pow(U256::from(decimals));
+        match self.decimals.cmp(&WEI_DECIMALS) {
+            Ordering::Equal => amount,
+            Ordering::Less => amount.saturating_mul(scale(WEI_DECIMALS - self.decimals)),
+            Ordering::Greater => amount / scale(self.decimals - WEI_DECIMALS),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_native_token_to_wei() {
+        // Given
+        let eth = NativeToken::new("ETH", None).unwrap();
+        let usdc = NativeToken::new("usdc", None).unwrap();
+        let custom = NativeToken::new("TKN", Some(20)).unwrap();
+
+        // When
+        let amount = U256::from(1_000_000);
+
+        // Then
+        assert_eq!(eth.to_wei(amount), amount);
+        assert_eq!(usdc.to_wei(amount), U256::from(10).pow(U256::from(18)));
+        assert_eq!(custom.to_wei(amount), U256::from(10_000));
+        assert!(NativeToken::new("TKN", None).is_err());
+        assert!(NativeToken::new("TKN", Some(78)).is_err());
+    }
+}
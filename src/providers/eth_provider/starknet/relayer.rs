@@ -1,18 +1,30 @@
 use crate::{
     constants::STARKNET_CHAIN_ID,
+    into_via_wrapper,
     models::transaction::transaction_data_to_starknet_calldata,
     providers::eth_provider::{
-        database::{ethereum::EthereumTransactionStore, types::transaction::EthStarknetHashes, Database},
+        database::{
+            ethereum::EthereumTransactionStore,
+            relayer::{RelayerNonceStore, SUBMITTED_NONCE_LEASE_GRACE},
+            types::transaction::EthStarknetHashes,
+            Database,
+        },
         error::{SignatureError, TransactionError},
         provider::EthApiResult,
-        starknet::kakarot_core::{starknet_address, EXECUTE_FROM_OUTSIDE},
+        starknet::{
+            kakarot_core::{starknet_address, DEPLOY_EXTERNALLY_OWNED_ACCOUNT, EXECUTE_FROM_OUTSIDE, KAKAROT_ADDRESS},
+            STARKNET_NATIVE_TOKEN,
+        },
     },
 };
+use alloy_primitives::{Address, B256, U256};
+use num_traits::ToPrimitive;
 use reth_primitives::TransactionSigned;
 use starknet::{
     accounts::{Account, ConnectedAccount, ExecutionEncoding, ExecutionV1, SingleOwnerAccount},
-    core::types::{BlockTag, Felt, NonZeroFelt},
-    providers::Provider,
+    core::types::{BlockTag, Call, Felt, NonZeroFelt, StarknetError, TransactionStatus},
+    macros::selector,
+    providers::{Provider, ProviderError},
     signers::{LocalWallet, SigningKey},
 };
 use std::{
@@ -20,6 +32,7 @@ use std::{
     ops::Deref,
     str::FromStr,
     sync::{Arc, LazyLock},
+    time::{SystemTime, UNIX_EPOCH},
 };
 
 /// Signer for all relayers
@@ -41,6 +54,8 @@ pub struct Relayer<SP: Provider + Send + Sync> {
     balance: Felt,
     /// The database used to store the relayer's transaction hashes map (Ethereum -> Starknet)
     database: Option<Arc<Database>>,
+    /// If set, the transactions are prepared and signed but not submitted to Starknet
+    dry_run: bool,
 }
 
 impl<SP> Relayer<SP>
@@ -57,14 +72,26 @@ where
             ExecutionEncoding::New,
         );
 
-        Self { account: relayer, balance, database }
+        Self { account: relayer, balance, database, dry_run: false }
+    }
+
+    /// Sets the dry run mode of the relayer. In dry run mode, the transactions go through the
+    /// whole relaying pipeline but are logged instead of being submitted to Starknet.
+    #[must_use]
+    pub const fn with_dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
     }
 
     /// Relay the provided Ethereum transaction on the Starknet network.
     /// The relayer nonce is directly fetched from the chain to have the most up-to-date value.
     /// This is a way to avoid nonce issues.
     ///
-    /// Returns the corresponding Starknet transaction hash.
+    /// When a database is set, the nonce is leased in the database before the submission, so that
+    /// concurrent submissions or a crash of the node during the submission can't reuse it.
+    ///
+    /// Returns the corresponding Starknet transaction hash. In dry run mode, the hash of the
+    /// signed but unsubmitted Starknet transaction is returned.
     pub async fn relay_transaction(&self, transaction: &TransactionSigned) -> EthApiResult<Felt> {
         // Transform the transaction's data to Starknet calldata
         let relayer_address = self.account.address();
@@ -75,7 +102,50 @@ where
         let eoa_address = starknet_address(eoa_address);
 
         // Construct the call
-        let call = starknet::core::types::Call { to: eoa_address, selector: *EXECUTE_FROM_OUTSIDE, calldata };
+        let call = Call { to: eoa_address, selector: *EXECUTE_FROM_OUTSIDE, calldata };
+        let starknet_hash = self.execute(call, transaction.hash).await?;
+
+        // Store a transaction hash mapping from Ethereum to Starknet in the database
+        if let Some(database) = self.database.as_ref().filter(|_| !self.dry_run) {
+            database.upsert_transaction_hashes(EthStarknetHashes { eth_hash: transaction.hash, starknet_hash }).await?;
+        }
+
+        Ok(starknet_hash)
+    }
+
+    /// Deploys the Starknet account of the EVM address through Kakarot, for accounts which were
+    /// never deployed. The nonce is leased like for the relayed transactions, under a key derived
+    /// from the EVM address.
+    ///
+    /// Returns the Starknet transaction hash of the deployment.
+    pub async fn deploy_account(&self, evm_address: Address) -> EthApiResult<Felt> {
+        let call = Call {
+            to: *KAKAROT_ADDRESS,
+            selector: *DEPLOY_EXTERNALLY_OWNED_ACCOUNT,
+            calldata: vec![into_via_wrapper!(evm_address)],
+        };
+        self.execute(call, B256::left_padding_from(evm_address.as_slice())).await
+    }
+
+    /// Transfers `amount` of the Starknet native token, which is the ETH balance of the EVM
+    /// accounts, to the Starknet account of the EVM address. The nonce is leased like for the
+    /// account deployments.
+    ///
+    /// Returns the Starknet transaction hash of the transfer.
+    pub async fn transfer(&self, evm_address: Address, amount: U256) -> EthApiResult<Felt> {
+        let [low, high] = [amount & U256::from(u128::MAX), amount >> 128].map(|limb| Felt::from(limb.to::<u128>()));
+        let call = Call {
+            to: *STARKNET_NATIVE_TOKEN,
+            selector: selector!("transfer"),
+            calldata: vec![starknet_address(evm_address), low, high],
+        };
+        self.execute(call, B256::left_padding_from(evm_address.as_slice())).await
+    }
+
+    /// Signs and submits the call, leasing the nonce for the Ethereum transaction with the given
+    /// hash when a database is set. Returns the Starknet transaction hash.
+    async fn execute(&self, call: Call, eth_hash: B256) -> EthApiResult<Felt> {
+        let relayer_address = self.account.address();
         let mut execution = ExecutionV1::new(vec![call], &self.account);
 
         // Fetch the relayer nonce from the Starknet provider
@@ -86,34 +156,126 @@ where
             .await
             .unwrap_or_default();
 
-        execution = execution.nonce(relayer_nonce);
+        // Lease the nonce before the submission, in order to persist its use
+        let lease = match &self.database {
+            Some(database) if !self.dry_run => {
+                let chain_nonce = relayer_nonce
+                    .to_u64()
+                    .ok_or_else(|| TransactionError::Broadcast("relayer nonce overflows u64".into()))?;
+                self.release_dropped_leases(database, chain_nonce).await;
+                let nonce = database.lease_nonce(relayer_address, chain_nonce, eth_hash).await?;
+                Some((database, nonce))
+            }
+            _ => None,
+        };
+        let nonce = lease.map_or(relayer_nonce, |(_, nonce)| nonce.into());
+
+        execution = execution.nonce(nonce);
 
         // We set the max fee to the balance of the account / 5. This means that the account could
         // send up to 5 transactions before hitting a feeder gateway error.
         execution = execution.max_fee(self.balance.floor_div(&NonZeroFelt::from_felt_unchecked(5.into())));
 
-        let prepared = execution.prepared().map_err(|_| SignatureError::SigningFailure)?;
-        let res = prepared.send().await.map_err(|err| TransactionError::Broadcast(err.into()))?;
+        let Ok(prepared) = execution.prepared() else {
+            self.release_lease(lease).await;
+            return Err(SignatureError::SigningFailure.into());
+        };
+        if self.dry_run {
+            let starknet_hash = prepared.transaction_hash(false);
+            tracing::info!(
+                target: "relayer",
+                ?starknet_hash,
+                ethereum_hash = ?eth_hash,
+                relayer = ?relayer_address,
+                "dry run: transaction not submitted"
+            );
+            return Ok(starknet_hash);
+        }
 
-        // Store a transaction hash mapping from Ethereum to Starknet in the database
+        let res = match prepared.send().await {
+            Ok(res) => res,
+            Err(err) => {
+                self.release_lease(lease).await;
+                return Err(TransactionError::Broadcast(err.into()).into());
+            }
+        };
 
-        if let Some(database) = &self.database {
-            database
-                .upsert_transaction_hashes(EthStarknetHashes {
-                    eth_hash: transaction.hash,
-                    starknet_hash: res.transaction_hash,
-                })
-                .await?;
+        // Mark the nonce as used
+        if let Some((database, nonce)) = lease {
+            // The transaction was submitted, failing to update the lease only delays its release
+            if let Err(err) = database.submit_nonce_lease(relayer_address, nonce, res.transaction_hash).await {
+                tracing::warn!(target: "relayer", ?err, nonce, "failed to mark nonce lease as submitted");
+            }
         }
 
         Ok(res.transaction_hash)
     }
 
+    /// Releases the submitted leases of the nonces not consumed on chain whose transaction was
+    /// dropped by Starknet after its submission (e.g. rejected by the sequencer), so that their
+    /// nonces are leased again instead of leaving a gap which blocks the following transactions of
+    /// the relayer. The leases younger than [`SUBMITTED_NONCE_LEASE_GRACE`] aren't checked, as
+    /// their transaction might not be known by the Starknet node yet.
+    async fn release_dropped_leases(&self, database: &Database, chain_nonce: u64) {
+        let relayer_address = self.account.address();
+        let leases = match database.submitted_nonce_leases(relayer_address, chain_nonce).await {
+            Ok(leases) => leases,
+            Err(err) => {
+                tracing::warn!(target: "relayer", ?err, "failed to fetch submitted nonce leases");
+                return;
+            }
+        };
+
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+        for lease in leases {
+            if now.saturating_sub(lease.timestamp) < SUBMITTED_NONCE_LEASE_GRACE.as_secs() {
+                continue;
+            }
+            let Some(starknet_hash) = lease.starknet_hash else {
+                continue;
+            };
+            let status = self.account.provider().get_transaction_status(starknet_hash).await;
+            if !is_dropped(&status) {
+                continue;
+            }
+
+            tracing::warn!(
+                target: "relayer",
+                monotonic_counter.dropped_nonce_leases = 1,
+                ?starknet_hash,
+                nonce = lease.nonce,
+                relayer = ?relayer_address,
+                "releasing the nonce of a transaction dropped by Starknet"
+            );
+            if let Err(err) = database.release_nonce_lease(relayer_address, lease.nonce).await {
+                tracing::warn!(target: "relayer", ?err, nonce = lease.nonce, "failed to release nonce lease");
+            }
+        }
+    }
+
+    /// Releases the nonce lease of a transaction which wasn't submitted.
+    async fn release_lease(&self, lease: Option<(&Arc<Database>, u64)>) {
+        if let Some((database, nonce)) = lease {
+            if let Err(err) = database.release_nonce_lease(self.account.address(), nonce).await {
+                tracing::warn!(target: "relayer", ?err, nonce, "failed to release nonce lease");
+            }
+        }
+    }
+
     pub fn address(&self) -> Felt {
         self.account.address()
     }
 }
 
+/// Returns true if the status of a submitted transaction shows that it was dropped by Starknet: it
+/// was rejected, or isn't known by the Starknet node anymore.
+fn is_dropped(status: &Result<TransactionStatus, ProviderError>) -> bool {
+    matches!(
+        status,
+        Ok(TransactionStatus::Rejected) | Err(ProviderError::StarknetError(StarknetError::TransactionHashNotFound))
+    )
+}
+
 impl<SP> Deref for Relayer<SP>
 where
     SP: Provider + Send + Sync,
@@ -124,3 +286,28 @@ where
         &self.account
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use starknet::core::types::ExecutionResult;
+
+    #[test]
+    fn test_is_dropped() {
+        // Given
+        let rejected = Ok(TransactionStatus::Rejected);
+        let not_found = Err(ProviderError::StarknetError(StarknetError::TransactionHashNotFound));
+        let received = Ok(TransactionStatus::Received);
+        let reverted = Ok(TransactionStatus::AcceptedOnL2(ExecutionResult::Reverted { reason: "reverted".into() }));
+        let unavailable = Err(ProviderError::RateLimited);
+
+        // Then
+        assert!(is_dropped(&rejected));
+        assert!(is_dropped(&not_found));
+        assert!(!is_dropped(&received));
+        // A reverted transaction consumes its nonce
+        assert!(!is_dropped(&reverted));
+        // The outcome of the submission is unknown
+        assert!(!is_dropped(&unavailable));
+    }
+}
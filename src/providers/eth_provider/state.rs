@@ -1,34 +1,72 @@
-use super::{
-    database::state::{EthCacheDatabase, EthDatabase},
-    error::{EthApiError, ExecutionError, TransactionError},
-    starknet::kakarot_core::{account_contract::AccountContractReader, starknet_address},
-    utils::{contract_not_found, entrypoint_not_found, split_u256},
-};
-use crate::{
-    into_via_wrapper,
-    providers::eth_provider::{
-        provider::{EthApiResult, EthDataProvider},
-        BlockProvider, ChainProvider,
-    },
-};
+use super::{provider::EthApiResult, BlockProvider, ChainProvider};
 use alloy_eips::BlockId;
 use alloy_primitives::{Address, Bytes, B256, U256};
 use alloy_rpc_types::{
-    serde_helpers::JsonStorageKey,
-    state::{EvmOverrides, StateOverride},
-    BlockOverrides, TransactionRequest,
+    serde_helpers::JsonStorageKey, state::StateOverride, BlockOverrides, Bundle, EthCallResponse, StateContext,
+    TransactionRequest,
 };
 use async_trait::async_trait;
 use auto_impl::auto_impl;
-use mongodb::bson::doc;
-use num_traits::cast::ToPrimitive;
-use reth_evm_ethereum::EthEvmConfig;
-use reth_node_api::ConfigureEvm;
-use reth_revm::db::CacheDB;
-use reth_rpc_eth_types::error::ensure_success;
-use starknet::core::utils::get_storage_var_address;
-use std::sync::Arc;
-use tracing::Instrument;
+use reth_revm::primitives::BlockEnv;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "mongo")]
+use {
+    super::{
+        database::{
+            ethereum::EthereumBalanceStore,
+            state::{EthCacheDatabase, EthDatabase},
+            types::transaction::is_deposit,
+        },
+        error::{EthApiError, ExecutionError, TransactionError},
+        provider::EthDataProvider,
+        starknet::{
+            kakarot_core::{account_contract::AccountContractReader, starknet_address},
+            NATIVE_TOKEN,
+        },
+        utils::{contract_not_found, entrypoint_not_found, split_u256},
+    },
+    crate::{into_via_wrapper, tracing::builder::block_env},
+    alloy_primitives::TxKind,
+    alloy_rpc_types::{state::EvmOverrides, TransactionIndex},
+    mongodb::bson::doc,
+    num_traits::cast::ToPrimitive,
+    reth_evm_ethereum::EthEvmConfig,
+    reth_node_api::{ConfigureEvm, ConfigureEvmEnv},
+    reth_revm::{
+        db::CacheDB,
+        primitives::{Bytecode, CfgEnv, Env, EnvWithHandlerCfg, HandlerCfg, SpecId, TxEnv},
+        Database, DatabaseRef,
+    },
+    reth_rpc_eth_types::error::ensure_success,
+    starknet::core::utils::get_storage_var_address,
+    std::sync::Arc,
+    tracing::Instrument,
+};
+
+/// The type of a Kakarot account.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum AccountType {
+    /// A deployed account without code.
+    Eoa,
+    /// A deployed account with code.
+    Contract,
+    /// An account whose Starknet contract isn't deployed yet. It is deployed by Kakarot on its
+    /// first transaction or on its first transfer.
+    Uninitialized,
+}
+
+impl AccountType {
+    /// Returns the type of a deployed account with the given code.
+    pub fn from_code(code: &Bytes) -> Self {
+        if code.is_empty() {
+            Self::Eoa
+        } else {
+            Self::Contract
+        }
+    }
+}
 
 #[async_trait]
 #[auto_impl(Arc, &)]
@@ -47,6 +85,9 @@ pub trait StateProvider: ChainProvider + BlockProvider {
     /// Returns the code for the address at the given block.
     async fn get_code(&self, address: Address, block_id: Option<BlockId>) -> EthApiResult<Bytes>;
 
+    /// Returns the type of the account at the address, at the given block.
+    async fn account_type(&self, address: Address, block_id: Option<BlockId>) -> EthApiResult<AccountType>;
+
     /// Returns the result of a call.
     async fn call(
         &self,
@@ -55,18 +96,42 @@ pub trait StateProvider: ChainProvider + BlockProvider {
         state_overrides: Option<StateOverride>,
         block_overrides: Option<Box<BlockOverrides>>,
     ) -> EthApiResult<Bytes>;
+
+    /// Executes the bundles of calls sequentially, each call being executed on top of the state
+    /// changes of the previous ones. The calls are executed on top of the state of the block of
+    /// the state context, or after the transaction index of the state context in that block.
+    async fn call_many(
+        &self,
+        bundles: Vec<Bundle>,
+        state_context: Option<StateContext>,
+        state_overrides: Option<StateOverride>,
+    ) -> EthApiResult<Vec<Vec<EthCallResponse>>>;
 }
 
+#[cfg(feature = "mongo")]
 #[async_trait]
 impl<SP> StateProvider for EthDataProvider<SP>
 where
     SP: starknet::providers::Provider + Send + Sync,
 {
     async fn balance(&self, address: Address, block_id: Option<BlockId>) -> EthApiResult<U256> {
+        let starknet_address = starknet_address(address);
+
+        // The state of past blocks can be pruned by the Starknet node, use the native token
+        // balance snapshots for these blocks when available and not stale.
+        if let Some(number) = self.historical_block_number(block_id).await? {
+            if number < self.block_number().await?.to::<u64>() {
+                if let Some(balance) = self.database().native_balance(&starknet_address, number).await? {
+                    return Ok(NATIVE_TOKEN.to_wei(balance));
+                }
+            }
+        }
+
         // Convert the optional Ethereum block ID to a Starknet block ID.
         let starknet_block_id = self.to_starknet_block_id(block_id).await?;
-        // Get the balance of the address at the given block ID.
-        self.starknet_provider().balance_at(starknet_address(address), starknet_block_id).await.map_err(Into::into)
+        // Get the balance of the address at the given block ID, in wei.
+        let balance = self.starknet_provider().balance_at(starknet_address, starknet_block_id).await?;
+        Ok(NATIVE_TOKEN.to_wei(balance))
     }
 
     async fn storage_at(
@@ -102,11 +167,20 @@ where
     async fn get_code(&self, address: Address, block_id: Option<BlockId>) -> EthApiResult<Bytes> {
         let starknet_block_id = self.to_starknet_block_id(block_id).await?;
 
-        let address = starknet_address(address);
-        let account_contract = AccountContractReader::new(address, self.starknet_provider_inner());
+        let account_contract = AccountContractReader::new(starknet_address(address), self.starknet_provider_inner());
         let span = tracing::span!(tracing::Level::INFO, "sn::code");
         let bytecode = account_contract.bytecode().block_id(starknet_block_id).call().instrument(span).await;
 
+        // The bytecode of a legacy account isn't exposed by its class. The code of an account being
+        // immutable, read it with the class the account was upgraded to.
+        let bytecode = if entrypoint_not_found(&bytecode) && self.is_legacy_account(address, block_id).await? {
+            let span = tracing::span!(tracing::Level::INFO, "sn::code");
+            let pending = starknet::core::types::BlockId::Tag(starknet::core::types::BlockTag::Pending);
+            account_contract.bytecode().block_id(pending).call().instrument(span).await
+        } else {
+            bytecode
+        };
+
         if contract_not_found(&bytecode) || entrypoint_not_found(&bytecode) {
             return Ok(Bytes::default());
         }
@@ -116,6 +190,14 @@ where
         Ok(Bytes::from(bytecode.into_iter().filter_map(|x| x.to_u8()).collect::<Vec<_>>()))
     }
 
+    async fn account_type(&self, address: Address, block_id: Option<BlockId>) -> EthApiResult<AccountType> {
+        if self.account_class_hash(address, block_id).await?.is_none() {
+            return Ok(AccountType::Uninitialized);
+        }
+
+        Ok(AccountType::from_code(&self.get_code(address, block_id).await?))
+    }
+
     async fn call(
         &self,
         request: TransactionRequest,
@@ -179,4 +261,274 @@ where
         let output = self.call_inner(request, block_id).await?;
         Ok(Bytes::from(output.0.into_iter().filter_map(|x| x.to_u8()).collect::<Vec<_>>()))
     }
+
+    async fn call_many(
+        &self,
+        bundles: Vec<Bundle>,
+        state_context: Option<StateContext>,
+        state_overrides: Option<StateOverride>,
+    ) -> EthApiResult<Vec<Vec<EthCallResponse>>> {
+        let StateContext { block_number, transaction_index } = state_context.unwrap_or_default();
+        let block_id = block_number.unwrap_or_default();
+        let header = self.header(&block_id).await?.ok_or(match block_id {
+            BlockId::Hash(hash) => EthApiError::UnknownBlock(hash.block_hash.into()),
+            BlockId::Number(number) => EthApiError::UnknownBlock(number.as_number().unwrap_or_default().into()),
+        })?;
+
+        // When a transaction index is provided, the calls are executed on top of the state of the
+        // parent block, after replaying the transactions of the block preceding the index.
+        let (state_block_id, replayed_transactions) = match transaction_index {
+            Some(TransactionIndex::Index(index)) => {
+                let transactions = self.block_transactions(Some(block_id)).await?.unwrap_or_default();
+                (BlockId::from(header.parent_hash), transactions.into_iter().take(index).collect())
+            }
+            _ => (block_id, Vec::new()),
+        };
+
+        let cfg = CfgEnv::default().with_chain_id(self.chain_id().await?.unwrap_or_default().to());
+        let block_env = block_env(&header);
+        let mut db = EthCacheDatabase(CacheDB::new(EthDatabase::new(self, state_block_id)));
+        let evm_config = EthEvmConfig::new(Arc::new(Default::default()));
+
+        for tx in replayed_transactions {
+            // Transactions reverted on Starknet didn't update the state, and the deposit
+            // transactions can't be replayed in the EVM.
+            if tx.other.get("reverted").is_some() || is_deposit(&tx) {
+                continue;
+            }
+            let tx_env = evm_config.tx_env(&tx.clone().try_into()?, tx.from);
+            let env = EnvWithHandlerCfg::new(
+                Env::boxed(cfg.clone(), block_env.clone(), tx_env),
+                HandlerCfg::new(SpecId::CANCUN),
+            );
+            evm_config
+                .evm_with_env(&mut db.0, env)
+                .transact_commit()
+                .map_err(|err| TransactionError::Call(err.into()))?;
+        }
+
+        if let Some(state_overrides) = state_overrides {
+            apply_state_overrides(state_overrides, &mut db.0)?;
+        }
+
+        let mut results = Vec::with_capacity(bundles.len());
+        for Bundle { transactions, block_override } in bundles {
+            let mut bundle_block_env = block_env.clone();
+            if let Some(block_override) = block_override {
+                apply_block_overrides(block_override, &mut bundle_block_env);
+            }
+
+            let mut responses = Vec::with_capacity(transactions.len());
+            for request in transactions {
+                let env = call_env(request, &cfg, &bundle_block_env);
+                let result = evm_config
+                    .evm_with_env(&mut db.0, env)
+                    .transact_commit()
+                    .map_err(|err| TransactionError::Call(err.into()))?;
+
+                responses.push(match ensure_success(result) {
+                    Ok(value) => EthCallResponse { value: Some(value), error: None },
+                    Err(err) => EthCallResponse { value: None, error: Some(err.to_string()) },
+                });
+            }
+            results.push(responses);
+        }
+
+        Ok(results)
+    }
+}
+
+/// Creates the environment of a call request. Like for `eth_call`, the base fee of the block
+/// is set to zero when the request doesn't specify a gas price.
+#[cfg(feature = "mongo")]
+fn call_env(request: TransactionRequest, cfg: &CfgEnv, block_env: &BlockEnv) -> EnvWithHandlerCfg {
+    let mut block_env = block_env.clone();
+    let gas_price = request.gas_price.or(request.max_fee_per_gas);
+    if gas_price.is_none() {
+        block_env.basefee = U256::ZERO;
+    }
+
+    let tx_env = TxEnv {
+        caller: request.from.unwrap_or_default(),
+        gas_limit: request.gas.unwrap_or_else(|| block_env.gas_limit.saturating_to()),
+        gas_price: U256::from(gas_price.unwrap_or_default()),
+        gas_priority_fee: request.max_priority_fee_per_gas.map(U256::from),
+        transact_to: request.to.unwrap_or(TxKind::Create),
+        value: request.value.unwrap_or_default(),
+        data: request.input.into_input().unwrap_or_default(),
+        nonce: request.nonce,
+        chain_id: request.chain_id,
+        access_list: request.access_list.map(|access_list| access_list.0).unwrap_or_default(),
+        ..Default::default()
+    };
+
+    EnvWithHandlerCfg::new(Env::boxed(cfg.clone(), block_env, tx_env), HandlerCfg::new(SpecId::CANCUN))
+}
+
+/// Applies the block overrides to the block environment.
+pub(crate) fn apply_block_overrides(overrides: BlockOverrides, block_env: &mut BlockEnv) {
+    let BlockOverrides { number, difficulty, time, gas_limit, coinbase, random, base_fee, .. } = overrides;
+    if let Some(number) = number {
+        block_env.number = number;
+    }
+    if let Some(difficulty) = difficulty {
+        block_env.difficulty = difficulty;
+    }
+    if let Some(time) = time {
+        block_env.timestamp = U256::from(time);
+    }
+    if let Some(gas_limit) = gas_limit {
+        block_env.gas_limit = U256::from(gas_limit);
+    }
+    if let Some(coinbase) = coinbase {
+        block_env.coinbase = coinbase;
+    }
+    if let Some(random) = random {
+        block_env.prevrandao = Some(random);
+    }
+    if let Some(base_fee) = base_fee {
+        block_env.basefee = base_fee;
+    }
+}
+
+/// Applies the state overrides to the database. The `state` of an account override replaces the
+/// whole storage of the account, while its `stateDiff` only replaces the given slots.
+#[cfg(feature = "mongo")]
+fn apply_state_overrides<DB: DatabaseRef>(overrides: StateOverride, db: &mut CacheDB<DB>) -> Result<(), DB::Error> {
+    for (address, account_override) in overrides {
+        let mut info = db.basic(address)?.unwrap_or_default();
+        if let Some(balance) = account_override.balance {
+            info.balance = balance;
+        }
+        if let Some(nonce) = account_override.nonce {
+            info.nonce = nonce;
+        }
+        if let Some(code) = account_override.code {
+            let code = Bytecode::new_raw(code);
+            info.code_hash = code.hash_slow();
+            info.code = Some(code);
+        }
+        db.insert_account_info(address, info);
+
+        if let Some(state) = account_override.state {
+            let storage = state
+                .into_iter()
+                .map(|(slot, value)| (U256::from_be_bytes(slot.0), U256::from_be_bytes(value.0)))
+                .collect();
+            db.replace_account_storage(address, storage)?;
+        }
+        if let Some(state_diff) = account_override.state_diff {
+            for (slot, value) in state_diff {
+                db.insert_account_storage(address, U256::from_be_bytes(slot.0), U256::from_be_bytes(value.0))?;
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(all(test, feature = "mongo"))]
+mod tests {
+    use super::*;
+    use alloy_primitives::bytes;
+    use alloy_rpc_types::state::AccountOverride;
+    use reth_revm::db::EmptyDB;
+
+    #[test]
+    fn test_account_type() {
+        // When
+        let eoa = AccountType::from_code(&Bytes::new());
+        let contract = AccountType::from_code(&bytes!("6080604052"));
+
+        // Then
+        assert_eq!(eoa, AccountType::Eoa);
+        assert_eq!(contract, AccountType::Contract);
+        assert_eq!(serde_json::to_value(AccountType::Uninitialized).unwrap(), "uninitialized");
+    }
+
+    #[test]
+    fn test_call_env_without_gas_price() {
+        // Given
+        let cfg = CfgEnv::default().with_chain_id(1);
+        let block_env = BlockEnv { basefee: U256::from(10), gas_limit: U256::from(1_000_000), ..Default::default() };
+        let request = TransactionRequest {
+            from: Some(Address::with_last_byte(1)),
+            to: Some(TxKind::Call(Address::with_last_byte(2))),
+            value: Some(U256::from(3)),
+            ..Default::default()
+        };
+
+        // When
+        let env = call_env(request, &cfg, &block_env);
+
+        // Then
+        assert_eq!(env.block.basefee, U256::ZERO);
+        assert_eq!(env.tx.gas_limit, 1_000_000);
+        assert_eq!(env.tx.caller, Address::with_last_byte(1));
+        assert_eq!(env.tx.transact_to, TxKind::Call(Address::with_last_byte(2)));
+        assert_eq!(env.tx.value, U256::from(3));
+    }
+
+    #[test]
+    fn test_call_env_with_gas_price() {
+        // Given
+        let block_env = BlockEnv { basefee: U256::from(10), ..Default::default() };
+        let request = TransactionRequest { gas: Some(21000), max_fee_per_gas: Some(20), ..Default::default() };
+
+        // When
+        let env = call_env(request, &CfgEnv::default(), &block_env);
+
+        // Then
+        assert_eq!(env.block.basefee, U256::from(10));
+        assert_eq!(env.tx.gas_price, U256::from(20));
+        assert_eq!(env.tx.gas_limit, 21000);
+        assert_eq!(env.tx.transact_to, TxKind::Create);
+    }
+
+    #[test]
+    fn test_apply_block_overrides() {
+        // Given
+        let mut block_env = BlockEnv::default();
+        let overrides = BlockOverrides {
+            number: Some(U256::from(10)),
+            time: Some(1000),
+            base_fee: Some(U256::from(7)),
+            ..Default::default()
+        };
+
+        // When
+        apply_block_overrides(overrides, &mut block_env);
+
+        // Then
+        assert_eq!(block_env.number, U256::from(10));
+        assert_eq!(block_env.timestamp, U256::from(1000));
+        assert_eq!(block_env.basefee, U256::from(7));
+    }
+
+    #[test]
+    fn test_apply_state_overrides() {
+        // Given
+        let address = Address::with_last_byte(1);
+        let mut db = CacheDB::new(EmptyDB::default());
+        db.insert_account_storage(address, U256::from(1), U256::from(1)).unwrap();
+        let mut overrides = StateOverride::default();
+        overrides.insert(
+            address,
+            AccountOverride {
+                balance: Some(U256::from(100)),
+                code: Some(Bytes::from_static(&[0x60, 0x00])),
+                state_diff: Some([(B256::with_last_byte(2), B256::with_last_byte(2))].into_iter().collect()),
+                ..Default::default()
+            },
+        );
+
+        // When
+        apply_state_overrides(overrides, &mut db).unwrap();
+
+        // Then
+        let info = db.basic(address).unwrap().unwrap();
+        assert_eq!(info.balance, U256::from(100));
+        assert_eq!(info.code_hash, Bytecode::new_raw(Bytes::from_static(&[0x60, 0x00])).hash_slow());
+        assert_eq!(db.storage(address, U256::from(1)).unwrap(), U256::from(1));
+        assert_eq!(db.storage(address, U256::from(2)).unwrap(), U256::from(2));
+    }
 }
@@ -1,27 +1,81 @@
 use super::{
-    database::{
-        filter::EthDatabaseFilterBuilder,
-        types::transaction::{ExtendedTransaction, StoredTransaction},
-    },
-    error::ExecutionError,
-    starknet::kakarot_core::{account_contract::AccountContractReader, starknet_address},
-    utils::{contract_not_found, entrypoint_not_found},
-};
-use crate::{
-    into_via_wrapper,
-    providers::eth_provider::{
-        database::filter::{self},
-        provider::{EthApiResult, EthDataProvider},
-        ChainProvider,
-    },
+    database::types::transaction::{DroppedTransaction, ExtendedTransaction, PoolEvent},
+    provider::EthApiResult,
+    ChainProvider,
 };
+use crate::eth_rpc::openrpc::SchemaOf;
 use alloy_eips::{BlockId, BlockNumberOrTag};
-use alloy_primitives::{Address, B256, U256};
+use alloy_primitives::{Address, Bytes, B256, U256, U64};
 use alloy_rpc_types::Index;
 use async_trait::async_trait;
 use auto_impl::auto_impl;
-use mongodb::bson::doc;
-use tracing::Instrument;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "mongo")]
+use {
+    super::{
+        constant::{ADDRESS_HISTORY_DEFAULT_PAGE_SIZE, ADDRESS_HISTORY_MAX_PAGE_SIZE},
+        database::{
+            archive::archive_ranges,
+            ethereum::{EthereumBlockStore, EthereumTransactionStore},
+            filter::{self, EthDatabaseFilterBuilder},
+            types::{log::StoredLog, transaction::StoredTransaction},
+            FindOpts,
+        },
+        error::{EthApiError, ExecutionError},
+        provider::EthDataProvider,
+        starknet::kakarot_core::{account_contract::AccountContractReader, starknet_address},
+        utils::{circuit_open, contract_not_found, entrypoint_not_found},
+    },
+    crate::into_via_wrapper,
+    alloy_rpc_types::BlockHashOrNumber,
+    mongodb::bson::doc,
+    std::collections::BTreeMap,
+    tracing::Instrument,
+};
+
+/// A request for the transactions touching an address over a block range.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct AddressHistoryRequest {
+    /// The address for which to return the activity.
+    #[schemars(with = "SchemaOf<Address>")]
+    pub address: Address,
+    /// The first block of the range. Defaults to the earliest block.
+    #[schemars(with = "SchemaOf<Option<BlockNumberOrTag>>")]
+    pub from_block: Option<BlockNumberOrTag>,
+    /// The last block of the range. Defaults to the latest block.
+    #[schemars(with = "SchemaOf<Option<BlockNumberOrTag>>")]
+    pub to_block: Option<BlockNumberOrTag>,
+    /// The cursor returned by the previous page, if any.
+    pub cursor: Option<AddressHistoryCursor>,
+    /// The maximum number of transactions to return.
+    pub page_size: Option<u64>,
+}
+
+/// The position of the last transaction of a page of the address history.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct AddressHistoryCursor {
+    /// The block number of the transaction.
+    #[schemars(with = "SchemaOf<U64>")]
+    pub block_number: U64,
+    /// The index of the transaction in the block.
+    #[schemars(with = "SchemaOf<U64>")]
+    pub transaction_index: U64,
+}
+
+/// A page of the transactions touching an address.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct AddressHistory {
+    /// The transactions sent by, sent to or emitting a log from the address, ordered by position
+    /// in the chain.
+    #[schemars(with = "SchemaOf<Vec<ExtendedTransaction>>")]
+    pub transactions: Vec<ExtendedTransaction>,
+    /// The cursor to use to fetch the next page. `None` if this is the last page.
+    pub next_cursor: Option<AddressHistoryCursor>,
+}
 
 #[async_trait]
 #[auto_impl(Arc, &)]
@@ -45,16 +99,37 @@ pub trait TransactionProvider: ChainProvider {
 
     /// Returns the nonce for the address at the given block.
     async fn transaction_count(&self, address: Address, block_id: Option<BlockId>) -> EthApiResult<U256>;
+
+    /// Returns a page of the transactions touching an address, as sender, recipient or log
+    /// emitter, over a block range.
+    async fn address_history(&self, request: AddressHistoryRequest) -> EthApiResult<AddressHistory>;
+
+    /// Returns the transaction dropped by the node with the given hash, along with the reason
+    /// it was dropped.
+    async fn dropped_transaction(&self, hash: B256) -> EthApiResult<Option<DroppedTransaction>>;
+
+    /// Returns the events of the transaction with the given hash in the pool, in the order they
+    /// happened.
+    async fn transaction_timeline(&self, hash: B256) -> EthApiResult<Vec<PoolEvent>>;
+
+    /// Returns the exact raw bytes of the transaction with the given hash, as submitted to the
+    /// node. Returns `None` if the transaction wasn't submitted to the node.
+    async fn submitted_raw_transaction(&self, hash: B256) -> EthApiResult<Option<Bytes>>;
 }
 
+#[cfg(feature = "mongo")]
 #[async_trait]
 impl<SP> TransactionProvider for EthDataProvider<SP>
 where
     SP: starknet::providers::Provider + Send + Sync,
 {
     async fn transaction_by_hash(&self, hash: B256) -> EthApiResult<Option<ExtendedTransaction>> {
-        let filter = EthDatabaseFilterBuilder::<filter::Transaction>::default().with_tx_hash(&hash).build();
-        Ok(self.database().get_one::<StoredTransaction>(filter, None).await?.map(Into::into))
+        if let Some(transaction) = self.database().transaction(&hash).await? {
+            return Ok(Some(transaction));
+        }
+
+        // Until the indexer catches up, a transaction relayed by the node is served from Starknet
+        Ok(self.starknet_fallback(hash).await?.map(|(transaction, _)| transaction))
     }
 
     async fn transaction_by_block_hash_and_index(
@@ -66,7 +141,8 @@ where
             .with_block_hash(&hash)
             .with_tx_index(&index)
             .build();
-        Ok(self.database().get_one::<StoredTransaction>(filter, None).await?.map(Into::into))
+        let transaction = self.database().get_one::<StoredTransaction>(filter, None).await?;
+        Ok(self.database().with_effective_gas_prices(transaction.into_iter().collect()).await?.pop())
     }
 
     async fn transaction_by_block_number_and_index(
@@ -79,22 +155,183 @@ where
             .with_block_number(block_number)
             .with_tx_index(&index)
             .build();
-        Ok(self.database().get_one::<StoredTransaction>(filter, None).await?.map(Into::into))
+        let transaction = self.database().get_one::<StoredTransaction>(filter, None).await?;
+        Ok(self.database().with_effective_gas_prices(transaction.into_iter().collect()).await?.pop())
     }
 
     async fn transaction_count(&self, address: Address, block_id: Option<BlockId>) -> EthApiResult<U256> {
         let starknet_block_id = self.to_starknet_block_id(block_id).await?;
 
-        let address = starknet_address(address);
-        let account_contract = AccountContractReader::new(address, self.starknet_provider_inner());
+        let account_contract = AccountContractReader::new(starknet_address(address), self.starknet_provider_inner());
         let span = tracing::span!(tracing::Level::INFO, "sn::kkrt_nonce");
         let maybe_nonce = account_contract.get_nonce().block_id(starknet_block_id).call().instrument(span).await;
 
-        if contract_not_found(&maybe_nonce) || entrypoint_not_found(&maybe_nonce) {
+        if contract_not_found(&maybe_nonce) {
             return Ok(U256::ZERO);
         }
+        // The nonce of a legacy account isn't exposed by its class, count its transactions instead.
+        if entrypoint_not_found(&maybe_nonce) {
+            if self.is_legacy_account(address, block_id).await? {
+                return self.stored_transaction_count(address, block_id).await;
+            }
+            return Ok(U256::ZERO);
+        }
+        // Fall back to the transactions stored in the database while the Starknet provider is unavailable.
+        if circuit_open(&maybe_nonce) {
+            return self.stored_transaction_count(address, block_id).await;
+        }
         let nonce = maybe_nonce.map_err(ExecutionError::from)?.nonce;
 
         Ok(into_via_wrapper!(nonce))
     }
+    async fn address_history(&self, request: AddressHistoryRequest) -> EthApiResult<AddressHistory> {
+        let from = self.tag_into_block_number(request.from_block.unwrap_or(BlockNumberOrTag::Earliest)).await?;
+        let to = self.tag_into_block_number(request.to_block.unwrap_or(BlockNumberOrTag::Latest)).await?;
+        let page_size =
+            request.page_size.unwrap_or(ADDRESS_HISTORY_DEFAULT_PAGE_SIZE).clamp(1, ADDRESS_HISTORY_MAX_PAGE_SIZE);
+        let from = request.cursor.map_or(from, |cursor| from.max(cursor.block_number.to()));
+        if from > to {
+            return Ok(AddressHistory::default());
+        }
+
+        let mut transactions_filter = EthDatabaseFilterBuilder::<filter::Transaction>::default()
+            .with_block_number_range(from, to)
+            .with_sender_or_recipient(&request.address);
+        if let Some(cursor) = request.cursor {
+            transactions_filter =
+                transactions_filter.with_position_after(cursor.block_number.to(), cursor.transaction_index.to());
+        }
+        let logs_filter = |from, to| {
+            let builder = EthDatabaseFilterBuilder::<filter::Log>::default()
+                .with_block_number_range(from, to)
+                .with_addresses(&[request.address]);
+            match request.cursor {
+                Some(cursor) => builder.with_position_after(cursor.block_number.to(), cursor.transaction_index.to()),
+                None => builder,
+            }
+            .build()
+        };
+
+        // Fetch one more document than the page size in order to know if another page follows
+        let limit = page_size + 1;
+        let transactions: Vec<StoredTransaction> = self
+            .database()
+            .get(
+                transactions_filter.build(),
+                FindOpts::default().with_limit(limit).with_sort(doc! {"tx.blockNumber": 1, "tx.transactionIndex": 1}),
+            )
+            .await?;
+
+        // The logs of the blocks up to the last archived block are queried in the archive, and
+        // precede the ones of the hot database
+        let (archived, hot_range) = archive_ranges(self.database(), from, to).await?;
+        let mut logs: Vec<StoredLog> = Vec::new();
+        for (database, (from, to)) in archived.into_iter().chain(hot_range.map(|range| (self.database(), range))) {
+            let remaining = limit - logs.len() as u64;
+            if remaining == 0 {
+                break;
+            }
+            let opts = FindOpts::default()
+                .with_limit(remaining)
+                .with_sort(doc! {"log.blockNumber": 1, "log.transactionIndex": 1, "log.logIndex": 1});
+            logs.extend(database.get::<StoredLog>(logs_filter(from, to), opts).await?);
+        }
+
+        // Both results are sorted by position. If one of them was truncated by the limit, it only
+        // covers the positions up to its last document.
+        let transactions_truncated = transactions.len() as u64 == limit;
+        let logs_truncated = logs.len() as u64 == limit;
+        let boundary = [
+            transactions_truncated
+                .then(|| transactions.last().map(|tx| position(tx.block_number, tx.transaction_index))),
+            logs_truncated.then(|| logs.last().map(|log| position(log.block_number, log.transaction_index))),
+        ]
+        .into_iter()
+        .flatten()
+        .flatten()
+        .min();
+
+        // Merge the transactions and the hashes of the transactions which emitted logs
+        let mut positions = BTreeMap::<AddressHistoryCursor, (Option<B256>, Option<ExtendedTransaction>)>::new();
+        for tx in self.database().with_effective_gas_prices(transactions).await? {
+            positions.insert(position(tx.block_number, tx.transaction_index), (Some(tx.hash), Some(tx)));
+        }
+        for log in logs {
+            positions.entry(position(log.block_number, log.transaction_index)).or_insert((log.transaction_hash, None));
+        }
+        if let Some(boundary) = boundary {
+            positions.retain(|position, _| *position <= boundary);
+        }
+
+        let has_next_page = transactions_truncated || logs_truncated || positions.len() as u64 > page_size;
+        let page: Vec<_> = positions.into_iter().take(usize::try_from(page_size).unwrap_or(usize::MAX)).collect();
+        let next_cursor = if has_next_page { page.last().map(|(position, _)| *position) } else { None };
+
+        // Fetch the transactions which only emitted logs from the address
+        let missing: Vec<_> =
+            page.iter().filter(|(_, (_, tx))| tx.is_none()).filter_map(|(_, (hash, _))| *hash).collect();
+        let mut missing_transactions: BTreeMap<_, _> = if missing.is_empty() {
+            BTreeMap::new()
+        } else {
+            let filter = EthDatabaseFilterBuilder::<filter::Transaction>::default().with_tx_hashes(&missing).build();
+            let transactions = self.database().get::<StoredTransaction>(filter, None).await?;
+            self.database().with_effective_gas_prices(transactions).await?.into_iter().map(|tx| (tx.hash, tx)).collect()
+        };
+
+        let transactions = page
+            .into_iter()
+            .filter_map(|(_, (hash, tx))| tx.or_else(|| hash.and_then(|hash| missing_transactions.remove(&hash))))
+            .collect();
+
+        Ok(AddressHistory { transactions, next_cursor })
+    }
+
+    async fn dropped_transaction(&self, hash: B256) -> EthApiResult<Option<DroppedTransaction>> {
+        Ok(self.database().dropped_transaction(&hash).await?)
+    }
+
+    async fn transaction_timeline(&self, hash: B256) -> EthApiResult<Vec<PoolEvent>> {
+        Ok(self.database().pool_events(&hash).await?)
+    }
+
+    async fn submitted_raw_transaction(&self, hash: B256) -> EthApiResult<Option<Bytes>> {
+        Ok(self.database().raw_transaction(&hash).await?)
+    }
+}
+
+#[cfg(feature = "mongo")]
+impl<SP> EthDataProvider<SP>
+where
+    SP: starknet::providers::Provider + Send + Sync,
+{
+    /// Returns the transaction count of the address from the last transaction it sent stored in
+    /// the database, ignoring its pending transactions.
+    async fn stored_transaction_count(&self, address: Address, block_id: Option<BlockId>) -> EthApiResult<U256> {
+        let block_number = match self.block_id_into_block_number_or_hash(block_id.unwrap_or_default()).await? {
+            BlockHashOrNumber::Number(number) => number,
+            BlockHashOrNumber::Hash(hash) => {
+                self.database().header(hash.into()).await?.ok_or(EthApiError::UnknownBlock(hash.into()))?.number
+            }
+        };
+
+        let filter = EthDatabaseFilterBuilder::<filter::Transaction>::default()
+            .with_block_number_range(0, block_number)
+            .with_sender(&address)
+            .build();
+        let last_transaction = self
+            .database()
+            .get_one::<StoredTransaction>(filter, doc! {"tx.blockNumber": -1, "tx.transactionIndex": -1})
+            .await?;
+
+        Ok(last_transaction.map_or(U256::ZERO, |transaction| U256::from(transaction.tx.nonce) + U256::from(1)))
+    }
+}
+
+/// Returns the position in the chain of a transaction.
+#[cfg(feature = "mongo")]
+fn position(block_number: Option<u64>, transaction_index: Option<u64>) -> AddressHistoryCursor {
+    AddressHistoryCursor {
+        block_number: U64::from(block_number.unwrap_or_default()),
+        transaction_index: U64::from(transaction_index.unwrap_or_default()),
+    }
 }
@@ -3,6 +3,7 @@ use crate::providers::eth_provider::provider::EthApiResult;
 use alloy_rpc_types_txpool::TxpoolContent;
 use async_trait::async_trait;
 use auto_impl::auto_impl;
+#[cfg(feature = "mongo")]
 use mongodb::bson::doc;
 
 /// Ethereum provider trait. Used to abstract away the database and the network.
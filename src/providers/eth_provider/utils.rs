@@ -1,3 +1,4 @@
+use crate::providers::sn_provider::circuit_breaker::is_circuit_open;
 use alloy_primitives::{U128, U256};
 use cainome::cairo_serde::Error;
 use starknet::{
@@ -49,6 +50,12 @@ pub(crate) fn class_hash_not_declared<T>(err: &Result<T, Error>) -> bool {
     }
 }
 
+/// Checks if the error was raised by the open circuit breaker of the Starknet provider.
+#[inline]
+pub(crate) fn circuit_open<T>(err: &Result<T, Error>) -> bool {
+    matches!(err, Err(Error::Provider(err)) if is_circuit_open(err))
+}
+
 /// Checks if the error is an entrypoint not found error.
 #[inline]
 pub(crate) fn entrypoint_not_found<T>(err: &Result<T, Error>) -> bool {
@@ -0,0 +1,510 @@
+use async_trait::async_trait;
+use futures::future::join_all;
+use starknet::{
+    core::types::{
+        requests::{
+            CallRequest, GetBlockWithTxHashesRequest, GetClassHashAtRequest, GetNonceRequest, GetStorageAtRequest,
+            GetTransactionReceiptRequest, GetTransactionStatusRequest,
+        },
+        BlockHashAndNumber, BlockId, BroadcastedDeclareTransaction, BroadcastedDeployAccountTransaction,
+        BroadcastedInvokeTransaction, BroadcastedTransaction, ContractClass, DeclareTransactionResult,
+        DeployAccountTransactionResult, EventFilter, EventsPage, FeeEstimate, Felt, FunctionCall,
+        InvokeTransactionResult, MaybePendingBlockWithReceipts, MaybePendingBlockWithTxHashes,
+        MaybePendingBlockWithTxs, MaybePendingStateUpdate, MsgFromL1, SimulatedTransaction, SimulationFlag,
+        SimulationFlagForEstimateFee, SyncStatusType, Transaction, TransactionReceiptWithBlockInfo, TransactionStatus,
+        TransactionTrace, TransactionTraceWithHash,
+    },
+    providers::{Provider, ProviderError, ProviderImplError, ProviderRequestData, ProviderResponseData},
+};
+use std::{any::Any, str::FromStr, sync::LazyLock, time::Duration};
+use tokio::{
+    sync::{mpsc, oneshot},
+    time::Instant,
+};
+
+/// The batching configuration of the Starknet reads, read from the environment.
+pub static STARKNET_BATCH_CONFIG: LazyLock<BatchConfig> = LazyLock::new(BatchConfig::from_env);
+
+/// Configuration of the aggregation of concurrent Starknet reads into JSON-RPC batch requests.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BatchConfig {
+    /// Time during which concurrent requests are collected into the same batch.
+    /// A zero window disables the batching.
+    pub window: Duration,
+    /// Maximum number of requests sent in a single batch.
+    pub max_size: usize,
+}
+
+impl Default for BatchConfig {
+    fn default() -> Self {
+        Self { window: Duration::ZERO, max_size: 50 }
+    }
+}
+
+impl BatchConfig {
+    /// Reads the configuration from the environment, falling back to the default configuration.
+    pub fn from_env() -> Self {
+        fn var<T: FromStr>(key: &str) -> Option<T> {
+            std::env::var(key).ok().and_then(|val| T::from_str(&val).ok())
+        }
+
+        let default = Self::default();
+        Self {
+            window: var("STARKNET_BATCH_WINDOW_MS").map_or(default.window, Duration::from_millis),
+            max_size: var("STARKNET_BATCH_MAX_SIZE").unwrap_or(default.max_size),
+        }
+    }
+
+    /// Returns true if the requests should be batched.
+    pub const fn is_enabled(&self) -> bool {
+        !self.window.is_zero() && self.max_size > 1
+    }
+}
+
+/// The errors raised by the batching of the requests.
+#[derive(Debug, thiserror::Error)]
+pub enum BatchError {
+    /// The aggregator stopped before answering the request.
+    #[error("batch aggregator dropped the request")]
+    Dropped,
+    /// The response of the batch doesn't match the request.
+    #[error("unexpected response in batch")]
+    UnexpectedResponse,
+}
+
+impl ProviderImplError for BatchError {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl From<BatchError> for ProviderError {
+    fn from(err: BatchError) -> Self {
+        Self::Other(Box::new(err))
+    }
+}
+
+/// A request waiting to be sent in the next batch.
+#[derive(Debug)]
+struct PendingRequest {
+    request: ProviderRequestData,
+    response: oneshot::Sender<Result<ProviderResponseData, ProviderError>>,
+}
+
+/// A Starknet provider which aggregates the concurrent reads issued within a small time window
+/// into a single JSON-RPC batch request, cutting the round trips to the Starknet node.
+///
+/// Only the reads (calls, storage, nonces, class hashes, blocks with transaction hashes,
+/// receipts and transaction statuses) are batched, the other requests are forwarded as is.
+/// If a batch fails, e.g. because the endpoint doesn't support batch requests, its requests
+/// are sent individually.
+#[derive(Debug, Clone)]
+pub struct BatchingProvider<SP> {
+    /// The underlying Starknet provider.
+    provider: SP,
+    /// The sender to the batch aggregator, none if the batching is disabled.
+    sender: Option<mpsc::UnboundedSender<PendingRequest>>,
+}
+
+impl<SP> BatchingProvider<SP>
+where
+    SP: Provider + Clone + Send + Sync + 'static,
+{
+    /// Creates a new [`BatchingProvider`], spawning the batch aggregator if the batching is enabled.
+    pub fn new(provider: SP, config: BatchConfig) -> Self {
+        if !config.is_enabled() {
+            return Self { provider, sender: None };
+        }
+
+        let (sender, receiver) = mpsc::unbounded_channel();
+        tokio::spawn(aggregate(provider.clone(), receiver, config));
+        Self { provider, sender: Some(sender) }
+    }
+
+    /// Returns the underlying Starknet provider.
+    pub const fn inner(&self) -> &SP {
+        &self.provider
+    }
+
+    /// Sends the request in the next batch, or directly if the batching is disabled.
+    async fn request(&self, request: ProviderRequestData) -> Result<ProviderResponseData, ProviderError> {
+        let Some(sender) = &self.sender else {
+            return send_request(&self.provider, request).await;
+        };
+
+        let (response, receiver) = oneshot::channel();
+        if let Err(mpsc::error::SendError(pending)) = sender.send(PendingRequest { request, response }) {
+            return send_request(&self.provider, pending.request).await;
+        }
+        receiver.await.unwrap_or_else(|_| Err(BatchError::Dropped.into()))
+    }
+}
+
+/// Collects the pending requests into batches of at most `max_size` requests, sending a batch
+/// once its window has elapsed.
+async fn aggregate<SP>(provider: SP, mut receiver: mpsc::UnboundedReceiver<PendingRequest>, config: BatchConfig)
+where
+    SP: Provider + Clone + Send + Sync + 'static,
+{
+    while let Some(first) = receiver.recv().await {
+        let mut batch = vec![first];
+        let deadline = Instant::now() + config.window;
+        while batch.len() < config.max_size {
+            match tokio::time::timeout_at(deadline, receiver.recv()).await {
+                Ok(Some(pending)) => batch.push(pending),
+                _ => break,
+            }
+        }
+        tokio::spawn(send_batch(provider.clone(), batch));
+    }
+}
+
+/// Sends the batch and dispatches the responses to the callers. Falls back to individual
+/// requests if the batch request fails.
+async fn send_batch<SP: Provider + Send + Sync>(provider: SP, batch: Vec<PendingRequest>) {
+    tracing::debug!(target: "starknet_batch", size = batch.len(), "sending batch");
+
+    if batch.len() > 1 {
+        let requests = batch.iter().map(|pending| pending.request.clone()).collect::<Vec<_>>();
+        match provider.batch_requests(&requests).await {
+            Ok(responses) if responses.len() == batch.len() => {
+                for (pending, response) in batch.into_iter().zip(responses) {
+                    let _ = pending.response.send(Ok(response));
+                }
+                return;
+            }
+            Ok(responses) => tracing::warn!(
+                target: "starknet_batch",
+                expected = batch.len(),
+                received = responses.len(),
+                "unexpected number of responses in batch, sending the requests individually"
+            ),
+            Err(err) => {
+                tracing::warn!(target: "starknet_batch", %err, "batch request failed, sending the requests individually");
+            }
+        }
+    }
+
+    join_all(batch.into_iter().map(|pending| async {
+        let response = send_request(&provider, pending.request).await;
+        let _ = pending.response.send(response);
+    }))
+    .await;
+}
+
+/// Sends a single request to the provider.
+async fn send_request<SP: Provider + Send + Sync>(
+    provider: &SP,
+    request: ProviderRequestData,
+) -> Result<ProviderResponseData, ProviderError> {
+    Ok(match request {
+        ProviderRequestData::Call(request) => {
+            ProviderResponseData::Call(provider.call(request.request, request.block_id).await?)
+        }
+        ProviderRequestData::GetStorageAt(request) => ProviderResponseData::GetStorageAt(
+            provider.get_storage_at(request.contract_address, request.key, request.block_id).await?,
+        ),
+        ProviderRequestData::GetNonce(request) => {
+            ProviderResponseData::GetNonce(provider.get_nonce(request.block_id, request.contract_address).await?)
+        }
+        ProviderRequestData::GetClassHashAt(request) => ProviderResponseData::GetClassHashAt(
+            provider.get_class_hash_at(request.block_id, request.contract_address).await?,
+        ),
+        ProviderRequestData::GetBlockWithTxHashes(request) => {
+            ProviderResponseData::GetBlockWithTxHashes(provider.get_block_with_tx_hashes(request.block_id).await?)
+        }
+        ProviderRequestData::GetTransactionReceipt(request) => ProviderResponseData::GetTransactionReceipt(
+            provider.get_transaction_receipt(request.transaction_hash).await?,
+        ),
+        ProviderRequestData::GetTransactionStatus(request) => {
+            ProviderResponseData::GetTransactionStatus(provider.get_transaction_status(request.transaction_hash).await?)
+        }
+        request => provider.batch_requests([request]).await?.pop().ok_or(BatchError::UnexpectedResponse)?,
+    })
+}
+
+/// Sends the request through the aggregator and extracts the response of the expected variant.
+macro_rules! batched {
+    ($provider:expr, $variant:ident($request:expr)) => {
+        match $provider.request(ProviderRequestData::$variant($request)).await? {
+            ProviderResponseData::$variant(response) => Ok(response),
+            _ => Err(BatchError::UnexpectedResponse.into()),
+        }
+    };
+}
+
+#[async_trait]
+impl<SP> Provider for BatchingProvider<SP>
+where
+    SP: Provider + Clone + Send + Sync + 'static,
+{
+    async fn spec_version(&self) -> Result<String, ProviderError> {
+        self.provider.spec_version().await
+    }
+
+    async fn get_block_with_tx_hashes<B>(&self, block_id: B) -> Result<MaybePendingBlockWithTxHashes, ProviderError>
+    where
+        B: AsRef<BlockId> + Send + Sync,
+    {
+        batched!(self, GetBlockWithTxHashes(GetBlockWithTxHashesRequest { block_id: *block_id.as_ref() }))
+    }
+
+    async fn get_block_with_txs<B>(&self, block_id: B) -> Result<MaybePendingBlockWithTxs, ProviderError>
+    where
+        B: AsRef<BlockId> + Send + Sync,
+    {
+        self.provider.get_block_with_txs(block_id).await
+    }
+
+    async fn get_block_with_receipts<B>(&self, block_id: B) -> Result<MaybePendingBlockWithReceipts, ProviderError>
+    where
+        B: AsRef<BlockId> + Send + Sync,
+    {
+        self.provider.get_block_with_receipts(block_id).await
+    }
+
+    async fn get_state_update<B>(&self, block_id: B) -> Result<MaybePendingStateUpdate, ProviderError>
+    where
+        B: AsRef<BlockId> + Send + Sync,
+    {
+        self.provider.get_state_update(block_id).await
+    }
+
+    async fn get_storage_at<A, K, B>(&self, contract_address: A, key: K, block_id: B) -> Result<Felt, ProviderError>
+    where
+        A: AsRef<Felt> + Send + Sync,
+        K: AsRef<Felt> + Send + Sync,
+        B: AsRef<BlockId> + Send + Sync,
+    {
+        batched!(
+            self,
+            GetStorageAt(GetStorageAtRequest {
+                contract_address: *contract_address.as_ref(),
+                key: *key.as_ref(),
+                block_id: *block_id.as_ref(),
+            })
+        )
+    }
+
+    async fn get_transaction_status<H>(&self, transaction_hash: H) -> Result<TransactionStatus, ProviderError>
+    where
+        H: AsRef<Felt> + Send + Sync,
+    {
+        batched!(
+            self,
+            GetTransactionStatus(GetTransactionStatusRequest { transaction_hash: *transaction_hash.as_ref() })
+        )
+    }
+
+    async fn get_transaction_by_hash<H>(&self, transaction_hash: H) -> Result<Transaction, ProviderError>
+    where
+        H: AsRef<Felt> + Send + Sync,
+    {
+        self.provider.get_transaction_by_hash(transaction_hash).await
+    }
+
+    async fn get_transaction_by_block_id_and_index<B>(
+        &self,
+        block_id: B,
+        index: u64,
+    ) -> Result<Transaction, ProviderError>
+    where
+        B: AsRef<BlockId> + Send + Sync,
+    {
+        self.provider.get_transaction_by_block_id_and_index(block_id, index).await
+    }
+
+    async fn get_transaction_receipt<H>(
+        &self,
+        transaction_hash: H,
+    ) -> Result<TransactionReceiptWithBlockInfo, ProviderError>
+    where
+        H: AsRef<Felt> + Send + Sync,
+    {
+        batched!(
+            self,
+            GetTransactionReceipt(GetTransactionReceiptRequest { transaction_hash: *transaction_hash.as_ref() })
+        )
+    }
+
+    async fn get_class<B, H>(&self, block_id: B, class_hash: H) -> Result<ContractClass, ProviderError>
+    where
+        B: AsRef<BlockId> + Send + Sync,
+        H: AsRef<Felt> + Send + Sync,
+    {
+        self.provider.get_class(block_id, class_hash).await
+    }
+
+    async fn get_class_hash_at<B, A>(&self, block_id: B, contract_address: A) -> Result<Felt, ProviderError>
+    where
+        B: AsRef<BlockId> + Send + Sync,
+        A: AsRef<Felt> + Send + Sync,
+    {
+        batched!(
+            self,
+            GetClassHashAt(GetClassHashAtRequest {
+                block_id: *block_id.as_ref(),
+                contract_address: *contract_address.as_ref(),
+            })
+        )
+    }
+
+    async fn get_class_at<B, A>(&self, block_id: B, contract_address: A) -> Result<ContractClass, ProviderError>
+    where
+        B: AsRef<BlockId> + Send + Sync,
+        A: AsRef<Felt> + Send + Sync,
+    {
+        self.provider.get_class_at(block_id, contract_address).await
+    }
+
+    async fn get_block_transaction_count<B>(&self, block_id: B) -> Result<u64, ProviderError>
+    where
+        B: AsRef<BlockId> + Send + Sync,
+    {
+        self.provider.get_block_transaction_count(block_id).await
+    }
+
+    async fn call<R, B>(&self, request: R, block_id: B) -> Result<Vec<Felt>, ProviderError>
+    where
+        R: AsRef<FunctionCall> + Send + Sync,
+        B: AsRef<BlockId> + Send + Sync,
+    {
+        batched!(self, Call(CallRequest { request: request.as_ref().clone(), block_id: *block_id.as_ref() }))
+    }
+
+    async fn estimate_fee<R, S, B>(
+        &self,
+        request: R,
+        simulation_flags: S,
+        block_id: B,
+    ) -> Result<Vec<FeeEstimate>, ProviderError>
+    where
+        R: AsRef<[BroadcastedTransaction]> + Send + Sync,
+        S: AsRef<[SimulationFlagForEstimateFee]> + Send + Sync,
+        B: AsRef<BlockId> + Send + Sync,
+    {
+        self.provider.estimate_fee(request, simulation_flags, block_id).await
+    }
+
+    async fn estimate_message_fee<M, B>(&self, message: M, block_id: B) -> Result<FeeEstimate, ProviderError>
+    where
+        M: AsRef<MsgFromL1> + Send + Sync,
+        B: AsRef<BlockId> + Send + Sync,
+    {
+        self.provider.estimate_message_fee(message, block_id).await
+    }
+
+    async fn block_number(&self) -> Result<u64, ProviderError> {
+        self.provider.block_number().await
+    }
+
+    async fn block_hash_and_number(&self) -> Result<BlockHashAndNumber, ProviderError> {
+        self.provider.block_hash_and_number().await
+    }
+
+    async fn chain_id(&self) -> Result<Felt, ProviderError> {
+        self.provider.chain_id().await
+    }
+
+    async fn syncing(&self) -> Result<SyncStatusType, ProviderError> {
+        self.provider.syncing().await
+    }
+
+    async fn get_events(
+        &self,
+        filter: EventFilter,
+        continuation_token: Option<String>,
+        chunk_size: u64,
+    ) -> Result<EventsPage, ProviderError> {
+        self.provider.get_events(filter, continuation_token, chunk_size).await
+    }
+
+    async fn get_nonce<B, A>(&self, block_id: B, contract_address: A) -> Result<Felt, ProviderError>
+    where
+        B: AsRef<BlockId> + Send + Sync,
+        A: AsRef<Felt> + Send + Sync,
+    {
+        batched!(
+            self,
+            GetNonce(GetNonceRequest { block_id: *block_id.as_ref(), contract_address: *contract_address.as_ref() })
+        )
+    }
+
+    async fn add_invoke_transaction<I>(&self, invoke_transaction: I) -> Result<InvokeTransactionResult, ProviderError>
+    where
+        I: AsRef<BroadcastedInvokeTransaction> + Send + Sync,
+    {
+        self.provider.add_invoke_transaction(invoke_transaction).await
+    }
+
+    async fn add_declare_transaction<D>(
+        &self,
+        declare_transaction: D,
+    ) -> Result<DeclareTransactionResult, ProviderError>
+    where
+        D: AsRef<BroadcastedDeclareTransaction> + Send + Sync,
+    {
+        self.provider.add_declare_transaction(declare_transaction).await
+    }
+
+    async fn add_deploy_account_transaction<D>(
+        &self,
+        deploy_account_transaction: D,
+    ) -> Result<DeployAccountTransactionResult, ProviderError>
+    where
+        D: AsRef<BroadcastedDeployAccountTransaction> + Send + Sync,
+    {
+        self.provider.add_deploy_account_transaction(deploy_account_transaction).await
+    }
+
+    async fn trace_transaction<H>(&self, transaction_hash: H) -> Result<TransactionTrace, ProviderError>
+    where
+        H: AsRef<Felt> + Send + Sync,
+    {
+        self.provider.trace_transaction(transaction_hash).await
+    }
+
+    async fn simulate_transactions<B, TX, S>(
+        &self,
+        block_id: B,
+        transactions: TX,
+        simulation_flags: S,
+    ) -> Result<Vec<SimulatedTransaction>, ProviderError>
+    where
+        B: AsRef<BlockId> + Send + Sync,
+        TX: AsRef<[BroadcastedTransaction]> + Send + Sync,
+        S: AsRef<[SimulationFlag]> + Send + Sync,
+    {
+        self.provider.simulate_transactions(block_id, transactions, simulation_flags).await
+    }
+
+    async fn trace_block_transactions<B>(&self, block_id: B) -> Result<Vec<TransactionTraceWithHash>, ProviderError>
+    where
+        B: AsRef<BlockId> + Send + Sync,
+    {
+        self.provider.trace_block_transactions(block_id).await
+    }
+
+    async fn batch_requests<R>(&self, requests: R) -> Result<Vec<ProviderResponseData>, ProviderError>
+    where
+        R: AsRef<[ProviderRequestData]> + Send + Sync,
+    {
+        self.provider.batch_requests(requests).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_batch_config_disabled_by_default() {
+        // Given
+        let config = BatchConfig::default();
+
+        // Then
+        assert!(!config.is_enabled());
+        assert!(BatchConfig { window: Duration::from_millis(2), ..config }.is_enabled());
+        assert!(!BatchConfig { window: Duration::from_millis(2), max_size: 1 }.is_enabled());
+    }
+}
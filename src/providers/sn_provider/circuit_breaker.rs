@@ -0,0 +1,558 @@
+use async_trait::async_trait;
+use starknet::{
+    core::types::{
+        BlockHashAndNumber, BlockId, BroadcastedDeclareTransaction, BroadcastedDeployAccountTransaction,
+        BroadcastedInvokeTransaction, BroadcastedTransaction, ContractClass, DeclareTransactionResult,
+        DeployAccountTransactionResult, EventFilter, EventsPage, FeeEstimate, Felt, FunctionCall,
+        InvokeTransactionResult, MaybePendingBlockWithReceipts, MaybePendingBlockWithTxHashes,
+        MaybePendingBlockWithTxs, MaybePendingStateUpdate, MsgFromL1, SimulatedTransaction, SimulationFlag,
+        SimulationFlagForEstimateFee, SyncStatusType, Transaction, TransactionReceiptWithBlockInfo, TransactionStatus,
+        TransactionTrace, TransactionTraceWithHash,
+    },
+    providers::{Provider, ProviderError, ProviderImplError, ProviderRequestData, ProviderResponseData},
+};
+use std::{
+    any::Any,
+    str::FromStr,
+    sync::{Arc, LazyLock, Mutex, PoisonError},
+    time::{Duration, Instant},
+};
+
+/// The circuit breaker configuration of the Starknet provider, read from the environment.
+pub static STARKNET_CIRCUIT_BREAKER_CONFIG: LazyLock<CircuitBreakerConfig> =
+    LazyLock::new(CircuitBreakerConfig::from_env);
+
+/// Configuration of the circuit breaker around the Starknet provider.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CircuitBreakerConfig {
+    /// Number of consecutive failures after which the breaker opens. Zero disables the breaker.
+    pub failure_threshold: u32,
+    /// Time after which an open breaker lets a probe request through.
+    pub reset_timeout: Duration,
+}
+
+impl Default for CircuitBreakerConfig {
+    fn default() -> Self {
+        Self { failure_threshold: 5, reset_timeout: Duration::from_secs(10) }
+    }
+}
+
+impl CircuitBreakerConfig {
+    /// Reads the configuration from the environment, falling back to the default configuration.
+    pub fn from_env() -> Self {
+        fn var<T: FromStr>(key: &str) -> Option<T> {
+            std::env::var(key).ok().and_then(|val| T::from_str(&val).ok())
+        }
+
+        let default = Self::default();
+        Self {
+            failure_threshold: var("STARKNET_CIRCUIT_BREAKER_THRESHOLD").unwrap_or(default.failure_threshold),
+            reset_timeout: var("STARKNET_CIRCUIT_BREAKER_RESET_MS")
+                .map_or(default.reset_timeout, Duration::from_millis),
+        }
+    }
+
+    /// Returns true if the circuit breaker is enabled.
+    pub const fn is_enabled(&self) -> bool {
+        self.failure_threshold > 0
+    }
+}
+
+/// The errors raised by the circuit breaker.
+#[derive(Debug, thiserror::Error)]
+pub enum CircuitBreakerError {
+    /// The breaker is open, the request wasn't sent to the Starknet provider.
+    #[error("starknet provider unavailable: circuit breaker is open")]
+    Open,
+}
+
+impl ProviderImplError for CircuitBreakerError {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl From<CircuitBreakerError> for ProviderError {
+    fn from(err: CircuitBreakerError) -> Self {
+        Self::Other(Box::new(err))
+    }
+}
+
+/// Returns true if the error was raised by an open circuit breaker.
+pub fn is_circuit_open(err: &ProviderError) -> bool {
+    matches!(err, ProviderError::Other(err) if err.as_any().is::<CircuitBreakerError>())
+}
+
+/// The state of a circuit breaker.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CircuitState {
+    /// The requests are sent to the provider.
+    Closed,
+    /// The requests fail without being sent to the provider.
+    Open,
+    /// A probe request is allowed to check if the provider recovered.
+    HalfOpen,
+}
+
+#[derive(Debug, Default)]
+struct BreakerState {
+    consecutive_failures: u32,
+    opened_at: Option<Instant>,
+    probe_started_at: Option<Instant>,
+}
+
+/// Counts the consecutive failures of the requests and opens after `failure_threshold` failures.
+///
+/// Once `reset_timeout` has elapsed, an open breaker lets a single probe request through: the
+/// breaker closes if the probe succeeds and opens again otherwise. A probe which never completes
+/// (e.g. cancelled) is replaced by a new one after another `reset_timeout`.
+#[derive(Debug)]
+pub struct CircuitBreaker {
+    config: CircuitBreakerConfig,
+    state: Mutex<BreakerState>,
+}
+
+impl CircuitBreaker {
+    /// Creates a new closed circuit breaker.
+    pub fn new(config: CircuitBreakerConfig) -> Self {
+        Self { config, state: Mutex::default() }
+    }
+
+    /// Returns the current state of the breaker.
+    pub fn state(&self) -> CircuitState {
+        let state = self.lock();
+        match state.opened_at {
+            None => CircuitState::Closed,
+            Some(opened_at) if opened_at.elapsed() >= self.config.reset_timeout => CircuitState::HalfOpen,
+            Some(_) => CircuitState::Open,
+        }
+    }
+
+    /// Checks if a request can be sent to the provider.
+    pub fn acquire(&self) -> Result<(), CircuitBreakerError> {
+        let mut state = self.lock();
+        let Some(opened_at) = state.opened_at else { return Ok(()) };
+
+        let reset_timeout = self.config.reset_timeout;
+        let probe_running = matches!(state.probe_started_at, Some(started_at) if started_at.elapsed() < reset_timeout);
+        let can_probe = opened_at.elapsed() >= reset_timeout && !probe_running;
+        if can_probe {
+            state.probe_started_at = Some(Instant::now());
+            return Ok(());
+        }
+        Err(CircuitBreakerError::Open)
+    }
+
+    /// Records the result of a request sent to the provider.
+    pub fn record<T>(&self, result: &Result<T, ProviderError>) {
+        if !self.config.is_enabled() {
+            return;
+        }
+
+        let mut state = self.lock();
+        if !result.as_ref().is_err_and(is_failure) {
+            if state.opened_at.is_some() {
+                tracing::info!(target: "starknet_circuit_breaker", "circuit breaker closed");
+            }
+            *state = BreakerState::default();
+            return;
+        }
+
+        state.consecutive_failures = state.consecutive_failures.saturating_add(1);
+        let is_probe = state.probe_started_at.is_some();
+        let reached_threshold =
+            state.opened_at.is_none() && state.consecutive_failures >= self.config.failure_threshold;
+        if is_probe || reached_threshold {
+            tracing::warn!(
+                target: "starknet_circuit_breaker",
+                counter.starknet_circuit_breaker_opened = 1,
+                failures = state.consecutive_failures,
+                "circuit breaker opened"
+            );
+            state.opened_at = Some(Instant::now());
+            state.probe_started_at = None;
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, BreakerState> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// Returns true if the error means the provider is unavailable. Starknet errors (e.g. a
+/// contract not found) are valid answers of the provider and don't count as failures.
+const fn is_failure(err: &ProviderError) -> bool {
+    matches!(err, ProviderError::Other(_) | ProviderError::RateLimited)
+}
+
+/// A Starknet provider which stops sending requests to the underlying provider after repeated
+/// failures, returning fast failures instead of piling up requests waiting for timeouts.
+#[derive(Debug, Clone)]
+pub struct CircuitBreakerProvider<SP> {
+    /// The underlying Starknet provider.
+    provider: SP,
+    /// The circuit breaker shared by the clones of the provider.
+    breaker: Arc<CircuitBreaker>,
+}
+
+impl<SP> CircuitBreakerProvider<SP> {
+    /// Creates a new [`CircuitBreakerProvider`].
+    pub fn new(provider: SP, config: CircuitBreakerConfig) -> Self {
+        Self { provider, breaker: Arc::new(CircuitBreaker::new(config)) }
+    }
+
+    /// Returns the underlying Starknet provider.
+    pub const fn inner(&self) -> &SP {
+        &self.provider
+    }
+
+    /// Returns the circuit breaker of the provider.
+    pub fn breaker(&self) -> &CircuitBreaker {
+        &self.breaker
+    }
+}
+
+/// Sends the request through the circuit breaker and records its result.
+macro_rules! guarded {
+    ($provider:expr, $request:expr) => {{
+        $provider.breaker.acquire()?;
+        let result = $request.await;
+        $provider.breaker.record(&result);
+        result
+    }};
+}
+
+#[async_trait]
+impl<SP> Provider for CircuitBreakerProvider<SP>
+where
+    SP: Provider + Send + Sync,
+{
+    async fn spec_version(&self) -> Result<String, ProviderError> {
+        guarded!(self, self.provider.spec_version())
+    }
+
+    async fn get_block_with_tx_hashes<B>(&self, block_id: B) -> Result<MaybePendingBlockWithTxHashes, ProviderError>
+    where
+        B: AsRef<BlockId> + Send + Sync,
+    {
+        guarded!(self, self.provider.get_block_with_tx_hashes(block_id))
+    }
+
+    async fn get_block_with_txs<B>(&self, block_id: B) -> Result<MaybePendingBlockWithTxs, ProviderError>
+    where
+        B: AsRef<BlockId> + Send + Sync,
+    {
+        guarded!(self, self.provider.get_block_with_txs(block_id))
+    }
+
+    async fn get_block_with_receipts<B>(&self, block_id: B) -> Result<MaybePendingBlockWithReceipts, ProviderError>
+    where
+        B: AsRef<BlockId> + Send + Sync,
+    {
+        guarded!(self, self.provider.get_block_with_receipts(block_id))
+    }
+
+    async fn get_state_update<B>(&self, block_id: B) -> Result<MaybePendingStateUpdate, ProviderError>
+    where
+        B: AsRef<BlockId> + Send + Sync,
+    {
+        guarded!(self, self.provider.get_state_update(block_id))
+    }
+
+    async fn get_storage_at<A, K, B>(&self, contract_address: A, key: K, block_id: B) -> Result<Felt, ProviderError>
+    where
+        A: AsRef<Felt> + Send + Sync,
+        K: AsRef<Felt> + Send + Sync,
+        B: AsRef<BlockId> + Send + Sync,
+    {
+        guarded!(self, self.provider.get_storage_at(contract_address, key, block_id))
+    }
+
+    async fn get_transaction_status<H>(&self, transaction_hash: H) -> Result<TransactionStatus, ProviderError>
+    where
+        H: AsRef<Felt> + Send + Sync,
+    {
+        guarded!(self, self.provider.get_transaction_status(transaction_hash))
+    }
+
+    async fn get_transaction_by_hash<H>(&self, transaction_hash: H) -> Result<Transaction, ProviderError>
+    where
+        H: AsRef<Felt> + Send + Sync,
+    {
+        guarded!(self, self.provider.get_transaction_by_hash(transaction_hash))
+    }
+
+    async fn get_transaction_by_block_id_and_index<B>(
+        &self,
+        block_id: B,
+        index: u64,
+    ) -> Result<Transaction, ProviderError>
+    where
+        B: AsRef<BlockId> + Send + Sync,
+    {
+        guarded!(self, self.provider.get_transaction_by_block_id_and_index(block_id, index))
+    }
+
+    async fn get_transaction_receipt<H>(
+        &self,
+        transaction_hash: H,
+    ) -> Result<TransactionReceiptWithBlockInfo, ProviderError>
+    where
+        H: AsRef<Felt> + Send + Sync,
+    {
+        guarded!(self, self.provider.get_transaction_receipt(transaction_hash))
+    }
+
+    async fn get_class<B, H>(&self, block_id: B, class_hash: H) -> Result<ContractClass, ProviderError>
+    where
+        B: AsRef<BlockId> + Send + Sync,
+        H: AsRef<Felt> + Send + Sync,
+    {
+        guarded!(self, self.provider.get_class(block_id, class_hash))
+    }
+
+    async fn get_class_hash_at<B, A>(&self, block_id: B, contract_address: A) -> Result<Felt, ProviderError>
+    where
+        B: AsRef<BlockId> + Send + Sync,
+        A: AsRef<Felt> + Send + Sync,
+    {
+        guarded!(self, self.provider.get_class_hash_at(block_id, contract_address))
+    }
+
+    async fn get_class_at<B, A>(&self, block_id: B, contract_address: A) -> Result<ContractClass, ProviderError>
+    where
+        B: AsRef<BlockId> + Send + Sync,
+        A: AsRef<Felt> + Send + Sync,
+    {
+        guarded!(self, self.provider.get_class_at(block_id, contract_address))
+    }
+
+    async fn get_block_transaction_count<B>(&self, block_id: B) -> Result<u64, ProviderError>
+    where
+        B: AsRef<BlockId> + Send + Sync,
+    {
+        guarded!(self, self.provider.get_block_transaction_count(block_id))
+    }
+
+    async fn call<R, B>(&self, request: R, block_id: B) -> Result<Vec<Felt>, ProviderError>
+    where
+        R: AsRef<FunctionCall> + Send + Sync,
+        B: AsRef<BlockId> + Send + Sync,
+    {
+        guarded!(self, self.provider.call(request, block_id))
+    }
+
+    async fn estimate_fee<R, S, B>(
+        &self,
+        request: R,
+        simulation_flags: S,
+        block_id: B,
+    ) -> Result<Vec<FeeEstimate>, ProviderError>
+    where
+        R: AsRef<[BroadcastedTransaction]> + Send + Sync,
+        S: AsRef<[SimulationFlagForEstimateFee]> + Send + Sync,
+        B: AsRef<BlockId> + Send + Sync,
+    {
+        guarded!(self, self.provider.estimate_fee(request, simulation_flags, block_id))
+    }
+
+    async fn estimate_message_fee<M, B>(&self, message: M, block_id: B) -> Result<FeeEstimate, ProviderError>
+    where
+        M: AsRef<MsgFromL1> + Send + Sync,
+        B: AsRef<BlockId> + Send + Sync,
+    {
+        guarded!(self, self.provider.estimate_message_fee(message, block_id))
+    }
+
+    async fn block_number(&self) -> Result<u64, ProviderError> {
+        guarded!(self, self.provider.block_number())
+    }
+
+    async fn block_hash_and_number(&self) -> Result<BlockHashAndNumber, ProviderError> {
+        guarded!(self, self.provider.block_hash_and_number())
+    }
+
+    async fn chain_id(&self) -> Result<Felt, ProviderError> {
+        guarded!(self, self.provider.chain_id())
+    }
+
+    async fn syncing(&self) -> Result<SyncStatusType, ProviderError> {
+        guarded!(self, self.provider.syncing())
+    }
+
+    async fn get_events(
+        &self,
+        filter: EventFilter,
+        continuation_token: Option<String>,
+        chunk_size: u64,
+    ) -> Result<EventsPage, ProviderError> {
+        guarded!(self, self.provider.get_events(filter, continuation_token, chunk_size))
+    }
+
+    async fn get_nonce<B, A>(&self, block_id: B, contract_address: A) -> Result<Felt, ProviderError>
+    where
+        B: AsRef<BlockId> + Send + Sync,
+        A: AsRef<Felt> + Send + Sync,
+    {
+        guarded!(self, self.provider.get_nonce(block_id, contract_address))
+    }
+
+    async fn add_invoke_transaction<I>(&self, invoke_transaction: I) -> Result<InvokeTransactionResult, ProviderError>
+    where
+        I: AsRef<BroadcastedInvokeTransaction> + Send + Sync,
+    {
+        guarded!(self, self.provider.add_invoke_transaction(invoke_transaction))
+    }
+
+    async fn add_declare_transaction<D>(
+        &self,
+        declare_transaction: D,
+    ) -> Result<DeclareTransactionResult, ProviderError>
+    where
+        D: AsRef<BroadcastedDeclareTransaction> + Send + Sync,
+    {
+        guarded!(self, self.provider.add_declare_transaction(declare_transaction))
+    }
+
+    async fn add_deploy_account_transaction<D>(
+        &self,
+        deploy_account_transaction: D,
+    ) -> Result<DeployAccountTransactionResult, ProviderError>
+    where
+        D: AsRef<BroadcastedDeployAccountTransaction> + Send + Sync,
+    {
+        guarded!(self, self.provider.add_deploy_account_transaction(deploy_account_transaction))
+    }
+
+    async fn trace_transaction<H>(&self, transaction_hash: H) -> Result<TransactionTrace, ProviderError>
+    where
+        H: AsRef<Felt> + Send + Sync,
+    {
+        guarded!(self, self.provider.trace_transaction(transaction_hash))
+    }
+
+    async fn simulate_transactions<B, TX, S>(
+        &self,
+        block_id: B,
+        transactions: TX,
+        simulation_flags: S,
+    ) -> Result<Vec<SimulatedTransaction>, ProviderError>
+    where
+        B: AsRef<BlockId> + Send + Sync,
+        TX: AsRef<[BroadcastedTransaction]> + Send + Sync,
+        S: AsRef<[SimulationFlag]> + Send + Sync,
+    {
+        guarded!(self, self.provider.simulate_transactions(block_id, transactions, simulation_flags))
+    }
+
+    async fn trace_block_transactions<B>(&self, block_id: B) -> Result<Vec<TransactionTraceWithHash>, ProviderError>
+    where
+        B: AsRef<BlockId> + Send + Sync,
+    {
+        guarded!(self, self.provider.trace_block_transactions(block_id))
+    }
+
+    async fn batch_requests<R>(&self, requests: R) -> Result<Vec<ProviderResponseData>, ProviderError>
+    where
+        R: AsRef<[ProviderRequestData]> + Send + Sync,
+    {
+        guarded!(self, self.provider.batch_requests(requests))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use starknet::core::types::StarknetError;
+
+    fn config() -> CircuitBreakerConfig {
+        CircuitBreakerConfig { failure_threshold: 2, reset_timeout: Duration::from_millis(20) }
+    }
+
+    fn failure() -> Result<(), ProviderError> {
+        Err(ProviderError::RateLimited)
+    }
+
+    #[test]
+    fn test_circuit_breaker_opens_after_consecutive_failures() {
+        // Given
+        let breaker = CircuitBreaker::new(config());
+
+        // When
+        breaker.record(&failure());
+        breaker.record(&Ok(()));
+        breaker.record(&failure());
+
+        // Then
+        assert_eq!(breaker.state(), CircuitState::Closed);
+        assert!(breaker.acquire().is_ok());
+
+        // When
+        breaker.record(&failure());
+
+        // Then
+        assert_eq!(breaker.state(), CircuitState::Open);
+        assert!(is_circuit_open(&breaker.acquire().map_err(ProviderError::from).unwrap_err()));
+    }
+
+    #[test]
+    fn test_circuit_breaker_ignores_starknet_errors() {
+        // Given
+        let breaker = CircuitBreaker::new(config());
+
+        // When
+        for _ in 0..3 {
+            breaker.record::<()>(&Err(ProviderError::StarknetError(StarknetError::ContractNotFound)));
+        }
+
+        // Then
+        assert_eq!(breaker.state(), CircuitState::Closed);
+    }
+
+    #[test]
+    fn test_circuit_breaker_half_open_probe() {
+        // Given
+        let breaker = CircuitBreaker::new(config());
+        breaker.record(&failure());
+        breaker.record(&failure());
+
+        // When
+        std::thread::sleep(Duration::from_millis(25));
+
+        // Then
+        // A single probe is let through
+        assert_eq!(breaker.state(), CircuitState::HalfOpen);
+        assert!(breaker.acquire().is_ok());
+        assert!(breaker.acquire().is_err());
+
+        // When
+        // The probe fails
+        breaker.record(&failure());
+
+        // Then
+        assert_eq!(breaker.state(), CircuitState::Open);
+
+        // When
+        // The next probe succeeds
+        std::thread::sleep(Duration::from_millis(25));
+        assert!(breaker.acquire().is_ok());
+        breaker.record(&Ok(()));
+
+        // Then
+        assert_eq!(breaker.state(), CircuitState::Closed);
+    }
+
+    #[test]
+    fn test_circuit_breaker_disabled() {
+        // Given
+        let breaker = CircuitBreaker::new(CircuitBreakerConfig { failure_threshold: 0, ..config() });
+
+        // When
+        for _ in 0..10 {
+            breaker.record(&failure());
+        }
+
+        // Then
+        assert_eq!(breaker.state(), CircuitState::Closed);
+    }
+}
@@ -0,0 +1,294 @@
+use crate::chaos::{FaultInjector, FAULT_INJECTOR};
+use async_trait::async_trait;
+use starknet::{
+    core::types::{
+        BlockHashAndNumber, BlockId, BroadcastedDeclareTransaction, BroadcastedDeployAccountTransaction,
+        BroadcastedInvokeTransaction, BroadcastedTransaction, ContractClass, DeclareTransactionResult,
+        DeployAccountTransactionResult, EventFilter, EventsPage, FeeEstimate, Felt, FunctionCall,
+        InvokeTransactionResult, MaybePendingBlockWithReceipts, MaybePendingBlockWithTxHashes,
+        MaybePendingBlockWithTxs, MaybePendingStateUpdate, MsgFromL1, SimulatedTransaction, SimulationFlag,
+        SimulationFlagForEstimateFee, SyncStatusType, Transaction, TransactionReceiptWithBlockInfo, TransactionStatus,
+        TransactionTrace, TransactionTraceWithHash,
+    },
+    providers::{Provider, ProviderError, ProviderRequestData, ProviderResponseData},
+};
+use std::sync::Arc;
+
+/// A Starknet provider delaying and dropping the calls to the underlying provider as configured
+/// by the fault injector, used to test the resilience of the node to an unreliable Starknet node.
+#[derive(Debug, Clone)]
+pub struct FaultInjectionProvider<SP> {
+    /// The underlying Starknet provider.
+    provider: SP,
+    /// The fault injector drawing the faults of the calls.
+    injector: Arc<FaultInjector>,
+}
+
+impl<SP> FaultInjectionProvider<SP> {
+    /// Creates a new [`FaultInjectionProvider`] drawing its faults from the fault injector of the
+    /// node.
+    pub fn new(provider: SP) -> Self {
+        Self::with_injector(provider, FAULT_INJECTOR.clone())
+    }
+
+    /// Creates a new [`FaultInjectionProvider`] drawing its faults from the given fault injector.
+    pub const fn with_injector(provider: SP, injector: Arc<FaultInjector>) -> Self {
+        Self { provider, injector }
+    }
+
+    /// Returns the fault injector of the provider.
+    pub const fn injector(&self) -> &Arc<FaultInjector> {
+        &self.injector
+    }
+
+    /// Returns the underlying Starknet provider.
+    pub const fn inner(&self) -> &SP {
+        &self.provider
+    }
+}
+
+/// Sends the request to the underlying provider unless the fault injector drops it.
+macro_rules! faulty {
+    ($self:ident, $request:expr) => {{
+        $self.injector.starknet_call().await?;
+        $request.await
+    }};
+}
+
+#[async_trait]
+impl<SP> Provider for FaultInjectionProvider<SP>
+where
+    SP: Provider + Send + Sync,
+{
+    async fn spec_version(&self) -> Result<String, ProviderError> {
+        faulty!(self, self.provider.spec_version())
+    }
+
+    async fn get_block_with_tx_hashes<B>(&self, block_id: B) -> Result<MaybePendingBlockWithTxHashes, ProviderError>
+    where
+        B: AsRef<BlockId> + Send + Sync,
+    {
+        faulty!(self, self.provider.get_block_with_tx_hashes(block_id))
+    }
+
+    async fn get_block_with_txs<B>(&self, block_id: B) -> Result<MaybePendingBlockWithTxs, ProviderError>
+    where
+        B: AsRef<BlockId> + Send + Sync,
+    {
+        faulty!(self, self.provider.get_block_with_txs(block_id))
+    }
+
+    async fn get_block_with_receipts<B>(&self, block_id: B) -> Result<MaybePendingBlockWithReceipts, ProviderError>
+    where
+        B: AsRef<BlockId> + Send + Sync,
+    {
+        faulty!(self, self.provider.get_block_with_receipts(block_id))
+    }
+
+    async fn get_state_update<B>(&self, block_id: B) -> Result<MaybePendingStateUpdate, ProviderError>
+    where
+        B: AsRef<BlockId> + Send + Sync,
+    {
+        faulty!(self, self.provider.get_state_update(block_id))
+    }
+
+    async fn get_storage_at<A, K, B>(&self, contract_address: A, key: K, block_id: B) -> Result<Felt, ProviderError>
+    where
+        A: AsRef<Felt> + Send + Sync,
+        K: AsRef<Felt> + Send + Sync,
+        B: AsRef<BlockId> + Send + Sync,
+    {
+        faulty!(self, self.provider.get_storage_at(contract_address, key, block_id))
+    }
+
+    async fn get_transaction_status<H>(&self, transaction_hash: H) -> Result<TransactionStatus, ProviderError>
+    where
+        H: AsRef<Felt> + Send + Sync,
+    {
+        faulty!(self, self.provider.get_transaction_status(transaction_hash))
+    }
+
+    async fn get_transaction_by_hash<H>(&self, transaction_hash: H) -> Result<Transaction, ProviderError>
+    where
+        H: AsRef<Felt> + Send + Sync,
+    {
+        faulty!(self, self.provider.get_transaction_by_hash(transaction_hash))
+    }
+
+    async fn get_transaction_by_block_id_and_index<B>(
+        &self,
+        block_id: B,
+        index: u64,
+    ) -> Result<Transaction, ProviderError>
+    where
+        B: AsRef<BlockId> + Send + Sync,
+    {
+        faulty!(self, self.provider.get_transaction_by_block_id_and_index(block_id, index))
+    }
+
+    async fn get_transaction_receipt<H>(
+        &self,
+        transaction_hash: H,
+    ) -> Result<TransactionReceiptWithBlockInfo, ProviderError>
+    where
+        H: AsRef<Felt> + Send + Sync,
+    {
+        faulty!(self, self.provider.get_transaction_receipt(transaction_hash))
+    }
+
+    async fn get_class<B, H>(&self, block_id: B, class_hash: H) -> Result<ContractClass, ProviderError>
+    where
+        B: AsRef<BlockId> + Send + Sync,
+        H: AsRef<Felt> + Send + Sync,
+    {
+        faulty!(self, self.provider.get_class(block_id, class_hash))
+    }
+
+    async fn get_class_hash_at<B, A>(&self, block_id: B, contract_address: A) -> Result<Felt, ProviderError>
+    where
+        B: AsRef<BlockId> + Send + Sync,
+        A: AsRef<Felt> + Send + Sync,
+    {
+        faulty!(self, self.provider.get_class_hash_at(block_id, contract_address))
+    }
+
+    async fn get_class_at<B, A>(&self, block_id: B, contract_address: A) -> Result<ContractClass, ProviderError>
+    where
+        B: AsRef<BlockId> + Send + Sync,
+        A: AsRef<Felt> + Send + Sync,
+    {
+        faulty!(self, self.provider.get_class_at(block_id, contract_address))
+    }
+
+    async fn get_block_transaction_count<B>(&self, block_id: B) -> Result<u64, ProviderError>
+    where
+        B: AsRef<BlockId> + Send + Sync,
+    {
+        faulty!(self, self.provider.get_block_transaction_count(block_id))
+    }
+
+    async fn call<R, B>(&self, request: R, block_id: B) -> Result<Vec<Felt>, ProviderError>
+    where
+        R: AsRef<FunctionCall> + Send + Sync,
+        B: AsRef<BlockId> + Send + Sync,
+    {
+        faulty!(self, self.provider.call(request, block_id))
+    }
+
+    async fn estimate_fee<R, S, B>(
+        &self,
+        request: R,
+        simulation_flags: S,
+        block_id: B,
+    ) -> Result<Vec<FeeEstimate>, ProviderError>
+    where
+        R: AsRef<[BroadcastedTransaction]> + Send + Sync,
+        S: AsRef<[SimulationFlagForEstimateFee]> + Send + Sync,
+        B: AsRef<BlockId> + Send + Sync,
+    {
+        faulty!(self, self.provider.estimate_fee(request, simulation_flags, block_id))
+    }
+
+    async fn estimate_message_fee<M, B>(&self, message: M, block_id: B) -> Result<FeeEstimate, ProviderError>
+    where
+        M: AsRef<MsgFromL1> + Send + Sync,
+        B: AsRef<BlockId> + Send + Sync,
+    {
+        faulty!(self, self.provider.estimate_message_fee(message, block_id))
+    }
+
+    async fn block_number(&self) -> Result<u64, ProviderError> {
+        faulty!(self, self.provider.block_number())
+    }
+
+    async fn block_hash_and_number(&self) -> Result<BlockHashAndNumber, ProviderError> {
+        faulty!(self, self.provider.block_hash_and_number())
+    }
+
+    async fn chain_id(&self) -> Result<Felt, ProviderError> {
+        faulty!(self, self.provider.chain_id())
+    }
+
+    async fn syncing(&self) -> Result<SyncStatusType, ProviderError> {
+        faulty!(self, self.provider.syncing())
+    }
+
+    async fn get_events(
+        &self,
+        filter: EventFilter,
+        continuation_token: Option<String>,
+        chunk_size: u64,
+    ) -> Result<EventsPage, ProviderError> {
+        faulty!(self, self.provider.get_events(filter, continuation_token, chunk_size))
+    }
+
+    async fn get_nonce<B, A>(&self, block_id: B, contract_address: A) -> Result<Felt, ProviderError>
+    where
+        B: AsRef<BlockId> + Send + Sync,
+        A: AsRef<Felt> + Send + Sync,
+    {
+        faulty!(self, self.provider.get_nonce(block_id, contract_address))
+    }
+
+    async fn add_invoke_transaction<I>(&self, invoke_transaction: I) -> Result<InvokeTransactionResult, ProviderError>
+    where
+        I: AsRef<BroadcastedInvokeTransaction> + Send + Sync,
+    {
+        faulty!(self, self.provider.add_invoke_transaction(invoke_transaction))
+    }
+
+    async fn add_declare_transaction<D>(
+        &self,
+        declare_transaction: D,
+    ) -> Result<DeclareTransactionResult, ProviderError>
+    where
+        D: AsRef<BroadcastedDeclareTransaction> + Send + Sync,
+    {
+        faulty!(self, self.provider.add_declare_transaction(declare_transaction))
+    }
+
+    async fn add_deploy_account_transaction<D>(
+        &self,
+        deploy_account_transaction: D,
+    ) -> Result<DeployAccountTransactionResult, ProviderError>
+    where
+        D: AsRef<BroadcastedDeployAccountTransaction> + Send + Sync,
+    {
+        faulty!(self, self.provider.add_deploy_account_transaction(deploy_account_transaction))
+    }
+
+    async fn trace_transaction<H>(&self, transaction_hash: H) -> Result<TransactionTrace, ProviderError>
+    where
+        H: AsRef<Felt> + Send + Sync,
+    {
+        faulty!(self, self.provider.trace_transaction(transaction_hash))
+    }
+
+    async fn simulate_transactions<B, TX, S>(
+        &self,
+        block_id: B,
+        transactions: TX,
+        simulation_flags: S,
+    ) -> Result<Vec<SimulatedTransaction>, ProviderError>
+    where
+        B: AsRef<BlockId> + Send + Sync,
+        TX: AsRef<[BroadcastedTransaction]> + Send + Sync,
+        S: AsRef<[SimulationFlag]> + Send + Sync,
+    {
+        faulty!(self, self.provider.simulate_transactions(block_id, transactions, simulation_flags))
+    }
+
+    async fn trace_block_transactions<B>(&self, block_id: B) -> Result<Vec<TransactionTraceWithHash>, ProviderError>
+    where
+        B: AsRef<BlockId> + Send + Sync,
+    {
+        faulty!(self, self.provider.trace_block_transactions(block_id))
+    }
+
+    async fn batch_requests<R>(&self, requests: R) -> Result<Vec<ProviderResponseData>, ProviderError>
+    where
+        R: AsRef<[ProviderRequestData]> + Send + Sync,
+    {
+        faulty!(self, self.provider.batch_requests(requests))
+    }
+}
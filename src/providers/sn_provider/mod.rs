@@ -1,3 +1,15 @@
+pub mod batch;
+pub mod circuit_breaker;
+#[cfg(feature = "chaos")]
+pub mod fault_injection;
+pub mod offline;
+pub mod routing;
 pub mod starknet_provider;
 
+pub use batch::BatchingProvider;
+pub use circuit_breaker::CircuitBreakerProvider;
+#[cfg(feature = "chaos")]
+pub use fault_injection::FaultInjectionProvider;
+pub use offline::OfflineProvider;
+pub use routing::RoutingProvider;
 pub use starknet_provider::StarknetProvider;
@@ -0,0 +1,403 @@
+use async_trait::async_trait;
+use starknet::{
+    core::types::{
+        BlockHashAndNumber, BlockId, BroadcastedDeclareTransaction, BroadcastedDeployAccountTransaction,
+        BroadcastedInvokeTransaction, BroadcastedTransaction, ContractClass, DeclareTransactionResult,
+        DeployAccountTransactionResult, EventFilter, EventsPage, FeeEstimate, Felt, FunctionCall,
+        InvokeTransactionResult, MaybePendingBlockWithReceipts, MaybePendingBlockWithTxHashes,
+        MaybePendingBlockWithTxs, MaybePendingStateUpdate, MsgFromL1, SimulatedTransaction, SimulationFlag,
+        SimulationFlagForEstimateFee, SyncStatusType, Transaction, TransactionReceiptWithBlockInfo, TransactionStatus,
+        TransactionTrace, TransactionTraceWithHash,
+    },
+    providers::{Provider, ProviderError, ProviderRequestData, ProviderResponseData},
+};
+use std::{
+    str::FromStr,
+    sync::{Arc, LazyLock, Mutex, PoisonError},
+    time::{Duration, Instant},
+};
+use url::Url;
+
+/// The routing configuration of the Starknet requests, read from the environment.
+pub static STARKNET_ROUTING_CONFIG: LazyLock<RoutingConfig> = LazyLock::new(RoutingConfig::from_env);
+
+/// Interval after which the head of the chain is fetched again to route the requests.
+const HEAD_REFRESH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Configuration of the routing of the Starknet requests between a full node and an archive node.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RoutingConfig {
+    /// The url of the archive Starknet node serving the historical blocks. The routing is
+    /// disabled if not set.
+    pub archive_url: Option<Url>,
+    /// The number of blocks behind the head of the chain from which a block is historical.
+    pub threshold: u64,
+}
+
+impl Default for RoutingConfig {
+    fn default() -> Self {
+        Self { archive_url: None, threshold: 128 }
+    }
+}
+
+impl RoutingConfig {
+    /// Reads the configuration from the environment, falling back to the default configuration.
+    pub fn from_env() -> Self {
+        fn var<T: FromStr>(key: &str) -> Option<T> {
+            std::env::var(key).ok().and_then(|val| T::from_str(&val).ok())
+        }
+
+        let default = Self::default();
+        Self {
+            archive_url: var("STARKNET_ARCHIVE_NETWORK"),
+            threshold: var("STARKNET_ARCHIVE_THRESHOLD_BLOCKS").unwrap_or(default.threshold),
+        }
+    }
+}
+
+/// Returns true if the requests on the block should be sent to the archive node, given the head
+/// of the chain and the threshold. The blocks queried by hash are sent to the archive node, as
+/// their height is unknown, and the blocks queried by tag to the full node.
+pub const fn is_historical(block_id: &BlockId, head: u64, threshold: u64) -> bool {
+    match block_id {
+        BlockId::Tag(_) => false,
+        BlockId::Hash(_) => true,
+        BlockId::Number(number) => number.saturating_add(threshold) < head,
+    }
+}
+
+/// A Starknet provider which sends the requests on the historical blocks to an archive node and
+/// the other requests, including the transactions, to a fast full node.
+#[derive(Debug, Clone)]
+pub struct RoutingProvider<SP> {
+    /// The provider of the full node.
+    latest: SP,
+    /// The provider of the archive node, none if the routing is disabled.
+    archive: Option<SP>,
+    /// The number of blocks behind the head from which a block is historical.
+    threshold: u64,
+    /// The last head of the chain fetched from the full node, shared by the clones of the provider.
+    head: Arc<Mutex<Option<(Instant, u64)>>>,
+}
+
+impl<SP> RoutingProvider<SP> {
+    /// Creates a new [`RoutingProvider`]. Without archive provider, all the requests are sent to
+    /// the provider of the full node.
+    pub fn new(latest: SP, archive: Option<SP>, threshold: u64) -> Self {
+        Self { latest, archive, threshold, head: Arc::default() }
+    }
+
+    /// Returns the provider of the full node.
+    pub const fn latest(&self) -> &SP {
+        &self.latest
+    }
+
+    /// Returns the provider of the archive node, if any.
+    pub const fn archive(&self) -> Option<&SP> {
+        self.archive.as_ref()
+    }
+}
+
+impl<SP> RoutingProvider<SP>
+where
+    SP: Provider + Send + Sync,
+{
+    /// Returns the provider serving the requests on the block.
+    async fn provider_for(&self, block_id: &BlockId) -> &SP {
+        let Some(archive) = self.archive.as_ref() else { return &self.latest };
+        if matches!(block_id, BlockId::Tag(_)) {
+            return &self.latest;
+        }
+
+        // The archive node serves all the blocks, including when the head is unknown
+        let historical = self.head().await.map_or(true, |head| is_historical(block_id, head, self.threshold));
+        if historical {
+            tracing::info!(target: "starknet_routing", monotonic_counter.starknet_archive_requests = 1);
+            archive
+        } else {
+            &self.latest
+        }
+    }
+
+    /// Returns the head of the chain, fetched from the full node at most once per
+    /// [`HEAD_REFRESH_INTERVAL`].
+    async fn head(&self) -> Option<u64> {
+        let cached = *self.head.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Some((_, head)) = cached.filter(|(fetched_at, _)| fetched_at.elapsed() < HEAD_REFRESH_INTERVAL) {
+            return Some(head);
+        }
+
+        let head = self.latest.block_number().await.ok()?;
+        *self.head.lock().unwrap_or_else(PoisonError::into_inner) = Some((Instant::now(), head));
+        Some(head)
+    }
+}
+
+#[async_trait]
+impl<SP> Provider for RoutingProvider<SP>
+where
+    SP: Provider + Send + Sync,
+{
+    async fn spec_version(&self) -> Result<String, ProviderError> {
+        self.latest.spec_version().await
+    }
+
+    async fn get_block_with_tx_hashes<B>(&self, block_id: B) -> Result<MaybePendingBlockWithTxHashes, ProviderError>
+    where
+        B: AsRef<BlockId> + Send + Sync,
+    {
+        self.provider_for(block_id.as_ref()).await.get_block_with_tx_hashes(block_id).await
+    }
+
+    async fn get_block_with_txs<B>(&self, block_id: B) -> Result<MaybePendingBlockWithTxs, ProviderError>
+    where
+        B: AsRef<BlockId> + Send + Sync,
+    {
+        self.provider_for(block_id.as_ref()).await.get_block_with_txs(block_id).await
+    }
+
+    async fn get_block_with_receipts<B>(&self, block_id: B) -> Result<MaybePendingBlockWithReceipts, ProviderError>
+    where
+        B: AsRef<BlockId> + Send + Sync,
+    {
+        self.provider_for(block_id.as_ref()).await.get_block_with_receipts(block_id).await
+    }
+
+    async fn get_state_update<B>(&self, block_id: B) -> Result<MaybePendingStateUpdate, ProviderError>
+    where
+        B: AsRef<BlockId> + Send + Sync,
+    {
+        self.provider_for(block_id.as_ref()).await.get_state_update(block_id).await
+    }
+
+    async fn get_storage_at<A, K, B>(&self, contract_address: A, key: K, block_id: B) -> Result<Felt, ProviderError>
+    where
+        A: AsRef<Felt> + Send + Sync,
+        K: AsRef<Felt> + Send + Sync,
+        B: AsRef<BlockId> + Send + Sync,
+    {
+        self.provider_for(block_id.as_ref()).await.get_storage_at(contract_address, key, block_id).await
+    }
+
+    async fn get_transaction_status<H>(&self, transaction_hash: H) -> Result<TransactionStatus, ProviderError>
+    where
+        H: AsRef<Felt> + Send + Sync,
+    {
+        self.latest.get_transaction_status(transaction_hash).await
+    }
+
+    async fn get_transaction_by_hash<H>(&self, transaction_hash: H) -> Result<Transaction, ProviderError>
+    where
+        H: AsRef<Felt> + Send + Sync,
+    {
+        self.latest.get_transaction_by_hash(transaction_hash).await
+    }
+
+    async fn get_transaction_by_block_id_and_index<B>(
+        &self,
+        block_id: B,
+        index: u64,
+    ) -> Result<Transaction, ProviderError>
+    where
+        B: AsRef<BlockId> + Send + Sync,
+    {
+        self.provider_for(block_id.as_ref()).await.get_transaction_by_block_id_and_index(block_id, index).await
+    }
+
+    async fn get_transaction_receipt<H>(
+        &self,
+        transaction_hash: H,
+    ) -> Result<TransactionReceiptWithBlockInfo, ProviderError>
+    where
+        H: AsRef<Felt> + Send + Sync,
+    {
+        self.latest.get_transaction_receipt(transaction_hash).await
+    }
+
+    async fn get_class<B, H>(&self, block_id: B, class_hash: H) -> Result<ContractClass, ProviderError>
+    where
+        B: AsRef<BlockId> + Send + Sync,
+        H: AsRef<Felt> + Send + Sync,
+    {
+        self.provider_for(block_id.as_ref()).await.get_class(block_id, class_hash).await
+    }
+
+    async fn get_class_hash_at<B, A>(&self, block_id: B, contract_address: A) -> Result<Felt, ProviderError>
+    where
+        B: AsRef<BlockId> + Send + Sync,
+        A: AsRef<Felt> + Send + Sync,
+    {
+        self.provider_for(block_id.as_ref()).await.get_class_hash_at(block_id, contract_address).await
+    }
+
+    async fn get_class_at<B, A>(&self, block_id: B, contract_address: A) -> Result<ContractClass, ProviderError>
+    where
+        B: AsRef<BlockId> + Send + Sync,
+        A: AsRef<Felt> + Send + Sync,
+    {
+        self.provider_for(block_id.as_ref()).await.get_class_at(block_id, contract_address).await
+    }
+
+    async fn get_block_transaction_count<B>(&self, block_id: B) -> Result<u64, ProviderError>
+    where
+        B: AsRef<BlockId> + Send + Sync,
+    {
+        self.provider_for(block_id.as_ref()).await.get_block_transaction_count(block_id).await
+    }
+
+    async fn call<R, B>(&self, request: R, block_id: B) -> Result<Vec<Felt>, ProviderError>
+    where
+        R: AsRef<FunctionCall> + Send + Sync,
+        B: AsRef<BlockId> + Send + Sync,
+    {
+        self.provider_for(block_id.as_ref()).await.call(request, block_id).await
+    }
+
+    async fn estimate_fee<R, S, B>(
+        &self,
+        request: R,
+        simulation_flags: S,
+        block_id: B,
+    ) -> Result<Vec<FeeEstimate>, ProviderError>
+    where
+        R: AsRef<[BroadcastedTransaction]> + Send + Sync,
+        S: AsRef<[SimulationFlagForEstimateFee]> + Send + Sync,
+        B: AsRef<BlockId> + Send + Sync,
+    {
+        self.provider_for(block_id.as_ref()).await.estimate_fee(request, simulation_flags, block_id).await
+    }
+
+    async fn estimate_message_fee<M, B>(&self, message: M, block_id: B) -> Result<FeeEstimate, ProviderError>
+    where
+        M: AsRef<MsgFromL1> + Send + Sync,
+        B: AsRef<BlockId> + Send + Sync,
+    {
+        self.provider_for(block_id.as_ref()).await.estimate_message_fee(message, block_id).await
+    }
+
+    async fn block_number(&self) -> Result<u64, ProviderError> {
+        self.latest.block_number().await
+    }
+
+    async fn block_hash_and_number(&self) -> Result<BlockHashAndNumber, ProviderError> {
+        self.latest.block_hash_and_number().await
+    }
+
+    async fn chain_id(&self) -> Result<Felt, ProviderError> {
+        self.latest.chain_id().await
+    }
+
+    async fn syncing(&self) -> Result<SyncStatusType, ProviderError> {
+        self.latest.syncing().await
+    }
+
+    async fn get_events(
+        &self,
+        filter: EventFilter,
+        continuation_token: Option<String>,
+        chunk_size: u64,
+    ) -> Result<EventsPage, ProviderError> {
+        // The events are routed on the first block of the range
+        let provider = match filter.from_block.as_ref() {
+            Some(from_block) => self.provider_for(from_block).await,
+            None => &self.latest,
+        };
+        provider.get_events(filter, continuation_token, chunk_size).await
+    }
+
+    async fn get_nonce<B, A>(&self, block_id: B, contract_address: A) -> Result<Felt, ProviderError>
+    where
+        B: AsRef<BlockId> + Send + Sync,
+        A: AsRef<Felt> + Send + Sync,
+    {
+        self.provider_for(block_id.as_ref()).await.get_nonce(block_id, contract_address).await
+    }
+
+    async fn add_invoke_transaction<I>(&self, invoke_transaction: I) -> Result<InvokeTransactionResult, ProviderError>
+    where
+        I: AsRef<BroadcastedInvokeTransaction> + Send + Sync,
+    {
+        self.latest.add_invoke_transaction(invoke_transaction).await
+    }
+
+    async fn add_declare_transaction<D>(
+        &self,
+        declare_transaction: D,
+    ) -> Result<DeclareTransactionResult, ProviderError>
+    where
+        D: AsRef<BroadcastedDeclareTransaction> + Send + Sync,
+    {
+        self.latest.add_declare_transaction(declare_transaction).await
+    }
+
+    async fn add_deploy_account_transaction<D>(
+        &self,
+        deploy_account_transaction: D,
+    ) -> Result<DeployAccountTransactionResult, ProviderError>
+    where
+        D: AsRef<BroadcastedDeployAccountTransaction> + Send + Sync,
+    {
+        self.latest.add_deploy_account_transaction(deploy_account_transaction).await
+    }
+
+    async fn trace_transaction<H>(&self, transaction_hash: H) -> Result<TransactionTrace, ProviderError>
+    where
+        H: AsRef<Felt> + Send + Sync,
+    {
+        self.latest.trace_transaction(transaction_hash).await
+    }
+
+    async fn simulate_transactions<B, TX, S>(
+        &self,
+        block_id: B,
+        transactions: TX,
+        simulation_flags: S,
+    ) -> Result<Vec<SimulatedTransaction>, ProviderError>
+    where
+        B: AsRef<BlockId> + Send + Sync,
+        TX: AsRef<[BroadcastedTransaction]> + Send + Sync,
+        S: AsRef<[SimulationFlag]> + Send + Sync,
+    {
+        self.provider_for(block_id.as_ref()).await.simulate_transactions(block_id, transactions, simulation_flags).await
+    }
+
+    async fn trace_block_transactions<B>(&self, block_id: B) -> Result<Vec<TransactionTraceWithHash>, ProviderError>
+    where
+        B: AsRef<BlockId> + Send + Sync,
+    {
+        self.provider_for(block_id.as_ref()).await.trace_block_transactions(block_id).await
+    }
+
+    async fn batch_requests<R>(&self, requests: R) -> Result<Vec<ProviderResponseData>, ProviderError>
+    where
+        R: AsRef<[ProviderRequestData]> + Send + Sync,
+    {
+        // The requests of a batch can target different blocks, the batches are sent to the full node
+        self.latest.batch_requests(requests).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use starknet::core::types::BlockTag;
+
+    #[test]
+    fn test_is_historical() {
+        // Given
+        let (head, threshold) = (100, 10);
+
+        // When
+        let historical = [
+            is_historical(&BlockId::Number(89), head, threshold),
+            is_historical(&BlockId::Number(90), head, threshold),
+            is_historical(&BlockId::Number(101), head, threshold),
+            is_historical(&BlockId::Hash(Felt::ONE), head, threshold),
+            is_historical(&BlockId::Tag(BlockTag::Latest), head, threshold),
+        ];
+
+        // Then
+        assert_eq!(historical, [true, false, false, true, false]);
+    }
+}
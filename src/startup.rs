@@ -0,0 +1,1139 @@
+use crate::{
+    constants::REPLAY_MODE,
+    eth_rpc::middleware::{admin_access::AdminApiKeys, concurrency::ConcurrencyLimits},
+    pool::constants::ONE_TENTH_ETH,
+    providers::{
+        eth_provider::{
+            constant::{NodeMode, PriorityFeePolicy},
+            database::{archive::ArchiveConfig, parse_collection_read_preferences, parse_read_preference, ReadConfig},
+            starknet::{kakarot_core::KAKAROT_ADDRESS, NativeToken},
+        },
+        sn_provider::StarknetProvider,
+    },
+};
+use alloy_primitives::{Address, B256, U256};
+use mongodb::bson::doc;
+use starknet::{
+    core::types::{BlockId, BlockTag, Felt},
+    providers::{jsonrpc::HttpTransport, JsonRpcClient, Provider},
+};
+use std::{fmt, net::SocketAddr, str::FromStr};
+use url::Url;
+
+/// A single problem detected while validating the node configuration at startup.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StartupIssue {
+    /// The name of the checked item (environment variable, service, account...).
+    pub subject: String,
+    /// A description of the problem.
+    pub problem: String,
+    /// A hint on how to fix the problem.
+    pub hint: String,
+}
+
+impl fmt::Display for StartupIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {} (hint: {})", self.subject, self.problem, self.hint)
+    }
+}
+
+/// The list of problems detected while validating the node configuration at startup.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StartupReport {
+    issues: Vec<StartupIssue>,
+}
+
+impl StartupReport {
+    /// Records a new issue in the report.
+    pub fn push(&mut self, subject: impl Into<String>, problem: impl Into<String>, hint: impl Into<String>) {
+        self.issues.push(StartupIssue { subject: subject.into(), problem: problem.into(), hint: hint.into() });
+    }
+
+    /// Returns the issues recorded in the report.
+    pub fn issues(&self) -> &[StartupIssue] {
+        &self.issues
+    }
+
+    /// Returns true if no issue was recorded.
+    pub fn is_empty(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
+/// Error returned when the startup validation detected at least one problem.
+#[derive(Debug)]
+pub struct StartupError(pub StartupReport);
+
+impl std::error::Error for StartupError {}
+
+impl fmt::Display for StartupError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "startup validation failed with {} issue(s):", self.0.issues.len())?;
+        for issue in &self.0.issues {
+            writeln!(f, "  - {issue}")?;
+        }
+        Ok(())
+    }
+}
+
+/// The configuration of the node, validated at startup.
+#[derive(Debug, Clone)]
+pub struct StartupConfig {
+    /// The Starknet network url.
+    pub network_url: Url,
+    /// The Mongo connection string.
+    pub mongo_connection_string: String,
+    /// The Mongo database name.
+    pub mongo_database_name: String,
+    /// The configuration of the reads of the Mongo database.
+    pub mongo_read_config: ReadConfig,
+    /// The configuration of the archive of the logs and receipts, if the archival is enabled.
+    pub mongo_archive_config: Option<ArchiveConfig>,
+    /// The addresses of the relayers.
+    pub relayers_addresses: Vec<Felt>,
+    /// The addresses of the relayers serving the protocol keepers, if any.
+    pub keeper_relayers_addresses: Vec<Felt>,
+    /// The addresses of the relayers serving the faucet, if any.
+    pub faucet_relayers_addresses: Vec<Felt>,
+}
+
+/// Validates the environment of the node, using `lookup` to read the variables.
+///
+/// Every problem is recorded in the returned report. The configuration is only
+/// returned if all the values it holds were found and valid.
+pub fn validate_env<F>(lookup: F) -> (Option<StartupConfig>, StartupReport)
+where
+    F: Fn(&str) -> Option<String>,
+{
+    let mut report = StartupReport::default();
+
+    let mut required = |name: &str, hint: &str| {
+        let value = lookup(name).filter(|value| !value.trim().is_empty());
+        if value.is_none() {
+            report.push(name, "missing environment variable", hint);
+        }
+        value
+    };
+
+    let network_url = required("STARKNET_NETWORK", "set it to the url of a Starknet JSON-RPC provider");
+    let kakarot_address = required("KAKAROT_ADDRESS", "set it to the address of the deployed Kakarot core contract");
+    let uninitialized_account_class_hash = required(
+        "UNINITIALIZED_ACCOUNT_CLASS_HASH",
+        "set it to the class hash of the declared uninitialized account contract",
+    );
+    let account_contract_class_hash =
+        required("ACCOUNT_CONTRACT_CLASS_HASH", "set it to the class hash of the declared account contract");
+    let rpc_url = required("KAKAROT_RPC_URL", "set it to the socket address to bind, e.g. 127.0.0.1:3030");
+    let mongo_connection_string =
+        required("MONGO_CONNECTION_STRING", "set it to a Mongo connection string, e.g. mongodb://localhost:27017");
+    let mongo_database_name = required("MONGO_DATABASE_NAME", "set it to the name of the indexer database");
+    let relayers_addresses =
+        required("RELAYERS_ADDRESSES", "set it to a comma separated list of funded Starknet relayer addresses");
+    let relayer_private_key = required("RELAYER_PRIVATE_KEY", "set it to the private key of the relayer accounts");
+    // Defaults to `DEFAULT_MAX_FELTS_IN_CALLDATA` when unset.
+    let max_felts_in_calldata = lookup("MAX_FELTS_IN_CALLDATA").filter(|value| !value.trim().is_empty());
+    #[cfg(feature = "hive")]
+    let katana_keys = (
+        required("KATANA_ACCOUNT_ADDRESS", "set it to the address of the deployer account"),
+        required("KATANA_PRIVATE_KEY", "set it to the private key of the deployer account"),
+    );
+    #[cfg(feature = "forwarding")]
+    let main_rpc_url = required("MAIN_RPC_URL", "set it to the url of the node to forward transactions to");
+
+    // The white listed hashes can be empty, but must be set.
+    let white_listed_hashes = lookup("WHITE_LISTED_EIP_155_TRANSACTION_HASHES");
+    if white_listed_hashes.is_none() {
+        report.push(
+            "WHITE_LISTED_EIP_155_TRANSACTION_HASHES",
+            "missing environment variable",
+            "set it to a comma separated list of pre EIP-155 transaction hashes, or leave it empty",
+        );
+    }
+
+    let network_url = network_url.and_then(|url| {
+        Url::parse(&url)
+            .inspect_err(|err| report.push("STARKNET_NETWORK", format!("invalid url {url}: {err}"), "use a valid url"))
+            .ok()
+    });
+
+    let mut check_felt = |name: &str, value: Option<String>| {
+        value.and_then(|value| {
+            Felt::from_str(&value)
+                .inspect_err(|_| report.push(name, format!("invalid felt {value}"), "use a hex encoded felt"))
+                .ok()
+        })
+    };
+    check_felt("KAKAROT_ADDRESS", kakarot_address);
+    check_felt("UNINITIALIZED_ACCOUNT_CLASS_HASH", uninitialized_account_class_hash);
+    check_felt("ACCOUNT_CONTRACT_CLASS_HASH", account_contract_class_hash);
+    check_felt("RELAYER_PRIVATE_KEY", relayer_private_key);
+    #[cfg(feature = "hive")]
+    {
+        check_felt("KATANA_ACCOUNT_ADDRESS", katana_keys.0);
+        check_felt("KATANA_PRIVATE_KEY", katana_keys.1);
+    }
+
+    if let Some(rpc_url) = rpc_url {
+        if SocketAddr::from_str(&rpc_url).is_err() {
+            report.push("KAKAROT_RPC_URL", format!("invalid socket address {rpc_url}"), "use the <ip>:<port> format");
+        }
+    }
+
+    if let Some(max_felts) = max_felts_in_calldata {
+        if usize::from_str(&max_felts).is_err() {
+            report.push("MAX_FELTS_IN_CALLDATA", format!("invalid number {max_felts}"), "use a positive integer");
+        }
+    }
+
+    if let Some(archive_url) = lookup("STARKNET_ARCHIVE_NETWORK").filter(|url| !url.trim().is_empty()) {
+        if Url::parse(&archive_url).is_err() {
+            report.push("STARKNET_ARCHIVE_NETWORK", format!("invalid url {archive_url}"), "use a valid url");
+        }
+    }
+
+    #[cfg(feature = "forwarding")]
+    if let Some(main_rpc_url) = main_rpc_url {
+        if Url::parse(&main_rpc_url).is_err() {
+            report.push("MAIN_RPC_URL", format!("invalid url {main_rpc_url}"), "use a valid url");
+        }
+    }
+
+    if let Some(node_mode) = lookup("NODE_MODE") {
+        if let Err(err) = NodeMode::from_str(&node_mode) {
+            report.push("NODE_MODE", err, "use either full or archive");
+        }
+    }
+
+    if let Some(policy) = lookup("PRIORITY_FEE_POLICY") {
+        if let Err(err) = PriorityFeePolicy::from_str(&policy) {
+            report.push("PRIORITY_FEE_POLICY", err, "use either ignored, forwarded or burned");
+        }
+    }
+
+    if let Some(retention) = lookup("HISTORY_RETENTION_BLOCKS") {
+        if u64::from_str(&retention).is_err() {
+            report.push("HISTORY_RETENTION_BLOCKS", format!("invalid number {retention}"), "use a positive integer");
+        }
+    }
+
+    if let Some(probe_private_key) = lookup("PROBE_PRIVATE_KEY").filter(|key| !key.trim().is_empty()) {
+        if B256::from_str(&probe_private_key).is_err() {
+            report.push("PROBE_PRIVATE_KEY", "invalid private key", "use a hex encoded 32 bytes private key");
+        }
+    }
+
+    let native_token_symbol = lookup("NATIVE_TOKEN_SYMBOL").unwrap_or_else(|| "ETH".to_string());
+    match lookup("NATIVE_TOKEN_DECIMALS").map(|decimals| u8::from_str(&decimals).map_err(|_| decimals)) {
+        Some(Err(decimals)) => {
+            report.push("NATIVE_TOKEN_DECIMALS", format!("invalid decimals {decimals}"), "use a number below 78");
+        }
+        decimals => {
+            if let Err(err) = NativeToken::new(&native_token_symbol, decimals.and_then(Result::ok)) {
+                report.push("NATIVE_TOKEN_DECIMALS", err, "set the decimals of the native token, below 78");
+            }
+        }
+    }
+
+    for key in ["CHAIN_RPC_URLS", "CHAIN_BLOCK_EXPLORER_URLS"] {
+        let urls = lookup(key).unwrap_or_default();
+        for url in urls.split(',').map(str::trim).filter(|url| !url.is_empty()) {
+            if Url::parse(url).is_err() {
+                report.push(key, format!("invalid url {url}"), "use a comma separated list of urls");
+            }
+        }
+    }
+
+    if let Some(value) = lookup("RPC_MAX_REQUEST_BODY_SIZE") {
+        if u32::from_str(&value).is_err() {
+            report.push(
+                "RPC_MAX_REQUEST_BODY_SIZE",
+                format!("invalid size {value}"),
+                "use a number of bytes below 4 GiB",
+            );
+        }
+    }
+
+    if let Some(value) = lookup("RPC_MAX_CONNECTIONS") {
+        if u32::from_str(&value).is_err() {
+            report.push("RPC_MAX_CONNECTIONS", format!("invalid number {value}"), "use a positive integer");
+        }
+    }
+
+    if let Some(value) = lookup("RPC_CONCURRENCY_LIMITS") {
+        if let Err(err) = ConcurrencyLimits::from_str(&value) {
+            report.push(
+                "RPC_CONCURRENCY_LIMITS",
+                err.to_string(),
+                "use a comma separated list of <namespace or method>=<limit>, e.g. debug=2,eth_call=16",
+            );
+        }
+    }
+
+    let admin_api_keys = lookup("ADMIN_API_KEYS").map(|value| AdminApiKeys::from_str(&value)).transpose();
+    match admin_api_keys {
+        Ok(keys) => {
+            // The admin namespace denies every call without a key, enabling it without keys is a
+            // misconfiguration
+            let enabled = lookup("ADMIN_API_ENABLED").is_some_and(|enabled| enabled == "true");
+            if enabled && keys.unwrap_or_default().is_empty() {
+                report.push(
+                    "ADMIN_API_KEYS",
+                    "the admin API is enabled without API keys",
+                    "set it to a comma separated list of <read or admin>:<key>, or disable ADMIN_API_ENABLED",
+                );
+            }
+        }
+        Err(err) => {
+            report.push("ADMIN_API_KEYS", err.to_string(), "use a comma separated list of <read or admin>:<key>");
+        }
+    }
+
+    // A zero interval would let the admin keys drain the relayers with account deployments
+    if let Some(value) = lookup("DEPLOY_ACCOUNT_INTERVAL_SECS") {
+        if !u64::from_str(&value).is_ok_and(|interval| interval > 0) {
+            report.push(
+                "DEPLOY_ACCOUNT_INTERVAL_SECS",
+                format!("invalid interval {value}"),
+                "use a positive number of seconds",
+            );
+        }
+    }
+
+    // A zero lease would expire before being renewed, the instances electing each other in turn
+    if let Some(value) = lookup("INSTANCE_LEASE_TTL_SECS") {
+        if !u64::from_str(&value).is_ok_and(|ttl| ttl > 0) {
+            report.push(
+                "INSTANCE_LEASE_TTL_SECS",
+                format!("invalid lease duration {value}"),
+                "use a positive number of seconds",
+            );
+        }
+    }
+
+    // The instance id is the owner of the writer lease, it defaults to the hostname if empty
+    if let Some(instance_id) = lookup("INSTANCE_ID").filter(|id| !id.is_empty()) {
+        if instance_id.chars().any(char::is_whitespace) {
+            report.push(
+                "INSTANCE_ID",
+                format!("invalid instance id {instance_id:?}"),
+                "use an id without whitespaces, or leave it empty to use the hostname",
+            );
+        }
+    }
+
+    for key in [
+        "TRACE_MAX_STRUCT_LOGS",
+        "TRACE_MAX_CALL_FRAMES",
+        "TRACE_MAX_MEMORY_BYTES",
+        "TRACE_CACHE_TTL_SECS",
+        "STARKNET_BATCH_WINDOW_MS",
+        "STARKNET_BATCH_MAX_SIZE",
+        "STARKNET_CIRCUIT_BREAKER_THRESHOLD",
+        "STARKNET_CIRCUIT_BREAKER_RESET_MS",
+        "STARKNET_ARCHIVE_THRESHOLD_BLOCKS",
+        "RPC_CONCURRENCY_QUEUE_TIMEOUT_MS",
+        "NONCE_LEASE_TIMEOUT_SECS",
+        "BLOCK_GAS_LIMIT_REFRESH_INTERVAL_SECS",
+        "PENDING_BLOCK_POLL_INTERVAL_MS",
+        "PENDING_RECEIPTS_TTL_SECS",
+        "RAW_TRANSACTIONS_RETENTION_SECS",
+        "DROPPED_TRANSACTIONS_RETENTION_SECS",
+        "ARCHIVE_HOT_WINDOW_BLOCKS",
+        "ARCHIVE_BATCH_BLOCKS",
+        "ARCHIVE_INTERVAL_SECS",
+        "FAUCET_AMOUNT_WEI",
+        "FAUCET_INTERVAL_SECS",
+        "PROBE_INTERVAL_SECS",
+        "PROBE_TIMEOUT_SECS",
+        "FIREHOSE_START_BLOCK",
+        "FIREHOSE_POLL_INTERVAL_MS",
+        "CHAOS_STARKNET_DELAY_MS",
+        "CHAOS_SEED",
+        "META_TX_BUDGET_INTERVAL_SECS",
+        "TRACE_PUBLIC_MAX_STRUCT_LOGS",
+        "TRACE_PUBLIC_MAX_CALL_FRAMES",
+        "TRACE_PUBLIC_CONCURRENCY",
+        "TRACE_PUBLIC_QUEUE_SIZE",
+        "TRACE_PUBLIC_QUEUE_TIMEOUT_SECS",
+    ] {
+        if let Some(value) = lookup(key) {
+            if u64::from_str(&value).is_err() {
+                report.push(key, format!("invalid number {value}"), "use a positive integer");
+            }
+        }
+    }
+
+    for key in [
+        "TRACE_ENABLE_MEMORY",
+        "TRACE_ENABLE_STACK",
+        "TRACE_ENABLE_STORAGE",
+        "ADMIN_API_ENABLED",
+        "DRY_RUN",
+        "REPLAY_MODE",
+        "STRICT_EIP155",
+        "STARKNET_FALLBACK_PERSIST",
+        "RPC_TCP_KEEPALIVE",
+        "RPC_TCP_NODELAY",
+        "FIREHOSE_TRACES",
+        "TRACE_PUBLIC_TIER",
+        "INSTANCE_COORDINATION_ENABLED",
+    ] {
+        if let Some(value) = lookup(key) {
+            if bool::from_str(&value).is_err() {
+                report.push(key, format!("invalid boolean {value}"), "use either true or false");
+            }
+        }
+    }
+
+    for key in [
+        "CHAOS_STARKNET_DROP_PROBABILITY",
+        "CHAOS_MONGO_WRITE_FAILURE_PROBABILITY",
+        "CHAOS_RELAYER_DUPLICATE_PROBABILITY",
+    ] {
+        if let Some(value) = lookup(key) {
+            if !f64::from_str(&value).is_ok_and(|probability| (0.0..=1.0).contains(&probability)) {
+                report.push(key, format!("invalid probability {value}"), "use a number between 0 and 1");
+            }
+        }
+    }
+
+    for hash in white_listed_hashes.unwrap_or_default().replace(' ', "").split(',').filter(|hash| !hash.is_empty()) {
+        if B256::from_str(hash).is_err() {
+            report.push(
+                "WHITE_LISTED_EIP_155_TRANSACTION_HASHES",
+                format!("invalid transaction hash {hash}"),
+                "use 32 bytes hex encoded hashes",
+            );
+        }
+    }
+
+    for key in ["PRIORITY_SENDERS", "KEEPER_SENDERS", "FAUCET_SENDERS", "DEPLOYER_ALLOWLIST"] {
+        let senders = lookup(key).unwrap_or_default().replace(' ', "");
+        for address in senders.split(',').filter(|address| !address.is_empty()) {
+            if Address::from_str(address).is_err() {
+                report.push(
+                    key,
+                    format!("invalid address {address}"),
+                    "use a comma separated list of hex encoded Ethereum addresses",
+                );
+            }
+        }
+    }
+
+    #[cfg(feature = "meta-tx")]
+    {
+        if let Some(forwarder) = lookup("META_TX_FORWARDER").filter(|forwarder| !forwarder.trim().is_empty()) {
+            if Address::from_str(&forwarder).is_err() {
+                report.push("META_TX_FORWARDER", format!("invalid address {forwarder}"), "use a hex encoded address");
+            }
+        }
+        if let Some(sponsors) = lookup("META_TX_SPONSORS") {
+            if let Err(err) = crate::meta_tx::parse_sponsors(&sponsors) {
+                report.push("META_TX_SPONSORS", err, "use a comma separated list of <private key>:<max gas>:<budget>");
+            }
+        }
+    }
+
+    // The read configuration is optional, empty values fall back to the defaults
+    let optional = |name: &str| lookup(name).filter(|value| !value.trim().is_empty());
+    let mut mongo_read_config =
+        ReadConfig { connection_string: optional("MONGO_READ_CONNECTION_STRING"), ..Default::default() };
+    if let Some(mode) = optional("MONGO_READ_PREFERENCE") {
+        match parse_read_preference(&mode) {
+            Ok(read_preference) => mongo_read_config.read_preference = Some(read_preference),
+            Err(err) => report.push("MONGO_READ_PREFERENCE", err, "use a MongoDB read preference mode"),
+        }
+    }
+    if let Some(preferences) = optional("MONGO_COLLECTION_READ_PREFERENCES") {
+        match parse_collection_read_preferences(&preferences) {
+            Ok(preferences) => mongo_read_config.collection_read_preferences = preferences,
+            Err(err) => report.push(
+                "MONGO_COLLECTION_READ_PREFERENCES",
+                err,
+                "use a comma separated list of collection:mode, e.g. logs:secondary",
+            ),
+        }
+    }
+
+    // The archival is enabled by the name of the archive database, which must differ from the hot one
+    let mongo_archive_config = optional("MONGO_ARCHIVE_DATABASE_NAME").map(|database_name| ArchiveConfig {
+        connection_string: optional("MONGO_ARCHIVE_CONNECTION_STRING"),
+        database_name,
+    });
+    if let Some(archive) = &mongo_archive_config {
+        if archive.connection_string.is_none() && mongo_database_name.as_ref() == Some(&archive.database_name) {
+            report.push(
+                "MONGO_ARCHIVE_DATABASE_NAME",
+                "the archive database is the indexer database",
+                "use a distinct database name or connection string",
+            );
+        }
+    }
+
+    let mut parse_relayers = |name: &str, addresses: String| {
+        addresses
+            .replace(' ', "")
+            .split(',')
+            .filter(|address| !address.is_empty())
+            .filter_map(|address| {
+                Felt::from_str(address)
+                    .inspect_err(|_| {
+                        report.push(name, format!("invalid address {address}"), "use hex encoded felts");
+                    })
+                    .ok()
+            })
+            .collect::<Vec<_>>()
+    };
+    let relayers_addresses = relayers_addresses.map(|addresses| parse_relayers("RELAYERS_ADDRESSES", addresses));
+    // The relayers of the other traffic classes are optional, the users relayers serve the
+    // classes without relayers
+    let keeper_relayers_addresses = lookup("KEEPER_RELAYERS_ADDRESSES")
+        .map(|addresses| parse_relayers("KEEPER_RELAYERS_ADDRESSES", addresses))
+        .unwrap_or_default();
+    let faucet_relayers_addresses = lookup("FAUCET_RELAYERS_ADDRESSES")
+        .map(|addresses| parse_relayers("FAUCET_RELAYERS_ADDRESSES", addresses))
+        .unwrap_or_default();
+
+    // The faucet pays out from its own relayers, never from the relayers of the users
+    #[cfg(feature = "faucet")]
+    if faucet_relayers_addresses.is_empty() {
+        report.push(
+            "FAUCET_RELAYERS_ADDRESSES",
+            "the faucet is enabled without faucet relayers",
+            "set it to a comma separated list of funded Starknet relayer addresses dedicated to the faucet",
+        );
+    }
+
+    // A relayer serves a single traffic class
+    let mut registered = relayers_addresses.clone().unwrap_or_default();
+    for (key, addresses) in [
+        ("KEEPER_RELAYERS_ADDRESSES", &keeper_relayers_addresses),
+        ("FAUCET_RELAYERS_ADDRESSES", &faucet_relayers_addresses),
+    ] {
+        for address in addresses {
+            if registered.contains(address) {
+                report.push(
+                    key,
+                    format!("relayer {address:#x} already serves another traffic class"),
+                    "use distinct relayers for each traffic class",
+                );
+            } else {
+                registered.push(*address);
+            }
+        }
+    }
+
+    let config = match (network_url, mongo_connection_string, mongo_database_name, relayers_addresses) {
+        (Some(network_url), Some(mongo_connection_string), Some(mongo_database_name), Some(relayers_addresses))
+            if report.is_empty() =>
+        {
+            Some(StartupConfig {
+                network_url,
+                mongo_connection_string,
+                mongo_database_name,
+                mongo_read_config,
+                mongo_archive_config,
+                relayers_addresses,
+                keeper_relayers_addresses,
+                faucet_relayers_addresses,
+            })
+        }
+        _ => None,
+    };
+
+    (config, report)
+}
+
+/// Validates the configuration of the node before it binds its port.
+///
+/// The validation checks the environment, the database connectivity, the presence
+/// of the Kakarot core contract on Starknet and the balances of the relayers. All
+/// the problems are collected and returned at once.
+pub async fn validate_startup() -> Result<StartupConfig, StartupError> {
+    let (config, mut report) = validate_env(|name| std::env::var(name).ok());
+    let Some(config) = config else {
+        return Err(StartupError(report));
+    };
+
+    // Check the database connectivity
+    let database_name = &config.mongo_database_name;
+    let ping = |connection_string: String| async move {
+        let client = mongodb::Client::with_uri_str(&connection_string).await?;
+        client.database(database_name).run_command(doc! {"ping": 1}).await
+    };
+    if let Err(err) = ping(config.mongo_connection_string.clone()).await {
+        report.push(
+            "MongoDB",
+            format!("failed to reach the database: {err}"),
+            "check MONGO_CONNECTION_STRING and that the database is running",
+        );
+    }
+    if let Some(read_connection_string) = config.mongo_read_config.connection_string.clone() {
+        if let Err(err) = ping(read_connection_string).await {
+            report.push(
+                "MongoDB",
+                format!("failed to reach the read database: {err}"),
+                "check MONGO_READ_CONNECTION_STRING and that the database is running",
+            );
+        }
+    }
+
+    // In replay mode, the node serves the database only and doesn't access Starknet
+    if !*REPLAY_MODE {
+        validate_starknet(&config, &mut report).await;
+    }
+
+    if report.is_empty() {
+        Ok(config)
+    } else {
+        Err(StartupError(report))
+    }
+}
+
+/// Checks the Starknet side of the configuration: the Kakarot core contract and the funds of
+/// the relayers.
+async fn validate_starknet(config: &StartupConfig, report: &mut StartupReport) {
+    // Check the Kakarot core contract is deployed
+    let starknet_provider = StarknetProvider::new(JsonRpcClient::new(HttpTransport::new(config.network_url.clone())));
+    if let Err(err) = starknet_provider.get_class_hash_at(BlockId::Tag(BlockTag::Pending), *KAKAROT_ADDRESS).await {
+        report.push(
+            "KAKAROT_ADDRESS",
+            format!("no contract found at {:#x}: {err}", *KAKAROT_ADDRESS),
+            "check KAKAROT_ADDRESS and that STARKNET_NETWORK points to the right network",
+        );
+    }
+
+    // Check that at least one relayer of the users is funded
+    let mut funded_relayers = 0usize;
+    let relayers = config
+        .relayers_addresses
+        .iter()
+        .chain(&config.keeper_relayers_addresses)
+        .chain(&config.faucet_relayers_addresses);
+    for address in relayers {
+        match starknet_provider.balance_at(*address, BlockId::Tag(BlockTag::Pending)).await {
+            Ok(balance) if balance >= U256::from(ONE_TENTH_ETH) => {
+                if config.relayers_addresses.contains(address) {
+                    funded_relayers += 1;
+                }
+            }
+            Ok(balance) => {
+                tracing::warn!(relayer = ?address, %balance, "relayer balance is below the relaying threshold");
+            }
+            Err(err) => report.push(
+                format!("relayer {address:#x}"),
+                format!("failed to fetch balance: {err}"),
+                "check STARKNET_NETWORK and that the relayer account is deployed",
+            ),
+        }
+    }
+    if funded_relayers == 0 {
+        report.push(
+            "RELAYERS_ADDRESSES",
+            "no relayer holds enough funds to relay transactions",
+            "fund at least one relayer with 0.1 ETH or more",
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn valid_env() -> HashMap<&'static str, &'static str> {
+        #[allow(unused_mut)]
+        let mut env = HashMap::from([
+            ("STARKNET_NETWORK", "http://0.0.0.0:5050"),
+            ("KAKAROT_ADDRESS", "0x1"),
+            ("UNINITIALIZED_ACCOUNT_CLASS_HASH", "0x2"),
+            ("ACCOUNT_CONTRACT_CLASS_HASH", "0x3"),
+            ("KAKAROT_RPC_URL", "127.0.0.1:3030"),
+            ("MONGO_CONNECTION_STRING", "mongodb://localhost:27017"),
+            ("MONGO_DATABASE_NAME", "kakarot-local"),
+            ("RELAYERS_ADDRESSES", "0x4,0x5"),
+            ("RELAYER_PRIVATE_KEY", "0x6"),
+            ("MAX_FELTS_IN_CALLDATA", "22500"),
+            ("WHITE_LISTED_EIP_155_TRANSACTION_HASHES", ""),
+            ("KATANA_ACCOUNT_ADDRESS", "0x7"),
+            ("KATANA_PRIVATE_KEY", "0x8"),
+            ("MAIN_RPC_URL", "http://0.0.0.0:8545"),
+        ]);
+        #[cfg(feature = "faucet")]
+        env.insert("FAUCET_RELAYERS_ADDRESSES", "0x9");
+        env
+    }
+
+    #[test]
+    fn test_validate_env_valid() {
+        // Given
+        let env = valid_env();
+
+        // When
+        let (config, report) = validate_env(|name| env.get(name).map(ToString::to_string));
+
+        // Then
+        assert!(report.is_empty(), "{report:?}");
+        let config = config.expect("valid config");
+        assert_eq!(config.relayers_addresses, vec![Felt::from(4), Felt::from(5)]);
+        assert_eq!(config.mongo_database_name, "kakarot-local");
+    }
+
+    #[test]
+    fn test_validate_env_mongo_read_config() {
+        // Given
+        let mut env = valid_env();
+        env.insert("MONGO_READ_PREFERENCE", "secondaryPreferred");
+        env.insert("MONGO_COLLECTION_READ_PREFERENCES", "logs:secondary");
+
+        // When
+        let (config, report) = validate_env(|name| env.get(name).map(ToString::to_string));
+
+        // Then
+        assert!(report.is_empty(), "{report:?}");
+        let read_config = config.expect("valid config").mongo_read_config;
+        assert!(read_config.connection_string.is_none());
+        assert!(read_config.read_preference.is_some());
+        assert!(read_config.collection_read_preferences.contains_key("logs"));
+
+        // When
+        env.insert("MONGO_READ_PREFERENCE", "tertiary");
+        let (config, report) = validate_env(|name| env.get(name).map(ToString::to_string));
+
+        // Then
+        assert!(config.is_none());
+        let subjects = report.issues().iter().map(|issue| issue.subject.as_str()).collect::<Vec<_>>();
+        assert_eq!(subjects, vec!["MONGO_READ_PREFERENCE"]);
+    }
+
+    #[test]
+    fn test_validate_env_mongo_archive_config() {
+        // Given
+        let mut env = valid_env();
+        env.insert("MONGO_ARCHIVE_DATABASE_NAME", "kakarot-archive");
+
+        // When
+        let (config, report) = validate_env(|name| env.get(name).map(ToString::to_string));
+
+        // Then
+        assert!(report.is_empty(), "{report:?}");
+        let archive_config = config.expect("valid config").mongo_archive_config.expect("archive config");
+        assert!(archive_config.connection_string.is_none());
+        assert_eq!(archive_config.database_name, "kakarot-archive");
+
+        // When
+        env.insert("MONGO_ARCHIVE_DATABASE_NAME", "kakarot-local");
+        let (config, report) = validate_env(|name| env.get(name).map(ToString::to_string));
+
+        // Then
+        assert!(config.is_none());
+        let subjects = report.issues().iter().map(|issue| issue.subject.as_str()).collect::<Vec<_>>();
+        assert_eq!(subjects, vec!["MONGO_ARCHIVE_DATABASE_NAME"]);
+    }
+
+    #[test]
+    fn test_validate_env_traffic_class_relayers() {
+        // Given
+        let mut env = valid_env();
+        env.insert("KEEPER_RELAYERS_ADDRESSES", "0x7");
+        env.insert("FAUCET_RELAYERS_ADDRESSES", "0x8, 0x9");
+        env.insert("FAUCET_SENDERS", "0x0000000000000000000000000000000000000001");
+
+        // When
+        let (config, report) = validate_env(|name| env.get(name).map(ToString::to_string));
+
+        // Then
+        assert!(report.is_empty(), "{report:?}");
+        let config = config.expect("valid config");
+        assert_eq!(config.keeper_relayers_addresses, vec![Felt::from(7)]);
+        assert_eq!(config.faucet_relayers_addresses, vec![Felt::from(8), Felt::from(9)]);
+
+        // When
+        env.insert("FAUCET_RELAYERS_ADDRESSES", "0x4,0x7");
+        env.insert("KEEPER_SENDERS", "0x1");
+        let (config, report) = validate_env(|name| env.get(name).map(ToString::to_string));
+
+        // Then
+        assert!(config.is_none());
+        let subjects = report.issues().iter().map(|issue| issue.subject.as_str()).collect::<Vec<_>>();
+        assert_eq!(subjects, vec!["KEEPER_SENDERS", "FAUCET_RELAYERS_ADDRESSES", "FAUCET_RELAYERS_ADDRESSES"]);
+    }
+
+    #[cfg(feature = "faucet")]
+    #[test]
+    fn test_validate_env_faucet_without_relayers() {
+        // Given
+        let mut env = valid_env();
+        env.remove("FAUCET_RELAYERS_ADDRESSES");
+
+        // When
+        let (config, report) = validate_env(|name| env.get(name).map(ToString::to_string));
+
+        // Then
+        assert!(config.is_none());
+        let subjects = report.issues().iter().map(|issue| issue.subject.as_str()).collect::<Vec<_>>();
+        assert_eq!(subjects, vec!["FAUCET_RELAYERS_ADDRESSES"]);
+    }
+
+    #[test]
+    fn test_validate_env_max_felts_in_calldata() {
+        // Given
+        let mut env = valid_env();
+        env.remove("MAX_FELTS_IN_CALLDATA");
+
+        // When
+        let (config, report) = validate_env(|name| env.get(name).map(ToString::to_string));
+
+        // Then
+        assert!(report.is_empty(), "{report:?}");
+        assert!(config.is_some());
+
+        // When
+        env.insert("MAX_FELTS_IN_CALLDATA", "many");
+        let (config, report) = validate_env(|name| env.get(name).map(ToString::to_string));
+
+        // Then
+        assert!(config.is_none());
+        let subjects = report.issues().iter().map(|issue| issue.subject.as_str()).collect::<Vec<_>>();
+        assert_eq!(subjects, vec!["MAX_FELTS_IN_CALLDATA"]);
+    }
+
+    #[test]
+    fn test_validate_env_reports_all_issues() {
+        // Given
+        let mut env = valid_env();
+        env.remove("MONGO_CONNECTION_STRING");
+        env.remove("RELAYER_PRIVATE_KEY");
+        env.insert("KAKAROT_ADDRESS", "not a felt");
+        env.insert("KAKAROT_RPC_URL", "localhost");
+
+        // When
+        let (config, report) = validate_env(|name| env.get(name).map(ToString::to_string));
+
+        // Then
+        assert!(config.is_none());
+        let subjects = report.issues().iter().map(|issue| issue.subject.as_str()).collect::<Vec<_>>();
+        assert_eq!(
+            subjects,
+            vec!["MONGO_CONNECTION_STRING", "RELAYER_PRIVATE_KEY", "KAKAROT_ADDRESS", "KAKAROT_RPC_URL"]
+        );
+    }
+
+    #[test]
+    fn test_validate_env_invalid_priority_senders() {
+        // Given
+        let mut env = valid_env();
+        env.insert("PRIORITY_SENDERS", "0x0000000000000000000000000000000000000001, 0x2");
+
+        // When
+        let (config, report) = validate_env(|name| env.get(name).map(ToString::to_string));
+
+        // Then
+        assert!(config.is_none());
+        let subjects = report.issues().iter().map(|issue| issue.subject.as_str()).collect::<Vec<_>>();
+        assert_eq!(subjects, vec!["PRIORITY_SENDERS"]);
+    }
+
+    #[test]
+    fn test_validate_env_invalid_rpc_server_options() {
+        // Given
+        let mut env = valid_env();
+        env.insert("RPC_MAX_REQUEST_BODY_SIZE", "10MB");
+        env.insert("RPC_MAX_CONNECTIONS", "-1");
+        env.insert("RPC_TCP_KEEPALIVE", "yes");
+
+        // When
+        let (config, report) = validate_env(|name| env.get(name).map(ToString::to_string));
+
+        // Then
+        assert!(config.is_none());
+        let subjects = report.issues().iter().map(|issue| issue.subject.as_str()).collect::<Vec<_>>();
+        assert_eq!(subjects, vec!["RPC_MAX_REQUEST_BODY_SIZE", "RPC_MAX_CONNECTIONS", "RPC_TCP_KEEPALIVE"]);
+    }
+
+    #[test]
+    fn test_validate_env_native_token() {
+        // Given
+        let mut env = valid_env();
+        env.insert("NATIVE_TOKEN_SYMBOL", "TKN");
+
+        // When
+        let (config, report) = validate_env(|name| env.get(name).map(ToString::to_string));
+
+        // Then
+        assert!(config.is_none());
+        let subjects = report.issues().iter().map(|issue| issue.subject.as_str()).collect::<Vec<_>>();
+        assert_eq!(subjects, vec!["NATIVE_TOKEN_DECIMALS"]);
+
+        // When
+        env.insert("NATIVE_TOKEN_DECIMALS", "6");
+        let (config, report) = validate_env(|name| env.get(name).map(ToString::to_string));
+
+        // Then
+        assert!(report.is_empty(), "{report:?}");
+        assert!(config.is_some());
+    }
+
+    #[test]
+    fn test_validate_env_chain_metadata_urls() {
+        // Given
+        let mut env = valid_env();
+        env.insert("CHAIN_RPC_URLS", "https://rpc.kakarot.org, https://rpc2.kakarot.org");
+        env.insert("CHAIN_BLOCK_EXPLORER_URLS", "explorer.kakarot.org");
+
+        // When
+        let (config, report) = validate_env(|name| env.get(name).map(ToString::to_string));
+
+        // Then
+        assert!(config.is_none());
+        let subjects = report.issues().iter().map(|issue| issue.subject.as_str()).collect::<Vec<_>>();
+        assert_eq!(subjects, vec!["CHAIN_BLOCK_EXPLORER_URLS"]);
+    }
+
+    #[test]
+    fn test_validate_env_invalid_concurrency_limits() {
+        // Given
+        let mut env = valid_env();
+        env.insert("RPC_CONCURRENCY_LIMITS", "debug=2,eth_call");
+
+        // When
+        let (config, report) = validate_env(|name| env.get(name).map(ToString::to_string));
+
+        // Then
+        assert!(config.is_none());
+        let subjects = report.issues().iter().map(|issue| issue.subject.as_str()).collect::<Vec<_>>();
+        assert_eq!(subjects, vec!["RPC_CONCURRENCY_LIMITS"]);
+    }
+
+    #[test]
+    fn test_validate_env_starknet_archive() {
+        // Given
+        let mut env = valid_env();
+        env.insert("STARKNET_ARCHIVE_NETWORK", "http://0.0.0.0:5051");
+        env.insert("STARKNET_ARCHIVE_THRESHOLD_BLOCKS", "1000");
+
+        // When
+        let (config, report) = validate_env(|name| env.get(name).map(ToString::to_string));
+
+        // Then
+        assert!(report.is_empty(), "{report:?}");
+        assert!(config.is_some());
+
+        // When
+        env.insert("STARKNET_ARCHIVE_NETWORK", "archive");
+        env.insert("STARKNET_ARCHIVE_THRESHOLD_BLOCKS", "-1");
+        let (config, report) = validate_env(|name| env.get(name).map(ToString::to_string));
+
+        // Then
+        assert!(config.is_none());
+        let subjects = report.issues().iter().map(|issue| issue.subject.as_str()).collect::<Vec<_>>();
+        assert_eq!(subjects, vec!["STARKNET_ARCHIVE_NETWORK", "STARKNET_ARCHIVE_THRESHOLD_BLOCKS"]);
+    }
+
+    #[test]
+    fn test_validate_env_firehose() {
+        // Given
+        let mut env = valid_env();
+        env.insert("FIREHOSE_START_BLOCK", "100");
+        env.insert("FIREHOSE_TRACES", "false");
+
+        // When
+        let (config, report) = validate_env(|name| env.get(name).map(ToString::to_string));
+
+        // Then
+        assert!(report.is_empty(), "{report:?}");
+        assert!(config.is_some());
+
+        // When
+        env.insert("FIREHOSE_POLL_INTERVAL_MS", "1s");
+        env.insert("FIREHOSE_TRACES", "no");
+        let (config, report) = validate_env(|name| env.get(name).map(ToString::to_string));
+
+        // Then
+        assert!(config.is_none());
+        let subjects = report.issues().iter().map(|issue| issue.subject.as_str()).collect::<Vec<_>>();
+        assert_eq!(subjects, vec!["FIREHOSE_POLL_INTERVAL_MS", "FIREHOSE_TRACES"]);
+    }
+
+    #[test]
+    fn test_validate_env_chaos() {
+        // Given
+        let mut env = valid_env();
+        env.insert("CHAOS_STARKNET_DROP_PROBABILITY", "0.1");
+        env.insert("CHAOS_SEED", "42");
+
+        // When
+        let (config, report) = validate_env(|name| env.get(name).map(ToString::to_string));
+
+        // Then
+        assert!(report.is_empty(), "{report:?}");
+        assert!(config.is_some());
+
+        // When
+        env.insert("CHAOS_MONGO_WRITE_FAILURE_PROBABILITY", "1.5");
+        env.insert("CHAOS_STARKNET_DELAY_MS", "1s");
+        let (config, report) = validate_env(|name| env.get(name).map(ToString::to_string));
+
+        // Then
+        assert!(config.is_none());
+        let subjects = report.issues().iter().map(|issue| issue.subject.as_str()).collect::<Vec<_>>();
+        assert_eq!(subjects, vec!["CHAOS_STARKNET_DELAY_MS", "CHAOS_MONGO_WRITE_FAILURE_PROBABILITY"]);
+    }
+
+    #[cfg(feature = "meta-tx")]
+    #[test]
+    fn test_validate_env_meta_tx() {
+        // Given
+        let mut env = valid_env();
+        env.insert("META_TX_FORWARDER", "0x0000000000000000000000000000000000000001");
+        env.insert(
+            "META_TX_SPONSORS",
+            "0x0000000000000000000000000000000000000000000000000000000000000001:500000:1000000000000000000",
+        );
+
+        // When
+        let (config, report) = validate_env(|name| env.get(name).map(ToString::to_string));
+
+        // Then
+        assert!(report.is_empty(), "{report:?}");
+        assert!(config.is_some());
+
+        // When
+        env.insert("META_TX_SPONSORS", "0x1:500000");
+        env.insert("META_TX_BUDGET_INTERVAL_SECS", "daily");
+        let (config, report) = validate_env(|name| env.get(name).map(ToString::to_string));
+
+        // Then
+        assert!(config.is_none());
+        let subjects = report.issues().iter().map(|issue| issue.subject.as_str()).collect::<Vec<_>>();
+        assert_eq!(subjects, vec!["META_TX_BUDGET_INTERVAL_SECS", "META_TX_SPONSORS"]);
+    }
+
+    #[test]
+    fn test_validate_env_invalid_admin_api_keys() {
+        // Given
+        let mut env = valid_env();
+        env.insert("ADMIN_API_KEYS", "read:oncall,write:operator");
+
+        // When
+        let (config, report) = validate_env(|name| env.get(name).map(ToString::to_string));
+
+        // Then
+        assert!(config.is_none());
+        let subjects = report.issues().iter().map(|issue| issue.subject.as_str()).collect::<Vec<_>>();
+        assert_eq!(subjects, vec!["ADMIN_API_KEYS"]);
+    }
+
+    #[test]
+    fn test_validate_env_admin_api_without_keys() {
+        // Given
+        let mut env = valid_env();
+        env.insert("ADMIN_API_ENABLED", "true");
+
+        // When
+        let (config, report) = validate_env(|name| env.get(name).map(ToString::to_string));
+        env.insert("ADMIN_API_KEYS", "admin:operator");
+        let (keyed_config, keyed_report) = validate_env(|name| env.get(name).map(ToString::to_string));
+
+        // Then
+        assert!(config.is_none());
+        let subjects = report.issues().iter().map(|issue| issue.subject.as_str()).collect::<Vec<_>>();
+        assert_eq!(subjects, vec!["ADMIN_API_KEYS"]);
+        assert!(keyed_report.is_empty(), "{keyed_report:?}");
+        assert!(keyed_config.is_some());
+    }
+
+    #[test]
+    fn test_validate_env_invalid_deploy_account_interval() {
+        // Given
+        let mut env = valid_env();
+        env.insert("DEPLOY_ACCOUNT_INTERVAL_SECS", "0");
+
+        // When
+        let (config, report) = validate_env(|name| env.get(name).map(ToString::to_string));
+        env.insert("DEPLOY_ACCOUNT_INTERVAL_SECS", "60");
+        let (valid_config, _) = validate_env(|name| env.get(name).map(ToString::to_string));
+
+        // Then
+        assert!(config.is_none());
+        let subjects = report.issues().iter().map(|issue| issue.subject.as_str()).collect::<Vec<_>>();
+        assert_eq!(subjects, vec!["DEPLOY_ACCOUNT_INTERVAL_SECS"]);
+        assert!(valid_config.is_some());
+    }
+
+    #[test]
+    fn test_validate_env_instance_coordination() {
+        // Given
+        let mut env = valid_env();
+        env.insert("INSTANCE_COORDINATION_ENABLED", "yes");
+        env.insert("INSTANCE_LEASE_TTL_SECS", "0");
+        env.insert("INSTANCE_ID", "rpc 1");
+
+        // When
+        let (config, report) = validate_env(|name| env.get(name).map(ToString::to_string));
+        env.insert("INSTANCE_COORDINATION_ENABLED", "true");
+        env.insert("INSTANCE_LEASE_TTL_SECS", "30");
+        env.insert("INSTANCE_ID", "");
+        let (valid_config, _) = validate_env(|name| env.get(name).map(ToString::to_string));
+
+        // Then
+        assert!(config.is_none());
+        let subjects = report.issues().iter().map(|issue| issue.subject.as_str()).collect::<Vec<_>>();
+        assert_eq!(subjects, vec!["INSTANCE_LEASE_TTL_SECS", "INSTANCE_ID", "INSTANCE_COORDINATION_ENABLED"]);
+        assert!(valid_config.is_some());
+    }
+
+    #[test]
+    fn test_validate_env_invalid_dry_run() {
+        // Given
+        let mut env = valid_env();
+        env.insert("DRY_RUN", "1");
+
+        // When
+        let (config, report) = validate_env(|name| env.get(name).map(ToString::to_string));
+        env.insert("DRY_RUN", "true");
+        let (valid_config, _) = validate_env(|name| env.get(name).map(ToString::to_string));
+
+        // Then
+        assert!(config.is_none());
+        let subjects = report.issues().iter().map(|issue| issue.subject.as_str()).collect::<Vec<_>>();
+        assert_eq!(subjects, vec!["DRY_RUN"]);
+        assert!(valid_config.is_some());
+    }
+
+    #[test]
+    fn test_validate_env_invalid_replay_mode() {
+        // Given
+        let mut env = valid_env();
+        env.insert("REPLAY_MODE", "yes");
+
+        // When
+        let (config, report) = validate_env(|name| env.get(name).map(ToString::to_string));
+        env.insert("REPLAY_MODE", "false");
+        let (valid_config, _) = validate_env(|name| env.get(name).map(ToString::to_string));
+
+        // Then
+        assert!(config.is_none());
+        let subjects = report.issues().iter().map(|issue| issue.subject.as_str()).collect::<Vec<_>>();
+        assert_eq!(subjects, vec!["REPLAY_MODE"]);
+        assert!(valid_config.is_some());
+    }
+
+    #[test]
+    fn test_validate_env_invalid_priority_fee_policy() {
+        // Given
+        let mut env = valid_env();
+        env.insert("PRIORITY_FEE_POLICY", "refunded");
+
+        // When
+        let (config, report) = validate_env(|name| env.get(name).map(ToString::to_string));
+        env.insert("PRIORITY_FEE_POLICY", "Burned");
+        let (valid_config, _) = validate_env(|name| env.get(name).map(ToString::to_string));
+
+        // Then
+        assert!(config.is_none());
+        let subjects = report.issues().iter().map(|issue| issue.subject.as_str()).collect::<Vec<_>>();
+        assert_eq!(subjects, vec!["PRIORITY_FEE_POLICY"]);
+        assert!(valid_config.is_some());
+    }
+}
@@ -0,0 +1,111 @@
+use std::{fmt, time::Duration};
+
+/// Records latency samples and computes percentiles over them.
+#[derive(Debug, Default, Clone)]
+pub struct LatencyRecorder {
+    samples: Vec<Duration>,
+}
+
+impl LatencyRecorder {
+    /// Records a new latency sample.
+    pub fn record(&mut self, sample: Duration) {
+        self.samples.push(sample);
+    }
+
+    /// Returns the number of recorded samples.
+    pub fn len(&self) -> usize {
+        self.samples.len()
+    }
+
+    /// Returns true if no sample was recorded.
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+
+    /// Returns the latency at the given percentile (between 0 and 100), using the nearest-rank method.
+    #[allow(clippy::cast_sign_loss)]
+    pub fn percentile(&self, percentile: f64) -> Option<Duration> {
+        if self.samples.is_empty() {
+            return None;
+        }
+        let mut samples = self.samples.clone();
+        samples.sort_unstable();
+
+        let rank = (percentile.clamp(0., 100.) / 100. * samples.len() as f64).ceil() as usize;
+        Some(samples[rank.saturating_sub(1)])
+    }
+
+    /// Returns a report of the recorded samples.
+    pub fn report(&self, name: &'static str, elapsed: Duration) -> LatencyReport {
+        LatencyReport {
+            name,
+            count: self.samples.len(),
+            throughput: self.samples.len() as f64 / elapsed.as_secs_f64().max(f64::EPSILON),
+            p50: self.percentile(50.).unwrap_or_default(),
+            p90: self.percentile(90.).unwrap_or_default(),
+            p99: self.percentile(99.).unwrap_or_default(),
+            max: self.samples.iter().max().copied().unwrap_or_default(),
+        }
+    }
+}
+
+/// A summary of the latencies of a benchmarked operation.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LatencyReport {
+    /// The name of the benchmarked operation.
+    pub name: &'static str,
+    /// The number of samples.
+    pub count: usize,
+    /// The number of samples per second.
+    pub throughput: f64,
+    /// The median latency.
+    pub p50: Duration,
+    /// The 90th percentile latency.
+    pub p90: Duration,
+    /// The 99th percentile latency.
+    pub p99: Duration,
+    /// The maximum latency.
+    pub max: Duration,
+}
+
+impl fmt::Display for LatencyReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:<24} count={:<8} throughput={:>8.2}/s p50={:?} p90={:?} p99={:?} max={:?}",
+            self.name, self.count, self.throughput, self.p50, self.p90, self.p99, self.max
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_latency_recorder_percentiles() {
+        // Given
+        let mut recorder = LatencyRecorder::default();
+        for ms in 1..=100 {
+            recorder.record(Duration::from_millis(ms));
+        }
+
+        // When
+        let report = recorder.report("test", Duration::from_secs(10));
+
+        // Then
+        assert_eq!(report.count, 100);
+        assert!((report.throughput - 10.).abs() < f64::EPSILON);
+        assert_eq!(report.p50, Duration::from_millis(50));
+        assert_eq!(report.p90, Duration::from_millis(90));
+        assert_eq!(report.p99, Duration::from_millis(99));
+        assert_eq!(report.max, Duration::from_millis(100));
+    }
+
+    #[test]
+    fn test_latency_recorder_empty() {
+        let recorder = LatencyRecorder::default();
+        assert!(recorder.is_empty());
+        assert_eq!(recorder.percentile(50.), None);
+    }
+}
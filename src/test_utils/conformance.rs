@@ -0,0 +1,267 @@
+//! Harness checking that the responses of the RPC have the shape expected by the client
+//! libraries (e.g. viem or ethers), described by the conformance vectors of
+//! `tests/conformance/clients.json`.
+//!
+//! A shape is either:
+//! - a string naming a type: `quantity`, `data`, `hash`, `address`, `bloom`, `bool`, `number`,
+//!   `string`, `null` or `any`, or a named shape of the suite prefixed by `#`. A trailing `?`
+//!   allows `null`;
+//! - an array holding the shape of the elements, or an empty array for any array;
+//! - an object holding the shapes of the fields. A trailing `?` on a field name marks it as
+//!   optional, and the `...` field includes the fields of a named shape. Extra fields are
+//!   allowed.
+
+use serde::Deserialize;
+use serde_json::Value;
+use std::{collections::HashMap, fmt, path::Path};
+
+/// A request of a client library, with the shape of the result it expects.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ConformanceVector {
+    /// The client library sending the request, e.g. `viem`.
+    pub client: String,
+    /// The method of the request.
+    pub method: String,
+    /// The parameters of the request. The strings prefixed by `$` are placeholders replaced by
+    /// the values of the test environment.
+    #[serde(default)]
+    pub params: Vec<Value>,
+    /// The shape of the result.
+    pub result: Value,
+}
+
+impl ConformanceVector {
+    /// Returns the parameters of the request, with the placeholders replaced by their value.
+    pub fn params(&self, placeholders: &HashMap<String, Value>) -> Vec<Value> {
+        self.params.iter().map(|param| replace_placeholders(param, placeholders)).collect()
+    }
+}
+
+/// The conformance vectors of the client libraries, with the named shapes they share.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ConformanceSuite {
+    /// The shapes referenced by the vectors as `#<name>`.
+    #[serde(default)]
+    pub shapes: HashMap<String, Value>,
+    /// The vectors of the client libraries.
+    pub vectors: Vec<ConformanceVector>,
+}
+
+/// A value which doesn't have the expected shape.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShapeMismatch {
+    /// The path of the value in the response, e.g. `result.transactions[0].hash`.
+    pub path: String,
+    /// The expected shape.
+    pub expected: String,
+    /// The value found, `None` if the field is missing.
+    pub found: Option<Value>,
+}
+
+impl fmt::Display for ShapeMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.found {
+            Some(found) => write!(f, "{}: expected {}, found {found}", self.path, self.expected),
+            None => write!(f, "{}: expected {}, found a missing field", self.path, self.expected),
+        }
+    }
+}
+
+impl ConformanceSuite {
+    /// Loads the suite from the JSON file.
+    pub fn load(path: impl AsRef<Path>) -> eyre::Result<Self> {
+        Ok(serde_json::from_str(&std::fs::read_to_string(path)?)?)
+    }
+
+    /// Checks that the value has the shape, and returns the mismatch found first, if any.
+    pub fn check(&self, value: &Value, shape: &Value) -> Result<(), ShapeMismatch> {
+        self.check_at("result", value, shape)
+    }
+
+    fn check_at(&self, path: &str, value: &Value, shape: &Value) -> Result<(), ShapeMismatch> {
+        let mismatch =
+            || ShapeMismatch { path: path.to_string(), expected: shape.to_string(), found: Some(value.clone()) };
+
+        match shape {
+            Value::String(shape) => {
+                let (ty, nullable) = shape.strip_suffix('?').map_or((shape.as_str(), false), |ty| (ty, true));
+                if nullable && value.is_null() {
+                    return Ok(());
+                }
+                if let Some(name) = ty.strip_prefix('#') {
+                    return self.check_at(path, value, self.named_shape(path, name)?);
+                }
+                is_of_type(value, ty).then_some(()).ok_or_else(mismatch)
+            }
+            Value::Array(shapes) => {
+                let values = value.as_array().ok_or_else(mismatch)?;
+                let Some(shape) = shapes.first() else {
+                    return Ok(());
+                };
+                values
+                    .iter()
+                    .enumerate()
+                    .try_for_each(|(i, value)| self.check_at(&format!("{path}[{i}]"), value, shape))
+            }
+            Value::Object(fields) => {
+                let object = value.as_object().ok_or_else(mismatch)?;
+                for (key, shape) in fields {
+                    if key == "..." {
+                        let name = shape.as_str().and_then(|name| name.strip_prefix('#')).unwrap_or_default();
+                        self.check_at(path, value, self.named_shape(path, name)?)?;
+                        continue;
+                    }
+
+                    let (key, optional) = key.strip_suffix('?').map_or((key.as_str(), false), |key| (key, true));
+                    match object.get(key) {
+                        Some(field) => self.check_at(&format!("{path}.{key}"), field, shape)?,
+                        None if optional => {}
+                        None => {
+                            return Err(ShapeMismatch {
+                                path: format!("{path}.{key}"),
+                                expected: shape.to_string(),
+                                found: None,
+                            })
+                        }
+                    }
+                }
+                Ok(())
+            }
+            literal => (value == literal).then_some(()).ok_or_else(mismatch),
+        }
+    }
+
+    fn named_shape(&self, path: &str, name: &str) -> Result<&Value, ShapeMismatch> {
+        self.shapes.get(name).ok_or_else(|| ShapeMismatch {
+            path: path.to_string(),
+            expected: format!("the unknown shape #{name}"),
+            found: None,
+        })
+    }
+}
+
+/// Returns true if the value is of the type.
+fn is_of_type(value: &Value, ty: &str) -> bool {
+    let hex = |len: Option<usize>| {
+        value.as_str().and_then(|value| value.strip_prefix("0x")).is_some_and(|digits| {
+            digits.chars().all(|c| c.is_ascii_hexdigit())
+                && digits.len() % 2 == 0
+                && len.map_or(true, |len| digits.len() == 2 * len)
+        })
+    };
+
+    match ty {
+        // A quantity is encoded without leading zeros, as required by the JSON-RPC specification
+        "quantity" => value.as_str().and_then(|value| value.strip_prefix("0x")).is_some_and(|digits| {
+            !digits.is_empty()
+                && digits.chars().all(|c| c.is_ascii_hexdigit())
+                && (digits == "0" || !digits.starts_with('0'))
+        }),
+        "data" => hex(None),
+        "hash" => hex(Some(32)),
+        "address" => hex(Some(20)),
+        "bloom" => hex(Some(256)),
+        "bool" => value.is_boolean(),
+        "number" => value.is_number(),
+        "string" => value.is_string(),
+        "null" => value.is_null(),
+        "any" => true,
+        _ => false,
+    }
+}
+
+/// Replaces the strings prefixed by `$` with the value of the placeholder, if any.
+fn replace_placeholders(value: &Value, placeholders: &HashMap<String, Value>) -> Value {
+    match value {
+        Value::String(s) => {
+            s.strip_prefix('$').and_then(|name| placeholders.get(name)).cloned().unwrap_or_else(|| value.clone())
+        }
+        Value::Array(values) => values.iter().map(|value| replace_placeholders(value, placeholders)).collect(),
+        Value::Object(fields) => {
+            fields.iter().map(|(key, value)| (key.clone(), replace_placeholders(value, placeholders))).collect()
+        }
+        _ => value.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn suite() -> ConformanceSuite {
+        serde_json::from_value(json!({
+            "shapes": {
+                "log": {"address": "address", "topics": ["hash"], "logIndex?": "quantity?"}
+            },
+            "vectors": []
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_check_shape() {
+        // Given
+        let suite = suite();
+        let shape = json!({"...": "#log", "blockNumber": "quantity", "removed": "bool"});
+        let log = json!({
+            "address": "0x0000000000000000000000000000000000000001",
+            "topics": ["0x0000000000000000000000000000000000000000000000000000000000000002"],
+            "blockNumber": "0x1a",
+            "removed": false,
+            "extra": 1
+        });
+
+        let with = |key: &str, value: Value| {
+            let mut log = log.clone();
+            log[key] = value;
+            log
+        };
+
+        // When
+        let valid = suite.check(&log, &shape);
+        let padded = suite.check(&with("blockNumber", json!("0x01")), &shape);
+        let missing = suite.check(&json!({"topics": [], "blockNumber": "0x1", "removed": true}), &shape);
+        let invalid_topic = suite.check(&with("topics", json!(["0x02"])), &shape);
+
+        // Then
+        assert_eq!(valid, Ok(()));
+        assert_eq!(padded.unwrap_err().path, "result.blockNumber");
+        assert_eq!(missing.unwrap_err().path, "result.address");
+        assert_eq!(invalid_topic.unwrap_err().path, "result.topics[0]");
+    }
+
+    #[test]
+    fn test_check_nullable_and_optional() {
+        // Given
+        let suite = suite();
+        let shape = json!({"to": "address?", "contractAddress?": "address?"});
+
+        // When
+        let null = suite.check(&json!({"to": null}), &shape);
+        let missing = suite.check(&json!({}), &shape);
+
+        // Then
+        assert_eq!(null, Ok(()));
+        assert_eq!(missing.unwrap_err().path, "result.to");
+    }
+
+    #[test]
+    fn test_replace_placeholders() {
+        // Given
+        let vector: ConformanceVector = serde_json::from_value(json!({
+            "client": "viem",
+            "method": "eth_getLogs",
+            "params": [{"blockHash": "$blockHash", "address": "$unknown"}],
+            "result": ["#log"]
+        }))
+        .unwrap();
+        let placeholders = HashMap::from([("blockHash".to_string(), json!("0x01"))]);
+
+        // When
+        let params = vector.params(&placeholders);
+
+        // Then
+        assert_eq!(params, vec![json!({"blockHash": "0x01", "address": "$unknown"})]);
+    }
+}
@@ -0,0 +1,132 @@
+//! Builders spinning up a ready-to-use Kakarot environment for integration tests: a Katana
+//! sequencer running Kakarot, a Mongo database and a Kakarot RPC server, with optional funded
+//! accounts and ERC20/ERC721 contracts.
+//!
+//! ```ignore
+//! let env = TestEnvironmentBuilder::new().with_funded_account(address, 10u128.pow(18)).with_erc20().build().await?;
+//! let provider = ProviderBuilder::new().on_http(env.rpc_url().parse()?);
+//! ```
+
+use super::{
+    eoa::KakarotEOA, evm_contract::KakarotEvmContract, katana::Katana, mongo::RANDOM_BYTES_SIZE,
+    rpc::start_kakarot_rpc_server,
+};
+use alloy_dyn_abi::DynSolValue;
+use alloy_primitives::{Address, U256};
+use jsonrpsee::server::ServerHandle;
+use starknet::providers::{jsonrpc::HttpTransport, JsonRpcClient};
+use std::{net::SocketAddr, sync::Arc};
+use url::Url;
+
+/// Builder of a [`TestEnvironment`].
+#[derive(Debug, Clone, Default)]
+pub struct TestEnvironmentBuilder {
+    /// Whether the database is filled with random blocks, transactions and logs.
+    random_data: bool,
+    /// The accounts funded by the EOA of the environment, with their balance.
+    funded_accounts: Vec<(Address, u128)>,
+    /// Whether an ERC20 contract is deployed.
+    erc20: bool,
+    /// Whether an ERC721 contract is deployed.
+    erc721: bool,
+}
+
+impl TestEnvironmentBuilder {
+    /// Creates a builder of an environment with an empty database.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Fills the database with random blocks, transactions and logs.
+    #[must_use]
+    pub const fn with_random_data(mut self) -> Self {
+        self.random_data = true;
+        self
+    }
+
+    /// Funds the account with the given value, transferred from the EOA of the environment.
+    #[must_use]
+    pub fn with_funded_account(mut self, address: Address, value: u128) -> Self {
+        self.funded_accounts.push((address, value));
+        self
+    }
+
+    /// Deploys an ERC20 contract named `Test` (`TT`), with 18 decimals.
+    #[must_use]
+    pub const fn with_erc20(mut self) -> Self {
+        self.erc20 = true;
+        self
+    }
+
+    /// Deploys an ERC721 contract named `Test` (`TT`).
+    #[must_use]
+    pub const fn with_erc721(mut self) -> Self {
+        self.erc721 = true;
+        self
+    }
+
+    /// Starts the sequencer, the database and the RPC server, funds the accounts and deploys the
+    /// contracts.
+    pub async fn build(self) -> Result<TestEnvironment, eyre::Error> {
+        let katana = if self.random_data { Katana::new(RANDOM_BYTES_SIZE).await } else { Katana::new_empty().await };
+        let eoa = katana.eoa();
+
+        for (address, value) in self.funded_accounts {
+            eoa.fund(address, value).await?;
+        }
+
+        let erc20 = if self.erc20 {
+            let args = [
+                DynSolValue::String("Test".into()),
+                DynSolValue::String("TT".into()),
+                DynSolValue::Uint(U256::from(18), 8),
+            ];
+            Some(eoa.deploy_evm_contract(Some("ERC20"), &args).await?)
+        } else {
+            None
+        };
+        let erc721 = if self.erc721 {
+            let args = [DynSolValue::String("Test".into()), DynSolValue::String("TT".into())];
+            Some(eoa.deploy_evm_contract(Some("ERC721"), &args).await?)
+        } else {
+            None
+        };
+
+        let (rpc_address, server_handle) = start_kakarot_rpc_server(&katana).await?;
+
+        Ok(TestEnvironment { katana, erc20, erc721, rpc_address, _server_handle: server_handle })
+    }
+}
+
+/// A Kakarot environment for integration tests. The RPC server is stopped and the database
+/// removed when the environment is dropped.
+#[allow(missing_debug_implementations)]
+pub struct TestEnvironment {
+    /// The Katana sequencer and the database.
+    pub katana: Katana,
+    /// The ERC20 contract, if deployed.
+    pub erc20: Option<KakarotEvmContract>,
+    /// The ERC721 contract, if deployed.
+    pub erc721: Option<KakarotEvmContract>,
+    rpc_address: SocketAddr,
+    /// The handle of the RPC server, which stops the server when dropped.
+    _server_handle: ServerHandle,
+}
+
+impl TestEnvironment {
+    /// Returns the URL of the Kakarot RPC server.
+    pub fn rpc_url(&self) -> String {
+        format!("http://localhost:{}", self.rpc_address.port())
+    }
+
+    /// Returns the URL of the Starknet RPC of the Katana sequencer.
+    pub fn starknet_url(&self) -> Url {
+        self.katana.sequencer().url()
+    }
+
+    /// Returns the EOA deploying the contracts and funding the accounts, which owns the
+    /// contracts.
+    pub const fn eoa(&self) -> &KakarotEOA<Arc<JsonRpcClient<HttpTransport>>> {
+        self.katana.eoa()
+    }
+}
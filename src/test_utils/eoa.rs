@@ -221,8 +221,34 @@ impl<P: Provider + Send + Sync + Clone> KakarotEOA<P> {
         )?;
         let tx_signed = self.sign_transaction(tx.clone())?;
         let _ = self.send_transaction(tx_signed.clone()).await?;
+        self.relay_and_wait(&tx_signed).await?;
 
-        // Prepare the relayer
+        Ok(tx)
+    }
+
+    /// Funds the given address with value
+    /// The transaction is signed and sent by the EOA
+    /// The transaction is waited for until it is confirmed
+    pub async fn fund(&self, to: Address, value: u128) -> Result<Transaction, eyre::Error> {
+        let tx = Transaction::Eip1559(TxEip1559 {
+            chain_id: self.eth_client.eth_provider().chain_id().await?.unwrap_or_default().try_into()?,
+            nonce: self.nonce().await?.try_into()?,
+            gas_limit: TX_GAS_LIMIT,
+            max_fee_per_gas: TX_GAS_PRICE.into(),
+            to: TxKind::Call(to),
+            value: U256::from(value),
+            ..Default::default()
+        });
+        let tx_signed = self.sign_transaction(tx.clone())?;
+        let _ = self.send_transaction(tx_signed.clone()).await?;
+        self.relay_and_wait(&tx_signed).await?;
+
+        Ok(tx)
+    }
+
+    /// Relays the transaction with the relayer of the EOA and waits for its confirmation
+    /// Returns the Starknet transaction hash
+    async fn relay_and_wait(&self, tx_signed: &TransactionSigned) -> Result<Felt, eyre::Error> {
         let relayer_balance = self
             .eth_client
             .starknet_provider()
@@ -230,14 +256,13 @@ impl<P: Provider + Send + Sync + Clone> KakarotEOA<P> {
             .await?;
         let relayer_balance = into_via_try_wrapper!(relayer_balance)?;
 
-        // Relay the transaction
         let starknet_transaction_hash = Relayer::new(
             self.relayer.address(),
             relayer_balance,
             self.starknet_provider(),
             Some(Arc::new(self.eth_client.eth_provider().database().clone())),
         )
-        .relay_transaction(&tx_signed)
+        .relay_transaction(tx_signed)
         .await
         .expect("Failed to relay transaction");
 
@@ -250,7 +275,7 @@ impl<P: Provider + Send + Sync + Clone> KakarotEOA<P> {
         .await
         .expect("Tx polling failed");
 
-        Ok(tx)
+        Ok(starknet_transaction_hash)
     }
 
     /// Transfers value to the given address
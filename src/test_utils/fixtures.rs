@@ -1,5 +1,11 @@
 use rstest::fixture;
 use tracing_subscriber::{filter, FmtSubscriber};
+#[cfg(feature = "chaos")]
+use {
+    super::katana::FaultyEthClient,
+    crate::chaos::{FaultConfig, FaultInjector},
+    std::sync::Arc,
+};
 #[cfg(any(test, feature = "arbitrary", feature = "testing"))]
 use {
     super::katana::Katana,
@@ -71,6 +77,20 @@ pub async fn plain_opcodes(#[future] counter: (Katana, KakarotEvmContract)) -> (
     (katana, contract)
 }
 
+/// This fixture deploys a counter contract on Katana, without faults, and returns an Ethereum
+/// client reaching Katana through a fault injector. The injector is seeded from `CHAOS_SEED`
+/// (0 by default) and injects no fault until the test configures it.
+#[cfg(feature = "chaos")]
+#[fixture]
+#[awt]
+pub async fn chaos(#[future] counter: (Katana, KakarotEvmContract)) -> (Katana, KakarotEvmContract, FaultyEthClient) {
+    let (katana, counter) = counter;
+    let seed = FaultConfig::from_env().seed.unwrap_or_default();
+    let injector = Arc::new(FaultInjector::new(FaultConfig { seed: Some(seed), ..Default::default() }));
+    let eth_client = katana.faulty_eth_client(injector);
+    (katana, counter, eth_client)
+}
+
 /// This fixture creates a new test environment on Katana.
 #[cfg(any(test, feature = "arbitrary", feature = "testing"))]
 #[fixture]
@@ -51,6 +51,12 @@ use {
     std::collections::HashSet,
     std::str::FromStr as _,
 };
+#[cfg(feature = "chaos")]
+use {crate::chaos::FaultInjector, crate::providers::sn_provider::FaultInjectionProvider};
+
+/// The Ethereum client of the Katana test environment, reaching Katana through a fault injector.
+#[cfg(feature = "chaos")]
+pub type FaultyEthClient = EthClient<FaultInjectionProvider<Arc<JsonRpcClient<HttpTransport>>>>;
 
 fn load_genesis() -> Genesis {
     Genesis::try_from(
@@ -240,6 +246,17 @@ impl<'a> Katana {
         self.eoa.eth_client.eth_provider().starknet_provider_inner().clone()
     }
 
+    /// Returns an Ethereum client sharing the database of the environment, whose calls to Katana
+    /// are delayed and dropped by the given fault injector.
+    #[cfg(feature = "chaos")]
+    pub fn faulty_eth_client(&self, injector: Arc<FaultInjector>) -> FaultyEthClient {
+        EthClient::new(
+            FaultInjectionProvider::with_injector(self.starknet_provider(), injector),
+            PoolConfig { gas_limit: KKRT_BLOCK_GAS_LIMIT, ..Default::default() },
+            self.eth_provider().database().clone(),
+        )
+    }
+
     pub const fn eoa(&self) -> &KakarotEOA<Arc<JsonRpcClient<HttpTransport>>> {
         &self.eoa
     }
@@ -1,6 +1,15 @@
 use crate::providers::eth_provider::{
-    database::types::{header::ExtendedBlock, receipt::ExtendedTxReceipt, transaction::ExtendedTransaction},
+    database::types::{
+        header::ExtendedBlock,
+        receipt::ExtendedTxReceipt,
+        transaction::{DroppedTransaction, ExtendedTransaction, PoolEvent},
+    },
+    gas::{DataAvailabilityCost, FeeSuggestions},
+    logs::{LogQuery, LogQueryResult},
     provider::EthApiResult,
+    receipts::{ReceiptProof, TransactionConfirmations, TransactionExecutionResources},
+    state::AccountType,
+    transactions::{AddressHistory, AddressHistoryRequest},
     BlockProvider, ChainProvider, GasProvider, LogProvider, ReceiptProvider, StateProvider, TransactionProvider,
 };
 use alloy_eips::{BlockId, BlockNumberOrTag};
@@ -32,6 +41,8 @@ mock! {
             full: bool,
         ) -> EthApiResult<Option<ExtendedBlock>>;
 
+        async fn block_by_starknet_hash(&self, starknet_hash: B256, full: bool) -> EthApiResult<Option<ExtendedBlock>>;
+
         async fn block_transaction_count_by_hash(&self, hash: B256) -> EthApiResult<Option<U256>>;
 
         async fn block_transaction_count_by_number(&self, number_or_tag: BlockNumberOrTag) -> EthApiResult<Option<U256>>;
@@ -53,11 +64,19 @@ mock! {
         async fn fee_history(&self, block_count: U64, newest_block: BlockNumberOrTag, reward_percentiles: Option<Vec<f64>>) -> EthApiResult<alloy_rpc_types::FeeHistory>;
 
         async fn gas_price(&self) -> EthApiResult<U256>;
+
+        async fn suggest_fees(&self) -> EthApiResult<FeeSuggestions>;
+
+        async fn data_availability_cost(&self, request: TransactionRequest) -> EthApiResult<DataAvailabilityCost>;
+
+        async fn block_gas_limit(&self) -> EthApiResult<u64>;
     }
 
     #[async_trait]
     impl LogProvider for EthereumProviderStruct {
         async fn get_logs(&self, filter: Filter) -> EthApiResult<FilterChanges>;
+
+        async fn query_logs(&self, query: LogQuery) -> EthApiResult<LogQueryResult>;
     }
 
     #[async_trait]
@@ -65,6 +84,12 @@ mock! {
         async fn transaction_receipt(&self, hash: B256) -> EthApiResult<Option<ExtendedTxReceipt>>;
 
         async fn block_receipts(&self, block_id: Option<BlockId>) -> EthApiResult<Option<Vec<ExtendedTxReceipt>>>;
+
+        async fn transaction_confirmations(&self, hash: B256) -> EthApiResult<Option<TransactionConfirmations>>;
+
+        async fn receipt_proof(&self, hash: B256) -> EthApiResult<Option<ReceiptProof>>;
+
+        async fn execution_resources(&self, hash: B256) -> EthApiResult<Option<TransactionExecutionResources>>;
     }
 
     #[async_trait]
@@ -75,7 +100,11 @@ mock! {
 
         async fn get_code(&self, address: Address, block_id: Option<BlockId>) -> EthApiResult<Bytes>;
 
+        async fn account_type(&self, address: Address, block_id: Option<BlockId>) -> EthApiResult<AccountType>;
+
         async fn call(&self, request: TransactionRequest, block_id: Option<BlockId>, state_overrides: Option<alloy_rpc_types::state::StateOverride>, block_overrides: Option<Box<alloy_rpc_types::BlockOverrides>>) -> EthApiResult<Bytes>;
+
+        async fn call_many(&self, bundles: Vec<alloy_rpc_types::Bundle>, state_context: Option<alloy_rpc_types::StateContext>, state_overrides: Option<alloy_rpc_types::state::StateOverride>) -> EthApiResult<Vec<Vec<alloy_rpc_types::EthCallResponse>>>;
     }
 
     #[async_trait]
@@ -87,5 +116,13 @@ mock! {
         async fn transaction_by_block_number_and_index(&self, number_or_tag: BlockNumberOrTag, index: alloy_rpc_types::Index) -> EthApiResult<Option<ExtendedTransaction>>;
 
         async fn transaction_count(&self, address: Address, block_id: Option<BlockId>) -> EthApiResult<U256>;
+
+        async fn address_history(&self, request: AddressHistoryRequest) -> EthApiResult<AddressHistory>;
+
+        async fn dropped_transaction(&self, hash: B256) -> EthApiResult<Option<DroppedTransaction>>;
+
+        async fn transaction_timeline(&self, hash: B256) -> EthApiResult<Vec<PoolEvent>>;
+
+        async fn submitted_raw_transaction(&self, hash: B256) -> EthApiResult<Option<Bytes>>;
     }
 }
@@ -1,4 +1,8 @@
+pub mod bench;
+#[cfg(feature = "conformance")]
+pub mod conformance;
 pub mod constants;
+pub mod environment;
 pub mod eoa;
 pub mod evm_contract;
 pub mod fixtures;
@@ -1,9 +1,17 @@
 use super::katana::Katana;
-use crate::eth_rpc::{config::RPCConfig, rpc::KakarotRpcModuleBuilder, run_server};
+use crate::{
+    eth_rpc::{config::RPCConfig, rpc::KakarotRpcModuleBuilder, run_server},
+    pool::relayers::RelayerRegistry,
+};
 use jsonrpsee::server::ServerHandle;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
-use std::net::SocketAddr;
+use starknet::accounts::Account;
+use std::{net::SocketAddr, sync::Arc};
+
+/// The API key of the admin role in the configuration of the test server, sent in the `X-Api-Key`
+/// header of the admin calls.
+pub const TEST_ADMIN_API_KEY: &str = "test-admin";
 
 /// Sets up the environment for Kakarot RPC integration tests by deploying the Kakarot contracts
 /// and starting the Kakarot RPC server.
@@ -55,13 +63,27 @@ use std::net::SocketAddr;
 /// `allow(dead_code)` is used because this function is used in tests,
 /// and each test is compiled separately, so the compiler thinks this function is unused
 pub async fn start_kakarot_rpc_server(katana: &Katana) -> Result<(SocketAddr, ServerHandle), eyre::Report> {
-    let eth_client = katana.eth_client();
+    #[cfg(feature = "testing")]
+    let config = RPCConfig::new_test_config_from_port(rand::random());
+    #[cfg(not(feature = "testing"))]
+    let config = RPCConfig::from_port(3030)?;
+    start_kakarot_rpc_server_with_config(katana, config).await
+}
+
+/// Starts the Kakarot RPC server with the admin module enabled, using the given configuration
+/// (e.g. to test the admin API keys).
+pub async fn start_kakarot_rpc_server_with_config(
+    katana: &Katana,
+    config: RPCConfig,
+) -> Result<(SocketAddr, ServerHandle), eyre::Report> {
+    let eth_client = Arc::new(katana.eth_client());
+    let relayers = Arc::new(RelayerRegistry::new([katana.eoa().relayer.address()]));
     Ok(run_server(
-        KakarotRpcModuleBuilder::new(eth_client.into()).rpc_module()?,
-        #[cfg(feature = "testing")]
-        RPCConfig::new_test_config_from_port(rand::random()),
-        #[cfg(not(feature = "testing"))]
-        RPCConfig::from_port(3030),
+        KakarotRpcModuleBuilder::new(Arc::clone(&eth_client))
+            .with_pool_module(Arc::clone(&eth_client))
+            .with_admin_module(eth_client, relayers)
+            .rpc_module()?,
+        config,
     )
     .await?)
 }
@@ -1,14 +1,18 @@
-use super::{Tracer, TracerResult};
+use super::{
+    limits::{current_trace_limits, TraceLimits},
+    Tracer, TracerResult,
+};
 use crate::providers::eth_provider::{
     database::{
         state::{EthCacheDatabase, EthDatabase},
-        types::transaction::ExtendedTransaction,
+        types::transaction::{is_deposit, ExtendedTransaction},
     },
     error::{EthApiError, TransactionError},
     provider::EthereumProvider,
+    state::apply_block_overrides,
 };
 use alloy_primitives::{B256, U256};
-use alloy_rpc_types::{Block, BlockId, BlockTransactions, Header};
+use alloy_rpc_types::{state::BlockOverrides, Block, BlockId, BlockTransactions, Header};
 use alloy_rpc_types_trace::geth::{GethDebugTracingCallOptions, GethDebugTracingOptions};
 use reth_revm::{
     db::CacheDB,
@@ -93,7 +97,9 @@ pub struct TracerBuilder<P: EthereumProvider + Send + Sync + Clone, Status = Flo
     eth_provider: P,
     env: Env,
     block: Block<ExtendedTransaction>,
+    block_overrides: Option<BlockOverrides>,
     tracing_options: TracingOptions,
+    trace_limits: TraceLimits,
     _phantom: std::marker::PhantomData<Status>,
 }
 
@@ -113,7 +119,9 @@ impl<P: EthereumProvider + Send + Sync + Clone> TracerBuilder<P, Floating> {
             eth_provider,
             env,
             block: Default::default(),
+            block_overrides: None,
             tracing_options: Default::default(),
+            trace_limits: current_trace_limits(),
             _phantom: std::marker::PhantomData,
         })
     }
@@ -126,7 +134,9 @@ impl<P: EthereumProvider + Send + Sync + Clone> TracerBuilder<P, Floating> {
             eth_provider: self.eth_provider.clone(),
             env: self.env.clone(),
             block,
+            block_overrides: self.block_overrides.clone(),
             tracing_options: self.tracing_options.clone(),
+            trace_limits: self.trace_limits,
             _phantom: std::marker::PhantomData,
         })
     }
@@ -179,10 +189,28 @@ impl<P: EthereumProvider + Send + Sync + Clone> TracerBuilder<P, Pinned> {
         self
     }
 
+    /// Sets the overrides of the block environment, applied on top of the environment built from
+    /// the header of the block
+    #[must_use]
+    pub fn with_block_overrides(mut self, block_overrides: BlockOverrides) -> Self {
+        self.block_overrides = Some(block_overrides);
+        self
+    }
+
+    /// Sets the limits of the traces, capping the tracing options
+    #[must_use]
+    pub const fn with_trace_limits(mut self, trace_limits: TraceLimits) -> Self {
+        self.trace_limits = trace_limits;
+        self
+    }
+
     /// Builds the tracer.
     pub fn build(self) -> TracerResult<Tracer<P>> {
+        // The deposit transactions can't be replayed in the EVM
         let transactions = match &self.block.transactions {
-            BlockTransactions::Full(transactions) => transactions.clone(),
+            BlockTransactions::Full(transactions) => {
+                transactions.iter().filter(|transaction| !is_deposit(transaction)).cloned().collect()
+            }
             _ => return Err(TransactionError::ExpectedFullTransactions.into()),
         };
 
@@ -191,9 +219,10 @@ impl<P: EthereumProvider + Send + Sync + Clone> TracerBuilder<P, Pinned> {
         let db =
             EthCacheDatabase(CacheDB::new(EthDatabase::new(self.eth_provider, self.block.header.parent_hash.into())));
 
-        let tracing_options = self.tracing_options;
+        let trace_limits = self.trace_limits;
+        let tracing_options = trace_limits.cap_tracing_options(self.tracing_options);
 
-        Ok(Tracer { transactions, env, db, tracing_options })
+        Ok(Tracer { transactions, env, db, tracing_options, trace_limits })
     }
 
     /// Init an `EnvWithHandlerCfg`.
@@ -202,25 +231,36 @@ impl<P: EthereumProvider + Send + Sync + Clone> TracerBuilder<P, Pinned> {
         EnvWithHandlerCfg::new(env, HandlerCfg::new(SpecId::CANCUN))
     }
 
-    /// Inits the Env by using `self.block` to set the block environment.
+    /// Inits the Env by using `self.block` to set the block environment, then applies the block
+    /// overrides.
     fn init_env_with_block_env(&self) -> Env {
         let mut env = self.env.clone();
-
-        let Header { number, timestamp, miner, base_fee_per_gas, difficulty, .. } = self.block.header.clone();
-        let block_env = BlockEnv {
-            number: U256::from(number),
-            timestamp: U256::from(timestamp),
-            gas_limit: U256::from(TRACING_BLOCK_GAS_LIMIT),
-            coinbase: miner,
-            basefee: U256::from(base_fee_per_gas.unwrap_or_default()),
-            prevrandao: Some(B256::from_slice(&difficulty.to_be_bytes::<32>()[..])),
-            ..Default::default()
-        };
-        env.block = block_env;
+        env.block = block_env(&self.block.header);
+        if let Some(block_overrides) = self.block_overrides.clone() {
+            apply_block_overrides(block_overrides, &mut env.block);
+        }
         env
     }
 }
 
+/// Creates the block environment of the given header, using the [`TRACING_BLOCK_GAS_LIMIT`].
+///
+/// The prevrandao is the mix hash of the stored header, as after the merge, and falls back to the
+/// difficulty for the headers stored without mix hash.
+pub fn block_env(header: &Header) -> BlockEnv {
+    let Header { number, timestamp, miner, base_fee_per_gas, difficulty, mix_hash, .. } = header.clone();
+    BlockEnv {
+        number: U256::from(number),
+        timestamp: U256::from(timestamp),
+        gas_limit: U256::from(TRACING_BLOCK_GAS_LIMIT),
+        coinbase: miner,
+        basefee: U256::from(base_fee_per_gas.unwrap_or_default()),
+        difficulty,
+        prevrandao: Some(mix_hash.unwrap_or_else(|| B256::from(difficulty))),
+        ..Default::default()
+    }
+}
+
 // The following tests validates the behavior of the TracerBuilder when interacting with a mock Ethereum provider.
 // Each test focuses on different scenarios where the TracerBuilder is expected to handle various errors correctly,
 // such as unknown blocks, not found transactions, and invalid chain IDs.
@@ -228,7 +268,7 @@ impl<P: EthereumProvider + Send + Sync + Clone> TracerBuilder<P, Pinned> {
 mod tests {
     use super::*;
     use crate::test_utils::mock_provider::MockEthereumProviderStruct;
-    use alloy_primitives::U64;
+    use alloy_primitives::{Address, U64};
     use alloy_rpc_types::Transaction;
     use alloy_serde::WithOtherFields;
     use std::sync::Arc;
@@ -303,6 +343,74 @@ mod tests {
         assert!(matches!(result, Err(EthApiError::TransactionNotFound(_))));
     }
 
+    #[test]
+    fn test_block_env() {
+        // Given
+        let header = Header {
+            number: 10,
+            timestamp: 1000,
+            miner: Address::with_last_byte(1),
+            base_fee_per_gas: Some(7),
+            mix_hash: Some(B256::repeat_byte(2)),
+            ..Default::default()
+        };
+
+        // When
+        let block_env = block_env(&header);
+
+        // Then
+        assert_eq!(block_env.number, U256::from(10));
+        assert_eq!(block_env.timestamp, U256::from(1000));
+        assert_eq!(block_env.coinbase, Address::with_last_byte(1));
+        assert_eq!(block_env.basefee, U256::from(7));
+        assert_eq!(block_env.prevrandao, Some(B256::repeat_byte(2)));
+    }
+
+    #[test]
+    fn test_block_env_without_mix_hash() {
+        // Given
+        let header = Header { difficulty: U256::from(3), mix_hash: None, ..Default::default() };
+
+        // When
+        let block_env = block_env(&header);
+
+        // Then
+        assert_eq!(block_env.difficulty, U256::from(3));
+        assert_eq!(block_env.prevrandao, Some(B256::with_last_byte(3)));
+    }
+
+    #[tokio::test]
+    async fn test_tracer_builder_with_block_overrides() {
+        // Given
+        let mut mock_provider = MockEthereumProviderStruct::new();
+        mock_provider.expect_chain_id().returning(|| Ok(Some(U64::from(1))));
+        mock_provider.expect_block_by_number().returning(|_, _| {
+            Ok(Some(WithOtherFields::new(Block {
+                header: Header {
+                    hash: B256::repeat_byte(1),
+                    miner: Address::with_last_byte(1),
+                    mix_hash: Some(B256::repeat_byte(2)),
+                    ..Default::default()
+                },
+                ..Default::default()
+            })))
+        });
+        let builder = TracerBuilder::new(Arc::new(&mock_provider)).await.unwrap();
+
+        // When
+        let builder = builder
+            .with_block_id(BlockId::Number(1.into()))
+            .await
+            .unwrap()
+            .with_block_overrides(BlockOverrides { random: Some(B256::repeat_byte(3)), ..Default::default() });
+        let env = builder.init_env_with_block_env();
+
+        // Then
+        assert_eq!(env.cfg.chain_id, 1);
+        assert_eq!(env.block.coinbase, Address::with_last_byte(1));
+        assert_eq!(env.block.prevrandao, Some(B256::repeat_byte(3)));
+    }
+
     #[tokio::test]
     async fn test_tracer_builder_build_error() {
         // Create a mock Ethereum provider
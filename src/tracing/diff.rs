@@ -0,0 +1,184 @@
+use crate::{eth_rpc::openrpc::SchemaOf, providers::eth_provider::starknet::kakarot_core::TRANSACTION_EXECUTED};
+use alloy_primitives::{Bytes, Log, B256};
+use reth_revm::primitives::ExecutionResult;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use starknet::core::types::Event;
+
+/// The outcome of the execution of a transaction.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ExecutionOutcome {
+    /// Whether the transaction succeeded.
+    pub success: bool,
+    /// The gas used by the transaction.
+    pub gas_used: u64,
+    /// The data returned by the transaction, `None` if unknown.
+    #[schemars(with = "SchemaOf<Option<Bytes>>")]
+    pub return_data: Option<Bytes>,
+    /// The logs emitted by the transaction.
+    #[schemars(with = "SchemaOf<Vec<Log>>")]
+    pub logs: Vec<Log>,
+}
+
+impl From<ExecutionResult> for ExecutionOutcome {
+    fn from(result: ExecutionResult) -> Self {
+        Self {
+            success: result.is_success(),
+            gas_used: result.gas_used(),
+            return_data: Some(result.output().cloned().unwrap_or_default()),
+            logs: result.logs().to_vec(),
+        }
+    }
+}
+
+/// A divergence between the local execution of a transaction and its execution by Kakarot.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum Divergence {
+    /// The transaction succeeded on one side only.
+    Status { local: bool, kakarot: bool },
+    /// The gas used differs.
+    GasUsed { local: u64, kakarot: u64 },
+    /// The returned data differs.
+    ReturnData {
+        #[schemars(with = "SchemaOf<Bytes>")]
+        local: Bytes,
+        #[schemars(with = "SchemaOf<Bytes>")]
+        kakarot: Bytes,
+    },
+    /// The log at the given index differs, or is only emitted on one side.
+    Log {
+        index: usize,
+        #[schemars(with = "SchemaOf<Option<Log>>")]
+        local: Option<Log>,
+        #[schemars(with = "SchemaOf<Option<Log>>")]
+        kakarot: Option<Log>,
+    },
+}
+
+/// The comparison of the local execution of a mined transaction with its execution by Kakarot.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct TransactionDiff {
+    /// The hash of the transaction.
+    #[schemars(with = "SchemaOf<B256>")]
+    pub transaction_hash: B256,
+    /// The outcome of the execution of the transaction by the local EVM.
+    pub local: ExecutionOutcome,
+    /// The outcome of the execution of the transaction by Kakarot.
+    pub kakarot: ExecutionOutcome,
+    /// The divergences between both outcomes, empty if the executions match.
+    pub divergences: Vec<Divergence>,
+}
+
+impl TransactionDiff {
+    /// Compares both outcomes of the transaction.
+    pub fn new(transaction_hash: B256, local: ExecutionOutcome, kakarot: ExecutionOutcome) -> Self {
+        let divergences = divergences(&local, &kakarot);
+        Self { transaction_hash, local, kakarot, divergences }
+    }
+}
+
+/// Returns the divergences between the local and the Kakarot outcomes. The return data is only
+/// compared when known on both sides.
+fn divergences(local: &ExecutionOutcome, kakarot: &ExecutionOutcome) -> Vec<Divergence> {
+    let mut divergences = Vec::new();
+
+    if local.success != kakarot.success {
+        divergences.push(Divergence::Status { local: local.success, kakarot: kakarot.success });
+    }
+    if local.gas_used != kakarot.gas_used {
+        divergences.push(Divergence::GasUsed { local: local.gas_used, kakarot: kakarot.gas_used });
+    }
+    if let (Some(local), Some(kakarot)) = (&local.return_data, &kakarot.return_data) {
+        if local != kakarot {
+            divergences.push(Divergence::ReturnData { local: local.clone(), kakarot: kakarot.clone() });
+        }
+    }
+
+    for index in 0..local.logs.len().max(kakarot.logs.len()) {
+        let (local, kakarot) = (local.logs.get(index), kakarot.logs.get(index));
+        if local != kakarot {
+            divergences.push(Divergence::Log { index, local: local.cloned(), kakarot: kakarot.cloned() });
+        }
+    }
+
+    divergences
+}
+
+/// Returns the data returned by the Kakarot execution, read from the `transaction_executed`
+/// event emitted by the account. The data of the event is laid out as the length of the
+/// response, followed by one felt per byte of the response, the success flag and the gas used.
+pub fn transaction_executed_response(events: &[Event]) -> Option<Bytes> {
+    let event = events.iter().find(|event| event.keys.first() == Some(&*TRANSACTION_EXECUTED))?;
+    let (length, data) = event.data.split_first()?;
+    let length = usize::try_from(*length).ok()?;
+
+    data.get(..length)?.iter().map(|byte| u8::try_from(*byte).ok()).collect::<Option<Vec<_>>>().map(Into::into)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::{bytes, Address, LogData};
+    use starknet::core::types::Felt;
+
+    fn outcome(success: bool, gas_used: u64, return_data: Option<Bytes>, logs: Vec<Log>) -> ExecutionOutcome {
+        ExecutionOutcome { success, gas_used, return_data, logs }
+    }
+
+    fn log(data: Bytes) -> Log {
+        Log { address: Address::ZERO, data: LogData::new_unchecked(vec![], data) }
+    }
+
+    /// Returns the data of a `transaction_executed` event with the given response.
+    fn transaction_executed_data(response: &[u8]) -> Vec<Felt> {
+        std::iter::once(Felt::from(response.len()))
+            .chain(response.iter().map(|byte| Felt::from(*byte)))
+            .chain([Felt::ONE, Felt::from(21000)])
+            .collect()
+    }
+
+    #[test]
+    fn test_transaction_diff() {
+        // Given
+        let local = outcome(true, 21000, Some(bytes!("01")), vec![log(bytes!("01")), log(bytes!("02"))]);
+        let kakarot = outcome(false, 25000, Some(bytes!("02")), vec![log(bytes!("01"))]);
+
+        // When
+        let diff = TransactionDiff::new(B256::ZERO, local.clone(), kakarot);
+        let no_diff = TransactionDiff::new(B256::ZERO, local.clone(), ExecutionOutcome { return_data: None, ..local });
+
+        // Then
+        assert_eq!(
+            diff.divergences,
+            vec![
+                Divergence::Status { local: true, kakarot: false },
+                Divergence::GasUsed { local: 21000, kakarot: 25000 },
+                Divergence::ReturnData { local: bytes!("01"), kakarot: bytes!("02") },
+                Divergence::Log { index: 1, local: Some(log(bytes!("02"))), kakarot: None },
+            ]
+        );
+        assert!(no_diff.divergences.is_empty());
+    }
+
+    #[test]
+    fn test_transaction_executed_response() {
+        // Given
+        let event = |keys: Vec<Felt>, data: Vec<Felt>| Event { from_address: Felt::ONE, keys, data };
+        let executed = event(vec![*TRANSACTION_EXECUTED], transaction_executed_data(&[0xde, 0xad]));
+        let other = event(vec![Felt::TWO], transaction_executed_data(&[0x01]));
+        let truncated = event(vec![*TRANSACTION_EXECUTED], vec![Felt::from(3), Felt::ONE]);
+
+        // When
+        let response = transaction_executed_response(&[other.clone(), executed]);
+        let missing = transaction_executed_response(&[other]);
+        let invalid = transaction_executed_response(&[truncated]);
+
+        // Then
+        assert_eq!(response, Some(bytes!("dead")));
+        assert_eq!(missing, None);
+        assert_eq!(invalid, None);
+    }
+}
@@ -0,0 +1,492 @@
+use super::builder::TracingOptions;
+use alloy_primitives::{Address, U256};
+use alloy_rpc_types_trace::geth::GethDefaultTracingOptions;
+use reth_revm::{
+    interpreter::{
+        CallInputs, CallOutcome, CreateInputs, CreateOutcome, EOFCreateInputs, Gas, InstructionResult, Interpreter,
+        InterpreterResult,
+    },
+    primitives::{Bytes, EVMError, Log},
+    Database, EvmContext, Inspector,
+};
+use revm_inspectors::tracing::{StackSnapshotType, TracingInspector, TracingInspectorConfig};
+use std::{future::Future, str::FromStr, sync::LazyLock};
+
+/// The server side limits of the traces, read from the environment.
+pub static TRACE_LIMITS: LazyLock<TraceLimits> = LazyLock::new(TraceLimits::from_env);
+
+/// The limits of the traces requested by the public users, read from the environment.
+pub static PUBLIC_TRACE_LIMITS: LazyLock<TraceLimits> = LazyLock::new(|| TraceLimits::public_from_env(*TRACE_LIMITS));
+
+tokio::task_local! {
+    /// Set while a trace requested by a public user is computed.
+    static PUBLIC_TIER: ();
+}
+
+/// Computes the traces of the future with the limits of the public tier.
+pub async fn with_public_tier<F: Future>(future: F) -> F::Output {
+    PUBLIC_TIER.scope((), future).await
+}
+
+/// Returns true if the current trace is requested by a public user.
+pub fn is_public_tier() -> bool {
+    PUBLIC_TIER.try_with(|()| ()).is_ok()
+}
+
+/// Returns the limits of the current trace, which depend on the tier of the requester.
+pub fn current_trace_limits() -> TraceLimits {
+    if is_public_tier() {
+        *PUBLIC_TRACE_LIMITS
+    } else {
+        *TRACE_LIMITS
+    }
+}
+
+/// Limits on the output of the tracing endpoints.
+///
+/// Requests can lower these limits through their Geth tracing options (`limit`, `enableMemory`,
+/// `disableStack`, `disableStorage`), but can never exceed them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TraceLimits {
+    /// Maximum number of opcode steps returned by the default tracer for a transaction.
+    pub max_struct_logs: u64,
+    /// Maximum number of call frames returned for a transaction.
+    pub max_call_frames: usize,
+    /// Maximum number of bytes of memory of an opcode step whose memory is captured.
+    pub max_memory_bytes: usize,
+    /// Whether the memory can be captured.
+    pub enable_memory: bool,
+    /// Whether the stack can be captured.
+    pub enable_stack: bool,
+    /// Whether the storage can be captured.
+    pub enable_storage: bool,
+}
+
+impl Default for TraceLimits {
+    fn default() -> Self {
+        Self {
+            max_struct_logs: 100_000,
+            max_call_frames: 10_000,
+            max_memory_bytes: 32 * 1024,
+            enable_memory: false,
+            enable_stack: true,
+            enable_storage: true,
+        }
+    }
+}
+
+impl TraceLimits {
+    /// Reads the limits from the environment, falling back to the default limits.
+    pub fn from_env() -> Self {
+        fn var<T: FromStr>(key: &str) -> Option<T> {
+            std::env::var(key).ok().and_then(|val| T::from_str(&val).ok())
+        }
+
+        let default = Self::default();
+        Self {
+            max_struct_logs: var("TRACE_MAX_STRUCT_LOGS").unwrap_or(default.max_struct_logs),
+            max_call_frames: var("TRACE_MAX_CALL_FRAMES").unwrap_or(default.max_call_frames),
+            max_memory_bytes: var("TRACE_MAX_MEMORY_BYTES").unwrap_or(default.max_memory_bytes),
+            enable_memory: var("TRACE_ENABLE_MEMORY").unwrap_or(default.enable_memory),
+            enable_stack: var("TRACE_ENABLE_STACK").unwrap_or(default.enable_stack),
+            enable_storage: var("TRACE_ENABLE_STORAGE").unwrap_or(default.enable_storage),
+        }
+    }
+
+    /// Reads the limits of the public tier from the environment, which can never exceed the
+    /// server side limits. The memory is never captured for the public users.
+    pub fn public_from_env(limits: Self) -> Self {
+        fn var<T: FromStr>(key: &str) -> Option<T> {
+            std::env::var(key).ok().and_then(|val| T::from_str(&val).ok())
+        }
+
+        Self {
+            max_struct_logs: var("TRACE_PUBLIC_MAX_STRUCT_LOGS").unwrap_or(1_000).min(limits.max_struct_logs),
+            max_call_frames: var("TRACE_PUBLIC_MAX_CALL_FRAMES").unwrap_or(100).min(limits.max_call_frames),
+            max_memory_bytes: 0,
+            enable_memory: false,
+            ..limits
+        }
+    }
+
+    /// Caps the tracing options of a request to the limits.
+    pub fn cap_tracing_options(&self, tracing_options: TracingOptions) -> TracingOptions {
+        match tracing_options {
+            TracingOptions::Geth(mut options) => {
+                self.cap_geth_config(&mut options.config);
+                TracingOptions::Geth(options)
+            }
+            TracingOptions::GethCall(mut options) => {
+                self.cap_geth_config(&mut options.tracing_options.config);
+                TracingOptions::GethCall(options)
+            }
+            TracingOptions::Parity(config) => TracingOptions::Parity(self.cap_inspector_config(config)),
+        }
+    }
+
+    /// Caps the options of the Geth default tracer. A missing or zero `limit` is replaced by
+    /// the maximum number of struct logs.
+    fn cap_geth_config(&self, config: &mut GethDefaultTracingOptions) {
+        let limit = config.limit.filter(|limit| *limit > 0).unwrap_or(self.max_struct_logs);
+        config.limit = Some(limit.min(self.max_struct_logs));
+
+        if !self.enable_memory {
+            config.enable_memory = Some(false);
+        }
+        if !self.enable_stack {
+            config.disable_stack = Some(true);
+        }
+        if !self.enable_storage {
+            config.disable_storage = Some(true);
+        }
+    }
+
+    /// Caps the configuration of the tracing inspector used for Parity traces.
+    fn cap_inspector_config(&self, mut config: TracingInspectorConfig) -> TracingInspectorConfig {
+        config.record_memory_snapshots &= self.enable_memory;
+        if !self.enable_stack {
+            config.record_stack_snapshots = StackSnapshotType::None;
+        }
+        config.record_state_diff &= self.enable_storage;
+        config
+    }
+}
+
+/// A [`TracingInspector`] enforcing the [`TraceLimits`] while the transaction executes, so that
+/// a trace never grows past the limits in memory.
+///
+/// The steps past the `limit` of the request aren't recorded, like in Geth. The execution is
+/// aborted with an error as soon as a trace exceeds the server side limits: the maximum number of
+/// struct logs or call frames, or the maximum memory of a step whose memory is captured.
+#[derive(Debug)]
+pub struct LimitedInspector {
+    inner: TracingInspector,
+    limits: TraceLimits,
+    /// The maximum number of steps to record.
+    max_steps: u64,
+    /// The number of recorded steps.
+    steps: u64,
+    /// The number of recorded frames.
+    frames: usize,
+    /// The depth of the current frame below the last recorded frame, zero if the current frame
+    /// is recorded.
+    skipped_depth: usize,
+    /// Whether the current step is recorded.
+    step_recorded: bool,
+}
+
+impl LimitedInspector {
+    /// Returns an inspector with the given configuration, recording at most `max_steps` steps.
+    pub fn new(config: TracingInspectorConfig, limits: TraceLimits, max_steps: u64) -> Self {
+        Self {
+            inner: TracingInspector::new(config),
+            limits,
+            max_steps: max_steps.min(limits.max_struct_logs),
+            steps: 0,
+            frames: 0,
+            skipped_depth: 0,
+            step_recorded: false,
+        }
+    }
+
+    /// Returns the inner [`TracingInspector`], holding the recorded traces.
+    pub fn into_inner(self) -> TracingInspector {
+        self.inner
+    }
+
+    /// Enters a frame, returning true if the frame is recorded. The execution is aborted if the
+    /// frame exceeds the maximum number of call frames.
+    fn enter_frame<DB: Database>(&mut self, context: &mut EvmContext<DB>) -> bool {
+        if self.skipped_depth == 0 && self.frames < self.limits.max_call_frames {
+            self.frames += 1;
+            return true;
+        }
+        if self.skipped_depth == 0 {
+            abort(context, format!("trace exceeds the limit of {} call frames", self.limits.max_call_frames));
+        }
+        self.skipped_depth += 1;
+        false
+    }
+
+    /// Exits a frame, returning true if the frame was recorded.
+    fn exit_frame(&mut self) -> bool {
+        if self.skipped_depth == 0 {
+            return true;
+        }
+        self.skipped_depth -= 1;
+        false
+    }
+}
+
+/// Aborts the execution of the transaction with the given error.
+fn abort<DB: Database>(context: &mut EvmContext<DB>, error: String) {
+    if context.error.is_ok() {
+        context.error = Err(EVMError::Custom(error));
+    }
+}
+
+/// The result of a frame whose execution is aborted.
+fn aborted_result() -> InterpreterResult {
+    InterpreterResult::new(InstructionResult::FatalExternalError, Bytes::new(), Gas::new(0))
+}
+
+impl<DB: Database> Inspector<DB> for LimitedInspector {
+    fn step(&mut self, interp: &mut Interpreter, context: &mut EvmContext<DB>) {
+        let config = *self.inner.config();
+        self.step_recorded = self.skipped_depth == 0 && config.record_steps && self.steps < self.max_steps;
+        if !self.step_recorded {
+            // Only the server side limit aborts the execution, the limit of the request truncates
+            if config.record_steps && self.skipped_depth == 0 && self.max_steps == self.limits.max_struct_logs {
+                abort(context, format!("trace exceeds the limit of {} struct logs", self.limits.max_struct_logs));
+                interp.instruction_result = InstructionResult::FatalExternalError;
+            }
+            return;
+        }
+        if config.record_memory_snapshots && interp.shared_memory.len() > self.limits.max_memory_bytes {
+            self.step_recorded = false;
+            abort(context, format!("trace exceeds the limit of {} bytes of memory", self.limits.max_memory_bytes));
+            interp.instruction_result = InstructionResult::FatalExternalError;
+            return;
+        }
+        self.steps += 1;
+        self.inner.step(interp, context);
+    }
+
+    fn step_end(&mut self, interp: &mut Interpreter, context: &mut EvmContext<DB>) {
+        if self.step_recorded {
+            self.inner.step_end(interp, context);
+        }
+    }
+
+    fn log(&mut self, interp: &mut Interpreter, context: &mut EvmContext<DB>, log: &Log) {
+        if self.skipped_depth == 0 {
+            self.inner.log(interp, context, log);
+        }
+    }
+
+    fn call(&mut self, context: &mut EvmContext<DB>, inputs: &mut CallInputs) -> Option<CallOutcome> {
+        if self.enter_frame(context) {
+            return self.inner.call(context, inputs);
+        }
+        Some(CallOutcome::new(aborted_result(), inputs.return_memory_offset.clone()))
+    }
+
+    fn call_end(&mut self, context: &mut EvmContext<DB>, inputs: &CallInputs, outcome: CallOutcome) -> CallOutcome {
+        if self.exit_frame() {
+            return self.inner.call_end(context, inputs, outcome);
+        }
+        outcome
+    }
+
+    fn create(&mut self, context: &mut EvmContext<DB>, inputs: &mut CreateInputs) -> Option<CreateOutcome> {
+        if self.enter_frame(context) {
+            return self.inner.create(context, inputs);
+        }
+        Some(CreateOutcome::new(aborted_result(), None))
+    }
+
+    fn create_end(
+        &mut self,
+        context: &mut EvmContext<DB>,
+        inputs: &CreateInputs,
+        outcome: CreateOutcome,
+    ) -> CreateOutcome {
+        if self.exit_frame() {
+            return self.inner.create_end(context, inputs, outcome);
+        }
+        outcome
+    }
+
+    fn eofcreate(&mut self, context: &mut EvmContext<DB>, inputs: &mut EOFCreateInputs) -> Option<CreateOutcome> {
+        if self.enter_frame(context) {
+            return self.inner.eofcreate(context, inputs);
+        }
+        Some(CreateOutcome::new(aborted_result(), None))
+    }
+
+    fn eofcreate_end(
+        &mut self,
+        context: &mut EvmContext<DB>,
+        inputs: &EOFCreateInputs,
+        outcome: CreateOutcome,
+    ) -> CreateOutcome {
+        if self.exit_frame() {
+            return self.inner.eofcreate_end(context, inputs, outcome);
+        }
+        outcome
+    }
+
+    fn selfdestruct(&mut self, contract: Address, target: Address, value: U256) {
+        if self.skipped_depth == 0 {
+            Inspector::<DB>::selfdestruct(&mut self.inner, contract, target, value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::TxKind;
+    use alloy_rpc_types_trace::geth::GethDebugTracingOptions;
+    use reth_evm_ethereum::EthEvmConfig;
+    use reth_node_api::ConfigureEvm;
+    use reth_revm::{
+        db::{CacheDB, EmptyDB},
+        primitives::{AccountInfo, Bytecode, Env, EnvWithHandlerCfg, SpecId},
+    };
+    use revm_inspectors::tracing::types::CallTraceArena;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_cap_geth_options() {
+        // Given
+        let limits = TraceLimits { max_struct_logs: 10, enable_memory: false, ..Default::default() };
+        let mut options = GethDebugTracingOptions::default();
+        options.config.limit = Some(100);
+        options.config.enable_memory = Some(true);
+
+        // When
+        let options = limits.cap_tracing_options(options.into());
+
+        // Then
+        let config = &options.as_geth().unwrap().config;
+        assert_eq!(config.limit, Some(10));
+        assert_eq!(config.enable_memory, Some(false));
+    }
+
+    #[test]
+    fn test_cap_geth_options_keeps_lower_request_limit() {
+        // Given
+        let limits = TraceLimits { max_struct_logs: 10, ..Default::default() };
+        let mut options = GethDebugTracingOptions::default();
+        options.config.limit = Some(5);
+
+        // When
+        let options = limits.cap_tracing_options(options.into());
+
+        // Then
+        assert_eq!(options.as_geth().unwrap().config.limit, Some(5));
+    }
+
+    #[test]
+    fn test_cap_parity_config() {
+        // Given
+        let limits = TraceLimits { enable_memory: false, enable_stack: false, ..Default::default() };
+        let config = TracingInspectorConfig::all();
+
+        // When
+        let options = limits.cap_tracing_options(config.into());
+
+        // Then
+        let config = options.as_parity().unwrap();
+        assert!(!config.record_memory_snapshots);
+        assert_eq!(config.record_stack_snapshots, StackSnapshotType::None);
+        assert!(config.record_state_diff);
+    }
+
+    #[tokio::test]
+    async fn test_public_tier_limits() {
+        // Given
+        let limits = TraceLimits { enable_memory: true, ..Default::default() };
+
+        // When
+        let public = TraceLimits::public_from_env(limits);
+        let in_public_tier = with_public_tier(async { (is_public_tier(), current_trace_limits()) }).await;
+
+        // Then
+        assert_eq!(public.max_struct_logs, 1_000);
+        assert_eq!(public.max_call_frames, 100);
+        assert!(!public.enable_memory);
+        assert!(!is_public_tier());
+        assert_eq!(in_public_tier, (true, *PUBLIC_TRACE_LIMITS));
+        assert_eq!(current_trace_limits(), *TRACE_LIMITS);
+    }
+
+    /// Executes the code with the inspector, from an account holding the code.
+    fn inspect(code: &[u8], mut inspector: LimitedInspector) -> Result<CallTraceArena, String> {
+        let contract = Address::with_last_byte(0xc0);
+        let mut db = CacheDB::new(EmptyDB::default());
+        db.insert_account_info(
+            contract,
+            AccountInfo { code: Some(Bytecode::new_raw(Bytes::copy_from_slice(code))), ..Default::default() },
+        );
+
+        let mut env = Env::default();
+        env.tx.transact_to = TxKind::Call(contract);
+        env.tx.gas_limit = 1_000_000;
+        let env = EnvWithHandlerCfg::new_with_spec_id(Box::new(env), SpecId::CANCUN);
+
+        {
+            let mut evm =
+                EthEvmConfig::new(Arc::new(Default::default())).evm_with_env_and_inspector(db, env, &mut inspector);
+            evm.transact().map_err(|err| err.to_string())?;
+        }
+
+        Ok(inspector.into_inner().into_traces())
+    }
+
+    #[test]
+    fn test_limited_inspector_request_limit() {
+        // Given
+        // 10 times PUSH0 POP, then STOP
+        let code = [[0x5f, 0x50].repeat(10), vec![0x00]].concat();
+        let inspector = LimitedInspector::new(TracingInspectorConfig::default_geth(), TraceLimits::default(), 5);
+
+        // When
+        let traces = inspect(&code, inspector).unwrap();
+
+        // Then
+        assert_eq!(traces.nodes()[0].trace.steps.len(), 5);
+    }
+
+    #[test]
+    fn test_limited_inspector_struct_logs() {
+        // Given
+        // 10 times PUSH0 POP, then STOP
+        let code = [[0x5f, 0x50].repeat(10), vec![0x00]].concat();
+        let limits = TraceLimits { max_struct_logs: 5, ..Default::default() };
+        let inspector = LimitedInspector::new(TracingInspectorConfig::default_geth(), limits, u64::MAX);
+
+        // When
+        let err = inspect(&code, inspector).unwrap_err();
+
+        // Then
+        assert!(err.contains("trace exceeds the limit of 5 struct logs"));
+    }
+
+    #[test]
+    fn test_limited_inspector_call_frames() {
+        // Given
+        // The contract calls itself until it runs out of gas: PUSH0 x5 ADDRESS GAS CALL STOP
+        let code = [0x5f, 0x5f, 0x5f, 0x5f, 0x5f, 0x30, 0x5a, 0xf1, 0x00];
+        let limits = TraceLimits { max_call_frames: 3, ..Default::default() };
+        let inspector = LimitedInspector::new(TracingInspectorConfig::default_parity(), limits, u64::MAX);
+
+        // When
+        let err = inspect(&code, inspector).unwrap_err();
+
+        // Then
+        assert!(err.contains("trace exceeds the limit of 3 call frames"));
+    }
+
+    #[test]
+    fn test_limited_inspector_memory() {
+        // Given
+        // PUSH2 0x0100 PUSH0 MSTORE, PUSH0 PUSH2 0x0100 MSTORE, STOP: the memory grows from 32 to
+        // 288 bytes at the last step
+        let code = [0x61, 0x01, 0x00, 0x5f, 0x52, 0x5f, 0x61, 0x01, 0x00, 0x52, 0x00];
+        let config = TracingInspectorConfig::default_geth().set_memory_snapshots(true);
+        let within_limit =
+            LimitedInspector::new(config, TraceLimits { max_memory_bytes: 288, ..Default::default() }, u64::MAX);
+        let over_limit =
+            LimitedInspector::new(config, TraceLimits { max_memory_bytes: 64, ..Default::default() }, u64::MAX);
+
+        // When
+        let traces = inspect(&code, within_limit).unwrap();
+        let err = inspect(&code, over_limit).unwrap_err();
+
+        // Then
+        assert_eq!(traces.nodes()[0].trace.steps.len(), 7);
+        assert!(err.contains("trace exceeds the limit of 64 bytes of memory"));
+    }
+}
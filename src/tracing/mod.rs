@@ -1,4 +1,6 @@
 pub mod builder;
+pub mod diff;
+pub mod limits;
 
 use crate::{
     providers::eth_provider::{
@@ -6,7 +8,10 @@ use crate::{
         error::{EthApiError, TransactionError},
         provider::EthereumProvider,
     },
-    tracing::builder::TracingOptions,
+    tracing::{
+        builder::TracingOptions,
+        limits::{LimitedInspector, TraceLimits},
+    },
 };
 use alloy_primitives::{ruint::FromUintError, B256};
 use alloy_rpc_types::{TransactionInfo, TransactionRequest};
@@ -22,7 +27,7 @@ use eyre::eyre;
 use reth_evm_ethereum::EthEvmConfig;
 use reth_node_api::{ConfigureEvm, ConfigureEvmEnv};
 use reth_revm::{
-    primitives::{Env, EnvWithHandlerCfg},
+    primitives::{Env, EnvWithHandlerCfg, ExecutionResult},
     DatabaseCommit,
 };
 use revm_inspectors::tracing::{TracingInspector, TracingInspectorConfig};
@@ -86,6 +91,7 @@ pub struct Tracer<P: EthereumProvider + Send + Sync> {
     env: EnvWithHandlerCfg,
     db: EthCacheDatabase<P>,
     tracing_options: TracingOptions,
+    trace_limits: TraceLimits,
 }
 
 impl<P: EthereumProvider + Send + Sync + Clone> Tracer<P> {
@@ -95,6 +101,7 @@ impl<P: EthereumProvider + Send + Sync + Clone> Tracer<P> {
         db: &EthCacheDatabase<P>,
         tx: &WithOtherFields<alloy_rpc_types::Transaction>,
         opts: GethDebugTracingOptions,
+        trace_limits: &TraceLimits,
     ) -> TracingStateResult {
         // Extract options
         let GethDebugTracingOptions { tracer_config, config, tracer, .. } = opts;
@@ -110,9 +117,12 @@ impl<P: EthereumProvider + Send + Sync + Clone> Tracer<P> {
                         .into_call_config()
                         .map_err(|err| EthApiError::Transaction(TransactionError::Tracing(err.into())))?;
 
-                    // Initialize tracing inspector with call config
-                    let mut inspector =
-                        TracingInspector::new(TracingInspectorConfig::from_geth_call_config(&call_config));
+                    // Initialize tracing inspector with call config, recording up to the limits
+                    let mut inspector = LimitedInspector::new(
+                        TracingInspectorConfig::from_geth_call_config(&call_config),
+                        *trace_limits,
+                        trace_limits.max_struct_logs,
+                    );
 
                     // Build EVM with environment and inspector
                     let eth_evm_config = EthEvmConfig::new(Arc::new(Default::default()));
@@ -125,7 +135,7 @@ impl<P: EthereumProvider + Send + Sync + Clone> Tracer<P> {
                     };
 
                     // Get call traces
-                    let call_frame = inspector.into_geth_builder().geth_call_traces(
+                    let call_frame = inspector.into_inner().into_geth_builder().geth_call_traces(
                         tracer_config.into_call_config().map_err(|err| TransactionError::Tracing(err.into()))?,
                         res.result.gas_used(),
                     );
@@ -148,8 +158,12 @@ impl<P: EthereumProvider + Send + Sync + Clone> Tracer<P> {
             }
         }
 
-        // Use default tracer
-        let mut inspector = TracingInspector::new(TracingInspectorConfig::from_geth_config(&config));
+        // Use default tracer, recording up to the limit of the options
+        let mut inspector = LimitedInspector::new(
+            TracingInspectorConfig::from_geth_config(&config),
+            *trace_limits,
+            config.limit.unwrap_or(trace_limits.max_struct_logs),
+        );
         let eth_evm_config = EthEvmConfig::new(Arc::new(Default::default()));
 
         let res = {
@@ -160,11 +174,9 @@ impl<P: EthereumProvider + Send + Sync + Clone> Tracer<P> {
 
         let gas_used = res.result.gas_used();
         let return_value = res.result.into_output().unwrap_or_default();
-        let frame = inspector.into_geth_builder().geth_traces(gas_used, return_value, config);
-        Ok((
-            TracingResult::Geth(vec![TraceResult::Success { result: frame.into(), tx_hash: Some(tx.hash) }]),
-            res.state,
-        ))
+        let trace: GethTrace =
+            inspector.into_inner().into_geth_builder().geth_traces(gas_used, return_value, config).into();
+        Ok((TracingResult::Geth(vec![TraceResult::Success { result: trace, tx_hash: Some(tx.hash) }]), res.state))
     }
 
     /// Traces the transaction with Parity tracing options and returns the resulting traces and state.
@@ -173,6 +185,7 @@ impl<P: EthereumProvider + Send + Sync + Clone> Tracer<P> {
         db: &EthCacheDatabase<P>,
         tx: &WithOtherFields<alloy_rpc_types::Transaction>,
         tracing_config: TracingInspectorConfig,
+        trace_limits: &TraceLimits,
     ) -> TracingStateResult {
         // Get block base fee
         let block_base_fee = env
@@ -182,8 +195,8 @@ impl<P: EthereumProvider + Send + Sync + Clone> Tracer<P> {
             .try_into()
             .map_err(|err: FromUintError<u128>| TransactionError::Tracing(err.into()))?;
 
-        // Initialize tracing inspector with given config
-        let mut inspector = TracingInspector::new(tracing_config);
+        // Initialize tracing inspector with given config, recording up to the limits
+        let mut inspector = LimitedInspector::new(tracing_config, *trace_limits, trace_limits.max_struct_logs);
 
         // Build EVM with environment and inspector
         let eth_evm_config = EthEvmConfig::new(Arc::new(Default::default()));
@@ -199,11 +212,10 @@ impl<P: EthereumProvider + Send + Sync + Clone> Tracer<P> {
         // Create transaction info
         let transaction_info = TransactionInfo::from(&tx.inner).with_base_fee(block_base_fee);
 
+        let traces = inspector.into_inner().into_parity_builder().into_localized_transaction_traces(transaction_info);
+
         // Return Parity trace result
-        Ok((
-            TracingResult::Parity(inspector.into_parity_builder().into_localized_transaction_traces(transaction_info)),
-            res.state,
-        ))
+        Ok((TracingResult::Parity(traces), res.state))
     }
 
     /// Trace the block in the parity format.
@@ -244,6 +256,24 @@ impl<P: EthereumProvider + Send + Sync + Clone> Tracer<P> {
         Err(EthApiError::TransactionNotFound(transaction_hash))
     }
 
+    /// Executes the transaction with the given hash on top of the previous transactions of the
+    /// block, without tracing it.
+    pub fn execute_transaction(mut self, transaction_hash: B256) -> TracerResult<ExecutionResult> {
+        for tx in self.transactions.clone() {
+            let env = env_with_tx(&self.env, &tx)?;
+            let eth_evm_config = EthEvmConfig::new(Arc::new(Default::default()));
+
+            let mut evm = eth_evm_config.evm_with_env(&mut self.db.0, env);
+            if tx.hash == transaction_hash {
+                let res = evm.transact().map_err(|err| TransactionError::Tracing(err.into()))?;
+                return Ok(res.result);
+            }
+            evm.transact_commit().map_err(|err| TransactionError::Tracing(err.into()))?;
+        }
+
+        Err(EthApiError::TransactionNotFound(transaction_hash))
+    }
+
     /// Debugs a transaction request by tracing it using the provided tracing options.
     ///
     /// This function returns an error if the tracing options are not supported or if there is an issue
@@ -283,9 +313,12 @@ impl<P: EthereumProvider + Send + Sync + Clone> Tracer<P> {
                     let call_config =
                         tracer_config.into_call_config().map_err(|err| TransactionError::Tracing(err.into()))?;
 
-                    // Create a new tracing inspector with the call configuration.
-                    let mut inspector =
-                        TracingInspector::new(TracingInspectorConfig::from_geth_call_config(&call_config));
+                    // Create a new tracing inspector with the call configuration, recording up to the limits.
+                    let mut inspector = LimitedInspector::new(
+                        TracingInspectorConfig::from_geth_call_config(&call_config),
+                        self.trace_limits,
+                        self.trace_limits.max_struct_logs,
+                    );
 
                     // Build EVM with environment and inspector.
                     let eth_evm_config = EthEvmConfig::new(Arc::new(Default::default()));
@@ -305,7 +338,7 @@ impl<P: EthereumProvider + Send + Sync + Clone> Tracer<P> {
                     };
 
                     // Get the call traces from the inspector.
-                    let frame = inspector.into_geth_builder().geth_call_traces(call_config, gas_used);
+                    let frame = inspector.into_inner().into_geth_builder().geth_call_traces(call_config, gas_used);
 
                     // Return the obtained call traces.
                     return Ok(frame.into());
@@ -324,6 +357,15 @@ impl<P: EthereumProvider + Send + Sync + Clone> Tracer<P> {
         Ok(GethTrace::Default(Default::default()))
     }
 
+    /// Returns an iterator over the Geth traces of the transactions of the block.
+    ///
+    /// Each transaction is traced when the iterator is advanced, so the traces of the whole
+    /// block are never kept in memory at the same time.
+    pub fn debug_block_traces(self) -> TransactionTraces<P, TraceResult> {
+        let txs = self.transactions.clone();
+        self.into_traces(TracingResult::as_geth, txs)
+    }
+
     /// Traces the provided transactions using the given closure.
     /// The `convert_result` closure takes the resulting tracing result
     /// and converts it into the desired type.
@@ -332,38 +374,84 @@ impl<P: EthereumProvider + Send + Sync + Clone> Tracer<P> {
         convert_result: fn(&TracingResult) -> Option<&Vec<T>>,
         transactions: &[WithOtherFields<alloy_rpc_types::Transaction>],
     ) -> TracerResult<Vec<T>> {
-        let mut traces: Vec<T> = Vec::with_capacity(self.transactions.len());
-        let mut transactions = transactions.iter().peekable();
-        let mut db = self.db;
-
-        while let Some(tx) = transactions.next() {
-            let env = env_with_tx(&self.env, tx)?;
-
-            let (res, state_changes) = if tx.other.get("reverted").is_some() {
-                (TracingResult::default_failure(&self.tracing_options, tx), HashMap::default())
-            } else {
-                match &self.tracing_options {
-                    TracingOptions::Geth(opts) => Self::trace_geth(env, &db, tx, opts.clone())?,
-                    TracingOptions::Parity(tracing_config) => Self::trace_parity(env, &db, tx, *tracing_config)?,
-                    TracingOptions::GethCall(_) => {
-                        return Err(EthApiError::Transaction(TransactionError::Tracing(
-                            eyre!("`TracingOptions::GethCall` is not supported in `trace_transactions` context").into(),
-                        )))
-                    }
-                }
-            };
+        let mut traces: Vec<T> = Vec::with_capacity(transactions.len());
+        for result in self.into_traces(convert_result, transactions.to_vec()) {
+            traces.append(&mut result?);
+        }
 
-            if let Some(result) = convert_result(&res) {
-                traces.append(&mut result.clone());
-            }
+        TracerResult::Ok(traces)
+    }
 
-            // Only commit to the database if there are more transactions to process.
-            if transactions.peek().is_some() {
-                db.0.commit(state_changes);
+    /// Converts the tracer into an iterator tracing the provided transactions one by one.
+    fn into_traces<T: Clone>(
+        self,
+        convert_result: fn(&TracingResult) -> Option<&Vec<T>>,
+        transactions: Vec<WithOtherFields<alloy_rpc_types::Transaction>>,
+    ) -> TransactionTraces<P, T> {
+        TransactionTraces {
+            transactions: transactions.into_iter(),
+            env: self.env,
+            db: self.db,
+            tracing_options: self.tracing_options,
+            trace_limits: self.trace_limits,
+            convert_result,
+        }
+    }
+}
+
+/// An iterator over the traces of transactions, tracing each transaction on top of the state
+/// of the previous ones. The iterator stops after the first error.
+#[derive(Debug)]
+pub struct TransactionTraces<P: EthereumProvider + Send + Sync, T> {
+    transactions: std::vec::IntoIter<WithOtherFields<alloy_rpc_types::Transaction>>,
+    env: EnvWithHandlerCfg,
+    db: EthCacheDatabase<P>,
+    tracing_options: TracingOptions,
+    trace_limits: TraceLimits,
+    convert_result: fn(&TracingResult) -> Option<&Vec<T>>,
+}
+
+impl<P: EthereumProvider + Send + Sync + Clone, T: Clone> TransactionTraces<P, T> {
+    /// Traces the transaction, committing its state changes if there are more transactions to process.
+    fn trace(&mut self, tx: &WithOtherFields<alloy_rpc_types::Transaction>) -> TracerResult<Vec<T>> {
+        let env = env_with_tx(&self.env, tx)?;
+
+        let (res, state_changes) = if tx.other.get("reverted").is_some() {
+            (TracingResult::default_failure(&self.tracing_options, tx), HashMap::default())
+        } else {
+            match &self.tracing_options {
+                TracingOptions::Geth(opts) => Tracer::trace_geth(env, &self.db, tx, opts.clone(), &self.trace_limits)?,
+                TracingOptions::Parity(tracing_config) => {
+                    Tracer::trace_parity(env, &self.db, tx, *tracing_config, &self.trace_limits)?
+                }
+                TracingOptions::GethCall(_) => {
+                    return Err(EthApiError::Transaction(TransactionError::Tracing(
+                        eyre!("`TracingOptions::GethCall` is not supported in `trace_transactions` context").into(),
+                    )))
+                }
             }
+        };
+
+        // Only commit to the database if there are more transactions to process.
+        if !self.transactions.as_slice().is_empty() {
+            self.db.0.commit(state_changes);
         }
 
-        TracerResult::Ok(traces)
+        Ok((self.convert_result)(&res).cloned().unwrap_or_default())
+    }
+}
+
+impl<P: EthereumProvider + Send + Sync + Clone, T: Clone> Iterator for TransactionTraces<P, T> {
+    type Item = TracerResult<Vec<T>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let tx = self.transactions.next()?;
+        let result = self.trace(&tx);
+        if result.is_err() {
+            // Skip the remaining transactions, which would be traced on top of an invalid state
+            self.transactions = Vec::new().into_iter();
+        }
+        Some(result)
     }
 }
 
@@ -0,0 +1,121 @@
+#![allow(clippy::used_underscore_binding)]
+#![cfg(all(feature = "testing", feature = "chaos"))]
+use alloy_primitives::{Address, Bytes};
+use kakarot_rpc::{
+    chaos::FaultConfig,
+    models::felt::Felt252Wrapper,
+    providers::eth_provider::{
+        database::ethereum::EthereumTransactionStore, starknet::kakarot_core::KAKAROT_ADDRESS, ReceiptProvider,
+        StateProvider,
+    },
+    test_utils::{
+        eoa::Eoa,
+        evm_contract::KakarotEvmContract,
+        fixtures::{chaos, setup},
+        katana::{FaultyEthClient, Katana},
+    },
+};
+use rstest::*;
+use starknet::core::types::{BlockId, BlockTag};
+
+/// The maximum number of attempts of a read under faults.
+const MAX_ATTEMPTS: usize = 64;
+
+/// Configures the fault injector of the client, keeping its seed.
+fn inject_faults(eth_client: &FaultyEthClient, starknet_drop_probability: f64) {
+    let injector = eth_client.eth_provider().starknet_provider_inner().injector();
+    let config = FaultConfig { starknet_drop_probability, seed: injector.config().seed, ..Default::default() };
+    injector.set_config(config).expect("Failed to configure the fault injector");
+}
+
+/// Reads the code of the address under faults, dropping half of the Starknet calls.
+async fn read_code_under_faults(eth_client: &FaultyEthClient, address: Address) -> Vec<Option<Bytes>> {
+    inject_faults(eth_client, 0.5);
+    let mut reads = Vec::with_capacity(MAX_ATTEMPTS);
+    for _ in 0..MAX_ATTEMPTS {
+        reads.push(eth_client.eth_provider().get_code(address, None).await.ok());
+    }
+    reads
+}
+
+#[rstest]
+#[awt]
+#[tokio::test(flavor = "multi_thread")]
+async fn test_dropped_starknet_calls_fail_the_reads(
+    #[future] chaos: (Katana, KakarotEvmContract, FaultyEthClient),
+    _setup: (),
+) {
+    // Given
+    let (katana, counter, eth_client) = chaos;
+    let counter_address: Address =
+        Felt252Wrapper::from(counter.evm_address).try_into().expect("Failed to convert EVM address");
+    let eoa_address = katana.eoa().evm_address().unwrap();
+    let expected_code = katana.eth_provider().get_code(counter_address, None).await.unwrap();
+
+    // When
+    inject_faults(&eth_client, 1.);
+    let dropped_code = eth_client.eth_provider().get_code(counter_address, None).await;
+    let dropped_balance = eth_client.eth_provider().balance(eoa_address, None).await;
+    inject_faults(&eth_client, 0.);
+    let code = eth_client.eth_provider().get_code(counter_address, None).await.unwrap();
+
+    // Then
+    assert!(dropped_code.is_err());
+    assert!(dropped_balance.is_err());
+    assert_eq!(code, expected_code);
+}
+
+#[rstest]
+#[awt]
+#[tokio::test(flavor = "multi_thread")]
+async fn test_faults_are_reproducible(#[future] chaos: (Katana, KakarotEvmContract, FaultyEthClient), _setup: ()) {
+    // Given
+    let (katana, counter, eth_client) = chaos;
+    let counter_address: Address =
+        Felt252Wrapper::from(counter.evm_address).try_into().expect("Failed to convert EVM address");
+    let expected_code = katana.eth_provider().get_code(counter_address, None).await.unwrap();
+
+    // When
+    let first_run = read_code_under_faults(&eth_client, counter_address).await;
+    let second_run = read_code_under_faults(&eth_client, counter_address).await;
+
+    // Then
+    assert_eq!(first_run, second_run);
+    assert!(first_run.contains(&None));
+    assert!(first_run.iter().flatten().all(|code| code == &expected_code));
+}
+
+#[rstest]
+#[awt]
+#[tokio::test(flavor = "multi_thread")]
+async fn test_pending_receipt_under_faults(#[future] chaos: (Katana, KakarotEvmContract, FaultyEthClient), _setup: ()) {
+    // Given
+    let (katana, counter, eth_client) = chaos;
+    let eoa = katana.eoa();
+    let eth_provider = eth_client.eth_provider();
+    let transaction = eoa.call_evm_contract(&counter, "inc", &[], 0).await.expect("Failed to increment counter");
+    let transaction = eoa.sign_transaction(transaction).expect("Failed to sign transaction");
+    let hash = transaction.hash();
+    let starknet_hash = eth_provider.database().starknet_transaction_hash(&hash).await.unwrap().expect("Missing hash");
+    let expected = katana.eth_provider().transaction_receipt(hash).await.unwrap().expect("Missing receipt");
+    eth_provider.pending_receipts().insert_relayed(starknet_hash, transaction, eoa.evm_address().unwrap());
+
+    // When
+    inject_faults(&eth_client, 0.5);
+    let mut attempts = 0;
+    let block = loop {
+        attempts += 1;
+        match eth_provider.starknet_block(BlockId::Tag(BlockTag::Latest), None).await {
+            Ok(block) => break block,
+            Err(err) => assert!(attempts < MAX_ATTEMPTS, "failed to fetch the block under faults: {err}"),
+        }
+    };
+    let applied = eth_provider.pending_receipts().apply_block(&block, *KAKAROT_ADDRESS);
+
+    // Then
+    assert_eq!(applied, 1);
+    let receipt = eth_provider.pending_receipts().receipt(&hash).expect("Missing pending receipt");
+    assert_eq!(receipt.transaction_hash, hash);
+    assert_eq!(receipt.status(), expected.status());
+    assert_eq!(receipt.gas_used, expected.gas_used);
+}
@@ -0,0 +1,69 @@
+#![allow(clippy::used_underscore_binding)]
+#![cfg(feature = "conformance")]
+use alloy_primitives::Address;
+use kakarot_rpc::{
+    models::felt::Felt252Wrapper,
+    test_utils::{
+        conformance::ConformanceSuite, environment::TestEnvironmentBuilder, fixtures::setup, rpc::RawRpcParamsBuilder,
+    },
+};
+use rstest::*;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+
+#[rstest]
+#[tokio::test(flavor = "multi_thread")]
+async fn test_client_conformance(_setup: ()) {
+    // Given
+    let suite = ConformanceSuite::load(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/conformance/clients.json"))
+        .expect("Failed to load the conformance vectors");
+    let env = TestEnvironmentBuilder::new()
+        .with_random_data()
+        .with_erc20()
+        .build()
+        .await
+        .expect("Failed to build the test environment");
+
+    let transaction = env.katana.most_recent_transaction().expect("Failed to get the most recent transaction");
+    let erc20: Address = Felt252Wrapper::from(env.erc20.as_ref().expect("Missing ERC20").evm_address)
+        .try_into()
+        .expect("Failed to convert EVM address");
+    let placeholders = HashMap::from([
+        ("transactionHash".to_string(), json!(transaction.hash)),
+        ("blockHash".to_string(), json!(transaction.block_hash)),
+        ("blockNumber".to_string(), json!(transaction.block_number.map(|number| format!("{number:#x}")))),
+        ("sender".to_string(), json!(transaction.from)),
+        ("erc20".to_string(), json!(erc20)),
+    ]);
+
+    // When
+    let client = reqwest::Client::new();
+    let mut failures = Vec::new();
+    for vector in &suite.vectors {
+        let request = vector
+            .params(&placeholders)
+            .into_iter()
+            .fold(RawRpcParamsBuilder::new(&vector.method), RawRpcParamsBuilder::add_param)
+            .build();
+        let res = client
+            .post(env.rpc_url())
+            .header("Content-Type", "application/json")
+            .body(request)
+            .send()
+            .await
+            .expect("Failed to call the RPC");
+        let response: Value = serde_json::from_str(&res.text().await.expect("Failed to get response body"))
+            .expect("Failed to deserialize response body");
+
+        let result = match response.get("error") {
+            Some(error) => Err(format!("error {error}")),
+            None => suite.check(&response["result"], &vector.result).map_err(|mismatch| mismatch.to_string()),
+        };
+        if let Err(err) = result {
+            failures.push(format!("[{}] {}: {err}", vector.client, vector.method));
+        }
+    }
+
+    // Then
+    assert!(failures.is_empty(), "responses incompatible with the client libraries:\n{}", failures.join("\n"));
+}
@@ -80,16 +80,55 @@ async fn test_raw_transaction(#[future] katana: Katana, _setup: ()) {
         });
     }
 
-    // For EIP1559, `gas_price` is recomputed as per https://github.com/paradigmxyz/reth/blob/603e39ab74509e0863fc023461a4c760fb2126d1/crates/rpc/rpc-types-compat/src/transaction/mod.rs#L54
-    if rpc_transaction.transaction_type.unwrap() == 2 {
-        transaction.gas_price = rpc_transaction.gas_price;
-    }
-
     assert_eq!(transaction, rpc_transaction);
 
     drop(server_handle);
 }
 
+#[rstest]
+#[awt]
+#[tokio::test(flavor = "multi_thread")]
+async fn test_eth_raw_transaction(#[future] katana: Katana, _setup: ()) {
+    let (server_addr, server_handle) =
+        start_kakarot_rpc_server(&katana).await.expect("Error setting up Kakarot RPC server");
+
+    // First transaction of the database
+    let tx = katana.first_transaction().unwrap();
+    let reqwest_client = reqwest::Client::new();
+
+    let requests = [
+        RawRpcParamsBuilder::new("eth_getRawTransactionByHash").add_param(format!("0x{:064x}", tx.hash)).build(),
+        RawRpcParamsBuilder::new("eth_getRawTransactionByBlockHashAndIndex")
+            .add_param(format!("0x{:064x}", tx.block_hash.unwrap()))
+            .add_param(format!("0x{:x}", tx.transaction_index.unwrap()))
+            .build(),
+        RawRpcParamsBuilder::new("eth_getRawTransactionByBlockNumberAndIndex")
+            .add_param(format!("0x{:x}", tx.block_number.unwrap()))
+            .add_param(format!("0x{:x}", tx.transaction_index.unwrap()))
+            .build(),
+    ];
+
+    for request in requests {
+        let res = reqwest_client
+            .post(format!("http://localhost:{}", server_addr.port()))
+            .header("Content-Type", "application/json")
+            .body(request)
+            .send()
+            .await
+            .expect("Failed to call Eth RPC");
+        let response = res.text().await.expect("Failed to get response body");
+        let raw: Value = serde_json::from_str(&response).expect("Failed to deserialize response body");
+        let rlp_bytes: Option<Bytes> =
+            serde_json::from_value(raw["result"].clone()).expect("Failed to deserialize result");
+
+        // The decoded transaction must hash to the requested transaction
+        let transaction = TransactionSigned::decode_2718(&mut rlp_bytes.unwrap().as_ref()).unwrap();
+        assert_eq!(transaction.hash(), tx.hash);
+    }
+
+    drop(server_handle);
+}
+
 #[rstest]
 #[awt]
 #[tokio::test(flavor = "multi_thread")]
@@ -0,0 +1,42 @@
+#![allow(clippy::used_underscore_binding)]
+#![cfg(feature = "testing")]
+use alloy_primitives::{Address, U256};
+use kakarot_rpc::{
+    providers::eth_provider::StateProvider,
+    test_utils::{environment::TestEnvironmentBuilder, fixtures::setup, rpc::RawRpcParamsBuilder},
+};
+use rstest::*;
+use serde_json::Value;
+
+#[rstest]
+#[tokio::test(flavor = "multi_thread")]
+async fn test_environment_builder(_setup: ()) {
+    // Given
+    let address = Address::random();
+    let value = 10u128.pow(18);
+
+    // When
+    let env = TestEnvironmentBuilder::new()
+        .with_funded_account(address, value)
+        .with_erc20()
+        .build()
+        .await
+        .expect("Failed to build the test environment");
+
+    // Then
+    let balance = env.katana.eth_provider().balance(address, None).await.expect("Failed to get balance");
+    assert_eq!(balance, U256::from(value));
+    assert!(env.erc20.is_some());
+    assert!(env.erc721.is_none());
+
+    let res = reqwest::Client::new()
+        .post(env.rpc_url())
+        .header("Content-Type", "application/json")
+        .body(RawRpcParamsBuilder::new("eth_getBalance").add_param(address).add_param("latest").build())
+        .send()
+        .await
+        .expect("eth_getBalance error");
+    let response: Value =
+        serde_json::from_str(&res.text().await.expect("Failed to get response body")).expect("Failed to deserialize");
+    assert_eq!(response["result"], serde_json::to_value(U256::from(value)).unwrap());
+}
@@ -8,25 +8,38 @@ use alloy_rpc_types::{
     request::TransactionInput,
     serde_helpers::JsonStorageKey,
     state::{AccountOverride, StateOverride},
-    Filter, FilterBlockOption, FilterChanges, Log, RpcBlockHash, Topic, TransactionRequest,
+    Bundle, Filter, FilterBlockOption, FilterChanges, Log, RpcBlockHash, Topic, TransactionRequest,
 };
 use alloy_sol_types::{sol, SolCall};
+use alloy_trie::{proof::verify_proof, root::ordered_trie_root_with_encoder, Nibbles};
 use arbitrary::Arbitrary;
 use kakarot_rpc::{
     client::{KakarotTransactions, TransactionHashProvider},
     into_via_try_wrapper,
     models::felt::Felt252Wrapper,
-    providers::eth_provider::{
-        constant::{MAX_LOGS, STARKNET_MODULUS},
-        database::{
-            ethereum::EthereumTransactionStore,
-            filter,
-            filter::EthDatabaseFilterBuilder,
-            types::transaction::{EthStarknetHashes, StoredEthStarknetTransactionHash, StoredTransaction},
+    providers::{
+        eth_provider::{
+            constant::{LOG_QUERY_MAX_BLOCK_RANGE, MAX_LOGS, STARKNET_MODULUS},
+            database::{
+                ethereum::EthereumTransactionStore,
+                filter,
+                filter::EthDatabaseFilterBuilder,
+                types::{
+                    header::StoredHeader,
+                    receipt::StoredTransactionReceipt,
+                    transaction::{DropReason, EthStarknetHashes, StoredEthStarknetTransactionHash, StoredTransaction},
+                },
+                CollectionName,
+            },
+            fallback::SUBMISSION_STATUS_FIELD,
+            logs::LogQuery,
+            provider::EthereumProvider,
+            starknet::{kakarot_core::KAKAROT_ADDRESS, relayer::Relayer},
+            transactions::AddressHistoryRequest,
+            BlockProvider, ChainProvider, GasProvider, LogProvider, ReceiptProvider, StateProvider,
+            TransactionProvider,
         },
-        provider::EthereumProvider,
-        starknet::relayer::Relayer,
-        BlockProvider, ChainProvider, GasProvider, LogProvider, ReceiptProvider, StateProvider, TransactionProvider,
+        sn_provider::{batch::BatchConfig, BatchingProvider},
     },
     test_utils::{
         eoa::Eoa,
@@ -36,15 +49,17 @@ use kakarot_rpc::{
         tx_waiter::watch_tx,
     },
 };
+use mongodb::bson::{doc, Document};
 use rand::Rng;
-use reth_primitives::{sign_message, Transaction, TransactionSigned};
+use reth_primitives::{sign_message, Receipt, ReceiptWithBloom, Transaction, TransactionSigned};
 use reth_transaction_pool::{TransactionOrigin, TransactionPool};
 use rstest::*;
 use starknet::{
     accounts::Account,
-    core::types::{BlockId, BlockTag, Felt},
+    core::types::{BlockId, BlockStatus, BlockTag, Felt},
+    providers::Provider as _,
 };
-use std::sync::Arc;
+use std::{sync::Arc, time::Duration};
 
 #[rstest]
 #[awt]
@@ -92,6 +107,26 @@ async fn test_block_by_hash(#[future] katana: Katana, _setup: ()) {
     assert_eq!(block.inner.header, katana.header_by_hash(block_hash).unwrap());
 }
 
+#[rstest]
+#[awt]
+#[tokio::test(flavor = "multi_thread")]
+async fn test_block_by_starknet_hash(#[future] katana: Katana, _setup: ()) {
+    // Given
+    let eth_provider = katana.eth_provider();
+    let block_hash = katana.most_recent_transaction().unwrap().block_hash.unwrap();
+    let header = katana.header_by_hash(block_hash).unwrap();
+
+    // When
+    let block = eth_provider.block_by_starknet_hash(block_hash, false).await.unwrap().unwrap();
+    let unknown = eth_provider.block_by_starknet_hash(B256::random(), false).await.unwrap();
+
+    // Then
+    assert_eq!(block.header.number, header.number);
+    assert_eq!(block.other.get("starknetBlockHash"), Some(&serde_json::json!(block_hash.to_string())));
+    assert_eq!(block.other.get("starknetBlockNumber"), Some(&serde_json::json!(format!("{:#x}", header.number))));
+    assert!(unknown.is_none());
+}
+
 #[rstest]
 #[awt]
 #[tokio::test(flavor = "multi_thread")]
@@ -131,6 +166,30 @@ async fn test_block_by_number(#[future] katana: Katana, _setup: ()) {
     assert_eq!(block.header.number, block_number);
 }
 
+#[rstest]
+#[awt]
+#[tokio::test(flavor = "multi_thread")]
+async fn test_block_by_number_ignores_transactions_of_replaced_block(#[future] katana: Katana, _setup: ()) {
+    // Given
+    let eth_provider = katana.eth_provider();
+    let transaction = katana.most_recent_transaction().unwrap();
+    let block_number = transaction.block_number.unwrap();
+
+    // A transaction indexed with the same block number, but for another block
+    let mut stale_transaction = transaction.clone();
+    stale_transaction.hash = B256::random();
+    stale_transaction.block_hash = Some(B256::random());
+    eth_provider.database().upsert_transaction(stale_transaction.clone()).await.unwrap();
+
+    // When
+    let block = eth_provider.block_by_number(block_number.into(), false).await.unwrap().unwrap();
+
+    // Then
+    let hashes = block.transactions.hashes().collect::<Vec<_>>();
+    assert!(hashes.contains(&transaction.hash));
+    assert!(!hashes.contains(&stale_transaction.hash));
+}
+
 #[rstest]
 #[awt]
 #[tokio::test(flavor = "multi_thread")]
@@ -482,6 +541,36 @@ async fn test_get_logs_block_hash(#[future] katana: Katana, _setup: ()) {
     assert!(filtered_logs.iter().all(|log| log.block_hash.unwrap() == block_hash));
 }
 
+#[rstest]
+#[awt]
+#[tokio::test(flavor = "multi_thread")]
+async fn test_query_logs(#[future] katana: Katana, _setup: ()) {
+    // Given
+    let provider = katana.eth_provider();
+    let block_number = katana.first_transaction().unwrap().block_number.unwrap();
+    let mut expected = katana.logs_by_block_number(block_number);
+    expected.sort_by_key(|log| log.log_index);
+
+    // When
+    let query = LogQuery {
+        from_block: Some(block_number.into()),
+        to_block: Some(block_number.into()),
+        limit: Some(1000),
+        ..Default::default()
+    };
+    let mut result = provider.query_logs(query).await.unwrap();
+    result.logs.sort_by_key(|log| log.log_index);
+    let too_many_blocks =
+        LogQuery { from_block: Some(0.into()), to_block: Some(LOG_QUERY_MAX_BLOCK_RANGE.into()), ..Default::default() };
+    let too_many_addresses = LogQuery { addresses: vec![Address::ZERO; 101], ..Default::default() };
+
+    // Then
+    assert_eq!(result.logs, expected);
+    assert_eq!(result.next_cursor, None);
+    assert!(provider.query_logs(too_many_blocks).await.is_err());
+    assert!(provider.query_logs(too_many_addresses).await.is_err());
+}
+
 #[rstest]
 #[awt]
 #[tokio::test(flavor = "multi_thread")]
@@ -542,6 +631,27 @@ async fn test_estimate_gas(#[future] counter: (Katana, KakarotEvmContract), _set
     assert!(estimate > U256::from(0));
 }
 
+#[rstest]
+#[awt]
+#[tokio::test(flavor = "multi_thread")]
+async fn test_estimate_gas_max_init_code_size_exceeded(#[future] katana: Katana, _setup: ()) {
+    // Given
+    let eoa = katana.eoa();
+    let eth_provider = katana.eth_provider();
+    let request = TransactionRequest {
+        from: Some(eoa.evm_address().unwrap()),
+        to: Some(TxKind::Create),
+        input: TransactionInput { input: Some(Bytes::from(vec![0; 49_153])), data: None },
+        ..Default::default()
+    };
+
+    // When
+    let err = eth_provider.estimate_gas(request, None).await.unwrap_err();
+
+    // Then
+    assert_eq!(err.to_string(), "max initcode size exceeded: code size 49153, limit 49152");
+}
+
 #[rstest]
 #[awt]
 #[tokio::test(flavor = "multi_thread")]
@@ -573,6 +683,26 @@ async fn test_fee_history(#[future] katana: Katana, _setup: ()) {
     assert_eq!(fee_history.oldest_block, 0);
 }
 
+#[rstest]
+#[awt]
+#[tokio::test(flavor = "multi_thread")]
+async fn test_suggest_fees(#[future] katana: Katana, _setup: ()) {
+    // Given
+    let eth_provider = katana.eth_provider();
+    let gas_price = eth_provider.gas_price().await.unwrap();
+
+    // When
+    let suggestions = eth_provider.suggest_fees().await.unwrap();
+
+    // Then
+    assert_eq!(suggestions.base_fee_per_gas, gas_price);
+    assert!((0. ..=1.).contains(&suggestions.gas_used_ratio));
+    assert!(suggestions.slow.max_fee_per_gas >= gas_price);
+    assert!(suggestions.slow.max_fee_per_gas <= suggestions.normal.max_fee_per_gas);
+    assert!(suggestions.normal.max_fee_per_gas <= suggestions.fast.max_fee_per_gas);
+    assert!(suggestions.slow.max_priority_fee_per_gas <= suggestions.fast.max_priority_fee_per_gas);
+}
+
 #[rstest]
 #[awt]
 #[tokio::test(flavor = "multi_thread")]
@@ -667,6 +797,271 @@ async fn test_block_receipts(#[future] katana: Katana, _setup: ()) {
     assert!(receipts.is_none());
 }
 
+#[rstest]
+#[awt]
+#[tokio::test(flavor = "multi_thread")]
+async fn test_transaction_confirmations(#[future] katana: Katana, _setup: ()) {
+    // Given
+    // The first transaction is mined in the genesis block
+    let eth_provider = katana.eth_provider();
+    let transaction = katana.first_transaction().unwrap();
+    let latest = eth_provider.block_number().await.unwrap().to::<u64>();
+
+    // When
+    let confirmations =
+        eth_provider.transaction_confirmations(transaction.hash).await.unwrap().expect("Missing confirmations");
+
+    // Then
+    assert_eq!(confirmations.transaction_hash, transaction.hash);
+    assert_eq!(confirmations.block_hash, transaction.block_hash.unwrap());
+    assert_eq!(confirmations.block_number, 0);
+    assert_eq!(confirmations.confirmations, latest + 1);
+    assert!(matches!(confirmations.finality_status, BlockStatus::AcceptedOnL2 | BlockStatus::AcceptedOnL1));
+}
+
+#[rstest]
+#[awt]
+#[tokio::test(flavor = "multi_thread")]
+async fn test_transaction_confirmations_unknown_transaction(#[future] katana: Katana, _setup: ()) {
+    // Given
+    let eth_provider = katana.eth_provider();
+
+    // When
+    let confirmations = eth_provider.transaction_confirmations(B256::from(U256::from(0x00c0_fefe))).await.unwrap();
+
+    // Then
+    assert!(confirmations.is_none());
+}
+
+#[rstest]
+#[awt]
+#[tokio::test(flavor = "multi_thread")]
+async fn test_receipt_proof(#[future] katana: Katana, _setup: ()) {
+    // Given
+    let eth_provider = katana.eth_provider();
+    let database = eth_provider.database();
+    let transaction = katana.first_transaction().unwrap();
+    let (hash, block_hash) = (transaction.hash, transaction.block_hash.unwrap());
+
+    // The transaction is the only one of its block, at index 0
+    let filter = EthDatabaseFilterBuilder::<filter::Receipt>::default().with_tx_hash(&hash).build();
+    database
+        .inner()
+        .collection::<Document>(StoredTransactionReceipt::collection_name())
+        .update_one(filter, doc! {"$set": {"receipt.transactionIndex": "0x0"}})
+        .await
+        .unwrap();
+    let receipts = eth_provider.block_receipts(Some(block_hash.into())).await.unwrap().unwrap();
+    assert_eq!(receipts.len(), 1);
+
+    // The receipts root of the header is the root of the trie of the encoded receipts
+    let encoded_receipts = receipts
+        .iter()
+        .map(|receipt| {
+            ReceiptWithBloom {
+                receipt: Receipt {
+                    tx_type: Into::<u8>::into(receipt.transaction_type()).try_into().unwrap(),
+                    success: receipt.inner.status(),
+                    cumulative_gas_used: receipt.inner.inner.cumulative_gas_used().try_into().unwrap(),
+                    logs: receipt
+                        .inner
+                        .inner
+                        .logs()
+                        .iter()
+                        .filter_map(|log| {
+                            reth_primitives::Log::new(log.address(), log.topics().to_vec(), log.data().data.clone())
+                        })
+                        .collect(),
+                },
+                bloom: *receipt.inner.inner.logs_bloom(),
+            }
+            .encoded_2718()
+        })
+        .collect::<Vec<_>>();
+    let receipts_root =
+        ordered_trie_root_with_encoder(&encoded_receipts, |receipt, buf| buf.extend_from_slice(receipt));
+    let filter = EthDatabaseFilterBuilder::<filter::Header>::default().with_block_hash(&block_hash).build();
+    database
+        .inner()
+        .collection::<Document>(StoredHeader::collection_name())
+        .update_one(filter, doc! {"$set": {"header.receiptsRoot": format!("{receipts_root:#x}")}})
+        .await
+        .unwrap();
+
+    // When
+    let proof = eth_provider.receipt_proof(hash).await.unwrap().expect("Missing proof");
+
+    // Then
+    assert_eq!(proof.block_hash, block_hash);
+    assert_eq!(proof.receipts_root, receipts_root);
+    assert_eq!(proof.receipt.as_ref(), encoded_receipts[0].as_slice());
+    assert!(proof.starknet.is_none());
+    verify_proof(receipts_root, Nibbles::unpack(&proof.key), Some(proof.receipt.to_vec()), &proof.proof)
+        .expect("Failed to verify the receipt proof");
+}
+
+#[rstest]
+#[awt]
+#[tokio::test(flavor = "multi_thread")]
+async fn test_receipt_proof_unknown_transaction(#[future] katana: Katana, _setup: ()) {
+    // Given
+    let eth_provider = katana.eth_provider();
+
+    // When
+    let proof = eth_provider.receipt_proof(B256::from(U256::from(0x00c0_fefe))).await.unwrap();
+
+    // Then
+    assert!(proof.is_none());
+}
+
+#[rstest]
+#[awt]
+#[tokio::test(flavor = "multi_thread")]
+async fn test_execution_resources_unknown_transaction(#[future] katana: Katana, _setup: ()) {
+    // Given
+    let eth_provider = katana.eth_provider();
+
+    // When
+    let resources = eth_provider.execution_resources(B256::from(U256::from(0x00c0_fefe))).await.unwrap();
+
+    // Then
+    assert!(resources.is_none());
+}
+
+#[rstest]
+#[awt]
+#[tokio::test(flavor = "multi_thread")]
+async fn test_transaction_starknet_fallback(#[future] counter: (Katana, KakarotEvmContract), _setup: ()) {
+    // Given
+    let katana = counter.0;
+    let counter = counter.1;
+    let eth_provider = katana.eth_provider();
+    let eoa = katana.eoa();
+
+    // The transaction is relayed, but not indexed in the database
+    let transaction = eoa.call_evm_contract(&counter, "inc", &[], 0).await.expect("Failed to increment counter");
+    let hash = eoa.sign_transaction(transaction).expect("Failed to sign transaction").hash();
+
+    // When
+    let transaction = eth_provider.transaction_by_hash(hash).await.unwrap().expect("Missing transaction");
+    let receipt = eth_provider.transaction_receipt(hash).await.unwrap().expect("Missing receipt");
+
+    // Then
+    assert_eq!(transaction.hash, hash);
+    assert_eq!(transaction.from, eoa.evm_address().unwrap());
+    assert_eq!(receipt.transaction_hash, hash);
+    assert_eq!(receipt.block_number, transaction.block_number);
+    assert_eq!(receipt.transaction_index, transaction.transaction_index);
+    assert_eq!(transaction.other[SUBMISSION_STATUS_FIELD], "included");
+    assert!(receipt.status());
+    assert!(receipt.gas_used > 0);
+}
+
+#[rstest]
+#[awt]
+#[tokio::test(flavor = "multi_thread")]
+async fn test_pending_receipt(#[future] counter: (Katana, KakarotEvmContract), _setup: ()) {
+    // Given
+    let katana = counter.0;
+    let counter = counter.1;
+    let eth_provider = katana.eth_provider();
+    let eoa = katana.eoa();
+
+    // The transaction is relayed, but not indexed in the database
+    let transaction = eoa.call_evm_contract(&counter, "inc", &[], 0).await.expect("Failed to increment counter");
+    let transaction = eoa.sign_transaction(transaction).expect("Failed to sign transaction");
+    let hash = transaction.hash();
+    let starknet_hash = eth_provider.database().starknet_transaction_hash(&hash).await.unwrap().expect("Missing hash");
+    eth_provider.pending_receipts().insert_relayed(starknet_hash, transaction, eoa.evm_address().unwrap());
+
+    // When
+    let block = eth_provider.starknet_block(BlockId::Tag(BlockTag::Latest), None).await.unwrap();
+    let applied = eth_provider.pending_receipts().apply_block(&block, *KAKAROT_ADDRESS);
+    let receipt = eth_provider.transaction_receipt(hash).await.unwrap().expect("Missing receipt");
+
+    // Then
+    assert_eq!(applied, 1);
+    assert_eq!(Some(&receipt), eth_provider.pending_receipts().receipt(&hash).as_ref());
+    assert_eq!(receipt.transaction_hash, hash);
+    assert_eq!(receipt.from, eoa.evm_address().unwrap());
+    assert!(receipt.status());
+    assert!(receipt.gas_used > 0);
+}
+
+#[rstest]
+#[awt]
+#[tokio::test(flavor = "multi_thread")]
+async fn test_repair_receipts(#[future] counter: (Katana, KakarotEvmContract), _setup: ()) {
+    // Given
+    let katana = counter.0;
+    let counter = counter.1;
+    let eth_provider = katana.eth_provider();
+    let database = eth_provider.database();
+    let eoa = katana.eoa();
+
+    // The transaction is indexed with a receipt which doesn't match its Starknet transaction
+    let transaction = eoa.call_evm_contract(&counter, "inc", &[], 0).await.expect("Failed to increment counter");
+    let hash = eoa.sign_transaction(transaction).expect("Failed to sign transaction").hash();
+    let transaction = eth_provider.transaction_by_hash(hash).await.unwrap().expect("Missing transaction");
+    let receipt = eth_provider.transaction_receipt(hash).await.unwrap().expect("Missing receipt");
+    database.upsert_transaction(transaction.clone()).await.unwrap();
+    let mut wrong_receipt = receipt.clone();
+    wrong_receipt.gas_used = 0;
+    let filter = EthDatabaseFilterBuilder::<filter::Receipt>::default().with_tx_hash(&hash).build();
+    database.update_one(StoredTransactionReceipt { receipt: wrong_receipt }, filter, true).await.unwrap();
+    let block_number = transaction.block_number.unwrap();
+
+    // When
+    let dry_run = eth_provider.repair_receipts(block_number, block_number, true).await.unwrap();
+    let repair = eth_provider.repair_receipts(block_number, block_number, false).await.unwrap();
+
+    // Then
+    assert!(dry_run.mismatched.contains(&hash));
+    assert!(repair.mismatched.contains(&hash));
+    let repaired = eth_provider.transaction_receipt(hash).await.unwrap().expect("Missing receipt");
+    assert!(repaired.status());
+    assert_eq!(repaired.gas_used, receipt.gas_used);
+}
+
+#[rstest]
+#[awt]
+#[tokio::test(flavor = "multi_thread")]
+async fn test_address_history(#[future] katana: Katana, _setup: ()) {
+    // Given
+    let eth_provider = katana.eth_provider();
+    let address = katana.first_transaction().unwrap().from;
+
+    // The transactions sent by or sent to the address, or which emitted a log from the address
+    let log_hashes: Vec<_> =
+        katana.logs_by_address(&[address]).into_iter().filter_map(|log| log.transaction_hash).collect();
+    let mut expected: Vec<_> = katana
+        .transactions
+        .iter()
+        .filter(|tx| tx.from == address || tx.to == Some(address) || log_hashes.contains(&tx.hash))
+        .map(|tx| (tx.block_number.unwrap_or_default(), tx.transaction_index.unwrap_or_default(), tx.hash))
+        .collect();
+    expected.sort_unstable();
+    let expected: Vec<_> = expected.into_iter().map(|(_, _, hash)| hash).collect();
+
+    // When
+    let mut hashes = Vec::new();
+    let mut cursor = None;
+    loop {
+        let request = AddressHistoryRequest { address, cursor, page_size: Some(1), ..Default::default() };
+        let page = eth_provider.address_history(request).await.unwrap();
+        assert!(page.transactions.len() <= 1);
+        hashes.extend(page.transactions.iter().map(|tx| tx.hash));
+
+        cursor = page.next_cursor;
+        if cursor.is_none() {
+            break;
+        }
+    }
+
+    // Then
+    assert_eq!(hashes, expected);
+}
+
 #[rstest]
 #[awt]
 #[tokio::test(flavor = "multi_thread")]
@@ -704,6 +1099,30 @@ async fn test_to_starknet_block_id(#[future] katana: Katana, _setup: ()) {
     assert!(unknown_starknet_block_number.is_err());
 }
 
+#[rstest]
+#[awt]
+#[tokio::test(flavor = "multi_thread")]
+async fn test_safe_and_finalized_block_tags(#[future] katana: Katana, _setup: ()) {
+    // Given
+    let eth_provider = katana.eth_provider();
+    let latest = eth_provider.block_number().await.unwrap().to::<u64>();
+
+    // When
+    let safe_block = eth_provider.block_by_number(BlockNumberOrTag::Safe, false).await.unwrap().unwrap();
+    let safe_block_id = eth_provider.to_starknet_block_id(Some(BlockNumberOrTag::Safe.into())).await.unwrap();
+    let finalized_block_id = eth_provider.to_starknet_block_id(Some(BlockNumberOrTag::Finalized.into())).await.unwrap();
+
+    // Then
+    // The safe block is the last block accepted on L2
+    assert_eq!(safe_block.header.number, latest);
+    assert_eq!(safe_block_id, starknet::core::types::BlockId::Tag(BlockTag::Latest));
+    // The finalized block is the last block accepted on L1, which can't be ahead of the safe block
+    match finalized_block_id {
+        starknet::core::types::BlockId::Number(number) => assert!(number <= latest),
+        other => panic!("expected a block number, got {other:?}"),
+    }
+}
+
 #[rstest]
 #[awt]
 #[tokio::test(flavor = "multi_thread")]
@@ -738,10 +1157,12 @@ async fn test_send_raw_transaction(#[future] katana_empty: Katana, _setup: ()) {
     assert_eq!(mempool_size.total, 0);
 
     // Send the transaction
-    let _ = eth_client
-        .send_raw_transaction(transaction_signed.encoded_2718().into())
-        .await
-        .expect("failed to send transaction");
+    let raw: Bytes = transaction_signed.encoded_2718().into();
+    let _ = eth_client.send_raw_transaction(raw.clone()).await.expect("failed to send transaction");
+
+    // The submitted bytes are stored as is
+    let stored_raw = eth_provider.submitted_raw_transaction(transaction_signed.hash).await.unwrap();
+    assert_eq!(stored_raw, Some(raw));
 
     // Prepare the relayer
     let relayer_balance = eth_client
@@ -798,6 +1219,59 @@ async fn test_send_raw_transaction(#[future] katana_empty: Katana, _setup: ()) {
     assert_eq!(tx_in_mempool.unwrap().hash(), *transaction_signed.hash());
 }
 
+#[rstest]
+#[awt]
+#[tokio::test(flavor = "multi_thread")]
+async fn test_relay_transaction_dry_run(#[future] katana_empty: Katana, _setup: ()) {
+    // Given
+    let katana = katana_empty;
+    let eth_client = katana.eth_client();
+    let chain_id = katana.eth_provider().chain_id().await.unwrap_or_default().unwrap_or_default().to();
+
+    let transaction = Transaction::Eip1559(TxEip1559 {
+        chain_id,
+        nonce: 0,
+        gas_limit: 21000,
+        to: TxKind::Call(Address::random()),
+        value: U256::from(1000),
+        max_fee_per_gas: 875_000_000,
+        ..Default::default()
+    });
+    let signature = sign_message(katana.eoa().private_key(), transaction.signature_hash()).unwrap();
+    let transaction_signed = TransactionSigned::from_transaction_and_signature(transaction, signature);
+
+    let relayer_balance = eth_client
+        .starknet_provider()
+        .balance_at(katana.eoa.relayer.address(), BlockId::Tag(BlockTag::Latest))
+        .await
+        .expect("Failed to get relayer balance");
+    let relayer_balance = into_via_try_wrapper!(relayer_balance).expect("Failed to convert balance");
+
+    // When
+    let starknet_hash = Relayer::new(
+        katana.eoa.relayer.address(),
+        relayer_balance,
+        &(*(*eth_client.starknet_provider())),
+        Some(Arc::new(eth_client.eth_provider().database().clone())),
+    )
+    .with_dry_run(true)
+    .relay_transaction(&transaction_signed)
+    .await
+    .expect("Failed to relay transaction");
+
+    // Then
+    // The transaction was not submitted to Starknet
+    assert!(eth_client.starknet_provider().get_transaction_by_hash(starknet_hash).await.is_err());
+
+    // No hash mapping was stored
+    let filter = EthDatabaseFilterBuilder::<filter::EthStarknetTransactionHash>::default()
+        .with_tx_hash(&transaction_signed.hash)
+        .build();
+    let hash_mapping: Option<StoredEthStarknetTransactionHash> =
+        eth_client.eth_provider().database().get_one(filter, None).await.unwrap();
+    assert!(hash_mapping.is_none());
+}
+
 #[rstest]
 #[awt]
 #[tokio::test(flavor = "multi_thread")]
@@ -933,6 +1407,44 @@ async fn test_send_raw_transaction_exceed_max_priority_fee_per_gas(#[future] kat
     assert_eq!(mempool_size_after_send.total, 0);
 }
 
+#[rstest]
+#[awt]
+#[tokio::test(flavor = "multi_thread")]
+async fn test_send_raw_transaction_records_dropped_transaction(#[future] katana_empty: Katana, _setup: ()) {
+    // Given
+    let katana = katana_empty;
+    let eth_client = katana.eth_client();
+    let chain_id = katana.eth_provider().chain_id().await.unwrap_or_default().unwrap_or_default().to();
+
+    // A transaction exceeding the block gas limit
+    let transaction = Transaction::Eip1559(TxEip1559 {
+        chain_id,
+        nonce: 0,
+        gas_limit: u64::MAX,
+        to: TxKind::Call(Address::random()),
+        value: U256::from(1000),
+        max_fee_per_gas: 875_000_000,
+        ..Default::default()
+    });
+    let signature = sign_message(katana.eoa().private_key(), transaction.signature_hash()).unwrap();
+    let transaction_signed = TransactionSigned::from_transaction_and_signature(transaction, signature);
+
+    // When
+    let res = eth_client.send_raw_transaction(transaction_signed.encoded_2718().into()).await;
+
+    // Then
+    assert!(res.is_err());
+    let dropped = katana
+        .eth_provider()
+        .dropped_transaction(transaction_signed.hash)
+        .await
+        .unwrap()
+        .expect("dropped transaction not recorded");
+    assert_eq!(dropped.hash, transaction_signed.hash);
+    assert_eq!(dropped.reason, DropReason::Invalid);
+    assert!(!dropped.message.is_empty());
+}
+
 #[rstest]
 #[awt]
 #[tokio::test(flavor = "multi_thread")]
@@ -1121,7 +1633,8 @@ async fn test_send_raw_transaction_wrong_chain_id(#[future] katana: Katana, _set
     let result = eth_client.send_raw_transaction(transaction_signed.encoded_2718().into()).await;
 
     // Then
-    assert!(result.is_err()); // Ensure the transaction is rejected
+    let err = result.expect_err("transaction with wrong chain id should be rejected");
+    assert!(err.to_string().contains(&format!("invalid chain id {wrong_chain_id}")));
 
     let mempool_size_after_send = eth_client.mempool().pool_size();
     // Verify that the number of pending transactions in the mempool remains unchanged (0 tx)
@@ -1328,6 +1841,42 @@ async fn test_call_with_state_override_bytecode(#[future] plain_opcodes: (Katana
         .expect("Failed to set number in Counter contract");
 }
 
+#[rstest]
+#[awt]
+#[tokio::test(flavor = "multi_thread")]
+async fn test_call_many_shares_state(#[future] katana: Katana, _setup: ()) {
+    // Given
+    let eth_provider = katana.eth_provider();
+    let sender = address!("95222290DD7278Aa3Ddd389Cc1E1d165CC4BAfe5");
+    let request = |nonce: u64| TransactionRequest {
+        from: Some(sender),
+        to: Some(TxKind::Call(Address::ZERO)),
+        gas: Some(21000),
+        value: Some(U256::from(1_000)),
+        nonce: Some(nonce),
+        ..Default::default()
+    };
+
+    // Override the balance of the sender
+    let mut state_override = StateOverride::default();
+    state_override.insert(sender, AccountOverride { balance: Some(U256::from(1_000_000)), ..Default::default() });
+
+    // When
+    // The calls of the second bundle are executed on top of the calls of the first bundle,
+    // so the nonces must follow each other.
+    let bundles = vec![
+        Bundle { transactions: vec![request(0), request(1)], block_override: None },
+        Bundle { transactions: vec![request(2)], block_override: None },
+    ];
+    let responses = eth_provider.call_many(bundles, None, Some(state_override)).await.expect("Failed to call many");
+
+    // Then
+    assert_eq!(responses.len(), 2);
+    assert_eq!(responses[0].len(), 2);
+    assert_eq!(responses[1].len(), 1);
+    assert!(responses.iter().flatten().all(|response| response.error.is_none() && response.value.is_some()));
+}
+
 #[rstest]
 #[awt]
 #[tokio::test(flavor = "multi_thread")]
@@ -1347,6 +1896,13 @@ async fn test_transaction_by_hash(#[future] katana_empty: Katana, _setup: ()) {
         .await
         .expect("Failed to insert transaction into the mempool");
 
+    // The transaction waiting to be relayed is served with the pending status
+    let pending = katana_empty.eth_client.transaction_by_hash(tx_hash).await.unwrap().expect("Missing transaction");
+    assert_eq!(pending.other[SUBMISSION_STATUS_FIELD], "pending");
+    let receipt = katana_empty.eth_client.transaction_receipt(tx_hash).await.unwrap().expect("Missing receipt");
+    assert_eq!(receipt.other[SUBMISSION_STATUS_FIELD], "pending");
+    assert_eq!(receipt.block_number, None);
+
     // Add a hash mapping to the database
     let starknet_hash = Felt::from_hex("0x0208a0a10250e382e1e4bbe2880906c2791bf6275695e02fbbc6aeff9cd8b31a").unwrap();
     let updated_transaction_hashes = EthStarknetHashes { eth_hash: tx_hash, starknet_hash };
@@ -1362,6 +1918,9 @@ async fn test_transaction_by_hash(#[future] katana_empty: Katana, _setup: ()) {
     // Check if the first transaction is returned correctly by the `transaction_by_hash` method
     let tx = katana_empty.eth_client.transaction_by_hash(tx_hash).await.unwrap();
     assert!(tx.is_some());
+    assert_eq!(tx.as_ref().unwrap().other[SUBMISSION_STATUS_FIELD], "relayed");
+    let receipt = katana_empty.eth_client.transaction_receipt(tx_hash).await.unwrap().expect("Missing receipt");
+    assert_eq!(receipt.other[SUBMISSION_STATUS_FIELD], "relayed");
 
     assert_eq!(
         *tx.unwrap()
@@ -1424,10 +1983,11 @@ async fn test_transaction_by_hash(#[future] katana_empty: Katana, _setup: ()) {
     // Insert the transaction into the database to simulate a transaction that has been indexed
     katana_empty.eth_provider().database().upsert_transaction(transaction.clone().tx).await.unwrap();
 
-    // Check if the indexed transaction is returned correctly by the `transaction_by_hash` method
+    // Check if the indexed transaction is returned correctly by the `transaction_by_hash` method,
+    // its block not being indexed
     assert_eq!(
         katana_empty.eth_client.transaction_by_hash(transaction.tx.hash).await.unwrap().unwrap(),
-        transaction.tx
+        transaction.with_base_fee(None)
     );
 }
 
@@ -1447,3 +2007,30 @@ async fn test_with_other_fields(#[future] katana: Katana, _setup: ()) {
     );
     assert_eq!(receipt_from_db.unwrap(), run_out_of_resources_receipt);
 }
+
+#[rstest]
+#[awt]
+#[tokio::test(flavor = "multi_thread")]
+async fn test_batched_starknet_reads(#[future] katana: Katana, _setup: ()) {
+    // Given
+    let starknet_provider = katana.starknet_provider();
+    let batching_provider = BatchingProvider::new(
+        starknet_provider.clone(),
+        BatchConfig { window: Duration::from_millis(50), max_size: 50 },
+    );
+    let account = katana.eoa().starknet_address().expect("Failed to get the starknet address");
+    let block_id = BlockId::Tag(BlockTag::Latest);
+
+    // When
+    // The concurrent reads are sent in the same batch
+    let (nonce, class_hash, block) = tokio::join!(
+        batching_provider.get_nonce(block_id, account),
+        batching_provider.get_class_hash_at(block_id, account),
+        batching_provider.get_block_with_tx_hashes(BlockId::Number(0)),
+    );
+
+    // Then
+    assert_eq!(nonce.unwrap(), starknet_provider.get_nonce(block_id, account).await.unwrap());
+    assert_eq!(class_hash.unwrap(), starknet_provider.get_class_hash_at(block_id, account).await.unwrap());
+    assert_eq!(block.unwrap(), starknet_provider.get_block_with_tx_hashes(BlockId::Number(0)).await.unwrap());
+}
@@ -1,19 +1,28 @@
 #![allow(clippy::used_underscore_binding)]
 #![cfg(feature = "testing")]
 
-use alloy_primitives::B256;
+use alloy_primitives::{Address, B256};
 use kakarot_rpc::{
-    providers::eth_provider::constant::Constant,
+    eth_rpc::{api::kakarot_api::KakarotAdminApiServer, servers::kakarot_rpc::KakarotAdminRpc},
+    pool::relayers::RelayerRegistry,
+    providers::eth_provider::{
+        constant::{Capabilities, ChainMetadata, Constant, PriorityFeePolicy, CHAIN_METADATA, KAKAROT_CAPABILITIES},
+        error::EthRpcErrorCode,
+        state::AccountType,
+        ChainProvider, StateProvider,
+    },
     test_utils::{
+        eoa::Eoa,
         fixtures::{katana, setup},
         katana::Katana,
         rpc::{start_kakarot_rpc_server, RawRpcParamsBuilder},
+        tx_waiter::watch_tx,
     },
 };
 use rstest::*;
-use serde_json::Value;
+use serde_json::{json, Value};
 use starknet::core::types::Felt;
-use std::str::FromStr;
+use std::{str::FromStr, sync::Arc, time::Duration};
 
 #[cfg(feature = "forwarding")]
 #[rstest]
@@ -84,6 +93,7 @@ async fn test_kakarot_get_config(#[future] katana: Katana, _setup: ()) {
         max_felts_in_calldata,
         white_listed_eip_155_transaction_hashes: vec![B256::from_str(white_listed_eip_155_transaction_hashes).unwrap()],
         kakarot_address: Felt::from_hex("0x03d937c035c878245caf64531a5756109c53068da139362728feb561405371cb").unwrap(),
+        priority_fee_policy: PriorityFeePolicy::Ignored,
     };
 
     // Start the Kakarot RPC server
@@ -110,3 +120,394 @@ async fn test_kakarot_get_config(#[future] katana: Katana, _setup: ()) {
 
     drop(server_handle);
 }
+
+#[rstest]
+#[awt]
+#[tokio::test(flavor = "multi_thread")]
+async fn test_kakarot_get_capabilities(#[future] katana: Katana, _setup: ()) {
+    // Start the Kakarot RPC server
+    let (server_addr, server_handle) =
+        start_kakarot_rpc_server(&katana).await.expect("Error setting up Kakarot RPC server");
+
+    // Send the RPC request to get the capabilities
+    let reqwest_client = reqwest::Client::new();
+    let res = reqwest_client
+        .post(format!("http://localhost:{}", server_addr.port()))
+        .header("Content-Type", "application/json")
+        .body(RawRpcParamsBuilder::new("kakarot_getCapabilities").build())
+        .send()
+        .await
+        .expect("kakarot_getCapabilities error");
+
+    // Deserialize the response
+    let capabilities: Capabilities = serde_json::from_str(&res.text().await.expect("Failed to get response body"))
+        .and_then(|raw: Value| serde_json::from_value(raw["result"].clone()))
+        .expect("Failed to deserialize response body or convert result to Capabilities");
+
+    // Assert that the returned capabilities match the expected value
+    assert_eq!(capabilities, *KAKAROT_CAPABILITIES);
+    assert_eq!(capabilities.hardfork, "cancun");
+    assert!(capabilities.precompiles.iter().any(|precompile| precompile.name == "cairoCall"));
+    assert!(!capabilities.supported_transaction_types.contains(&3));
+
+    drop(server_handle);
+}
+
+#[rstest]
+#[awt]
+#[tokio::test(flavor = "multi_thread")]
+async fn test_kakarot_get_chain_metadata(#[future] katana: Katana, _setup: ()) {
+    // Start the Kakarot RPC server
+    let (server_addr, server_handle) =
+        start_kakarot_rpc_server(&katana).await.expect("Error setting up Kakarot RPC server");
+
+    // Send the RPC request to get the chain metadata
+    let reqwest_client = reqwest::Client::new();
+    let res = reqwest_client
+        .post(format!("http://localhost:{}", server_addr.port()))
+        .header("Content-Type", "application/json")
+        .body(RawRpcParamsBuilder::new("kakarot_getChainMetadata").build())
+        .send()
+        .await
+        .expect("kakarot_getChainMetadata error");
+    let raw: Value = serde_json::from_str(&res.text().await.expect("Failed to get response body"))
+        .expect("Failed to deserialize response body");
+
+    // Deserialize the response
+    let metadata: ChainMetadata =
+        serde_json::from_value(raw["result"].clone()).expect("Failed to convert result to ChainMetadata");
+
+    // Assert that the returned metadata match the chain of the provider
+    assert_eq!(metadata, *CHAIN_METADATA);
+    assert_eq!(Some(metadata.chain_id), katana.eth_provider().chain_id().await.unwrap());
+    assert_eq!(metadata.native_currency.decimals, 18);
+    assert!(raw["result"]["chainId"].as_str().is_some_and(|chain_id| chain_id.starts_with("0x")));
+
+    drop(server_handle);
+}
+
+#[rstest]
+#[awt]
+#[tokio::test(flavor = "multi_thread")]
+async fn test_kakarot_get_account_type(#[future] katana: Katana, _setup: ()) {
+    // Start the Kakarot RPC server
+    let (server_addr, server_handle) =
+        start_kakarot_rpc_server(&katana).await.expect("Error setting up Kakarot RPC server");
+
+    let reqwest_client = reqwest::Client::new();
+    let account_type = |address: Address| {
+        let reqwest_client = reqwest_client.clone();
+        async move {
+            let res = reqwest_client
+                .post(format!("http://localhost:{}", server_addr.port()))
+                .header("Content-Type", "application/json")
+                .body(RawRpcParamsBuilder::new("kakarot_getAccountType").add_param(address).build())
+                .send()
+                .await
+                .expect("kakarot_getAccountType error");
+            serde_json::from_str(&res.text().await.expect("Failed to get response body"))
+                .and_then(|raw: Value| serde_json::from_value::<AccountType>(raw["result"].clone()))
+                .expect("Failed to deserialize response body or convert result to AccountType")
+        }
+    };
+
+    // The EOA of Katana is deployed, a random address isn't
+    let eoa = account_type(katana.eoa().evm_address().expect("Failed to get EOA address")).await;
+    let uninitialized = account_type(Address::random()).await;
+
+    assert_eq!(eoa, AccountType::Eoa);
+    assert_eq!(uninitialized, AccountType::Uninitialized);
+
+    drop(server_handle);
+}
+
+#[rstest]
+#[awt]
+#[tokio::test(flavor = "multi_thread")]
+async fn test_kakarot_deploy_account(#[future] katana: Katana, _setup: ()) {
+    // Given
+    let eth_client = Arc::new(katana.eth_client());
+    let relayers = Arc::new(RelayerRegistry::new([katana.eoa().relayer.address()]));
+    let admin = KakarotAdminRpc::new(eth_client, relayers);
+    let address = Address::left_padding_from(&[0xde, 0x01]);
+
+    // When
+    let starknet_hash = admin.deploy_account(address).await.expect("failed to deploy the account");
+    watch_tx(katana.starknet_provider(), starknet_hash, Duration::from_millis(300), 60)
+        .await
+        .expect("account deployment failed");
+
+    // Then
+    let account_type = katana.eth_provider().account_type(address, None).await.expect("failed to get account type");
+    assert_eq!(account_type, AccountType::Eoa);
+
+    // When
+    let err = admin.deploy_account(address).await.unwrap_err();
+
+    // Then
+    // The account can't be deployed twice
+    assert_eq!(err.code(), EthRpcErrorCode::InvalidInput as i32);
+}
+
+#[rstest]
+#[awt]
+#[tokio::test(flavor = "multi_thread")]
+async fn test_error_response_contains_request_id(#[future] katana: Katana, _setup: ()) {
+    // Start the Kakarot RPC server
+    let (server_addr, server_handle) =
+        start_kakarot_rpc_server(&katana).await.expect("Error setting up Kakarot RPC server");
+
+    // Send a request to an unknown method
+    let reqwest_client = reqwest::Client::new();
+    let res = reqwest_client
+        .post(format!("http://localhost:{}", server_addr.port()))
+        .header("Content-Type", "application/json")
+        .body(RawRpcParamsBuilder::new("kakarot_unknownMethod").build())
+        .send()
+        .await
+        .expect("kakarot_unknownMethod error");
+    let response: Value =
+        serde_json::from_str(&res.text().await.expect("Failed to get response body")).expect("Failed to deserialize");
+
+    // Assert that the error contains the request id
+    let request_id = response["error"]["data"]["requestId"].as_str().expect("Missing request id");
+    assert_eq!(request_id.len(), 16);
+
+    drop(server_handle);
+}
+
+#[rstest]
+#[awt]
+#[tokio::test(flavor = "multi_thread")]
+async fn test_mining_endpoints(#[future] katana: Katana, _setup: ()) {
+    // Start the Kakarot RPC server
+    let (server_addr, server_handle) =
+        start_kakarot_rpc_server(&katana).await.expect("Error setting up Kakarot RPC server");
+
+    let reqwest_client = reqwest::Client::new();
+    let call = |body: String| {
+        let reqwest_client = reqwest_client.clone();
+        async move {
+            let res = reqwest_client
+                .post(format!("http://localhost:{}", server_addr.port()))
+                .header("Content-Type", "application/json")
+                .body(body)
+                .send()
+                .await
+                .expect("Failed to call the mining endpoint");
+            serde_json::from_str::<Value>(&res.text().await.expect("Failed to get response body"))
+                .expect("Failed to deserialize response body")
+        }
+    };
+
+    // The node answers like a node which doesn't mine
+    let mining = call(RawRpcParamsBuilder::new("eth_mining").build()).await;
+    let hashrate = call(RawRpcParamsBuilder::new("eth_hashrate").build()).await;
+    let work = call(RawRpcParamsBuilder::new("eth_getWork").build()).await;
+    let submit_hashrate =
+        call(RawRpcParamsBuilder::new("eth_submitHashrate").add_param("0x1").add_param(B256::ZERO).build()).await;
+    let submit_work = call(
+        RawRpcParamsBuilder::new("eth_submitWork")
+            .add_param("0x0000000000000001")
+            .add_param(B256::ZERO)
+            .add_param(B256::ZERO)
+            .build(),
+    )
+    .await;
+
+    assert_eq!(mining["result"], Value::Bool(false));
+    assert_eq!(hashrate["result"], "0x0");
+    assert_eq!(work["error"]["code"], -32000);
+    assert_eq!(submit_hashrate["result"], Value::Bool(false));
+    assert_eq!(submit_work["result"], Value::Bool(false));
+
+    // The coinbase is the miner of the latest block
+    let coinbase = call(RawRpcParamsBuilder::new("eth_coinbase").build()).await;
+    let latest =
+        call(RawRpcParamsBuilder::new("eth_getBlockByNumber").add_param("latest").add_param(false).build()).await;
+    assert_eq!(coinbase["result"], latest["result"]["miner"]);
+
+    drop(server_handle);
+}
+
+#[rstest]
+#[awt]
+#[tokio::test(flavor = "multi_thread")]
+async fn test_get_logs_nested_topics(#[future] katana: Katana, _setup: ()) {
+    // Given
+    let logs = katana.logs_with_min_topics(3);
+    let (topic_one, topic_two) = (logs[0].topics()[0], logs[1].topics()[0]);
+    let (server_addr, server_handle) =
+        start_kakarot_rpc_server(&katana).await.expect("Error setting up Kakarot RPC server");
+
+    let reqwest_client = reqwest::Client::new();
+    let get_logs = |topics: Value| {
+        let reqwest_client = reqwest_client.clone();
+        async move {
+            let res = reqwest_client
+                .post(format!("http://localhost:{}", server_addr.port()))
+                .header("Content-Type", "application/json")
+                .body(RawRpcParamsBuilder::new("eth_getLogs").add_param(json!({ "topics": topics })).build())
+                .send()
+                .await
+                .expect("Failed to call eth_getLogs");
+            serde_json::from_str::<Value>(&res.text().await.expect("Failed to get response body"))
+                .expect("Failed to deserialize response body")
+        }
+    };
+
+    // When
+    let ored = get_logs(json!([[topic_one, topic_two]])).await;
+    let wildcard = get_logs(json!([[topic_one, null]])).await;
+    let all = get_logs(json!([])).await;
+    let too_many = get_logs(json!([null, null, null, null, topic_one])).await;
+
+    // Then
+    let ored = ored["result"].as_array().expect("Failed to get the logs");
+    assert!(ored.len() >= 2);
+    assert!(ored.iter().all(|log| log["topics"][0] == json!(topic_one) || log["topics"][0] == json!(topic_two)));
+    assert_eq!(wildcard["result"], all["result"]);
+    assert!(too_many.get("error").is_some());
+
+    drop(server_handle);
+}
+
+#[cfg(feature = "faucet")]
+#[rstest]
+#[awt]
+#[tokio::test(flavor = "multi_thread")]
+async fn test_request_funds(#[future] katana: Katana, _setup: ()) {
+    use alloy_primitives::U256;
+    use jsonrpsee::Extensions;
+    use kakarot_rpc::{
+        eth_rpc::{
+            api::faucet_api::KakarotFaucetApiServer,
+            middleware::client_ip::ClientIp,
+            servers::faucet_rpc::{KakarotFaucetRpc, FAUCET_AMOUNT_WEI},
+        },
+        pool::relayers::{RelayerRegistry, TrafficClass},
+        providers::eth_provider::StateProvider as _,
+        test_utils::tx_waiter::watch_tx,
+    };
+    use std::{
+        net::{IpAddr, Ipv4Addr},
+        sync::Arc,
+        time::Duration,
+    };
+
+    // Given
+    let relayers = Arc::new(RelayerRegistry::new([]));
+    relayers.register_for(katana.eoa().relayer.address(), TrafficClass::Faucet);
+    let faucet = KakarotFaucetRpc::new(Arc::new(katana.eth_client()), relayers);
+    let address = Address::left_padding_from(&[0xfa, 0x01]);
+
+    // When
+    let mut extensions = Extensions::new();
+    extensions.insert(ClientIp(IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1))));
+    let starknet_hash = faucet.request_funds(&extensions, address).await.expect("failed to request funds");
+    watch_tx(katana.starknet_provider(), starknet_hash, Duration::from_millis(300), 60)
+        .await
+        .expect("faucet transfer failed");
+
+    // Then
+    let balance = katana.eth_provider().balance(address, None).await.expect("failed to get balance");
+    assert_eq!(balance, U256::from(*FAUCET_AMOUNT_WEI));
+}
+
+#[cfg(feature = "faucet")]
+#[rstest]
+#[awt]
+#[tokio::test(flavor = "multi_thread")]
+async fn test_request_funds_rate_limited(#[future] katana: Katana, _setup: ()) {
+    use jsonrpsee::Extensions;
+    use kakarot_rpc::{
+        eth_rpc::{
+            api::faucet_api::KakarotFaucetApiServer, middleware::client_ip::ClientIp,
+            servers::faucet_rpc::KakarotFaucetRpc,
+        },
+        pool::relayers::{RelayerRegistry, TrafficClass},
+        providers::eth_provider::error::EthRpcErrorCode,
+    };
+    use std::{
+        net::{IpAddr, Ipv4Addr},
+        sync::Arc,
+    };
+
+    // Given
+    let relayers = Arc::new(RelayerRegistry::new([]));
+    relayers.register_for(katana.eoa().relayer.address(), TrafficClass::Faucet);
+    let faucet = KakarotFaucetRpc::new(Arc::new(katana.eth_client()), relayers);
+    let address = Address::left_padding_from(&[0xfa, 0x03]);
+    let mut extensions = Extensions::new();
+    extensions.insert(ClientIp(IpAddr::V4(Ipv4Addr::new(10, 0, 0, 3))));
+    faucet.request_funds(&extensions, address).await.expect("failed to request funds");
+
+    // When
+    let err = faucet.request_funds(&extensions, address).await.unwrap_err();
+
+    // Then
+    assert_eq!(err.code(), EthRpcErrorCode::RequestLimitExceeded as i32);
+}
+
+#[cfg(feature = "faucet")]
+#[rstest]
+#[awt]
+#[tokio::test(flavor = "multi_thread")]
+async fn test_request_funds_without_client_ip(#[future] katana: Katana, _setup: ()) {
+    use jsonrpsee::Extensions;
+    use kakarot_rpc::{
+        eth_rpc::{api::faucet_api::KakarotFaucetApiServer, servers::faucet_rpc::KakarotFaucetRpc},
+        pool::relayers::{RelayerRegistry, TrafficClass},
+        providers::eth_provider::error::EthRpcErrorCode,
+    };
+    use std::sync::Arc;
+
+    // Given
+    let relayers = Arc::new(RelayerRegistry::new([]));
+    relayers.register_for(katana.eoa().relayer.address(), TrafficClass::Faucet);
+    let faucet = KakarotFaucetRpc::new(Arc::new(katana.eth_client()), relayers);
+
+    // When
+    // The request has neither forwarding headers nor a peer address
+    let err = faucet.request_funds(&Extensions::new(), Address::left_padding_from(&[0xfa, 0x04])).await.unwrap_err();
+
+    // Then
+    assert_eq!(err.code(), EthRpcErrorCode::InvalidRequest as i32);
+    assert_eq!(err.message(), "the client IP of the request can't be determined");
+}
+
+#[cfg(feature = "faucet")]
+#[rstest]
+#[awt]
+#[tokio::test(flavor = "multi_thread")]
+async fn test_request_funds_exhausted_balance(#[future] katana: Katana, _setup: ()) {
+    use jsonrpsee::Extensions;
+    use kakarot_rpc::{
+        eth_rpc::{
+            api::faucet_api::KakarotFaucetApiServer, middleware::client_ip::ClientIp,
+            servers::faucet_rpc::KakarotFaucetRpc,
+        },
+        pool::relayers::{RelayerRegistry, TrafficClass},
+        providers::eth_provider::error::EthRpcErrorCode,
+    };
+    use std::{
+        net::{IpAddr, Ipv4Addr},
+        sync::Arc,
+    };
+
+    // Given
+    // An unfunded faucet relayer
+    let relayers = Arc::new(RelayerRegistry::new([katana.eoa().relayer.address()]));
+    relayers.register_for(Felt::from(0xfa_0bad_u64), TrafficClass::Faucet);
+    let faucet = KakarotFaucetRpc::new(Arc::new(katana.eth_client()), relayers);
+
+    // When
+    let mut extensions = Extensions::new();
+    extensions.insert(ClientIp(IpAddr::V4(Ipv4Addr::new(10, 0, 0, 2))));
+    let err = faucet.request_funds(&extensions, Address::left_padding_from(&[0xfa, 0x02])).await.unwrap_err();
+
+    // Then
+    // The relayer of the users doesn't pay out
+    assert_eq!(err.code(), EthRpcErrorCode::ResourceUnavailable as i32);
+    assert_eq!(err.message(), "the faucet is out of funds, retry later");
+}
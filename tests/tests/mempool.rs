@@ -3,18 +3,32 @@
 use alloy_consensus::{TxEip1559, EMPTY_ROOT_HASH};
 use alloy_eips::eip2718::Encodable2718;
 use alloy_primitives::{Address, TxKind, B64, U256};
-use alloy_rpc_types::Header;
+use alloy_rpc_types::{Header, Index};
 use kakarot_rpc::{
+    client::{EthClient, KakarotTransactions, PendingBlockProvider},
     constants::KKRT_BLOCK_GAS_LIMIT,
-    pool::mempool::maintain_transaction_pool,
+    coordination::Leadership,
+    pool::{
+        mempool::{maintain_transaction_pool, with_memory_budget, PoolMaintainer},
+        probe::Probe,
+        prune::PruneSchedule,
+        reconcile::reconcile_relayed_transactions,
+        snapshot::{import_pool_snapshot, PoolSnapshot, SnapshotOrigin, SubPool},
+    },
     providers::eth_provider::{
         constant::U64_HEX_STRING_LEN,
         database::{
+            ethereum::EthereumTransactionStore,
             filter::{self, format_hex, EthDatabaseFilterBuilder},
-            types::header::StoredHeader,
+            relayer::RelayerNonceStore,
+            types::{
+                header::StoredHeader,
+                relayer::{NonceLease, NonceLeaseStatus, StoredNonceLease},
+                transaction::RawTransaction,
+            },
         },
         error::SignatureError,
-        ChainProvider,
+        ChainProvider, GasProvider,
     },
     test_utils::{
         eoa::Eoa,
@@ -27,9 +41,10 @@ use mongodb::{
     options::{UpdateModifications, UpdateOptions},
 };
 use reth_primitives::{sign_message, Transaction, TransactionSigned, TransactionSignedEcRecovered};
-use reth_transaction_pool::{EthPooledTransaction, PoolTransaction, TransactionOrigin, TransactionPool};
+use reth_transaction_pool::{EthPooledTransaction, PoolConfig, PoolTransaction, TransactionOrigin, TransactionPool};
 use revm_primitives::B256;
 use rstest::*;
+use starknet::core::types::Felt;
 use std::{sync::Arc, time::Duration};
 
 #[rstest]
@@ -203,6 +218,38 @@ async fn test_mempool_add_transactions(#[future] katana_empty: Katana, _setup: (
     assert_eq!(mempool_size.total, transaction_number);
 }
 
+#[rstest]
+#[awt]
+#[tokio::test(flavor = "multi_thread")]
+async fn test_mempool_memory_budget_evicts(#[future] katana_empty: Katana, _setup: ()) {
+    let katana: Katana = katana_empty;
+
+    // Given
+    let transaction_number = 3;
+    let transactions =
+        create_sample_transactions(&katana, transaction_number).await.expect("Failed to create sample transactions");
+    let pooled_transactions = transactions.into_iter().map(|(pooled, _)| pooled).collect::<Vec<_>>();
+
+    // The size of the transactions in the pool, with the default budget
+    let eth_client = katana.eth_client();
+    let _ = eth_client.mempool().add_transactions(TransactionOrigin::Local, pooled_transactions.clone()).await;
+    let pool_size = eth_client.mempool().pool_size();
+    assert_eq!(pool_size.pending, transaction_number);
+
+    // A client whose budget doesn't fit all the transactions
+    let budget = pool_size.pending_size - 1;
+    let config = with_memory_budget(PoolConfig { gas_limit: KKRT_BLOCK_GAS_LIMIT, ..Default::default() }, budget);
+    let eth_client = EthClient::new(katana.starknet_provider(), config, katana.eth_provider().database().clone());
+
+    // When
+    let _ = eth_client.mempool().add_transactions(TransactionOrigin::Local, pooled_transactions).await;
+
+    // Then
+    let pool_size = eth_client.mempool().pool_size();
+    assert!(pool_size.pending < transaction_number);
+    assert!(pool_size.pending_size <= budget);
+}
+
 #[rstest]
 #[awt]
 #[tokio::test(flavor = "multi_thread")]
@@ -319,6 +366,218 @@ async fn test_mempool_get_private_transactions(#[future] katana_empty: Katana, _
     assert_eq!(*private_transaction[0].hash(), transaction_signed.hash());
 }
 
+#[rstest]
+#[awt]
+#[tokio::test(flavor = "multi_thread")]
+async fn test_mempool_pending_block_transactions(#[future] katana_empty: Katana, _setup: ()) {
+    let katana: Katana = katana_empty;
+
+    let eth_client = katana.eth_client();
+
+    // Given
+    let transactions = create_sample_transactions(&katana, 2).await.expect("Failed to create sample transactions");
+    let pooled_transactions = transactions.iter().map(|(pooled, _)| pooled.clone()).collect::<Vec<_>>();
+    let _ = eth_client.mempool().add_transactions(TransactionOrigin::Local, pooled_transactions).await;
+
+    // When
+    let first = eth_client.pending_transaction_by_index(Index::from(0)).await.unwrap();
+    let second = eth_client.pending_transaction_by_index(Index::from(1)).await.unwrap();
+    let missing = eth_client.pending_transaction_by_index(Index::from(2)).await.unwrap();
+    let count = eth_client.pending_block_transaction_count().await.unwrap();
+    let block = eth_client.pending_block(true).await.unwrap().expect("Missing pending block");
+
+    // Then
+    assert_eq!(count, Some(U256::from(2)));
+    assert_eq!(first.as_ref().unwrap().hash, transactions[0].1.hash());
+    assert_eq!(second.as_ref().unwrap().hash, transactions[1].1.hash());
+    assert!(missing.is_none());
+    // The block and the index lookups are built the same way
+    assert_eq!(block.header.number, katana.block_number() + 1);
+    assert_eq!(block.transactions.as_transactions().unwrap(), [first.unwrap(), second.unwrap()]);
+}
+
+#[rstest]
+#[awt]
+#[tokio::test(flavor = "multi_thread")]
+async fn test_mempool_pending_block_transactions_by_hash(#[future] katana_empty: Katana, _setup: ()) {
+    use alloy_eips::BlockNumberOrTag;
+    use kakarot_rpc::eth_rpc::{api::eth_api::EthApiServer, servers::eth_rpc::KakarotEthRpc};
+
+    let katana: Katana = katana_empty;
+
+    let eth_client = Arc::new(katana.eth_client());
+    let eth_rpc = KakarotEthRpc::new(eth_client.clone());
+
+    // Given
+    let transactions = create_sample_transactions(&katana, 2).await.expect("Failed to create sample transactions");
+    let pooled_transactions = transactions.iter().map(|(pooled, _)| pooled.clone()).collect::<Vec<_>>();
+    let _ = eth_client.mempool().add_transactions(TransactionOrigin::Local, pooled_transactions).await;
+    let block =
+        eth_rpc.block_by_number(BlockNumberOrTag::Pending, false).await.unwrap().expect("Missing pending block");
+
+    // When
+    let hash = block.header.hash;
+    let first = eth_rpc.transaction_by_block_hash_and_index(hash, Index::from(0)).await.unwrap();
+    let second = eth_rpc.transaction_by_block_hash_and_index(hash, Index::from(1)).await.unwrap();
+    let missing = eth_rpc.transaction_by_block_hash_and_index(hash, Index::from(2)).await.unwrap();
+    let count = eth_rpc.block_transaction_count_by_hash(hash).await.unwrap();
+
+    // Then
+    // The hash of the pending block resolves to the pending block
+    assert!(hash.is_zero());
+    assert_eq!(first.unwrap().hash, transactions[0].1.hash());
+    assert_eq!(second.unwrap().hash, transactions[1].1.hash());
+    assert!(missing.is_none());
+    assert_eq!(count, Some(U256::from(2)));
+}
+
+#[rstest]
+#[awt]
+#[tokio::test(flavor = "multi_thread")]
+async fn test_send_raw_transaction_duplicate(#[future] katana_empty: Katana, _setup: ()) {
+    let katana: Katana = katana_empty;
+
+    let eth_client = katana.eth_client();
+
+    // Given
+    let transactions = create_sample_transactions(&katana, 1).await.expect("Failed to create sample transactions");
+    let transaction_signed = &transactions[0].1;
+    let first = eth_client
+        .send_raw_transaction(transaction_signed.encoded_2718().into())
+        .await
+        .expect("Failed to send transaction");
+
+    // When
+    let second = eth_client.send_raw_transaction(transaction_signed.encoded_2718().into()).await;
+
+    // Then
+    assert_eq!(second.expect("Duplicate transaction should be accepted"), first);
+    assert_eq!(first, transaction_signed.hash());
+    assert_eq!(eth_client.mempool().pool_size().total, 1);
+}
+
+#[rstest]
+#[awt]
+#[tokio::test(flavor = "multi_thread")]
+async fn test_send_raw_transaction_deployer_allowlist(#[future] katana_empty: Katana, _setup: ()) {
+    let katana: Katana = katana_empty;
+
+    let eth_client = katana.eth_client();
+    let signer = katana.eoa().evm_address().expect("Failed to get eoa address");
+    let chain_id = katana.eth_provider().chain_id().await.unwrap_or_default().unwrap_or_default().to();
+
+    // Given
+    let transaction = Transaction::Eip1559(TxEip1559 {
+        chain_id,
+        gas_limit: 100_000,
+        to: TxKind::Create,
+        input: vec![0x60, 0x00, 0x60, 0x00, 0xf3].into(),
+        max_fee_per_gas: 875_000_000,
+        ..Default::default()
+    });
+    let signature = sign_message(katana.eoa().private_key(), transaction.signature_hash()).unwrap();
+    let transaction_signed = TransactionSigned::from_transaction_and_signature(transaction, signature);
+    eth_client.set_deployer_allowlist(Some([Address::random()].into()));
+
+    // When
+    let rejected = eth_client.send_raw_transaction(transaction_signed.encoded_2718().into()).await;
+
+    // Then
+    let err = rejected.expect_err("Contract creation of a sender outside of the allowlist should be rejected");
+    assert!(err.to_string().contains("deployer allowlist"), "{err}");
+    assert_eq!(eth_client.mempool().pool_size().total, 0);
+
+    // When
+    eth_client.set_deployer_allowlist(Some([signer].into()));
+    let accepted = eth_client.send_raw_transaction(transaction_signed.encoded_2718().into()).await;
+
+    // Then
+    assert_eq!(
+        accepted.expect("Contract creation of an allowed deployer should be accepted"),
+        transaction_signed.hash()
+    );
+    assert_eq!(eth_client.deployer_allowlist(), Some([signer].into()));
+}
+
+#[rstest]
+#[awt]
+#[tokio::test(flavor = "multi_thread")]
+async fn test_mempool_export_import_snapshot(#[future] katana_empty: Katana, _setup: ()) {
+    let katana: Katana = katana_empty;
+
+    let eth_client = katana.eth_client();
+
+    // Given
+    let transactions = create_sample_transactions(&katana, 2).await.expect("Failed to create sample transactions");
+    let (pooled_transactions, signed_transactions): (Vec<_>, Vec<_>) = transactions.into_iter().unzip();
+    let hashes = signed_transactions.iter().map(TransactionSigned::hash).collect::<Vec<_>>();
+    let _ = eth_client.mempool().add_transactions(TransactionOrigin::External, pooled_transactions).await;
+
+    // When
+    let snapshot = PoolSnapshot::new(eth_client.mempool().as_ref());
+    let _ = eth_client.mempool().remove_transactions(hashes.clone());
+    let import = import_pool_snapshot(&eth_client, snapshot.clone()).await.expect("Failed to import the snapshot");
+
+    // Then
+    assert_eq!(snapshot.transactions.len(), 2);
+    for (transaction, signed) in snapshot.transactions.iter().zip(&signed_transactions) {
+        assert_eq!(transaction.hash, signed.hash());
+        assert_eq!(transaction.sub_pool, SubPool::Pending);
+        assert_eq!(transaction.origin, SnapshotOrigin::External);
+        assert_eq!(transaction.raw.to_vec(), signed.encoded_2718());
+    }
+    assert_eq!(import.imported, hashes);
+    assert!(import.failed.is_empty());
+    assert_eq!(eth_client.mempool().pool_size().pending, 2);
+}
+
+#[rstest]
+#[awt]
+#[tokio::test(flavor = "multi_thread")]
+async fn test_reconcile_relayed_transactions(#[future] katana: Katana, _setup: ()) {
+    let eth_client = katana.eth_client();
+    let database = eth_client.eth_provider().database();
+    let relayer = katana.sequencer.account().address();
+
+    // Given
+    // The relayer of the Katana fixture already consumed its first nonce, while the last nonces
+    // below are far ahead of its nonce on chain
+    let transactions = create_sample_transactions(&katana, 3).await.expect("Failed to create sample transactions");
+    let hashes = transactions.iter().map(|(_, signed)| signed.hash()).collect::<Vec<_>>();
+    let leases = [
+        NonceLease::new(relayer, 0, hashes[0]),
+        NonceLease::new(relayer, 1_000_000, hashes[1]),
+        NonceLease {
+            status: NonceLeaseStatus::Submitted,
+            starknet_hash: Some(Felt::from(0xdead)),
+            ..NonceLease::new(relayer, 1_000_001, hashes[2])
+        },
+    ];
+    for ((_, signed), lease) in transactions.iter().zip(leases) {
+        database
+            .upsert_raw_transaction(RawTransaction { hash: signed.hash(), bytes: signed.encoded_2718().into() })
+            .await
+            .expect("Failed to store raw transaction");
+        database.insert_one(StoredNonceLease::from(lease)).await.expect("Failed to insert nonce lease");
+    }
+
+    // When
+    let reconciliation = reconcile_relayed_transactions(&eth_client).await.expect("Failed to reconcile");
+    let resent = eth_client
+        .send_raw_transaction(transactions[0].1.encoded_2718().into())
+        .await
+        .expect("Failed to send transaction");
+
+    // Then
+    assert!(reconciliation.recovered.is_empty());
+    assert_eq!(reconciliation.in_flight, vec![hashes[0]]);
+    assert_eq!(reconciliation.released, vec![hashes[1], hashes[2]]);
+    assert_eq!(resent, hashes[0]);
+    assert_eq!(eth_client.mempool().pool_size().total, 0);
+    assert!(database.nonce_lease_of(&hashes[1]).await.unwrap().is_none());
+    assert!(database.nonce_lease_of(&hashes[2]).await.unwrap().is_none());
+}
+
 // Helper function to create a sample transaction
 pub async fn create_sample_transactions(
     katana: &Katana,
@@ -390,12 +649,12 @@ async fn test_maintain_mempool(#[future] katana: Katana, _setup: ()) {
 
     // Start maintaining the transaction pool
     //
-    // This task will periodically prune the mempool based on the given prune_duration.
-    // For testing purposes, we set the prune_duration to 100 milliseconds.
-    let prune_duration = Duration::from_millis(100);
+    // This task will periodically prune the mempool based on the given prune schedule.
+    // For testing purposes, we set the prune duration to 100 milliseconds.
+    let prune_schedule = PruneSchedule::fixed(Duration::from_millis(100));
     let eth_client_clone = Arc::clone(&eth_client);
     let maintain_task = tokio::spawn(async move {
-        maintain_transaction_pool(eth_client_clone, prune_duration);
+        maintain_transaction_pool(eth_client_clone, prune_schedule, Leadership::writer());
     });
 
     // Initialize the block number based on the current blockchain state from katana.
@@ -486,3 +745,35 @@ async fn test_mempool_config(#[future] katana: Katana, _setup: ()) {
     // Check the gas limit for Kakarot blocks
     assert_eq!(eth_client.mempool().config().gas_limit, KKRT_BLOCK_GAS_LIMIT);
 }
+
+#[rstest]
+#[awt]
+#[tokio::test(flavor = "multi_thread")]
+async fn test_mempool_block_gas_limit_refresh(#[future] katana: Katana, _setup: ()) {
+    // Given
+    let eth_client = Arc::new(katana.eth_client());
+    let kakarot_block_gas_limit = eth_client.eth_provider().block_gas_limit().await.unwrap();
+    assert_eq!(eth_client.block_gas_limit(), KKRT_BLOCK_GAS_LIMIT);
+    assert_ne!(kakarot_block_gas_limit, KKRT_BLOCK_GAS_LIMIT);
+
+    // When
+    let mut maintainer = PoolMaintainer::new(eth_client.clone(), PruneSchedule::fixed(Duration::from_secs(300)));
+    maintainer.maintain().await;
+
+    // Then
+    assert_eq!(eth_client.block_gas_limit(), kakarot_block_gas_limit);
+}
+
+#[rstest]
+#[awt]
+#[tokio::test(flavor = "multi_thread")]
+async fn test_probe_address(#[future] katana: Katana, _setup: ()) {
+    // Given
+    let eth_client = Arc::new(katana.eth_client());
+
+    // When
+    let probe = Probe::new(katana.eoa().private_key(), eth_client).expect("Failed to create probe");
+
+    // Then
+    assert_eq!(probe.address(), katana.eoa().evm_address().expect("Failed to get eoa address"));
+}
@@ -1,5 +1,8 @@
 pub mod alchemy_api;
+pub mod chaos;
+pub mod conformance;
 pub mod debug_api;
+pub mod environment;
 pub mod eth_provider;
 pub mod kakarot_api;
 pub mod mempool;
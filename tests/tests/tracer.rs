@@ -228,6 +228,47 @@ async fn test_debug_trace_block_by_number(#[future] plain_opcodes: (Katana, Kaka
     assert!(block_traces.len() == TRACING_TRANSACTIONS_COUNT);
 }
 
+#[rstest]
+#[awt]
+#[tokio::test(flavor = "multi_thread")]
+async fn test_debug_block_traces(#[future] plain_opcodes: (Katana, KakarotEvmContract), _setup: ()) {
+    // Given
+    let katana = plain_opcodes.0;
+    let plain_opcodes = plain_opcodes.1;
+    tracing(&katana, &plain_opcodes, "createCounterAndInvoke", Box::new(|_| vec![])).await;
+
+    let opts: GethDebugTracingOptions = serde_json::from_value(json!({
+        "tracer": "callTracer",
+        "tracerConfig": {
+            "onlyTopCall": false
+        }
+    }))
+    .expect("Failed to deserialize tracing options");
+    let eth_provider = &katana.eth_provider();
+    let opts = &opts;
+    let tracer = move || async move {
+        TracerBuilder::new(Arc::new(eth_provider))
+            .await
+            .expect("Failed to create tracer_builder")
+            .with_block_id(TRACING_BLOCK_NUMBER.into())
+            .await
+            .expect("Failed to set block number")
+            .with_tracing_options(kakarot_rpc::tracing::builder::TracingOptions::Geth(opts.clone()))
+            .build()
+            .expect("Failed to build tracer")
+    };
+
+    // When
+    let streamed_traces =
+        tracer().await.debug_block_traces().collect::<Result<Vec<_>, _>>().expect("Failed to trace block transactions");
+    let block_traces = tracer().await.debug_block().expect("Failed to trace block");
+
+    // Then
+    // The transactions are traced one at a time, with the same result as tracing the whole block
+    assert_eq!(streamed_traces.len(), TRACING_TRANSACTIONS_COUNT);
+    assert_eq!(streamed_traces.into_iter().flatten().collect::<Vec<_>>(), block_traces);
+}
+
 #[rstest]
 #[awt]
 #[tokio::test(flavor = "multi_thread")]
@@ -303,3 +344,56 @@ async fn test_debug_trace_transaction(#[future] plain_opcodes: (Katana, KakarotE
         TraceResult::Error { .. } => panic!("Expected a success trace result"),
     };
 }
+
+#[rstest]
+#[awt]
+#[tokio::test(flavor = "multi_thread")]
+async fn test_execute_transaction(#[future] plain_opcodes: (Katana, KakarotEvmContract), _setup: ()) {
+    let katana = plain_opcodes.0;
+    let plain_opcodes = plain_opcodes.1;
+    tracing(&katana, &plain_opcodes, "createCounterAndInvoke", Box::new(|_| vec![])).await;
+
+    // Get the block in order to execute a transaction.
+    let block = katana
+        .eth_provider()
+        .block_by_number(TRACING_BLOCK_NUMBER.into(), false)
+        .await
+        .expect("Failed to get block")
+        .unwrap();
+
+    let index = TRACING_TRANSACTIONS_COUNT - 2;
+    let tx_hash = block.transactions.as_hashes().unwrap().get(index).unwrap();
+
+    // Get the Ethereum provider from the Katana instance.
+    let eth_provider = katana.eth_provider();
+    let tracer_builder = TracerBuilder::new(Arc::new(&eth_provider)).await.expect("Failed to create tracer_builder");
+
+    // Execute the transaction.
+    let tracer = tracer_builder
+        .clone()
+        .with_transaction_hash(*tx_hash)
+        .await
+        .expect("Failed to set transaction hash")
+        .build()
+        .expect("Failed to build tracer");
+    let result = tracer.execute_transaction(*tx_hash).expect("Failed to execute transaction");
+
+    // Get the call trace of the transaction.
+    let opts: GethDebugTracingOptions =
+        serde_json::from_value(json!({"tracer": "callTracer"})).expect("Failed to deserialize tracing options");
+    let tracer = tracer_builder
+        .with_transaction_hash(*tx_hash)
+        .await
+        .expect("Failed to set transaction hash")
+        .with_tracing_options(kakarot_rpc::tracing::builder::TracingOptions::Geth(opts))
+        .build()
+        .expect("Failed to build tracer");
+    let GethTrace::CallTracer(frame) = tracer.debug_transaction(*tx_hash).expect("Failed to trace transaction") else {
+        panic!("Expected a call trace")
+    };
+
+    // The execution should match the trace.
+    assert!(result.is_success());
+    assert_eq!(U256::from(result.gas_used()), frame.gas_used);
+    assert_eq!(result.output().cloned().unwrap_or_default(), frame.output.unwrap_or_default());
+}
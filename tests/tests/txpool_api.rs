@@ -2,20 +2,32 @@
 #![cfg(feature = "testing")]
 use crate::tests::mempool::create_sample_transactions;
 use alloy_consensus::Transaction;
+use alloy_primitives::{Address, U256, U64};
 use alloy_rpc_types_txpool::{TxpoolContent, TxpoolContentFrom, TxpoolInspect, TxpoolInspectSummary, TxpoolStatus};
 use jsonrpsee::server::ServerHandle;
 use kakarot_rpc::{
-    providers::eth_provider::database::types::transaction::ExtendedTransaction,
+    eth_rpc::{config::RPCConfig, middleware::admin_access::AdminApiKeys},
+    pool::{
+        build_stats::{BlockBuildStats, BLOCK_BUILD_STATS},
+        content::TxpoolContentPage,
+        nonce_gaps::{NonceGap, NonceGaps},
+        ordering::PoolDrainOrder,
+    },
+    providers::eth_provider::{database::types::transaction::ExtendedTransaction, error::EthRpcErrorCode},
     test_utils::{
+        eoa::Eoa,
         fixtures::{katana_empty, setup},
         katana::Katana,
-        rpc::{start_kakarot_rpc_server, RawRpcParamsBuilder},
+        rpc::{
+            start_kakarot_rpc_server, start_kakarot_rpc_server_with_config, RawRpcParamsBuilder, TEST_ADMIN_API_KEY,
+        },
     },
 };
 use reth_transaction_pool::{TransactionOrigin, TransactionPool};
 use rstest::*;
 use serde::{de::DeserializeOwned, Serialize};
 use serde_json::Value;
+use starknet::core::types::Felt;
 use std::net::SocketAddr;
 
 async fn initial_setup(katana: Katana) -> (SocketAddr, ServerHandle, Katana) {
@@ -41,6 +53,7 @@ async fn request<D: DeserializeOwned, S: Serialize>(method: &str, port: u16, par
     let res = reqwest_client
         .post(format!("http://localhost:{port}"))
         .header("Content-Type", "application/json")
+        .header("X-Api-Key", TEST_ADMIN_API_KEY)
         .body(body)
         .send()
         .await
@@ -242,3 +255,201 @@ async fn test_txpool_inspect(#[future] katana_empty: Katana, _setup: ()) {
     // Drop the server handle to shut down the server after the test
     drop(server_handle);
 }
+
+#[rstest]
+#[awt]
+#[tokio::test(flavor = "multi_thread")]
+async fn test_admin_pool_inspect_order(#[future] katana_empty: Katana, _setup: ()) {
+    let (server_addr, server_handle, katana_empty) = initial_setup(katana_empty).await;
+
+    // Create two sample transactions of the same sender
+    let transactions =
+        create_sample_transactions(&katana_empty, 2).await.expect("Failed to create sample transactions");
+
+    // Insert the transactions into the mempool
+    for (transaction, _) in transactions.clone() {
+        katana_empty
+            .eth_client
+            .mempool()
+            .add_transaction(TransactionOrigin::Local, transaction)
+            .await
+            .expect("Failed to insert transaction into the mempool");
+    }
+
+    // Fetch the drain order of the pool
+    let order: PoolDrainOrder = request("admin_poolInspectOrder", server_addr.port(), Vec::<String>::new()).await;
+
+    // Check that the transactions are drained in nonce order, one per iteration of the relay loop
+    let hashes: Vec<_> = order.transactions.iter().map(|transaction| transaction.hash).collect();
+    let expected_hashes: Vec<_> =
+        transactions.iter().map(|(_, transaction_signed)| transaction_signed.hash()).collect();
+    assert_eq!(hashes, expected_hashes);
+    assert_eq!(order.transactions.iter().map(|transaction| transaction.round).collect::<Vec<_>>(), vec![0, 1]);
+    assert!(order.transactions.iter().all(|transaction| !transaction.priority_lane));
+
+    // Drop the server handle to shut down the server after the test
+    drop(server_handle);
+}
+
+#[rstest]
+#[awt]
+#[tokio::test(flavor = "multi_thread")]
+async fn test_admin_add_relayer(#[future] katana_empty: Katana, _setup: ()) {
+    let (server_addr, server_handle, katana_empty) = initial_setup(katana_empty).await;
+
+    // The Starknet account of the EOA is deployed and funded
+    let funded_account = katana_empty.eoa().starknet_address().expect("Failed to get the starknet address");
+    let added: Option<bool> = request("admin_addRelayer", server_addr.port(), vec![funded_account]).await;
+    assert_eq!(added, Some(true));
+
+    // An account which isn't deployed is rejected
+    let added: Option<bool> = request("admin_addRelayer", server_addr.port(), vec![Felt::from(0xdead_u64)]).await;
+    assert_eq!(added, None);
+
+    // Drop the server handle to shut down the server after the test
+    drop(server_handle);
+}
+
+#[rstest]
+#[awt]
+#[tokio::test(flavor = "multi_thread")]
+async fn test_admin_api_without_keys(#[future] katana_empty: Katana, _setup: ()) {
+    // Given
+    let mut config = RPCConfig::new_test_config_from_port(rand::random());
+    config.admin_api_keys = AdminApiKeys::default();
+    let (server_addr, server_handle) =
+        start_kakarot_rpc_server_with_config(&katana_empty, config).await.expect("Error setting up Kakarot RPC server");
+    let reqwest_client = reqwest::Client::new();
+
+    for (method, params) in
+        [("admin_relayerQueue", vec![]), ("kakarot_deployAccount", vec![Address::left_padding_from(&[0xde, 0x01])])]
+    {
+        // When
+        let body = params.into_iter().fold(RawRpcParamsBuilder::new(method), RawRpcParamsBuilder::add_param).build();
+        let response = reqwest_client
+            .post(format!("http://localhost:{}", server_addr.port()))
+            .header("Content-Type", "application/json")
+            .header("X-Api-Key", TEST_ADMIN_API_KEY)
+            .body(body)
+            .send()
+            .await
+            .expect("Failed to call the admin RPC");
+        let response = response.text().await.expect("Failed to get response body");
+        let raw: Value = serde_json::from_str(&response).expect("Failed to deserialize response body");
+
+        // Then
+        assert_eq!(raw["error"]["code"], EthRpcErrorCode::InvalidRequest as i32, "{method} wasn't rejected");
+        assert!(raw["result"].is_null());
+    }
+
+    // Drop the server handle to shut down the server after the test
+    drop(server_handle);
+}
+
+#[rstest]
+#[awt]
+#[tokio::test(flavor = "multi_thread")]
+async fn test_kakarot_txpool_content_paginated(#[future] katana_empty: Katana, _setup: ()) {
+    let (server_addr, server_handle, katana_empty) = initial_setup(katana_empty).await;
+
+    // Create three sample transactions of the same sender
+    let transactions =
+        create_sample_transactions(&katana_empty, 3).await.expect("Failed to create sample transactions");
+    let sender = katana_empty.eoa().evm_address().expect("Failed to get eoa address");
+
+    // Insert the transactions into the mempool
+    for (transaction, _) in transactions {
+        katana_empty
+            .eth_client
+            .mempool()
+            .add_transaction(TransactionOrigin::Local, transaction)
+            .await
+            .expect("Failed to insert transaction into the mempool");
+    }
+
+    // Fetch the content of the mempool by pages of two transactions
+    let first: TxpoolContentPage<ExtendedTransaction> =
+        request("kakarot_txpoolContentPaginated", server_addr.port(), vec![Value::Null, serde_json::json!("0x2")])
+            .await;
+    let cursor = serde_json::to_value(first.next_cursor).unwrap();
+    let last: TxpoolContentPage<ExtendedTransaction> =
+        request("kakarot_txpoolContentPaginated", server_addr.port(), vec![cursor, serde_json::json!("0x2")]).await;
+
+    // Check that the pages cover the transactions in nonce order
+    assert_eq!(first.pending[&sender].keys().collect::<Vec<_>>(), ["0", "1"]);
+    assert_eq!(first.next_cursor.map(|cursor| cursor.nonce), Some(U64::from(1)));
+    assert_eq!(last.pending[&sender].keys().collect::<Vec<_>>(), ["2"]);
+    assert_eq!(last.next_cursor, None);
+
+    // Drop the server handle to shut down the server after the test
+    drop(server_handle);
+}
+
+#[rstest]
+#[awt]
+#[tokio::test(flavor = "multi_thread")]
+async fn test_kakarot_get_nonce_gaps(#[future] katana_empty: Katana, _setup: ()) {
+    let (server_addr, server_handle, katana_empty) = initial_setup(katana_empty).await;
+
+    // Create four sample transactions of the same sender
+    let transactions =
+        create_sample_transactions(&katana_empty, 4).await.expect("Failed to create sample transactions");
+    let sender = katana_empty.eoa().evm_address().expect("Failed to get eoa address");
+
+    // Insert the transactions with the nonces 0 and 3 into the mempool
+    for index in [0, 3] {
+        katana_empty
+            .eth_client
+            .mempool()
+            .add_transaction(TransactionOrigin::Local, transactions[index].0.clone())
+            .await
+            .expect("Failed to insert transaction into the mempool");
+    }
+
+    // Fetch the nonce gaps of the sender
+    let gaps: NonceGaps = request("kakarot_getNonceGaps", server_addr.port(), vec![sender]).await;
+
+    // Check that the nonces 1 and 2 block the last transaction
+    assert_eq!(gaps.address, sender);
+    assert_eq!(gaps.onchain_nonce, 0);
+    assert_eq!(gaps.highest_pool_nonce, Some(3));
+    assert_eq!(gaps.gaps, vec![NonceGap { from: 1, to: 2 }]);
+    assert_eq!(gaps.blocking_nonce, Some(1));
+
+    // Drop the server handle to shut down the server after the test
+    drop(server_handle);
+}
+
+#[rstest]
+#[awt]
+#[tokio::test(flavor = "multi_thread")]
+async fn test_kakarot_get_block_build_stats(#[future] katana_empty: Katana, _setup: ()) {
+    let (server_addr, server_handle, _katana_empty) = initial_setup(katana_empty).await;
+
+    // Record the build of a block by the relay loop
+    let block_number = 1_000_000;
+    let relayer = Felt::ONE;
+    BLOCK_BUILD_STATS.record_pulled(block_number, 21_000, U256::from(21_000));
+    BLOCK_BUILD_STATS.record_skipped(block_number);
+    BLOCK_BUILD_STATS.record_submission(block_number, relayer, true);
+
+    // Fetch the build stats of the block
+    let stats: Option<BlockBuildStats> =
+        request("kakarot_getBlockBuildStats", server_addr.port(), vec![U64::from(block_number)]).await;
+
+    // Check the stats of the block
+    let stats = stats.expect("Failed to get the build stats of the block");
+    assert_eq!(stats.block_number, block_number);
+    assert_eq!(stats.pulled_transactions, 1);
+    assert_eq!(stats.skipped_gas_transactions, 1);
+    assert_eq!(stats.relayers.get(&relayer), Some(&1));
+    assert_eq!(stats.total_fees, U256::from(21_000));
+
+    // Check that the blocks which weren't built by the node have no stats
+    let stats: Option<BlockBuildStats> =
+        request("kakarot_getBlockBuildStats", server_addr.port(), vec![U64::from(block_number + 1)]).await;
+    assert!(stats.is_none());
+
+    // Drop the server handle to shut down the server after the test
+    drop(server_handle);
+}